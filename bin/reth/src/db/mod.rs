@@ -1,19 +1,41 @@
 //! Database debugging tool
 use crate::dirs::{DbPath, PlatformPath};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use comfy_table::{Cell, Row, Table as ComfyTable};
 use eyre::{Result, WrapErr};
 use reth_db::{
     cursor::{DbCursorRO, Walker},
     database::Database,
-    table::Table,
-    tables,
-    transaction::DbTx,
+    mdbx::{Env, EnvKind, WriteMap, DATA_FILE_NAME},
+    table::{Compress, Decode, Decompress, Encode, Table},
+    table_dispatch, tables,
+    tables::{
+        codecs::compression::{
+            dictionary_table, store_dictionary, stored_dictionary, train_dictionary,
+            DEFAULT_DICTIONARY_SAMPLES, DEFAULT_DICTIONARY_SIZE,
+        },
+        TableType, Tables,
+    },
+    transaction::{DbTx, DbTxMut},
+    version::{check_database_version, database_version, Migrator},
 };
 use reth_interfaces::test_utils::generators::random_block_range;
+use reth_primitives::{
+    hex,
+    tiny_keccak::{Hasher, Keccak},
+    Bytes, H256,
+};
 use reth_provider::insert_canonical_block;
-use std::collections::BTreeMap;
-use tracing::{error, info};
+use reth_rlp::{Decodable, DecodeError};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+use tracing::{debug, error, info};
 
 /// DB List TUI
 mod tui;
@@ -52,13 +74,32 @@ pub enum Subcommands {
     },
     /// Deletes all database entries
     Drop,
+    /// Upgrades the database schema to the version of this client
+    Migrate,
+    /// Copies a consistent snapshot of the database to a folder, the node can keep running
+    Backup(BackupArgs),
+    /// Restores the database from a backup, the node must not be running
+    Restore(RestoreArgs),
+    /// Prints the values of a key
+    Get(GetArgs),
+    /// Computes a checksum of the contents of a table
+    Checksum(ChecksumArgs),
+    /// Compares the contents of two databases
+    Diff(DiffArgs),
+    /// Writes the entries of a table to a file
+    Export(ExportArgs),
+    /// Inserts the entries of an exported table, existing keys are overwritten
+    Import(ImportArgs),
+    /// Trains a compression dictionary for a table and recompresses its values, the node must not
+    /// be running
+    Compress(CompressArgs),
 }
 
 #[derive(Parser, Debug)]
 /// The arguments for the `reth db list` command
 pub struct ListArgs {
     /// The table name
-    table: String, // TODO: Convert to enum
+    table: Tables,
     /// Where to start iterating
     #[arg(long, short, default_value = "0")]
     start: usize,
@@ -67,9 +108,154 @@ pub struct ListArgs {
     len: usize,
 }
 
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db backup` command
+pub struct BackupArgs {
+    /// The folder to copy the database to, it must not contain a database yet
+    to: PathBuf,
+    /// Leave free pages out of the copy, this also avoids blocking writes during the copy
+    #[arg(long)]
+    compact: bool,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db restore` command
+pub struct RestoreArgs {
+    /// The folder of the backup
+    from: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db get` command
+pub struct GetArgs {
+    /// The table name
+    table: Tables,
+    /// The key, either `0x`-prefixed hex of the encoded key, a number for keys that are encoded
+    /// as big-endian `u64` (e.g. block numbers), or text (e.g. stage names)
+    key: String,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db checksum` command
+pub struct ChecksumArgs {
+    /// The table name
+    table: Tables,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db diff` command
+pub struct DiffArgs {
+    /// The folder of the first database
+    a: PathBuf,
+    /// The folder of the second database
+    b: PathBuf,
+    /// The table to compare, all tables are compared if not set
+    table: Option<Tables>,
+    /// How many differences to print per table
+    #[arg(long, default_value = "10")]
+    limit: usize,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db export` command
+pub struct ExportArgs {
+    /// The table name
+    table: Tables,
+    /// The first key to export, in the same format as for `reth db get`
+    #[arg(long)]
+    start: Option<String>,
+    /// The key to stop at, it is not exported
+    #[arg(long)]
+    end: Option<String>,
+    /// The format of the entries
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+    format: ExportFormat,
+    /// The file to write to, defaults to stdout
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db import` command
+pub struct ImportArgs {
+    /// The table name
+    table: Tables,
+    /// The file written by `reth db export`
+    input: PathBuf,
+    /// The format of the entries
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+    format: ExportFormat,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth db compress` command
+pub struct CompressArgs {
+    /// The table name, tables with the same value type share a dictionary
+    table: Tables,
+    /// How many of the latest values to train the dictionary on
+    #[arg(long, default_value_t = DEFAULT_DICTIONARY_SAMPLES)]
+    samples: usize,
+    /// The maximum size of the dictionary in bytes
+    #[arg(long, default_value_t = DEFAULT_DICTIONARY_SIZE)]
+    dictionary_size: usize,
+    /// How many values to recompress per transaction
+    #[arg(long, default_value = "10000")]
+    batch_size: usize,
+}
+
+/// The formats of exported table entries.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+pub enum ExportFormat {
+    /// One JSON object per line, with the encoded key and value and the decoded value
+    Json,
+    /// RLP lists of the encoded key and value
+    Rlp,
+}
+
+/// An exported table entry in the JSON format.
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonEntry {
+    /// The encoded key.
+    key: Bytes,
+    /// The encoded value.
+    value: Bytes,
+    /// The decoded value, only for reading, it is ignored on import.
+    #[serde(default, skip_deserializing)]
+    decoded: serde_json::Value,
+}
+
+/// A table entry together with its encoding.
+///
+/// The value is not compressed with the dictionaries of the database, so it can be imported into
+/// any database.
+struct RawEntry<T: Table> {
+    key: T::Key,
+    raw_key: Vec<u8>,
+    raw_value: Vec<u8>,
+    /// The decoded value as JSON, which does not depend on how the value is compressed.
+    content: Vec<u8>,
+}
+
+impl<T: Table> RawEntry<T> {
+    fn new((key, value): (T::Key, T::Value)) -> Result<Self> {
+        let raw_key = key.clone().encode().as_ref().to_vec();
+        let content = serde_json::to_vec(&value)?;
+        Ok(Self { key, raw_key, raw_value: value.compress().as_ref().to_vec(), content })
+    }
+}
+
 impl Command {
     /// Execute `db` command
     pub async fn execute(&self) -> eyre::Result<()> {
+        // the database is replaced by the backup, so it must not be opened
+        if let Subcommands::Restore(args) = &self.command {
+            return restore(&args.from, self.db.as_ref())
+        }
+        // the compared databases are opened read-only
+        if let Subcommands::Diff(args) = &self.command {
+            return diff(args)
+        }
+
         std::fs::create_dir_all(&self.db)?;
 
         // TODO: Auto-impl for Database trait
@@ -132,55 +318,83 @@ impl Command {
                 tool.seed(*len)?;
             }
             Subcommands::List(args) => {
-                macro_rules! table_tui {
-                    ($arg:expr, $start:expr, $len:expr => [$($table:ident),*]) => {
-                        match $arg {
-                            $(stringify!($table) => {
-                                tool.db.view(|tx| {
-                                    let table_db = tx.inner.open_db(Some(stringify!($table))).wrap_err("Could not open db.")?;
-                                    let stats = tx.inner.db_stat(&table_db).wrap_err(format!("Could not find table: {}", stringify!($table)))?;
-                                    let total_entries = stats.entries();
-                                    if $start > total_entries - 1 {
-                                        error!(
-                                            target: "reth::cli",
-                                            "Start index {start} is greater than the final entry index ({final_entry_idx}) in the table {table}",
-                                            start = $start,
-                                            final_entry_idx = total_entries - 1,
-                                            table = stringify!($table)
-                                        );
-                                        return Ok(());
-                                    }
-                                    let map = tool.list::<tables::$table>($start, $len)?;
-                                    tui::DbListTUI::<tables::$table>::show_tui(map, $start, total_entries)
-                                })??
-                            },)*
-                            _ => {
-                                error!(target: "reth::cli", "Unknown table.");
-                                return Ok(());
-                            }
+                table_dispatch!(args.table, T => {
+                    tool.db.view(|tx| {
+                        let table_db =
+                            tx.inner.open_db(Some(T::NAME)).wrap_err("Could not open db.")?;
+                        let stats = tx
+                            .inner
+                            .db_stat(&table_db)
+                            .wrap_err(format!("Could not find table: {}", T::NAME))?;
+                        let total_entries = stats.entries();
+                        if args.start > total_entries - 1 {
+                            error!(
+                                target: "reth::cli",
+                                "Start index {start} is greater than the final entry index ({final_entry_idx}) in the table {table}",
+                                start = args.start,
+                                final_entry_idx = total_entries - 1,
+                                table = T::NAME
+                            );
+                            return Ok(())
                         }
-                    }
-                }
-
-                table_tui!(args.table.as_str(), args.start, args.len => [
-                    CanonicalHeaders,
-                    HeaderTD,
-                    HeaderNumbers,
-                    Headers,
-                    BlockBodies,
-                    BlockOmmers,
-                    TxHashNumber,
-                    PlainStorageState,
-                    PlainAccountState,
-                    BlockTransitionIndex,
-                    TxTransitionIndex,
-                    SyncStage,
-                    Transactions
-                ]);
+                        let map = tool.list::<T>(args.start, args.len)?;
+                        tui::DbListTUI::<T>::show_tui(map, args.start, total_entries)
+                    })??
+                });
             }
             Subcommands::Drop => {
                 tool.drop(&self.db)?;
             }
+            Subcommands::Migrate => {
+                // migrations may move data to new tables, they are not created for newer versions
+                Migrator::new(&db).pending()?;
+                db.create_tables()?;
+                tool.migrate()?;
+            }
+            Subcommands::Backup(args) => {
+                std::fs::create_dir_all(&args.to)?;
+                info!(target: "reth::cli", path = %args.to.display(), compact = args.compact, "Copying database");
+                db.backup(&args.to, args.compact)?;
+                verify_backup(&args.to)?;
+                info!(target: "reth::cli", path = %args.to.display(), "Backup complete");
+            }
+            Subcommands::Get(args) => {
+                table_dispatch!(args.table, T => tool.get::<T>(parse_key::<T>(&args.key)?))?;
+            }
+            Subcommands::Checksum(args) => {
+                let (checksum, entries) = table_dispatch!(args.table, T => tool.checksum::<T>())?;
+                println!("{}: {checksum:?} ({entries} entries)", args.table);
+            }
+            Subcommands::Export(args) => {
+                let mut out: Box<dyn Write> = match &args.output {
+                    Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                    None => Box::new(std::io::stdout().lock()),
+                };
+                let entries = table_dispatch!(args.table, T => {
+                    let start = args.start.as_deref().map(parse_key::<T>).transpose()?;
+                    let end = args.end.as_deref().map(parse_key::<T>).transpose()?;
+                    tool.export::<T>(start, end, args.format, &mut out)?
+                });
+                out.flush()?;
+                info!(target: "reth::cli", table = %args.table, entries, "Table exported");
+            }
+            Subcommands::Import(args) => {
+                let entries = read_entries(&args.input, args.format)?;
+                let entries = table_dispatch!(args.table, T => tool.import::<T>(entries))?;
+                info!(target: "reth::cli", table = %args.table, entries, "Table imported");
+            }
+            Subcommands::Compress(args) => {
+                let Some(dictionary) = dictionary_table(args.table) else {
+                    eyre::bail!("Values of table {} are not compressed", args.table)
+                };
+                tool.train_dictionary(dictionary, args.samples, args.dictionary_size)?;
+                let entries =
+                    table_dispatch!(args.table, T => tool.recompress::<T>(args.batch_size))?;
+                info!(target: "reth::cli", table = %args.table, entries, "Table recompressed");
+            }
+            Subcommands::Restore(_) | Subcommands::Diff(_) => {
+                unreachable!("handled before the database is opened")
+            }
         }
 
         Ok(())
@@ -232,9 +446,525 @@ impl<'a, DB: Database> DbTool<'a, DB> {
             .map_err(|e| eyre::eyre!(e))
     }
 
+    /// Prints all values of the key as JSON.
+    fn get<T: Table>(&mut self, key: T::Key) -> Result<()> {
+        let values = self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<T>()?;
+            let mut values = Vec::new();
+            // dupsort tables can hold multiple values per key
+            let mut entry = cursor.seek_exact(key.clone())?;
+            while let Some((entry_key, value)) = entry {
+                if entry_key != key {
+                    break
+                }
+                values.push(value);
+                entry = cursor.next()?;
+            }
+            Ok::<_, reth_db::Error>(values)
+        })??;
+
+        if values.is_empty() {
+            error!(target: "reth::cli", "Key {key:?} not found in table {}", T::NAME);
+        }
+        for value in values {
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
+        Ok(())
+    }
+
+    /// Hashes the encoded keys and the decoded values of the table in the order they are stored
+    /// in, so the checksum does not depend on how the values are compressed. Returns the hash and
+    /// the number of entries.
+    fn checksum<T: Table>(&mut self) -> Result<(H256, usize)> {
+        self.db.view(|tx| {
+            let mut cursor = tx.cursor_read::<T>()?;
+            let first = cursor.first().transpose();
+
+            let mut hasher = Keccak::v256();
+            let mut entries = 0;
+            for entry in Walker::new(&mut cursor, first) {
+                let entry = RawEntry::<T>::new(entry?)?;
+                for bytes in [entry.raw_key, entry.content] {
+                    hasher.update(&(bytes.len() as u64).to_be_bytes());
+                    hasher.update(&bytes);
+                }
+                entries += 1;
+            }
+
+            let mut checksum = H256::zero();
+            hasher.finalize(&mut checksum.0);
+            Ok::<_, eyre::Report>((checksum, entries))
+        })?
+    }
+
+    /// Writes the entries of the table from `start` up to, but excluding, `end`. Returns the
+    /// number of written entries.
+    fn export<T: Table>(
+        &mut self,
+        start: Option<T::Key>,
+        end: Option<T::Key>,
+        format: ExportFormat,
+        out: &mut dyn Write,
+    ) -> Result<usize> {
+        let end = end.map(|key| key.encode().as_ref().to_vec());
+        let tx = self.db.tx()?;
+        let mut cursor = tx.cursor_read::<T>()?;
+        let first = match start {
+            Some(key) => cursor.seek(key),
+            None => cursor.first(),
+        }
+        .transpose();
+
+        let mut entries = 0;
+        for entry in Walker::new(&mut cursor, first) {
+            let (key, value) = entry?;
+            let decoded = match format {
+                ExportFormat::Json => serde_json::to_value(&value)?,
+                ExportFormat::Rlp => serde_json::Value::Null,
+            };
+            let entry = RawEntry::<T>::new((key, value))?;
+            if end.as_ref().map_or(false, |end| entry.raw_key >= *end) {
+                break
+            }
+
+            let (key, value) = (Bytes::from(entry.raw_key), Bytes::from(entry.raw_value));
+            match format {
+                ExportFormat::Json => {
+                    serde_json::to_writer(&mut *out, &JsonEntry { key, value, decoded })?;
+                    writeln!(out)?;
+                }
+                ExportFormat::Rlp => {
+                    let mut buf = Vec::new();
+                    reth_rlp::encode_list::<Bytes, _>(&[key, value], &mut buf);
+                    out.write_all(&buf)?;
+                }
+            }
+            entries += 1;
+        }
+        Ok(entries)
+    }
+
+    /// Decodes the encoded entries and inserts them into the table in a single transaction.
+    /// Returns the number of inserted entries.
+    fn import<T: Table>(&mut self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> Result<usize> {
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| Ok((T::Key::decode(key)?, T::Value::decompress(value)?)))
+            .collect::<Result<Vec<_>>>()
+            .wrap_err_with(|| format!("Invalid entry for table {}", T::NAME))?;
+
+        let len = entries.len();
+        let tx = self.db.tx_mut()?;
+        for (key, value) in entries {
+            tx.put::<T>(key, value)?;
+        }
+        tx.commit()?;
+        Ok(len)
+    }
+
+    /// Trains and stores the compression dictionary of the table, unless it already has one.
+    fn train_dictionary(&mut self, table: Tables, samples: usize, size: usize) -> Result<()> {
+        if stored_dictionary(&self.db.tx()?, table)?.is_some() {
+            info!(target: "reth::cli", %table, "Using the stored dictionary");
+            return Ok(())
+        }
+
+        info!(target: "reth::cli", %table, samples, "Training dictionary");
+        let tx = self.db.tx()?;
+        let dictionary = table_dispatch!(table, T => train_dictionary::<T, _>(&tx, self.db.dictionaries(), samples, size))?;
+        drop(tx);
+        store_dictionary(self.db, table, &dictionary)?;
+        info!(target: "reth::cli", %table, size = dictionary.len(), "Dictionary stored");
+        Ok(())
+    }
+
+    /// Rewrites all values of the table in batches, which compresses them with the dictionary of
+    /// the table. Returns the number of values.
+    fn recompress<T: Table>(&mut self, batch_size: usize) -> Result<usize> {
+        let batch_size = batch_size.max(1);
+        let mut start: Option<T::Key> = None;
+        let mut total = 0;
+        loop {
+            let tx = self.db.tx_mut()?;
+            let mut entries = {
+                let mut cursor = tx.cursor_read::<T>()?;
+                let first = match start.take() {
+                    Some(key) => cursor.seek(key),
+                    None => cursor.first(),
+                }
+                .transpose();
+                Walker::new(&mut cursor, first)
+                    .take(batch_size + 1)
+                    .collect::<std::result::Result<Vec<_>, _>>()?
+            };
+            if entries.len() > batch_size {
+                start = entries.pop().map(|(key, _)| key);
+            }
+
+            total += entries.len();
+            for (key, value) in entries {
+                tx.put::<T>(key, value)?;
+            }
+            tx.commit()?;
+            debug!(target: "reth::cli", table = T::NAME, total, "Recompressed batch");
+
+            if start.is_none() {
+                return Ok(total)
+            }
+        }
+    }
+
+    /// Runs all pending migrations of the database schema.
+    fn migrate(&mut self) -> Result<()> {
+        let migrator = Migrator::new(self.db);
+        let pending = migrator.pending()?;
+        if pending.is_empty() {
+            let version = database_version(&self.db.tx()?)?;
+            info!(target: "reth::cli", ?version, "Database is up to date");
+            return Ok(())
+        }
+        for migration in pending {
+            info!(
+                target: "reth::cli",
+                from = migration.from_version(),
+                description = migration.description(),
+                "Pending migration"
+            );
+        }
+
+        migrator.run(|migration, checkpoint| match checkpoint {
+            Some(checkpoint) => debug!(
+                target: "reth::cli",
+                from = migration.from_version(),
+                ?checkpoint,
+                "Migrated batch"
+            ),
+            None => info!(
+                target: "reth::cli",
+                version = migration.from_version() + 1,
+                "Migration complete"
+            ),
+        })?;
+        Ok(())
+    }
+
     fn drop(&mut self, path: &PlatformPath<DbPath>) -> Result<()> {
         info!(target: "reth::cli", "Dropping db at {}", path);
         std::fs::remove_dir_all(path).wrap_err("Dropping the database failed")?;
         Ok(())
     }
 }
+
+/// Opens the backup in `dir` and checks that it contains the stage checkpoints of a synced
+/// database.
+fn verify_backup(dir: &Path) -> Result<()> {
+    let db = Env::<WriteMap>::open(dir, EnvKind::RO)
+        .wrap_err_with(|| format!("Could not open backup at {}", dir.display()))?;
+    let checkpoints = db.view(|tx| {
+        tx.cursor_read::<tables::SyncStage>()?
+            .walk(Vec::new())?
+            .collect::<std::result::Result<Vec<_>, _>>()
+    })??;
+    if checkpoints.is_empty() {
+        eyre::bail!("Backup at {} has no stage checkpoints", dir.display())
+    }
+    for (stage, progress) in checkpoints {
+        let stage = String::from_utf8_lossy(&stage);
+        info!(target: "reth::cli", %stage, progress, "Backup checkpoint");
+    }
+    Ok(())
+}
+
+/// Validates the backup in `from` and copies it to the database folder `to`, which must not contain
+/// a database yet.
+///
+/// The backup is copied to a temporary file next to the database that is only renamed once it was
+/// synced to disk, so an interrupted restore never leaves a partial database behind.
+fn restore(from: &Path, to: &Path) -> Result<()> {
+    let target = to.join(DATA_FILE_NAME);
+    if target.exists() {
+        eyre::bail!("{} already contains a database, drop it first", to.display())
+    }
+    verify_backup(from)?;
+    let backup = Env::<WriteMap>::open(from, EnvKind::RO)?;
+    check_database_version(&backup)
+        .wrap_err_with(|| format!("Backup at {} can not be restored", from.display()))?;
+    drop(backup);
+
+    info!(target: "reth::cli", from = %from.display(), to = %to.display(), "Restoring database");
+    std::fs::create_dir_all(to)?;
+    let partial = to.join(format!("{DATA_FILE_NAME}.partial"));
+    let copied = std::fs::copy(from.join(DATA_FILE_NAME), &partial)
+        .and_then(|_| OpenOptions::new().write(true).open(&partial)?.sync_all());
+    if let Err(err) = copied {
+        let _ = std::fs::remove_file(&partial);
+        return Err(err).wrap_err("Restoring the database failed")
+    }
+    std::fs::rename(&partial, &target).wrap_err("Restoring the database failed")?;
+    #[cfg(unix)]
+    File::open(to)?.sync_all()?;
+    info!(target: "reth::cli", "Database restored");
+    Ok(())
+}
+
+/// Parses a key of the table given on the command line.
+///
+/// Hex strings prefixed by `0x` are read as the encoded key, numbers as big-endian `u64` and
+/// anything else as UTF-8 text.
+fn parse_key<T: Table>(key: &str) -> Result<T::Key> {
+    let encoded = if let Some(key) = key.strip_prefix("0x") {
+        hex::decode(key)?
+    } else if let Ok(number) = key.parse::<u64>() {
+        number.to_be_bytes().to_vec()
+    } else {
+        key.as_bytes().to_vec()
+    };
+    T::Key::decode(encoded).wrap_err_with(|| format!("Invalid key for table {}", T::NAME))
+}
+
+/// Reads the encoded entries of a file written by `reth db export`.
+fn read_entries(path: &Path, format: ExportFormat) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let mut reader = BufReader::new(
+        File::open(path).wrap_err_with(|| format!("Could not open {}", path.display()))?,
+    );
+    let mut entries = Vec::new();
+    match format {
+        ExportFormat::Json => {
+            for line in reader.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue
+                }
+                let entry: JsonEntry = serde_json::from_str(&line)?;
+                entries.push((entry.key.to_vec(), entry.value.to_vec()));
+            }
+        }
+        ExportFormat::Rlp => {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            let mut buf = data.as_slice();
+            while !buf.is_empty() {
+                let [key, value]: [Bytes; 2] = Vec::<Bytes>::decode(&mut buf)?
+                    .try_into()
+                    .map_err(|_| DecodeError::Custom("expected a key and a value"))?;
+                entries.push((key.to_vec(), value.to_vec()));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Compares the tables of the databases in `args` and prints the differing keys.
+fn diff(args: &DiffArgs) -> Result<()> {
+    let open = |path: &Path| {
+        Env::<WriteMap>::open(path, EnvKind::RO)
+            .wrap_err_with(|| format!("Could not open database at {}", path.display()))
+    };
+    let (a, b) = (open(&args.a)?, open(&args.b)?);
+
+    let tables = match args.table {
+        Some(table) => vec![table],
+        None => Tables::ALL.to_vec(),
+    };
+    let mut total = 0;
+    for table in tables {
+        let dupsort = matches!(table.table_type(), TableType::DupSort);
+        let differences =
+            table_dispatch!(table, T => diff_table::<T, _, _>(&a, &b, dupsort, args.limit))?;
+        if differences > 0 {
+            info!(target: "reth::cli", %table, differences, "Table differs");
+        }
+        total += differences;
+    }
+
+    if total > 0 {
+        eyre::bail!("Found {total} differences")
+    }
+    info!(target: "reth::cli", "Databases are equal");
+    Ok(())
+}
+
+/// Walks the table in both databases in the order the entries are stored in and prints up to
+/// `limit` keys that are only in one of them or have different values. Returns the number of
+/// differences.
+///
+/// The entries of dupsort tables are compared as `(key, value)` pairs, so a differing value is
+/// reported as missing from either database.
+fn diff_table<T: Table, A: Database, B: Database>(
+    a: &A,
+    b: &B,
+    dupsort: bool,
+    limit: usize,
+) -> Result<usize> {
+    let (tx_a, tx_b) = (a.tx()?, b.tx()?);
+    let (mut cursor_a, mut cursor_b) = (tx_a.cursor_read::<T>()?, tx_b.cursor_read::<T>()?);
+    let (first_a, first_b) = (cursor_a.first().transpose(), cursor_b.first().transpose());
+    let mut entries_a = Walker::new(&mut cursor_a, first_a);
+    let mut entries_b = Walker::new(&mut cursor_b, first_b);
+
+    let mut next_a = entries_a.next().transpose()?.map(RawEntry::<T>::new).transpose()?;
+    let mut next_b = entries_b.next().transpose()?.map(RawEntry::<T>::new).transpose()?;
+    let mut differences = 0;
+    loop {
+        let only_in_a =
+            |a: &RawEntry<T>| (true, false, Some((a.key.clone(), "only in the first database")));
+        let only_in_b =
+            |b: &RawEntry<T>| (false, true, Some((b.key.clone(), "only in the second database")));
+        let (advance_a, advance_b, difference) = match (&next_a, &next_b) {
+            (None, None) => break,
+            (Some(a), None) => only_in_a(a),
+            (None, Some(b)) => only_in_b(b),
+            (Some(a), Some(b)) => {
+                let order = if dupsort {
+                    (&a.raw_key, &a.raw_value).cmp(&(&b.raw_key, &b.raw_value))
+                } else {
+                    a.raw_key.cmp(&b.raw_key)
+                };
+                match order {
+                    Ordering::Less => only_in_a(a),
+                    Ordering::Greater => only_in_b(b),
+                    Ordering::Equal => (
+                        true,
+                        true,
+                        (a.content != b.content).then(|| (a.key.clone(), "has different values")),
+                    ),
+                }
+            }
+        };
+
+        if let Some((key, difference)) = difference {
+            if differences < limit {
+                println!("{}: {key:?} {difference}", T::NAME);
+            }
+            differences += 1;
+        }
+        if advance_a {
+            next_a = entries_a.next().transpose()?.map(RawEntry::new).transpose()?;
+        }
+        if advance_b {
+            next_b = entries_b.next().transpose()?.map(RawEntry::new).transpose()?;
+        }
+    }
+    Ok(differences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::mdbx::test_utils::create_test_db;
+    use reth_primitives::{Account, Address, StorageEntry, U256};
+
+    /// Inserts accounts with the nonces and storage slots with the values.
+    fn insert_state(
+        db: &Env<WriteMap>,
+        accounts: &[(Address, u64)],
+        slots: &[(Address, u64, u64)],
+    ) {
+        db.update(|tx| {
+            for (address, nonce) in accounts {
+                let account = Account { nonce: *nonce, ..Default::default() };
+                tx.put::<tables::PlainAccountState>(*address, account).unwrap();
+            }
+            for (address, key, value) in slots {
+                let entry =
+                    StorageEntry { key: H256::from_low_u64_be(*key), value: U256::from(*value) };
+                tx.put::<tables::PlainStorageState>(*address, entry).unwrap();
+            }
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn export_import_roundtrip() {
+        let (address, other) = (Address::random(), Address::random());
+        let source = create_test_db::<WriteMap>(EnvKind::RW);
+        insert_state(&source, &[(address, 1), (other, 2)], &[(address, 1, 1), (address, 2, 2)]);
+
+        let dir = tempfile::tempdir().unwrap();
+        for format in [ExportFormat::Json, ExportFormat::Rlp] {
+            let target = create_test_db::<WriteMap>(EnvKind::RW);
+            for table in [Tables::PlainAccountState, Tables::PlainStorageState] {
+                let path = dir.path().join(format!("{table}-{format:?}"));
+                let mut out = File::create(&path).unwrap();
+                let exported = table_dispatch!(table, T => {
+                    DbTool::new(&*source).unwrap().export::<T>(None, None, format, &mut out)
+                })
+                .unwrap();
+                drop(out);
+
+                let entries = read_entries(&path, format).unwrap();
+                let imported =
+                    table_dispatch!(table, T => DbTool::new(&*target).unwrap().import::<T>(entries))
+                        .unwrap();
+                assert_eq!((exported, imported), (2, 2));
+
+                let dupsort = matches!(table.table_type(), TableType::DupSort);
+                let differences =
+                    table_dispatch!(table, T => diff_table::<T, _, _>(&*source, &*target, dupsort, 0))
+                        .unwrap();
+                assert_eq!(differences, 0, "{table} differs after {format:?} roundtrip");
+            }
+        }
+    }
+
+    #[test]
+    fn restore_checks_backup_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        db.update(|tx| tx.put::<tables::SyncStage>(b"Headers".to_vec(), 1)).unwrap().unwrap();
+        let backup = |name: &str| {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(&path).unwrap();
+            db.backup(&path, false).unwrap();
+            path
+        };
+
+        let restored = dir.path().join("restored");
+        restore(&backup("current"), &restored).unwrap();
+        assert!(restored.join(DATA_FILE_NAME).exists());
+        assert!(!restored.join(format!("{DATA_FILE_NAME}.partial")).exists());
+
+        // backups of databases with another schema version are rejected
+        db.update(|tx| {
+            tx.put::<tables::Config>(b"DatabaseVersion".to_vec(), u64::MAX.to_be_bytes().to_vec())
+        })
+        .unwrap()
+        .unwrap();
+        let rejected = dir.path().join("rejected");
+        assert!(restore(&backup("newer"), &rejected).is_err());
+        assert!(!rejected.join(DATA_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn checksum_depends_on_contents() {
+        let (address, other) = (Address::random(), Address::random());
+        let a = create_test_db::<WriteMap>(EnvKind::RW);
+        insert_state(&a, &[(address, 1), (other, 2)], &[]);
+        let b = create_test_db::<WriteMap>(EnvKind::RW);
+        insert_state(&b, &[(other, 2), (address, 1)], &[]);
+
+        let checksum = |db: &Env<WriteMap>| {
+            DbTool::new(db).unwrap().checksum::<tables::PlainAccountState>().unwrap()
+        };
+        assert_eq!(checksum(&*a), checksum(&*b));
+        assert_eq!(checksum(&*a).1, 2);
+
+        insert_state(&b, &[(address, 3)], &[]);
+        assert_ne!(checksum(&*a).0, checksum(&*b).0);
+    }
+
+    #[test]
+    fn diff_plain_and_dupsort_tables() {
+        let (address, removed) = (Address::random(), Address::random());
+        let a = create_test_db::<WriteMap>(EnvKind::RW);
+        insert_state(&a, &[(address, 1), (removed, 1)], &[(address, 1, 1), (address, 2, 2)]);
+        let b = create_test_db::<WriteMap>(EnvKind::RW);
+        insert_state(&b, &[(address, 2)], &[(address, 1, 1), (address, 2, 3)]);
+
+        // a different nonce and an account that is only in the first database
+        assert_eq!(diff_table::<tables::PlainAccountState, _, _>(&*a, &*b, false, 10).unwrap(), 2);
+        // the changed slot is reported as missing from either database
+        assert_eq!(diff_table::<tables::PlainStorageState, _, _>(&*a, &*b, true, 10).unwrap(), 2);
+        assert_eq!(diff_table::<tables::PlainStorageState, _, _>(&*a, &*a, true, 10).unwrap(), 0);
+    }
+}
@@ -56,6 +56,22 @@ struct NetworkOpts {
     /// Do not persist peers. Cannot be used with --peers-file
     #[arg(long, verbatim_doc_comment, conflicts_with = "peers_file")]
     no_persist_peers: bool,
+
+    /// Map the discovery and RLPx ports on the gateway.
+    ///
+    /// Requires --nat to be `any`, `upnp` or `natpmp:<gateway>`.
+    #[arg(long, verbatim_doc_comment)]
+    port_mapping: bool,
+}
+
+impl NetworkOpts {
+    /// Applies the options that are not covered by the [reth_staged_sync::Config] to the network
+    /// config.
+    fn apply<C>(&self, config: &mut reth_network::NetworkConfig<C>) {
+        if let Some(discv4) = config.discovery_v4_config.as_mut() {
+            discv4.enable_port_mapping = self.port_mapping;
+        }
+    }
 }
 
 /// Parameters for configuring the rpc more granularity via CLI
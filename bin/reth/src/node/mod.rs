@@ -16,19 +16,36 @@ use reth_db::mdbx::{Env, WriteMap};
 use reth_downloaders::{bodies, headers};
 use reth_interfaces::consensus::{Consensus, ForkchoiceState};
 use reth_net_nat::NatResolver;
-use reth_network::{FetchClient, NetworkConfig, NetworkEvent, NetworkHandle};
+use reth_network::{FetchClient, NetworkConfig, NetworkEvent, NetworkHandle, SnapProtocolHandler};
 use reth_network_api::NetworkInfo;
-use reth_primitives::{BlockNumber, ChainSpec, H256};
-use reth_provider::ShareableDatabase;
-use reth_rpc_builder::{RethRpcModule, RpcServerConfig, TransportRpcModuleConfig};
+use reth_primitives::{BlockNumber, ChainSpec, H256, MINIMUM_PRUNING_DISTANCE};
+use reth_provider::{
+    static_files::{StaticFileProducer, StaticFileProvider},
+    BlockProvider, ShareableDatabase, StateCache,
+};
+use reth_rpc::{BundleStore, PendingBlockBuilder, PendingBlockCache};
+use reth_rpc_builder::{
+    RethRpcModule, RpcModuleBuilder, RpcServerConfig, TransportRpcModuleConfig,
+};
 use reth_staged_sync::{utils::init::init_genesis, Config};
 use reth_stages::{
     prelude::*,
     stages::{ExecutionStage, SenderRecoveryStage, TotalDifficultyStage},
+    Pruner,
+};
+use reth_transaction_pool::PoolConfig;
+use std::{
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
-use std::{io, net::SocketAddr, path::Path, sync::Arc, time::Duration};
 use tracing::{debug, info, warn};
 
+/// How often finalized blocks are moved to static files.
+const STATIC_FILES_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Start the node
 #[derive(Debug, Parser)]
 pub struct Command {
@@ -87,6 +104,20 @@ pub struct Command {
 
     #[clap(flatten)]
     rpc: RpcServerOpts,
+
+    /// File with banned transaction senders and recipients.
+    ///
+    /// Each line is either `sender <address>` or `recipient <address>`. The file can be reloaded
+    /// at runtime via `admin_reloadTransactionBanList`.
+    #[arg(long = "txpool.ban-list", value_name = "FILE", help_heading = "TxPool")]
+    txpool_ban_list: Option<PathBuf>,
+
+    /// The path to the folder to move finalized headers, transactions and receipts to.
+    ///
+    /// Blocks are moved once all stages processed them and they are more than 128 blocks below
+    /// the tip. The data is still served from the folder, so it must be used on every restart.
+    #[arg(long = "static-files", value_name = "PATH", verbatim_doc_comment)]
+    static_files: Option<PathBuf>,
 }
 
 impl Command {
@@ -112,28 +143,65 @@ impl Command {
 
         init_genesis(db.clone(), self.chain.clone())?;
 
+        // the state cache is shared by the RPC and the execution stage
+        let state_cache = StateCache::default();
+        let provider = ShareableDatabase::new(db.clone()).with_state_cache(state_cache.clone());
+        let static_files = self.open_static_files()?;
+        let provider = match &static_files {
+            Some(static_files) => provider.with_static_files(static_files.clone()),
+            None => provider,
+        };
+
         let consensus = self.init_consensus()?;
         info!(target: "reth::cli", "Consensus engine initialized");
 
         info!(target: "reth::cli", "Connecting to P2P network");
-        let netconf = self.load_network_config(&config, &db);
+        let netconf = self.load_network_config(&config, &db, &provider);
         let network = netconf.start_network().await?;
 
         info!(target: "reth::cli", peer_id = %network.peer_id(), local_addr = %network.local_addr(), "Connected to P2P network");
 
         // TODO(mattsse): cleanup, add cli args
-        let _rpc_server = reth_rpc_builder::launch(
-            ShareableDatabase::new(db.clone()),
-            reth_transaction_pool::test_utils::testing_pool(),
-            network.clone(),
-            TransportRpcModuleConfig::default()
-                .with_http(vec![RethRpcModule::Admin, RethRpcModule::Eth]),
-            RpcServerConfig::default().with_http(Default::default()),
-        )
-        .await?;
+        let transaction_pool =
+            reth_transaction_pool::test_utils::testing_pool_with_config(PoolConfig {
+                ban_list_path: self.txpool_ban_list.clone(),
+                ..Default::default()
+            });
+        let pending_block = PendingBlockCache::default();
+        let bundles = BundleStore::default();
+        tokio::spawn(
+            PendingBlockBuilder::new(
+                provider.clone(),
+                transaction_pool.clone(),
+                self.chain.clone(),
+                pending_block.clone(),
+            )
+            .with_bundles(bundles.clone())
+            .run(),
+        );
+
+        let _rpc_server = RpcModuleBuilder::new(provider, transaction_pool, network.clone())
+            .with_pending_block(pending_block)
+            .with_bundles(bundles)
+            .with_chain_spec(Arc::new(self.chain.clone()))
+            .build(TransportRpcModuleConfig::default().with_http(vec![
+                RethRpcModule::Admin,
+                RethRpcModule::Eth,
+                RethRpcModule::EthBundle,
+            ]))
+            .start_server(RpcServerConfig::default().with_http(Default::default()))
+            .await?;
         info!(target: "reth::cli", "Started RPC server");
 
-        let mut pipeline = self.build_pipeline(&config, &network, &consensus, &db).await?;
+        let mut pipeline = self
+            .build_pipeline(&config, &network, &consensus, &db, &state_cache, static_files.as_ref())
+            .await?;
+
+        if let Some(static_files) = static_files {
+            let stages = pipeline.stage_ids().into_iter().map(|id| id.0);
+            let producer = StaticFileProducer::new(db.clone(), static_files, stages);
+            self.spawn_static_file_producer(&db, producer);
+        }
 
         tokio::spawn(handle_events(stream_select(
             network.event_listener().map(Into::into),
@@ -178,6 +246,47 @@ impl Command {
         }
     }
 
+    /// Opens the static files, if enabled.
+    fn open_static_files(&self) -> eyre::Result<Option<Arc<StaticFileProvider>>> {
+        let Some(dir) = &self.static_files else { return Ok(None) };
+        let static_files = Arc::new(StaticFileProvider::new(dir)?);
+        info!(target: "reth::cli", path = %dir.display(), "Static files opened");
+        Ok(Some(static_files))
+    }
+
+    /// Periodically moves the finalized blocks to static files.
+    fn spawn_static_file_producer(
+        &self,
+        db: &Arc<Env<WriteMap>>,
+        producer: StaticFileProducer<Env<WriteMap>>,
+    ) {
+        let provider = ShareableDatabase::new(db.clone());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(STATIC_FILES_INTERVAL);
+            loop {
+                interval.tick().await;
+                let finalized = match provider.chain_info() {
+                    Ok(info) => info.best_number.checked_sub(MINIMUM_PRUNING_DISTANCE),
+                    Err(error) => {
+                        warn!(target: "reth::cli", %error, "Failed to read the best block");
+                        continue
+                    }
+                };
+                let Some(finalized) = finalized else { continue };
+                let producer = producer.clone();
+                match tokio::task::spawn_blocking(move || producer.run(finalized)).await {
+                    Ok(Ok(highest)) => {
+                        debug!(target: "reth::cli", ?highest, "Moved finalized blocks to static files")
+                    }
+                    Ok(Err(error)) => {
+                        warn!(target: "reth::cli", %error, "Failed to move blocks to static files")
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+    }
+
     fn init_consensus(&self) -> eyre::Result<Arc<dyn Consensus>> {
         let (consensus, notifier) = BeaconConsensus::builder().build(self.chain.clone());
 
@@ -202,16 +311,21 @@ impl Command {
         &self,
         config: &Config,
         db: &Arc<Env<WriteMap>>,
+        provider: &ShareableDatabase<Env<WriteMap>>,
     ) -> NetworkConfig<ShareableDatabase<Env<WriteMap>>> {
         let peers_file = (!self.network.no_persist_peers).then_some(&self.network.peers_file);
-        config.network_config(
+        let mut netconf = config.network_config(
             db.clone(),
             self.chain.clone(),
             self.network.disable_discovery,
             self.network.bootnodes.clone(),
             self.nat,
             peers_file.map(|f| f.as_ref().to_path_buf()),
-        )
+        );
+        self.network.apply(&mut netconf);
+        // serve the blocks that were moved to static files to peers as well
+        netconf.client = Arc::new(provider.clone());
+        netconf.add_rlpx_sub_protocol(SnapProtocolHandler::new(Arc::new(provider.clone())))
     }
 
     async fn build_pipeline(
@@ -220,6 +334,8 @@ impl Command {
         network: &NetworkHandle,
         consensus: &Arc<dyn Consensus>,
         db: &Arc<Env<WriteMap>>,
+        state_cache: &StateCache,
+        static_files: Option<&Arc<StaticFileProvider>>,
     ) -> eyre::Result<Pipeline<Env<WriteMap>, NetworkHandle>> {
         let fetch_client = Arc::new(network.fetch_client().await?);
 
@@ -233,6 +349,19 @@ impl Command {
             builder = builder.with_max_block(max_block)
         }
 
+        let prune_conf = &config.prune;
+        if !prune_conf.parts.is_empty() {
+            let mut pruner = Pruner::new(
+                prune_conf.parts.clone(),
+                prune_conf.block_interval,
+                prune_conf.batch_size,
+            );
+            if let Some(static_files) = static_files {
+                pruner = pruner.with_static_files(static_files.clone());
+            }
+            builder = builder.with_pruner(pruner)
+        }
+
         let pipeline = builder
             .with_sync_state_updater(network.clone())
             .add_stages(
@@ -249,10 +378,13 @@ impl Command {
                         batch_size: stage_conf.sender_recovery.batch_size,
                         commit_threshold: stage_conf.sender_recovery.commit_threshold,
                     })
-                    .set(ExecutionStage {
-                        chain_spec: self.chain.clone(),
-                        commit_threshold: stage_conf.execution.commit_threshold,
-                    }),
+                    .set(
+                        ExecutionStage::new(
+                            self.chain.clone(),
+                            stage_conf.execution.commit_threshold,
+                        )
+                        .with_state_cache(state_cache.clone()),
+                    ),
             )
             .build();
 
@@ -0,0 +1,424 @@
+//! `reth p2p crawl` command
+use clap::{Parser, ValueEnum};
+use futures::{stream::FuturesUnordered, Stream, StreamExt};
+use reth_discv4::{
+    bootnodes::{goerli_nodes, mainnet_nodes, sepolia_nodes},
+    DiscoveryUpdate, Discv4, Discv4Config,
+};
+use reth_dns_discovery::{DnsDiscoveryConfig, DnsDiscoveryService, DnsResolver};
+use reth_ecies::{stream::ECIESStream, util::pk2id};
+use reth_eth_wire::{DisconnectReason, HelloMessage, Status, UnauthedEthStream, UnauthedP2PStream};
+use reth_network::config::rng_secret_key;
+use reth_primitives::{hex, Chain, ChainSpec, Head, NodeRecord, PeerId, H256, U256};
+use secp256k1::{SecretKey, SECP256K1};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write as _,
+    net::SocketAddr,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{net::TcpStream, sync::Semaphore, task::JoinHandle};
+use tracing::{debug, info};
+
+/// The output format of the `reth p2p crawl` command
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+pub enum CrawlFormat {
+    /// A JSON array of crawled nodes
+    Json,
+    /// One line per crawled node with a header
+    Csv,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth p2p crawl` command
+pub struct CrawlArgs {
+    /// How long to crawl the network, in seconds
+    #[arg(long, default_value = "300")]
+    duration: u64,
+
+    /// The maximum number of concurrent handshakes
+    #[arg(long, default_value = "64")]
+    concurrency: usize,
+
+    /// The timeout of the handshake with a single node, in seconds
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+
+    /// The interval between random discovery lookups, in milliseconds
+    #[arg(long, default_value = "500")]
+    lookup_interval: u64,
+
+    /// The UDP address to run discovery on
+    #[arg(long, default_value = "0.0.0.0:30303")]
+    addr: SocketAddr,
+
+    /// Do not sync the chain's public DNS tree
+    #[arg(long)]
+    disable_dns: bool,
+
+    /// The output format
+    #[arg(long, value_enum, default_value = "json")]
+    format: CrawlFormat,
+
+    /// Where to write the output, stdout if not set
+    #[arg(long, short, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+/// The outcome of the handshake with a single node.
+///
+/// Fields that could not be learned, because the handshake failed before, are `None`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawledNode {
+    /// The id of the node
+    pub peer_id: PeerId,
+    /// The `enode://` URL of the node
+    pub enode: String,
+    /// The ENR of the node, if it was received via DNS or discv4
+    pub enr: Option<String>,
+    /// Round trip time of the TCP connect, in milliseconds
+    pub latency_ms: Option<u64>,
+    /// The client version from the `Hello` message
+    pub client_version: Option<String>,
+    /// The capabilities from the `Hello` message
+    pub capabilities: Vec<String>,
+    /// The negotiated `eth` version
+    pub eth_version: Option<u8>,
+    /// The network ID from the `Status` message
+    pub network_id: Option<u64>,
+    /// The hash of the fork ID from the `Status` message
+    pub fork_hash: Option<String>,
+    /// The next fork of the fork ID from the `Status` message
+    pub fork_next: Option<u64>,
+    /// The genesis hash from the `Status` message
+    pub genesis: Option<H256>,
+    /// The best block hash from the `Status` message
+    pub best_hash: Option<H256>,
+    /// The total difficulty from the `Status` message
+    pub total_difficulty: Option<U256>,
+    /// Why the handshake failed
+    pub error: Option<String>,
+}
+
+impl CrawledNode {
+    fn new(record: NodeRecord) -> Self {
+        Self {
+            peer_id: record.id,
+            enode: record.to_string(),
+            enr: None,
+            latency_ms: None,
+            client_version: None,
+            capabilities: Vec::new(),
+            eth_version: None,
+            network_id: None,
+            fork_hash: None,
+            fork_next: None,
+            genesis: None,
+            best_hash: None,
+            total_difficulty: None,
+            error: None,
+        }
+    }
+
+    fn on_status(&mut self, status: Status) {
+        self.eth_version = Some(status.version);
+        self.network_id = Some(status.chain.id());
+        self.fork_hash = Some(hex::encode(status.forkid.hash.0));
+        self.fork_next = Some(status.forkid.next);
+        self.genesis = Some(status.genesis);
+        self.best_hash = Some(status.blockhash);
+        self.total_difficulty = Some(status.total_difficulty);
+    }
+
+    /// The CSV header matching [CrawledNode::to_csv_row]
+    const CSV_HEADER: &'static str = "peer_id,enode,enr,latency_ms,client_version,capabilities,eth_version,network_id,fork_hash,fork_next,genesis,best_hash,total_difficulty,error";
+
+    fn to_csv_row(&self) -> String {
+        fn opt<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(ToString::to_string).unwrap_or_default()
+        }
+        fn opt_hash(value: &Option<H256>) -> String {
+            value.map(|hash| format!("{hash:?}")).unwrap_or_default()
+        }
+        fn escape(value: String) -> String {
+            if value.contains([',', '"', '\n']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value
+            }
+        }
+
+        [
+            format!("{:?}", self.peer_id),
+            self.enode.clone(),
+            opt(&self.enr),
+            opt(&self.latency_ms),
+            opt(&self.client_version),
+            self.capabilities.join(" "),
+            opt(&self.eth_version),
+            opt(&self.network_id),
+            opt(&self.fork_hash),
+            opt(&self.fork_next),
+            opt_hash(&self.genesis),
+            opt_hash(&self.best_hash),
+            opt(&self.total_difficulty),
+            opt(&self.error),
+        ]
+        .into_iter()
+        .map(escape)
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+impl CrawlArgs {
+    /// Execute `p2p crawl` command
+    pub async fn execute(
+        &self,
+        chain_spec: &ChainSpec,
+        extra_boot_nodes: impl IntoIterator<Item = NodeRecord>,
+    ) -> eyre::Result<()> {
+        let secret_key = rng_secret_key();
+        let local_id = pk2id(&secret_key.public_key(SECP256K1));
+
+        // the crawler does not accept incoming connections, the tcp port is only advertised
+        let local_record = NodeRecord {
+            address: self.addr.ip(),
+            tcp_port: self.addr.port(),
+            udp_port: self.addr.port(),
+            id: local_id,
+        };
+        let discv4_config = Discv4Config::builder()
+            .add_boot_nodes(boot_nodes(chain_spec))
+            .add_boot_nodes(extra_boot_nodes)
+            .enable_dht_random_walk(true)
+            .lookup_interval(Duration::from_millis(self.lookup_interval))
+            .build();
+        let discv4 = Discv4::spawn(self.addr, local_record, secret_key, discv4_config).await?;
+        let mut discv4_updates = discv4.update_stream().await?;
+
+        let mut dns_updates: Pin<Box<dyn Stream<Item = (NodeRecord, String)> + Send>> =
+            match chain_spec.chain().public_dns_network_protocol() {
+                Some(link) if !self.disable_dns => {
+                    let mut dns = DnsDiscoveryService::new(
+                        Arc::new(DnsResolver::from_system_conf()?),
+                        DnsDiscoveryConfig::default(),
+                    );
+                    let updates = dns.node_record_stream();
+                    dns.sync_tree(&link)?;
+                    dns.spawn();
+                    Box::pin(updates.map(|update| (update.node_record, update.enr.to_base64())))
+                }
+                _ => Box::pin(futures::stream::pending()),
+            };
+
+        let hello = HelloMessage::builder(local_id).build();
+        let head = Head {
+            hash: chain_spec.genesis_hash(),
+            number: 0,
+            total_difficulty: U256::ZERO,
+            timestamp: 0,
+        };
+        let status = Status::spec_builder(chain_spec, &head).build();
+        let timeout = Duration::from_secs(self.timeout);
+
+        let (lookup_tx, mut lookup_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut lookup_interval =
+            tokio::time::interval(Duration::from_millis(self.lookup_interval));
+        let deadline = tokio::time::sleep(Duration::from_secs(self.duration));
+        tokio::pin!(deadline);
+
+        let mut crawler = Crawler {
+            secret_key,
+            hello,
+            status,
+            timeout,
+            permits: Arc::new(Semaphore::new(self.concurrency.max(1))),
+            seen: HashSet::from([local_id]),
+            enrs: HashMap::new(),
+            handshakes: FuturesUnordered::new(),
+        };
+        let mut crawled = Vec::new();
+
+        info!(target: "reth::cli", chain = %chain_spec.chain(), "Crawling the network");
+
+        loop {
+            tokio::select! {
+                _ = &mut deadline => break,
+                _ = lookup_interval.tick() => {
+                    // look up a random target to walk the DHT beyond our own table
+                    let discv4 = discv4.clone();
+                    let lookup_tx = lookup_tx.clone();
+                    tokio::spawn(async move {
+                        if let Ok(records) = discv4.lookup(PeerId::random()).await {
+                            let _ = lookup_tx.send(records);
+                        }
+                    });
+                }
+                Some(records) = lookup_rx.recv() => {
+                    for record in records {
+                        crawler.on_discovered(record);
+                    }
+                }
+                Some(update) = discv4_updates.next() => {
+                    let mut updates = vec![update];
+                    while let Some(update) = updates.pop() {
+                        match update {
+                            DiscoveryUpdate::Added(record) |
+                            DiscoveryUpdate::EnrForkId(record, _) => crawler.on_discovered(record),
+                            DiscoveryUpdate::Enr(record, enr) => {
+                                crawler.enrs.insert(record.id, enr.to_base64());
+                                crawler.on_discovered(record);
+                            }
+                            DiscoveryUpdate::Batch(batch) => updates.extend(batch),
+                            DiscoveryUpdate::Removed(_) => {}
+                        }
+                    }
+                }
+                Some((record, enr)) = dns_updates.next() => {
+                    crawler.enrs.insert(record.id, enr);
+                    crawler.on_discovered(record);
+                }
+                Some(result) = crawler.handshakes.next() => {
+                    if let Ok(Some(node)) = result {
+                        crawled.push(node);
+                    }
+                }
+            }
+        }
+
+        // finish the handshakes in progress, but don't start new ones
+        crawler.permits.close();
+        while let Some(result) = crawler.handshakes.next().await {
+            if let Ok(Some(node)) = result {
+                crawled.push(node);
+            }
+        }
+
+        // the ENR of a node may arrive after its handshake was started
+        for node in crawled.iter_mut() {
+            node.enr = crawler.enrs.remove(&node.peer_id);
+        }
+
+        info!(
+            target: "reth::cli",
+            discovered = crawler.seen.len() - 1,
+            crawled = crawled.len(),
+            reachable = crawled.iter().filter(|node| node.error.is_none()).count(),
+            "Finished crawling"
+        );
+
+        let output = match self.format {
+            CrawlFormat::Json => serde_json::to_string_pretty(&crawled)?,
+            CrawlFormat::Csv => {
+                let mut csv = format!("{}\n", CrawledNode::CSV_HEADER);
+                for node in crawled.iter() {
+                    let _ = writeln!(csv, "{}", node.to_csv_row());
+                }
+                csv
+            }
+        };
+        match &self.output {
+            Some(path) => std::fs::write(path, output)?,
+            None => println!("{output}"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Spawns a handshake for every newly discovered node.
+struct Crawler {
+    secret_key: SecretKey,
+    hello: HelloMessage,
+    status: Status,
+    timeout: Duration,
+    /// Limits the number of concurrent handshakes
+    permits: Arc<Semaphore>,
+    /// All nodes that were already discovered, including the local node
+    seen: HashSet<PeerId>,
+    /// The ENRs of the discovered nodes, received via DNS or discv4
+    enrs: HashMap<PeerId, String>,
+    /// The spawned handshakes
+    handshakes: FuturesUnordered<JoinHandle<Option<CrawledNode>>>,
+}
+
+impl Crawler {
+    fn on_discovered(&mut self, record: NodeRecord) {
+        if !self.seen.insert(record.id) {
+            return
+        }
+        let permits = Arc::clone(&self.permits);
+        let (secret_key, hello, status, timeout) =
+            (self.secret_key, self.hello.clone(), self.status, self.timeout);
+        self.handshakes.push(tokio::spawn(async move {
+            // the semaphore is closed once the crawl is over
+            let _permit = permits.acquire_owned().await.ok()?;
+            Some(crawl_node(record, secret_key, hello, status, timeout).await)
+        }));
+    }
+}
+
+/// Returns the discovery boot nodes of the chain, falling back to the mainnet nodes.
+fn boot_nodes(chain_spec: &ChainSpec) -> Vec<NodeRecord> {
+    match chain_spec.chain().id() {
+        id if id == Chain::goerli().id() => goerli_nodes(),
+        id if id == Chain::sepolia().id() => sepolia_nodes(),
+        _ => mainnet_nodes(),
+    }
+}
+
+/// Performs the `Hello` and `Status` handshake with the node and disconnects afterwards.
+async fn crawl_node(
+    record: NodeRecord,
+    secret_key: SecretKey,
+    hello: HelloMessage,
+    status: Status,
+    timeout: Duration,
+) -> CrawledNode {
+    let mut node = CrawledNode::new(record);
+    match tokio::time::timeout(timeout, handshake(&mut node, record, secret_key, hello, status))
+        .await
+    {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => node.error = Some(err.to_string()),
+        Err(_) => node.error = Some("handshake timed out".to_string()),
+    }
+    debug!(target: "reth::cli", enode = %node.enode, error = ?node.error, "Crawled node");
+    node
+}
+
+/// Runs the RLPx, `Hello` and `Status` handshakes and records the results in the [CrawledNode].
+async fn handshake(
+    node: &mut CrawledNode,
+    record: NodeRecord,
+    secret_key: SecretKey,
+    hello: HelloMessage,
+    status: Status,
+) -> eyre::Result<()> {
+    let start = Instant::now();
+    let stream = TcpStream::connect(record.tcp_addr()).await?;
+    node.latency_ms = Some(start.elapsed().as_millis() as u64);
+
+    let stream = ECIESStream::connect(stream, secret_key, record.id).await?;
+    let (p2p_stream, their_hello) = UnauthedP2PStream::new(stream).handshake(hello).await?;
+    node.client_version = Some(their_hello.client_version);
+    node.capabilities = their_hello
+        .capabilities
+        .iter()
+        .map(|cap| format!("{}/{}", cap.name, cap.version))
+        .collect();
+
+    // the status of the node is recorded even if it is on a different chain or fork
+    let status = Status { version: p2p_stream.shared_capability().version(), ..status };
+    let mut eth_stream = UnauthedEthStream::new(p2p_stream);
+    let their_status = eth_stream.exchange_status(status).await?;
+    node.on_status(their_status);
+
+    let _ = eth_stream.into_inner().disconnect(DisconnectReason::ClientQuitting).await;
+    Ok(())
+}
@@ -0,0 +1,140 @@
+//! `reth p2p dns-tree` command
+use clap::{Parser, ValueEnum};
+use reth_dns_discovery::{
+    publish::DnsTree,
+    tree::{LinkEntry, NodeEntry},
+    DnsDiscoveryEvent, DnsDiscoveryService, MapResolver,
+};
+use reth_primitives::hex;
+use secp256k1::{PublicKey, SecretKey, SECP256K1};
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio_stream::StreamExt;
+use tracing::info;
+
+/// How long to wait for a built tree to be resolved through the in-memory resolver.
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The output format of the `reth p2p dns-tree` command
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+pub enum DnsTreeFormat {
+    /// A DNS zone file
+    Zone,
+    /// A JSON map of fully qualified names to TXT records
+    Json,
+}
+
+#[derive(Parser, Debug)]
+/// The arguments for the `reth p2p dns-tree` command
+pub struct DnsTreeArgs {
+    /// The domain the tree is published at
+    #[arg(long)]
+    domain: String,
+
+    /// File with one ENR (`enr:...`) per line, e.g. the output of a crawl.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    #[arg(long, value_name = "FILE")]
+    nodes: PathBuf,
+
+    /// File with the hex encoded secret key that signs the tree root
+    #[arg(long, value_name = "FILE")]
+    key: PathBuf,
+
+    /// Links to other trees (`enrtree://<key>@<domain>`)
+    #[arg(long = "link", value_name = "LINK")]
+    links: Vec<LinkEntry>,
+
+    /// The sequence number of the tree, defaults to the current unix timestamp
+    #[arg(long)]
+    seq: Option<u64>,
+
+    /// The output format
+    #[arg(long, value_enum, default_value = "zone")]
+    format: DnsTreeFormat,
+
+    /// The TTL of the records in the zone file
+    #[arg(long, default_value = "1800")]
+    ttl: u32,
+
+    /// Where to write the output, stdout if not set
+    #[arg(long, short, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+impl DnsTreeArgs {
+    /// Execute `p2p dns-tree` command
+    pub async fn execute(&self) -> eyre::Result<()> {
+        let key = std::fs::read_to_string(&self.key)?;
+        let key = SecretKey::from_slice(&hex::decode(key.trim().trim_start_matches("0x"))?)?;
+
+        let mut nodes = Vec::new();
+        for line in std::fs::read_to_string(&self.nodes)?.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            let entry: NodeEntry<SecretKey> =
+                line.parse().map_err(|err| eyre::eyre!("Invalid ENR {line}: {err}"))?;
+            nodes.push(entry.enr);
+        }
+
+        let seq = match self.seq {
+            Some(seq) => seq,
+            None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+
+        let mut tree = DnsTree::new(seq, nodes, self.links.clone());
+        tree.sign(&key)?;
+
+        let link = LinkEntry {
+            domain: self.domain.trim_end_matches('.').to_string(),
+            pubkey: PublicKey::from_secret_key(SECP256K1, &key),
+        };
+        self.verify(&tree, link.clone()).await?;
+        info!(target: "reth::cli", nodes = tree.nodes().count(), %link, "Built DNS tree");
+
+        let output = match self.format {
+            DnsTreeFormat::Zone => tree.to_zone_file(&link.domain, self.ttl),
+            DnsTreeFormat::Json => {
+                serde_json::to_string_pretty(&tree.to_txt_records(&link.domain))?
+            }
+        };
+        match &self.output {
+            Some(path) => std::fs::write(path, output)?,
+            None => println!("{output}"),
+        }
+
+        Ok(())
+    }
+
+    /// Resolves all nodes of the tree through a [MapResolver] to make sure the published tree can
+    /// be synced.
+    async fn verify(&self, tree: &DnsTree, link: LinkEntry) -> eyre::Result<()> {
+        let resolver = MapResolver::default();
+        tree.insert_into(&resolver, &link.domain);
+
+        let mut service = DnsDiscoveryService::new(Arc::new(resolver), Default::default());
+        service.sync_tree_with_link(link);
+
+        let mut expected = tree.nodes().map(|enr| enr.to_base64()).collect::<HashSet<_>>();
+        tokio::time::timeout(VERIFY_TIMEOUT, async {
+            while !expected.is_empty() {
+                match service.next().await {
+                    Some(DnsDiscoveryEvent::Enr(enr)) => {
+                        expected.remove(&enr.to_base64());
+                    }
+                    None => break,
+                }
+            }
+        })
+        .await
+        .map_err(|_| eyre::eyre!("Failed to resolve {} nodes of the tree", expected.len()))?;
+
+        Ok(())
+    }
+}
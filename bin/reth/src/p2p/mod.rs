@@ -5,6 +5,8 @@ use crate::{
 };
 use backon::{ConstantBackoff, Retryable};
 use clap::{Parser, Subcommand};
+use crawl::CrawlArgs;
+use dns_tree::DnsTreeArgs;
 use reth_db::mdbx::{Env, EnvKind, WriteMap};
 use reth_discv4::NatResolver;
 use reth_interfaces::p2p::{
@@ -16,6 +18,9 @@ use reth_primitives::{BlockHashOrNumber, ChainSpec, NodeRecord, SealedHeader};
 use reth_staged_sync::Config;
 use std::sync::Arc;
 
+mod crawl;
+mod dns_tree;
+
 /// `reth p2p` command
 #[derive(Debug, Parser)]
 pub struct Command {
@@ -78,51 +83,28 @@ pub enum Subcommands {
         #[arg(value_parser = hash_or_num_value_parser)]
         id: BlockHashOrNumber,
     },
+    /// Build and sign an EIP-1459 DNS tree from a list of node records
+    DnsTree(DnsTreeArgs),
+    /// Crawl the network and record the handshake of every discovered node
+    Crawl(CrawlArgs),
 }
 impl Command {
     /// Execute `p2p` command
     pub async fn execute(&self) -> eyre::Result<()> {
-        let tempdir = tempfile::TempDir::new()?;
-        let noop_db = Arc::new(Env::<WriteMap>::open(&tempdir.into_path(), EnvKind::RW)?);
-
-        let mut config: Config = confy::load_path(&self.config).unwrap_or_default();
-
-        if let Some(peer) = self.trusted_peer {
-            config.peers.trusted_nodes.insert(peer);
-        }
-
-        if config.peers.trusted_nodes.is_empty() && self.trusted_only {
-            eyre::bail!("No trusted nodes. Set trusted peer with `--trusted-peer <enode record>` or set `--trusted-only` to `false`")
-        }
-
-        config.peers.connect_trusted_nodes_only = self.trusted_only;
+        let backoff = ConstantBackoff::default().with_max_times(self.retries.max(1));
 
-        let network = config
-            .network_config(
-                noop_db,
-                self.chain.clone(),
-                self.disable_discovery,
-                None,
-                self.nat,
-                None,
-            )
-            .start_network()
-            .await?;
-
-        let fetch_client = network.fetch_client().await?;
-        let retries = self.retries.max(1);
-        let backoff = ConstantBackoff::default().with_max_times(retries);
-
-        match self.command {
+        match &self.command {
             Subcommands::Header { id } => {
-                let header = (move || self.get_single_header(fetch_client.clone(), id))
+                let fetch_client = self.fetch_client().await?;
+                let header = (move || self.get_single_header(fetch_client.clone(), *id))
                     .retry(backoff)
                     .notify(|err, _| println!("Error requesting header: {err}. Retrying..."))
                     .await?;
                 println!("Successfully downloaded header: {header:?}");
             }
             Subcommands::Body { id } => {
-                let hash = match id {
+                let fetch_client = self.fetch_client().await?;
+                let hash = match *id {
                     BlockHashOrNumber::Hash(hash) => hash,
                     BlockHashOrNumber::Number(number) => {
                         println!("Block number provided. Downloading header first...");
@@ -156,11 +138,46 @@ impl Command {
                 let body = result.into_iter().next().unwrap();
                 println!("Successfully downloaded body: {body:?}")
             }
+            // these commands do not require a running network
+            Subcommands::DnsTree(args) => args.execute().await?,
+            Subcommands::Crawl(args) => args.execute(&self.chain, self.trusted_peer).await?,
         }
 
         Ok(())
     }
 
+    /// Starts the network and returns a client to request data from peers.
+    async fn fetch_client(&self) -> eyre::Result<FetchClient> {
+        let tempdir = tempfile::TempDir::new()?;
+        let noop_db = Arc::new(Env::<WriteMap>::open(&tempdir.into_path(), EnvKind::RW)?);
+
+        let mut config: Config = confy::load_path(&self.config).unwrap_or_default();
+
+        if let Some(peer) = self.trusted_peer {
+            config.peers.trusted_nodes.insert(peer);
+        }
+
+        if config.peers.trusted_nodes.is_empty() && self.trusted_only {
+            eyre::bail!("No trusted nodes. Set trusted peer with `--trusted-peer <enode record>` or set `--trusted-only` to `false`")
+        }
+
+        config.peers.connect_trusted_nodes_only = self.trusted_only;
+
+        let network = config
+            .network_config(
+                noop_db,
+                self.chain.clone(),
+                self.disable_discovery,
+                None,
+                self.nat,
+                None,
+            )
+            .start_network()
+            .await?;
+
+        Ok(network.fetch_client().await?)
+    }
+
     /// Get a single header from network
     pub async fn get_single_header(
         &self,
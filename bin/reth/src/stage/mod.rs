@@ -136,17 +136,16 @@ impl Command {
                     });
                 }
 
-                let network = config
-                    .network_config(
-                        db.clone(),
-                        self.chain.clone(),
-                        self.network.disable_discovery,
-                        None,
-                        self.nat,
-                        None,
-                    )
-                    .start_network()
-                    .await?;
+                let mut network_config = config.network_config(
+                    db.clone(),
+                    self.chain.clone(),
+                    self.network.disable_discovery,
+                    None,
+                    self.nat,
+                    None,
+                );
+                self.network.apply(&mut network_config);
+                let network = network_config.start_network().await?;
                 let fetch_client = Arc::new(network.fetch_client().await?);
 
                 let mut stage = BodyStage {
@@ -182,8 +181,7 @@ impl Command {
                 stage.execute(&mut tx, input).await?;
             }
             StageEnum::Execution => {
-                let mut stage =
-                    ExecutionStage { chain_spec: self.chain.clone(), commit_threshold: num_blocks };
+                let mut stage = ExecutionStage::new(self.chain.clone(), num_blocks);
                 if !self.skip_unwind {
                     stage.unwind(&mut tx, unwind).await?;
                 }
@@ -8,13 +8,12 @@ use crate::{
 use hashbrown::hash_map::Entry;
 use reth_interfaces::executor::{BlockExecutor, Error};
 use reth_primitives::{
-    bloom::logs_bloom, Account, Address, Block, Bloom, ChainSpec, Hardfork, Head, Header, Log,
-    Receipt, TransactionSigned, H160, H256, U256,
+    bloom::logs_bloom, Account, Address, Block, Bloom, Bytes, ChainSpec, Hardfork, Head, Header,
+    Log, Receipt, TransactionSigned, H160, H256, U256,
 };
 use reth_provider::StateProvider;
 use revm::{
-    db::AccountState, Account as RevmAccount, AccountInfo, AnalysisKind, Bytecode, Return, SpecId,
-    EVM,
+    db::AccountState, Account as RevmAccount, AccountInfo, AnalysisKind, Return, SpecId, EVM,
 };
 use std::collections::BTreeMap;
 
@@ -31,7 +30,8 @@ impl<'a, DB> Executor<'a, DB>
 where
     DB: StateProvider,
 {
-    fn new(chain_spec: &'a ChainSpec, db: &'a mut SubState<DB>) -> Self {
+    /// Creates a new executor for the given chain that executes on top of the given state.
+    pub fn new(chain_spec: &'a ChainSpec, db: &'a mut SubState<DB>) -> Self {
         let mut evm = EVM::new();
         evm.database(db);
         Executor { chain_spec, evm }
@@ -57,7 +57,10 @@ where
         }
     }
 
-    fn init_block_env(&mut self, header: &Header, total_difficulty: U256) {
+    /// Configures the EVM environment for executing transactions of a block with the given header.
+    ///
+    /// The `total_difficulty` is used to determine the active hardforks.
+    pub fn init_block_env(&mut self, header: &Header, total_difficulty: U256) {
         let spec_id = revm_spec(
             self.chain_spec,
             Head {
@@ -86,7 +89,7 @@ where
     fn commit_changes(
         &mut self,
         changes: hashbrown::HashMap<H160, RevmAccount>,
-    ) -> (BTreeMap<Address, AccountChangeSet>, BTreeMap<H256, Bytecode>) {
+    ) -> (BTreeMap<Address, AccountChangeSet>, BTreeMap<H256, Bytes>) {
         let db = self.db();
 
         let mut change = BTreeMap::new();
@@ -128,7 +131,12 @@ where
                         match db.contracts.entry(account.info.code_hash) {
                             Entry::Vacant(entry) => {
                                 entry.insert(code.clone());
-                                new_bytecodes.insert(H256(account.info.code_hash.0), code.clone());
+                                // store the original code, without the padding added by the
+                                // analysis
+                                new_bytecodes.insert(
+                                    H256(account.info.code_hash.0),
+                                    Bytes::from(&code.bytes()[..code.len()]),
+                                );
                             }
                             Entry::Occupied(mut entry) => {
                                 entry.insert(code.clone());
@@ -196,6 +204,107 @@ where
         (change, new_bytecodes)
     }
 
+    /// Executes a single transaction on top of the current state and commits its changes to the
+    /// cached state.
+    ///
+    /// The `cumulative_gas_used` is the gas used by all transactions of the block that were
+    /// executed before this one, it's required for the receipt of the transaction.
+    ///
+    /// Returns the [TransactionChangeSet] of the transaction and the gas it used.
+    ///
+    /// Note: [Executor::init_block_env] must be called before executing transactions.
+    pub fn execute_transaction(
+        &mut self,
+        transaction: &TransactionSigned,
+        sender: Address,
+        cumulative_gas_used: u64,
+    ) -> Result<(TransactionChangeSet, u64), Error> {
+        self.transact(transaction, sender, cumulative_gas_used, false)
+    }
+
+    /// Same as [Executor::execute_transaction], but fails with [Error::TransactionRejected] if the
+    /// transaction did not pass the pre-execution checks of the EVM.
+    ///
+    /// This is meant for simulations on top of the current state, like building a pending block,
+    /// where transactions are not guaranteed to be valid. It must not be used to execute the
+    /// transactions of a block that is verified against the chain.
+    pub fn simulate_transaction(
+        &mut self,
+        transaction: &TransactionSigned,
+        sender: Address,
+        cumulative_gas_used: u64,
+    ) -> Result<(TransactionChangeSet, u64), Error> {
+        self.transact(transaction, sender, cumulative_gas_used, true)
+    }
+
+    fn transact(
+        &mut self,
+        transaction: &TransactionSigned,
+        sender: Address,
+        cumulative_gas_used: u64,
+        reject_unexecuted: bool,
+    ) -> Result<(TransactionChangeSet, u64), Error> {
+        // Fill revm structure.
+        revm_wrap::fill_tx_env(&mut self.evm.env.tx, transaction, sender);
+
+        // Execute transaction.
+        let out = self.evm.transact();
+
+        // Useful for debugging
+        // let out = evm.inspect(revm::inspectors::CustomPrintTracer::default());
+        // tracing::trace!(target:"evm","Executing transaction {:?}, \n:{out:?}: {:?}
+        // \nENV:{:?}",transaction.hash(),transaction,evm.env);
+
+        let (revm::ExecutionResult { exit_reason, gas_used, logs, .. }, state) = out;
+
+        // Fatal internal error.
+        if exit_reason == revm::Return::FatalExternalError {
+            return Err(Error::ExecutionFatalError)
+        }
+
+        // A transaction that failed the pre-execution checks (nonce, balance, fee caps) was never
+        // executed and did not spend any gas, not even the intrinsic gas.
+        if reject_unexecuted && gas_used == 0 {
+            return Err(Error::TransactionRejected {
+                hash: transaction.hash(),
+                error_code: exit_reason as u32,
+            })
+        }
+
+        // Success flag was added in `EIP-658: Embedding transaction status code in receipts`.
+        // TODO for verification (exit_reason): some error should return EVM error as the block
+        // with that transaction can have consensus error that would make block
+        // invalid.
+        let is_success = match exit_reason {
+            revm::return_ok!() => true,
+            revm::return_revert!() => false,
+            _ => false,
+            // TODO: Handle after bumping to revm v3.0: https://github.com/paradigmxyz/reth/issues/463
+            // e => return Err(Error::EVMError { error_code: e as u32 }),
+        };
+
+        // commit state
+        let (changeset, new_bytecodes) = self.commit_changes(state);
+
+        // Transform logs to reth format.
+        let logs: Vec<Log> = logs.into_iter().map(into_reth_log).collect();
+
+        // Create transaction changeset and calculate header bloom filter for receipt.
+        let changeset = TransactionChangeSet {
+            receipt: Receipt {
+                tx_type: transaction.tx_type(),
+                success: is_success,
+                cumulative_gas_used: cumulative_gas_used + gas_used,
+                bloom: logs_bloom(logs.iter()),
+                logs,
+            },
+            changeset,
+            new_bytecodes,
+        };
+
+        Ok((changeset, gas_used))
+    }
+
     /// Calculate Block reward changeset
     pub fn block_reward_changeset(
         &mut self,
@@ -360,57 +469,13 @@ where
                 })
             }
 
-            // Fill revm structure.
-            revm_wrap::fill_tx_env(&mut self.evm.env.tx, transaction, sender);
-
-            // Execute transaction.
-            let out = self.evm.transact();
-
-            // Useful for debugging
-            // let out = evm.inspect(revm::inspectors::CustomPrintTracer::default());
-            // tracing::trace!(target:"evm","Executing transaction {:?}, \n:{out:?}: {:?}
-            // \nENV:{:?}",transaction.hash(),transaction,evm.env);
-
-            let (revm::ExecutionResult { exit_reason, gas_used, logs, .. }, state) = out;
-
-            // Fatal internal error.
-            if exit_reason == revm::Return::FatalExternalError {
-                return Err(Error::ExecutionFatalError)
-            }
-
-            // Success flag was added in `EIP-658: Embedding transaction status code in receipts`.
-            // TODO for verification (exit_reason): some error should return EVM error as the block
-            // with that transaction can have consensus error that would make block
-            // invalid.
-            let is_success = match exit_reason {
-                revm::return_ok!() => true,
-                revm::return_revert!() => false,
-                _ => false,
-                // TODO: Handle after bumping to revm v3.0: https://github.com/paradigmxyz/reth/issues/463
-                // e => return Err(Error::EVMError { error_code: e as u32 }),
-            };
+            let (changeset, gas_used) =
+                self.execute_transaction(transaction, sender, cumulative_gas_used)?;
 
             // Add spent gas.
             cumulative_gas_used += gas_used;
 
-            // commit state
-            let (changeset, new_bytecodes) = self.commit_changes(state);
-
-            // Transform logs to reth format.
-            let logs: Vec<Log> = logs.into_iter().map(into_reth_log).collect();
-
-            // Push transaction changeset and calculte header bloom filter for receipt.
-            changesets.push(TransactionChangeSet {
-                receipt: Receipt {
-                    tx_type: transaction.tx_type(),
-                    success: is_success,
-                    cumulative_gas_used,
-                    bloom: logs_bloom(logs.iter()),
-                    logs,
-                },
-                changeset,
-                new_bytecodes,
-            })
+            changesets.push(changeset);
         }
 
         // Check if gas used matches the value set in header.
@@ -11,7 +11,7 @@ pub mod config;
 pub mod eth_dao_fork;
 
 /// Execution result types
-pub mod execution_result;
+pub use reth_provider::execution_result;
 /// Executor
 pub mod executor;
 /// Wrapper around revm database and types
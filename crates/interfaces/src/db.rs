@@ -28,4 +28,10 @@ pub enum Error {
     /// Failed to decode a key from a table..
     #[error("Error decoding value.")]
     DecodeError,
+    /// Failed to copy the database.
+    #[error("Database backup error code: {0:?}")]
+    Backup(u32),
+    /// Failed to compress or decompress a value.
+    #[error("Compression error: {0}")]
+    Compression(String),
 }
@@ -48,6 +48,8 @@ pub enum Error {
         transaction_gas_limit: u64,
         block_available_gas: u64,
     },
+    #[error("Transaction {hash:?} was rejected by the EVM with error code {error_code}")]
+    TransactionRejected { hash: H256, error_code: u32 },
     #[error("Block gas used {got} is different from expected gas used {expected}.")]
     BlockGasUsed { got: u64, expected: u64 },
     #[error("Revm error {error_code}")]
@@ -1,4 +1,4 @@
-use reth_primitives::{Address, BlockHash, BlockNumber, TransitionId, H256};
+use reth_primitives::{Address, BlockHash, BlockNumber, PrunePart, TransitionId, TxNumber, H256};
 
 /// KV error type. They are using u32 to represent error code.
 #[allow(missing_docs)]
@@ -12,6 +12,8 @@ pub enum Error {
     BlockBody { block_number: BlockNumber, block_hash: BlockHash },
     #[error("Block transition id does not exist for block #{block_number}")]
     BlockTransition { block_number: BlockNumber },
+    #[error("Total difficulty of block #{block_number} does not exist")]
+    TotalDifficulty { block_number: BlockNumber },
     #[error("Block number {block_number} from block hash #{block_hash} does not exist in canonical chain")]
     BlockCanonical { block_number: BlockNumber, block_hash: BlockHash },
     #[error("Block number {block_number} with hash #{received_hash:?} is not canonical block. Canonical block hash is #{expected_hash:?}")]
@@ -20,6 +22,18 @@ pub enum Error {
         expected_hash: BlockHash,
         received_hash: BlockHash,
     },
+    #[error("Transaction #{tx_id} does not exist in Transactions table")]
+    Transaction { tx_id: TxNumber },
+    #[error("Sender of transaction #{tx_id} does not exist in TxSenders table and could not be recovered")]
+    TransactionSender { tx_id: TxNumber },
+    #[error("State root mismatch at block #{block_number}: got {got:?}, expected {expected:?}")]
+    StateRootMismatch { block_number: BlockNumber, got: H256, expected: H256 },
+    #[error("Trie error: {0}")]
+    Trie(String),
+    #[error("Static file error: {0}")]
+    StaticFile(String),
+    #[error("{part} of block #{block_number} was pruned")]
+    Pruned { part: PrunePart, block_number: BlockNumber },
     #[error("Storage ChangeSet address: ({address:?} key: {storage_key:?}) for transition:#{transition_id} does not exist")]
     StorageChangeset { transition_id: TransitionId, address: Address, storage_key: H256 },
     #[error("Account {address:?} ChangeSet for transition #{transition_id} does not exist")]
@@ -24,6 +24,7 @@
 
 use std::{
     convert::TryFrom as _,
+    future::Future,
     io,
     net::SocketAddr,
     pin::Pin,
@@ -36,9 +37,10 @@ use std::{
 use tokio::{
     io::{AsyncRead, AsyncWrite, ReadBuf},
     net::TcpStream,
+    time::Sleep,
 };
 
-use crate::stream::HasRemoteAddr;
+use crate::{ratelimit::BandwidthLimiter, stream::HasRemoteAddr};
 
 /// Meters bandwidth usage of streams
 #[derive(Debug)]
@@ -47,6 +49,8 @@ struct BandwidthMeterInner {
     inbound: AtomicU64,
     /// Measures the number of outbound packets
     outbound: AtomicU64,
+    /// The meter that also records everything recorded by this meter.
+    parent: Option<BandwidthMeter>,
 }
 
 /// Public shareable struct used for getting bandwidth metering info
@@ -56,6 +60,43 @@ pub struct BandwidthMeter {
 }
 
 impl BandwidthMeter {
+    /// Returns a new meter that records into its own counters _and_ into this meter.
+    ///
+    /// This can be used to meter a single stream while still accounting for it in the aggregate.
+    pub fn child(&self) -> Self {
+        Self {
+            inner: Arc::new(BandwidthMeterInner {
+                inbound: AtomicU64::new(0),
+                outbound: AtomicU64::new(0),
+                parent: Some(self.clone()),
+            }),
+        }
+    }
+
+    /// Records `num_bytes` downloaded bytes.
+    pub fn record_inbound(&self, num_bytes: usize) {
+        self.add_inbound(u64::try_from(num_bytes).unwrap_or(u64::max_value()))
+    }
+
+    /// Records `num_bytes` uploaded bytes.
+    pub fn record_outbound(&self, num_bytes: usize) {
+        self.add_outbound(u64::try_from(num_bytes).unwrap_or(u64::max_value()))
+    }
+
+    fn add_inbound(&self, num_bytes: u64) {
+        self.inner.inbound.fetch_add(num_bytes, Ordering::Relaxed);
+        if let Some(parent) = &self.inner.parent {
+            parent.add_inbound(num_bytes);
+        }
+    }
+
+    fn add_outbound(&self, num_bytes: u64) {
+        self.inner.outbound.fetch_add(num_bytes, Ordering::Relaxed);
+        if let Some(parent) = &self.inner.parent {
+            parent.add_outbound(num_bytes);
+        }
+    }
+
     /// Returns the total number of bytes that have been downloaded on all the streams.
     ///
     /// > **Note**: This method is by design subject to race conditions. The returned value should
@@ -79,6 +120,7 @@ impl Default for BandwidthMeter {
             inner: Arc::new(BandwidthMeterInner {
                 inbound: AtomicU64::new(0),
                 outbound: AtomicU64::new(0),
+                parent: None,
             }),
         }
     }
@@ -86,6 +128,9 @@ impl Default for BandwidthMeter {
 
 /// Wraps around a single stream that implements [`AsyncRead`] + [`AsyncWrite`] and meters the
 /// bandwidth through it
+///
+/// Optionally, the stream enforces the rates of attached [`BandwidthLimiter`]s: reads and writes
+/// are delayed until all limiters of the direction have bytes available again.
 #[derive(Debug)]
 #[pin_project::pin_project]
 pub struct MeteredStream<S> {
@@ -94,19 +139,39 @@ pub struct MeteredStream<S> {
     inner: S,
     /// The [`BandwidthMeter`] struct this uses to meter bandwidth
     meter: BandwidthMeter,
+    /// Limits the download rate
+    download: StreamLimit,
+    /// Limits the upload rate
+    upload: StreamLimit,
 }
 
 impl<S> MeteredStream<S> {
     /// Creates a new [`MeteredStream`] wrapping around the provided stream,
     /// along with a new [`BandwidthMeter`]
     pub fn new(inner: S) -> Self {
-        Self { inner, meter: BandwidthMeter::default() }
+        Self::new_with_meter(inner, BandwidthMeter::default())
     }
 
     /// Creates a new [`MeteredStream`] wrapping around the provided stream,
     /// attaching the provided [`BandwidthMeter`]
     pub fn new_with_meter(inner: S, meter: BandwidthMeter) -> Self {
-        Self { inner, meter }
+        Self { inner, meter, download: Default::default(), upload: Default::default() }
+    }
+
+    /// Limits the download rate of the stream with the given [`BandwidthLimiter`].
+    ///
+    /// Multiple limiters can be attached, e.g. one for the stream and one shared by all streams.
+    pub fn with_download_limiter(mut self, limiter: BandwidthLimiter) -> Self {
+        self.download.limiters.push(limiter);
+        self
+    }
+
+    /// Limits the upload rate of the stream with the given [`BandwidthLimiter`].
+    ///
+    /// Multiple limiters can be attached, e.g. one for the stream and one shared by all streams.
+    pub fn with_upload_limiter(mut self, limiter: BandwidthLimiter) -> Self {
+        self.upload.limiters.push(limiter);
+        self
     }
 
     /// Provides a reference to the [`BandwidthMeter`] attached to this [`MeteredStream`]
@@ -115,6 +180,37 @@ impl<S> MeteredStream<S> {
     }
 }
 
+/// The [`BandwidthLimiter`]s of one direction of a [`MeteredStream`].
+#[derive(Debug, Default)]
+struct StreamLimit {
+    limiters: Vec<BandwidthLimiter>,
+    /// Set while waiting for the limiters to have bytes available again
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl StreamLimit {
+    /// Returns `Ready` if all limiters allow a transfer.
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        loop {
+            if let Some(delay) = self.delay.as_mut() {
+                ready!(delay.as_mut().poll(cx));
+                self.delay = None;
+            }
+            match self.limiters.iter().filter_map(BandwidthLimiter::delay).max() {
+                Some(delay) => self.delay = Some(Box::pin(tokio::time::sleep(delay))),
+                None => return Poll::Ready(()),
+            }
+        }
+    }
+
+    /// Records the transferred bytes in all limiters.
+    fn consume(&self, num_bytes: usize) {
+        for limiter in self.limiters.iter() {
+            limiter.consume(num_bytes);
+        }
+    }
+}
+
 impl<Stream: AsyncRead> AsyncRead for MeteredStream<Stream> {
     fn poll_read(
         self: Pin<&mut Self>,
@@ -122,15 +218,14 @@ impl<Stream: AsyncRead> AsyncRead for MeteredStream<Stream> {
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
         let this = self.project();
+        ready!(this.download.poll_ready(cx));
         let num_bytes = {
             let init_num_bytes = buf.filled().len();
             ready!(this.inner.poll_read(cx, buf))?;
             buf.filled().len() - init_num_bytes
         };
-        this.meter
-            .inner
-            .inbound
-            .fetch_add(u64::try_from(num_bytes).unwrap_or(u64::max_value()), Ordering::Relaxed);
+        this.download.consume(num_bytes);
+        this.meter.record_inbound(num_bytes);
         Poll::Ready(Ok(()))
     }
 }
@@ -142,11 +237,10 @@ impl<Stream: AsyncWrite> AsyncWrite for MeteredStream<Stream> {
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
         let this = self.project();
+        ready!(this.upload.poll_ready(cx));
         let num_bytes = ready!(this.inner.poll_write(cx, buf))?;
-        this.meter
-            .inner
-            .outbound
-            .fetch_add(u64::try_from(num_bytes).unwrap_or(u64::max_value()), Ordering::Relaxed);
+        this.upload.consume(num_bytes);
+        this.meter.record_outbound(num_bytes);
         Poll::Ready(Ok(num_bytes))
     }
 
@@ -270,4 +364,36 @@ mod tests {
         assert_bandwidth_counts(&shared_client_bandwidth_meter, 8, 8);
         assert_bandwidth_counts(&shared_server_bandwidth_meter, 8, 8);
     }
+
+    #[tokio::test]
+    async fn test_child_meter() {
+        let (client, server) = duplex(64);
+
+        let total = BandwidthMeter::default();
+        let mut metered_client = MeteredStream::new_with_meter(client, total.child());
+        let mut metered_server = MeteredStream::new(server);
+
+        duplex_stream_ping_pong(&mut metered_client, &mut metered_server).await;
+
+        assert_bandwidth_counts(metered_client.get_bandwidth_meter(), 4, 4);
+        assert_bandwidth_counts(&total, 4, 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_upload_limit() {
+        let (client, server) = duplex(1024);
+        let mut metered_client =
+            MeteredStream::new(client).with_upload_limiter(BandwidthLimiter::new(100));
+        let mut metered_server = MeteredStream::new(server);
+
+        let start = tokio::time::Instant::now();
+        // the first write drains the limiter, the second one needs to wait for it to refill
+        metered_client.write_all(&[0u8; 150]).await.unwrap();
+        metered_client.write_all(&[0u8; 10]).await.unwrap();
+        assert!(start.elapsed() >= std::time::Duration::from_millis(500));
+
+        let mut buf = [0u8; 160];
+        metered_server.read_exact(&mut buf).await.unwrap();
+        assert_bandwidth_counts(metered_client.get_bandwidth_meter(), 0, 160);
+    }
 }
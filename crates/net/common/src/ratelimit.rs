@@ -1,12 +1,14 @@
 //! A rate limit implementation to enforce a specific rate.
 
+use parking_lot::Mutex;
 use std::{
     future::{poll_fn, Future},
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
-use tokio::time::Sleep;
+use tokio::time::{Instant, Sleep};
 
 /// Given a [Rate] this type enforces a rate limit.
 #[derive(Debug)]
@@ -79,6 +81,22 @@ impl RateLimit {
             State::Limited => panic!("RateLimit limited; poll_ready must be called first"),
         }
     }
+
+    /// Updates the [RateLimit] for a new call without waiting.
+    ///
+    /// Returns `false` if the call exceeds the rate, in which case it should be dropped.
+    pub fn try_tick(&mut self) -> bool {
+        if let State::Limited = self.state {
+            let now = tokio::time::Instant::now();
+            if now < self.sleep.deadline() {
+                return false
+            }
+            self.state =
+                State::Ready { until: now + self.rate.duration(), remaining: self.rate.limit() };
+        }
+        self.tick();
+        true
+    }
 }
 
 /// Tracks the state of the [RateLimit]
@@ -114,6 +132,79 @@ impl Rate {
     }
 }
 
+/// Limits the throughput of bytes to a fixed rate.
+///
+/// This is a token bucket that holds at most one second worth of bytes. Transfers are allowed to
+/// exceed the available bytes, which puts the bucket into debt that has to be paid off before the
+/// next transfer, so that the rate is enforced on average.
+///
+/// All clones share the same bucket, so a single limiter can be shared by multiple streams.
+#[derive(Debug, Clone)]
+pub struct BandwidthLimiter {
+    inner: Arc<Mutex<TokenBucket>>,
+}
+
+// === impl BandwidthLimiter ===
+
+impl BandwidthLimiter {
+    /// Creates a new limiter that allows `bytes_per_second`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_second` is zero.
+    pub fn new(bytes_per_second: u64) -> Self {
+        assert!(bytes_per_second > 0, "bandwidth limit must be positive");
+        let bucket = TokenBucket {
+            bytes_per_second,
+            available: bytes_per_second as f64,
+            last_refill: Instant::now(),
+        };
+        Self { inner: Arc::new(Mutex::new(bucket)) }
+    }
+
+    /// Returns the configured rate in bytes per second.
+    pub fn bytes_per_second(&self) -> u64 {
+        self.inner.lock().bytes_per_second
+    }
+
+    /// Returns how long to wait until bytes can be transferred again, or `None` if bytes are
+    /// available.
+    pub fn delay(&self) -> Option<Duration> {
+        let mut bucket = self.inner.lock();
+        bucket.refill(Instant::now());
+        if bucket.available > 0. {
+            return None
+        }
+        // wait until the debt is paid off and at least one byte is available
+        Some(Duration::from_secs_f64((1. - bucket.available) / bucket.bytes_per_second as f64))
+    }
+
+    /// Records that `bytes` were transferred.
+    pub fn consume(&self, bytes: usize) {
+        let mut bucket = self.inner.lock();
+        bucket.refill(Instant::now());
+        bucket.available -= bytes as f64;
+    }
+}
+
+/// The state of a [BandwidthLimiter].
+#[derive(Debug)]
+struct TokenBucket {
+    bytes_per_second: u64,
+    /// Bytes that can be transferred, negative if in debt
+    available: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        let capacity = self.bytes_per_second as f64;
+        self.available = (self.available + elapsed * capacity).min(capacity);
+        self.last_refill = now;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,4 +243,36 @@ mod tests {
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn test_rate_limit_try_tick() {
+        let mut limit = RateLimit::new(Rate::new(2, Duration::from_millis(500)));
+
+        assert!(limit.try_tick());
+        assert!(limit.try_tick());
+        assert!(!limit.try_tick());
+
+        tokio::time::sleep(limit.rate.duration).await;
+
+        assert!(limit.try_tick());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bandwidth_limiter() {
+        let limiter = BandwidthLimiter::new(1000);
+        assert!(limiter.delay().is_none());
+
+        // exceeding the available bytes puts the limiter into debt
+        limiter.consume(1500);
+        let delay = limiter.delay().unwrap();
+        assert!(delay > Duration::from_millis(500) && delay <= Duration::from_millis(502));
+
+        tokio::time::sleep(delay).await;
+        assert!(limiter.delay().is_none());
+
+        // the bucket never holds more than one second worth of bytes
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        limiter.consume(1000);
+        assert!(limiter.delay().is_some());
+    }
 }
@@ -5,7 +5,7 @@
 
 use bytes::{Bytes, BytesMut};
 use reth_net_common::ban_list::BanList;
-use reth_net_nat::{NatResolver, ResolveNatInterval};
+use reth_net_nat::{mapping::MappingGateway, NatResolver, ResolveNatInterval};
 use reth_primitives::NodeRecord;
 use reth_rlp::Encodable;
 use std::{
@@ -65,6 +65,9 @@ pub struct Discv4Config {
     /// If configured and a `external_ip_resolver` is configured, try to resolve the external ip
     /// using this interval.
     pub resolve_external_ip_interval: Option<Duration>,
+    /// If true and the `external_ip_resolver` talks to a gateway, map the discovery and RLPx
+    /// ports on the gateway so that the node is reachable from outside the NAT.
+    pub enable_port_mapping: bool,
 }
 
 impl Discv4Config {
@@ -104,6 +107,15 @@ impl Discv4Config {
         let interval = self.resolve_external_ip_interval?;
         Some(ResolveNatInterval::interval(resolver, interval))
     }
+
+    /// Returns the gateway to request port mappings from, if port mapping is enabled and the
+    /// configured [NatResolver] supports it.
+    pub fn port_mapping_gateway(&self) -> Option<MappingGateway> {
+        if !self.enable_port_mapping {
+            return None
+        }
+        self.external_ip_resolver?.mapping_gateway()
+    }
 }
 
 impl Default for Discv4Config {
@@ -135,6 +147,7 @@ impl Default for Discv4Config {
             external_ip_resolver: Some(Default::default()),
             /// By default retry public IP using a 5min interval
             resolve_external_ip_interval: Some(Duration::from_secs(60 * 5)),
+            enable_port_mapping: false,
         }
     }
 }
@@ -285,6 +298,12 @@ impl Discv4ConfigBuilder {
         self
     }
 
+    /// Whether to map the discovery and RLPx ports on the gateway of the `external_ip_resolver`.
+    pub fn enable_port_mapping(&mut self, enable_port_mapping: bool) -> &mut Self {
+        self.config.enable_port_mapping = enable_port_mapping;
+        self
+    }
+
     /// Returns the configured [`Discv4Config`]
     pub fn build(&self) -> Discv4Config {
         self.config.clone()
@@ -67,9 +67,15 @@ pub use reth_primitives::NodeRecord;
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
 
-use reth_net_nat::ResolveNatInterval;
 /// reexport to get public ip.
 pub use reth_net_nat::{external_ip, NatResolver};
+use reth_net_nat::{
+    mapping::{
+        PortMappingHandle, PortMappingProtocol, PortMappingRequest, PortMappingUpdate,
+        DEFAULT_MAPPING_LEASE,
+    },
+    ResolveNatInterval,
+};
 
 /// The default port for discv4 via UDP
 ///
@@ -381,6 +387,12 @@ pub struct Discv4Service {
     ping_interval: Interval,
     /// The interval at which to attempt resolving external IP again.
     resolve_external_ip_interval: Option<ResolveNatInterval>,
+    /// Maintains the port mappings on the gateway, started on the first poll.
+    ///
+    /// Dropping it removes the mappings.
+    port_mapping: Option<PortMappingHandle>,
+    /// The local TCP port of the RLPx listener, which is mapped on the gateway.
+    local_tcp_port: u16,
     /// How this services is configured
     config: Discv4Config,
     /// Buffered events populated during poll.
@@ -477,6 +489,8 @@ impl Discv4Service {
             evict_expired_requests_interval,
             lookup_rotator,
             resolve_external_ip_interval: config.resolve_external_ip_interval(),
+            port_mapping: None,
+            local_tcp_port: local_node_record.tcp_port,
             config,
             queued_events: Default::default(),
         }
@@ -512,6 +526,45 @@ impl Discv4Service {
         }
     }
 
+    /// Returns the local ports that should be mapped on the gateway.
+    fn port_mapping_requests(&self) -> Vec<PortMappingRequest> {
+        vec![
+            PortMappingRequest::udp(self.local_address.port()),
+            PortMappingRequest::tcp(self.local_tcp_port),
+        ]
+    }
+
+    /// Advertises the external address of the gateway's port mappings.
+    fn on_port_mapping_update(&mut self, update: PortMappingUpdate) {
+        if let Some(ip) = update.external_ip {
+            self.set_external_ip_addr(ip);
+        }
+        let udp_port = update
+            .external_port(PortMappingProtocol::Udp, self.local_address.port())
+            .unwrap_or_else(|| self.local_address.port());
+        let tcp_port = update
+            .external_port(PortMappingProtocol::Tcp, self.local_tcp_port)
+            .unwrap_or(self.local_tcp_port);
+        if self.local_node_record.udp_port != udp_port {
+            info!(target : "discv4", %udp_port, "Updating external udp port");
+            self.local_node_record.udp_port = udp_port;
+            if self.local_node_record.address.is_ipv4() {
+                let _ = self.local_eip_868_enr.set_udp4(udp_port, &self.secret_key);
+            } else {
+                let _ = self.local_eip_868_enr.set_udp6(udp_port, &self.secret_key);
+            }
+        }
+        if self.local_node_record.tcp_port != tcp_port {
+            info!(target : "discv4", %tcp_port, "Updating external tcp port");
+            self.local_node_record.tcp_port = tcp_port;
+            if self.local_node_record.address.is_ipv4() {
+                let _ = self.local_eip_868_enr.set_tcp4(tcp_port, &self.secret_key);
+            } else {
+                let _ = self.local_eip_868_enr.set_tcp6(tcp_port, &self.secret_key);
+            }
+        }
+    }
+
     /// Returns the [PeerId] that identifies this node
     pub fn local_peer_id(&self) -> &PeerId {
         &self.local_node_record.id
@@ -1053,6 +1106,7 @@ impl Discv4Service {
                     (Some(new), None) => self.notify(DiscoveryUpdate::EnrForkId(record, new)),
                     _ => {}
                 }
+                self.notify(DiscoveryUpdate::Enr(record, msg.enr));
             }
         }
     }
@@ -1331,6 +1385,21 @@ impl Discv4Service {
                 self.set_external_ip_addr(ip);
             }
 
+            if self.port_mapping.is_none() {
+                if let Some(gateway) = self.config.port_mapping_gateway() {
+                    self.port_mapping = Some(PortMappingHandle::spawn(
+                        gateway,
+                        self.port_mapping_requests(),
+                        DEFAULT_MAPPING_LEASE,
+                    ));
+                }
+            }
+            while let Some(Poll::Ready(Some(update))) =
+                self.port_mapping.as_mut().map(|m| m.poll_update(cx))
+            {
+                self.on_port_mapping_update(update);
+            }
+
             // process all incoming commands
             if let Some(mut rx) = self.commands_rx.take() {
                 let mut is_done = false;
@@ -1370,6 +1439,11 @@ impl Discv4Service {
                             }
                             Discv4Command::SetTcpPort(port) => {
                                 debug!(target: "discv4", %port, "Update tcp port");
+                                self.local_tcp_port = port;
+                                if let Some(port_mapping) = &self.port_mapping {
+                                    // the external port is advertised once it's mapped
+                                    port_mapping.set_requests(self.port_mapping_requests());
+                                }
                                 self.local_node_record.tcp_port = port;
                                 if self.local_node_record.address.is_ipv4() {
                                     let _ = self.local_eip_868_enr.set_tcp4(port, &self.secret_key);
@@ -1833,6 +1907,8 @@ impl PingReason {
 pub enum DiscoveryUpdate {
     /// Received a [`ForkId`] via EIP-868 for the given [`NodeRecord`].
     EnrForkId(NodeRecord, ForkId),
+    /// Received the [`Enr`] of the given [`NodeRecord`] via EIP-868.
+    Enr(NodeRecord, Enr<SecretKey>),
     /// A new node was discovered _and_ added to the table.
     Added(NodeRecord),
     /// Node that was removed from the table
@@ -1849,7 +1925,7 @@ mod tests {
         test_utils::{create_discv4, create_discv4_with_config, rng_endpoint, rng_record},
     };
     use rand::{thread_rng, Rng};
-    use reth_primitives::{hex_literal::hex, ForkHash};
+    use reth_primitives::{hex_literal::hex, EnrForkIdEntry, ForkHash};
     use std::{future::poll_fn, net::Ipv4Addr};
 
     #[test]
@@ -1895,7 +1971,7 @@ mod tests {
         let config = Discv4Config::builder()
             .add_boot_nodes(all_nodes)
             .lookup_interval(Duration::from_secs(1))
-            .add_eip868_pair("eth", fork_id)
+            .add_eip868_pair("eth", EnrForkIdEntry::from(fork_id))
             .build();
         let (_discv4, mut service) = create_discv4_with_config(config).await;
 
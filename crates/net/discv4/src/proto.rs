@@ -3,7 +3,7 @@
 use crate::{error::DecodePacketError, PeerId, MAX_PACKET_SIZE, MIN_PACKET_SIZE};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use enr::Enr;
-use reth_primitives::{keccak256, ForkId, NodeRecord, H256};
+use reth_primitives::{keccak256, EnrForkIdEntry, ForkId, NodeRecord, H256};
 use reth_rlp::{Decodable, DecodeError, Encodable, Header};
 use reth_rlp_derive::{RlpDecodable, RlpEncodable};
 use secp256k1::{
@@ -225,10 +225,12 @@ pub struct EnrResponse {
 impl EnrResponse {
     /// Returns the [`ForkId`] if set
     ///
+    /// The `eth` entry is an [`EnrForkIdEntry`].
+    ///
     /// See also <https://github.com/ethereum/go-ethereum/blob/9244d5cd61f3ea5a7645fdf2a1a96d53421e412f/eth/protocols/eth/discovery.go#L36>
     pub fn eth_fork_id(&self) -> Option<ForkId> {
         let mut maybe_fork_id = self.enr.get(b"eth")?;
-        ForkId::decode(&mut maybe_fork_id).ok()
+        EnrForkIdEntry::decode(&mut maybe_fork_id).ok().map(Into::into)
     }
 }
 
@@ -622,6 +624,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_enr_response_fork_id() {
+        let mut rng = thread_rng();
+        let (secret_key, _) = SECP256K1.generate_keypair(&mut rng);
+        let fork_id = ForkId { hash: reth_primitives::ForkHash(rng.gen()), next: rng.gen() };
+
+        let mut buf = BytesMut::new();
+        EnrForkIdEntry::from(fork_id).encode(&mut buf);
+        let enr = enr::EnrBuilder::new("v4").add_value_rlp("eth", buf.freeze()).build(&secret_key);
+
+        let resp = EnrResponse { request_hash: H256::random(), enr: enr.unwrap() };
+        assert_eq!(resp.eth_fork_id(), Some(fork_id));
+    }
+
     #[test]
     fn decode_pong_packet() {
         let packet = "2ad84c37327a06c2522cf7bc039621da89f68907441b755935bb308dc4cd17d6fe550e90329ad6a516ca7db18e08900067928a0dfa3b5c75d55a42c984497373698d98616662c048983ea85895ea2da765eabeb15525478384e106337bfd8ed50002f3c9843ed8cae682fd1c80a008ad4dead0922211df47593e7d837b2b23d13954285871ca23250ea594993ded84635690e5829670";
@@ -8,7 +8,7 @@ use crate::{
     IngressReceiver, PeerId, SAFE_MAX_DATAGRAM_NEIGHBOUR_RECORDS,
 };
 use rand::{thread_rng, Rng, RngCore};
-use reth_primitives::{hex_literal::hex, ForkHash, ForkId, NodeRecord, H256};
+use reth_primitives::{hex_literal::hex, EnrForkIdEntry, ForkHash, ForkId, NodeRecord, H256};
 use secp256k1::{SecretKey, SECP256K1};
 use std::{
     collections::{HashMap, HashSet},
@@ -207,7 +207,10 @@ pub enum MockCommand {
 /// Creates a new testing instance for [`Discv4`] and its service
 pub async fn create_discv4() -> (Discv4, Discv4Service) {
     let fork_id = ForkId { hash: ForkHash(hex!("743f3d89")), next: 16191202 };
-    create_discv4_with_config(Discv4Config::builder().add_eip868_pair("eth", fork_id).build()).await
+    create_discv4_with_config(
+        Discv4Config::builder().add_eip868_pair("eth", EnrForkIdEntry::from(fork_id)).build(),
+    )
+    .await
 }
 
 /// Creates a new testing instance for [`Discv4`] and its service with the given config.
@@ -0,0 +1,120 @@
+//! A set of configuration parameters to tune the discovery v5 service.
+
+use crate::Enr;
+use reth_primitives::ForkId;
+use std::{
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::Duration,
+};
+
+/// The default port for discv5 via UDP.
+pub const DEFAULT_DISCOVERY_V5_PORT: u16 = 9000;
+
+/// Configuration parameters of the discovery v5 service.
+#[derive(Clone, Debug)]
+pub struct Discv5Config {
+    /// The configuration of the underlying discv5 protocol implementation.
+    pub discv5: discv5::Discv5Config,
+    /// The address of the UDP socket to listen on.
+    pub listen_addr: SocketAddr,
+    /// The TCP port of the RLPx listener to advertise in the local ENR.
+    pub tcp_port: u16,
+    /// The `eth` fork ID to advertise in the local ENR.
+    ///
+    /// See also <https://github.com/ethereum/devp2p/blob/master/enr-entries/eth.md>
+    pub fork_id: Option<ForkId>,
+    /// Nodes to boot from.
+    pub bootstrap_nodes: Vec<Enr>,
+    /// Whether to automatically lookup random nodes.
+    pub enable_lookup: bool,
+    /// The rate at which lookups should be triggered.
+    pub lookup_interval: Duration,
+    /// Size of the channel buffer for discovered nodes per update listener.
+    pub update_buffer: usize,
+}
+
+impl Discv5Config {
+    /// Returns a new default builder instance
+    pub fn builder() -> Discv5ConfigBuilder {
+        Default::default()
+    }
+}
+
+impl Default for Discv5Config {
+    fn default() -> Self {
+        Self {
+            discv5: discv5::Discv5ConfigBuilder::new().build(),
+            listen_addr: SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::UNSPECIFIED,
+                DEFAULT_DISCOVERY_V5_PORT,
+            )),
+            tcp_port: 30303,
+            fork_id: None,
+            bootstrap_nodes: Default::default(),
+            enable_lookup: true,
+            lookup_interval: Duration::from_secs(20),
+            update_buffer: 512,
+        }
+    }
+}
+
+/// Builder type for [`Discv5Config`]
+#[derive(Clone, Debug, Default)]
+pub struct Discv5ConfigBuilder {
+    config: Discv5Config,
+}
+
+impl Discv5ConfigBuilder {
+    /// Sets the configuration of the underlying discv5 protocol implementation.
+    pub fn discv5_config(&mut self, config: discv5::Discv5Config) -> &mut Self {
+        self.config.discv5 = config;
+        self
+    }
+
+    /// Sets the address of the UDP socket to listen on.
+    pub fn listen_addr(&mut self, listen_addr: SocketAddr) -> &mut Self {
+        self.config.listen_addr = listen_addr;
+        self
+    }
+
+    /// Sets the TCP port to advertise in the local ENR.
+    pub fn tcp_port(&mut self, tcp_port: u16) -> &mut Self {
+        self.config.tcp_port = tcp_port;
+        self
+    }
+
+    /// Sets the `eth` fork ID to advertise in the local ENR.
+    pub fn fork_id(&mut self, fork_id: ForkId) -> &mut Self {
+        self.config.fork_id = Some(fork_id);
+        self
+    }
+
+    /// Adds a boot node
+    pub fn add_boot_node(&mut self, node: Enr) -> &mut Self {
+        self.config.bootstrap_nodes.push(node);
+        self
+    }
+
+    /// Adds multiple boot nodes
+    pub fn add_boot_nodes(&mut self, nodes: impl IntoIterator<Item = Enr>) -> &mut Self {
+        self.config.bootstrap_nodes.extend(nodes);
+        self
+    }
+
+    /// Whether to automatically lookup
+    pub fn enable_lookup(&mut self, enable_lookup: bool) -> &mut Self {
+        self.config.enable_lookup = enable_lookup;
+        self
+    }
+
+    /// Sets the lookup interval duration.
+    pub fn lookup_interval(&mut self, lookup_interval: Duration) -> &mut Self {
+        self.config.lookup_interval = lookup_interval;
+        self
+    }
+
+    /// Returns the configured [`Discv5Config`]
+    pub fn build(&self) -> Discv5Config {
+        self.config.clone()
+    }
+}
@@ -0,0 +1,118 @@
+//! Helpers for the ENRs of discv5 nodes.
+
+use crate::{error::Discv5Error, Discv5Config, Enr};
+use discv5::enr::{CombinedKey, EnrBuilder, NodeId};
+use reth_primitives::{keccak256, EnrForkIdEntry, ForkId, NodeRecord, PeerId};
+use reth_rlp::{Decodable, Encodable};
+use secp256k1::{PublicKey, SecretKey};
+use std::net::IpAddr;
+
+/// The key of the `eth` entry in the ENR.
+pub const ETH_ENR_KEY: &str = "eth";
+
+/// Builds the local ENR and the corresponding signing key from the node's secret key.
+///
+/// The IP is only set if the configured listen address is not unspecified, otherwise it is
+/// discovered by discv5 via the socket votes of `PONG` responses.
+pub(crate) fn build_local_enr(
+    secret_key: &SecretKey,
+    config: &Discv5Config,
+) -> Result<(Enr, CombinedKey), Discv5Error> {
+    let mut secret = secret_key.secret_bytes();
+    let key = CombinedKey::secp256k1_from_bytes(&mut secret)
+        .map_err(|err| Discv5Error::Enr(err.to_string()))?;
+
+    let mut builder = EnrBuilder::new("v4");
+    let addr = config.listen_addr;
+    if !addr.ip().is_unspecified() {
+        builder.ip(addr.ip());
+    }
+    match addr.ip() {
+        IpAddr::V4(_) => {
+            builder.udp4(addr.port());
+            builder.tcp4(config.tcp_port);
+        }
+        IpAddr::V6(_) => {
+            builder.udp6(addr.port());
+            builder.tcp6(config.tcp_port);
+        }
+    }
+
+    if let Some(fork_id) = config.fork_id {
+        builder.add_value(ETH_ENR_KEY, &eth_entry(fork_id));
+    }
+
+    let enr = builder.build(&key).map_err(|err| Discv5Error::Enr(err.to_string()))?;
+    Ok((enr, key))
+}
+
+/// Returns the [`NodeRecord`] of the node, if the ENR contains an IP, a UDP and a TCP port.
+///
+/// Nodes without a TCP port can't be dialed via RLPx.
+pub fn enr_to_node_record(enr: &Enr) -> Option<NodeRecord> {
+    let (address, udp_port, tcp_port) = if let Some(ip) = enr.ip4() {
+        (IpAddr::V4(ip), enr.udp4()?, enr.tcp4()?)
+    } else {
+        (IpAddr::V6(enr.ip6()?), enr.udp6()?, enr.tcp6()?)
+    };
+    let pk = PublicKey::from_slice(&enr.public_key().encode()).ok()?;
+    let id = PeerId::from_slice(&pk.serialize_uncompressed()[1..]);
+    Some(NodeRecord { address, tcp_port, udp_port, id })
+}
+
+/// Returns the [`ForkId`] of the `eth` entry in the ENR, if any.
+///
+/// Only execution layer nodes advertise an `eth` entry.
+pub fn enr_fork_id(enr: &Enr) -> Option<ForkId> {
+    let mut entry = enr.get(ETH_ENR_KEY)?;
+    EnrForkIdEntry::decode(&mut entry).ok().map(Into::into)
+}
+
+/// Returns the value of the `eth` entry that advertises the given [`ForkId`].
+pub(crate) fn eth_entry(fork_id: ForkId) -> RawRlp {
+    let mut buf = Vec::new();
+    EnrForkIdEntry::from(fork_id).encode(&mut buf);
+    RawRlp(buf)
+}
+
+/// Returns the discv5 [`NodeId`] of the node with the given [`PeerId`].
+pub fn peer_id_to_node_id(peer_id: PeerId) -> NodeId {
+    NodeId::new(&keccak256(peer_id).0)
+}
+
+/// An ENR value that is already RLP encoded.
+pub(crate) struct RawRlp(Vec<u8>);
+
+impl rlp::Encodable for RawRlp {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append_raw(&self.0, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{hex_literal::hex, ForkHash};
+    use secp256k1::SECP256K1;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn test_local_enr() {
+        let (secret_key, pk) = SECP256K1.generate_keypair(&mut secp256k1::rand::thread_rng());
+        let fork_id = ForkId { hash: ForkHash(hex!("743f3d89")), next: 16191202 };
+        let config = Discv5Config::builder()
+            .listen_addr("127.0.0.1:9000".parse::<SocketAddr>().unwrap())
+            .tcp_port(30303)
+            .fork_id(fork_id)
+            .build();
+
+        let (enr, _) = build_local_enr(&secret_key, &config).unwrap();
+        assert_eq!(enr_fork_id(&enr), Some(fork_id));
+
+        let record = enr_to_node_record(&enr).unwrap();
+        assert_eq!(record.id, PeerId::from_slice(&pk.serialize_uncompressed()[1..]));
+        assert_eq!(record.udp_addr(), config.listen_addr);
+        assert_eq!(record.tcp_port, 30303);
+        assert_eq!(peer_id_to_node_id(record.id), enr.node_id());
+    }
+}
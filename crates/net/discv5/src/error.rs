@@ -0,0 +1,32 @@
+//! Error types that can occur in this crate.
+
+use tokio::sync::{mpsc::error::SendError, oneshot::error::RecvError};
+
+/// High level errors that can occur when interacting with the discovery service
+#[derive(Debug, thiserror::Error)]
+pub enum Discv5Error {
+    /// Failed to create the local ENR.
+    #[error("Failed to build local ENR: {0}")]
+    Enr(String),
+    /// Failed to start the discv5 service.
+    #[error("Failed to start discv5: {0}")]
+    Start(String),
+    /// A `FINDNODE` lookup failed.
+    #[error("Lookup failed: {0}")]
+    Lookup(String),
+    /// A `TALKREQ` request failed.
+    #[error("TALKREQ failed: {0}")]
+    TalkRequest(String),
+    /// Failed to send a command over the channel
+    #[error("Failed to send on a closed channel")]
+    Send,
+    /// Failed to receive a command response
+    #[error(transparent)]
+    Receive(#[from] RecvError),
+}
+
+impl<T> From<SendError<T>> for Discv5Error {
+    fn from(_: SendError<T>) -> Self {
+        Discv5Error::Send
+    }
+}
@@ -0,0 +1,435 @@
+#![warn(missing_docs, unused_crate_dependencies)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![doc(test(
+    no_crate_inject,
+    attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
+))]
+
+//! Discovery v5 support: <https://github.com/ethereum/devp2p/blob/master/discv5/discv5.md>
+//!
+//! The wire protocol, the `WHOAREYOU` handshake with its AES-GCM session keys, the kademlia table
+//! and recursive `FINDNODE` lookups are implemented by the [discv5](https://github.com/sigp/discv5)
+//! crate, which also provides the kademlia table of discv4.
+//!
+//! This crate consists of a [`Discv5`] and [`Discv5Service`] pair, similar to discv4. The service
+//! drives random lookups, routes incoming `TALKREQ` requests and converts the ENRs of discovered
+//! execution layer nodes into [`NodeRecord`]s. The [`Discv5`] frontend is used to interact with
+//! the service. Whenever a node is discovered, the service produces a [`Discv5Update`] that
+//! listeners will receive.
+use crate::error::Discv5Error;
+use discv5::{enr::NodeId, Discv5Event, QueryError};
+use futures::FutureExt;
+use reth_primitives::{ForkId, PeerId};
+use secp256k1::SecretKey;
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::IpAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::{
+    sync::{mpsc, mpsc::error::TrySendError, oneshot, oneshot::Sender as OneshotSender},
+    task::JoinHandle,
+    time::Interval,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, trace};
+
+pub mod error;
+
+mod config;
+pub use config::{Discv5Config, Discv5ConfigBuilder, DEFAULT_DISCOVERY_V5_PORT};
+
+mod enr;
+pub use crate::enr::{enr_fork_id, enr_to_node_record, peer_id_to_node_id, ETH_ENR_KEY};
+
+// reexport the discv5 types used in the API
+pub use discv5::{Enr, TalkRequest};
+pub use reth_primitives::NodeRecord;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
+/// The future of a recursive `FINDNODE` lookup.
+type LookupFuture = Pin<Box<dyn Future<Output = Result<Vec<Enr>, QueryError>> + Send>>;
+
+/// The Discv5 frontend
+#[derive(Clone)]
+pub struct Discv5 {
+    /// The underlying discv5 protocol implementation.
+    discv5: Arc<discv5::Discv5>,
+    /// channel to send commands over to the service
+    to_service: mpsc::Sender<Discv5Command>,
+}
+
+// === impl Discv5 ===
+
+impl Discv5 {
+    /// Starts the discv5 protocol on the configured address and returns the frontend and the
+    /// service that still needs to be spawned, see [`Discv5Service::spawn`].
+    ///
+    /// The local ENR is derived from the given secret key, so the node has the same identity in
+    /// discv4 and discv5.
+    pub async fn bind(
+        secret_key: SecretKey,
+        config: Discv5Config,
+    ) -> Result<(Self, Discv5Service), Discv5Error> {
+        let (local_enr, enr_key) = crate::enr::build_local_enr(&secret_key, &config)?;
+
+        let mut discv5 = discv5::Discv5::new(local_enr, enr_key, config.discv5.clone())
+            .map_err(|err| Discv5Error::Start(err.to_string()))?;
+
+        for node in config.bootstrap_nodes.iter().cloned() {
+            if let Err(err) = discv5.add_enr(node) {
+                debug!(target : "discv5", %err, "failed to add boot node");
+            }
+        }
+
+        discv5
+            .start(config.listen_addr)
+            .await
+            .map_err(|err| Discv5Error::Start(format!("{err:?}")))?;
+        trace!(target : "discv5", local_addr=?config.listen_addr, "started discv5");
+
+        let events =
+            discv5.event_stream().await.map_err(|err| Discv5Error::Start(format!("{err:?}")))?;
+
+        let discv5 = Arc::new(discv5);
+        let (to_service, commands_rx) = mpsc::channel(100);
+        let service = Discv5Service::new(Arc::clone(&discv5), events, commands_rx, config);
+        Ok((Self { discv5, to_service }, service))
+    }
+
+    /// Same as [`Self::bind`] but also spawns the service onto a new task.
+    pub async fn spawn(secret_key: SecretKey, config: Discv5Config) -> Result<Self, Discv5Error> {
+        let (discv5, service) = Self::bind(secret_key, config).await?;
+        service.spawn();
+        Ok(discv5)
+    }
+
+    /// Returns the current local ENR of the node.
+    pub fn local_enr(&self) -> Enr {
+        self.discv5.local_enr()
+    }
+
+    /// Updates the `eth` entry of the local ENR to advertise the given [`ForkId`].
+    pub fn set_fork_id(&self, fork_id: ForkId) {
+        if let Err(err) = self.discv5.enr_insert(ETH_ENR_KEY, &crate::enr::eth_entry(fork_id)) {
+            debug!(target : "discv5", ?err, "failed to update the fork id of the local ENR");
+        }
+    }
+
+    /// Adds the node to the table, if it's not already present.
+    pub fn add_node(&self, enr: Enr) {
+        if let Err(err) = self.discv5.add_enr(enr) {
+            debug!(target : "discv5", %err, "failed to add node");
+        }
+    }
+
+    /// Adds the peer and ip to the ban list.
+    ///
+    /// This will prevent any future inclusion in the table
+    pub fn ban(&self, peer_id: PeerId, ip: IpAddr) {
+        self.discv5.ban_node(&peer_id_to_node_id(peer_id), None);
+        self.discv5.ban_ip(ip, None);
+    }
+
+    /// Adds the ip to the ban list.
+    ///
+    /// This will prevent any future inclusion in the table
+    pub fn ban_ip(&self, ip: IpAddr) {
+        self.discv5.ban_ip(ip, None);
+    }
+
+    /// Performs a recursive `FINDNODE` lookup for the closest nodes to the given target.
+    ///
+    /// Returns the ENRs of all nodes that were found, including nodes that are not part of the
+    /// execution layer.
+    pub async fn lookup(&self, target: NodeId) -> Result<Vec<Enr>, Discv5Error> {
+        self.discv5.find_node(target).await.map_err(|err| Discv5Error::Lookup(format!("{err:?}")))
+    }
+
+    /// Sends a `TALKREQ` for the given protocol to the node and returns the response.
+    pub async fn talk_req(
+        &self,
+        node: Enr,
+        protocol: impl Into<Vec<u8>>,
+        request: Vec<u8>,
+    ) -> Result<Vec<u8>, Discv5Error> {
+        self.discv5
+            .talk_req(node, protocol.into(), request)
+            .await
+            .map_err(|err| Discv5Error::TalkRequest(format!("{err:?}")))
+    }
+
+    /// Returns a stream of all incoming `TALKREQ` requests for the given protocol.
+    ///
+    /// Every request must be answered via [`TalkRequest::respond`]. Requests for protocols
+    /// without a listener are answered with an empty response.
+    pub async fn talk_requests(
+        &self,
+        protocol: impl Into<Vec<u8>>,
+    ) -> Result<ReceiverStream<TalkRequest>, Discv5Error> {
+        let (tx, rx) = oneshot::channel();
+        let cmd = Discv5Command::TalkRequests { protocol: protocol.into(), tx };
+        self.to_service.send(cmd).await?;
+        Ok(rx.await?)
+    }
+
+    /// Returns the receiver half of new listener channel that streams [`Discv5Update`]s.
+    pub async fn update_stream(&self) -> Result<ReceiverStream<Discv5Update>, Discv5Error> {
+        let (tx, rx) = oneshot::channel();
+        let cmd = Discv5Command::Updates(tx);
+        self.to_service.send(cmd).await?;
+        Ok(rx.await?)
+    }
+}
+
+/// Drives random lookups and dispatches the events of the discv5 protocol.
+#[must_use = "Service does nothing unless polled"]
+pub struct Discv5Service {
+    /// The underlying discv5 protocol implementation.
+    discv5: Arc<discv5::Discv5>,
+    /// Events emitted by the discv5 protocol.
+    events: mpsc::Receiver<Discv5Event>,
+    /// Commands sent by the [`Discv5`] frontend.
+    commands_rx: mpsc::Receiver<Discv5Command>,
+    /// All subscribers for table updates
+    update_listeners: Vec<mpsc::Sender<Discv5Update>>,
+    /// Listeners for incoming `TALKREQ` requests by protocol.
+    talk_listeners: HashMap<Vec<u8>, mpsc::Sender<TalkRequest>>,
+    /// The interval at which to trigger random lookups.
+    lookup_interval: Option<Interval>,
+    /// The currently active random lookup.
+    pending_lookup: Option<LookupFuture>,
+    /// The configuration of the service.
+    config: Discv5Config,
+}
+
+// === impl Discv5Service ===
+
+impl Discv5Service {
+    fn new(
+        discv5: Arc<discv5::Discv5>,
+        events: mpsc::Receiver<Discv5Event>,
+        commands_rx: mpsc::Receiver<Discv5Command>,
+        config: Discv5Config,
+    ) -> Self {
+        let lookup_interval = config
+            .enable_lookup
+            .then(|| tokio::time::interval_at(tokio::time::Instant::now(), config.lookup_interval));
+        Self {
+            discv5,
+            events,
+            commands_rx,
+            update_listeners: Default::default(),
+            talk_listeners: Default::default(),
+            lookup_interval,
+            pending_lookup: None,
+            config,
+        }
+    }
+
+    /// Spawns this service onto a new task
+    ///
+    /// Note: requires a running runtime
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::task::spawn(self)
+    }
+
+    /// Creates a new channel for [`Discv5Update`]s
+    pub fn update_stream(&mut self) -> ReceiverStream<Discv5Update> {
+        let (tx, rx) = mpsc::channel(self.config.update_buffer);
+        self.update_listeners.push(tx);
+        ReceiverStream::new(rx)
+    }
+
+    /// Starts a lookup for a random target, unless a lookup is already in progress.
+    fn lookup_random(&mut self) {
+        if self.pending_lookup.is_some() {
+            return
+        }
+        let target = NodeId::random();
+        trace!(target : "discv5", ?target, "starting lookup");
+        let discv5 = Arc::clone(&self.discv5);
+        self.pending_lookup = Some(Box::pin(async move { discv5.find_node(target).await }));
+    }
+
+    /// Notifies all listeners about the node, if it's a node of the execution layer.
+    fn on_discovered(&mut self, enr: &Enr) {
+        let Some(fork_id) = enr_fork_id(enr) else { return };
+        let Some(record) = enr_to_node_record(enr) else { return };
+        self.notify(Discv5Update::Added(record, fork_id));
+    }
+
+    /// Routes the request to the listener of its protocol.
+    fn on_talk_request(&mut self, request: TalkRequest) {
+        let request = match self.talk_listeners.get(request.protocol()) {
+            Some(listener) => match listener.try_send(request) {
+                Ok(()) => return,
+                Err(TrySendError::Full(request)) => request,
+                Err(TrySendError::Closed(request)) => {
+                    self.talk_listeners.remove(request.protocol());
+                    request
+                }
+            },
+            None => request,
+        };
+        // unknown protocols are answered with an empty response
+        let _ = request.respond(Vec::new());
+    }
+
+    fn on_command(&mut self, cmd: Discv5Command) {
+        match cmd {
+            Discv5Command::Updates(tx) => {
+                let rx = self.update_stream();
+                let _ = tx.send(rx);
+            }
+            Discv5Command::TalkRequests { protocol, tx } => {
+                let (listener, rx) = mpsc::channel(self.config.update_buffer);
+                self.talk_listeners.insert(protocol, listener);
+                let _ = tx.send(ReceiverStream::new(rx));
+            }
+        }
+    }
+
+    fn notify(&mut self, update: Discv5Update) {
+        self.update_listeners.retain_mut(|listener| match listener.try_send(update.clone()) {
+            Ok(()) => true,
+            Err(err) => match err {
+                TrySendError::Full(_) => true,
+                TrySendError::Closed(_) => false,
+            },
+        });
+    }
+}
+
+impl Future for Discv5Service {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        // drain commands
+        while let Poll::Ready(Some(cmd)) = this.commands_rx.poll_recv(cx) {
+            this.on_command(cmd);
+        }
+
+        // drain events of the protocol
+        loop {
+            match this.events.poll_recv(cx) {
+                Poll::Ready(Some(event)) => match event {
+                    Discv5Event::Discovered(enr) => this.on_discovered(&enr),
+                    Discv5Event::SessionEstablished(enr, _) => this.on_discovered(&enr),
+                    Discv5Event::TalkRequest(request) => this.on_talk_request(request),
+                    _ => {}
+                },
+                // the protocol was shut down
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Pending => break,
+            }
+        }
+
+        // trigger random lookups
+        if let Some(interval) = this.lookup_interval.as_mut() {
+            while interval.poll_tick(cx).is_ready() {
+                this.lookup_random();
+            }
+        }
+
+        if let Some(mut lookup) = this.pending_lookup.take() {
+            match lookup.poll_unpin(cx) {
+                Poll::Ready(Ok(nodes)) => {
+                    trace!(target : "discv5", num=nodes.len(), "lookup finished");
+                    for enr in nodes {
+                        this.on_discovered(&enr);
+                    }
+                }
+                Poll::Ready(Err(err)) => {
+                    debug!(target : "discv5", ?err, "lookup failed");
+                }
+                Poll::Pending => this.pending_lookup = Some(lookup),
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Commands sent from the [`Discv5`] frontend to the [`Discv5Service`]
+enum Discv5Command {
+    Updates(OneshotSender<ReceiverStream<Discv5Update>>),
+    TalkRequests { protocol: Vec<u8>, tx: OneshotSender<ReceiverStream<TalkRequest>> },
+}
+
+/// Represents a discovered execution layer node.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Discv5Update {
+    /// A node that advertises an `eth` entry was discovered, with the [`ForkId`] of the entry.
+    Added(NodeRecord, ForkId),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{create_discv5, create_discv5_with_config};
+    use tokio_stream::StreamExt;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_discover_node() {
+        reth_tracing::init_test_tracing();
+
+        let (node_1, service_1) = create_discv5().await;
+        let (node_2, service_2) = create_discv5().await;
+        service_1.spawn();
+        service_2.spawn();
+
+        let mut updates = node_1.update_stream().await.unwrap();
+        node_1.add_node(node_2.local_enr());
+        let _ = node_1.lookup(NodeId::random()).await;
+
+        let Discv5Update::Added(record, fork_id) = updates.next().await.unwrap();
+        assert_eq!(Some(record), enr_to_node_record(&node_2.local_enr()));
+        assert_eq!(Some(fork_id), enr_fork_id(&node_2.local_enr()));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_set_fork_id() {
+        let (node, _service) = create_discv5().await;
+        let seq = node.local_enr().seq();
+
+        let fork_id = ForkId { hash: reth_primitives::ForkHash([1, 2, 3, 4]), next: 42 };
+        node.set_fork_id(fork_id);
+        assert_eq!(enr_fork_id(&node.local_enr()), Some(fork_id));
+        assert!(node.local_enr().seq() > seq);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_talk_request() {
+        reth_tracing::init_test_tracing();
+
+        let config = Discv5Config::builder().enable_lookup(false).build();
+        let (node_1, service_1) = create_discv5_with_config(config.clone()).await;
+        let (node_2, service_2) = create_discv5_with_config(config).await;
+        service_1.spawn();
+        service_2.spawn();
+
+        let mut requests = node_2.talk_requests("test").await.unwrap();
+        tokio::spawn(async move {
+            while let Some(request) = requests.next().await {
+                let response = request.body().to_vec();
+                let _ = request.respond(response);
+            }
+        });
+
+        let response = node_1.talk_req(node_2.local_enr(), "test", b"ping".to_vec()).await.unwrap();
+        assert_eq!(response, b"ping".to_vec());
+
+        // no listener for the protocol
+        let response =
+            node_1.talk_req(node_2.local_enr(), "other", b"ping".to_vec()).await.unwrap();
+        assert!(response.is_empty());
+    }
+}
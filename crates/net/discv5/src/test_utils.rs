@@ -0,0 +1,31 @@
+//! Utilities for testing several discv5 nodes in process.
+
+use crate::{Discv5, Discv5Config, Discv5Service};
+use reth_primitives::{hex_literal::hex, ForkHash, ForkId};
+use secp256k1::{rand::thread_rng, SECP256K1};
+use std::net::{SocketAddr, UdpSocket};
+
+/// Creates a new testing instance for [`Discv5`] and its service that listens on a free local
+/// port.
+pub async fn create_discv5() -> (Discv5, Discv5Service) {
+    create_discv5_with_config(Discv5Config::default()).await
+}
+
+/// Creates a new testing instance for [`Discv5`] and its service with the given config.
+///
+/// The listen address and the `eth` fork ID of the config are replaced.
+pub async fn create_discv5_with_config(mut config: Discv5Config) -> (Discv5, Discv5Service) {
+    let (secret_key, _) = SECP256K1.generate_keypair(&mut thread_rng());
+    config.listen_addr = unused_local_addr();
+    config.tcp_port = config.listen_addr.port();
+    config.fork_id = Some(ForkId { hash: ForkHash(hex!("743f3d89")), next: 16191202 });
+    Discv5::bind(secret_key, config).await.unwrap()
+}
+
+/// Returns a local address with a port that is currently not in use.
+///
+/// The ENR must contain the actual port, so the port can't be assigned by the OS on bind.
+fn unused_local_addr() -> SocketAddr {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.local_addr().unwrap()
+}
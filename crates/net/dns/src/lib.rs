@@ -17,7 +17,7 @@ pub use config::DnsDiscoveryConfig;
 use enr::Enr;
 use error::ParseDnsEntryError;
 use lru::LruCache;
-use reth_primitives::{ForkId, NodeRecord, PeerId};
+use reth_primitives::{EnrForkIdEntry, ForkId, NodeRecord, PeerId};
 use secp256k1::SecretKey;
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
@@ -44,6 +44,7 @@ use tracing::{debug, trace, warn};
 
 mod config;
 mod error;
+pub mod publish;
 mod query;
 pub mod resolver;
 mod sync;
@@ -363,6 +364,8 @@ pub struct DnsNodeRecordUpdate {
     pub node_record: NodeRecord,
     /// The forkid of the node, if present in the ENR
     pub fork_id: Option<ForkId>,
+    /// The resolved [Enr] of the node
+    pub enr: Enr<SecretKey>,
 }
 
 /// Commands sent from [DnsDiscoveryHandle] to [DnsDiscoveryService]
@@ -392,9 +395,9 @@ fn convert_enr_node_record(enr: &Enr<SecretKey>) -> Option<DnsNodeRecordUpdate>
     .into_ipv4_mapped();
 
     let mut maybe_fork_id = enr.get(b"eth")?;
-    let fork_id = ForkId::decode(&mut maybe_fork_id).ok();
+    let fork_id = EnrForkIdEntry::decode(&mut maybe_fork_id).ok().map(Into::into);
 
-    Some(DnsNodeRecordUpdate { node_record, fork_id })
+    Some(DnsNodeRecordUpdate { node_record, fork_id, enr: enr.clone() })
 }
 
 #[cfg(test)]
@@ -453,7 +456,7 @@ mod tests {
         let mut builder = EnrBuilder::new("v4");
         let mut buf = Vec::new();
         let fork_id = Hardfork::Frontier.fork_id(&MAINNET).unwrap();
-        fork_id.encode(&mut buf);
+        EnrForkIdEntry::from(fork_id).encode(&mut buf);
         builder.ip4(Ipv4Addr::LOCALHOST).udp4(30303).tcp4(30303).add_value(b"eth", &buf);
         let enr = builder.build(&secret_key).unwrap();
 
@@ -0,0 +1,317 @@
+//! Build and sign [EIP-1459](https://eips.ethereum.org/EIPS/eip-1459) ENR trees.
+//!
+//! This is the publishing side of DNS discovery: a [DnsTree] is created from a set of node
+//! records and links to other trees, signed with the tree's key and then exported as a map of TXT
+//! records or as a zone file that can be served by any DNS provider.
+//!
+//! Every entry of the tree is stored at the subdomain that is derived from its content: the
+//! base32 (no padding) encoding of the first 16 bytes of the `keccak256` hash of the entry's text.
+//! Nodes and links are placed in the leaves of two separate subtrees, intermediate nodes are
+//! `enrtree-branch` entries with at most [MAX_CHILDREN] hashes each so that they fit into a single
+//! DNS message.
+
+use crate::{
+    resolver::MapResolver,
+    tree::{BranchEntry, DnsEntry, LinkEntry, NodeEntry, TreeRootEntry},
+};
+use bytes::Bytes;
+use data_encoding::BASE32_NOPAD;
+use enr::{Enr, EnrError, EnrKey, EnrKeyUnambiguous};
+use reth_primitives::keccak256;
+use secp256k1::SecretKey;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// The maximum number of children of a branch entry.
+///
+/// A branch with this many hashes still fits the 370 byte TXT record size that keeps a DNS
+/// response within a single UDP packet.
+pub const MAX_CHILDREN: usize = 13;
+
+/// Number of hash bytes that make up the subdomain of an entry.
+const HASH_ABBREV_SIZE: usize = 16;
+
+/// Maximum length of a single character-string in a TXT record.
+const MAX_TXT_STRING_LEN: usize = 255;
+
+/// Returns the subdomain of the entry.
+///
+/// This is the base32 encoding of the abbreviated `keccak256` hash of the entry's text.
+pub fn subdomain<K: EnrKeyUnambiguous>(entry: &DnsEntry<K>) -> String {
+    let hash = keccak256(entry.to_string().as_bytes());
+    BASE32_NOPAD.encode(&hash[..HASH_ABBREV_SIZE])
+}
+
+/// An EIP-1459 merkle tree of node records and links.
+///
+/// The tree is unsigned after creation, see [DnsTree::sign].
+#[derive(Debug, Clone)]
+pub struct DnsTree<K: EnrKeyUnambiguous = SecretKey> {
+    /// The root entry of the tree.
+    root: TreeRootEntry,
+    /// All entries of the tree, except the root, by subdomain.
+    entries: BTreeMap<String, DnsEntry<K>>,
+}
+
+// === impl DnsTree ===
+
+impl<K: EnrKeyUnambiguous> DnsTree<K> {
+    /// Creates a new unsigned tree with the given sequence number from the node records and links.
+    ///
+    /// Records are deduplicated by their node id, keeping the record with the highest sequence
+    /// number. Nodes and links are sorted so that the same input always results in the same tree.
+    pub fn new(
+        sequence_number: u64,
+        nodes: impl IntoIterator<Item = Enr<K>>,
+        links: impl IntoIterator<Item = LinkEntry<K>>,
+    ) -> Self {
+        let mut records = BTreeMap::new();
+        for enr in nodes {
+            let id = enr.node_id().raw();
+            match records.get(&id) {
+                Some(existing) if existing.seq() >= enr.seq() => {}
+                _ => {
+                    records.insert(id, enr);
+                }
+            }
+        }
+        let nodes = records.into_values().map(|enr| DnsEntry::Node(NodeEntry { enr })).collect();
+
+        let mut links = links.into_iter().map(DnsEntry::Link).collect::<Vec<_>>();
+        links.sort_by_cached_key(|link| link.to_string());
+        links.dedup_by(|a, b| a.to_string() == b.to_string());
+
+        let mut tree = Self {
+            root: TreeRootEntry {
+                enr_root: Default::default(),
+                link_root: Default::default(),
+                sequence_number,
+                signature: Bytes::new(),
+            },
+            entries: Default::default(),
+        };
+
+        let enr_root = tree.build(nodes);
+        tree.root.enr_root = tree.insert(enr_root);
+        let link_root = tree.build(links);
+        tree.root.link_root = tree.insert(link_root);
+
+        tree
+    }
+
+    /// Builds the subtree for the given leaf entries and returns its root.
+    ///
+    /// All entries below the returned root are inserted into the tree.
+    fn build(&mut self, mut entries: Vec<DnsEntry<K>>) -> DnsEntry<K> {
+        if entries.len() == 1 {
+            return entries.pop().expect("exists")
+        }
+        if entries.len() <= MAX_CHILDREN {
+            let children = entries.into_iter().map(|entry| self.insert(entry)).collect();
+            return DnsEntry::Branch(BranchEntry { children })
+        }
+
+        let mut subtrees = Vec::with_capacity(entries.len() / MAX_CHILDREN + 1);
+        while !entries.is_empty() {
+            let rest = entries.split_off(entries.len().min(MAX_CHILDREN));
+            subtrees.push(self.build(entries));
+            entries = rest;
+        }
+        self.build(subtrees)
+    }
+
+    /// Inserts the entry at its subdomain and returns the subdomain.
+    fn insert(&mut self, entry: DnsEntry<K>) -> String {
+        let hash = subdomain(&entry);
+        self.entries.insert(hash.clone(), entry);
+        hash
+    }
+
+    /// Returns the root entry of the tree.
+    pub fn root(&self) -> &TreeRootEntry {
+        &self.root
+    }
+
+    /// Returns the sequence number of the tree.
+    pub fn sequence_number(&self) -> u64 {
+        self.root.sequence_number
+    }
+
+    /// Returns all entries of the tree, except the root, by subdomain.
+    pub fn entries(&self) -> &BTreeMap<String, DnsEntry<K>> {
+        &self.entries
+    }
+
+    /// Returns an iterator over all node records of the tree.
+    pub fn nodes(&self) -> impl Iterator<Item = &Enr<K>> + '_ {
+        self.entries.values().filter_map(|entry| match entry {
+            DnsEntry::Node(node) => Some(&node.enr),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over all links of the tree.
+    pub fn links(&self) -> impl Iterator<Item = &LinkEntry<K>> + '_ {
+        self.entries.values().filter_map(|entry| match entry {
+            DnsEntry::Link(link) => Some(link),
+            _ => None,
+        })
+    }
+
+    /// Signs the root of the tree with the given key.
+    ///
+    /// The public key of `key` is the one that must be used in the `enrtree://` link to the tree.
+    pub fn sign<S: EnrKey>(&mut self, key: &S) -> Result<(), EnrError> {
+        self.root.sign(key)
+    }
+
+    /// Returns the TXT records of the tree, by fully qualified name under `domain`.
+    ///
+    /// The root is stored at `domain` itself, all other entries at `<subdomain>.<domain>`.
+    pub fn to_txt_records(&self, domain: &str) -> BTreeMap<String, String> {
+        let mut records = BTreeMap::new();
+        records.insert(domain.to_string(), self.root.to_string());
+        for (hash, entry) in self.entries.iter() {
+            records.insert(format!("{hash}.{domain}"), entry.to_string());
+        }
+        records
+    }
+
+    /// Returns the records of the tree as a DNS zone file for `domain`.
+    ///
+    /// Records that exceed the length of a single TXT character-string are split into multiple
+    /// strings.
+    pub fn to_zone_file(&self, domain: &str, ttl: u32) -> String {
+        let domain = domain.trim_end_matches('.');
+        let mut zone = format!(
+            "; EIP-1459 ENR tree for {domain}, seq={}\n$ORIGIN {domain}.\n",
+            self.sequence_number()
+        );
+        let mut write_record = |name: &str, txt: &str| {
+            let strings = txt
+                .as_bytes()
+                .chunks(MAX_TXT_STRING_LEN)
+                .map(|chunk| format!("\"{}\"", String::from_utf8_lossy(chunk)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = writeln!(zone, "{name:<27} {ttl} IN TXT {strings}");
+        };
+
+        write_record("@", &self.root.to_string());
+        for (hash, entry) in self.entries.iter() {
+            write_record(hash, &entry.to_string());
+        }
+        zone
+    }
+
+    /// Inserts all TXT records of the tree under `domain` into the [MapResolver].
+    ///
+    /// This can be used to verify a tree with a
+    /// [DnsDiscoveryService](crate::DnsDiscoveryService) before it is published.
+    pub fn insert_into(&self, resolver: &MapResolver, domain: &str) {
+        for (name, txt) in self.to_txt_records(domain) {
+            resolver.insert(name, txt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DnsDiscoveryEvent, DnsDiscoveryService};
+    use enr::EnrBuilder;
+    use secp256k1::rand::thread_rng;
+    use std::{collections::HashSet, net::Ipv4Addr, sync::Arc};
+    use tokio_stream::StreamExt;
+
+    fn rng_enr(port: u16) -> Enr<SecretKey> {
+        let secret_key = SecretKey::new(&mut thread_rng());
+        EnrBuilder::new("v4")
+            .ip4(Ipv4Addr::LOCALHOST)
+            .udp4(port)
+            .tcp4(port)
+            .build(&secret_key)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let tree = DnsTree::<SecretKey>::new(1, vec![], vec![]);
+        let empty = DnsEntry::<SecretKey>::Branch(BranchEntry { children: vec![] });
+        assert_eq!(tree.root().enr_root, subdomain(&empty));
+        assert_eq!(tree.root().link_root, subdomain(&empty));
+        assert_eq!(tree.entries().len(), 1);
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let nodes = (0..100).map(|i| rng_enr(30303 + i)).collect::<Vec<_>>();
+        let secret_key = SecretKey::new(&mut thread_rng());
+        let link: LinkEntry = LinkEntry {
+            domain: "morenodes.example.org".to_string(),
+            pubkey: SecretKey::new(&mut thread_rng()).public(),
+        };
+
+        let mut tree = DnsTree::new(7, nodes.clone(), vec![link.clone()]);
+        tree.sign(&secret_key).unwrap();
+        assert!(tree.root().verify::<SecretKey>(&secret_key.public()));
+
+        assert_eq!(tree.nodes().count(), nodes.len());
+        assert_eq!(tree.links().cloned().collect::<Vec<_>>(), vec![link]);
+
+        for (hash, entry) in tree.entries() {
+            // every entry is stored at the hash of its text and parses back to the same entry
+            let txt = entry.to_string();
+            let parsed: DnsEntry<SecretKey> = txt.parse().unwrap();
+            assert_eq!(&subdomain(&parsed), hash);
+            if let DnsEntry::Branch(branch) = entry {
+                assert!(branch.children.len() <= MAX_CHILDREN);
+                assert!(branch.children.iter().all(|child| tree.entries().contains_key(child)));
+            }
+        }
+
+        let records = tree.to_txt_records("nodes.example.org");
+        assert_eq!(records["nodes.example.org"], tree.root().to_string());
+        assert_eq!(records.len(), tree.entries().len() + 1);
+
+        let zone = tree.to_zone_file("nodes.example.org", 300);
+        assert_eq!(zone.lines().filter(|line| line.contains(" IN TXT ")).count(), records.len());
+    }
+
+    #[test]
+    fn test_dedup_nodes() {
+        let secret_key = SecretKey::new(&mut thread_rng());
+        let mut enr = EnrBuilder::new("v4").ip4(Ipv4Addr::LOCALHOST).build(&secret_key).unwrap();
+        let old = enr.clone();
+        enr.set_tcp4(30303, &secret_key).unwrap();
+
+        let tree = DnsTree::new(1, vec![enr.clone(), old], vec![]);
+        assert_eq!(tree.nodes().cloned().collect::<Vec<_>>(), vec![enr]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sync_published_tree() {
+        reth_tracing::init_test_tracing();
+
+        let nodes = (0..40).map(|i| rng_enr(30303 + i)).collect::<Vec<_>>();
+        let secret_key = SecretKey::new(&mut thread_rng());
+        let mut tree = DnsTree::new(1, nodes.clone(), vec![]);
+        tree.sign(&secret_key).unwrap();
+
+        let link =
+            LinkEntry { domain: "nodes.example.org".to_string(), pubkey: secret_key.public() };
+        let resolver = MapResolver::default();
+        tree.insert_into(&resolver, &link.domain);
+
+        let mut service = DnsDiscoveryService::new(Arc::new(resolver), Default::default());
+        service.sync_tree_with_link(link);
+
+        let mut expected = nodes.into_iter().map(|enr| enr.to_base64()).collect::<HashSet<_>>();
+        while !expected.is_empty() {
+            match service.next().await.unwrap() {
+                DnsDiscoveryEvent::Enr(enr) => {
+                    assert!(expected.remove(&enr.to_base64()));
+                }
+            }
+        }
+    }
+}
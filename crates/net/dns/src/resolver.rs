@@ -30,9 +30,11 @@ where
                 None
             }
             Ok(lookup) => {
+                // entries that exceed the maximum length of a character-string are split into
+                // multiple strings of the same record
                 let txt = lookup.into_iter().next()?;
-                let entry = txt.iter().next()?;
-                String::from_utf8(entry.to_vec()).ok()
+                let entry = txt.iter().flat_map(|s| s.iter().copied()).collect::<Vec<_>>();
+                String::from_utf8(entry).ok()
             }
         }
     }
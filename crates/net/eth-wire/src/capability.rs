@@ -64,6 +64,12 @@ impl Capability {
     pub fn is_eth_v67(&self) -> bool {
         self.name == "eth" && self.version == 67
     }
+
+    /// Whether this is eth v68.
+    #[inline]
+    pub fn is_eth_v68(&self) -> bool {
+        self.name == "eth" && self.version == 68
+    }
 }
 
 #[cfg(any(test, feature = "arbitrary"))]
@@ -90,6 +96,36 @@ impl proptest::arbitrary::Arbitrary for Capability {
     }
 }
 
+/// A sub-protocol that can be multiplexed over an RLPx connection, alongside `eth`.
+///
+/// Message ids are assigned to shared capabilities in alphabetical order, so every sub-protocol
+/// must know how many message ids it reserves.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Protocol {
+    /// The capability the protocol is announced with in the `Hello` message.
+    pub cap: Capability,
+    /// The number of message ids the protocol reserves.
+    pub messages: u8,
+}
+
+impl Protocol {
+    /// Create a new protocol with the given capability and number of messages.
+    pub fn new(cap: Capability, messages: u8) -> Self {
+        Self { cap, messages }
+    }
+
+    /// The `eth` protocol of the given version.
+    pub fn eth(version: EthVersion) -> Self {
+        Self { cap: version.into(), messages: version.total_messages() }
+    }
+}
+
+impl From<EthVersion> for Protocol {
+    fn from(version: EthVersion) -> Self {
+        Protocol::eth(version)
+    }
+}
+
 /// Represents all capabilities of a node.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Capabilities {
@@ -97,6 +133,7 @@ pub struct Capabilities {
     inner: Vec<Capability>,
     eth_66: bool,
     eth_67: bool,
+    eth_68: bool,
 }
 
 impl Capabilities {
@@ -115,7 +152,7 @@ impl Capabilities {
     /// Whether the peer supports `eth` sub-protocol.
     #[inline]
     pub fn supports_eth(&self) -> bool {
-        self.eth_67 || self.eth_66
+        self.eth_68 || self.eth_67 || self.eth_66
     }
 
     /// Whether this peer supports eth v66 protocol.
@@ -129,6 +166,12 @@ impl Capabilities {
     pub fn supports_eth_v67(&self) -> bool {
         self.eth_67
     }
+
+    /// Whether this peer supports eth v68 protocol.
+    #[inline]
+    pub fn supports_eth_v68(&self) -> bool {
+        self.eth_68
+    }
 }
 
 impl From<Vec<Capability>> for Capabilities {
@@ -136,6 +179,7 @@ impl From<Vec<Capability>> for Capabilities {
         Self {
             eth_66: value.iter().any(Capability::is_eth_v66),
             eth_67: value.iter().any(Capability::is_eth_v67),
+            eth_68: value.iter().any(Capability::is_eth_v68),
             inner: value,
         }
     }
@@ -154,6 +198,7 @@ impl Decodable for Capabilities {
         Ok(Self {
             eth_66: inner.iter().any(Capability::is_eth_v66),
             eth_67: inner.iter().any(Capability::is_eth_v67),
+            eth_68: inner.iter().any(Capability::is_eth_v68),
             inner,
         })
     }
@@ -166,19 +211,31 @@ pub enum SharedCapability {
     /// The `eth` capability.
     Eth { version: EthVersion, offset: u8 },
 
-    /// An unknown capability.
-    UnknownCapability { name: SmolStr, version: u8, offset: u8 },
+    /// A capability that is not handled by the `eth` stream, like a registered sub-protocol.
+    UnknownCapability { name: SmolStr, version: u8, offset: u8, messages: u8 },
 }
 
 impl SharedCapability {
-    /// Creates a new [`SharedCapability`] based on the given name, offset, and version.
-    pub(crate) fn new(name: &str, version: u8, offset: u8) -> Result<Self, SharedCapabilityError> {
-        match name {
+    /// Creates a new [`SharedCapability`] based on the given protocol and offset.
+    pub(crate) fn new(protocol: &Protocol, offset: u8) -> Result<Self, SharedCapabilityError> {
+        let Protocol { cap, messages } = protocol;
+        let version = cap.version as u8;
+        match cap.name.as_str() {
             "eth" => Ok(Self::Eth { version: EthVersion::try_from(version)?, offset }),
-            _ => Ok(Self::UnknownCapability { name: name.into(), version, offset }),
+            name => Ok(Self::UnknownCapability {
+                name: name.into(),
+                version,
+                offset,
+                messages: *messages,
+            }),
         }
     }
 
+    /// Whether this is the `eth` capability.
+    pub fn is_eth(&self) -> bool {
+        matches!(self, SharedCapability::Eth { .. })
+    }
+
     /// Returns the name of the capability.
     pub fn name(&self) -> &str {
         match self {
@@ -204,12 +261,17 @@ impl SharedCapability {
     }
 
     /// Returns the number of protocol messages supported by this capability.
-    pub fn num_messages(&self) -> Result<u8, SharedCapabilityError> {
+    pub fn num_messages(&self) -> u8 {
         match self {
-            SharedCapability::Eth { version, .. } => Ok(version.total_messages()),
-            _ => Err(SharedCapabilityError::UnknownCapability),
+            SharedCapability::Eth { version, .. } => version.total_messages(),
+            SharedCapability::UnknownCapability { messages, .. } => *messages,
         }
     }
+
+    /// Returns true if the given message id belongs to this capability.
+    pub fn contains_message_id(&self, id: u8) -> bool {
+        id.checked_sub(self.offset()).map_or(false, |id| id < self.num_messages())
+    }
 }
 
 /// An error that may occur while creating a [`SharedCapability`].
@@ -218,9 +280,6 @@ pub enum SharedCapabilityError {
     /// Unsupported `eth` version.
     #[error(transparent)]
     UnsupportedVersion(#[from] ParseVersionError),
-    /// Cannot determine the number of messages for unknown capabilities.
-    #[error("cannot determine the number of messages for unknown capabilities")]
-    UnknownCapability,
 }
 
 #[cfg(test)]
@@ -229,7 +288,7 @@ mod tests {
 
     #[test]
     fn from_eth_67() {
-        let capability = SharedCapability::new("eth", 67, 0).unwrap();
+        let capability = SharedCapability::new(&EthVersion::Eth67.into(), 0).unwrap();
 
         assert_eq!(capability.name(), "eth");
         assert_eq!(capability.version(), 67);
@@ -238,10 +297,23 @@ mod tests {
 
     #[test]
     fn from_eth_66() {
-        let capability = SharedCapability::new("eth", 66, 0).unwrap();
+        let capability = SharedCapability::new(&EthVersion::Eth66.into(), 0).unwrap();
 
         assert_eq!(capability.name(), "eth");
         assert_eq!(capability.version(), 66);
         assert_eq!(capability, SharedCapability::Eth { version: EthVersion::Eth66, offset: 0 });
     }
+
+    #[test]
+    fn unknown_capability_message_ids() {
+        let protocol = Protocol::new(Capability::new("snap".into(), 1), 8);
+        let capability = SharedCapability::new(&protocol, 0x1a).unwrap();
+
+        assert!(!capability.is_eth());
+        assert_eq!(capability.num_messages(), 8);
+        assert!(!capability.contains_message_id(0x19));
+        assert!(capability.contains_message_id(0x1a));
+        assert!(capability.contains_message_id(0x21));
+        assert!(!capability.contains_message_id(0x22));
+    }
 }
@@ -62,6 +62,8 @@ pub enum EthHandshakeError {
     InvalidFork(#[from] ValidationError),
     #[error("mismatched genesis in Status message. expected: {expected:?}, got: {got:?}")]
     MismatchedGenesis { expected: H256, got: H256 },
+    #[error("unsupported eth protocol version: {0}")]
+    UnsupportedProtocolVersion(u8),
     #[error("mismatched protocol version in Status message. expected: {expected:?}, got: {got:?}")]
     MismatchedProtocolVersion { expected: u8, got: u8 },
     #[error("mismatched chain in Status message. expected: {expected:?}, got: {got:?}")]
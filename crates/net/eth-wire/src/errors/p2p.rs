@@ -35,6 +35,8 @@ pub enum P2PStreamError {
     PingBeforeHandshake,
     #[error("too many messages buffered before sending")]
     SendBufferFull,
+    #[error("too many messages buffered for a sub-protocol")]
+    ProtocolBufferFull,
     #[error("disconnected")]
     Disconnected(DisconnectReason),
     #[error("unknown disconnect reason: {0}")]
@@ -66,6 +68,8 @@ pub enum P2PHandshakeError {
     NonHelloMessageInHandshake,
     #[error("no capabilities shared with peer")]
     NoSharedCapabilities,
+    #[error("shared capabilities reserve more message ids than available")]
+    TooManyCapabilityMessages,
     #[error("no response received when sending out handshake")]
     NoResponse,
     #[error("handshake timed out")]
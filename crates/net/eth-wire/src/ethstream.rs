@@ -1,13 +1,13 @@
 use crate::{
     errors::{EthHandshakeError, EthStreamError},
     message::{EthBroadcastMessage, ProtocolBroadcastMessage},
-    types::{EthMessage, ProtocolMessage, Status},
+    types::{EthMessage, EthVersion, ProtocolMessage, Status},
 };
 use bytes::{Bytes, BytesMut};
 use futures::{ready, Sink, SinkExt, StreamExt};
 use pin_project::pin_project;
 use reth_primitives::ForkFilter;
-use reth_rlp::{Decodable, Encodable};
+use reth_rlp::Encodable;
 use std::{
     pin::Pin,
     task::{Context, Poll},
@@ -51,11 +51,63 @@ where
         status: Status,
         fork_filter: ForkFilter,
     ) -> Result<(EthStream<S>, Status), EthStreamError> {
+        let version = EthVersion::try_from(status.version)
+            .map_err(|_| EthHandshakeError::UnsupportedProtocolVersion(status.version))?;
+        let resp = self.exchange_status(status).await?;
+
+        // TODO: Add any missing checks
+        // https://github.com/ethereum/go-ethereum/blob/9244d5cd61f3ea5a7645fdf2a1a96d53421e412f/eth/protocols/eth/handshake.go#L87-L89
+        tracing::trace!(
+            status=%resp,
+            "validating incoming eth status from peer"
+        );
+        if status.genesis != resp.genesis {
+            return Err(EthHandshakeError::MismatchedGenesis {
+                expected: status.genesis,
+                got: resp.genesis,
+            }
+            .into())
+        }
+
+        if status.version != resp.version {
+            return Err(EthHandshakeError::MismatchedProtocolVersion {
+                expected: status.version,
+                got: resp.version,
+            }
+            .into())
+        }
+
+        if status.chain != resp.chain {
+            return Err(EthHandshakeError::MismatchedChain {
+                expected: status.chain,
+                got: resp.chain,
+            }
+            .into())
+        }
+
+        fork_filter.validate(resp.forkid).map_err(EthHandshakeError::InvalidFork)?;
+
+        // now we can create the `EthStream` because the peer has successfully completed
+        // the handshake
+        let stream = EthStream::new(version, self.inner);
+
+        Ok((stream, resp))
+    }
+
+    /// Sends our `Status` and returns the `Status` message sent by the remote peer, _without_
+    /// validating it against ours.
+    ///
+    /// This does not complete the handshake, which makes it useful to inspect peers that are on a
+    /// different chain or fork, for example when crawling the network.
+    pub async fn exchange_status(&mut self, status: Status) -> Result<Status, EthStreamError> {
         tracing::trace!(
             %status,
             "sending eth status to peer"
         );
 
+        let version = EthVersion::try_from(status.version)
+            .map_err(|_| EthHandshakeError::UnsupportedProtocolVersion(status.version))?;
+
         // we need to encode and decode here on our own because we don't have an `EthStream` yet
         // The max length for a status with TTD is: <msg id = 1 byte> + <rlp(status) = 88 byte>
         let mut our_status_bytes = BytesMut::with_capacity(1 + 88);
@@ -74,7 +126,7 @@ where
             return Err(EthStreamError::MessageTooBig(their_msg.len()))
         }
 
-        let msg = match ProtocolMessage::decode(&mut their_msg.as_ref()) {
+        let msg = match ProtocolMessage::decode_message(version, &mut their_msg.as_ref()) {
             Ok(m) => m,
             Err(err) => {
                 tracing::debug!("rlp decode error in eth handshake: msg={their_msg:x}");
@@ -82,46 +134,8 @@ where
             }
         };
 
-        // TODO: Add any missing checks
-        // https://github.com/ethereum/go-ethereum/blob/9244d5cd61f3ea5a7645fdf2a1a96d53421e412f/eth/protocols/eth/handshake.go#L87-L89
         match msg.message {
-            EthMessage::Status(resp) => {
-                tracing::trace!(
-                    status=%resp,
-                    "validating incoming eth status from peer"
-                );
-                if status.genesis != resp.genesis {
-                    return Err(EthHandshakeError::MismatchedGenesis {
-                        expected: status.genesis,
-                        got: resp.genesis,
-                    }
-                    .into())
-                }
-
-                if status.version != resp.version {
-                    return Err(EthHandshakeError::MismatchedProtocolVersion {
-                        expected: status.version,
-                        got: resp.version,
-                    }
-                    .into())
-                }
-
-                if status.chain != resp.chain {
-                    return Err(EthHandshakeError::MismatchedChain {
-                        expected: status.chain,
-                        got: resp.chain,
-                    }
-                    .into())
-                }
-
-                fork_filter.validate(resp.forkid).map_err(EthHandshakeError::InvalidFork)?;
-
-                // now we can create the `EthStream` because the peer has successfully completed
-                // the handshake
-                let stream = EthStream::new(self.inner);
-
-                Ok((stream, resp))
-            }
+            EthMessage::Status(resp) => Ok(resp),
             _ => Err(EthStreamError::EthHandshakeError(
                 EthHandshakeError::NonStatusMessageInHandshake,
             )),
@@ -134,6 +148,8 @@ where
 #[pin_project]
 #[derive(Debug)]
 pub struct EthStream<S> {
+    /// Negotiated eth version.
+    version: EthVersion,
     #[pin]
     inner: S,
 }
@@ -141,8 +157,13 @@ pub struct EthStream<S> {
 impl<S> EthStream<S> {
     /// Creates a new unauthed [`EthStream`] from a provided stream. You will need
     /// to manually handshake a peer.
-    pub fn new(inner: S) -> Self {
-        Self { inner }
+    pub fn new(version: EthVersion, inner: S) -> Self {
+        Self { version, inner }
+    }
+
+    /// Returns the eth version.
+    pub fn version(&self) -> EthVersion {
+        self.version
     }
 
     /// Returns the underlying stream.
@@ -201,7 +222,7 @@ where
             return Poll::Ready(Some(Err(EthStreamError::MessageTooBig(bytes.len()))))
         }
 
-        let msg = match ProtocolMessage::decode(&mut bytes.as_ref()) {
+        let msg = match ProtocolMessage::decode_message(*this.version, &mut bytes.as_ref()) {
             Ok(m) => m,
             Err(err) => {
                 tracing::debug!("rlp decode error: msg={bytes:x}");
@@ -318,6 +339,43 @@ mod tests {
         handle.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn can_exchange_status_with_other_chain() {
+        let genesis = H256::random();
+        let fork_filter = ForkFilter::new(Head::default(), genesis, Vec::new());
+
+        let status = Status {
+            version: EthVersion::Eth67 as u8,
+            chain: Chain::Mainnet.into(),
+            total_difficulty: U256::ZERO,
+            blockhash: H256::random(),
+            genesis,
+            forkid: fork_filter.current(),
+        };
+        let other_status =
+            Status { chain: Chain::Goerli.into(), genesis: H256::random(), ..status };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let handle = tokio::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let stream = PassthroughCodec::default().framed(incoming);
+            let their_status =
+                UnauthedEthStream::new(stream).exchange_status(other_status).await.unwrap();
+            assert_eq!(their_status, status);
+        });
+
+        let outgoing = TcpStream::connect(local_addr).await.unwrap();
+        let sink = PassthroughCodec::default().framed(outgoing);
+
+        // the status of the other chain is returned as is
+        let their_status = UnauthedEthStream::new(sink).exchange_status(status).await.unwrap();
+        assert_eq!(their_status, other_status);
+
+        handle.await.unwrap();
+    }
+
     #[tokio::test]
     async fn can_write_and_read_cleartext() {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -335,7 +393,7 @@ mod tests {
             // roughly based off of the design of tokio::net::TcpListener
             let (incoming, _) = listener.accept().await.unwrap();
             let stream = PassthroughCodec::default().framed(incoming);
-            let mut stream = EthStream::new(stream);
+            let mut stream = EthStream::new(EthVersion::Eth67, stream);
 
             // use the stream to get the next message
             let message = stream.next().await.unwrap().unwrap();
@@ -344,7 +402,7 @@ mod tests {
 
         let outgoing = TcpStream::connect(local_addr).await.unwrap();
         let sink = PassthroughCodec::default().framed(outgoing);
-        let mut client_stream = EthStream::new(sink);
+        let mut client_stream = EthStream::new(EthVersion::Eth67, sink);
 
         client_stream.send(test_msg).await.unwrap();
 
@@ -370,7 +428,7 @@ mod tests {
             // roughly based off of the design of tokio::net::TcpListener
             let (incoming, _) = listener.accept().await.unwrap();
             let stream = ECIESStream::incoming(incoming, server_key).await.unwrap();
-            let mut stream = EthStream::new(stream);
+            let mut stream = EthStream::new(EthVersion::Eth67, stream);
 
             // use the stream to get the next message
             let message = stream.next().await.unwrap().unwrap();
@@ -384,7 +442,7 @@ mod tests {
 
         let outgoing = TcpStream::connect(local_addr).await.unwrap();
         let outgoing = ECIESStream::connect(outgoing, client_key, server_id).await.unwrap();
-        let mut client_stream = EthStream::new(outgoing);
+        let mut client_stream = EthStream::new(EthVersion::Eth67, outgoing);
 
         client_stream.send(test_msg).await.unwrap();
 
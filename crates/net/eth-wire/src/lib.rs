@@ -12,6 +12,7 @@ mod disconnect;
 pub mod errors;
 mod ethstream;
 mod hello;
+pub mod multiplex;
 mod p2pstream;
 mod pinger;
 pub use builder::*;
@@ -0,0 +1,110 @@
+//! Support for multiplexing additional sub-protocols over a [`P2PStream`](crate::P2PStream).
+//!
+//! The [`P2PStream`](crate::P2PStream) handles the reserved `p2p` messages (ping, pong,
+//! disconnect) and the messages of its primary capability, usually `eth`. Messages of all other
+//! shared capabilities are routed to a [`ProtocolConnection`] for that capability based on their
+//! message id range.
+
+use crate::capability::SharedCapability;
+use bytes::{Bytes, BytesMut};
+use futures::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// The maximum number of messages buffered in each direction of a [`ProtocolConnection`].
+pub(crate) const MAX_PROTOCOL_MESSAGES: usize = 64;
+
+/// The connection of a single sub-protocol that is multiplexed over a
+/// [`P2PStream`](crate::P2PStream).
+///
+/// Message ids of the messages yielded by this stream and passed to
+/// [`ProtocolConnection::send`] are relative to the capability, so the first message of the
+/// protocol has the id `0x00`.
+///
+/// The stream ends once the underlying connection was closed.
+#[derive(Debug)]
+pub struct ProtocolConnection {
+    /// The shared capability of this protocol.
+    cap: SharedCapability,
+    /// Messages received from the wire for this protocol.
+    from_wire: ReceiverStream<BytesMut>,
+    /// Sends messages to the [`P2PStream`](crate::P2PStream), with their absolute message id.
+    to_wire: mpsc::Sender<Bytes>,
+}
+
+// === impl ProtocolConnection ===
+
+impl ProtocolConnection {
+    /// Creates a new connection for the given capability and returns it together with the
+    /// sender half for messages received from the wire.
+    pub(crate) fn new(
+        cap: SharedCapability,
+        to_wire: mpsc::Sender<Bytes>,
+    ) -> (Self, mpsc::Sender<BytesMut>) {
+        let (tx, rx) = mpsc::channel(MAX_PROTOCOL_MESSAGES);
+        (Self { cap, from_wire: ReceiverStream::new(rx), to_wire }, tx)
+    }
+
+    /// Returns the shared capability of this protocol.
+    pub fn shared_capability(&self) -> &SharedCapability {
+        &self.cap
+    }
+
+    /// Queues the message to be sent to the peer.
+    ///
+    /// The first byte of the message is the message id relative to the capability.
+    ///
+    /// Returns an error if the message id is out of range for the capability, if too many messages
+    /// are already queued because the peer doesn't keep up, or if the connection was closed.
+    pub fn send(&self, msg: Bytes) -> Result<(), ProtocolConnectionError> {
+        let id = *msg.first().ok_or(ProtocolConnectionError::EmptyMessage)?;
+        if id >= self.cap.num_messages() {
+            return Err(ProtocolConnectionError::InvalidMessageId(id))
+        }
+
+        let mut msg = BytesMut::from(&msg[..]);
+        msg[0] = id + self.cap.offset();
+        self.to_wire.try_send(msg.freeze()).map_err(|err| match err {
+            mpsc::error::TrySendError::Full(_) => ProtocolConnectionError::SendBufferFull,
+            mpsc::error::TrySendError::Closed(_) => ProtocolConnectionError::ConnectionClosed,
+        })
+    }
+}
+
+impl Stream for ProtocolConnection {
+    type Item = BytesMut;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().from_wire).poll_next(cx)
+    }
+}
+
+/// Errors when sending messages over a [`ProtocolConnection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum ProtocolConnectionError {
+    /// The message has no message id.
+    #[error("empty protocol message")]
+    EmptyMessage,
+    /// The message id is not reserved by the capability.
+    #[error("message id {0} is out of range for the capability")]
+    InvalidMessageId(u8),
+    /// Too many messages are queued to be sent to the peer.
+    #[error("too many messages buffered before sending")]
+    SendBufferFull,
+    /// The underlying connection was closed.
+    #[error("connection closed")]
+    ConnectionClosed,
+}
+
+/// The [`P2PStream`](crate::P2PStream) side of a [`ProtocolConnection`].
+#[derive(Debug)]
+pub(crate) struct ProtocolChannel {
+    /// The shared capability of the protocol.
+    pub(crate) cap: SharedCapability,
+    /// Delivers messages received from the wire to the [`ProtocolConnection`].
+    pub(crate) to_protocol: mpsc::Sender<BytesMut>,
+}
@@ -1,9 +1,10 @@
 #![allow(dead_code, unreachable_pub, missing_docs, unused_variables)]
 use crate::{
-    capability::{Capability, SharedCapability},
+    capability::{Capability, Protocol, SharedCapability},
     errors::{P2PHandshakeError, P2PStreamError},
+    multiplex::{ProtocolChannel, ProtocolConnection, MAX_PROTOCOL_MESSAGES},
     pinger::{Pinger, PingerEvent},
-    DisconnectReason, HelloMessage,
+    DisconnectReason, EthVersion, HelloMessage,
 };
 use bytes::{Buf, Bytes, BytesMut};
 use futures::{Sink, SinkExt, StreamExt};
@@ -12,12 +13,13 @@ use pin_project::pin_project;
 use reth_codecs::derive_arbitrary;
 use reth_rlp::{Decodable, DecodeError, Encodable, EMPTY_LIST_CODE};
 use std::{
-    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    collections::{BTreeSet, HashMap, VecDeque},
     io,
     pin::Pin,
     task::{ready, Context, Poll},
     time::Duration,
 };
+use tokio::sync::mpsc;
 use tokio_stream::Stream;
 
 #[cfg(feature = "serde")]
@@ -54,18 +56,33 @@ const GRACE_PERIOD: Duration = Duration::from_secs(2);
 /// `p2p` stream.
 const MAX_P2P_CAPACITY: usize = 64;
 
+/// [`MAX_UNHANDLED_PROTOCOL_MESSAGES`] is the maximum number of messages of shared sub-protocols
+/// that are buffered until a [`ProtocolConnection`] for the sub-protocol is created.
+const MAX_UNHANDLED_PROTOCOL_MESSAGES: usize = MAX_PROTOCOL_MESSAGES;
+
 /// An un-authenticated [`P2PStream`]. This is consumed and returns a [`P2PStream`] after the
 /// `Hello` handshake is completed.
 #[pin_project]
 pub struct UnauthedP2PStream<S> {
     #[pin]
     inner: S,
+    /// Additional sub-protocols that can be multiplexed over the stream.
+    protocols: Vec<Protocol>,
 }
 
 impl<S> UnauthedP2PStream<S> {
     /// Create a new `UnauthedP2PStream` from a type `S` which implements `Stream` and `Sink`.
     pub fn new(inner: S) -> Self {
-        Self { inner }
+        Self { inner, protocols: Vec::new() }
+    }
+
+    /// Sets the additional sub-protocols that are supported next to `eth`.
+    ///
+    /// The capabilities of these protocols must also be announced in the `Hello` message. `eth`
+    /// capabilities don't need to be registered.
+    pub fn with_protocols(mut self, protocols: Vec<Protocol>) -> Self {
+        self.protocols = protocols;
+        self
     }
 }
 
@@ -158,20 +175,35 @@ where
             })
         }
 
-        // determine shared capabilities (currently returns only one capability)
+        // determine the protocols for all capabilities we announced
+        let mut local_protocols = Vec::with_capacity(hello.capabilities.len());
+        for cap in hello.capabilities {
+            if cap.name == "eth" {
+                match EthVersion::try_from(cap.version as u8) {
+                    Ok(version) => local_protocols.push(Protocol::eth(version)),
+                    Err(err) => tracing::debug!(?err, "unsupported eth capability"),
+                }
+            } else if let Some(protocol) = self.protocols.iter().find(|p| p.cap == cap) {
+                local_protocols.push(protocol.clone());
+            } else {
+                tracing::debug!(?cap, "no protocol registered for announced capability");
+            }
+        }
+
+        // determine shared capabilities
         let capability_res =
-            set_capability_offsets(hello.capabilities, their_hello.capabilities.clone());
+            set_capability_offsets(local_protocols, their_hello.capabilities.clone());
 
-        let shared_capability = match capability_res {
+        let shared_capabilities = match capability_res {
             Err(err) => {
                 // we don't share any capabilities, send a disconnect message
                 self.send_disconnect(DisconnectReason::UselessPeer).await?;
                 Err(err)
             }
-            Ok(cap) => Ok(cap),
+            Ok(caps) => Ok(caps),
         }?;
 
-        let stream = P2PStream::new(self.inner, shared_capability);
+        let stream = P2PStream::new(self.inner, shared_capabilities)?;
 
         Ok((stream, their_hello))
     }
@@ -194,9 +226,25 @@ pub struct P2PStream<S> {
     /// The state machine used for keeping track of the peer's ping status.
     pinger: Pinger,
 
-    /// The supported capability for this stream.
+    /// The capability the messages of this stream belong to, usually `eth`.
     shared_capability: SharedCapability,
 
+    /// All capabilities shared with the peer, ordered by their message id offset.
+    shared_capabilities: Vec<SharedCapability>,
+
+    /// Channels of the sub-protocols that are multiplexed over this stream.
+    protocols: Vec<ProtocolChannel>,
+
+    /// Sender half handed out to new [`ProtocolConnection`]s.
+    protocols_tx: mpsc::Sender<Bytes>,
+
+    /// Messages sent by multiplexed sub-protocols, with their absolute message id.
+    protocols_rx: mpsc::Receiver<Bytes>,
+
+    /// Messages of shared sub-protocols that were received before a [`ProtocolConnection`] for
+    /// the sub-protocol was created, with their absolute message id.
+    unhandled_protocol_messages: VecDeque<BytesMut>,
+
     /// Outgoing messages buffered for sending to the underlying stream.
     outgoing_messages: VecDeque<Bytes>,
 
@@ -209,16 +257,37 @@ impl<S> P2PStream<S> {
     /// Create a new [`P2PStream`] from the provided stream.
     /// New [`P2PStream`]s are assumed to have completed the `p2p` handshake successfully and are
     /// ready to send and receive subprotocol messages.
-    pub fn new(inner: S, capability: SharedCapability) -> Self {
-        Self {
+    ///
+    /// Messages of the `eth` capability, or the capability with the lowest offset if `eth` is not
+    /// shared, are handled by the stream itself. All other capabilities can be accessed via
+    /// [`P2PStream::protocol_connection`].
+    ///
+    /// Returns an error if no capabilities are shared.
+    pub fn new(
+        inner: S,
+        shared_capabilities: Vec<SharedCapability>,
+    ) -> Result<Self, P2PStreamError> {
+        let shared_capability = shared_capabilities
+            .iter()
+            .find(|cap| cap.is_eth())
+            .or_else(|| shared_capabilities.first())
+            .cloned()
+            .ok_or(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities))?;
+        let (protocols_tx, protocols_rx) = mpsc::channel(MAX_PROTOCOL_MESSAGES);
+        Ok(Self {
             inner,
             encoder: snap::raw::Encoder::new(),
             decoder: snap::raw::Decoder::new(),
             pinger: Pinger::new(PING_INTERVAL, PING_TIMEOUT),
-            shared_capability: capability,
+            shared_capability,
+            shared_capabilities,
+            protocols: Vec::new(),
+            protocols_tx,
+            protocols_rx,
+            unhandled_protocol_messages: VecDeque::new(),
             outgoing_messages: VecDeque::new(),
             disconnecting: false,
-        }
+        })
     }
 
     /// Returns the shared capability for this stream.
@@ -226,11 +295,89 @@ impl<S> P2PStream<S> {
         &self.shared_capability
     }
 
+    /// Returns all capabilities shared with the peer, ordered by their message id offset.
+    pub fn shared_capabilities(&self) -> &[SharedCapability] {
+        &self.shared_capabilities
+    }
+
+    /// Returns a [`ProtocolConnection`] for the shared capability with the given name.
+    ///
+    /// Returns `None` if the capability is not shared, is the capability of this stream or if a
+    /// connection for it was already created.
+    ///
+    /// Messages for the protocol are only read from the wire while this stream is polled.
+    /// Messages of the protocol that were received before the connection was created are
+    /// delivered first.
+    pub fn protocol_connection(&mut self, name: &str) -> Option<ProtocolConnection> {
+        if self.shared_capability.name() == name ||
+            self.protocols.iter().any(|channel| channel.cap.name() == name)
+        {
+            return None
+        }
+        let cap = self.shared_capabilities.iter().find(|cap| cap.name() == name)?.clone();
+        let (conn, to_protocol) = ProtocolConnection::new(cap.clone(), self.protocols_tx.clone());
+
+        // deliver the messages that arrived before the protocol was attached
+        let mut unhandled = VecDeque::with_capacity(self.unhandled_protocol_messages.len());
+        for mut msg in self.unhandled_protocol_messages.drain(..) {
+            if cap.contains_message_id(msg[0]) {
+                msg[0] -= cap.offset();
+                // never full, the channel has room for all `MAX_UNHANDLED_PROTOCOL_MESSAGES`
+                let _ = to_protocol.try_send(msg);
+            } else {
+                unhandled.push_back(msg);
+            }
+        }
+        self.unhandled_protocol_messages = unhandled;
+
+        self.protocols.push(ProtocolChannel { cap, to_protocol });
+        Some(conn)
+    }
+
     /// Returns `true` if the connection is about to disconnect.
     pub fn is_disconnecting(&self) -> bool {
         self.disconnecting
     }
 
+    /// Compresses the message with _snappy_ and sets the given message id.
+    fn compress_message(&mut self, id: u8, msg: &[u8]) -> Result<Bytes, snap::Error> {
+        let mut compressed = BytesMut::zeroed(1 + snap::raw::max_compress_len(msg.len() - 1));
+        let compressed_size =
+            self.encoder.compress(&msg[1..], &mut compressed[1..]).map_err(|err| {
+                tracing::debug!(
+                    ?err,
+                    msg=%hex::encode(&msg[1..]),
+                    "error compressing p2p message"
+                );
+                err
+            })?;
+
+        // truncate the compressed buffer to the actual compressed size (plus one for the message
+        // id)
+        compressed.truncate(compressed_size + 1);
+        compressed[0] = id;
+
+        Ok(compressed.freeze())
+    }
+
+    /// Queues in all messages sent by multiplexed sub-protocols.
+    ///
+    /// Returns `true` if any message was queued.
+    fn queue_protocol_messages(&mut self, cx: &mut Context<'_>) -> Result<bool, P2PStreamError> {
+        let mut queued = false;
+        while self.outgoing_messages.len() < MAX_P2P_CAPACITY {
+            match self.protocols_rx.poll_recv(cx) {
+                Poll::Ready(Some(msg)) => {
+                    let compressed = self.compress_message(msg[0], &msg)?;
+                    self.outgoing_messages.push_back(compressed);
+                    queued = true;
+                }
+                _ => break,
+            }
+        }
+        Ok(queued)
+    }
+
     /// Queues in a _snappy_ encoded [`P2PMessage::Pong`] message.
     fn send_pong(&mut self) {
         let pong = P2PMessage::Pong;
@@ -309,6 +456,14 @@ where
             return Poll::Ready(None)
         }
 
+        // queue in messages of multiplexed protocols and flush them, since they're not sent via
+        // the sink
+        if this.queue_protocol_messages(cx)? {
+            if let Poll::Ready(Err(err)) = Pin::new(&mut *this).poll_flush(cx) {
+                return Poll::Ready(Some(Err(err)))
+            }
+        }
+
         // we should loop here to ensure we don't return Poll::Pending if we have a message to
         // return behind any pings we need to respond to
         while let Poll::Ready(res) = this.inner.poll_next_unpin(cx) {
@@ -401,9 +556,41 @@ where
                     //  * `eth/67` is reserved message IDs 0x10 - 0x19.
                     //  * `qrs/65` is reserved message IDs 0x1a - 0x21.
                     //
-                    decompress_buf[0] = bytes[0] - this.shared_capability.offset();
+                    if this.shared_capability.contains_message_id(id) {
+                        decompress_buf[0] = id - this.shared_capability.offset();
+                        return Poll::Ready(Some(Ok(decompress_buf)))
+                    }
 
-                    return Poll::Ready(Some(Ok(decompress_buf)))
+                    // route the message to the multiplexed protocol it belongs to
+                    if let Some(channel) =
+                        this.protocols.iter().find(|channel| channel.cap.contains_message_id(id))
+                    {
+                        decompress_buf[0] = id - channel.cap.offset();
+                        match channel.to_protocol.try_send(decompress_buf) {
+                            Ok(()) => {}
+                            Err(mpsc::error::TrySendError::Full(_)) => {
+                                // the peer sends messages faster than the protocol handles them
+                                return Poll::Ready(Some(Err(P2PStreamError::ProtocolBufferFull)))
+                            }
+                            // the protocol connection may have been dropped
+                            Err(mpsc::error::TrySendError::Closed(_)) => {}
+                        }
+                    } else if this.shared_capabilities.iter().any(|cap| cap.contains_message_id(id))
+                    {
+                        // buffer the message until the protocol is attached
+                        if this.unhandled_protocol_messages.len() < MAX_UNHANDLED_PROTOCOL_MESSAGES
+                        {
+                            decompress_buf[0] = id;
+                            this.unhandled_protocol_messages.push_back(decompress_buf);
+                        } else {
+                            tracing::debug!(id, "dropping message of unhandled capability");
+                        }
+                    } else {
+                        // the message id does not belong to any shared capability, this is passed
+                        // to the stream which will reject it
+                        decompress_buf[0] = id.saturating_sub(this.shared_capability.offset());
+                        return Poll::Ready(Some(Ok(decompress_buf)))
+                    }
                 }
             }
         }
@@ -421,6 +608,8 @@ where
     fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         let mut this = self.as_mut();
 
+        this.queue_protocol_messages(cx)?;
+
         // poll the pinger to determine if we should send a ping
         match this.pinger.poll_ping(cx) {
             Poll::Pending => {}
@@ -466,32 +655,18 @@ where
     }
 
     fn start_send(self: Pin<&mut Self>, item: Bytes) -> Result<(), Self::Error> {
-        let this = self.project();
+        let this = self.get_mut();
 
         // ensure we have free capacity
         if this.outgoing_messages.len() >= MAX_P2P_CAPACITY {
             return Err(P2PStreamError::SendBufferFull)
         }
 
-        let mut compressed = BytesMut::zeroed(1 + snap::raw::max_compress_len(item.len() - 1));
-        let compressed_size =
-            this.encoder.compress(&item[1..], &mut compressed[1..]).map_err(|err| {
-                tracing::debug!(
-                    ?err,
-                    msg=%hex::encode(&item[1..]),
-                    "error compressing p2p message"
-                );
-                err
-            })?;
-
-        // truncate the compressed buffer to the actual compressed size (plus one for the message
-        // id)
-        compressed.truncate(compressed_size + 1);
-
         // all messages sent in this stream are subprotocol messages, so we need to switch the
         // message id based on the offset
-        compressed[0] = item[0] + this.shared_capability.offset();
-        this.outgoing_messages.push_back(compressed.freeze());
+        let id = item[0] + this.shared_capability.offset();
+        let compressed = this.compress_message(id, &item)?;
+        this.outgoing_messages.push_back(compressed);
 
         Ok(())
     }
@@ -524,20 +699,24 @@ where
 }
 
 /// Determines the offsets for each shared capability between the input list of peer
-/// capabilities and the input list of locally supported capabilities.
+/// capabilities and the input list of locally supported protocols.
 ///
-/// Currently only `eth` versions 66 and 67 are supported.
+/// Returns all shared capabilities, ordered by their message id offset.
+///
+/// `eth` versions 66 and 67 are supported natively, other protocols must declare the number of
+/// messages they reserve.
 /// Additionally, the `p2p` capability version 5 is supported, but is
-/// expected _not_ to be in neither `local_capabilities` or `peer_capabilities`.
+/// expected _not_ to be in neither `local_protocols` or `peer_capabilities`.
 pub fn set_capability_offsets(
-    local_capabilities: Vec<Capability>,
+    local_protocols: Vec<Protocol>,
     peer_capabilities: Vec<Capability>,
-) -> Result<SharedCapability, P2PStreamError> {
+) -> Result<Vec<SharedCapability>, P2PStreamError> {
     // find intersection of capabilities
-    let our_capabilities = local_capabilities.into_iter().collect::<HashSet<_>>();
+    let our_protocols =
+        local_protocols.into_iter().map(|p| (p.cap.clone(), p)).collect::<HashMap<_, _>>();
 
-    // map of capability name to version
-    let mut shared_capabilities = HashMap::new();
+    // map of capability name to the protocol of the highest shared version
+    let mut shared_protocols: HashMap<_, &Protocol> = HashMap::new();
 
     // The `Ord` implementation for capability names should be equivalent to geth (and every other
     // client), since geth uses golang's default string comparison, which orders strings
@@ -556,22 +735,19 @@ pub fn set_capability_offsets(
     // find highest shared version of each shared capability
     for peer_capability in peer_capabilities {
         // if this is Some, we share this capability
-        if our_capabilities.contains(&peer_capability) {
+        if let Some(protocol) = our_protocols.get(&peer_capability) {
             // If multiple versions are shared of the same (equal name) capability, the numerically
             // highest wins, others are ignored
-
-            let version = shared_capabilities.get(&peer_capability.name);
-            if version.is_none() ||
-                (version.is_some() && peer_capability.version > *version.expect("is some; qed"))
-            {
-                shared_capabilities.insert(peer_capability.name.clone(), peer_capability.version);
+            let version = shared_protocols.get(&peer_capability.name).map(|p| p.cap.version);
+            if version.map_or(true, |version| peer_capability.version > version) {
+                shared_protocols.insert(peer_capability.name.clone(), protocol);
                 shared_capability_names.insert(peer_capability.name);
             }
         }
     }
 
     // disconnect if we don't share any capabilities
-    if shared_capabilities.is_empty() {
+    if shared_protocols.is_empty() {
         return Err(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities))
     }
 
@@ -584,35 +760,19 @@ pub fn set_capability_offsets(
     // alphabetic order.
     let mut offset = MAX_RESERVED_MESSAGE_ID + 1;
     for name in shared_capability_names {
-        let version = shared_capabilities.get(&name).unwrap();
+        let protocol = shared_protocols[&name];
 
-        let shared_capability = SharedCapability::new(&name, *version as u8, offset)?;
+        let shared_capability = SharedCapability::new(protocol, offset)?;
 
-        match shared_capability {
-            SharedCapability::UnknownCapability { .. } => {
-                // Capabilities which are not shared are ignored
-                tracing::debug!("unknown capability: name={:?}, version={}", name, version,);
-            }
-            SharedCapability::Eth { .. } => {
-                // increment the offset if the capability is known
-                offset += shared_capability.num_messages()?;
+        // increment the offset by the number of messages the capability reserves
+        offset = offset
+            .checked_add(shared_capability.num_messages())
+            .ok_or(P2PStreamError::HandshakeError(P2PHandshakeError::TooManyCapabilityMessages))?;
 
-                shared_with_offsets.push(shared_capability);
-            }
-        }
+        shared_with_offsets.push(shared_capability);
     }
 
-    // TODO: support multiple capabilities - we would need a new Stream type to go on top of
-    // `P2PStream` containing its capability. `P2PStream` would still send pings and handle
-    // pongs, but instead contain a map of capabilities to their respective stream / channel.
-    // Each channel would be responsible for containing the offset for that stream and would
-    // only increment / decrement message IDs.
-    // NOTE: since the `P2PStream` currently only supports one capability, we set the
-    // capability with the lowest offset.
-    Ok(shared_with_offsets
-        .first()
-        .ok_or(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities))?
-        .clone())
+    Ok(shared_with_offsets)
 }
 
 /// This represents only the reserved `p2p` subprotocol messages.
@@ -784,7 +944,7 @@ impl Decodable for ProtocolVersion {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{DisconnectReason, EthVersion};
+    use crate::{multiplex::ProtocolConnectionError, DisconnectReason, EthVersion};
     use reth_ecies::util::pk2id;
     use secp256k1::{SecretKey, SECP256K1};
     use tokio::net::{TcpListener, TcpStream};
@@ -945,28 +1105,52 @@ mod tests {
 
     #[test]
     fn test_peer_lower_capability_version() {
-        let local_capabilities: Vec<Capability> =
+        let local_protocols: Vec<Protocol> =
             vec![EthVersion::Eth66.into(), EthVersion::Eth67.into()];
         let peer_capabilities: Vec<Capability> = vec![EthVersion::Eth66.into()];
 
-        let shared_capability =
-            set_capability_offsets(local_capabilities, peer_capabilities).unwrap();
+        let shared_capabilities =
+            set_capability_offsets(local_protocols, peer_capabilities).unwrap();
 
         assert_eq!(
-            shared_capability,
-            SharedCapability::Eth {
+            shared_capabilities,
+            vec![SharedCapability::Eth {
                 version: EthVersion::Eth66,
                 offset: MAX_RESERVED_MESSAGE_ID + 1
-            }
+            }]
+        )
+    }
+
+    #[test]
+    fn test_multiple_capability_offsets() {
+        let snap = Protocol::new(Capability::new("snap".into(), 1), 8);
+        let local_protocols = vec![EthVersion::Eth67.into(), snap.clone()];
+        let peer_capabilities = vec![snap.cap.clone(), EthVersion::Eth67.into()];
+
+        let shared_capabilities =
+            set_capability_offsets(local_protocols, peer_capabilities).unwrap();
+
+        let eth_offset = MAX_RESERVED_MESSAGE_ID + 1;
+        assert_eq!(
+            shared_capabilities,
+            vec![
+                SharedCapability::Eth { version: EthVersion::Eth67, offset: eth_offset },
+                SharedCapability::UnknownCapability {
+                    name: "snap".into(),
+                    version: 1,
+                    offset: eth_offset + EthVersion::Eth67.total_messages(),
+                    messages: 8
+                }
+            ]
         )
     }
 
     #[test]
     fn test_peer_capability_version_too_low() {
-        let local_capabilities: Vec<Capability> = vec![EthVersion::Eth67.into()];
+        let local_protocols: Vec<Protocol> = vec![EthVersion::Eth67.into()];
         let peer_capabilities: Vec<Capability> = vec![EthVersion::Eth66.into()];
 
-        let shared_capability = set_capability_offsets(local_capabilities, peer_capabilities);
+        let shared_capability = set_capability_offsets(local_protocols, peer_capabilities);
 
         assert!(matches!(
             shared_capability,
@@ -976,10 +1160,10 @@ mod tests {
 
     #[test]
     fn test_peer_capability_version_too_high() {
-        let local_capabilities: Vec<Capability> = vec![EthVersion::Eth66.into()];
+        let local_protocols: Vec<Protocol> = vec![EthVersion::Eth66.into()];
         let peer_capabilities: Vec<Capability> = vec![EthVersion::Eth67.into()];
 
-        let shared_capability = set_capability_offsets(local_capabilities, peer_capabilities);
+        let shared_capability = set_capability_offsets(local_protocols, peer_capabilities);
 
         assert!(matches!(
             shared_capability,
@@ -987,6 +1171,184 @@ mod tests {
         ))
     }
 
+    #[tokio::test]
+    async fn test_multiplex_sub_protocol() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let protocol = Protocol::new(Capability::new("test".into(), 1), 2);
+        let hello = {
+            let protocol = protocol.clone();
+            move || {
+                let (mut hello, _) = eth_hello();
+                hello.capabilities.push(protocol.cap.clone());
+                hello
+            }
+        };
+
+        let server_protocol = protocol.clone();
+        let server_hello = hello.clone();
+        let handle = tokio::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let stream = crate::PassthroughCodec::default().framed(incoming);
+
+            let (mut p2p_stream, _) = UnauthedP2PStream::new(stream)
+                .with_protocols(vec![server_protocol])
+                .handshake(server_hello())
+                .await
+                .unwrap();
+
+            // `eth` is handled by the stream itself
+            assert!(p2p_stream.protocol_connection("eth").is_none());
+            let mut conn = p2p_stream.protocol_connection("test").unwrap();
+            assert_eq!(
+                conn.shared_capability().offset(),
+                MAX_RESERVED_MESSAGE_ID + 1 + EthVersion::Eth67.total_messages()
+            );
+            tokio::spawn(async move { while p2p_stream.next().await.is_some() {} });
+
+            // echo the message back
+            let msg = conn.next().await.unwrap();
+            conn.send(msg.freeze()).unwrap();
+        });
+
+        let outgoing = TcpStream::connect(local_addr).await.unwrap();
+        let sink = crate::PassthroughCodec::default().framed(outgoing);
+
+        let (mut p2p_stream, _) = UnauthedP2PStream::new(sink)
+            .with_protocols(vec![protocol])
+            .handshake(hello())
+            .await
+            .unwrap();
+        assert_eq!(p2p_stream.shared_capabilities().len(), 2);
+
+        let mut conn = p2p_stream.protocol_connection("test").unwrap();
+        assert!(p2p_stream.protocol_connection("test").is_none());
+        tokio::spawn(async move { while p2p_stream.next().await.is_some() {} });
+
+        assert_eq!(
+            conn.send(Bytes::from_static(&[0x02, 0xc0])),
+            Err(ProtocolConnectionError::InvalidMessageId(2))
+        );
+        conn.send(Bytes::from_static(&[0x01, 0xc0])).unwrap();
+        let msg = conn.next().await.unwrap();
+        assert_eq!(&msg[..], &[0x01, 0xc0]);
+
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_buffer_unhandled_sub_protocol_messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let protocol = Protocol::new(Capability::new("test".into(), 1), 2);
+        let hello = {
+            let protocol = protocol.clone();
+            move || {
+                let (mut hello, _) = eth_hello();
+                hello.capabilities.push(protocol.cap.clone());
+                hello
+            }
+        };
+
+        let server_protocol = protocol.clone();
+        let server_hello = hello.clone();
+        let handle = tokio::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let stream = crate::PassthroughCodec::default().framed(incoming);
+
+            let (mut p2p_stream, _) = UnauthedP2PStream::new(stream)
+                .with_protocols(vec![server_protocol])
+                .handshake(server_hello())
+                .await
+                .unwrap();
+
+            // the `eth` message is sent after the message of the sub-protocol
+            let msg = p2p_stream.next().await.unwrap().unwrap();
+            assert_eq!(&msg[..], &[0x00, 0xc0]);
+
+            let mut conn = p2p_stream.protocol_connection("test").unwrap();
+            let msg = conn.next().await.unwrap();
+            assert_eq!(&msg[..], &[0x01, 0xc0]);
+        });
+
+        let outgoing = TcpStream::connect(local_addr).await.unwrap();
+        let sink = crate::PassthroughCodec::default().framed(outgoing);
+
+        let (mut p2p_stream, _) = UnauthedP2PStream::new(sink)
+            .with_protocols(vec![protocol])
+            .handshake(hello())
+            .await
+            .unwrap();
+
+        let conn = p2p_stream.protocol_connection("test").unwrap();
+        conn.send(Bytes::from_static(&[0x01, 0xc0])).unwrap();
+        p2p_stream.send(Bytes::from_static(&[0x00, 0xc0])).await.unwrap();
+
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_on_full_sub_protocol_buffer() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let protocol = Protocol::new(Capability::new("test".into(), 1), 2);
+        let hello = {
+            let protocol = protocol.clone();
+            move || {
+                let (mut hello, _) = eth_hello();
+                hello.capabilities.push(protocol.cap.clone());
+                hello
+            }
+        };
+
+        let server_protocol = protocol.clone();
+        let server_hello = hello.clone();
+        let handle = tokio::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let stream = crate::PassthroughCodec::default().framed(incoming);
+
+            let (mut p2p_stream, _) = UnauthedP2PStream::new(stream)
+                .with_protocols(vec![server_protocol])
+                .handshake(server_hello())
+                .await
+                .unwrap();
+
+            // the connection is never polled, so its messages pile up
+            let _conn = p2p_stream.protocol_connection("test").unwrap();
+            let err = p2p_stream.next().await.unwrap().unwrap_err();
+            assert!(matches!(err, P2PStreamError::ProtocolBufferFull));
+        });
+
+        let outgoing = TcpStream::connect(local_addr).await.unwrap();
+        let sink = crate::PassthroughCodec::default().framed(outgoing);
+
+        let (mut p2p_stream, _) = UnauthedP2PStream::new(sink)
+            .with_protocols(vec![protocol])
+            .handshake(hello())
+            .await
+            .unwrap();
+
+        // the first message id after `eth` belongs to the sub-protocol
+        let id = EthVersion::Eth67.total_messages();
+        for _ in 0..=MAX_PROTOCOL_MESSAGES {
+            p2p_stream.send(Bytes::from(vec![id, 0xc0])).await.unwrap();
+        }
+
+        handle.await.unwrap();
+    }
+
+    #[test]
+    fn test_new_without_shared_capabilities() {
+        let res = P2PStream::new((), Vec::new());
+        assert!(matches!(
+            res,
+            Err(P2PStreamError::HandshakeError(P2PHandshakeError::NoSharedCapabilities))
+        ))
+    }
+
     #[test]
     fn snappy_decode_encode_ping() {
         let snappy_ping = b"\x02\x01\0\xc0";
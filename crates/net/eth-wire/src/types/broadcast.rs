@@ -1,7 +1,12 @@
 //! Types for broadcasting new data.
+use crate::EthMessage;
+use bytes::{BufMut, BytesMut};
 use reth_codecs::derive_arbitrary;
 use reth_primitives::{Header, TransactionSigned, H256, U128};
-use reth_rlp::{RlpDecodable, RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper};
+use reth_rlp::{
+    length_of_length, Decodable, DecodeError, Encodable, Header as RlpHeader, RlpDecodable,
+    RlpDecodableWrapper, RlpEncodable, RlpEncodableWrapper,
+};
 use std::sync::Arc;
 
 #[cfg(feature = "serde")]
@@ -112,21 +117,160 @@ pub struct SharedTransactions(
     pub Vec<Arc<TransactionSigned>>,
 );
 
+/// A transaction announcement of either `eth/66` and `eth/67`, or `eth/68`, depending on the
+/// version negotiated with the peer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NewPooledTransactionHashes {
+    /// An `eth/66` or `eth/67` announcement, consisting of transaction hashes only.
+    Eth66(NewPooledTransactionHashes66),
+    /// An `eth/68` announcement, which also includes the types and sizes of the transactions.
+    Eth68(NewPooledTransactionHashes68),
+}
+
+// === impl NewPooledTransactionHashes ===
+
+impl NewPooledTransactionHashes {
+    /// Returns the announced transaction hashes.
+    pub fn hashes(&self) -> &[H256] {
+        match self {
+            NewPooledTransactionHashes::Eth66(msg) => &msg.0,
+            NewPooledTransactionHashes::Eth68(msg) => &msg.hashes,
+        }
+    }
+
+    /// Consumes the type and returns the announced transaction hashes.
+    pub fn into_hashes(self) -> Vec<H256> {
+        match self {
+            NewPooledTransactionHashes::Eth66(msg) => msg.0,
+            NewPooledTransactionHashes::Eth68(msg) => msg.hashes,
+        }
+    }
+
+    /// Returns the number of announced transactions.
+    pub fn len(&self) -> usize {
+        self.hashes().len()
+    }
+
+    /// Returns true if no transactions are announced.
+    pub fn is_empty(&self) -> bool {
+        self.hashes().is_empty()
+    }
+
+    /// Whether this is an `eth/68` announcement.
+    pub fn is_eth68(&self) -> bool {
+        matches!(self, NewPooledTransactionHashes::Eth68(_))
+    }
+}
+
+impl From<NewPooledTransactionHashes> for EthMessage {
+    fn from(msg: NewPooledTransactionHashes) -> Self {
+        match msg {
+            NewPooledTransactionHashes::Eth66(msg) => EthMessage::NewPooledTransactionHashes66(msg),
+            NewPooledTransactionHashes::Eth68(msg) => EthMessage::NewPooledTransactionHashes68(msg),
+        }
+    }
+}
+
+impl From<NewPooledTransactionHashes66> for NewPooledTransactionHashes {
+    fn from(msg: NewPooledTransactionHashes66) -> Self {
+        NewPooledTransactionHashes::Eth66(msg)
+    }
+}
+
+impl From<NewPooledTransactionHashes68> for NewPooledTransactionHashes {
+    fn from(msg: NewPooledTransactionHashes68) -> Self {
+        NewPooledTransactionHashes::Eth68(msg)
+    }
+}
+
 /// This informs peers of transaction hashes for transactions that have appeared on the network,
 /// but have not been included in a block.
+///
+/// This is the announcement of `eth/66` and `eth/67`.
 #[derive_arbitrary(rlp)]
 #[derive(Clone, Debug, PartialEq, Eq, RlpEncodableWrapper, RlpDecodableWrapper, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct NewPooledTransactionHashes(
+pub struct NewPooledTransactionHashes66(
     /// Transaction hashes for new transactions that have appeared on the network.
     /// Clients should request the transactions with the given hashes using a
     /// [`GetPooledTransactions`](crate::GetPooledTransactions) message.
     pub Vec<H256>,
 );
 
-impl From<Vec<H256>> for NewPooledTransactionHashes {
+impl From<Vec<H256>> for NewPooledTransactionHashes66 {
     fn from(v: Vec<H256>) -> Self {
-        NewPooledTransactionHashes(v)
+        NewPooledTransactionHashes66(v)
+    }
+}
+
+/// Same as [`NewPooledTransactionHashes66`] but extends that beside the transaction hashes, the
+/// node sends the transaction types and their sizes (as defined in EIP-2718) as well.
+///
+/// This is the announcement of `eth/68`: <https://eips.ethereum.org/EIPS/eip-5793>
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NewPooledTransactionHashes68 {
+    /// Transaction types for new transactions that have appeared on the network.
+    ///
+    /// Encoded as a single byte string.
+    pub types: Vec<u8>,
+    /// Transaction sizes for new transactions that have appeared on the network, as the length of
+    /// their EIP-2718 encoding.
+    pub sizes: Vec<usize>,
+    /// Transaction hashes for new transactions that have appeared on the network.
+    pub hashes: Vec<H256>,
+}
+
+// === impl NewPooledTransactionHashes68 ===
+
+impl NewPooledTransactionHashes68 {
+    /// Returns an iterator over the `(hash, type, size)` of all announced transactions.
+    pub fn iter(&self) -> impl Iterator<Item = (&H256, &u8, &usize)> + '_ {
+        self.hashes
+            .iter()
+            .zip(self.types.iter())
+            .zip(self.sizes.iter())
+            .map(|((h, t), s)| (h, t, s))
+    }
+
+    fn payload_length(&self) -> usize {
+        self.types.as_slice().length() + self.sizes.length() + self.hashes.length()
+    }
+}
+
+impl Encodable for NewPooledTransactionHashes68 {
+    fn encode(&self, out: &mut dyn BufMut) {
+        RlpHeader { list: true, payload_length: self.payload_length() }.encode(out);
+        self.types.as_slice().encode(out);
+        self.sizes.encode(out);
+        self.hashes.encode(out);
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + length_of_length(payload_length)
+    }
+}
+
+impl Decodable for NewPooledTransactionHashes68 {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let header = RlpHeader::decode(buf)?;
+        if !header.list {
+            return Err(DecodeError::UnexpectedString)
+        }
+        let started_len = buf.len();
+        let types = BytesMut::decode(buf)?.to_vec();
+        let sizes = Vec::<usize>::decode(buf)?;
+        let hashes = Vec::<H256>::decode(buf)?;
+        if started_len - buf.len() != header.payload_length {
+            return Err(DecodeError::ListLengthMismatch {
+                expected: header.payload_length,
+                got: started_len - buf.len(),
+            })
+        }
+        Ok(Self { types, sizes, hashes })
     }
 }
 
@@ -145,4 +289,25 @@ mod tests {
         let latest = blocks.latest().unwrap();
         assert_eq!(latest.number, 100);
     }
+
+    #[test]
+    fn eth68_announcement_roundtrip() {
+        let msg = NewPooledTransactionHashes68 {
+            types: vec![0x00, 0x02],
+            sizes: vec![0x6e, 0x01f4],
+            hashes: vec![H256::repeat_byte(0x01), H256::repeat_byte(0x02)],
+        };
+        let mut buf = Vec::new();
+        msg.encode(&mut buf);
+        assert_eq!(buf.len(), msg.length());
+        // the types are encoded as a single byte string
+        assert_eq!(&buf[2..5], &[0x82, 0x00, 0x02]);
+
+        let decoded = NewPooledTransactionHashes68::decode(&mut &buf[..]).unwrap();
+        assert_eq!(decoded, msg);
+        assert_eq!(
+            decoded.iter().map(|(_, ty, size)| (*ty, *size)).collect::<Vec<_>>(),
+            vec![(0x00, 0x6e), (0x02, 0x01f4)]
+        );
+    }
 }
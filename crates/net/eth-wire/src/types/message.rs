@@ -1,8 +1,9 @@
 #![allow(missing_docs)]
 use super::{
-    broadcast::NewBlockHashes, BlockBodies, BlockHeaders, GetBlockBodies, GetBlockHeaders,
-    GetNodeData, GetPooledTransactions, GetReceipts, NewBlock, NewPooledTransactionHashes,
-    NodeData, PooledTransactions, Receipts, Status, Transactions,
+    broadcast::NewBlockHashes, BlockBodies, BlockHeaders, EthVersion, GetBlockBodies,
+    GetBlockHeaders, GetNodeData, GetPooledTransactions, GetReceipts, NewBlock,
+    NewPooledTransactionHashes66, NewPooledTransactionHashes68, NodeData, PooledTransactions,
+    Receipts, Status, Transactions,
 };
 use crate::SharedTransactions;
 use bytes::{Buf, BufMut};
@@ -21,11 +22,15 @@ pub struct ProtocolMessage {
 }
 
 impl ProtocolMessage {
-    /// Create a new ProtocolMessage from a message type and message rlp bytes.
+    /// Create a new ProtocolMessage from the message rlp bytes, using the first byte to determine
+    /// the message type.
+    ///
+    /// Messages are decoded as defined by the given version of the `eth` protocol.
     pub fn decode_message(
-        message_type: EthMessageID,
+        version: EthVersion,
         buf: &mut &[u8],
     ) -> Result<Self, reth_rlp::DecodeError> {
+        let message_type = EthMessageID::decode(buf)?;
         let message = match message_type {
             EthMessageID::Status => EthMessage::Status(Status::decode(buf)?),
             EthMessageID::NewBlockHashes => {
@@ -33,9 +38,14 @@ impl ProtocolMessage {
             }
             EthMessageID::NewBlock => EthMessage::NewBlock(Box::new(NewBlock::decode(buf)?)),
             EthMessageID::Transactions => EthMessage::Transactions(Transactions::decode(buf)?),
-            EthMessageID::NewPooledTransactionHashes => {
-                EthMessage::NewPooledTransactionHashes(NewPooledTransactionHashes::decode(buf)?)
-            }
+            EthMessageID::NewPooledTransactionHashes => match version {
+                EthVersion::Eth66 | EthVersion::Eth67 => EthMessage::NewPooledTransactionHashes66(
+                    NewPooledTransactionHashes66::decode(buf)?,
+                ),
+                EthVersion::Eth68 => EthMessage::NewPooledTransactionHashes68(
+                    NewPooledTransactionHashes68::decode(buf)?,
+                ),
+            },
             EthMessageID::GetBlockHeaders => {
                 let request_pair = RequestPair::<GetBlockHeaders>::decode(buf)?;
                 EthMessage::GetBlockHeaders(request_pair)
@@ -93,15 +103,6 @@ impl Encodable for ProtocolMessage {
     }
 }
 
-/// Decodes a protocol message from bytes, using the first byte to determine the message type.
-/// This decodes `eth/66` request ids for each message type.
-impl Decodable for ProtocolMessage {
-    fn decode(buf: &mut &[u8]) -> Result<Self, reth_rlp::DecodeError> {
-        let message_type = EthMessageID::decode(buf)?;
-        Self::decode_message(message_type, buf)
-    }
-}
-
 impl From<EthMessage> for ProtocolMessage {
     fn from(message: EthMessage) -> Self {
         ProtocolMessage { message_type: message.message_id(), message }
@@ -133,7 +134,7 @@ impl From<EthBroadcastMessage> for ProtocolBroadcastMessage {
     }
 }
 
-/// Represents a message in the eth wire protocol, versions 66 and 67.
+/// Represents a message in the eth wire protocol, versions 66, 67 and 68.
 ///
 /// The ethereum wire protocol is a set of messages that are broadcasted to the network in two
 /// styles:
@@ -152,7 +153,10 @@ pub enum EthMessage {
     NewBlockHashes(NewBlockHashes),
     NewBlock(Box<NewBlock>),
     Transactions(Transactions),
-    NewPooledTransactionHashes(NewPooledTransactionHashes),
+    /// Transaction announcement of `eth/66` and `eth/67`
+    NewPooledTransactionHashes66(NewPooledTransactionHashes66),
+    /// Transaction announcement of `eth/68`, including the types and sizes of the transactions
+    NewPooledTransactionHashes68(NewPooledTransactionHashes68),
 
     // The following messages are request-response message pairs
     GetBlockHeaders(RequestPair<GetBlockHeaders>),
@@ -175,7 +179,8 @@ impl EthMessage {
             EthMessage::NewBlockHashes(_) => EthMessageID::NewBlockHashes,
             EthMessage::NewBlock(_) => EthMessageID::NewBlock,
             EthMessage::Transactions(_) => EthMessageID::Transactions,
-            EthMessage::NewPooledTransactionHashes(_) => EthMessageID::NewPooledTransactionHashes,
+            EthMessage::NewPooledTransactionHashes66(_) |
+            EthMessage::NewPooledTransactionHashes68(_) => EthMessageID::NewPooledTransactionHashes,
             EthMessage::GetBlockHeaders(_) => EthMessageID::GetBlockHeaders,
             EthMessage::BlockHeaders(_) => EthMessageID::BlockHeaders,
             EthMessage::GetBlockBodies(_) => EthMessageID::GetBlockBodies,
@@ -197,7 +202,8 @@ impl Encodable for EthMessage {
             EthMessage::NewBlockHashes(new_block_hashes) => new_block_hashes.encode(out),
             EthMessage::NewBlock(new_block) => new_block.encode(out),
             EthMessage::Transactions(transactions) => transactions.encode(out),
-            EthMessage::NewPooledTransactionHashes(hashes) => hashes.encode(out),
+            EthMessage::NewPooledTransactionHashes66(hashes) => hashes.encode(out),
+            EthMessage::NewPooledTransactionHashes68(hashes) => hashes.encode(out),
             EthMessage::GetBlockHeaders(request) => request.encode(out),
             EthMessage::BlockHeaders(headers) => headers.encode(out),
             EthMessage::GetBlockBodies(request) => request.encode(out),
@@ -216,7 +222,8 @@ impl Encodable for EthMessage {
             EthMessage::NewBlockHashes(new_block_hashes) => new_block_hashes.length(),
             EthMessage::NewBlock(new_block) => new_block.length(),
             EthMessage::Transactions(transactions) => transactions.length(),
-            EthMessage::NewPooledTransactionHashes(hashes) => hashes.length(),
+            EthMessage::NewPooledTransactionHashes66(hashes) => hashes.length(),
+            EthMessage::NewPooledTransactionHashes68(hashes) => hashes.length(),
             EthMessage::GetBlockHeaders(request) => request.length(),
             EthMessage::BlockHeaders(headers) => headers.length(),
             EthMessage::GetBlockBodies(request) => request.length(),
@@ -403,8 +410,10 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::types::message::RequestPair;
+    use super::{EthMessage, ProtocolMessage};
+    use crate::types::{message::RequestPair, EthVersion, NewPooledTransactionHashes68};
     use hex_literal::hex;
+    use reth_primitives::H256;
     use reth_rlp::{Decodable, Encodable};
 
     fn encode<T: Encodable>(value: T) -> Vec<u8> {
@@ -438,4 +447,18 @@ mod test {
         assert_eq!(expected.length(), raw_pair.len());
         assert_eq!(expected, got);
     }
+
+    #[test]
+    fn decode_announcement_by_version() {
+        let msg = EthMessage::NewPooledTransactionHashes68(NewPooledTransactionHashes68 {
+            types: vec![0x02],
+            sizes: vec![120],
+            hashes: vec![H256::random()],
+        });
+        let raw = encode(ProtocolMessage::from(msg.clone()));
+
+        let decoded = ProtocolMessage::decode_message(EthVersion::Eth68, &mut &raw[..]).unwrap();
+        assert_eq!(decoded.message, msg);
+        assert!(ProtocolMessage::decode_message(EthVersion::Eth67, &mut &raw[..]).is_err());
+    }
 }
@@ -0,0 +1,371 @@
+//! Implements the `snap/1` protocol messages: <https://github.com/ethereum/devp2p/blob/master/caps/snap.md>
+//!
+//! Unlike `eth/66` messages, the request id is the first field of every `snap` message.
+use crate::capability::{Capability, Protocol};
+use bytes::{Buf, BufMut};
+use reth_codecs::derive_arbitrary;
+use reth_primitives::{Bytes, H256};
+use reth_rlp::{Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The number of message ids reserved by `snap/1`.
+pub const SNAP_PROTOCOL_MESSAGES: u8 = 8;
+
+/// Returns the `snap/1` [`Protocol`].
+pub fn snap_protocol() -> Protocol {
+    Protocol::new(Capability::new("snap".into(), 1), SNAP_PROTOCOL_MESSAGES)
+}
+
+/// Requests an unknown number of accounts from a given account trie, starting at the specified
+/// account hash and capped by the maximum allowed response size in bytes.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetAccountRange {
+    /// Request id to match up responses with.
+    pub request_id: u64,
+    /// Root hash of the account trie to serve.
+    pub root_hash: H256,
+    /// Account hash of the first to retrieve.
+    pub starting_hash: H256,
+    /// Account hash after which to stop serving data.
+    pub limit_hash: H256,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// An account in a [`AccountRange`] response.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountData {
+    /// Hash of the account address.
+    pub hash: H256,
+    /// The account in "slim" RLP encoding, with an empty storage root and code hash if the account
+    /// has no storage or code.
+    pub body: Bytes,
+}
+
+/// The response to [`GetAccountRange`], containing consecutive accounts and the merkle proofs for
+/// the boundaries of the range.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountRange {
+    /// Id of the request this is a response for.
+    pub request_id: u64,
+    /// List of consecutive accounts from the trie.
+    pub accounts: Vec<AccountData>,
+    /// List of trie nodes proving the account range.
+    pub proof: Vec<Bytes>,
+}
+
+/// Requests the storage slots of multiple accounts' storage tries.
+///
+/// The starting and limit hashes only apply to the first and last account respectively and may be
+/// empty.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetStorageRanges {
+    /// Request id to match up responses with.
+    pub request_id: u64,
+    /// Root hash of the account trie to serve.
+    pub root_hash: H256,
+    /// Account hashes of the storage tries to serve.
+    pub account_hashes: Vec<H256>,
+    /// Storage slot hash of the first to retrieve.
+    pub starting_hash: Bytes,
+    /// Storage slot hash after which to stop serving.
+    pub limit_hash: Bytes,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// A storage slot in a [`StorageRanges`] response.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StorageData {
+    /// Hash of the storage slot key.
+    pub hash: H256,
+    /// RLP encoded value of the storage slot.
+    pub data: Bytes,
+}
+
+/// The response to [`GetStorageRanges`], containing consecutive storage slots for the requested
+/// accounts and optionally the merkle proof for the last range.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StorageRanges {
+    /// Id of the request this is a response for.
+    pub request_id: u64,
+    /// List of list of consecutive slots from the tries, one list per account.
+    pub slots: Vec<Vec<StorageData>>,
+    /// List of trie nodes proving the last slot range, if it's incomplete.
+    pub proof: Vec<Bytes>,
+}
+
+/// Requests a number of contract byte-codes by hash.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetByteCodes {
+    /// Request id to match up responses with.
+    pub request_id: u64,
+    /// Code hashes to retrieve the code for.
+    pub hashes: Vec<H256>,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetByteCodes`], containing the codes in request order.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ByteCodes {
+    /// Id of the request this is a response for.
+    pub request_id: u64,
+    /// The requested bytecodes in order.
+    pub codes: Vec<Bytes>,
+}
+
+/// Requests a number of state (either account or storage) trie nodes by path.
+///
+/// Every path set is either a single compact encoded path into the account trie, or an account
+/// hash followed by compact encoded paths into that account's storage trie.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetTrieNodes {
+    /// Request id to match up responses with.
+    pub request_id: u64,
+    /// Root hash of the account trie to serve.
+    pub root_hash: H256,
+    /// Trie paths to retrieve the nodes for, grouped by account.
+    pub paths: Vec<Vec<Bytes>>,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetTrieNodes`], containing the nodes in request order.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrieNodes {
+    /// Id of the request this is a response for.
+    pub request_id: u64,
+    /// The requested trie nodes in order.
+    pub nodes: Vec<Bytes>,
+}
+
+/// Represents message IDs for `snap/1` protocol messages.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum SnapMessageID {
+    GetAccountRange = 0x00,
+    AccountRange = 0x01,
+    GetStorageRanges = 0x02,
+    StorageRanges = 0x03,
+    GetByteCodes = 0x04,
+    ByteCodes = 0x05,
+    GetTrieNodes = 0x06,
+    TrieNodes = 0x07,
+}
+
+impl Encodable for SnapMessageID {
+    fn encode(&self, out: &mut dyn BufMut) {
+        out.put_u8(*self as u8);
+    }
+    fn length(&self) -> usize {
+        1
+    }
+}
+
+impl Decodable for SnapMessageID {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let id = buf.first().ok_or(DecodeError::InputTooShort)?;
+        let id = match id {
+            0x00 => SnapMessageID::GetAccountRange,
+            0x01 => SnapMessageID::AccountRange,
+            0x02 => SnapMessageID::GetStorageRanges,
+            0x03 => SnapMessageID::StorageRanges,
+            0x04 => SnapMessageID::GetByteCodes,
+            0x05 => SnapMessageID::ByteCodes,
+            0x06 => SnapMessageID::GetTrieNodes,
+            0x07 => SnapMessageID::TrieNodes,
+            _ => return Err(DecodeError::Custom("Invalid message ID")),
+        };
+        buf.advance(1);
+        Ok(id)
+    }
+}
+
+/// A `snap/1` protocol message, prefixed with its message id when encoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(missing_docs)]
+pub enum SnapMessage {
+    GetAccountRange(GetAccountRange),
+    AccountRange(AccountRange),
+    GetStorageRanges(GetStorageRanges),
+    StorageRanges(StorageRanges),
+    GetByteCodes(GetByteCodes),
+    ByteCodes(ByteCodes),
+    GetTrieNodes(GetTrieNodes),
+    TrieNodes(TrieNodes),
+}
+
+// === impl SnapMessage ===
+
+impl SnapMessage {
+    /// Returns the message's ID.
+    pub fn message_id(&self) -> SnapMessageID {
+        match self {
+            SnapMessage::GetAccountRange(_) => SnapMessageID::GetAccountRange,
+            SnapMessage::AccountRange(_) => SnapMessageID::AccountRange,
+            SnapMessage::GetStorageRanges(_) => SnapMessageID::GetStorageRanges,
+            SnapMessage::StorageRanges(_) => SnapMessageID::StorageRanges,
+            SnapMessage::GetByteCodes(_) => SnapMessageID::GetByteCodes,
+            SnapMessage::ByteCodes(_) => SnapMessageID::ByteCodes,
+            SnapMessage::GetTrieNodes(_) => SnapMessageID::GetTrieNodes,
+            SnapMessage::TrieNodes(_) => SnapMessageID::TrieNodes,
+        }
+    }
+
+    /// Returns the request id of the message.
+    pub fn request_id(&self) -> u64 {
+        match self {
+            SnapMessage::GetAccountRange(msg) => msg.request_id,
+            SnapMessage::AccountRange(msg) => msg.request_id,
+            SnapMessage::GetStorageRanges(msg) => msg.request_id,
+            SnapMessage::StorageRanges(msg) => msg.request_id,
+            SnapMessage::GetByteCodes(msg) => msg.request_id,
+            SnapMessage::ByteCodes(msg) => msg.request_id,
+            SnapMessage::GetTrieNodes(msg) => msg.request_id,
+            SnapMessage::TrieNodes(msg) => msg.request_id,
+        }
+    }
+}
+
+/// Encodes the message id followed by the RLP encoded message.
+impl Encodable for SnapMessage {
+    fn encode(&self, out: &mut dyn BufMut) {
+        self.message_id().encode(out);
+        match self {
+            SnapMessage::GetAccountRange(msg) => msg.encode(out),
+            SnapMessage::AccountRange(msg) => msg.encode(out),
+            SnapMessage::GetStorageRanges(msg) => msg.encode(out),
+            SnapMessage::StorageRanges(msg) => msg.encode(out),
+            SnapMessage::GetByteCodes(msg) => msg.encode(out),
+            SnapMessage::ByteCodes(msg) => msg.encode(out),
+            SnapMessage::GetTrieNodes(msg) => msg.encode(out),
+            SnapMessage::TrieNodes(msg) => msg.encode(out),
+        }
+    }
+
+    fn length(&self) -> usize {
+        let payload = match self {
+            SnapMessage::GetAccountRange(msg) => msg.length(),
+            SnapMessage::AccountRange(msg) => msg.length(),
+            SnapMessage::GetStorageRanges(msg) => msg.length(),
+            SnapMessage::StorageRanges(msg) => msg.length(),
+            SnapMessage::GetByteCodes(msg) => msg.length(),
+            SnapMessage::ByteCodes(msg) => msg.length(),
+            SnapMessage::GetTrieNodes(msg) => msg.length(),
+            SnapMessage::TrieNodes(msg) => msg.length(),
+        };
+        self.message_id().length() + payload
+    }
+}
+
+/// Decodes a message from bytes, using the first byte to determine the message type.
+impl Decodable for SnapMessage {
+    fn decode(buf: &mut &[u8]) -> Result<Self, DecodeError> {
+        let msg = match SnapMessageID::decode(buf)? {
+            SnapMessageID::GetAccountRange => {
+                SnapMessage::GetAccountRange(GetAccountRange::decode(buf)?)
+            }
+            SnapMessageID::AccountRange => SnapMessage::AccountRange(AccountRange::decode(buf)?),
+            SnapMessageID::GetStorageRanges => {
+                SnapMessage::GetStorageRanges(GetStorageRanges::decode(buf)?)
+            }
+            SnapMessageID::StorageRanges => SnapMessage::StorageRanges(StorageRanges::decode(buf)?),
+            SnapMessageID::GetByteCodes => SnapMessage::GetByteCodes(GetByteCodes::decode(buf)?),
+            SnapMessageID::ByteCodes => SnapMessage::ByteCodes(ByteCodes::decode(buf)?),
+            SnapMessageID::GetTrieNodes => SnapMessage::GetTrieNodes(GetTrieNodes::decode(buf)?),
+            SnapMessageID::TrieNodes => SnapMessage::TrieNodes(TrieNodes::decode(buf)?),
+        };
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(msg: SnapMessage) {
+        let mut buf = Vec::new();
+        msg.encode(&mut buf);
+        assert_eq!(buf.len(), msg.length());
+        assert_eq!(buf[0], msg.message_id() as u8);
+        let decoded = SnapMessage::decode(&mut &buf[..]).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn snap_message_roundtrip() {
+        roundtrip(SnapMessage::GetAccountRange(GetAccountRange {
+            request_id: 1,
+            root_hash: H256::random(),
+            starting_hash: H256::zero(),
+            limit_hash: H256::repeat_byte(0xff),
+            response_bytes: 512 * 1024,
+        }));
+        roundtrip(SnapMessage::AccountRange(AccountRange {
+            request_id: 1,
+            accounts: vec![AccountData { hash: H256::random(), body: Bytes::from(vec![0xc4]) }],
+            proof: vec![Bytes::from(vec![0xc0])],
+        }));
+        roundtrip(SnapMessage::GetStorageRanges(GetStorageRanges {
+            request_id: 2,
+            root_hash: H256::random(),
+            account_hashes: vec![H256::random(), H256::random()],
+            starting_hash: Bytes::default(),
+            limit_hash: Bytes::default(),
+            response_bytes: 1024,
+        }));
+        roundtrip(SnapMessage::StorageRanges(StorageRanges {
+            request_id: 2,
+            slots: vec![
+                vec![StorageData { hash: H256::random(), data: Bytes::from(vec![0x01]) }],
+                vec![],
+            ],
+            proof: vec![],
+        }));
+        roundtrip(SnapMessage::GetByteCodes(GetByteCodes {
+            request_id: 3,
+            hashes: vec![H256::random()],
+            response_bytes: 1024,
+        }));
+        roundtrip(SnapMessage::ByteCodes(ByteCodes {
+            request_id: 3,
+            codes: vec![Bytes::from(vec![0x60, 0x00])],
+        }));
+        roundtrip(SnapMessage::GetTrieNodes(GetTrieNodes {
+            request_id: 4,
+            root_hash: H256::random(),
+            paths: vec![vec![Bytes::from(vec![0x00])], vec![H256::random().0.to_vec().into()]],
+            response_bytes: 1024,
+        }));
+        roundtrip(SnapMessage::TrieNodes(TrieNodes {
+            request_id: 4,
+            nodes: vec![Bytes::from(vec![0xc0])],
+        }));
+    }
+}
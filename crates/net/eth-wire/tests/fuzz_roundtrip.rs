@@ -51,8 +51,8 @@ pub mod fuzz_rlp {
     use reth_eth_wire::{
         BlockBodies, BlockHeaders, DisconnectReason, GetBlockBodies, GetBlockHeaders, GetNodeData,
         GetPooledTransactions, GetReceipts, HelloMessage, NewBlock, NewBlockHashes,
-        NewPooledTransactionHashes, NodeData, P2PMessage, PooledTransactions, Receipts, Status,
-        Transactions,
+        NewPooledTransactionHashes66, NewPooledTransactionHashes68, NodeData, P2PMessage,
+        PooledTransactions, Receipts, Status, Transactions,
     };
     use reth_primitives::{BlockHashOrNumber, TransactionSigned};
     use reth_rlp::{RlpDecodableWrapper, RlpEncodableWrapper};
@@ -137,7 +137,8 @@ pub mod fuzz_rlp {
     fuzz_type_and_name!(GetBlockBodies, fuzz_GetBlockBodies);
     fuzz_type_and_name!(BlockBodies, fuzz_BlockBodies);
     fuzz_type_and_name!(NewBlock, fuzz_NewBlock);
-    fuzz_type_and_name!(NewPooledTransactionHashes, fuzz_NewPooledTransactionHashes);
+    fuzz_type_and_name!(NewPooledTransactionHashes66, fuzz_NewPooledTransactionHashes66);
+    fuzz_type_and_name!(NewPooledTransactionHashes68, fuzz_NewPooledTransactionHashes68);
     fuzz_type_and_name!(GetPooledTransactions, fuzz_GetPooledTransactions);
     fuzz_type_and_name!(PooledTransactions, fuzz_PooledTransactions);
     fuzz_type_and_name!(GetNodeData, fuzz_GetNodeData);
@@ -1,5 +1,5 @@
 //! Decoding tests for [`NewPooledTransactions`]
-use reth_eth_wire::NewPooledTransactionHashes;
+use reth_eth_wire::NewPooledTransactionHashes66;
 use reth_rlp::Decodable;
 use std::{fs, path::PathBuf};
 
@@ -9,5 +9,5 @@ fn decode_new_pooled_transaction_hashes_network() {
         .join("testdata/new_pooled_transactions_network_rlp");
     let data = fs::read_to_string(network_data_path).expect("Unable to read file");
     let hex_data = hex::decode(data.trim()).unwrap();
-    let _txs = NewPooledTransactionHashes::decode(&mut &hex_data[..]).unwrap();
+    let _txs = NewPooledTransactionHashes66::decode(&mut &hex_data[..]).unwrap();
 }
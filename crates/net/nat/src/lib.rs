@@ -5,14 +5,18 @@
     attr(deny(warnings, rust_2018_idioms), allow(dead_code, unused_variables))
 ))]
 
-//! Helpers for resolving the external IP.
+//! Helpers for resolving the external IP and mapping ports on the gateway.
 
+pub mod mapping;
+pub mod natpmp;
+
+use crate::{mapping::MappingGateway, natpmp::NatPmpClient};
 use igd::aio::search_gateway;
 use pin_project_lite::pin_project;
 use std::{
     fmt,
     future::{poll_fn, Future},
-    net::{AddrParseError, IpAddr},
+    net::{AddrParseError, IpAddr, SocketAddr},
     pin::Pin,
     str::FromStr,
     task::{ready, Context, Poll},
@@ -32,6 +36,8 @@ pub enum NatResolver {
     Any,
     /// Resolve via Upnp
     Upnp,
+    /// Resolve via the NAT-PMP server running on the given gateway
+    NatPmp(IpAddr),
     /// Resolve external IP via [public_ip::Resolver]
     PublicIp,
     /// Use the given [IpAddr]
@@ -47,6 +53,19 @@ impl NatResolver {
     pub async fn external_addr(self) -> Option<IpAddr> {
         external_addr_with(self).await
     }
+
+    /// Returns the gateway that can map ports, if the resolver talks to one.
+    ///
+    /// See also [PortMappingHandle](mapping::PortMappingHandle).
+    pub fn mapping_gateway(&self) -> Option<MappingGateway> {
+        match self {
+            NatResolver::Any | NatResolver::Upnp => Some(MappingGateway::Upnp),
+            NatResolver::NatPmp(gateway) => {
+                Some(MappingGateway::NatPmp(SocketAddr::new(*gateway, natpmp::NATPMP_PORT)))
+            }
+            NatResolver::PublicIp | NatResolver::ExternalIp(_) | NatResolver::None => None,
+        }
+    }
 }
 
 impl fmt::Display for NatResolver {
@@ -54,6 +73,7 @@ impl fmt::Display for NatResolver {
         match self {
             NatResolver::Any => f.write_str("any"),
             NatResolver::Upnp => f.write_str("upnp"),
+            NatResolver::NatPmp(gateway) => write!(f, "natpmp:{gateway}"),
             NatResolver::PublicIp => f.write_str("publicip"),
             NatResolver::ExternalIp(ip) => write!(f, "extip:{ip}"),
             NatResolver::None => f.write_str("none"),
@@ -84,6 +104,8 @@ impl FromStr for NatResolver {
             s => {
                 if let Some(ip) = s.strip_prefix("extip:") {
                     NatResolver::ExternalIp(ip.parse::<IpAddr>()?)
+                } else if let Some(gateway) = s.strip_prefix("natpmp:") {
+                    NatResolver::NatPmp(gateway.parse::<IpAddr>()?)
                 } else {
                     return Err(ParseNatResolverError::UnknonwVariant(format!(
                         "Unknown Nat Resolver: {s}"
@@ -177,6 +199,7 @@ pub async fn external_addr_with(resolver: NatResolver) -> Option<IpAddr> {
             .await
         }
         NatResolver::Upnp => resolve_external_ip_upnp().await,
+        NatResolver::NatPmp(gateway) => resolve_external_ip_natpmp(gateway).await,
         NatResolver::PublicIp => resolve_external_ip().await,
         NatResolver::ExternalIp(ip) => Some(ip),
         NatResolver::None => None,
@@ -244,6 +267,18 @@ async fn resolve_external_ip_upnp() -> Option<IpAddr> {
         .ok()
 }
 
+async fn resolve_external_ip_natpmp(gateway: IpAddr) -> Option<IpAddr> {
+    NatPmpClient::new(gateway)
+        .external_address()
+        .await
+        .map_err(|err| {
+            warn!(target: "net::nat", %err, "failed to resolve external ip via NAT-PMP");
+            err
+        })
+        .ok()
+        .map(IpAddr::V4)
+}
+
 async fn resolve_external_ip() -> Option<IpAddr> {
     public_ip::addr().await
 }
@@ -282,5 +317,10 @@ mod tests {
         let s = "extip:0.0.0.0";
         assert_eq!(ip, s.parse().unwrap());
         assert_eq!(ip.to_string().as_str(), s);
+
+        let natpmp = NatResolver::NatPmp(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
+        let s = "natpmp:192.168.0.1";
+        assert_eq!(natpmp, s.parse().unwrap());
+        assert_eq!(natpmp.to_string().as_str(), s);
     }
 }
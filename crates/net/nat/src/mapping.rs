@@ -0,0 +1,479 @@
+//! Port mappings on the gateway, so that the node is reachable from outside the NAT.
+//!
+//! Mappings are requested via UPnP IGD or NAT-PMP, renewed before their lease expires and removed
+//! once the [PortMappingHandle] is dropped.
+
+use crate::natpmp::NatPmpClient;
+use igd::aio::search_gateway;
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    net::UdpSocket,
+    sync::{mpsc, oneshot, watch},
+};
+use tracing::{debug, trace, warn};
+
+/// The default lease of a port mapping, mappings are renewed after half of the lease.
+pub const DEFAULT_MAPPING_LEASE: Duration = Duration::from_secs(60 * 60);
+
+/// How long to wait before trying again if not all mappings could be created.
+///
+/// The interval doubles with every consecutive failure, up to [MAX_RETRY_INTERVAL].
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The maximum interval between two attempts to create the mappings.
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The description of the mappings created via UPnP.
+const MAPPING_DESCRIPTION: &str = "reth";
+
+/// The transport protocol of a port mapping.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum PortMappingProtocol {
+    /// TCP, used by RLPx.
+    Tcp,
+    /// UDP, used by discovery.
+    Udp,
+}
+
+impl fmt::Display for PortMappingProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortMappingProtocol::Tcp => f.write_str("TCP"),
+            PortMappingProtocol::Udp => f.write_str("UDP"),
+        }
+    }
+}
+
+impl From<PortMappingProtocol> for igd::PortMappingProtocol {
+    fn from(protocol: PortMappingProtocol) -> Self {
+        match protocol {
+            PortMappingProtocol::Tcp => igd::PortMappingProtocol::TCP,
+            PortMappingProtocol::Udp => igd::PortMappingProtocol::UDP,
+        }
+    }
+}
+
+/// A local port that should be reachable from outside the NAT.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct PortMappingRequest {
+    /// The protocol to map.
+    pub protocol: PortMappingProtocol,
+    /// The local port, this is also requested as the external port.
+    pub port: u16,
+}
+
+impl PortMappingRequest {
+    /// Creates a request for the local TCP port.
+    pub fn tcp(port: u16) -> Self {
+        Self { protocol: PortMappingProtocol::Tcp, port }
+    }
+
+    /// Creates a request for the local UDP port.
+    pub fn udp(port: u16) -> Self {
+        Self { protocol: PortMappingProtocol::Udp, port }
+    }
+}
+
+/// A port mapping granted by the gateway.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct PortMapping {
+    /// The protocol of the mapping.
+    pub protocol: PortMappingProtocol,
+    /// The local port.
+    pub internal_port: u16,
+    /// The port on the gateway that is forwarded to the local port.
+    pub external_port: u16,
+    /// How long the mapping is valid.
+    pub lifetime: Duration,
+}
+
+/// The gateway to request port mappings from.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MappingGateway {
+    /// Search for an UPnP Internet Gateway Device in the local network.
+    Upnp,
+    /// The NAT-PMP server at the given address.
+    NatPmp(SocketAddr),
+}
+
+/// The external address of the node, reported whenever it changes.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PortMappingUpdate {
+    /// The external IP of the gateway, if it could be resolved.
+    pub external_ip: Option<IpAddr>,
+    /// All active mappings.
+    pub mappings: Vec<PortMapping>,
+}
+
+impl PortMappingUpdate {
+    /// Returns the external port that is mapped to the local port.
+    pub fn external_port(&self, protocol: PortMappingProtocol, internal_port: u16) -> Option<u16> {
+        self.mappings
+            .iter()
+            .find(|m| m.protocol == protocol && m.internal_port == internal_port)
+            .map(|m| m.external_port)
+    }
+}
+
+/// A handle to a task that maintains port mappings on the gateway.
+///
+/// The task creates the requested mappings, renews them before they expire and reports the
+/// external address via [PortMappingHandle::poll_update] whenever it changes. All mappings are
+/// removed when the handle is dropped or [PortMappingHandle::shutdown] is called.
+#[derive(Debug)]
+pub struct PortMappingHandle {
+    requests: watch::Sender<Vec<PortMappingRequest>>,
+    updates: mpsc::UnboundedReceiver<PortMappingUpdate>,
+    shutdown: Option<oneshot::Sender<oneshot::Sender<()>>>,
+}
+
+// === impl PortMappingHandle ===
+
+impl PortMappingHandle {
+    /// Spawns a task that maps the requested ports on the gateway.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn(
+        gateway: MappingGateway,
+        requests: Vec<PortMappingRequest>,
+        lease: Duration,
+    ) -> Self {
+        let (requests_tx, requests_rx) = watch::channel(requests);
+        let (updates_tx, updates_rx) = mpsc::unbounded_channel();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let task = PortMappingTask {
+            gateway,
+            lease,
+            requests: requests_rx,
+            updates: updates_tx,
+            current: Default::default(),
+            failures: 0,
+        };
+        tokio::spawn(task.run(shutdown_rx));
+        Self { requests: requests_tx, updates: updates_rx, shutdown: Some(shutdown_tx) }
+    }
+
+    /// Replaces the requested ports.
+    ///
+    /// Mappings of ports that are no longer requested are removed.
+    pub fn set_requests(&self, requests: Vec<PortMappingRequest>) {
+        self.requests.send_if_modified(|current| {
+            if *current == requests {
+                return false
+            }
+            *current = requests;
+            true
+        });
+    }
+
+    /// Polls for the next change of the external address.
+    pub fn poll_update(&mut self, cx: &mut Context<'_>) -> Poll<Option<PortMappingUpdate>> {
+        self.updates.poll_recv(cx)
+    }
+
+    /// Waits for the next change of the external address.
+    pub async fn next_update(&mut self) -> Option<PortMappingUpdate> {
+        self.updates.recv().await
+    }
+
+    /// Removes all mappings and waits until the gateway acknowledged the removal.
+    pub async fn shutdown(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let (tx, rx) = oneshot::channel();
+            if shutdown.send(tx).is_ok() {
+                let _ = rx.await;
+            }
+        }
+    }
+}
+
+/// Maintains the mappings of a [PortMappingHandle].
+struct PortMappingTask {
+    gateway: MappingGateway,
+    lease: Duration,
+    requests: watch::Receiver<Vec<PortMappingRequest>>,
+    updates: mpsc::UnboundedSender<PortMappingUpdate>,
+    /// The last reported state.
+    current: PortMappingUpdate,
+    /// The number of consecutive attempts that did not create all mappings.
+    failures: u32,
+}
+
+impl PortMappingTask {
+    async fn run(mut self, mut shutdown: oneshot::Receiver<oneshot::Sender<()>>) {
+        loop {
+            let renew_after = self.renew().await;
+            tokio::select! {
+                res = &mut shutdown => {
+                    self.remove_all().await;
+                    if let Ok(ack) = res {
+                        let _ = ack.send(());
+                    }
+                    return
+                }
+                res = self.requests.changed() => {
+                    if res.is_err() {
+                        // the handle was dropped
+                        self.remove_all().await;
+                        return
+                    }
+                }
+                _ = tokio::time::sleep(renew_after) => {}
+            }
+        }
+    }
+
+    /// Creates or renews all requested mappings and removes the ones that are no longer requested.
+    ///
+    /// Returns when to renew the mappings.
+    async fn renew(&mut self) -> Duration {
+        let requests = self.requests.borrow().clone();
+
+        let stale = self
+            .current
+            .mappings
+            .iter()
+            .filter(|m| {
+                !requests.iter().any(|r| r.protocol == m.protocol && r.port == m.internal_port)
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        if !stale.is_empty() {
+            remove_mappings(self.gateway, &stale).await;
+        }
+
+        let mut update = match add_mappings(self.gateway, &requests, self.lease).await {
+            Ok(update) => update,
+            Err(err) => {
+                // there is likely no gateway, so only the first failure is a warning
+                if self.failures == 0 {
+                    warn!(target: "net::nat", %err, "failed to create port mappings");
+                } else {
+                    debug!(target: "net::nat", %err, failures=self.failures, "failed to create port mappings");
+                }
+                PortMappingUpdate::default()
+            }
+        };
+        update.mappings.sort_by_key(|m| (m.internal_port, m.protocol == PortMappingProtocol::Udp));
+
+        let renew_after =
+            update.mappings.iter().map(|m| m.lifetime / 2).min().filter(|renew_after| {
+                !renew_after.is_zero() && update.mappings.len() == requests.len()
+            });
+        let renew_after = match renew_after {
+            Some(renew_after) => {
+                self.failures = 0;
+                renew_after
+            }
+            // nothing to map, wait for new requests
+            None if requests.is_empty() => {
+                self.failures = 0;
+                MAX_RETRY_INTERVAL
+            }
+            None => {
+                self.failures += 1;
+                retry_interval(self.failures)
+            }
+        };
+
+        if !same_addresses(&update, &self.current) {
+            debug!(target: "net::nat", external_ip=?update.external_ip, mappings=?update.mappings, "port mappings changed");
+            let _ = self.updates.send(update.clone());
+        }
+        self.current = update;
+
+        renew_after
+    }
+
+    /// Removes all mappings from the gateway.
+    async fn remove_all(&mut self) {
+        let mappings = std::mem::take(&mut self.current.mappings);
+        if !mappings.is_empty() {
+            remove_mappings(self.gateway, &mappings).await;
+        }
+    }
+}
+
+/// Returns how long to wait before the next attempt, after the given number of consecutive
+/// failures.
+fn retry_interval(failures: u32) -> Duration {
+    let factor = 1u32 << failures.saturating_sub(1).min(6);
+    (RETRY_INTERVAL * factor).min(MAX_RETRY_INTERVAL)
+}
+
+/// Returns true if both updates advertise the same external address.
+fn same_addresses(a: &PortMappingUpdate, b: &PortMappingUpdate) -> bool {
+    a.external_ip == b.external_ip &&
+        a.mappings.len() == b.mappings.len() &&
+        a.mappings.iter().zip(b.mappings.iter()).all(|(a, b)| {
+            a.protocol == b.protocol &&
+                a.internal_port == b.internal_port &&
+                a.external_port == b.external_port
+        })
+}
+
+/// Errors that prevent any mapping from being created.
+#[derive(Debug, thiserror::Error)]
+enum PortMappingError {
+    #[error("failed to find upnp gateway: {0}")]
+    Search(#[from] igd::SearchError),
+    #[error("failed to resolve local address: {0}")]
+    LocalAddr(#[from] std::io::Error),
+}
+
+/// Requests all mappings from the gateway.
+///
+/// Requests that are rejected by the gateway are logged and skipped.
+async fn add_mappings(
+    gateway: MappingGateway,
+    requests: &[PortMappingRequest],
+    lease: Duration,
+) -> Result<PortMappingUpdate, PortMappingError> {
+    let mut update = PortMappingUpdate::default();
+    match gateway {
+        MappingGateway::Upnp => {
+            let gateway = search_gateway(Default::default()).await?;
+            let local_ip = local_ip_towards(gateway.addr).await?;
+            let lease_secs = u32::try_from(lease.as_secs()).unwrap_or(u32::MAX);
+            for request in requests {
+                let local_addr = SocketAddr::new(local_ip, request.port);
+                match gateway
+                    .add_port(
+                        request.protocol.into(),
+                        request.port,
+                        local_addr,
+                        lease_secs,
+                        MAPPING_DESCRIPTION,
+                    )
+                    .await
+                {
+                    Ok(()) => update.mappings.push(PortMapping {
+                        protocol: request.protocol,
+                        internal_port: request.port,
+                        external_port: request.port,
+                        lifetime: lease,
+                    }),
+                    Err(err) => {
+                        warn!(target: "net::nat", ?err, ?request, "failed to add upnp port mapping")
+                    }
+                }
+            }
+            match gateway.get_external_ip().await {
+                Ok(ip) => update.external_ip = Some(ip),
+                Err(err) => {
+                    warn!(target: "net::nat", ?err, "failed to resolve external ip via upnp gateway")
+                }
+            }
+        }
+        MappingGateway::NatPmp(addr) => {
+            let client = NatPmpClient::with_addr(addr);
+            for request in requests {
+                match client.map_port(request.protocol, request.port, request.port, lease).await {
+                    Ok(mapping) => update.mappings.push(mapping),
+                    Err(err) => {
+                        warn!(target: "net::nat", %err, ?request, "failed to add NAT-PMP port mapping")
+                    }
+                }
+            }
+            match client.external_address().await {
+                Ok(ip) => update.external_ip = Some(ip.into()),
+                Err(err) => {
+                    warn!(target: "net::nat", %err, "failed to resolve external ip via NAT-PMP")
+                }
+            }
+        }
+    }
+    Ok(update)
+}
+
+/// Removes the mappings from the gateway.
+async fn remove_mappings(gateway: MappingGateway, mappings: &[PortMapping]) {
+    match gateway {
+        MappingGateway::Upnp => {
+            let gateway = match search_gateway(Default::default()).await {
+                Ok(gateway) => gateway,
+                Err(err) => {
+                    warn!(target: "net::nat", ?err, "failed to find upnp gateway");
+                    return
+                }
+            };
+            for mapping in mappings {
+                if let Err(err) =
+                    gateway.remove_port(mapping.protocol.into(), mapping.external_port).await
+                {
+                    warn!(target: "net::nat", ?err, ?mapping, "failed to remove upnp port mapping")
+                }
+            }
+        }
+        MappingGateway::NatPmp(addr) => {
+            let client = NatPmpClient::with_addr(addr);
+            for mapping in mappings {
+                if let Err(err) =
+                    client.remove_mapping(mapping.protocol, mapping.internal_port).await
+                {
+                    warn!(target: "net::nat", %err, ?mapping, "failed to remove NAT-PMP port mapping")
+                }
+            }
+        }
+    }
+    trace!(target: "net::nat", ?mappings, "removed port mappings");
+}
+
+/// Returns the local IP that is used to reach the given address.
+async fn local_ip_towards(addr: SocketAddr) -> std::io::Result<IpAddr> {
+    let bind_addr: SocketAddr = match addr {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(bind_addr).await?;
+    // no packets are sent, this only selects the route
+    socket.connect(addr).await?;
+    Ok(socket.local_addr()?.ip())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::natpmp::tests::{FakeGateway, EXTERNAL_IP};
+
+    #[test]
+    fn retry_interval_backs_off() {
+        assert_eq!(retry_interval(1), RETRY_INTERVAL);
+        assert_eq!(retry_interval(2), RETRY_INTERVAL * 2);
+        assert_eq!(retry_interval(3), RETRY_INTERVAL * 4);
+        assert_eq!(retry_interval(100), MAX_RETRY_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn maps_and_removes_ports() {
+        let gateway = FakeGateway::spawn().await;
+        let mut handle = PortMappingHandle::spawn(
+            MappingGateway::NatPmp(gateway.addr),
+            vec![PortMappingRequest::tcp(30303), PortMappingRequest::udp(30304)],
+            DEFAULT_MAPPING_LEASE,
+        );
+
+        let update = handle.next_update().await.unwrap();
+        assert_eq!(update.external_ip, Some(IpAddr::V4(EXTERNAL_IP)));
+        assert_eq!(update.external_port(PortMappingProtocol::Tcp, 30303), Some(31303));
+        assert_eq!(update.external_port(PortMappingProtocol::Udp, 30304), Some(31304));
+        assert_eq!(gateway.mapping(PortMappingProtocol::Tcp, 30303), Some(31303));
+
+        // changing the requested ports removes the old mapping
+        handle.set_requests(vec![PortMappingRequest::tcp(30305), PortMappingRequest::udp(30304)]);
+        let update = handle.next_update().await.unwrap();
+        assert_eq!(update.external_port(PortMappingProtocol::Tcp, 30303), None);
+        assert_eq!(update.external_port(PortMappingProtocol::Tcp, 30305), Some(31305));
+        assert_eq!(gateway.mapping(PortMappingProtocol::Tcp, 30303), None);
+
+        handle.shutdown().await;
+        assert!(gateway.mappings.lock().unwrap().is_empty());
+    }
+}
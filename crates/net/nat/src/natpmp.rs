@@ -0,0 +1,302 @@
+//! Minimal NAT-PMP client, see [RFC 6886](https://datatracker.ietf.org/doc/html/rfc6886).
+//!
+//! PCP ([RFC 6887](https://datatracker.ietf.org/doc/html/rfc6887)) is not supported. Many PCP
+//! gateways also answer NAT-PMP requests, but this is optional, see Appendix A of RFC 6887.
+
+use crate::mapping::{PortMapping, PortMappingProtocol};
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::net::UdpSocket;
+use tracing::trace;
+
+/// The port NAT-PMP servers listen on.
+pub const NATPMP_PORT: u16 = 5351;
+
+/// The NAT-PMP version, the first byte of every packet.
+const VERSION: u8 = 0;
+/// Opcode of the external address request.
+const OP_EXTERNAL_ADDRESS: u8 = 0;
+/// Opcode of the mapping request for UDP.
+const OP_MAP_UDP: u8 = 1;
+/// Opcode of the mapping request for TCP.
+const OP_MAP_TCP: u8 = 2;
+/// Added to the opcode of the request for the opcode of the response.
+const OP_RESPONSE: u8 = 128;
+
+/// The initial timeout of a request, doubled on every retransmission.
+const INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+/// How often a request is sent before giving up.
+///
+/// The RFC recommends 9 attempts, which takes over a minute in total. Gateways that support
+/// NAT-PMP typically answer immediately, so this gives up after ~4s.
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Errors of NAT-PMP requests.
+#[derive(Debug, thiserror::Error)]
+pub enum NatPmpError {
+    /// Failed to send or receive packets.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The gateway did not respond.
+    #[error("NAT-PMP gateway did not respond")]
+    Timeout,
+    /// The gateway responded with a malformed packet.
+    #[error("invalid NAT-PMP response")]
+    InvalidResponse,
+    /// The gateway responded with a non-zero result code.
+    #[error("NAT-PMP request failed with result code {0}")]
+    ResultCode(u16),
+}
+
+/// A client for a NAT-PMP server, usually the default gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NatPmpClient {
+    gateway: SocketAddr,
+}
+
+// === impl NatPmpClient ===
+
+impl NatPmpClient {
+    /// Creates a new client for the NAT-PMP server running on the gateway.
+    pub fn new(gateway: IpAddr) -> Self {
+        Self::with_addr(SocketAddr::new(gateway, NATPMP_PORT))
+    }
+
+    /// Creates a new client for the NAT-PMP server at the given address.
+    pub fn with_addr(gateway: SocketAddr) -> Self {
+        Self { gateway }
+    }
+
+    /// Returns the address of the NAT-PMP server.
+    pub fn gateway(&self) -> SocketAddr {
+        self.gateway
+    }
+
+    /// Requests the external IP address of the gateway.
+    pub async fn external_address(&self) -> Result<Ipv4Addr, NatPmpError> {
+        let response = self.request(&[VERSION, OP_EXTERNAL_ADDRESS]).await?;
+        if response.len() < 12 {
+            return Err(NatPmpError::InvalidResponse)
+        }
+        Ok(Ipv4Addr::new(response[8], response[9], response[10], response[11]))
+    }
+
+    /// Requests a mapping of the `external_port` to the `internal_port` of this host, valid for
+    /// `lifetime`.
+    ///
+    /// The gateway is free to assign a different external port and lifetime, the returned
+    /// [PortMapping] contains the values granted by the gateway.
+    pub async fn map_port(
+        &self,
+        protocol: PortMappingProtocol,
+        internal_port: u16,
+        external_port: u16,
+        lifetime: Duration,
+    ) -> Result<PortMapping, NatPmpError> {
+        let lifetime = u32::try_from(lifetime.as_secs()).unwrap_or(u32::MAX);
+        let response = self
+            .request(&encode_mapping_request(protocol, internal_port, external_port, lifetime))
+            .await?;
+        if response.len() < 16 {
+            return Err(NatPmpError::InvalidResponse)
+        }
+        Ok(PortMapping {
+            protocol,
+            internal_port: u16::from_be_bytes([response[8], response[9]]),
+            external_port: u16::from_be_bytes([response[10], response[11]]),
+            lifetime: Duration::from_secs(u32::from_be_bytes([
+                response[12],
+                response[13],
+                response[14],
+                response[15],
+            ]) as u64),
+        })
+    }
+
+    /// Removes the mapping of the `internal_port` of this host.
+    pub async fn remove_mapping(
+        &self,
+        protocol: PortMappingProtocol,
+        internal_port: u16,
+    ) -> Result<(), NatPmpError> {
+        // a mapping request with a lifetime of 0 deletes the mapping
+        self.request(&encode_mapping_request(protocol, internal_port, 0, 0)).await?;
+        Ok(())
+    }
+
+    /// Sends the request to the server and returns the response with a matching opcode.
+    ///
+    /// The request is retransmitted with an exponentially increasing timeout.
+    async fn request(&self, request: &[u8]) -> Result<Vec<u8>, NatPmpError> {
+        let bind_addr: SocketAddr = match self.gateway {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = UdpSocket::bind(bind_addr).await?;
+        socket.connect(self.gateway).await?;
+
+        let opcode = request[1] + OP_RESPONSE;
+        let mut timeout = INITIAL_TIMEOUT;
+        let mut buf = [0u8; 16];
+        for _ in 0..MAX_ATTEMPTS {
+            socket.send(request).await?;
+            let deadline = tokio::time::Instant::now() + timeout;
+            loop {
+                let len = match tokio::time::timeout_at(deadline, socket.recv(&mut buf)).await {
+                    Ok(res) => res?,
+                    Err(_) => break,
+                };
+                let response = &buf[..len];
+                if response.len() < 4 || response[0] != VERSION || response[1] != opcode {
+                    trace!(target: "net::nat", ?response, "ignoring unexpected NAT-PMP packet");
+                    continue
+                }
+                let result_code = u16::from_be_bytes([response[2], response[3]]);
+                if result_code != 0 {
+                    return Err(NatPmpError::ResultCode(result_code))
+                }
+                return Ok(response.to_vec())
+            }
+            timeout *= 2;
+        }
+        Err(NatPmpError::Timeout)
+    }
+}
+
+/// Encodes a mapping request packet.
+fn encode_mapping_request(
+    protocol: PortMappingProtocol,
+    internal_port: u16,
+    external_port: u16,
+    lifetime: u32,
+) -> [u8; 12] {
+    let opcode = match protocol {
+        PortMappingProtocol::Udp => OP_MAP_UDP,
+        PortMappingProtocol::Tcp => OP_MAP_TCP,
+    };
+    let mut packet = [0u8; 12];
+    packet[0] = VERSION;
+    packet[1] = opcode;
+    // bytes 2 and 3 are reserved
+    packet[4..6].copy_from_slice(&internal_port.to_be_bytes());
+    packet[6..8].copy_from_slice(&external_port.to_be_bytes());
+    packet[8..12].copy_from_slice(&lifetime.to_be_bytes());
+    packet
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    /// The external address announced by the [FakeGateway].
+    pub(crate) const EXTERNAL_IP: Ipv4Addr = Ipv4Addr::new(203, 0, 113, 7);
+
+    /// A NAT-PMP server on localhost that maps every internal port to the internal port + 1000.
+    #[derive(Clone)]
+    pub(crate) struct FakeGateway {
+        pub(crate) addr: SocketAddr,
+        /// All active mappings: (opcode, internal port) -> external port
+        pub(crate) mappings: Arc<Mutex<HashMap<(u8, u16), u16>>>,
+    }
+
+    impl FakeGateway {
+        pub(crate) async fn spawn() -> Self {
+            let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let gateway = Self { addr: socket.local_addr().unwrap(), mappings: Default::default() };
+            let mappings = gateway.mappings.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 12];
+                loop {
+                    let (len, from) = socket.recv_from(&mut buf).await.unwrap();
+                    let response = match (len, buf[1]) {
+                        (2, OP_EXTERNAL_ADDRESS) => {
+                            let mut response = vec![VERSION, OP_RESPONSE, 0, 0, 0, 0, 0, 1];
+                            response.extend_from_slice(&EXTERNAL_IP.octets());
+                            response
+                        }
+                        (12, opcode @ (OP_MAP_UDP | OP_MAP_TCP)) => {
+                            let internal_port = u16::from_be_bytes([buf[4], buf[5]]);
+                            let lifetime = [buf[8], buf[9], buf[10], buf[11]];
+                            let external_port = if lifetime == [0; 4] {
+                                mappings.lock().unwrap().remove(&(opcode, internal_port));
+                                0
+                            } else {
+                                let external_port = internal_port + 1000;
+                                mappings
+                                    .lock()
+                                    .unwrap()
+                                    .insert((opcode, internal_port), external_port);
+                                external_port
+                            };
+                            let mut response =
+                                vec![VERSION, opcode + OP_RESPONSE, 0, 0, 0, 0, 0, 1];
+                            response.extend_from_slice(&internal_port.to_be_bytes());
+                            response.extend_from_slice(&external_port.to_be_bytes());
+                            response.extend_from_slice(&lifetime);
+                            response
+                        }
+                        // unsupported opcode
+                        _ => vec![VERSION, buf[1] + OP_RESPONSE, 0, 5, 0, 0, 0, 1],
+                    };
+                    socket.send_to(&response, from).await.unwrap();
+                }
+            });
+            gateway
+        }
+
+        pub(crate) fn mapping(&self, protocol: PortMappingProtocol, port: u16) -> Option<u16> {
+            let opcode = match protocol {
+                PortMappingProtocol::Udp => OP_MAP_UDP,
+                PortMappingProtocol::Tcp => OP_MAP_TCP,
+            };
+            self.mappings.lock().unwrap().get(&(opcode, port)).copied()
+        }
+    }
+
+    #[tokio::test]
+    async fn external_address() {
+        let gateway = FakeGateway::spawn().await;
+        let client = NatPmpClient::with_addr(gateway.addr);
+        assert_eq!(client.external_address().await.unwrap(), EXTERNAL_IP);
+    }
+
+    #[tokio::test]
+    async fn map_and_remove_port() {
+        let gateway = FakeGateway::spawn().await;
+        let client = NatPmpClient::with_addr(gateway.addr);
+
+        let mapping = client
+            .map_port(PortMappingProtocol::Tcp, 30303, 30303, Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert_eq!(
+            mapping,
+            PortMapping {
+                protocol: PortMappingProtocol::Tcp,
+                internal_port: 30303,
+                external_port: 31303,
+                lifetime: Duration::from_secs(3600),
+            }
+        );
+        assert_eq!(gateway.mapping(PortMappingProtocol::Tcp, 30303), Some(31303));
+        assert_eq!(gateway.mapping(PortMappingProtocol::Udp, 30303), None);
+
+        client.remove_mapping(PortMappingProtocol::Tcp, 30303).await.unwrap();
+        assert_eq!(gateway.mapping(PortMappingProtocol::Tcp, 30303), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout_without_gateway() {
+        // bound but never answers
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let client = NatPmpClient::with_addr(socket.local_addr().unwrap());
+        assert!(matches!(client.external_address().await, Err(NatPmpError::Timeout)));
+    }
+}
@@ -0,0 +1,128 @@
+//! Bandwidth accounting per `eth` message type.
+
+use metrics::{register_counter, Counter};
+use reth_eth_wire::EthMessageID;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// All message types of the `eth` protocol.
+const ETH_MESSAGE_IDS: [EthMessageID; 15] = [
+    EthMessageID::Status,
+    EthMessageID::NewBlockHashes,
+    EthMessageID::Transactions,
+    EthMessageID::GetBlockHeaders,
+    EthMessageID::BlockHeaders,
+    EthMessageID::GetBlockBodies,
+    EthMessageID::BlockBodies,
+    EthMessageID::NewBlock,
+    EthMessageID::NewPooledTransactionHashes,
+    EthMessageID::GetPooledTransactions,
+    EthMessageID::PooledTransactions,
+    EthMessageID::GetNodeData,
+    EthMessageID::NodeData,
+    EthMessageID::GetReceipts,
+    EthMessageID::Receipts,
+];
+
+/// Number of slots required to index by [`EthMessageID`].
+const NUM_SLOTS: usize = EthMessageID::Receipts as usize + 1;
+
+/// Tracks the bytes of `eth` messages exchanged with all peers, per message type.
+///
+/// Sizes are the lengths of the RLP encoded messages before compression, so they don't include
+/// framing and encryption overhead. The bytes on the wire are tracked by the
+/// [`BandwidthMeter`](reth_net_common::bandwidth_meter::BandwidthMeter) of the network.
+///
+/// The totals are also exported as the `network_eth_message_inbound_bytes` and
+/// `network_eth_message_outbound_bytes` counters, labeled by `message`.
+#[derive(Debug, Clone, Default)]
+pub struct EthMessageBandwidth {
+    inner: Arc<EthMessageBandwidthInner>,
+}
+
+// === impl EthMessageBandwidth ===
+
+impl EthMessageBandwidth {
+    /// Returns the total number of bytes received in messages of the given type.
+    pub fn inbound(&self, id: EthMessageID) -> u64 {
+        self.inner.inbound[id as usize].load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of bytes sent in messages of the given type.
+    pub fn outbound(&self, id: EthMessageID) -> u64 {
+        self.inner.outbound[id as usize].load(Ordering::Relaxed)
+    }
+
+    /// Returns the received and sent bytes of all message types.
+    pub fn totals(&self) -> impl Iterator<Item = (EthMessageID, u64, u64)> + '_ {
+        ETH_MESSAGE_IDS.into_iter().map(|id| (id, self.inbound(id), self.outbound(id)))
+    }
+
+    /// Records a received message of the given type and size.
+    pub(crate) fn record_inbound(&self, id: EthMessageID, num_bytes: usize) {
+        let num_bytes = num_bytes as u64;
+        self.inner.inbound[id as usize].fetch_add(num_bytes, Ordering::Relaxed);
+        if let Some(counter) = &self.inner.inbound_counters[id as usize] {
+            counter.increment(num_bytes);
+        }
+    }
+
+    /// Records a sent message of the given type and size.
+    pub(crate) fn record_outbound(&self, id: EthMessageID, num_bytes: usize) {
+        let num_bytes = num_bytes as u64;
+        self.inner.outbound[id as usize].fetch_add(num_bytes, Ordering::Relaxed);
+        if let Some(counter) = &self.inner.outbound_counters[id as usize] {
+            counter.increment(num_bytes);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EthMessageBandwidthInner {
+    inbound: [AtomicU64; NUM_SLOTS],
+    outbound: [AtomicU64; NUM_SLOTS],
+    inbound_counters: [Option<Counter>; NUM_SLOTS],
+    outbound_counters: [Option<Counter>; NUM_SLOTS],
+}
+
+impl Default for EthMessageBandwidthInner {
+    fn default() -> Self {
+        let mut inbound_counters: [Option<Counter>; NUM_SLOTS] = Default::default();
+        let mut outbound_counters: [Option<Counter>; NUM_SLOTS] = Default::default();
+        for id in ETH_MESSAGE_IDS {
+            let message = format!("{id:?}");
+            inbound_counters[id as usize] = Some(register_counter!(
+                "network_eth_message_inbound_bytes",
+                "message" => message.clone()
+            ));
+            outbound_counters[id as usize] =
+                Some(register_counter!("network_eth_message_outbound_bytes", "message" => message));
+        }
+        Self {
+            inbound: Default::default(),
+            outbound: Default::default(),
+            inbound_counters,
+            outbound_counters,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_per_message_type() {
+        let bandwidth = EthMessageBandwidth::default();
+        bandwidth.record_inbound(EthMessageID::BlockHeaders, 100);
+        bandwidth.record_inbound(EthMessageID::BlockHeaders, 20);
+        bandwidth.record_outbound(EthMessageID::GetBlockHeaders, 10);
+
+        assert_eq!(bandwidth.inbound(EthMessageID::BlockHeaders), 120);
+        assert_eq!(bandwidth.outbound(EthMessageID::BlockHeaders), 0);
+        assert_eq!(bandwidth.outbound(EthMessageID::GetBlockHeaders), 10);
+        assert_eq!(bandwidth.totals().count(), ETH_MESSAGE_IDS.len());
+    }
+}
@@ -1,8 +1,9 @@
 //! Builder support for configuring the entire setup.
 
 use crate::{
-    eth_requests::EthRequestHandler, transactions::TransactionsManager, NetworkHandle,
-    NetworkManager,
+    eth_requests::EthRequestHandler,
+    transactions::{TransactionsManager, TransactionsManagerConfig},
+    NetworkHandle, NetworkManager,
 };
 use reth_transaction_pool::TransactionPool;
 use std::sync::Arc;
@@ -35,12 +36,22 @@ impl<C, Tx, Eth> NetworkBuilder<C, Tx, Eth> {
     pub fn transactions<Pool: TransactionPool>(
         self,
         pool: Pool,
+    ) -> NetworkBuilder<C, TransactionsManager<Pool>, Eth> {
+        self.transactions_with_config(pool, Default::default())
+    }
+
+    /// Creates a new [`TransactionsManager`] with the given [`TransactionsManagerConfig`] and
+    /// wires it to the network.
+    pub fn transactions_with_config<Pool: TransactionPool>(
+        self,
+        pool: Pool,
+        config: TransactionsManagerConfig,
     ) -> NetworkBuilder<C, TransactionsManager<Pool>, Eth> {
         let NetworkBuilder { mut network, request_handler, .. } = self;
         let (tx, rx) = mpsc::unbounded_channel();
         network.set_transactions(tx);
         let handle = network.handle().clone();
-        let transactions = TransactionsManager::new(handle, pool, rx);
+        let transactions = TransactionsManager::with_config(handle, pool, rx, config);
         NetworkBuilder { network, request_handler, transactions }
     }
 
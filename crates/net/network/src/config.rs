@@ -4,10 +4,12 @@ use crate::{
     error::NetworkError,
     import::{BlockImport, ProofOfStakeBlockImport},
     peers::PeersConfig,
+    protocol::{ProtocolHandler, RlpxSubProtocols},
     session::SessionsConfig,
     NetworkHandle, NetworkManager,
 };
 use reth_discv4::{Discv4Config, Discv4ConfigBuilder, DEFAULT_DISCOVERY_PORT};
+use reth_discv5::{Discv5Config, Discv5ConfigBuilder};
 use reth_primitives::{ChainSpec, ForkFilter, Head, NodeRecord, PeerId, MAINNET, U256};
 use reth_provider::{BlockProvider, HeaderProvider};
 use reth_tasks::TaskExecutor;
@@ -46,6 +48,8 @@ pub struct NetworkConfig<C> {
     pub dns_discovery_config: Option<DnsDiscoveryConfig>,
     /// How to set up discovery.
     pub discovery_v4_config: Option<Discv4Config>,
+    /// How to set up discovery v5, which runs next to discv4 on its own UDP port.
+    pub discovery_v5_config: Option<Discv5Config>,
     /// Address to use for discovery
     pub discovery_addr: SocketAddr,
     /// Address to listen for incoming connections
@@ -73,6 +77,8 @@ pub struct NetworkConfig<C> {
     pub status: Status,
     /// Sets the hello message for the p2p handshake in RLPx
     pub hello_message: HelloMessage,
+    /// Additional RLPx sub-protocols that are supported next to `eth`.
+    pub sub_protocols: RlpxSubProtocols,
 }
 
 // === impl NetworkConfig ===
@@ -94,11 +100,27 @@ impl<C> NetworkConfig<C> {
         self
     }
 
+    /// Sets the config to use for the discovery v5 protocol.
+    pub fn set_discovery_v5(mut self, discovery_config: Discv5Config) -> Self {
+        self.discovery_v5_config = Some(discovery_config);
+        self
+    }
+
     /// Sets the address for the incoming connection listener.
     pub fn set_listener_addr(mut self, listener_addr: SocketAddr) -> Self {
         self.listener_addr = listener_addr;
         self
     }
+
+    /// Adds an additional RLPx sub-protocol and announces it in the hello message.
+    pub fn add_rlpx_sub_protocol(mut self, handler: impl ProtocolHandler) -> Self {
+        let cap = handler.protocol().cap;
+        if !self.hello_message.capabilities.contains(&cap) {
+            self.hello_message.capabilities.push(cap);
+        }
+        self.sub_protocols.push(handler);
+        self
+    }
 }
 
 impl<C> NetworkConfig<C>
@@ -129,6 +151,9 @@ pub struct NetworkConfigBuilder {
     dns_discovery_config: Option<DnsDiscoveryConfig>,
     /// How to set up discovery.
     discovery_v4_builder: Option<Discv4ConfigBuilder>,
+    /// How to set up discovery v5.
+    #[serde(skip)]
+    discovery_v5_builder: Option<Discv5ConfigBuilder>,
     /// All boot nodes to start network discovery with.
     boot_nodes: HashSet<NodeRecord>,
     /// Address to use for discovery
@@ -150,6 +175,9 @@ pub struct NetworkConfigBuilder {
     hello_message: Option<HelloMessage>,
     /// Head used to start set for the fork filter and status.
     head: Option<Head>,
+    /// Additional RLPx sub-protocols that are supported next to `eth`.
+    #[serde(skip)]
+    sub_protocols: RlpxSubProtocols,
 }
 
 // === impl NetworkConfigBuilder ===
@@ -161,6 +189,7 @@ impl NetworkConfigBuilder {
             secret_key,
             dns_discovery_config: Some(Default::default()),
             discovery_v4_builder: Some(Default::default()),
+            discovery_v5_builder: None,
             boot_nodes: Default::default(),
             discovery_addr: None,
             listener_addr: None,
@@ -171,6 +200,7 @@ impl NetworkConfigBuilder {
             executor: None,
             hello_message: None,
             head: None,
+            sub_protocols: Default::default(),
         }
     }
 
@@ -202,6 +232,15 @@ impl NetworkConfigBuilder {
         self
     }
 
+    /// Adds an RLPx sub-protocol that is multiplexed next to `eth` on all sessions with peers that
+    /// support it.
+    ///
+    /// The capability of the protocol is added to the `HelloMessage`.
+    pub fn add_rlpx_sub_protocol(mut self, handler: impl ProtocolHandler) -> Self {
+        self.sub_protocols.push(handler);
+        self
+    }
+
     /// Set a custom peer config for how peers are handled
     pub fn peer_config(mut self, config: PeersConfig) -> Self {
         self.peers_config = Some(config);
@@ -238,6 +277,12 @@ impl NetworkConfigBuilder {
         self
     }
 
+    /// Enables discv5 discovery with the given config, which runs next to discv4.
+    pub fn discovery_v5(mut self, builder: Discv5ConfigBuilder) -> Self {
+        self.discovery_v5_builder = Some(builder);
+        self
+    }
+
     /// Disables Discv4 discovery.
     pub fn no_discv4_discovery(mut self) -> Self {
         self.discovery_v4_builder = None;
@@ -273,6 +318,7 @@ impl NetworkConfigBuilder {
     /// Disables all discovery services.
     pub fn disable_discovery(&mut self) {
         self.discovery_v4_builder = None;
+        self.discovery_v5_builder = None;
         self.dns_discovery_config = None;
     }
 
@@ -284,6 +330,7 @@ impl NetworkConfigBuilder {
             secret_key,
             mut dns_discovery_config,
             discovery_v4_builder,
+            discovery_v5_builder,
             boot_nodes,
             discovery_addr,
             listener_addr,
@@ -294,6 +341,7 @@ impl NetworkConfigBuilder {
             executor,
             hello_message,
             head,
+            sub_protocols,
         } = self;
 
         let listener_addr = listener_addr.unwrap_or_else(|| {
@@ -304,6 +352,13 @@ impl NetworkConfigBuilder {
             hello_message.unwrap_or_else(|| HelloMessage::builder(peer_id).build());
         hello_message.port = listener_addr.port();
 
+        // announce all sub-protocols
+        for protocol in sub_protocols.protocols() {
+            if !hello_message.capabilities.contains(&protocol.cap) {
+                hello_message.capabilities.push(protocol.cap);
+            }
+        }
+
         let head = head.unwrap_or(Head {
             hash: chain_spec.genesis_hash(),
             number: 0,
@@ -334,6 +389,7 @@ impl NetworkConfigBuilder {
             boot_nodes,
             dns_discovery_config,
             discovery_v4_config: discovery_v4_builder.map(|builder| builder.build()),
+            discovery_v5_config: discovery_v5_builder.map(|builder| builder.build()),
             discovery_addr: discovery_addr.unwrap_or_else(|| {
                 SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DEFAULT_DISCOVERY_PORT))
             }),
@@ -347,6 +403,7 @@ impl NetworkConfigBuilder {
             status,
             hello_message,
             fork_filter,
+            sub_protocols,
         }
     }
 }
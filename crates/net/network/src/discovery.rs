@@ -3,19 +3,25 @@
 use crate::error::NetworkError;
 use futures::StreamExt;
 use reth_discv4::{DiscoveryUpdate, Discv4, Discv4Config};
+use reth_discv5::{Discv5, Discv5Config, Discv5Update};
 use reth_dns_discovery::{
     DnsDiscoveryConfig, DnsDiscoveryHandle, DnsDiscoveryService, DnsNodeRecordUpdate, DnsResolver,
 };
-use reth_primitives::{ForkId, NodeRecord, PeerId};
+use reth_primitives::{EnrForkIdEntry, ForkId, NodeRecord, PeerId};
 use secp256k1::SecretKey;
 use std::{
     collections::{hash_map::Entry, HashMap, VecDeque},
     net::{IpAddr, SocketAddr},
     sync::Arc,
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
-use tokio::task::JoinHandle;
+use tokio::{task::JoinHandle, time::Interval};
 use tokio_stream::wrappers::ReceiverStream;
+use tracing::trace;
+
+/// The interval at which nodes that are waiting for their ENR are checked for a timeout.
+const ENR_TIMEOUT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// An abstraction over the configured discovery protocol.
 ///
@@ -33,6 +39,21 @@ pub struct Discovery {
     discv4_updates: Option<ReceiverStream<DiscoveryUpdate>>,
     /// The handle to the spawned discv4 service
     _discv4_service: Option<JoinHandle<()>>,
+    /// Nodes discovered via discv4 that are held back until the fork ID of their ENR is known.
+    ///
+    /// This way nodes of other chains never reach the peers manager.
+    pending_enr_nodes: HashMap<PeerId, (NodeRecord, Instant)>,
+    /// How long to wait for the fork ID of a node discovered via discv4, `None` if EIP-868 is
+    /// disabled.
+    enr_fork_id_timeout: Option<Duration>,
+    /// Interval at which nodes without a fork ID are released after the timeout.
+    pending_enr_interval: Option<Interval>,
+    /// Handler to interact with the Discovery v5 service
+    discv5: Option<Discv5>,
+    /// All discovered execution layer nodes from the discv5 service.
+    discv5_updates: Option<ReceiverStream<Discv5Update>>,
+    /// The handle to the spawned discv5 service
+    _discv5_service: Option<JoinHandle<()>>,
     /// Handler to interact with the DNS discovery service
     _dns_discovery: Option<DnsDiscoveryHandle>,
     /// Updates from the DNS discovery service.
@@ -46,16 +67,23 @@ pub struct Discovery {
 impl Discovery {
     /// Spawns the discovery service.
     ///
-    /// This will spawn the [`reth_discv4::Discv4Service`] onto a new task and establish a listener
-    /// channel to receive all discovered nodes.
+    /// This will spawn the [`reth_discv4::Discv4Service`] and [`reth_discv5::Discv5Service`] onto
+    /// new tasks and establish listener channels to receive all discovered nodes.
     pub async fn new(
         discovery_addr: SocketAddr,
         sk: SecretKey,
         discv4_config: Option<Discv4Config>,
+        discv5_config: Option<Discv5Config>,
         dns_discovery_config: Option<DnsDiscoveryConfig>,
     ) -> Result<Self, NetworkError> {
         // setup discv4
         let local_enr = NodeRecord::from_secret_key(discovery_addr, &sk);
+        let enr_fork_id_timeout = discv4_config
+            .as_ref()
+            .filter(|config| config.enable_eip868)
+            .map(|config| config.enr_expiration);
+        let pending_enr_interval =
+            enr_fork_id_timeout.map(|_| tokio::time::interval(ENR_TIMEOUT_CHECK_INTERVAL));
         let (discv4, discv4_updates, _discv4_service) = if let Some(disc_config) = discv4_config {
             let (discv4, mut discv4_service) =
                 Discv4::bind(discovery_addr, local_enr, sk, disc_config)
@@ -69,6 +97,17 @@ impl Discovery {
             (None, None, None)
         };
 
+        // setup discv5
+        let (discv5, discv5_updates, _discv5_service) = if let Some(disc_config) = discv5_config {
+            let (discv5, mut discv5_service) = Discv5::bind(sk, disc_config).await?;
+            let discv5_updates = discv5_service.update_stream();
+            // spawn the service
+            let _discv5_service = discv5_service.spawn();
+            (Some(discv5), Some(discv5_updates), Some(_discv5_service))
+        } else {
+            (None, None, None)
+        };
+
         // setup DNS discovery
         let (_dns_discovery, dns_discovery_updates, _dns_disc_service) =
             if let Some(dns_config) = dns_discovery_config {
@@ -88,6 +127,12 @@ impl Discovery {
             discv4,
             discv4_updates,
             _discv4_service,
+            pending_enr_nodes: Default::default(),
+            enr_fork_id_timeout,
+            pending_enr_interval,
+            discv5,
+            discv5_updates,
+            _discv5_service,
             discovered_nodes: Default::default(),
             queued_events: Default::default(),
             _dns_disc_service,
@@ -96,11 +141,15 @@ impl Discovery {
         })
     }
 
-    /// Updates the `eth:ForkId` field in discv4.
-    #[allow(unused)]
+    /// Updates the `eth:ForkId` field in discv4 and discv5.
+    ///
+    /// This is invoked on hardfork transitions, so the advertised fork ID stays current.
     pub(crate) fn update_fork_id(&self, fork_id: ForkId) {
         if let Some(discv4) = &self.discv4 {
-            discv4.set_eip868_rlp("eth".as_bytes().to_vec(), fork_id)
+            discv4.set_eip868_rlp("eth".as_bytes().to_vec(), EnrForkIdEntry::from(fork_id))
+        }
+        if let Some(discv5) = &self.discv5 {
+            discv5.set_fork_id(fork_id)
         }
     }
 
@@ -109,6 +158,9 @@ impl Discovery {
         if let Some(discv4) = &self.discv4 {
             discv4.ban_ip(ip)
         }
+        if let Some(discv5) = &self.discv5 {
+            discv5.ban_ip(ip)
+        }
     }
 
     /// Bans the [`PeerId`] and [`IpAddr`] in the discovery service.
@@ -116,6 +168,9 @@ impl Discovery {
         if let Some(discv4) = &self.discv4 {
             discv4.ban(peer_id, ip)
         }
+        if let Some(discv5) = &self.discv5 {
+            discv5.ban(peer_id, ip)
+        }
     }
 
     /// Returns the id with which the local identifies itself in the network
@@ -143,13 +198,29 @@ impl Discovery {
     fn on_discv4_update(&mut self, update: DiscoveryUpdate) {
         match update {
             DiscoveryUpdate::Added(record) => {
-                self.on_node_record_update(record, None);
+                if self.enr_fork_id_timeout.is_some() &&
+                    !self.discovered_nodes.contains_key(&record.id)
+                {
+                    // wait for the ENR response before the node is reported
+                    self.pending_enr_nodes.entry(record.id).or_insert((record, Instant::now()));
+                } else {
+                    self.on_node_record_update(record, None);
+                }
             }
             DiscoveryUpdate::EnrForkId(node, fork_id) => {
-                self.queued_events.push_back(DiscoveryEvent::EnrForkId(node.id, fork_id))
+                if self.pending_enr_nodes.remove(&node.id).is_some() &&
+                    !self.discovered_nodes.contains_key(&node.id)
+                {
+                    self.on_node_record_update(node, Some(fork_id));
+                } else {
+                    self.queued_events.push_back(DiscoveryEvent::EnrForkId(node.id, fork_id))
+                }
             }
+            // only the fork ID of the ENR is relevant
+            DiscoveryUpdate::Enr(_, _) => {}
             DiscoveryUpdate::Removed(node) => {
                 self.discovered_nodes.remove(&node);
+                self.pending_enr_nodes.remove(&node);
             }
             DiscoveryUpdate::Batch(updates) => {
                 for update in updates {
@@ -159,6 +230,34 @@ impl Discovery {
         }
     }
 
+    /// Reports all pending nodes for which no ENR was received in time, without a fork ID.
+    ///
+    /// Not all nodes support EIP-868, these are still checked during the `Status` handshake.
+    fn on_pending_enr_timeout(&mut self) {
+        let Some(timeout) = self.enr_fork_id_timeout else { return };
+        let now = Instant::now();
+        let timed_out = self
+            .pending_enr_nodes
+            .iter()
+            .filter(|(_, (_, discovered_at))| now.duration_since(*discovered_at) > timeout)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in timed_out {
+            if let Some((record, _)) = self.pending_enr_nodes.remove(&id) {
+                trace!(target : "net::discovery", peer_id=?id, "no ENR received for discovered node");
+                self.on_node_record_update(record, None);
+            }
+        }
+    }
+
+    fn on_discv5_update(&mut self, update: Discv5Update) {
+        match update {
+            Discv5Update::Added(record, fork_id) => {
+                self.on_node_record_update(record, Some(fork_id));
+            }
+        }
+    }
+
     pub(crate) fn poll(&mut self, cx: &mut Context<'_>) -> Poll<DiscoveryEvent> {
         loop {
             // Drain all buffered events first
@@ -173,12 +272,24 @@ impl Discovery {
                 self.on_discv4_update(update)
             }
 
+            while let Some(Poll::Ready(Some(update))) =
+                self.discv5_updates.as_mut().map(|updates| updates.poll_next_unpin(cx))
+            {
+                self.on_discv5_update(update)
+            }
+
             while let Some(Poll::Ready(Some(update))) =
                 self.dns_discovery_updates.as_mut().map(|updates| updates.poll_next_unpin(cx))
             {
                 self.on_node_record_update(update.node_record, update.fork_id);
             }
 
+            while let Some(Poll::Ready(_)) =
+                self.pending_enr_interval.as_mut().map(|interval| interval.poll_tick(cx))
+            {
+                self.on_pending_enr_timeout();
+            }
+
             if self.queued_events.is_empty() {
                 return Poll::Pending
             }
@@ -204,6 +315,12 @@ impl Discovery {
             discv4_updates: Default::default(),
             queued_events: Default::default(),
             _discv4_service: Default::default(),
+            pending_enr_nodes: Default::default(),
+            enr_fork_id_timeout: None,
+            pending_enr_interval: None,
+            discv5: None,
+            discv5_updates: None,
+            _discv5_service: None,
             _dns_discovery: None,
             dns_discovery_updates: None,
             _dns_disc_service: None,
@@ -223,6 +340,7 @@ pub enum DiscoveryEvent {
 mod tests {
     use super::*;
     use rand::thread_rng;
+    use reth_primitives::ForkHash;
     use secp256k1::SECP256K1;
     use std::net::{Ipv4Addr, SocketAddrV4};
 
@@ -231,9 +349,73 @@ mod tests {
         let mut rng = thread_rng();
         let (secret_key, _) = SECP256K1.generate_keypair(&mut rng);
         let discovery_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
-        let _discovery =
-            Discovery::new(discovery_addr, secret_key, Default::default(), Default::default())
-                .await
-                .unwrap();
+        let _discovery = Discovery::new(
+            discovery_addr,
+            secret_key,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_discv5_setup() {
+        let mut rng = thread_rng();
+        let (secret_key, _) = SECP256K1.generate_keypair(&mut rng);
+        let discovery_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+        let discv5_config = Discv5Config::builder()
+            .listen_addr(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0)))
+            .build();
+        let discovery = Discovery::new(discovery_addr, secret_key, None, Some(discv5_config), None)
+            .await
+            .unwrap();
+        assert!(discovery.discv5.is_some());
+    }
+
+    fn test_record() -> NodeRecord {
+        NodeRecord {
+            address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            tcp_port: 30303,
+            udp_port: 30303,
+            id: PeerId::random(),
+        }
+    }
+
+    #[test]
+    fn test_wait_for_enr_fork_id() {
+        let mut discovery = Discovery::noop();
+        discovery.enr_fork_id_timeout = Some(Duration::from_secs(20));
+
+        let record = test_record();
+        discovery.on_discv4_update(DiscoveryUpdate::Added(record));
+        assert!(discovery.queued_events.is_empty());
+
+        let fork_id = ForkId { hash: ForkHash([0xdc, 0xe9, 0x6c, 0x2d]), next: 0 };
+        discovery.on_discv4_update(DiscoveryUpdate::EnrForkId(record, fork_id));
+        assert!(discovery.pending_enr_nodes.is_empty());
+        assert!(matches!(
+            discovery.queued_events.pop_front(),
+            Some(DiscoveryEvent::Discovered { fork_id: Some(id), .. }) if id == fork_id
+        ));
+    }
+
+    #[test]
+    fn test_release_node_without_enr() {
+        let mut discovery = Discovery::noop();
+        discovery.enr_fork_id_timeout = Some(Duration::from_secs(20));
+
+        let record = test_record();
+        discovery
+            .pending_enr_nodes
+            .insert(record.id, (record, Instant::now() - Duration::from_secs(30)));
+        discovery.on_pending_enr_timeout();
+
+        assert!(discovery.pending_enr_nodes.is_empty());
+        assert!(matches!(
+            discovery.queued_events.pop_front(),
+            Some(DiscoveryEvent::Discovered { fork_id: None, peer_id, .. }) if peer_id == record.id
+        ));
     }
 }
@@ -17,6 +17,9 @@ pub enum NetworkError {
     /// IO error when creating the discovery service
     #[error("Failed to launch discovery service: {0}")]
     Discovery(io::Error),
+    /// Error when creating the discovery v5 service
+    #[error("Failed to launch discovery v5 service: {0}")]
+    Discv5(#[from] reth_discv5::error::Discv5Error),
     /// Error when setting up the DNS resolver failed
     ///
     /// See also [DnsResolver](reth_dns_discovery::DnsResolver::from_system_conf)
@@ -151,6 +154,7 @@ impl SessionError for EthStreamError {
             EthStreamError::P2PStreamError(P2PStreamError::UnknownDisconnectReason(_)) |
             EthStreamError::P2PStreamError(P2PStreamError::MessageTooBig { .. }) |
             EthStreamError::P2PStreamError(P2PStreamError::EmptyProtocolMessage) |
+            EthStreamError::P2PStreamError(P2PStreamError::ProtocolBufferFull) |
             EthStreamError::P2PStreamError(P2PStreamError::PingerError(_)) |
             EthStreamError::P2PStreamError(P2PStreamError::Snap(_)) => Some(BackoffKind::Medium),
             _ => None,
@@ -8,7 +8,8 @@ use reth_eth_wire::{
 };
 use reth_interfaces::p2p::error::RequestResult;
 use reth_primitives::{rpc, BlockHashOrNumber, Header, HeadersDirection, PeerId, U256};
-use reth_provider::{BlockProvider, HeaderProvider};
+use reth_provider::{BlockProvider, HeaderProvider, NodeDataProvider, ReceiptProvider};
+use reth_rlp::Encodable;
 use std::{
     borrow::Borrow,
     future::Future,
@@ -33,6 +34,16 @@ const MAX_HEADERS_SERVE: usize = 1024;
 /// SOFT_RESPONSE_LIMIT.
 const MAX_BODIES_SERVE: usize = 1024;
 
+/// Maximum number of receipts to serve.
+///
+/// Used to limit lookups.
+const MAX_RECEIPTS_SERVE: usize = 1024;
+
+/// Maximum number of node data entries to serve.
+///
+/// Used to limit lookups.
+const MAX_NODE_DATA_SERVE: usize = 1024;
+
 /// Estimated size in bytes of an RLP encoded body.
 // TODO: check 24kb blocksize assumption
 const APPROX_BODY_SIZE: usize = 24 * 1024;
@@ -72,7 +83,7 @@ impl<C> EthRequestHandler<C> {
 
 impl<C> EthRequestHandler<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + NodeDataProvider,
 {
     /// Returns the list of requested heders
     fn get_headers_response(&self, request: GetBlockHeaders) -> Vec<Header> {
@@ -184,6 +195,70 @@ where
 
         let _ = response.send(Ok(BlockBodies(bodies)));
     }
+
+    fn on_receipts_request(
+        &mut self,
+        _peer_id: PeerId,
+        request: GetReceipts,
+        response: oneshot::Sender<RequestResult<Receipts>>,
+    ) {
+        let mut receipts = Vec::new();
+
+        let mut total_bytes = 0;
+
+        for hash in request.0 {
+            if let Some(block_receipts) =
+                self.client.receipts_by_block_hash(hash).unwrap_or_default()
+            {
+                total_bytes += block_receipts.length();
+
+                receipts.push(block_receipts);
+
+                if total_bytes > SOFT_RESPONSE_LIMIT {
+                    break
+                }
+
+                if receipts.len() >= MAX_RECEIPTS_SERVE {
+                    break
+                }
+            } else {
+                break
+            }
+        }
+
+        let _ = response.send(Ok(Receipts(receipts)));
+    }
+
+    /// `GetNodeData` is only part of `eth/66`. Unknown entries are skipped, so peers may receive
+    /// an empty response if we don't have any of the requested entries.
+    fn on_node_data_request(
+        &mut self,
+        _peer_id: PeerId,
+        request: GetNodeData,
+        response: oneshot::Sender<RequestResult<NodeData>>,
+    ) {
+        let mut data = Vec::new();
+
+        let mut total_bytes = 0;
+
+        for hash in request.0 {
+            if let Some(entry) = self.client.node_data(hash).unwrap_or_default() {
+                total_bytes += entry.len();
+
+                data.push(entry);
+
+                if total_bytes > SOFT_RESPONSE_LIMIT {
+                    break
+                }
+
+                if data.len() >= MAX_NODE_DATA_SERVE {
+                    break
+                }
+            }
+        }
+
+        let _ = response.send(Ok(NodeData(data)));
+    }
 }
 
 /// An endless future.
@@ -191,7 +266,7 @@ where
 /// This should be spawned or used as part of `tokio::select!`.
 impl<C> Future for EthRequestHandler<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + NodeDataProvider,
 {
     type Output = ();
 
@@ -209,8 +284,12 @@ where
                     IncomingEthRequest::GetBlockBodies { peer_id, request, response } => {
                         this.on_bodies_request(peer_id, request, response)
                     }
-                    IncomingEthRequest::GetNodeData { .. } => {}
-                    IncomingEthRequest::GetReceipts { .. } => {}
+                    IncomingEthRequest::GetNodeData { peer_id, request, response } => {
+                        this.on_node_data_request(peer_id, request, response)
+                    }
+                    IncomingEthRequest::GetReceipts { peer_id, request, response } => {
+                        this.on_receipts_request(peer_id, request, response)
+                    }
                 },
             }
         }
@@ -10,26 +10,36 @@ use reth_interfaces::p2p::{
 };
 use reth_network_api::ReputationChangeKind;
 use reth_primitives::{Header, PeerId, H256};
+use reth_rlp::Encodable;
 use std::{
+    cmp::Ordering as CmpOrdering,
     collections::{HashMap, VecDeque},
     sync::{
         atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
     task::{Context, Poll},
+    time::Instant,
 };
 use tokio::sync::{mpsc, mpsc::UnboundedSender, oneshot};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 mod client;
+mod stats;
 pub use client::FetchClient;
+use stats::{PeerStats, ResponseQuality, MIN_SAMPLES};
+
+/// Peers that score below this are evicted if connection slots are scarce.
+const EVICTION_SCORE: f64 = 0.1;
 
 /// Manages data fetching operations.
 ///
 /// This type is hooked into the staged sync pipeline and delegates download request to available
 /// peers and sends the response once ready.
 ///
-/// This type maintains a list of connected peers that are available for requests.
+/// This type maintains a list of connected peers that are available for requests. Every response is
+/// recorded in the rolling [`PeerStats`] of the peer, which are used to route requests to the
+/// fastest and most reliable peers and to size bodies requests according to the peer's throughput.
 pub struct StateFetcher {
     /// Currently active [`GetBlockHeaders`] requests
     inflight_headers_requests:
@@ -76,8 +86,16 @@ impl StateFetcher {
         best_number: u64,
         timeout: Arc<AtomicU64>,
     ) {
-        self.peers
-            .insert(peer_id, Peer { state: PeerState::Idle, best_hash, best_number, timeout });
+        self.peers.insert(
+            peer_id,
+            Peer {
+                state: PeerState::Idle,
+                best_hash,
+                best_number,
+                timeout,
+                stats: Default::default(),
+            },
+        );
     }
 
     /// Removes the peer from the peer list, after which it is no longer available for future
@@ -118,16 +136,48 @@ impl StateFetcher {
     }
 
     /// Returns the _next_ idle peer that's ready to accept a request,
-    /// prioritizing those with the lowest timeout/latency.
-    /// Once a peer has been yielded, it will be moved to the end of the map
+    /// prioritizing those with the highest score and then those with the lowest timeout/latency.
     fn next_peer(&mut self) -> Option<PeerId> {
         self.peers
             .iter()
             .filter(|(_, peer)| peer.state.is_idle())
-            .min_by_key(|(_, peer)| peer.timeout())
+            .min_by(|(_, a), (_, b)| {
+                b.stats
+                    .score()
+                    .partial_cmp(&a.stats.score())
+                    .unwrap_or(CmpOrdering::Equal)
+                    .then_with(|| a.timeout().cmp(&b.timeout()))
+            })
+            .map(|(id, _)| *id)
+    }
+
+    /// Returns the peer with the lowest score, if its score is low enough to be evicted in favor
+    /// of another peer.
+    ///
+    /// Only peers with enough recorded responses are considered.
+    pub(crate) fn eviction_candidate(&self, can_evict: impl Fn(&PeerId) -> bool) -> Option<PeerId> {
+        self.peers
+            .iter()
+            .filter(|(id, peer)| !matches!(peer.state, PeerState::Closing) && can_evict(id))
+            .filter(|(_, peer)| peer.stats.samples() >= MIN_SAMPLES)
+            .map(|(id, peer)| (id, peer.stats.score()))
+            .filter(|(_, score)| *score < EVICTION_SCORE)
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(CmpOrdering::Equal))
             .map(|(id, _)| *id)
     }
 
+    /// Returns the current score of the peer, see [`PeerStats::score`].
+    pub(crate) fn peer_score(&self, peer_id: &PeerId) -> Option<f64> {
+        self.peers.get(peer_id).map(|peer| peer.stats.score())
+    }
+
+    /// Records the quality of a response in the peer's stats.
+    fn record_response(&mut self, peer_id: &PeerId, quality: ResponseQuality) {
+        if let Some(peer) = self.peers.get_mut(peer_id) {
+            peer.stats.record(quality);
+        }
+    }
+
     /// Returns the next action to return
     fn poll_action(&mut self) -> PollAction {
         // we only check and not pop here since we don't know yet whether a peer is available.
@@ -186,13 +236,16 @@ impl StateFetcher {
     /// Caution: this assumes the peer exists and is idle
     fn prepare_block_request(&mut self, peer_id: PeerId, req: DownloadRequest) -> BlockRequest {
         // update the peer's state
+        let mut max_bodies_request = None;
         if let Some(peer) = self.peers.get_mut(&peer_id) {
             peer.state = req.peer_state();
+            max_bodies_request = peer.stats.max_bodies_request();
         }
 
+        let started = Instant::now();
         match req {
             DownloadRequest::GetBlockHeaders { request, response, .. } => {
-                let inflight = Request { request: request.clone(), response };
+                let inflight = Request { request: request.clone(), response, started };
                 self.inflight_headers_requests.insert(peer_id, inflight);
                 let HeadersRequest { start, limit, direction } = request;
                 BlockRequest::GetBlockHeaders(GetBlockHeaders {
@@ -202,8 +255,14 @@ impl StateFetcher {
                     direction,
                 })
             }
-            DownloadRequest::GetBlockBodies { request, response, .. } => {
-                let inflight = Request { request: request.clone(), response };
+            DownloadRequest::GetBlockBodies { mut request, response, .. } => {
+                // Peers may respond with fewer bodies than requested, so a slow peer gets a
+                // smaller request that it can serve in time. Headers requests are not resized
+                // because the headers downloader expects the full range.
+                if let Some(max) = max_bodies_request {
+                    request.truncate(max);
+                }
+                let inflight = Request { request: request.clone(), response, started };
                 self.inflight_bodies_requests.insert(peer_id, inflight);
                 BlockRequest::GetBlockBodies(GetBlockBodies(request))
             }
@@ -239,6 +298,17 @@ impl StateFetcher {
             .map(|r| res.is_likely_bad_headers_response(&r.request))
             .unwrap_or_default();
 
+        if let Some(resp) = resp.as_ref() {
+            let quality = match &res {
+                Ok(_) if is_likely_bad_response => Some(ResponseQuality::Invalid),
+                Ok(headers) => Some(response_quality(resp.started, headers)),
+                Err(err) => error_quality(err),
+            };
+            if let Some(quality) = quality {
+                self.record_response(&peer_id, quality);
+            }
+        }
+
         if let Some(resp) = resp {
             // delegate the response
             let _ = resp.response.send(res.map(|h| (peer_id, h).into()));
@@ -265,6 +335,14 @@ impl StateFetcher {
         res: RequestResult<Vec<BlockBody>>,
     ) -> Option<BlockResponseOutcome> {
         if let Some(resp) = self.inflight_bodies_requests.remove(&peer_id) {
+            let quality = match &res {
+                Ok(bodies) if bodies.len() > resp.request.len() => Some(ResponseQuality::Invalid),
+                Ok(bodies) => Some(response_quality(resp.started, bodies)),
+                Err(err) => error_quality(err),
+            };
+            if let Some(quality) = quality {
+                self.record_response(&peer_id, quality);
+            }
             let _ = resp.response.send(res.map(|b| (peer_id, b).into()));
         }
         if let Some(peer) = self.peers.get_mut(&peer_id) {
@@ -285,6 +363,27 @@ impl StateFetcher {
     }
 }
 
+/// Returns the quality of a successful response to a request sent at `started`.
+fn response_quality<T: Encodable>(started: Instant, items: &[T]) -> ResponseQuality {
+    let rtt = started.elapsed();
+    if items.is_empty() {
+        return ResponseQuality::Empty { rtt }
+    }
+    let bytes = items.iter().map(Encodable::length).sum();
+    ResponseQuality::Valid { rtt, items: items.len(), bytes }
+}
+
+/// Returns the quality of a failed request, if the failure is the peer's fault.
+fn error_quality(err: &RequestError) -> Option<ResponseQuality> {
+    match err {
+        RequestError::Timeout => Some(ResponseQuality::Timeout),
+        RequestError::BadResponse => Some(ResponseQuality::Invalid),
+        RequestError::ChannelClosed |
+        RequestError::ConnectionDropped |
+        RequestError::UnsupportedCapability => None,
+    }
+}
+
 /// The outcome of [`StateFetcher::poll_action`]
 enum PollAction {
     Ready(FetchAction),
@@ -302,6 +401,8 @@ struct Peer {
     best_number: u64,
     /// Tracks the current timeout value we use for the peer.
     timeout: Arc<AtomicU64>,
+    /// Rolling stats of the peer's responses.
+    stats: PeerStats,
 }
 
 impl Peer {
@@ -349,9 +450,10 @@ impl PeerState {
 struct Request<Req, Resp> {
     /// The issued request object
     /// TODO: this can be attached to the response in error case
-    #[allow(unused)]
     request: Req,
     response: oneshot::Sender<Resp>,
+    /// When the request was sent
+    started: Instant,
 }
 
 /// Requests that can be sent to the Syncer from a [`FetchClient`]
@@ -416,7 +518,7 @@ mod tests {
     use super::*;
     use crate::{peers::PeersManager, PeersConfig};
     use reth_primitives::{SealedHeader, H256, H512};
-    use std::future::poll_fn;
+    use std::{future::poll_fn, time::Duration};
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_poll_fetcher() {
@@ -486,6 +588,57 @@ mod tests {
         assert_eq!(fetcher.next_peer(), Some(peer2));
     }
 
+    #[tokio::test]
+    async fn test_peer_scoring() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let fast = H512::random();
+        let slow = H512::random();
+        let bad = H512::random();
+        // the slow peer has the lowest timeout, so it would be preferred without stats
+        fetcher.new_active_peer(fast, H256::random(), 1, Arc::new(AtomicU64::new(30)));
+        fetcher.new_active_peer(slow, H256::random(), 1, Arc::new(AtomicU64::new(10)));
+        fetcher.new_active_peer(bad, H256::random(), 1, Arc::new(AtomicU64::new(20)));
+        assert_eq!(fetcher.next_peer(), Some(slow));
+
+        for _ in 0..MIN_SAMPLES {
+            fetcher.record_response(
+                &fast,
+                ResponseQuality::Valid {
+                    rtt: Duration::from_millis(50),
+                    items: 100,
+                    bytes: 1024 * 1024,
+                },
+            );
+            fetcher.record_response(
+                &slow,
+                ResponseQuality::Valid { rtt: Duration::from_secs(5), items: 2, bytes: 1024 },
+            );
+            fetcher.record_response(&bad, ResponseQuality::Invalid);
+        }
+        assert_eq!(fetcher.next_peer(), Some(fast));
+        assert_eq!(fetcher.eviction_candidate(|_| true), Some(bad));
+        // trusted peers are excluded by the caller
+        assert_eq!(fetcher.eviction_candidate(|peer_id| *peer_id != bad), None);
+
+        // slow peers get smaller bodies requests
+        let (tx, _rx) = oneshot::channel();
+        let request = DownloadRequest::GetBlockBodies {
+            request: vec![H256::random(); 100],
+            response: tx,
+            priority: Priority::default(),
+        };
+        match fetcher.prepare_block_request(slow, request) {
+            BlockRequest::GetBlockBodies(GetBlockBodies(hashes)) => assert_eq!(hashes.len(), 2),
+            _ => unreachable!(),
+        }
+        assert!(!fetcher.peers[&slow].state.is_idle());
+        assert_eq!(fetcher.next_peer(), Some(fast));
+
+        fetcher.on_pending_disconnect(&bad);
+        assert_eq!(fetcher.eviction_candidate(|_| true), None);
+    }
+
     #[tokio::test]
     async fn test_on_block_headers_response() {
         let manager = PeersManager::new(PeersConfig::default());
@@ -531,6 +684,7 @@ mod tests {
                     direction: Default::default(),
                 },
                 response: tx,
+                started: Instant::now(),
             };
             let mut header = SealedHeader::default().unseal();
             header.number = 0u64;
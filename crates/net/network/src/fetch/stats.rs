@@ -0,0 +1,206 @@
+//! Rolling statistics of the responses of a peer.
+
+use std::time::Duration;
+
+/// How much a new sample affects the moving averages.
+const SAMPLE_WEIGHT: f64 = 0.2;
+
+/// The number of samples after which the stats of a peer are considered meaningful.
+pub(crate) const MIN_SAMPLES: u64 = 5;
+
+/// A round trip time that halves the latency component of the score.
+const REFERENCE_RTT_MS: f64 = 500.;
+
+/// A throughput that halves the throughput component of the score.
+const REFERENCE_THROUGHPUT: f64 = 256. * 1024.;
+
+/// How long a bodies response should take, used to size bodies requests.
+const TARGET_RESPONSE_TIME: Duration = Duration::from_secs(2);
+
+/// The minimum number of bodies to request from a peer.
+///
+/// Requests for multiple bodies that are answered with a single body are treated as malicious by
+/// the bodies downloader, so this is never lower than 2.
+const MIN_BODIES_REQUEST: usize = 2;
+
+/// The score of a peer without samples.
+///
+/// Unknown peers are ranked as average peers, so they get a chance to prove themselves.
+pub(crate) const NEUTRAL_SCORE: f64 = 0.5;
+
+/// The outcome of a request to a peer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ResponseQuality {
+    /// The peer responded with `items` that are `bytes` long in total, `items` is not zero.
+    Valid { rtt: Duration, items: usize, bytes: usize },
+    /// The peer responded with nothing.
+    Empty { rtt: Duration },
+    /// The peer did not respond in time.
+    ///
+    /// This is penalized like an empty response, because honest peers time out as well when they
+    /// are busy.
+    Timeout,
+    /// The peer responded with data that does not match the request.
+    Invalid,
+}
+
+/// Rolling statistics of the responses of a peer.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PeerStats {
+    /// Number of recorded responses.
+    samples: u64,
+    /// Number of recorded valid responses.
+    valid_samples: u64,
+    /// Moving average of the round trip time, in milliseconds.
+    rtt_ms: f64,
+    /// Moving average of the bytes per second of valid responses.
+    throughput: f64,
+    /// Moving average of the size of a single response item.
+    item_size: f64,
+    /// Moving average of the share of empty responses.
+    empty_rate: f64,
+    /// Moving average of the share of invalid responses.
+    invalid_rate: f64,
+}
+
+// === impl PeerStats ===
+
+impl PeerStats {
+    /// Records the outcome of a request.
+    pub(crate) fn record(&mut self, quality: ResponseQuality) {
+        let first = self.samples == 0;
+        self.samples += 1;
+        let (empty, invalid) = match quality {
+            ResponseQuality::Valid { rtt, items, bytes } => {
+                let first_valid = self.valid_samples == 0;
+                self.valid_samples += 1;
+                self.rtt_ms = average(self.rtt_ms, rtt.as_secs_f64() * 1000., first);
+                let throughput = bytes as f64 / rtt.as_secs_f64().max(0.001);
+                self.throughput = average(self.throughput, throughput, first_valid);
+                let item_size = bytes as f64 / items.max(1) as f64;
+                self.item_size = average(self.item_size, item_size, first_valid);
+                (0., 0.)
+            }
+            ResponseQuality::Empty { rtt } => {
+                self.rtt_ms = average(self.rtt_ms, rtt.as_secs_f64() * 1000., first);
+                (1., 0.)
+            }
+            ResponseQuality::Timeout => (1., 0.),
+            ResponseQuality::Invalid => (0., 1.),
+        };
+        self.empty_rate = average(self.empty_rate, empty, first);
+        self.invalid_rate = average(self.invalid_rate, invalid, first);
+    }
+
+    /// Returns the number of recorded responses.
+    pub(crate) fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns the score of the peer in `[0, 1]`, higher is better.
+    ///
+    /// The score combines the latency and throughput of the peer, weighted by how often it
+    /// responded with usable data.
+    pub(crate) fn score(&self) -> f64 {
+        if self.samples == 0 {
+            return NEUTRAL_SCORE
+        }
+        let quality = (1. - self.invalid_rate) * (1. - self.empty_rate);
+        let latency = if self.rtt_ms > 0. {
+            REFERENCE_RTT_MS / (self.rtt_ms + REFERENCE_RTT_MS)
+        } else {
+            // only invalid responses so far
+            0.
+        };
+        let throughput = self.throughput / (self.throughput + REFERENCE_THROUGHPUT);
+        quality * (latency + throughput) / 2.
+    }
+
+    /// Returns how many bodies should be requested from the peer at once, so that the response
+    /// arrives within [`TARGET_RESPONSE_TIME`].
+    ///
+    /// Returns `None` if there are not enough samples to tell.
+    pub(crate) fn max_bodies_request(&self) -> Option<usize> {
+        if self.samples < MIN_SAMPLES || self.item_size == 0. {
+            return None
+        }
+        let bodies = self.throughput * TARGET_RESPONSE_TIME.as_secs_f64() / self.item_size;
+        Some((bodies as usize).max(MIN_BODIES_REQUEST))
+    }
+}
+
+/// Returns the new moving average after the sample.
+fn average(current: f64, sample: f64, first: bool) -> f64 {
+    if first {
+        sample
+    } else {
+        current + SAMPLE_WEIGHT * (sample - current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid(rtt_ms: u64, items: usize, bytes: usize) -> ResponseQuality {
+        ResponseQuality::Valid { rtt: Duration::from_millis(rtt_ms), items, bytes }
+    }
+
+    #[test]
+    fn fast_peers_score_higher() {
+        let mut fast = PeerStats::default();
+        let mut slow = PeerStats::default();
+        assert_eq!(fast.score(), NEUTRAL_SCORE);
+
+        for _ in 0..MIN_SAMPLES {
+            fast.record(valid(50, 100, 1024 * 1024));
+            slow.record(valid(2000, 100, 1024 * 1024));
+        }
+        assert!(fast.score() > NEUTRAL_SCORE);
+        assert!(slow.score() < NEUTRAL_SCORE);
+        assert!(fast.score() > slow.score());
+    }
+
+    #[test]
+    fn bad_responses_lower_the_score() {
+        let mut stats = PeerStats::default();
+        for _ in 0..MIN_SAMPLES {
+            stats.record(valid(50, 100, 1024 * 1024));
+        }
+        let good_score = stats.score();
+
+        stats.record(ResponseQuality::Empty { rtt: Duration::from_millis(50) });
+        let empty_score = stats.score();
+        assert!(empty_score < good_score);
+
+        stats.record(ResponseQuality::Timeout);
+        let timeout_score = stats.score();
+        assert!(timeout_score < empty_score);
+
+        stats.record(ResponseQuality::Invalid);
+        assert!(stats.score() < timeout_score);
+
+        let mut invalid = PeerStats::default();
+        invalid.record(ResponseQuality::Invalid);
+        assert_eq!(invalid.score(), 0.);
+    }
+
+    #[test]
+    fn bodies_request_size() {
+        let mut stats = PeerStats::default();
+        stats.record(valid(1000, 10, 10 * 1024));
+        assert_eq!(stats.max_bodies_request(), None);
+
+        for _ in 1..MIN_SAMPLES {
+            stats.record(valid(1000, 10, 10 * 1024));
+        }
+        // 10 KiB/s at 1 KiB per body
+        assert_eq!(stats.max_bodies_request(), Some(20));
+
+        let mut slow = PeerStats::default();
+        for _ in 0..MIN_SAMPLES {
+            slow.record(valid(10_000, 2, 10 * 1024));
+        }
+        assert_eq!(slow.max_bodies_request(), Some(MIN_BODIES_REQUEST));
+    }
+}
@@ -117,6 +117,7 @@
 /// Common helpers for network testing.
 pub mod test_utils;
 
+mod bandwidth;
 mod builder;
 mod cache;
 pub mod config;
@@ -132,11 +133,14 @@ mod message;
 mod metrics;
 mod network;
 pub mod peers;
+pub mod protocol;
 mod session;
+pub mod snap;
 mod state;
 mod swarm;
 pub mod transactions;
 
+pub use bandwidth::EthMessageBandwidth;
 pub use builder::NetworkBuilder;
 pub use config::{NetworkConfig, NetworkConfigBuilder};
 pub use fetch::FetchClient;
@@ -144,6 +148,8 @@ pub use manager::{NetworkEvent, NetworkManager};
 pub use message::PeerRequest;
 pub use network::NetworkHandle;
 pub use peers::PeersConfig;
-pub use session::PeerInfo;
+pub use protocol::{ProtocolHandler, RlpxSubProtocols};
+pub use session::{BandwidthLimits, PeerInfo, SessionsConfig};
+pub use snap::{SnapClient, SnapProtocolHandler};
 
 pub use reth_eth_wire::DisconnectReason;
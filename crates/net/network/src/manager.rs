@@ -16,6 +16,7 @@
 //! to the local node. Once a (tcp) connection is established, both peers start to authenticate a [RLPx session](https://github.com/ethereum/devp2p/blob/master/rlpx.md) via a handshake. If the handshake was successful, both peers announce their capabilities and are now ready to exchange sub-protocol messages via the RLPx session.
 
 use crate::{
+    bandwidth::EthMessageBandwidth,
     config::NetworkConfig,
     discovery::Discovery,
     error::NetworkError,
@@ -40,7 +41,7 @@ use reth_eth_wire::{
 };
 use reth_net_common::bandwidth_meter::BandwidthMeter;
 use reth_network_api::{EthProtocolInfo, NetworkStatus, ReputationChangeKind};
-use reth_primitives::{PeerId, H256};
+use reth_primitives::{EnrForkIdEntry, PeerId, H256};
 use reth_provider::BlockProvider;
 use std::{
     net::SocketAddr,
@@ -150,6 +151,7 @@ where
             client,
             secret_key,
             mut discovery_v4_config,
+            mut discovery_v5_config,
             discovery_addr,
             listener_addr,
             peers_config,
@@ -163,6 +165,7 @@ where
             status,
             fork_filter,
             dns_discovery_config,
+            sub_protocols,
             ..
         } = config;
 
@@ -175,18 +178,31 @@ where
         discovery_v4_config = discovery_v4_config.map(|mut disc_config| {
             // merge configured boot nodes
             disc_config.bootstrap_nodes.extend(boot_nodes.clone());
-            disc_config.add_eip868_pair("eth", status.forkid);
+            disc_config.add_eip868_pair("eth", EnrForkIdEntry::from(status.forkid));
             disc_config
         });
 
-        let discovery =
-            Discovery::new(discovery_addr, secret_key, discovery_v4_config, dns_discovery_config)
-                .await?;
+        discovery_v5_config = discovery_v5_config.map(|mut disc_config| {
+            // advertise the RLPx listener and the fork ID in the ENR
+            disc_config.tcp_port = incoming.local_address().port();
+            disc_config.fork_id = Some(status.forkid);
+            disc_config
+        });
+
+        let discovery = Discovery::new(
+            discovery_addr,
+            secret_key,
+            discovery_v4_config,
+            discovery_v5_config,
+            dns_discovery_config,
+        )
+        .await?;
         // need to retrieve the addr here since provided port could be `0`
         let local_peer_id = discovery.local_id();
 
         let num_active_peers = Arc::new(AtomicUsize::new(0));
         let bandwidth_meter: BandwidthMeter = BandwidthMeter::default();
+        let message_bandwidth = EthMessageBandwidth::default();
 
         let sessions = SessionManager::new(
             secret_key,
@@ -196,6 +212,9 @@ where
             hello_message,
             fork_filter,
             bandwidth_meter.clone(),
+            message_bandwidth.clone(),
+            sub_protocols,
+            peers_handle.clone(),
         );
 
         let state = NetworkState::new(
@@ -218,6 +237,7 @@ where
             peers_handle,
             network_mode,
             bandwidth_meter,
+            message_bandwidth,
             Arc::new(AtomicU64::new(chain_spec.chain.id())),
         );
 
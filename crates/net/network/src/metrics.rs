@@ -39,4 +39,8 @@ pub struct NetworkMetrics {
 pub struct TransactionsManagerMetrics {
     /// Total number of propagated transactions
     pub(crate) propagated_transactions: Counter,
+    /// Total number of transaction messages dropped due to the per-peer rate limit
+    pub(crate) rate_limited_messages: Counter,
+    /// Total number of peers disconnected because they sent too many bad transactions
+    pub(crate) disconnected_spammers: Counter,
 }
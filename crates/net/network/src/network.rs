@@ -1,6 +1,6 @@
 use crate::{
-    config::NetworkMode, manager::NetworkEvent, message::PeerRequest, peers::PeersHandle,
-    session::PeerInfo, FetchClient,
+    bandwidth::EthMessageBandwidth, config::NetworkMode, manager::NetworkEvent,
+    message::PeerRequest, peers::PeersHandle, session::PeerInfo, FetchClient,
 };
 use async_trait::async_trait;
 use parking_lot::Mutex;
@@ -13,7 +13,7 @@ use reth_net_common::bandwidth_meter::BandwidthMeter;
 use reth_network_api::{
     NetworkError, NetworkInfo, NetworkStatus, PeerKind, Peers, PeersInfo, ReputationChangeKind,
 };
-use reth_primitives::{Head, NodeRecord, PeerId, TransactionSigned, TxHash, H256};
+use reth_primitives::{Head, NodeRecord, PeerId, TransactionSigned, H256};
 use std::{
     net::SocketAddr,
     sync::{
@@ -46,6 +46,7 @@ impl NetworkHandle {
         peers: PeersHandle,
         network_mode: NetworkMode,
         bandwidth_meter: BandwidthMeter,
+        message_bandwidth: EthMessageBandwidth,
         chain_id: Arc<AtomicU64>,
     ) -> Self {
         let inner = NetworkInner {
@@ -56,6 +57,7 @@ impl NetworkHandle {
             peers,
             network_mode,
             bandwidth_meter,
+            message_bandwidth,
             is_syncing: Arc::new(Default::default()),
             chain_id,
         };
@@ -141,11 +143,11 @@ impl NetworkHandle {
     }
 
     /// Send transactions hashes to the peer.
-    pub fn send_transactions_hashes(&self, peer_id: PeerId, msg: Vec<TxHash>) {
-        self.send_message(NetworkHandleMessage::SendPooledTransactionHashes {
-            peer_id,
-            msg: NewPooledTransactionHashes(msg),
-        })
+    ///
+    /// `eth/68` announcements are downgraded to `eth/66` announcements if the peer doesn't
+    /// support `eth/68`.
+    pub fn send_transactions_hashes(&self, peer_id: PeerId, msg: NewPooledTransactionHashes) {
+        self.send_message(NetworkHandleMessage::SendPooledTransactionHashes { peer_id, msg })
     }
 
     /// Send full transactions to the peer
@@ -161,6 +163,12 @@ impl NetworkHandle {
         &self.inner.bandwidth_meter
     }
 
+    /// Provides a shareable reference to the [`EthMessageBandwidth`] that tracks the bytes of
+    /// `eth` messages per message type.
+    pub fn message_bandwidth(&self) -> &EthMessageBandwidth {
+        &self.inner.message_bandwidth
+    }
+
     /// Send message to gracefully shutdown node.
     ///
     /// This will disconnect all active and pending sessions and prevent
@@ -268,8 +276,10 @@ struct NetworkInner {
     peers: PeersHandle,
     /// The mode of the network
     network_mode: NetworkMode,
-    /// Used to measure inbound & outbound bandwidth across network streams (currently unused)
+    /// Used to measure inbound & outbound bandwidth across network streams
     bandwidth_meter: BandwidthMeter,
+    /// Used to measure the bytes of `eth` messages per message type
+    message_bandwidth: EthMessageBandwidth,
     /// Represents if the network is currently syncing.
     is_syncing: Arc<AtomicBool>,
     /// The chain id
@@ -315,3 +325,24 @@ pub(crate) enum NetworkHandleMessage {
     /// Gracefully shutdown network
     Shutdown(oneshot::Sender<()>),
 }
+
+#[cfg(test)]
+impl NetworkHandle {
+    /// Creates a handle without a [`NetworkManager`](crate::NetworkManager), the messages sent to
+    /// the manager are returned by the receiver instead.
+    pub(crate) fn test_handle() -> (Self, mpsc::UnboundedReceiver<NetworkHandleMessage>) {
+        let (to_manager_tx, from_handle) = mpsc::unbounded_channel();
+        let handle = Self::new(
+            Default::default(),
+            Arc::new(Mutex::new(SocketAddr::from(([127, 0, 0, 1], 0)))),
+            to_manager_tx,
+            PeerId::random(),
+            crate::peers::PeersManager::new(Default::default()).handle(),
+            NetworkMode::default(),
+            BandwidthMeter::default(),
+            Default::default(),
+            Default::default(),
+        );
+        (handle, from_handle)
+    }
+}
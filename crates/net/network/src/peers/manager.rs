@@ -203,6 +203,16 @@ impl PeersManager {
         self.connection_info.num_outbound
     }
 
+    /// Returns `true` if another outbound connection can be established.
+    pub(crate) fn has_free_outbound_slot(&self) -> bool {
+        self.connection_info.has_out_capacity()
+    }
+
+    /// Returns `true` if the peer is a trusted peer.
+    pub(crate) fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.peers.get(peer_id).map_or(false, |peer| peer.is_trusted())
+    }
+
     /// Invoked when a pending session was closed.
     pub(crate) fn on_incoming_pending_session_dropped(
         &mut self,
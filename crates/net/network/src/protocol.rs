@@ -0,0 +1,60 @@
+//! Support for additional RLPx sub-protocols that are multiplexed next to `eth`.
+
+use crate::peers::PeersHandle;
+use futures::future::BoxFuture;
+use reth_eth_wire::{capability::Protocol, multiplex::ProtocolConnection};
+use reth_primitives::PeerId;
+use std::{fmt, sync::Arc};
+
+/// A handler for an RLPx sub-protocol, like `snap`, that runs on the same connection as `eth`.
+///
+/// The capability of the [`ProtocolHandler::protocol`] is announced to all peers. For every
+/// established session with a peer that also supports the protocol, a new [`ProtocolConnection`]
+/// is handed to [`ProtocolHandler::on_connection`].
+pub trait ProtocolHandler: fmt::Debug + Send + Sync + 'static {
+    /// The protocol this handler implements.
+    fn protocol(&self) -> Protocol;
+
+    /// Invoked when a session with a peer that shares the protocol was established.
+    ///
+    /// The returned future is spawned and should drive the connection, including any handshake
+    /// the protocol requires. The [`ProtocolConnection`] ends once the session is closed.
+    ///
+    /// Misbehaving peers should be reported via the [`PeersHandle`].
+    fn on_connection(
+        &self,
+        peer_id: PeerId,
+        conn: ProtocolConnection,
+        peers: PeersHandle,
+    ) -> BoxFuture<'static, ()>;
+}
+
+/// All registered [`ProtocolHandler`]s.
+#[derive(Debug, Clone, Default)]
+pub struct RlpxSubProtocols {
+    handlers: Vec<Arc<dyn ProtocolHandler>>,
+}
+
+// === impl RlpxSubProtocols ===
+
+impl RlpxSubProtocols {
+    /// Adds a new sub-protocol handler.
+    pub fn push(&mut self, handler: impl ProtocolHandler) {
+        self.handlers.push(Arc::new(handler))
+    }
+
+    /// Returns the protocols of all handlers.
+    pub fn protocols(&self) -> Vec<Protocol> {
+        self.handlers.iter().map(|handler| handler.protocol()).collect()
+    }
+
+    /// Returns an iterator over all handlers.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn ProtocolHandler>> + '_ {
+        self.handlers.iter()
+    }
+
+    /// Returns true if no sub-protocols are registered.
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
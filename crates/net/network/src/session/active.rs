@@ -1,6 +1,7 @@
 //! Represents an established session.
 
 use crate::{
+    bandwidth::EthMessageBandwidth,
     message::{NewBlockMessage, PeerMessage, PeerRequest, PeerResponse, PeerResponseResult},
     session::{
         config::INITIAL_REQUEST_TIMEOUT,
@@ -16,12 +17,14 @@ use reth_eth_wire::{
     capability::Capabilities,
     errors::{EthHandshakeError, EthStreamError, P2PStreamError},
     message::{EthBroadcastMessage, RequestPair},
-    DisconnectReason, EthMessage, EthStream, P2PStream,
+    DisconnectReason, EthMessage, EthStream, EthVersion, NewPooledTransactionHashes,
+    NewPooledTransactionHashes66, P2PStream,
 };
 use reth_interfaces::p2p::error::RequestError;
 use reth_metrics_common::metered_sender::MeteredSender;
 use reth_net_common::bandwidth_meter::MeteredStream;
 use reth_primitives::PeerId;
+use reth_rlp::Encodable;
 use std::{
     collections::VecDeque,
     future::Future,
@@ -94,6 +97,8 @@ pub(crate) struct ActiveSession {
     /// If an [ActiveSession] does not receive a response at all within this duration then it is
     /// considered a protocol violation and the session will initiate a drop.
     pub(crate) protocol_breach_request_timeout: Duration,
+    /// Tracks the bytes of exchanged `eth` messages per message type
+    pub(crate) message_bandwidth: EthMessageBandwidth,
 }
 
 impl ActiveSession {
@@ -178,8 +183,11 @@ impl ActiveSession {
             EthMessage::Transactions(msg) => {
                 self.try_emit_broadcast(PeerMessage::ReceivedTransaction(msg)).into()
             }
-            EthMessage::NewPooledTransactionHashes(msg) => {
-                self.try_emit_broadcast(PeerMessage::PooledTransactions(msg)).into()
+            EthMessage::NewPooledTransactionHashes66(msg) => {
+                self.try_emit_broadcast(PeerMessage::PooledTransactions(msg.into())).into()
+            }
+            EthMessage::NewPooledTransactionHashes68(msg) => {
+                self.try_emit_broadcast(PeerMessage::PooledTransactions(msg.into())).into()
             }
             EthMessage::GetBlockHeaders(req) => {
                 on_request!(req, BlockHeaders, GetBlockHeaders)
@@ -237,7 +245,16 @@ impl ActiveSession {
                 self.queued_outgoing.push_back(EthBroadcastMessage::NewBlock(msg.block).into());
             }
             PeerMessage::PooledTransactions(msg) => {
-                self.queued_outgoing.push_back(EthMessage::NewPooledTransactionHashes(msg).into());
+                // the announcement must match the negotiated version
+                let msg = match msg {
+                    NewPooledTransactionHashes::Eth68(msg)
+                        if self.conn.version() != EthVersion::Eth68 =>
+                    {
+                        NewPooledTransactionHashes66(msg.hashes).into()
+                    }
+                    msg => msg,
+                };
+                self.queued_outgoing.push_back(EthMessage::from(msg).into());
             }
             PeerMessage::EthRequest(req) => {
                 let deadline = self.request_deadline();
@@ -500,8 +517,14 @@ impl Future for ActiveSession {
                 if let Some(msg) = this.queued_outgoing.pop_front() {
                     progress = true;
                     let res = match msg {
-                        OutgoingMessage::Eth(msg) => this.conn.start_send_unpin(msg),
-                        OutgoingMessage::Broadcast(msg) => this.conn.start_send_broadcast(msg),
+                        OutgoingMessage::Eth(msg) => {
+                            this.message_bandwidth.record_outbound(msg.message_id(), msg.length());
+                            this.conn.start_send_unpin(msg)
+                        }
+                        OutgoingMessage::Broadcast(msg) => {
+                            this.message_bandwidth.record_outbound(msg.message_id(), msg.length());
+                            this.conn.start_send_broadcast(msg)
+                        }
                     };
                     if let Err(err) = res {
                         error!(target: "net::session", ?err,  remote_peer_id=?this.remote_peer_id, "failed to send message");
@@ -551,6 +574,8 @@ impl Future for ActiveSession {
                         match res {
                             Ok(msg) => {
                                 trace!(target: "net::session", msg_id=?msg.message_id(), remote_peer_id=?this.remote_peer_id, "received eth message");
+                                this.message_bandwidth
+                                    .record_inbound(msg.message_id(), msg.length());
                                 // decode and handle message
                                 match this.on_incoming(msg) {
                                     OnIncomingMessageOutcome::Ok => {
@@ -699,8 +724,8 @@ mod tests {
     };
     use reth_ecies::util::pk2id;
     use reth_eth_wire::{
-        EthVersion, GetBlockBodies, HelloMessage, NewPooledTransactionHashes, ProtocolVersion,
-        Status, StatusBuilder, UnauthedEthStream, UnauthedP2PStream,
+        EthMessageID, EthVersion, GetBlockBodies, HelloMessage, NewPooledTransactionHashes66,
+        ProtocolVersion, Status, StatusBuilder, UnauthedEthStream, UnauthedP2PStream,
     };
     use reth_net_common::bandwidth_meter::BandwidthMeter;
     use reth_primitives::{ForkFilter, Hardfork, MAINNET};
@@ -788,6 +813,7 @@ mod tests {
                 self.hello.clone(),
                 self.status,
                 self.fork_filter.clone(),
+                Vec::new(),
             ));
 
             let mut stream = ReceiverStream::new(pending_sessions_rx);
@@ -830,6 +856,7 @@ mod tests {
                             INITIAL_REQUEST_TIMEOUT.as_millis() as u64,
                         )),
                         protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
+                        message_bandwidth: Default::default(),
                     }
                 }
                 _ => {
@@ -928,7 +955,7 @@ mod tests {
         let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
             for _ in 0..num_messages {
                 client_stream
-                    .send(EthMessage::NewPooledTransactionHashes(NewPooledTransactionHashes(
+                    .send(EthMessage::NewPooledTransactionHashes66(NewPooledTransactionHashes66(
                         vec![],
                     )))
                     .await
@@ -951,6 +978,46 @@ mod tests {
         rx.await.unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_record_message_bandwidth() {
+        let mut builder = SessionBuilder::default();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let num_messages = 10;
+        let msg = EthMessage::NewPooledTransactionHashes66(NewPooledTransactionHashes66(vec![
+            Default::default(),
+        ]));
+        let msg_len = msg.length() as u64;
+
+        let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
+            for _ in 0..num_messages {
+                client_stream.send(msg.clone()).await.unwrap();
+            }
+        });
+
+        let (tx, rx) = oneshot::channel();
+
+        tokio::task::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let session = builder.connect_incoming(incoming).await;
+            let message_bandwidth = session.message_bandwidth.clone();
+            session.await;
+
+            tx.send(message_bandwidth).unwrap();
+        });
+
+        tokio::task::spawn(fut);
+
+        let message_bandwidth = rx.await.unwrap();
+        assert_eq!(
+            message_bandwidth.inbound(EthMessageID::NewPooledTransactionHashes),
+            num_messages * msg_len
+        );
+        assert_eq!(message_bandwidth.inbound(EthMessageID::Transactions), 0);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_request_timeout() {
         reth_tracing::init_test_tracing();
@@ -1028,7 +1095,9 @@ mod tests {
 
         let fut = builder.with_client_stream(local_addr, move |mut client_stream| async move {
             client_stream
-                .send(EthMessage::NewPooledTransactionHashes(NewPooledTransactionHashes(vec![])))
+                .send(EthMessage::NewPooledTransactionHashes66(NewPooledTransactionHashes66(
+                    vec![],
+                )))
                 .await
                 .unwrap();
             let _ = tokio::time::timeout(Duration::from_secs(100), client_stream.next()).await;
@@ -4,6 +4,7 @@ use crate::{
     peers::{DEFAULT_MAX_PEERS_INBOUND, DEFAULT_MAX_PEERS_OUTBOUND},
     session::{Direction, ExceedsSessionLimit},
 };
+use reth_net_common::ratelimit::BandwidthLimiter;
 use std::time::Duration;
 
 /// Default request timeout for a single request.
@@ -37,6 +38,11 @@ pub struct SessionsConfig {
     /// `PROTOCOL_BREACH_REQUEST_TIMEOUT`) this is considered a protocol violation and results in a
     /// dropped session.
     pub protocol_breach_request_timeout: Duration,
+    /// Upload and download rates to enforce.
+    ///
+    /// By default, the bandwidth is not limited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bandwidth_limits: BandwidthLimits,
 }
 
 impl Default for SessionsConfig {
@@ -55,6 +61,7 @@ impl Default for SessionsConfig {
             limits: Default::default(),
             initial_internal_request_timeout: INITIAL_REQUEST_TIMEOUT,
             protocol_breach_request_timeout: PROTOCOL_BREACH_REQUEST_TIMEOUT,
+            bandwidth_limits: Default::default(),
         }
     }
 }
@@ -69,6 +76,57 @@ impl SessionsConfig {
         self.session_event_buffer = n;
         self
     }
+
+    /// Sets the upload and download rates to enforce.
+    pub fn with_bandwidth_limits(mut self, limits: BandwidthLimits) -> Self {
+        self.bandwidth_limits = limits;
+        self
+    }
+}
+
+/// Upload and download rates in bytes per second.
+///
+/// The global rates are shared by all sessions, the per peer rates are enforced for every session
+/// individually. Rates apply to the bytes on the wire, including the handshake.
+///
+/// By default, no rates will be enforced. A rate of zero is not enforced either.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BandwidthLimits {
+    /// Maximum upload rate of all sessions combined.
+    pub upload: Option<u64>,
+    /// Maximum download rate of all sessions combined.
+    pub download: Option<u64>,
+    /// Maximum upload rate of a single session.
+    pub peer_upload: Option<u64>,
+    /// Maximum download rate of a single session.
+    pub peer_download: Option<u64>,
+}
+
+impl BandwidthLimits {
+    /// Sets the maximum upload rate of all sessions combined.
+    pub fn with_upload(mut self, bytes_per_second: u64) -> Self {
+        self.upload = Some(bytes_per_second);
+        self
+    }
+
+    /// Sets the maximum download rate of all sessions combined.
+    pub fn with_download(mut self, bytes_per_second: u64) -> Self {
+        self.download = Some(bytes_per_second);
+        self
+    }
+
+    /// Sets the maximum upload rate of a single session.
+    pub fn with_peer_upload(mut self, bytes_per_second: u64) -> Self {
+        self.peer_upload = Some(bytes_per_second);
+        self
+    }
+
+    /// Sets the maximum download rate of a single session.
+    pub fn with_peer_download(mut self, bytes_per_second: u64) -> Self {
+        self.peer_download = Some(bytes_per_second);
+        self
+    }
 }
 
 /// Limits for sessions.
@@ -197,10 +255,22 @@ impl SessionCounter {
     }
 }
 
+/// Returns a limiter that enforces the rate, if the rate is set and not zero.
+pub(crate) fn bandwidth_limiter(bytes_per_second: Option<u64>) -> Option<BandwidthLimiter> {
+    bytes_per_second.filter(|rate| *rate > 0).map(BandwidthLimiter::new)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_zero_bandwidth_limit_is_unlimited() {
+        assert!(bandwidth_limiter(None).is_none());
+        assert!(bandwidth_limiter(Some(0)).is_none());
+        assert_eq!(bandwidth_limiter(Some(1024)).unwrap().bytes_per_second(), 1024);
+    }
+
     #[test]
     fn test_limits() {
         let mut limits = SessionCounter::new(SessionLimits::default().with_max_pending_inbound(2));
@@ -9,7 +9,7 @@ use reth_eth_wire::{
     errors::EthStreamError,
     DisconnectReason, EthStream, P2PStream, Status,
 };
-use reth_net_common::bandwidth_meter::MeteredStream;
+use reth_net_common::bandwidth_meter::{BandwidthMeter, MeteredStream};
 use reth_primitives::PeerId;
 use std::{io, net::SocketAddr, sync::Arc, time::Instant};
 use tokio::{
@@ -27,6 +27,8 @@ pub(crate) struct PendingSessionHandle {
     pub(crate) disconnect_tx: Option<oneshot::Sender<()>>,
     /// The direction of the session
     pub(crate) direction: Direction,
+    /// Meters the bandwidth of the session's stream
+    pub(crate) bandwidth_meter: BandwidthMeter,
 }
 
 // === impl PendingSessionHandle ===
@@ -63,6 +65,8 @@ pub(crate) struct ActiveSessionHandle {
     pub(crate) client_version: String,
     /// The address we're connected to
     pub(crate) remote_addr: SocketAddr,
+    /// Meters the bandwidth of the session's stream
+    pub(crate) bandwidth_meter: BandwidthMeter,
 }
 
 // === impl ActiveSessionHandle ===
@@ -88,6 +92,8 @@ pub struct PeerInfo {
     pub remote_addr: SocketAddr,
     /// The direction of the session
     pub direction: Direction,
+    /// The bandwidth used by the session, including the handshake
+    pub bandwidth_meter: BandwidthMeter,
 }
 
 /// Events a pending session can produce.
@@ -1,9 +1,12 @@
 //! Support for handling peer sessions.
 use crate::{
+    bandwidth::EthMessageBandwidth,
     message::PeerMessage,
+    peers::PeersHandle,
+    protocol::RlpxSubProtocols,
     session::{
         active::ActiveSession,
-        config::SessionCounter,
+        config::{bandwidth_limiter, SessionCounter},
         handle::{
             ActiveSessionHandle, ActiveSessionMessage, PendingSessionEvent, PendingSessionHandle,
             SessionCommand,
@@ -15,13 +18,14 @@ use fnv::FnvHashMap;
 use futures::{future::Either, io, FutureExt, StreamExt};
 use reth_ecies::{stream::ECIESStream, ECIESError};
 use reth_eth_wire::{
-    capability::{Capabilities, CapabilityMessage},
+    capability::{Capabilities, CapabilityMessage, Protocol},
     errors::EthStreamError,
     DisconnectReason, HelloMessage, Status, UnauthedEthStream, UnauthedP2PStream,
 };
 use reth_metrics_common::metered_sender::MeteredSender;
 use reth_net_common::{
     bandwidth_meter::{BandwidthMeter, MeteredStream},
+    ratelimit::BandwidthLimiter,
     stream::HasRemoteAddr,
 };
 use reth_primitives::{ForkFilter, ForkId, ForkTransition, Head, PeerId};
@@ -46,7 +50,7 @@ use tracing::{instrument, trace};
 mod active;
 mod config;
 mod handle;
-pub use config::SessionsConfig;
+pub use config::{BandwidthLimits, SessionsConfig};
 
 /// Internal identifier for active sessions.
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq, Hash)]
@@ -101,6 +105,18 @@ pub(crate) struct SessionManager {
     active_session_rx: ReceiverStream<ActiveSessionMessage>,
     /// Used to measure inbound & outbound bandwidth across all managed streams
     bandwidth_meter: BandwidthMeter,
+    /// Limits the upload rate of all managed streams combined
+    upload_limiter: Option<BandwidthLimiter>,
+    /// Limits the download rate of all managed streams combined
+    download_limiter: Option<BandwidthLimiter>,
+    /// The upload and download rates enforced for every single stream
+    bandwidth_limits: BandwidthLimits,
+    /// Tracks the bytes of exchanged `eth` messages per message type
+    message_bandwidth: EthMessageBandwidth,
+    /// Additional RLPx sub-protocols that are multiplexed next to `eth`.
+    sub_protocols: RlpxSubProtocols,
+    /// Handed to the sub-protocols to report misbehaving peers.
+    peers_handle: PeersHandle,
 }
 
 // === impl SessionManager ===
@@ -115,6 +131,9 @@ impl SessionManager {
         hello_message: HelloMessage,
         fork_filter: ForkFilter,
         bandwidth_meter: BandwidthMeter,
+        message_bandwidth: EthMessageBandwidth,
+        sub_protocols: RlpxSubProtocols,
+        peers_handle: PeersHandle,
     ) -> Self {
         let (pending_sessions_tx, pending_sessions_rx) = mpsc::channel(config.session_event_buffer);
         let (active_session_tx, active_session_rx) = mpsc::channel(config.session_event_buffer);
@@ -137,6 +156,12 @@ impl SessionManager {
             active_session_tx: MeteredSender::new(active_session_tx, "network_active_session"),
             active_session_rx: ReceiverStream::new(active_session_rx),
             bandwidth_meter,
+            upload_limiter: bandwidth_limiter(config.bandwidth_limits.upload),
+            download_limiter: bandwidth_limiter(config.bandwidth_limits.download),
+            bandwidth_limits: config.bandwidth_limits,
+            message_bandwidth,
+            sub_protocols,
+            peers_handle,
         }
     }
 
@@ -163,6 +188,20 @@ impl SessionManager {
         self.hello_message.clone()
     }
 
+    /// Returns the meter and the limiters for a new session.
+    ///
+    /// The session is metered separately but also accounted for in the manager's meter, and is
+    /// subject to both the global and its own rate limits.
+    fn new_session_bandwidth(&self) -> SessionBandwidth {
+        let mut bandwidth =
+            SessionBandwidth { meter: self.bandwidth_meter.child(), ..Default::default() };
+        bandwidth.upload.extend(self.upload_limiter.clone());
+        bandwidth.upload.extend(bandwidth_limiter(self.bandwidth_limits.peer_upload));
+        bandwidth.download.extend(self.download_limiter.clone());
+        bandwidth.download.extend(bandwidth_limiter(self.bandwidth_limits.peer_download));
+        bandwidth
+    }
+
     /// Spawns the given future onto a new task that is tracked in the `spawned_tasks`
     /// [`JoinSet`](tokio::task::JoinSet).
     fn spawn<F>(&self, f: F)
@@ -208,7 +247,9 @@ impl SessionManager {
 
         let (disconnect_tx, disconnect_rx) = oneshot::channel();
         let pending_events = self.pending_sessions_tx.clone();
-        let metered_stream = MeteredStream::new_with_meter(stream, self.bandwidth_meter.clone());
+        let bandwidth = self.new_session_bandwidth();
+        let bandwidth_meter = bandwidth.meter.clone();
+        let metered_stream = bandwidth.wrap(stream);
         self.spawn(start_pending_incoming_session(
             disconnect_rx,
             session_id,
@@ -219,11 +260,13 @@ impl SessionManager {
             self.hello_message.clone(),
             self.status,
             self.fork_filter.clone(),
+            self.sub_protocols.protocols(),
         ));
 
         let handle = PendingSessionHandle {
             disconnect_tx: Some(disconnect_tx),
             direction: Direction::Incoming,
+            bandwidth_meter,
         };
         self.pending_sessions.insert(session_id, handle);
         self.counter.inc_pending_inbound();
@@ -235,6 +278,8 @@ impl SessionManager {
         let session_id = self.next_id();
         let (disconnect_tx, disconnect_rx) = oneshot::channel();
         let pending_events = self.pending_sessions_tx.clone();
+        let bandwidth = self.new_session_bandwidth();
+        let bandwidth_meter = bandwidth.meter.clone();
         self.spawn(start_pending_outbound_session(
             disconnect_rx,
             pending_events,
@@ -245,12 +290,14 @@ impl SessionManager {
             self.hello_message.clone(),
             self.status,
             self.fork_filter.clone(),
-            self.bandwidth_meter.clone(),
+            bandwidth,
+            self.sub_protocols.protocols(),
         ));
 
         let handle = PendingSessionHandle {
             disconnect_tx: Some(disconnect_tx),
             direction: Direction::Outgoing(remote_peer_id),
+            bandwidth_meter,
         };
         self.pending_sessions.insert(session_id, handle);
         self.counter.inc_pending_outbound();
@@ -381,13 +428,16 @@ impl SessionManager {
                 remote_addr,
                 peer_id,
                 capabilities,
-                conn,
+                mut conn,
                 status,
                 direction,
                 client_id,
             } => {
                 // move from pending to established.
-                self.remove_pending_session(&session_id);
+                let bandwidth_meter = self
+                    .remove_pending_session(&session_id)
+                    .map(|session| session.bandwidth_meter)
+                    .unwrap_or_default();
 
                 // If there's already a session to the peer then we disconnect right away
                 if self.active_sessions.contains_key(&peer_id) {
@@ -413,6 +463,18 @@ impl SessionManager {
                     })
                 }
 
+                // hand the connections of all shared sub-protocols to their handlers
+                for handler in self.sub_protocols.iter() {
+                    let name = handler.protocol().cap.name;
+                    if let Some(protocol_conn) = conn.inner_mut().protocol_connection(&name) {
+                        self.spawn(handler.on_connection(
+                            peer_id,
+                            protocol_conn,
+                            self.peers_handle.clone(),
+                        ));
+                    }
+                }
+
                 let (commands_to_session, commands_rx) = mpsc::channel(self.session_command_buffer);
 
                 let (to_session_tx, messages_rx) = mpsc::channel(self.session_command_buffer);
@@ -442,6 +504,7 @@ impl SessionManager {
                     ),
                     internal_request_timeout: Arc::clone(&timeout),
                     protocol_breach_request_timeout: self.protocol_breach_request_timeout,
+                    message_bandwidth: self.message_bandwidth.clone(),
                 };
 
                 self.spawn(session);
@@ -455,6 +518,7 @@ impl SessionManager {
                     commands_to_session,
                     client_version: client_id,
                     remote_addr,
+                    bandwidth_meter,
                 };
 
                 self.active_sessions.insert(peer_id, handle);
@@ -551,6 +615,7 @@ impl SessionManager {
                 remote_addr: session.remote_addr,
                 capabilities: session.capabilities.clone(),
                 client_version: session.client_version.clone(),
+                bandwidth_meter: session.bandwidth_meter.clone(),
             })
             .collect()
     }
@@ -565,6 +630,7 @@ impl SessionManager {
             remote_addr: session.remote_addr,
             capabilities: session.capabilities.clone(),
             client_version: session.client_version.clone(),
+            bandwidth_meter: session.bandwidth_meter.clone(),
         })
     }
 }
@@ -675,6 +741,28 @@ impl Direction {
 #[error("Session limit reached {0}")]
 pub struct ExceedsSessionLimit(pub(crate) u32);
 
+/// The meter and the rate limiters of a single session's stream.
+#[derive(Debug, Default)]
+struct SessionBandwidth {
+    meter: BandwidthMeter,
+    upload: Vec<BandwidthLimiter>,
+    download: Vec<BandwidthLimiter>,
+}
+
+impl SessionBandwidth {
+    /// Wraps the stream in a [`MeteredStream`] that enforces the limits.
+    fn wrap(self, stream: TcpStream) -> MeteredStream<TcpStream> {
+        let mut stream = MeteredStream::new_with_meter(stream, self.meter);
+        for limiter in self.upload {
+            stream = stream.with_upload_limiter(limiter);
+        }
+        for limiter in self.download {
+            stream = stream.with_download_limiter(limiter);
+        }
+        stream
+    }
+}
+
 /// Starts the authentication process for a connection initiated by a remote peer.
 ///
 /// This will wait for the _incoming_ handshake request and answer it.
@@ -689,6 +777,7 @@ pub(crate) async fn start_pending_incoming_session(
     hello: HelloMessage,
     status: Status,
     fork_filter: ForkFilter,
+    protocols: Vec<Protocol>,
 ) {
     authenticate(
         disconnect_rx,
@@ -701,6 +790,7 @@ pub(crate) async fn start_pending_incoming_session(
         hello,
         status,
         fork_filter,
+        protocols,
     )
     .await
 }
@@ -718,10 +808,11 @@ async fn start_pending_outbound_session(
     hello: HelloMessage,
     status: Status,
     fork_filter: ForkFilter,
-    bandwidth_meter: BandwidthMeter,
+    bandwidth: SessionBandwidth,
+    protocols: Vec<Protocol>,
 ) {
     let stream = match TcpStream::connect(remote_addr).await {
-        Ok(stream) => MeteredStream::new_with_meter(stream, bandwidth_meter),
+        Ok(stream) => bandwidth.wrap(stream),
         Err(error) => {
             let _ = events
                 .send(PendingSessionEvent::OutgoingConnectionError {
@@ -745,6 +836,7 @@ async fn start_pending_outbound_session(
         hello,
         status,
         fork_filter,
+        protocols,
     )
     .await
 }
@@ -762,6 +854,7 @@ async fn authenticate(
     hello: HelloMessage,
     status: Status,
     fork_filter: ForkFilter,
+    protocols: Vec<Protocol>,
 ) {
     let stream = match get_eciess_stream(stream, secret_key, direction).await {
         Ok(stream) => stream,
@@ -778,7 +871,7 @@ async fn authenticate(
         }
     };
 
-    let unauthed = UnauthedP2PStream::new(stream);
+    let unauthed = UnauthedP2PStream::new(stream).with_protocols(protocols);
 
     let auth = authenticate_stream(
         unauthed,
@@ -0,0 +1,520 @@
+//! Serves `snap/1` requests from the database and sends `snap/1` requests to peers.
+//!
+//! The `snap` protocol runs next to `eth` as an RLPx sub-protocol, see [ProtocolHandler].
+
+use crate::{peers::PeersHandle, protocol::ProtocolHandler};
+use futures::{future::BoxFuture, FutureExt, StreamExt};
+use parking_lot::Mutex;
+use reth_eth_wire::{
+    capability::Protocol, multiplex::ProtocolConnection, snap_protocol, AccountData, AccountRange,
+    ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges, GetTrieNodes, SnapMessage,
+    StorageData, StorageRanges, TrieNodes,
+};
+use reth_interfaces::p2p::error::{RequestError, RequestResult};
+use reth_network_api::ReputationChangeKind;
+use reth_primitives::{PeerId, H256};
+use reth_provider::SnapProvider;
+use reth_rlp::{Decodable, Encodable};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{mpsc, oneshot};
+use tracing::trace;
+
+// Limits: <https://github.com/ethereum/go-ethereum/blob/b0d44338bbcefee044f1f635a84487cbbd8f0538/eth/protocols/snap/handler.go#L34-L56>
+
+/// Maximum size of replies to data retrievals, regardless of the requested size.
+const MAX_RESPONSE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Maximum number of accounts, bytecodes and trie nodes to serve.
+///
+/// Used to limit lookups.
+const MAX_LOOKUPS_SERVE: usize = 1024;
+
+/// How long to wait for the response to a request sent via a [SnapClient].
+const SNAP_REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// The connections of all peers that support `snap`, used to send requests.
+type SnapPeers = Arc<Mutex<HashMap<PeerId, mpsc::UnboundedSender<SnapRequest>>>>;
+
+/// Serves the `snap/1` requests of all peers that support the protocol.
+///
+/// Only the latest state is served, requests for older state roots are answered with empty
+/// responses. Requests to peers are sent via the [SnapClient] returned by
+/// [SnapProtocolHandler::snap_client].
+pub struct SnapProtocolHandler<C> {
+    /// The client type that can interact with the chain.
+    client: Arc<C>,
+    /// The connections of all peers that support `snap`.
+    peers: SnapPeers,
+    /// The id of the next request sent by any [SnapClient] of this handler.
+    next_request_id: Arc<AtomicU64>,
+}
+
+// === impl SnapProtocolHandler ===
+
+impl<C> SnapProtocolHandler<C> {
+    /// Create a new instance
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client, peers: Default::default(), next_request_id: Default::default() }
+    }
+
+    /// Returns a new [SnapClient] that sends requests to the peers connected via this handler.
+    pub fn snap_client(&self) -> SnapClient {
+        SnapClient {
+            peers: Arc::clone(&self.peers),
+            next_request_id: Arc::clone(&self.next_request_id),
+        }
+    }
+}
+
+impl<C> fmt::Debug for SnapProtocolHandler<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapProtocolHandler").field("peers", &self.peers).finish_non_exhaustive()
+    }
+}
+
+impl<C> SnapProtocolHandler<C>
+where
+    C: SnapProvider,
+{
+    /// Handles a decoded `snap` message and returns the response, if any.
+    fn on_message(client: &C, msg: SnapMessage) -> Option<SnapMessage> {
+        let response = match msg {
+            SnapMessage::GetAccountRange(req) => {
+                SnapMessage::AccountRange(Self::on_account_range_request(client, req))
+            }
+            SnapMessage::GetStorageRanges(req) => {
+                SnapMessage::StorageRanges(Self::on_storage_ranges_request(client, req))
+            }
+            SnapMessage::GetByteCodes(req) => {
+                SnapMessage::ByteCodes(Self::on_bytecodes_request(client, req))
+            }
+            SnapMessage::GetTrieNodes(req) => {
+                SnapMessage::TrieNodes(Self::on_trie_nodes_request(client, req))
+            }
+            // responses are routed to the pending requests of the connection
+            _ => return None,
+        };
+        Some(response)
+    }
+
+    fn on_account_range_request(client: &C, req: GetAccountRange) -> AccountRange {
+        let GetAccountRange { request_id, root_hash, starting_hash, limit_hash, response_bytes } =
+            req;
+        let max_bytes = response_bytes.min(MAX_RESPONSE_BYTES) as usize;
+
+        let range = client
+            .account_range(root_hash, starting_hash, limit_hash, max_bytes)
+            .unwrap_or_default()
+            .unwrap_or_default();
+        AccountRange {
+            request_id,
+            accounts: range
+                .entries
+                .into_iter()
+                .map(|(hash, body)| AccountData { hash, body })
+                .collect(),
+            proof: range.proof,
+        }
+    }
+
+    fn on_storage_ranges_request(client: &C, req: GetStorageRanges) -> StorageRanges {
+        let GetStorageRanges {
+            request_id,
+            root_hash,
+            account_hashes,
+            starting_hash,
+            limit_hash,
+            response_bytes,
+        } = req;
+        let mut max_bytes = response_bytes.min(MAX_RESPONSE_BYTES) as usize;
+
+        let mut slots = Vec::new();
+        let mut proof = Vec::new();
+        let last = account_hashes.len().saturating_sub(1);
+        for (idx, account) in account_hashes.into_iter().take(MAX_LOOKUPS_SERVE).enumerate() {
+            // the origin only applies to the first and the limit to the last account
+            let origin = match idx {
+                0 if starting_hash.len() == 32 => H256::from_slice(&starting_hash),
+                _ => H256::zero(),
+            };
+            let limit = match idx {
+                idx if idx == last && limit_hash.len() == 32 => H256::from_slice(&limit_hash),
+                _ => H256::repeat_byte(0xff),
+            };
+
+            let Ok(Some(range)) =
+                client.storage_range(root_hash, account, origin, limit, max_bytes)
+            else {
+                break
+            };
+            let size = range.entries.iter().map(|(_, data)| 32 + data.len()).sum::<usize>();
+            slots.push(
+                range.entries.into_iter().map(|(hash, data)| StorageData { hash, data }).collect(),
+            );
+
+            // only the last range of the response may be incomplete and carry a proof
+            if !range.proof.is_empty() {
+                proof = range.proof;
+                break
+            }
+            max_bytes = max_bytes.saturating_sub(size);
+            if max_bytes == 0 {
+                break
+            }
+        }
+
+        StorageRanges { request_id, slots, proof }
+    }
+
+    fn on_bytecodes_request(client: &C, req: GetByteCodes) -> ByteCodes {
+        let GetByteCodes { request_id, hashes, response_bytes } = req;
+        let max_bytes = response_bytes.min(MAX_RESPONSE_BYTES) as usize;
+
+        let mut codes = Vec::new();
+        let mut total_bytes = 0;
+        for hash in hashes.into_iter().take(MAX_LOOKUPS_SERVE) {
+            if let Ok(Some(code)) = client.bytecode(hash) {
+                total_bytes += code.len();
+                codes.push(code);
+                if total_bytes > max_bytes {
+                    break
+                }
+            }
+        }
+
+        ByteCodes { request_id, codes }
+    }
+
+    fn on_trie_nodes_request(client: &C, req: GetTrieNodes) -> TrieNodes {
+        let GetTrieNodes { request_id, root_hash, paths, response_bytes } = req;
+        let max_bytes = response_bytes.min(MAX_RESPONSE_BYTES) as usize;
+
+        let mut nodes = Vec::new();
+        let mut total_bytes = 0;
+        'paths: for path_set in paths {
+            let requested = path_set.len().max(2) - 1;
+            let Ok(Some(found)) = client.trie_nodes(root_hash, &path_set) else { break };
+            let complete = found.len() == requested;
+            for node in found {
+                total_bytes += node.len();
+                nodes.push(node);
+                if total_bytes > max_bytes || nodes.len() >= MAX_LOOKUPS_SERVE {
+                    break 'paths
+                }
+            }
+            // nodes must be returned in request order, so stop at the first missing node
+            if !complete {
+                break
+            }
+        }
+
+        TrieNodes { request_id, nodes }
+    }
+}
+
+impl<C> ProtocolHandler for SnapProtocolHandler<C>
+where
+    C: SnapProvider + 'static,
+{
+    fn protocol(&self) -> Protocol {
+        snap_protocol()
+    }
+
+    fn on_connection(
+        &self,
+        peer_id: PeerId,
+        mut conn: ProtocolConnection,
+        peers_handle: PeersHandle,
+    ) -> BoxFuture<'static, ()> {
+        let client = Arc::clone(&self.client);
+        let peers = Arc::clone(&self.peers);
+        let (requests_tx, mut requests) = mpsc::unbounded_channel();
+        peers.lock().insert(peer_id, requests_tx.clone());
+
+        async move {
+            let mut inflight: HashMap<u64, oneshot::Sender<SnapMessage>> = HashMap::new();
+            loop {
+                tokio::select! {
+                    msg = conn.next() => {
+                        let Some(msg) = msg else { break };
+                        let msg = match SnapMessage::decode(&mut &msg[..]) {
+                            Ok(msg) => msg,
+                            Err(err) => {
+                                trace!(target: "net::snap", ?peer_id, ?err, "Failed to decode snap message");
+                                peers_handle.reputation_change(peer_id, ReputationChangeKind::BadMessage);
+                                break
+                            }
+                        };
+                        match msg {
+                            SnapMessage::AccountRange(_) |
+                            SnapMessage::StorageRanges(_) |
+                            SnapMessage::ByteCodes(_) |
+                            SnapMessage::TrieNodes(_) => {
+                                // responses to requests we never sent are ignored
+                                if let Some(tx) = inflight.remove(&msg.request_id()) {
+                                    let _ = tx.send(msg);
+                                }
+                            }
+                            request => {
+                                // serving a request walks the trie in the database
+                                let client = Arc::clone(&client);
+                                let response = match tokio::task::spawn_blocking(move || {
+                                    Self::on_message(&client, request)
+                                })
+                                .await
+                                {
+                                    Ok(Some(response)) => response,
+                                    Ok(None) => continue,
+                                    Err(_) => break,
+                                };
+                                let mut buf = Vec::with_capacity(response.length());
+                                response.encode(&mut buf);
+                                if conn.send(buf.into()).is_err() {
+                                    break
+                                }
+                            }
+                        }
+                    }
+                    req = requests.recv() => {
+                        let Some(SnapRequest { msg, response }) = req else { break };
+                        // drop the requests that timed out
+                        inflight.retain(|_, tx| !tx.is_closed());
+                        let mut buf = Vec::with_capacity(msg.length());
+                        msg.encode(&mut buf);
+                        if conn.send(buf.into()).is_err() {
+                            break
+                        }
+                        inflight.insert(msg.request_id(), response);
+                    }
+                }
+            }
+
+            let mut peers = peers.lock();
+            if peers.get(&peer_id).map_or(false, |tx| tx.same_channel(&requests_tx)) {
+                peers.remove(&peer_id);
+            }
+        }
+        .boxed()
+    }
+}
+
+/// A request sent to a peer via its `snap` connection.
+#[derive(Debug)]
+struct SnapRequest {
+    /// The request message.
+    msg: SnapMessage,
+    /// Where to send the response to.
+    response: oneshot::Sender<SnapMessage>,
+}
+
+/// Sends `snap/1` requests to the peers connected via a [SnapProtocolHandler].
+///
+/// The request ids of all requests are assigned by the client.
+#[derive(Debug, Clone)]
+pub struct SnapClient {
+    /// The connections of all peers that support `snap`.
+    peers: SnapPeers,
+    /// The id of the next request.
+    next_request_id: Arc<AtomicU64>,
+}
+
+// === impl SnapClient ===
+
+impl SnapClient {
+    /// Returns the ids of all peers a `snap` connection is established with.
+    pub fn peers(&self) -> Vec<PeerId> {
+        self.peers.lock().keys().copied().collect()
+    }
+
+    /// Requests a range of accounts from the peer.
+    pub async fn get_account_range(
+        &self,
+        peer_id: PeerId,
+        mut request: GetAccountRange,
+    ) -> RequestResult<AccountRange> {
+        request.request_id = self.next_request_id();
+        match self.request(peer_id, SnapMessage::GetAccountRange(request)).await? {
+            SnapMessage::AccountRange(response) => Ok(response),
+            _ => Err(RequestError::BadResponse),
+        }
+    }
+
+    /// Requests the storage ranges of accounts from the peer.
+    pub async fn get_storage_ranges(
+        &self,
+        peer_id: PeerId,
+        mut request: GetStorageRanges,
+    ) -> RequestResult<StorageRanges> {
+        request.request_id = self.next_request_id();
+        match self.request(peer_id, SnapMessage::GetStorageRanges(request)).await? {
+            SnapMessage::StorageRanges(response) => Ok(response),
+            _ => Err(RequestError::BadResponse),
+        }
+    }
+
+    /// Requests contract bytecodes from the peer.
+    pub async fn get_byte_codes(
+        &self,
+        peer_id: PeerId,
+        mut request: GetByteCodes,
+    ) -> RequestResult<ByteCodes> {
+        request.request_id = self.next_request_id();
+        match self.request(peer_id, SnapMessage::GetByteCodes(request)).await? {
+            SnapMessage::ByteCodes(response) => Ok(response),
+            _ => Err(RequestError::BadResponse),
+        }
+    }
+
+    /// Requests trie nodes from the peer.
+    pub async fn get_trie_nodes(
+        &self,
+        peer_id: PeerId,
+        mut request: GetTrieNodes,
+    ) -> RequestResult<TrieNodes> {
+        request.request_id = self.next_request_id();
+        match self.request(peer_id, SnapMessage::GetTrieNodes(request)).await? {
+            SnapMessage::TrieNodes(response) => Ok(response),
+            _ => Err(RequestError::BadResponse),
+        }
+    }
+
+    fn next_request_id(&self) -> u64 {
+        self.next_request_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Sends the request to the peer and waits for the response.
+    async fn request(&self, peer_id: PeerId, msg: SnapMessage) -> RequestResult<SnapMessage> {
+        let (tx, rx) = oneshot::channel();
+        let conn = self.peers.lock().get(&peer_id).cloned();
+        conn.ok_or(RequestError::UnsupportedCapability)?
+            .send(SnapRequest { msg, response: tx })
+            .map_err(|_| RequestError::ConnectionDropped)?;
+        match tokio::time::timeout(SNAP_REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(RequestError::ConnectionDropped),
+            Err(_) => Err(RequestError::Timeout),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        database::Database, mdbx::test_utils::create_test_rw_db, tables, transaction::DbTxMut,
+    };
+    use reth_primitives::{keccak256, Account, Bytes, Header, StorageEntry, U256};
+    use reth_provider::{trie::DBTrieLoader, ShareableDatabase};
+
+    const ACCOUNT_WITH_STORAGE: H256 = H256::repeat_byte(0x20);
+
+    /// Returns a provider for a state of three accounts, with three storage slots for
+    /// [ACCOUNT_WITH_STORAGE], and the state root.
+    fn provider() -> (ShareableDatabase<impl Database>, H256) {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        for byte in [0x10, 0x20, 0x30] {
+            let account =
+                Account { nonce: byte as u64, balance: U256::from(byte), ..Default::default() };
+            tx.put::<tables::HashedAccount>(H256::repeat_byte(byte), account).unwrap();
+        }
+        for byte in [0x01, 0x02, 0x03] {
+            let entry = StorageEntry { key: H256::repeat_byte(byte), value: U256::from(byte) };
+            tx.put::<tables::HashedStorage>(ACCOUNT_WITH_STORAGE, entry).unwrap();
+        }
+        let root = DBTrieLoader::default().calculate_root(&tx).unwrap();
+
+        let header = Header { state_root: root, ..Default::default() };
+        let hash = header.hash_slow();
+        tx.put::<tables::CanonicalHeaders>(0, hash).unwrap();
+        tx.put::<tables::Headers>((0, hash).into(), header).unwrap();
+        tx.commit().unwrap();
+
+        (ShareableDatabase::new(db), root)
+    }
+
+    fn on_message<C: SnapProvider>(client: &C, msg: SnapMessage) -> SnapMessage {
+        SnapProtocolHandler::<C>::on_message(client, msg).unwrap()
+    }
+
+    #[test]
+    fn account_range_with_proof() {
+        let (provider, root) = provider();
+        let request = GetAccountRange {
+            request_id: 1,
+            root_hash: root,
+            starting_hash: H256::zero(),
+            limit_hash: H256::repeat_byte(0x20),
+            response_bytes: MAX_RESPONSE_BYTES,
+        };
+        let SnapMessage::AccountRange(response) =
+            on_message(&provider, SnapMessage::GetAccountRange(request))
+        else {
+            panic!("expected account range")
+        };
+        assert_eq!(response.request_id, 1);
+        assert_eq!(
+            response.accounts.iter().map(|account| account.hash).collect::<Vec<_>>(),
+            vec![H256::repeat_byte(0x10), H256::repeat_byte(0x20)]
+        );
+        assert_eq!(keccak256(&response.proof[0]), root);
+
+        // an unknown state root is answered with an empty range
+        let request = GetAccountRange {
+            request_id: 2,
+            root_hash: H256::repeat_byte(0xaa),
+            starting_hash: H256::zero(),
+            limit_hash: H256::repeat_byte(0xff),
+            response_bytes: MAX_RESPONSE_BYTES,
+        };
+        let SnapMessage::AccountRange(response) =
+            on_message(&provider, SnapMessage::GetAccountRange(request))
+        else {
+            panic!("expected account range")
+        };
+        assert!(response.accounts.is_empty());
+        assert!(response.proof.is_empty());
+    }
+
+    #[test]
+    fn storage_range_with_proof_at_limit() {
+        let (provider, root) = provider();
+        let request = |limit: H256| GetStorageRanges {
+            request_id: 1,
+            root_hash: root,
+            account_hashes: vec![ACCOUNT_WITH_STORAGE],
+            starting_hash: Bytes::default(),
+            limit_hash: Bytes::from(limit.as_bytes().to_vec()),
+            response_bytes: MAX_RESPONSE_BYTES,
+        };
+        let storage_ranges = |limit| {
+            let SnapMessage::StorageRanges(response) =
+                on_message(&provider, SnapMessage::GetStorageRanges(request(limit)))
+            else {
+                panic!("expected storage ranges")
+            };
+            response
+        };
+
+        // the slots after the limit have to be proven
+        let response = storage_ranges(H256::repeat_byte(0x02));
+        assert_eq!(
+            response.slots[0].iter().map(|slot| slot.hash).collect::<Vec<_>>(),
+            vec![H256::repeat_byte(0x01), H256::repeat_byte(0x02)]
+        );
+        assert!(!response.proof.is_empty());
+
+        // the complete storage needs no proof
+        let response = storage_ranges(H256::repeat_byte(0xff));
+        assert_eq!(response.slots[0].len(), 3);
+        assert!(response.proof.is_empty());
+    }
+}
@@ -26,13 +26,17 @@ use std::{
         Arc,
     },
     task::{Context, Poll},
+    time::Duration,
 };
-use tokio::sync::oneshot;
+use tokio::{sync::oneshot, time::Interval};
 use tracing::{debug, error};
 
 /// Cache limit of blocks to keep track of for a single peer.
 const PEER_BLOCK_CACHE_LIMIT: usize = 512;
 
+/// How often to check for low scoring peers that can be evicted.
+const PEER_EVICTION_INTERVAL: Duration = Duration::from_secs(30);
+
 /// The [`NetworkState`] keeps track of the state of all peers in the network.
 ///
 /// This includes:
@@ -61,6 +65,8 @@ pub struct NetworkState<C> {
     /// The fetcher streams RLPx related requests on a per-peer basis to this type. This type will
     /// then queue in the request and notify the fetcher once the result has been received.
     state_fetcher: StateFetcher,
+    /// Interval at which the lowest scoring peer is evicted if all outbound slots are taken.
+    peer_eviction_interval: Interval,
 }
 
 impl<C> NetworkState<C>
@@ -84,6 +90,7 @@ where
             discovery,
             genesis_hash,
             state_fetcher,
+            peer_eviction_interval: tokio::time::interval(PEER_EVICTION_INTERVAL),
         }
     }
 
@@ -317,6 +324,32 @@ where
         }
     }
 
+    /// Disconnects the lowest scoring peer of the fetcher if its score is poor and all outbound
+    /// slots are taken, so that the slot can be used for a better peer.
+    ///
+    /// Trusted peers are never evicted.
+    fn evict_low_scoring_peer(&mut self) {
+        if self.peers_manager.has_free_outbound_slot() {
+            return
+        }
+        let peers_manager = &self.peers_manager;
+        if let Some(peer_id) =
+            self.state_fetcher.eviction_candidate(|peer_id| !peers_manager.is_trusted(peer_id))
+        {
+            debug!(
+                target : "net",
+                ?peer_id,
+                score = ?self.state_fetcher.peer_score(&peer_id),
+                "Evicting low scoring peer"
+            );
+            self.state_fetcher.on_pending_disconnect(&peer_id);
+            self.queued_messages.push_back(StateAction::Disconnect {
+                peer_id,
+                reason: Some(DisconnectReason::UselessPeer),
+            });
+        }
+    }
+
     /// Sends The message to the peer's session and queues in a response.
     ///
     /// Caution: this will replace an already pending response. It's the responsibility of the
@@ -442,6 +475,10 @@ where
                 self.on_peer_action(action);
             }
 
+            if self.peer_eviction_interval.poll_tick(cx).is_ready() {
+                self.evict_low_scoring_peer();
+            }
+
             if self.queued_messages.is_empty() {
                 return Poll::Pending
             }
@@ -535,6 +572,7 @@ mod tests {
             discovery: Discovery::noop(),
             genesis_hash: Default::default(),
             state_fetcher: StateFetcher::new(handle, Default::default()),
+            peer_eviction_interval: tokio::time::interval(PEER_EVICTION_INTERVAL),
         }
     }
 
@@ -259,12 +259,15 @@ where
                 // Insert peer only if no fork id or a valid fork id
                 if fork_id.map_or_else(|| true, |f| self.sessions.is_valid_fork_id(f)) {
                     self.state_mut().peers_mut().add_peer(peer_id, socket_addr, fork_id);
+                } else {
+                    trace!(target: "net", ?peer_id, ?fork_id, "Ignoring discovered node with incompatible fork id");
                 }
             }
             StateAction::DiscoveredEnrForkId { peer_id, fork_id } => {
                 if self.sessions.is_valid_fork_id(fork_id) {
                     self.state_mut().peers_mut().set_discovered_fork_id(peer_id, fork_id);
                 } else {
+                    trace!(target: "net", ?peer_id, ?fork_id, "Removing peer with incompatible fork id");
                     self.state_mut().peers_mut().remove_peer(peer_id);
                 }
             }
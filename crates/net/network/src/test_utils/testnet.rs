@@ -8,7 +8,9 @@ use futures::{FutureExt, StreamExt};
 use pin_project::pin_project;
 use reth_eth_wire::{capability::Capability, DisconnectReason, HelloBuilder};
 use reth_primitives::PeerId;
-use reth_provider::{test_utils::NoopProvider, BlockProvider, HeaderProvider};
+use reth_provider::{
+    test_utils::NoopProvider, BlockProvider, HeaderProvider, NodeDataProvider, ReceiptProvider,
+};
 use secp256k1::SecretKey;
 use std::{
     fmt,
@@ -35,7 +37,7 @@ pub struct Testnet<C> {
 
 impl<C> Testnet<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + NodeDataProvider,
 {
     /// Same as [`Self::try_create_with`] but panics on error
     pub async fn create_with(num_peers: usize, provider: Arc<C>) -> Self {
@@ -121,7 +123,7 @@ where
 
 impl<C> Testnet<C>
 where
-    C: BlockProvider + HeaderProvider + 'static,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + NodeDataProvider + 'static,
 {
     /// Spawns the testnet to a separate task
     pub fn spawn(self) -> TestnetHandle<C> {
@@ -176,7 +178,7 @@ impl<C> fmt::Debug for Testnet<C> {
 
 impl<C> Future for Testnet<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + NodeDataProvider,
 {
     type Output = ();
 
@@ -220,7 +222,7 @@ pub struct Peer<C> {
 
 impl<C> Peer<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + NodeDataProvider,
 {
     /// Returns the number of connected peers.
     pub fn num_peers(&self) -> usize {
@@ -249,7 +251,7 @@ where
 
 impl<C> Future for Peer<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + NodeDataProvider,
 {
     type Output = ();
 
@@ -275,7 +277,7 @@ pub struct PeerConfig<C = NoopProvider> {
 
 impl<C> PeerConfig<C>
 where
-    C: BlockProvider + HeaderProvider,
+    C: BlockProvider + HeaderProvider + ReceiptProvider + NodeDataProvider,
 {
     /// Initialize the network with a random secret key, allowing the devp2p and discovery to bind
     /// to any available IP and port.
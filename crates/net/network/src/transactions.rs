@@ -10,15 +10,18 @@ use crate::{
 };
 use futures::{stream::FuturesUnordered, FutureExt, StreamExt};
 use reth_eth_wire::{
-    GetPooledTransactions, NewPooledTransactionHashes, PooledTransactions, Transactions,
+    DisconnectReason, EthVersion, GetPooledTransactions, NewPooledTransactionHashes,
+    NewPooledTransactionHashes66, NewPooledTransactionHashes68, PooledTransactions, Transactions,
 };
 use reth_interfaces::{p2p::error::RequestResult, sync::SyncStateProvider};
+use reth_net_common::ratelimit::{Rate, RateLimit};
 use reth_network_api::{Peers, ReputationChangeKind};
 use reth_primitives::{
     FromRecoveredTransaction, IntoRecoveredTransaction, PeerId, TransactionSigned, TxHash, H256,
 };
 use reth_transaction_pool::{
-    error::PoolResult, PropagateKind, PropagatedTransactions, TransactionPool,
+    error::{PoolError, PoolResult},
+    PropagateKind, PropagatedTransactions, TransactionPool,
 };
 use std::{
     collections::{hash_map::Entry, HashMap},
@@ -27,14 +30,71 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::Instant,
 };
-use tokio::sync::{mpsc, oneshot};
 use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
-use tracing::trace;
+use tracing::{debug, trace};
 
 /// Cache limit of transactions to keep track of for a single peer.
 const PEER_TRANSACTION_CACHE_LIMIT: usize = 1024 * 10;
 
+/// Default number of transaction messages a single peer is allowed to send per second.
+const DEFAULT_MAX_TRANSACTION_MESSAGES_PER_SECOND: u64 = 64;
+
+/// Default number of bad transactions within [`DEFAULT_BAD_TRANSACTIONS_WINDOW`] after which a
+/// peer is disconnected.
+const DEFAULT_MAX_BAD_TRANSACTIONS_PER_PEER: usize = 32;
+
+/// Default time window in which the bad transactions of a peer are counted.
+const DEFAULT_BAD_TRANSACTIONS_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Soft limit for the number of hashes in a single `GetPooledTransactions` request.
+const GET_POOLED_TRANSACTION_SOFT_LIMIT_NUM_HASHES: usize = 256;
+
+/// Soft limit for the announced byte size of the transactions requested in a single
+/// `GetPooledTransactions` request.
+///
+/// Only applies to `eth/68` announcements, which include the size of the transactions.
+const GET_POOLED_TRANSACTION_SOFT_LIMIT_BYTE_SIZE: usize = 128 * 1024;
+
+/// The maximum size of a transaction a peer may announce in an `eth/68` announcement.
+///
+/// This is the size limit of transactions in the geth pool, larger transactions are not relayed.
+const MAX_ANNOUNCED_TRANSACTION_SIZE: usize = 128 * 1024;
+
+/// Configuration for the [`TransactionsManager`].
+#[derive(Debug, Clone, Copy)]
+pub struct TransactionsManagerConfig {
+    /// The rate at which a single peer is allowed to send `Transactions` and
+    /// `NewPooledTransactionHashes` messages.
+    ///
+    /// Messages that exceed this rate are dropped.
+    pub peer_message_rate: Rate,
+    /// The number of invalid or underpriced transactions a peer is allowed to send within the
+    /// `bad_transactions_window` before it is disconnected.
+    pub max_bad_transactions_per_peer: usize,
+    /// The time window in which the bad transactions of a peer are counted, the count starts over
+    /// with the first bad transaction after the window elapsed.
+    pub bad_transactions_window: Duration,
+}
+
+impl Default for TransactionsManagerConfig {
+    fn default() -> Self {
+        Self {
+            peer_message_rate: Rate::new(
+                DEFAULT_MAX_TRANSACTION_MESSAGES_PER_SECOND,
+                Duration::from_secs(1),
+            ),
+            max_bad_transactions_per_peer: DEFAULT_MAX_BAD_TRANSACTIONS_PER_PEER,
+            bad_transactions_window: DEFAULT_BAD_TRANSACTIONS_WINDOW,
+        }
+    }
+}
+
 /// The future for inserting a function into the pool
 pub type PoolImportFuture = Pin<Box<dyn Future<Output = PoolResult<TxHash>> + Send + 'static>>;
 
@@ -102,6 +162,8 @@ pub struct TransactionsManager<Pool> {
     pending_transactions: ReceiverStream<TxHash>,
     /// Incoming events from the [`NetworkManager`](crate::NetworkManager).
     transaction_events: UnboundedReceiverStream<NetworkTransactionEvent>,
+    /// Spam protection settings.
+    config: TransactionsManagerConfig,
     /// TransactionsManager metrics
     metrics: TransactionsManagerMetrics,
 }
@@ -114,6 +176,18 @@ impl<Pool: TransactionPool> TransactionsManager<Pool> {
         network: NetworkHandle,
         pool: Pool,
         from_network: mpsc::UnboundedReceiver<NetworkTransactionEvent>,
+    ) -> Self {
+        Self::with_config(network, pool, from_network, Default::default())
+    }
+
+    /// Sets up a new instance with the given [`TransactionsManagerConfig`].
+    ///
+    /// Note: This expects an existing [`NetworkManager`](crate::NetworkManager) instance.
+    pub fn with_config(
+        network: NetworkHandle,
+        pool: Pool,
+        from_network: mpsc::UnboundedReceiver<NetworkTransactionEvent>,
+        config: TransactionsManagerConfig,
     ) -> Self {
         let network_events = network.event_listener();
         let (command_tx, command_rx) = mpsc::unbounded_channel();
@@ -133,6 +207,7 @@ impl<Pool: TransactionPool> TransactionsManager<Pool> {
             command_rx: UnboundedReceiverStream::new(command_rx),
             pending_transactions: ReceiverStream::new(pending),
             transaction_events: UnboundedReceiverStream::new(from_network),
+            config,
             metrics: Default::default(),
         }
     }
@@ -162,6 +237,8 @@ where
                 .pool
                 .get_all(request.0)
                 .into_iter()
+                // private transactions are never shared with peers
+                .filter(|tx| !tx.origin.is_private())
                 .map(|tx| tx.transaction.to_recovered_transaction().into_signed())
                 .collect::<Vec<_>>();
 
@@ -196,6 +273,7 @@ where
             self.pool
                 .get_all(hashes)
                 .into_iter()
+                .filter(|tx| !tx.origin.is_private())
                 .map(|tx| {
                     (*tx.hash(), Arc::new(tx.transaction.to_recovered_transaction().into_signed()))
                 })
@@ -227,7 +305,8 @@ where
                         propagated.0.entry(*hash).or_default().push(PropagateKind::Hash(*peer_id));
                     }
                     // send hashes of transactions
-                    self.network.send_transactions_hashes(*peer_id, hashes);
+                    let msg = new_pooled_transaction_hashes(peer.version, &full);
+                    self.network.send_transactions_hashes(*peer_id, msg);
                 } else {
                     // send full transactions
                     self.network.send_transactions(*peer_id, full);
@@ -246,6 +325,9 @@ where
     }
 
     /// Request handler for an incoming `NewPooledTransactionHashes`
+    ///
+    /// Missing transactions are requested in batches, for `eth/68` announcements the batches are
+    /// also limited by the announced sizes of the transactions.
     fn on_new_pooled_transaction_hashes(
         &mut self,
         peer_id: PeerId,
@@ -256,11 +338,29 @@ where
             return
         }
 
+        // the announced type and size of each transaction, if any
+        let mut announced = HashMap::new();
+        let mut transactions = match msg {
+            NewPooledTransactionHashes::Eth66(msg) => msg.0,
+            NewPooledTransactionHashes::Eth68(msg) => {
+                if msg.types.len() != msg.hashes.len() || msg.sizes.len() != msg.hashes.len() {
+                    trace!(target: "net::tx", ?peer_id, "Received malformed eth/68 announcement");
+                    self.report_bad_message(peer_id);
+                    return
+                }
+                if msg.sizes.iter().any(|size| *size > MAX_ANNOUNCED_TRANSACTION_SIZE) {
+                    trace!(target: "net::tx", ?peer_id, "Received eth/68 announcement of oversized transactions");
+                    self.report_bad_message(peer_id);
+                    return
+                }
+                announced.extend(msg.iter().map(|(hash, ty, size)| (*hash, (*ty, *size))));
+                msg.hashes
+            }
+        };
+
         let mut num_already_seen = 0;
 
         if let Some(peer) = self.peers.get_mut(&peer_id) {
-            let mut transactions = msg.0;
-
             // keep track of the transactions the peer knows
             for tx in transactions.iter().copied() {
                 if !peer.transactions.insert(tx) {
@@ -270,20 +370,37 @@ where
 
             self.pool.retain_unknown(&mut transactions);
 
-            if transactions.is_empty() {
-                // nothing to request
-                return
-            }
+            // request the missing transactions in batches
+            let mut hashes = transactions.into_iter().peekable();
+            while hashes.peek().is_some() {
+                let mut request = Vec::new();
+                let mut request_announced = HashMap::new();
+                let mut size = 0;
+                while request.len() < GET_POOLED_TRANSACTION_SOFT_LIMIT_NUM_HASHES &&
+                    size < GET_POOLED_TRANSACTION_SOFT_LIMIT_BYTE_SIZE
+                {
+                    let Some(hash) = hashes.next() else { break };
+                    if let Some((ty, tx_size)) = announced.remove(&hash) {
+                        size = size.saturating_add(tx_size);
+                        request_announced.insert(hash, (ty, tx_size));
+                    }
+                    request.push(hash);
+                }
 
-            // request the missing transactions
-            let (response, rx) = oneshot::channel();
-            let req = PeerRequest::GetPooledTransactions {
-                request: GetPooledTransactions(transactions),
-                response,
-            };
+                let (response, rx) = oneshot::channel();
+                let req = PeerRequest::GetPooledTransactions {
+                    request: GetPooledTransactions(request),
+                    response,
+                };
 
-            if peer.request_tx.try_send(req).is_ok() {
-                self.inflight_requests.push(GetPooledTxRequest { peer_id, response: rx })
+                if peer.request_tx.try_send(req).is_err() {
+                    break
+                }
+                self.inflight_requests.push(GetPooledTxRequest {
+                    peer_id,
+                    announced: request_announced,
+                    response: rx,
+                })
             }
         }
 
@@ -292,14 +409,43 @@ where
         }
     }
 
+    /// Handles the response to a `GetPooledTransactions` request.
+    ///
+    /// Transactions that don't match the type or size the peer announced are dropped and count as
+    /// bad transactions of the peer.
+    fn on_pooled_transactions_response(
+        &mut self,
+        peer_id: PeerId,
+        announced: HashMap<TxHash, (u8, usize)>,
+        mut transactions: Vec<TransactionSigned>,
+    ) {
+        let num_transactions = transactions.len();
+        transactions.retain(|tx| match announced.get(&tx.hash()) {
+            Some((ty, size)) => tx.tx_type() as u8 == *ty && tx.envelope_length() == *size,
+            None => true,
+        });
+
+        let num_mismatched = num_transactions - transactions.len();
+        if num_mismatched > 0 {
+            trace!(target: "net::tx", ?peer_id, num_mismatched, "Received transactions that don't match their announcement");
+            self.on_bad_transactions(peer_id, num_mismatched);
+        }
+
+        self.import_transactions(peer_id, transactions, TransactionSource::Response);
+    }
+
     /// Handles dedicated transaction events related tot the `eth` protocol.
     fn on_network_tx_event(&mut self, event: NetworkTransactionEvent) {
         match event {
             NetworkTransactionEvent::IncomingTransactions { peer_id, msg } => {
-                self.import_transactions(peer_id, msg.0, TransactionSource::Broadcast);
+                if self.on_peer_message(peer_id) {
+                    self.import_transactions(peer_id, msg.0, TransactionSource::Broadcast);
+                }
             }
             NetworkTransactionEvent::IncomingPooledTransactionHashes { peer_id, msg } => {
-                self.on_new_pooled_transaction_hashes(peer_id, msg)
+                if self.on_peer_message(peer_id) {
+                    self.on_new_pooled_transaction_hashes(peer_id, msg)
+                }
             }
             NetworkTransactionEvent::GetPooledTransactions { peer_id, request, response } => {
                 self.on_get_pooled_transactions(peer_id, request, response)
@@ -323,7 +469,9 @@ where
                 // remove the peer
                 self.peers.remove(&peer_id);
             }
-            NetworkEvent::SessionEstablished { peer_id, messages, .. } => {
+            NetworkEvent::SessionEstablished { peer_id, messages, status, .. } => {
+                let version = EthVersion::try_from(status.version).unwrap_or(EthVersion::Eth66);
+
                 // insert a new peer
                 self.peers.insert(
                     peer_id,
@@ -332,13 +480,31 @@ where
                             NonZeroUsize::new(PEER_TRANSACTION_CACHE_LIMIT).unwrap(),
                         ),
                         request_tx: messages,
+                        rate_limit: RateLimit::new(self.config.peer_message_rate),
+                        bad_transactions: 0,
+                        bad_transactions_since: Instant::now(),
+                        version,
                     },
                 );
 
                 // Send a `NewPooledTransactionHashes` to the peer with _all_ transactions in the
                 // pool
                 if !self.network.is_syncing() {
-                    let msg = NewPooledTransactionHashes(self.pool.pooled_transactions());
+                    let hashes = self.pool.pooled_transactions();
+                    let msg = if version == EthVersion::Eth68 {
+                        let transactions = self
+                            .pool
+                            .get_all(hashes)
+                            .into_iter()
+                            .filter(|tx| !tx.origin.is_private())
+                            .map(|tx| {
+                                Arc::new(tx.transaction.to_recovered_transaction().into_signed())
+                            })
+                            .collect::<Vec<_>>();
+                        new_pooled_transaction_hashes(version, &transactions)
+                    } else {
+                        NewPooledTransactionHashes66(hashes).into()
+                    };
                     self.network.send_message(NetworkHandleMessage::SendPooledTransactionHashes {
                         peer_id,
                         msg,
@@ -363,7 +529,7 @@ where
         }
 
         // tracks the quality of the given transactions
-        let mut has_bad_transactions = false;
+        let mut num_bad_transactions = 0;
         let mut num_already_seen = 0;
 
         if let Some(peer) = self.peers.get_mut(&peer_id) {
@@ -372,7 +538,7 @@ where
                 let tx = if let Some(tx) = tx.into_ecrecovered() {
                     tx
                 } else {
-                    has_bad_transactions = true;
+                    num_bad_transactions += 1;
                     continue
                 };
 
@@ -405,11 +571,49 @@ where
             }
         }
 
-        if has_bad_transactions || num_already_seen > 0 {
+        if num_bad_transactions > 0 {
+            self.on_bad_transactions(peer_id, num_bad_transactions);
+        } else if num_already_seen > 0 {
             self.report_bad_message(peer_id);
         }
     }
 
+    /// Applies the per-peer rate limit to an incoming transaction message.
+    ///
+    /// Returns `false` if the message should be dropped.
+    ///
+    /// Exceeding the rate limit is not penalized: honest peers relaying a burst of valid
+    /// transactions can hit it, so the message is only dropped.
+    fn on_peer_message(&mut self, peer_id: PeerId) -> bool {
+        let Some(peer) = self.peers.get_mut(&peer_id) else { return true };
+        if peer.rate_limit.try_tick() {
+            return true
+        }
+        trace!(target: "net::tx", ?peer_id, "Dropping rate limited transaction message");
+        self.metrics.rate_limited_messages.increment(1);
+        false
+    }
+
+    /// Records bad transactions sent by the peer and disconnects the peer once it exceeded the
+    /// configured limit within the configured window.
+    fn on_bad_transactions(&mut self, peer_id: PeerId, num: usize) {
+        self.report_bad_message(peer_id);
+
+        let Some(peer) = self.peers.get_mut(&peer_id) else { return };
+        if peer.bad_transactions_since.elapsed() >= self.config.bad_transactions_window {
+            peer.bad_transactions = 0;
+            peer.bad_transactions_since = Instant::now();
+        }
+        let limit = self.config.max_bad_transactions_per_peer;
+        let exceeded_before = peer.bad_transactions > limit;
+        peer.bad_transactions = peer.bad_transactions.saturating_add(num);
+        if !exceeded_before && peer.bad_transactions > limit {
+            debug!(target: "net::tx", ?peer_id, bad_transactions=peer.bad_transactions, "Disconnecting peer that sent too many bad transactions");
+            self.metrics.disconnected_spammers.increment(1);
+            self.network.disconnect_peer_with_reason(peer_id, DisconnectReason::UselessPeer);
+        }
+    }
+
     fn report_bad_message(&self, peer_id: PeerId) {
         self.network.reputation_change(peer_id, ReputationChangeKind::BadTransactions);
     }
@@ -418,10 +622,14 @@ where
         self.transactions_by_peers.remove(&hash);
     }
 
-    fn on_bad_import(&mut self, hash: TxHash) {
-        if let Some(peers) = self.transactions_by_peers.remove(&hash) {
+    fn on_bad_import(&mut self, err: PoolError) {
+        if let Some(peers) = self.transactions_by_peers.remove(err.hash()) {
+            if !err.is_bad_transaction() {
+                // the transaction was rejected due to the local state of the pool
+                return
+            }
             for peer_id in peers {
-                self.report_bad_message(peer_id);
+                self.on_bad_transactions(peer_id, 1);
             }
         }
     }
@@ -464,7 +672,7 @@ where
                     this.inflight_requests.push(req);
                 }
                 Poll::Ready(Ok(Ok(txs))) => {
-                    this.import_transactions(req.peer_id, txs.0, TransactionSource::Response);
+                    this.on_pooled_transactions_response(req.peer_id, req.announced, txs.0);
                 }
                 Poll::Ready(Ok(Err(_))) => {
                     this.report_bad_message(req.peer_id);
@@ -482,7 +690,7 @@ where
                     this.on_good_import(hash);
                 }
                 Err(err) => {
-                    this.on_bad_import(*err.hash());
+                    this.on_bad_import(err);
                 }
             }
         }
@@ -523,6 +731,8 @@ impl TransactionSource {
 #[allow(missing_docs)]
 struct GetPooledTxRequest {
     peer_id: PeerId,
+    /// The announced type and size of the requested transactions, if the peer announced them.
+    announced: HashMap<TxHash, (u8, usize)>,
     response: oneshot::Receiver<RequestResult<PooledTransactions>>,
 }
 
@@ -532,6 +742,32 @@ struct Peer {
     transactions: LruCache<H256>,
     /// A communication channel directly to the session task.
     request_tx: PeerRequestSender,
+    /// Limits the rate of incoming transaction messages.
+    rate_limit: RateLimit,
+    /// Number of invalid or underpriced transactions the peer sent us in the current window.
+    bad_transactions: usize,
+    /// When the current window of `bad_transactions` started.
+    bad_transactions_since: Instant,
+    /// The negotiated `eth` version of the session.
+    version: EthVersion,
+}
+
+/// Creates the announcement for the given transactions that matches the `eth` version of the
+/// peer.
+fn new_pooled_transaction_hashes(
+    version: EthVersion,
+    transactions: &[Arc<TransactionSigned>],
+) -> NewPooledTransactionHashes {
+    let hashes = transactions.iter().map(|tx| tx.hash()).collect();
+    if version != EthVersion::Eth68 {
+        return NewPooledTransactionHashes66(hashes).into()
+    }
+    NewPooledTransactionHashes68 {
+        types: transactions.iter().map(|tx| tx.tx_type() as u8).collect(),
+        sizes: transactions.iter().map(|tx| tx.envelope_length()).collect(),
+        hashes,
+    }
+    .into()
 }
 
 /// Commands to send to the [`TransactionsManager`](crate::transactions::TransactionsManager)
@@ -560,8 +796,9 @@ mod tests {
     use super::*;
     use crate::{NetworkConfigBuilder, NetworkManager};
     use reth_interfaces::sync::{SyncState, SyncStateUpdater};
+    use reth_primitives::Address;
     use reth_provider::test_utils::NoopProvider;
-    use reth_transaction_pool::test_utils::testing_pool;
+    use reth_transaction_pool::test_utils::{testing_pool, TestPool};
     use secp256k1::SecretKey;
 
     #[tokio::test(flavor = "multi_thread")]
@@ -594,4 +831,173 @@ mod tests {
 
         assert!(pool.is_empty());
     }
+
+    /// Returns a manager whose network messages are returned by the receiver.
+    fn test_manager(
+        config: TransactionsManagerConfig,
+    ) -> (TransactionsManager<TestPool>, mpsc::UnboundedReceiver<NetworkHandleMessage>) {
+        let (network, from_manager) = NetworkHandle::test_handle();
+        let (_, from_network) = mpsc::unbounded_channel();
+        (
+            TransactionsManager::with_config(network, testing_pool(), from_network, config),
+            from_manager,
+        )
+    }
+
+    /// Adds a session to a new peer, returns the peer and the requests sent to it.
+    fn add_peer(
+        manager: &mut TransactionsManager<TestPool>,
+        version: EthVersion,
+    ) -> (PeerId, mpsc::Receiver<PeerRequest>) {
+        let peer_id = PeerId::random();
+        let (to_session_tx, requests) = mpsc::channel(8);
+        manager.peers.insert(
+            peer_id,
+            Peer {
+                transactions: LruCache::new(
+                    NonZeroUsize::new(PEER_TRANSACTION_CACHE_LIMIT).unwrap(),
+                ),
+                request_tx: PeerRequestSender { peer_id, to_session_tx },
+                rate_limit: RateLimit::new(manager.config.peer_message_rate),
+                bad_transactions: 0,
+                bad_transactions_since: Instant::now(),
+                version,
+            },
+        );
+        (peer_id, requests)
+    }
+
+    /// Returns the reputation changes and disconnects the manager sent to the network.
+    fn peer_actions(
+        from_manager: &mut mpsc::UnboundedReceiver<NetworkHandleMessage>,
+    ) -> (Vec<(PeerId, ReputationChangeKind)>, Vec<(PeerId, Option<DisconnectReason>)>) {
+        let (mut reputation_changes, mut disconnects) = (Vec::new(), Vec::new());
+        while let Ok(msg) = from_manager.try_recv() {
+            match msg {
+                NetworkHandleMessage::ReputationChange(peer_id, kind) => {
+                    reputation_changes.push((peer_id, kind))
+                }
+                NetworkHandleMessage::DisconnectPeer(peer_id, reason) => {
+                    disconnects.push((peer_id, reason))
+                }
+                _ => {}
+            }
+        }
+        (reputation_changes, disconnects)
+    }
+
+    #[tokio::test]
+    async fn test_reject_oversized_announcement() {
+        let (mut manager, mut from_manager) = test_manager(Default::default());
+        let (peer_id, mut requests) = add_peer(&mut manager, EthVersion::Eth68);
+
+        let msg = NewPooledTransactionHashes68 {
+            types: vec![0, 0],
+            sizes: vec![usize::MAX, 100],
+            hashes: vec![H256::random(), H256::random()],
+        };
+        manager.on_new_pooled_transaction_hashes(peer_id, msg.into());
+
+        assert!(requests.try_recv().is_err());
+        let (reputation_changes, _) = peer_actions(&mut from_manager);
+        assert_eq!(reputation_changes, vec![(peer_id, ReputationChangeKind::BadTransactions)]);
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_peer_with_bad_transactions() {
+        let config =
+            TransactionsManagerConfig { max_bad_transactions_per_peer: 2, ..Default::default() };
+        let (mut manager, mut from_manager) = test_manager(config);
+        let (peer_id, _requests) = add_peer(&mut manager, EthVersion::Eth68);
+
+        manager.on_bad_transactions(peer_id, 2);
+        let (reputation_changes, disconnects) = peer_actions(&mut from_manager);
+        assert_eq!(reputation_changes, vec![(peer_id, ReputationChangeKind::BadTransactions)]);
+        assert!(disconnects.is_empty());
+
+        manager.on_bad_transactions(peer_id, 1);
+        manager.on_bad_transactions(peer_id, 1);
+        let (reputation_changes, disconnects) = peer_actions(&mut from_manager);
+        assert_eq!(reputation_changes.len(), 2);
+        // the peer is only disconnected once
+        assert_eq!(disconnects, vec![(peer_id, Some(DisconnectReason::UselessPeer))]);
+    }
+
+    #[tokio::test]
+    async fn test_bad_transactions_are_counted_per_window() {
+        let config = TransactionsManagerConfig {
+            max_bad_transactions_per_peer: 2,
+            bad_transactions_window: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let (mut manager, mut from_manager) = test_manager(config);
+        let (peer_id, _requests) = add_peer(&mut manager, EthVersion::Eth68);
+
+        manager.on_bad_transactions(peer_id, 2);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        manager.on_bad_transactions(peer_id, 2);
+
+        let (_, disconnects) = peer_actions(&mut from_manager);
+        assert!(disconnects.is_empty());
+        assert_eq!(manager.peers[&peer_id].bad_transactions, 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_peer_messages() {
+        let config = TransactionsManagerConfig {
+            peer_message_rate: Rate::new(2, Duration::from_secs(60 * 60)),
+            ..Default::default()
+        };
+        let (mut manager, mut from_manager) = test_manager(config);
+        let (peer_id, _requests) = add_peer(&mut manager, EthVersion::Eth68);
+
+        assert!(manager.on_peer_message(peer_id));
+        assert!(manager.on_peer_message(peer_id));
+        assert!(!manager.on_peer_message(peer_id));
+
+        // dropped messages are not penalized
+        let (reputation_changes, disconnects) = peer_actions(&mut from_manager);
+        assert!(reputation_changes.is_empty());
+        assert!(disconnects.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_banned_address_is_not_penalized() {
+        let (mut manager, mut from_manager) = test_manager(Default::default());
+        let (peer_id, _requests) = add_peer(&mut manager, EthVersion::Eth68);
+
+        let hash = H256::random();
+        manager.transactions_by_peers.insert(hash, vec![peer_id]);
+        manager.on_bad_import(PoolError::BannedAddress(hash, Address::random()));
+        let (reputation_changes, disconnects) = peer_actions(&mut from_manager);
+        assert!(reputation_changes.is_empty());
+        assert!(disconnects.is_empty());
+        assert_eq!(manager.peers[&peer_id].bad_transactions, 0);
+
+        // unlike invalid transactions
+        manager.transactions_by_peers.insert(hash, vec![peer_id]);
+        manager.on_bad_import(PoolError::ProtocolFeeCapTooLow(hash, 1));
+        let (reputation_changes, _) = peer_actions(&mut from_manager);
+        assert_eq!(reputation_changes, vec![(peer_id, ReputationChangeKind::BadTransactions)]);
+        assert_eq!(manager.peers[&peer_id].bad_transactions, 1);
+    }
+
+    #[test]
+    fn test_announcement_by_version() {
+        let tx = Arc::new(TransactionSigned::default());
+
+        let msg = new_pooled_transaction_hashes(EthVersion::Eth67, &[tx.clone()]);
+        assert_eq!(msg, NewPooledTransactionHashes66(vec![tx.hash()]).into());
+
+        let msg = new_pooled_transaction_hashes(EthVersion::Eth68, &[tx.clone()]);
+        assert_eq!(
+            msg,
+            NewPooledTransactionHashes68 {
+                types: vec![tx.tx_type() as u8],
+                sizes: vec![tx.envelope_length()],
+                hashes: vec![tx.hash()],
+            }
+            .into()
+        );
+    }
 }
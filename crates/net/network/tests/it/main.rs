@@ -1,5 +1,7 @@
 mod connect;
+mod multiplex;
 mod requests;
 mod session;
+mod snap;
 
 fn main() {}
@@ -0,0 +1,83 @@
+//! Tests for RLPx sub-protocols multiplexed next to `eth`
+
+use bytes::Bytes;
+use futures::{future::BoxFuture, FutureExt, StreamExt};
+use reth_eth_wire::{
+    capability::{Capability, Protocol},
+    multiplex::ProtocolConnection,
+};
+use reth_network::{peers::PeersHandle, NetworkConfigBuilder, NetworkManager, ProtocolHandler};
+use reth_network_api::{NetworkInfo, Peers};
+use reth_primitives::PeerId;
+use reth_provider::test_utils::NoopProvider;
+use secp256k1::SecretKey;
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
+use tokio::sync::mpsc;
+
+const PING: u8 = 0x00;
+const PONG: u8 = 0x01;
+
+/// A simple ping pong protocol that reports every received pong.
+#[derive(Debug)]
+struct PingPongProtocol {
+    pongs: mpsc::UnboundedSender<PeerId>,
+}
+
+impl ProtocolHandler for PingPongProtocol {
+    fn protocol(&self) -> Protocol {
+        Protocol::new(Capability::new("ping".into(), 1), 2)
+    }
+
+    fn on_connection(
+        &self,
+        peer_id: PeerId,
+        mut conn: ProtocolConnection,
+        _peers: PeersHandle,
+    ) -> BoxFuture<'static, ()> {
+        let pongs = self.pongs.clone();
+        async move {
+            conn.send(Bytes::from_static(&[PING, 0xc0])).unwrap();
+            while let Some(msg) = conn.next().await {
+                match msg[0] {
+                    PING => conn.send(Bytes::from_static(&[PONG, 0xc0])).unwrap(),
+                    _ => {
+                        let _ = pongs.send(peer_id);
+                    }
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sub_protocol_ping_pong() {
+    reth_tracing::init_test_tracing();
+    let (pongs_tx, mut pongs_rx) = mpsc::unbounded_channel();
+
+    let mut handles = Vec::new();
+    for _ in 0..2 {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let config = NetworkConfigBuilder::new(secret_key)
+            .listener_addr(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0))
+            .no_discv4_discovery()
+            .no_dns_discovery()
+            .add_rlpx_sub_protocol(PingPongProtocol { pongs: pongs_tx.clone() })
+            .build(Arc::new(NoopProvider::default()));
+        let network = NetworkManager::new(config).await.unwrap();
+        handles.push(network.handle().clone());
+        tokio::task::spawn(network);
+    }
+
+    handles[0].add_peer(*handles[1].peer_id(), handles[1].local_addr());
+
+    // both peers receive a pong from the other peer
+    let mut peers = vec![pongs_rx.recv().await.unwrap(), pongs_rx.recv().await.unwrap()];
+    peers.sort();
+    let mut expected = vec![*handles[0].peer_id(), *handles[1].peer_id()];
+    expected.sort();
+    assert_eq!(peers, expected);
+}
@@ -0,0 +1,63 @@
+//! Tests for sending `snap` requests to peers.
+use reth_eth_wire::GetAccountRange;
+use reth_interfaces::p2p::error::RequestError;
+use reth_network::{NetworkConfigBuilder, NetworkManager, SnapProtocolHandler};
+use reth_network_api::{NetworkInfo, Peers};
+use reth_primitives::H256;
+use reth_provider::test_utils::NoopProvider;
+use secp256k1::SecretKey;
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_account_range() {
+    reth_tracing::init_test_tracing();
+
+    let mut handles = Vec::new();
+    let mut clients = Vec::new();
+    for _ in 0..2 {
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let snap = SnapProtocolHandler::new(Arc::new(NoopProvider::default()));
+        clients.push(snap.snap_client());
+        let config = NetworkConfigBuilder::new(secret_key)
+            .listener_addr(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0))
+            .no_discv4_discovery()
+            .no_dns_discovery()
+            .add_rlpx_sub_protocol(snap)
+            .build(Arc::new(NoopProvider::default()));
+        let network = NetworkManager::new(config).await.unwrap();
+        handles.push(network.handle().clone());
+        tokio::task::spawn(network);
+    }
+
+    let client = &clients[0];
+    let peer_id = *handles[1].peer_id();
+    let request = GetAccountRange {
+        request_id: 0,
+        root_hash: H256::repeat_byte(0xaa),
+        starting_hash: H256::zero(),
+        limit_hash: H256::repeat_byte(0xff),
+        response_bytes: 1024,
+    };
+    assert_eq!(
+        client.get_account_range(peer_id, request.clone()).await,
+        Err(RequestError::UnsupportedCapability)
+    );
+
+    handles[0].add_peer(peer_id, handles[1].local_addr());
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while client.peers() != vec![peer_id] {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("snap connection established");
+
+    // the peer doesn't know the state root and answers with an empty range
+    let response = client.get_account_range(peer_id, request).await.unwrap();
+    assert!(response.accounts.is_empty());
+    assert!(response.proof.is_empty());
+}
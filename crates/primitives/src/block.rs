@@ -1,4 +1,4 @@
-use crate::{Header, SealedHeader, TransactionSigned, H256};
+use crate::{Address, Header, SealedHeader, TransactionSigned, H256};
 use reth_codecs::derive_arbitrary;
 use reth_rlp::{Decodable, DecodeError, Encodable, RlpDecodable, RlpEncodable};
 use serde::{Deserialize, Serialize};
@@ -61,6 +61,42 @@ impl Deref for SealedBlock {
     }
 }
 
+/// Ethereum full block with senders recovered from its transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BlockWithSenders {
+    /// Block
+    pub block: Block,
+    /// List of senders that match the transactions in the block
+    pub senders: Vec<Address>,
+}
+
+impl BlockWithSenders {
+    /// New block with senders. Return `None` if the number of senders does not match the number
+    /// of transactions.
+    pub fn new(block: Block, senders: Vec<Address>) -> Option<Self> {
+        (block.body.len() == senders.len()).then_some(Self { block, senders })
+    }
+
+    /// Split structure to its components
+    pub fn into_components(self) -> (Block, Vec<Address>) {
+        (self.block, self.senders)
+    }
+
+    /// Returns an iterator over the transactions of the block, paired with their senders.
+    pub fn transactions_with_sender(
+        &self,
+    ) -> impl Iterator<Item = (&Address, &TransactionSigned)> + '_ {
+        self.senders.iter().zip(self.block.body.iter())
+    }
+}
+
+impl Deref for BlockWithSenders {
+    type Target = Block;
+    fn deref(&self) -> &Self::Target {
+        &self.block
+    }
+}
+
 /// Either a block hash _or_ a block number
 #[derive_arbitrary(rlp)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -128,6 +128,27 @@ pub struct ForkId {
     pub next: u64,
 }
 
+/// The `eth` entry of an ENR as defined by EIP-2124: `[[fork-hash, fork-next], ...]`.
+///
+/// See also <https://github.com/ethereum/devp2p/blob/master/enr-entries/eth.md>
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, RlpEncodable, RlpDecodable)]
+pub struct EnrForkIdEntry {
+    /// The fork ID of the node.
+    pub fork_id: ForkId,
+}
+
+impl From<ForkId> for EnrForkIdEntry {
+    fn from(fork_id: ForkId) -> Self {
+        Self { fork_id }
+    }
+}
+
+impl From<EnrForkIdEntry> for ForkId {
+    fn from(entry: EnrForkIdEntry) -> Self {
+        entry.fork_id
+    }
+}
+
 /// Reason for rejecting provided `ForkId`.
 #[derive(Clone, Copy, Debug, Error, PartialEq, Eq, Hash)]
 pub enum ValidationError {
@@ -377,6 +398,20 @@ mod tests {
     const GENESIS_HASH: H256 =
         H256(hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"));
 
+    #[test]
+    fn enr_fork_id_entry() {
+        // the `eth` entry of a mainnet node
+        let entry = hex!("c7c684dce96c2d80");
+        let fork_id = ForkId { hash: ForkHash(hex!("dce96c2d")), next: 0 };
+
+        let decoded = EnrForkIdEntry::decode(&mut &entry[..]).unwrap();
+        assert_eq!(ForkId::from(decoded), fork_id);
+
+        let mut buf = Vec::new();
+        EnrForkIdEntry::from(fork_id).encode(&mut buf);
+        assert_eq!(buf, entry);
+    }
+
     // EIP test vectors.
     #[test]
     fn forkhash() {
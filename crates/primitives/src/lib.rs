@@ -26,6 +26,7 @@ mod jsonu256;
 mod log;
 mod net;
 mod peer;
+mod prune;
 mod receipt;
 mod storage;
 mod transaction;
@@ -35,7 +36,7 @@ pub mod proofs;
 
 pub use account::Account;
 pub use bits::H512;
-pub use block::{Block, BlockHashOrNumber, SealedBlock};
+pub use block::{Block, BlockHashOrNumber, BlockWithSenders, SealedBlock};
 pub use bloom::Bloom;
 pub use chain::{
     Chain, ChainInfo, ChainSpec, ChainSpecBuilder, ForkCondition, GOERLI, MAINNET, SEPOLIA,
@@ -43,7 +44,7 @@ pub use chain::{
 pub use constants::{
     EMPTY_OMMER_ROOT, GOERLI_GENESIS, KECCAK_EMPTY, MAINNET_GENESIS, SEPOLIA_GENESIS,
 };
-pub use forkid::{ForkFilter, ForkHash, ForkId, ForkTransition, ValidationError};
+pub use forkid::{EnrForkIdEntry, ForkFilter, ForkHash, ForkId, ForkTransition, ValidationError};
 pub use genesis::{Genesis, GenesisAccount};
 pub use hardfork::Hardfork;
 pub use header::{Head, Header, HeadersDirection, SealedHeader};
@@ -53,12 +54,13 @@ pub use jsonu256::JsonU256;
 pub use log::Log;
 pub use net::NodeRecord;
 pub use peer::{PeerId, WithPeerId};
+pub use prune::{PruneMode, PruneModes, PrunePart, MINIMUM_PRUNING_DISTANCE};
 pub use receipt::Receipt;
 pub use storage::{StorageEntry, StorageTrieEntry};
 pub use transaction::{
     AccessList, AccessListItem, FromRecoveredTransaction, IntoRecoveredTransaction, Signature,
-    Transaction, TransactionKind, TransactionSigned, TransactionSignedEcRecovered, TxEip1559,
-    TxEip2930, TxLegacy, TxType,
+    Transaction, TransactionKind, TransactionMeta, TransactionSigned, TransactionSignedEcRecovered,
+    TxEip1559, TxEip2930, TxLegacy, TxType,
 };
 
 /// A block hash.
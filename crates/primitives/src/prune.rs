@@ -0,0 +1,166 @@
+//! Pruning configuration.
+
+use crate::BlockNumber;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// The minimum number of blocks the history of the state is kept for.
+///
+/// Unwinds of the pipeline need the changesets of the unwound blocks, so the history of the most
+/// recent blocks is never pruned.
+pub const MINIMUM_PRUNING_DISTANCE: u64 = 128;
+
+/// A part of the database that can be pruned.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum PrunePart {
+    /// Receipts and logs of transactions.
+    Receipts,
+    /// Account changesets and the account history indices.
+    AccountHistory,
+    /// Storage changesets and the storage history indices.
+    StorageHistory,
+    /// The mapping of transaction hashes to transaction numbers.
+    TransactionLookup,
+}
+
+impl PrunePart {
+    /// All prune parts.
+    pub const ALL: [PrunePart; 4] = [
+        PrunePart::Receipts,
+        PrunePart::AccountHistory,
+        PrunePart::StorageHistory,
+        PrunePart::TransactionLookup,
+    ];
+
+    /// Returns the name of the part, used as its key in the database.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            PrunePart::Receipts => "Receipts",
+            PrunePart::AccountHistory => "AccountHistory",
+            PrunePart::StorageHistory => "StorageHistory",
+            PrunePart::TransactionLookup => "TransactionLookup",
+        }
+    }
+
+    /// Returns true if the part is needed to unwind the state, see [MINIMUM_PRUNING_DISTANCE].
+    pub const fn is_state_history(&self) -> bool {
+        matches!(self, PrunePart::AccountHistory | PrunePart::StorageHistory)
+    }
+}
+
+impl Display for PrunePart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How much of a [PrunePart] is pruned.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneMode {
+    /// Prune all blocks.
+    Full,
+    /// Keep the given number of most recent blocks.
+    Distance(u64),
+    /// Prune all blocks before the given block.
+    Before(BlockNumber),
+}
+
+impl PruneMode {
+    /// Returns the highest block that should be pruned if the chain tip is at `tip`, or `None` if
+    /// nothing should be pruned.
+    pub fn prune_target_block(&self, tip: BlockNumber) -> Option<BlockNumber> {
+        match self {
+            PruneMode::Full => Some(tip),
+            PruneMode::Distance(distance) => tip.checked_sub(*distance),
+            PruneMode::Before(block) => block.checked_sub(1).map(|block| block.min(tip)),
+        }
+    }
+}
+
+/// The [PruneMode] of each [PrunePart], parts without a mode are kept in full.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PruneModes {
+    /// Pruning of [PrunePart::Receipts].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipts: Option<PruneMode>,
+    /// Pruning of [PrunePart::AccountHistory].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_history: Option<PruneMode>,
+    /// Pruning of [PrunePart::StorageHistory].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_history: Option<PruneMode>,
+    /// Pruning of [PrunePart::TransactionLookup].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_lookup: Option<PruneMode>,
+}
+
+impl PruneModes {
+    /// Returns the mode of the part.
+    pub fn get(&self, part: PrunePart) -> Option<PruneMode> {
+        match part {
+            PrunePart::Receipts => self.receipts,
+            PrunePart::AccountHistory => self.account_history,
+            PrunePart::StorageHistory => self.storage_history,
+            PrunePart::TransactionLookup => self.transaction_lookup,
+        }
+    }
+
+    /// Returns true if no part is pruned.
+    pub fn is_empty(&self) -> bool {
+        PrunePart::ALL.iter().all(|part| self.get(*part).is_none())
+    }
+
+    /// Returns the highest block of the part that should be pruned if the chain tip is at `tip`.
+    ///
+    /// The state history of the last [MINIMUM_PRUNING_DISTANCE] blocks is always kept.
+    pub fn prune_target_block(&self, part: PrunePart, tip: BlockNumber) -> Option<BlockNumber> {
+        let target = self.get(part)?.prune_target_block(tip)?;
+        if part.is_state_history() {
+            tip.checked_sub(MINIMUM_PRUNING_DISTANCE).map(|max| target.min(max))
+        } else {
+            Some(target)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_target_block() {
+        assert_eq!(PruneMode::Full.prune_target_block(100), Some(100));
+        assert_eq!(PruneMode::Distance(10).prune_target_block(100), Some(90));
+        assert_eq!(PruneMode::Distance(200).prune_target_block(100), None);
+        assert_eq!(PruneMode::Before(50).prune_target_block(100), Some(49));
+        assert_eq!(PruneMode::Before(500).prune_target_block(100), Some(100));
+        assert_eq!(PruneMode::Before(0).prune_target_block(100), None);
+
+        let modes = PruneModes {
+            receipts: Some(PruneMode::Full),
+            account_history: Some(PruneMode::Full),
+            ..Default::default()
+        };
+        assert_eq!(modes.prune_target_block(PrunePart::Receipts, 1000), Some(1000));
+        assert_eq!(
+            modes.prune_target_block(PrunePart::AccountHistory, 1000),
+            Some(1000 - MINIMUM_PRUNING_DISTANCE)
+        );
+        assert_eq!(modes.prune_target_block(PrunePart::AccountHistory, 100), None);
+        assert_eq!(modes.prune_target_block(PrunePart::StorageHistory, 1000), None);
+    }
+
+    #[test]
+    fn serde_prune_modes() {
+        let modes = PruneModes {
+            receipts: Some(PruneMode::Distance(64)),
+            transaction_lookup: Some(PruneMode::Full),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&modes).unwrap();
+        assert_eq!(json, r#"{"receipts":{"distance":64},"transaction_lookup":"full"}"#);
+        assert_eq!(serde_json::from_str::<PruneModes>(&json).unwrap(), modes);
+    }
+}
@@ -0,0 +1,14 @@
+use crate::{BlockNumber, TxHash, H256};
+
+/// Additional fields in the context of a block that contains this transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransactionMeta {
+    /// Hash of the transaction.
+    pub tx_hash: TxHash,
+    /// Index of the transaction in the block
+    pub index: u64,
+    /// Hash of the block.
+    pub block_hash: H256,
+    /// Number of the block.
+    pub block_number: BlockNumber,
+}
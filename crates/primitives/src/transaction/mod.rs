@@ -2,12 +2,14 @@ use crate::{keccak256, Address, Bytes, ChainId, TxHash, H256};
 pub use access_list::{AccessList, AccessListItem};
 use bytes::{Buf, BytesMut};
 use derive_more::{AsRef, Deref};
+pub use meta::TransactionMeta;
 use reth_codecs::{add_arbitrary_tests, main_codec, Compact};
 use reth_rlp::{length_of_length, Decodable, DecodeError, Encodable, Header, EMPTY_STRING_CODE};
 pub use signature::Signature;
 pub use tx_type::TxType;
 
 mod access_list;
+mod meta;
 mod signature;
 mod tx_type;
 mod util;
@@ -564,6 +566,20 @@ impl TransactionSigned {
         buf.freeze()
     }
 
+    /// Returns the length of the enveloped encoding, without allocating.
+    ///
+    /// See also [TransactionSigned::encode_enveloped]
+    pub fn envelope_length(&self) -> usize {
+        match self.transaction {
+            Transaction::Legacy(_) => self.payload_len_inner(),
+            _ => {
+                let payload_length = self.transaction.fields_len() + self.signature.payload_len();
+                // 'transaction type byte length' + 'header length' + 'payload length'
+                1 + length_of_length(payload_length) + payload_length
+            }
+        }
+    }
+
     /// Encodes the transaction into the "raw" format (e.g. `eth_sendRawTransaction`).
     /// This format is also referred to as "binary" encoding.
     ///
@@ -1166,5 +1182,6 @@ mod tests {
 
         let encoded = decoded.envelope_encoded();
         assert_eq!(encoded, input);
+        assert_eq!(decoded.envelope_length(), input.len());
     }
 }
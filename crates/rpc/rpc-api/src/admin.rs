@@ -39,4 +39,11 @@ pub trait AdminApi {
     /// Returns the ENR of the node.
     #[method(name = "admin_nodeInfo")]
     async fn node_info(&self) -> RpcResult<NodeInfo>;
+
+    /// Reloads the banned transaction senders and recipients from the configured ban list file.
+    ///
+    /// Returns an error if the file is missing or invalid, in which case the current ban list is
+    /// kept.
+    #[method(name = "admin_reloadTransactionBanList")]
+    fn reload_transaction_ban_list(&self) -> RpcResult<bool>;
 }
@@ -1,6 +1,6 @@
 use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
 use reth_primitives::{
-    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId},
+    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId, BlockNumber as RpcBlockNumber},
     Address, BlockNumber, Bytes, H256, H64, U256, U64,
 };
 use reth_rpc_types::{
@@ -43,7 +43,11 @@ pub trait EthApi {
 
     /// Returns information about a block by number.
     #[method(name = "eth_getBlockByNumber")]
-    async fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>>;
+    async fn block_by_number(
+        &self,
+        number: RpcBlockNumber,
+        full: bool,
+    ) -> Result<Option<RichBlock>>;
 
     /// Returns the number of transactions in a block from a block matching the given block hash.
     #[method(name = "eth_getBlockTransactionCountByHash")]
@@ -0,0 +1,25 @@
+use jsonrpsee::{core::RpcResult as Result, proc_macros::rpc};
+use reth_primitives::{Bytes, H256};
+use reth_rpc_types::{EthBundleHash, EthSendBundle};
+
+/// Eth bundle rpc interface for private transaction submission.
+///
+/// Transactions submitted via this interface are never gossiped to the p2p network.
+#[cfg_attr(not(feature = "client"), rpc(server))]
+#[cfg_attr(feature = "client", rpc(server, client))]
+#[async_trait::async_trait]
+pub trait EthBundleApi {
+    /// Submits a raw transaction to the pool without propagating it to peers.
+    ///
+    /// Returns the hash of the transaction.
+    #[method(name = "eth_sendPrivateRawTransaction")]
+    async fn send_private_raw_transaction(&self, bytes: Bytes) -> Result<H256>;
+
+    /// Submits an ordered bundle of raw transactions that are included together in the target
+    /// block.
+    ///
+    /// The bundle is simulated against the current head state and rejected if any of its
+    /// transactions fails. Bundles that were not included expire after their target block.
+    #[method(name = "eth_sendBundle")]
+    async fn send_bundle(&self, bundle: EthSendBundle) -> Result<EthBundleHash>;
+}
@@ -13,6 +13,7 @@ mod admin;
 mod debug;
 mod engine;
 mod eth;
+mod eth_bundle;
 mod eth_filter;
 mod eth_pubsub;
 mod net;
@@ -26,8 +27,9 @@ pub use servers::*;
 pub mod servers {
     pub use crate::{
         admin::AdminApiServer, debug::DebugApiServer, engine::EngineApiServer, eth::EthApiServer,
-        eth_filter::EthFilterApiServer, eth_pubsub::EthPubSubApiServer, net::NetApiServer,
-        trace::TraceApiServer, web3::Web3ApiServer,
+        eth_bundle::EthBundleApiServer, eth_filter::EthFilterApiServer,
+        eth_pubsub::EthPubSubApiServer, net::NetApiServer, trace::TraceApiServer,
+        web3::Web3ApiServer,
     };
 }
 
@@ -40,6 +42,7 @@ pub use clients::*;
 pub mod clients {
     pub use crate::{
         admin::AdminApiClient, debug::DebugApiClient, engine::EngineApiClient, eth::EthApiClient,
-        net::NetApiClient, trace::TraceApiClient, web3::Web3ApiClient,
+        eth_bundle::EthBundleApiClient, net::NetApiClient, trace::TraceApiClient,
+        web3::Web3ApiClient,
     };
 }
@@ -26,12 +26,12 @@
 //!
 //! ```
 //! use reth_network_api::{NetworkInfo, Peers};
-//! use reth_provider::{BlockProvider, StateProviderFactory};
+//! use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
 //! use reth_rpc_builder::{RethRpcModule, RpcModuleBuilder, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig};
 //! use reth_transaction_pool::TransactionPool;
 //! pub async fn launch<Client, Pool, Network>(client: Client, pool: Pool, network: Network)
 //! where
-//!     Client: BlockProvider + StateProviderFactory + Clone + 'static,
+//!     Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
 //!     Pool: TransactionPool + Clone + 'static,
 //!     Network: NetworkInfo + Peers + Clone + 'static,
 //! {
@@ -60,8 +60,12 @@ use jsonrpsee::{
 use reth_ipc::server::IpcServer;
 pub use reth_ipc::server::{Builder as IpcServerBuilder, Endpoint};
 use reth_network_api::{NetworkInfo, Peers};
-use reth_provider::{BlockProvider, StateProviderFactory};
-use reth_rpc::{AdminApi, DebugApi, EthApi, NetApi, TraceApi, Web3Api};
+use reth_primitives::{ChainSpec, MAINNET};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
+use reth_rpc::{
+    AdminApi, BundleStore, DebugApi, EthApi, EthBundle, NetApi, PendingBlockCache, TraceApi,
+    Web3Api,
+};
 use reth_rpc_api::servers::*;
 use reth_transaction_pool::TransactionPool;
 use serde::{Deserialize, Serialize, Serializer};
@@ -70,6 +74,7 @@ use std::{
     fmt,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     str::FromStr,
+    sync::Arc,
 };
 use strum::{AsRefStr, EnumString, EnumVariantNames, ParseError, VariantNames};
 
@@ -93,7 +98,7 @@ pub async fn launch<Client, Pool, Network>(
     server_config: impl Into<RpcServerConfig>,
 ) -> Result<RpcServerHandle, RpcError>
 where
-    Client: BlockProvider + StateProviderFactory + Clone + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
     Pool: TransactionPool + Clone + 'static,
     Network: NetworkInfo + Peers + Clone + 'static,
 {
@@ -116,6 +121,12 @@ pub struct RpcModuleBuilder<Client, Pool, Network> {
     pool: Pool,
     /// The Network type to when creating all rpc handlers
     network: Network,
+    /// The pending block the `eth_` handlers use to serve `pending` queries
+    pending_block: PendingBlockCache,
+    /// Where bundles submitted via `eth_sendBundle` are stored
+    bundles: BundleStore,
+    /// The chain the handlers are configured for
+    chain_spec: Arc<ChainSpec>,
 }
 
 // === impl RpcBuilder ===
@@ -123,16 +134,45 @@ pub struct RpcModuleBuilder<Client, Pool, Network> {
 impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network> {
     /// Create a new instance of the builder
     pub fn new(client: Client, pool: Pool, network: Network) -> Self {
-        Self { client, pool, network }
+        Self {
+            client,
+            pool,
+            network,
+            pending_block: Default::default(),
+            bundles: Default::default(),
+            chain_spec: Arc::new(MAINNET.clone()),
+        }
+    }
+
+    /// Configure the cache of the pending block that is used to serve `pending` queries.
+    ///
+    /// See also [PendingBlockBuilder](reth_rpc::PendingBlockBuilder)
+    pub fn with_pending_block(mut self, pending_block: PendingBlockCache) -> Self {
+        self.pending_block = pending_block;
+        self
+    }
+
+    /// Configure the store for bundles submitted via `eth_sendBundle`.
+    ///
+    /// See also [PendingBlockBuilder::with_bundles](reth_rpc::PendingBlockBuilder::with_bundles)
+    pub fn with_bundles(mut self, bundles: BundleStore) -> Self {
+        self.bundles = bundles;
+        self
+    }
+
+    /// Configure the chain the handlers are used for, this is [MAINNET] by default.
+    pub fn with_chain_spec(mut self, chain_spec: Arc<ChainSpec>) -> Self {
+        self.chain_spec = chain_spec;
+        self
     }
 
     /// Configure the client instance.
     pub fn with_client<C>(self, client: C) -> RpcModuleBuilder<C, Pool, Network>
     where
-        C: BlockProvider + StateProviderFactory + 'static,
+        C: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
     {
-        let Self { pool, network, .. } = self;
-        RpcModuleBuilder { client, network, pool }
+        let Self { pool, network, pending_block, bundles, chain_spec, .. } = self;
+        RpcModuleBuilder { client, network, pool, pending_block, bundles, chain_spec }
     }
 
     /// Configure the transaction pool instance.
@@ -140,8 +180,8 @@ impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network> {
     where
         P: TransactionPool + 'static,
     {
-        let Self { client, network, .. } = self;
-        RpcModuleBuilder { client, network, pool }
+        let Self { client, network, pending_block, bundles, chain_spec, .. } = self;
+        RpcModuleBuilder { client, network, pool, pending_block, bundles, chain_spec }
     }
 
     /// Configure the network instance.
@@ -149,14 +189,14 @@ impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network> {
     where
         N: NetworkInfo + Peers + 'static,
     {
-        let Self { client, pool, .. } = self;
-        RpcModuleBuilder { client, network, pool }
+        let Self { client, pool, pending_block, bundles, chain_spec, .. } = self;
+        RpcModuleBuilder { client, network, pool, pending_block, bundles, chain_spec }
     }
 }
 
 impl<Client, Pool, Network> RpcModuleBuilder<Client, Pool, Network>
 where
-    Client: BlockProvider + StateProviderFactory + Clone + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
     Pool: TransactionPool + Clone + 'static,
     Network: NetworkInfo + Peers + Clone + 'static,
 {
@@ -167,9 +207,12 @@ where
     pub fn build(self, module_config: TransportRpcModuleConfig) -> TransportRpcModules<()> {
         let mut modules = TransportRpcModules::default();
 
-        let Self { client, pool, network } = self;
+        let Self { client, pool, network, pending_block, bundles, chain_spec } = self;
 
-        let mut registry = RethModuleRegistry::new(client, pool, network);
+        let mut registry = RethModuleRegistry::new(client, pool, network)
+            .with_pending_block(pending_block)
+            .with_bundles(bundles)
+            .with_chain_spec(chain_spec);
 
         if !module_config.is_empty() {
             let TransportRpcModuleConfig { http, ws, ipc } = module_config;
@@ -257,7 +300,7 @@ impl RpcModuleConfig {
         network: Network,
     ) -> RpcModule<()>
     where
-        Client: BlockProvider + StateProviderFactory + Clone + 'static,
+        Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
         Pool: TransactionPool + Clone + 'static,
         Network: NetworkInfo + Peers + Clone + 'static,
     {
@@ -317,6 +360,8 @@ pub enum RethRpcModule {
     Debug,
     /// `eth_` module
     Eth,
+    /// `eth_sendBundle` and `eth_sendPrivateRawTransaction` for private transaction submission
+    EthBundle,
     /// `net_` module
     Net,
     /// `trace_` module
@@ -345,6 +390,12 @@ pub struct RethModuleRegistry<Client, Pool, Network> {
     client: Client,
     pool: Pool,
     network: Network,
+    /// The pending block used by the [EthApi]
+    pending_block: PendingBlockCache,
+    /// Where bundles are stored by the [EthBundle] handler
+    bundles: BundleStore,
+    /// The chain the handlers are configured for
+    chain_spec: Arc<ChainSpec>,
     /// Holds a clone of the actual [EthApi] namespace impl since this can be required by other
     /// namespaces
     eth_api: Option<EthApi<Client, Pool, Network>>,
@@ -356,13 +407,40 @@ pub struct RethModuleRegistry<Client, Pool, Network> {
 
 impl<Client, Pool, Network> RethModuleRegistry<Client, Pool, Network>
 where
-    Client: BlockProvider + StateProviderFactory + Clone + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + Clone + 'static,
     Pool: TransactionPool + Clone + 'static,
     Network: NetworkInfo + Peers + Clone + 'static,
 {
     /// Creates a new, empty instance
     pub fn new(client: Client, pool: Pool, network: Network) -> Self {
-        Self { client, pool, network, eth_api: None, modules: Default::default() }
+        Self {
+            client,
+            pool,
+            network,
+            pending_block: Default::default(),
+            bundles: Default::default(),
+            chain_spec: Arc::new(MAINNET.clone()),
+            eth_api: None,
+            modules: Default::default(),
+        }
+    }
+
+    /// Configure the cache of the pending block that is used by the [EthApi].
+    pub fn with_pending_block(mut self, pending_block: PendingBlockCache) -> Self {
+        self.pending_block = pending_block;
+        self
+    }
+
+    /// Configure the store for bundles that is used by the [EthBundle] handler.
+    pub fn with_bundles(mut self, bundles: BundleStore) -> Self {
+        self.bundles = bundles;
+        self
+    }
+
+    /// Configure the chain the handlers are used for.
+    pub fn with_chain_spec(mut self, chain_spec: Arc<ChainSpec>) -> Self {
+        self.chain_spec = chain_spec;
+        self
     }
 
     /// Helper function to create a [RpcModule] if it's not `None`
@@ -392,12 +470,22 @@ where
             return methods
         }
         let methods: Methods = match namespace {
-            RethRpcModule::Admin => AdminApi::new(self.network.clone()).into_rpc().into(),
+            RethRpcModule::Admin => {
+                AdminApi::new(self.network.clone(), self.pool.clone()).into_rpc().into()
+            }
             RethRpcModule::Debug => {
                 let eth_api = self.eth_api();
                 DebugApi::new(eth_api).into_rpc().into()
             }
             RethRpcModule::Eth => self.eth_api().into_rpc().into(),
+            RethRpcModule::EthBundle => EthBundle::new(
+                self.client.clone(),
+                self.pool.clone(),
+                self.chain_spec.clone(),
+                self.bundles.clone(),
+            )
+            .into_rpc()
+            .into(),
             RethRpcModule::Net => {
                 let eth_api = self.eth_api();
                 NetApi::new(self.network.clone(), eth_api).into_rpc().into()
@@ -414,7 +502,13 @@ where
     fn eth_api(&mut self) -> EthApi<Client, Pool, Network> {
         self.eth_api
             .get_or_insert_with(|| {
-                EthApi::new(self.client.clone(), self.pool.clone(), self.network.clone())
+                EthApi::with_pending_block(
+                    self.client.clone(),
+                    self.pool.clone(),
+                    self.network.clone(),
+                    self.pending_block.clone(),
+                    self.chain_spec.clone(),
+                )
             })
             .clone()
     }
@@ -796,6 +890,7 @@ mod tests {
                 "admin" =>  RethRpcModule::Admin,
                 "debug" =>  RethRpcModule::Debug,
                 "eth" =>  RethRpcModule::Eth,
+                "eth-bundle" =>  RethRpcModule::EthBundle,
                 "net" =>  RethRpcModule::Net,
                 "trace" =>  RethRpcModule::Trace,
                 "web3" =>  RethRpcModule::Web3,
@@ -1,5 +1,8 @@
 use crate::Transaction;
-use reth_primitives::{Address, Bloom, Bytes, H256, H64, U256};
+use reth_primitives::{
+    Address, Block as PrimitiveBlock, Bloom, Bytes, Header as PrimitiveHeader, H256, H64, U256,
+};
+use reth_rlp::Encodable;
 use serde::{ser::Error, Deserialize, Serialize, Serializer};
 use std::{collections::BTreeMap, ops::Deref};
 
@@ -33,6 +36,66 @@ pub struct Block {
     pub base_fee_per_gas: Option<U256>,
 }
 
+/// Error that can occur when converting other types to blocks
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+pub enum BlockError {
+    /// A transaction failed sender recovery
+    #[error("transaction failed sender recovery")]
+    InvalidSignature,
+}
+
+// === impl Block ===
+
+impl Block {
+    /// Converts the given primitive block into a [Block] response.
+    ///
+    /// If `full` is set, the transactions are returned as full transaction objects, otherwise
+    /// only their hashes are included.
+    ///
+    /// The `block_hash` is `None` if the block is not sealed yet, for example the pending block.
+    pub fn from_block(
+        block: PrimitiveBlock,
+        total_difficulty: U256,
+        full: bool,
+        block_hash: Option<H256>,
+    ) -> Result<Self, BlockError> {
+        let block_length = block.length();
+        let uncles = block.ommers.iter().map(|ommer| ommer.hash_slow()).collect();
+        let PrimitiveBlock { header, body, .. } = block;
+
+        let transactions = if full {
+            let transactions = body
+                .into_iter()
+                .enumerate()
+                .map(|(idx, tx)| {
+                    let tx = tx.into_ecrecovered().ok_or(BlockError::InvalidSignature)?;
+                    Ok(Transaction::from_recovered_with_block_context(
+                        tx,
+                        block_hash,
+                        header.number,
+                        idx,
+                    ))
+                })
+                .collect::<Result<Vec<_>, BlockError>>()?;
+            BlockTransactions::Full(transactions)
+        } else {
+            BlockTransactions::Hashes(body.iter().map(|tx| tx.hash()).collect())
+        };
+
+        let base_fee_per_gas = header.base_fee_per_gas.map(U256::from);
+        let header = Header::from_primitive_with_hash(header, block_hash);
+
+        Ok(Self {
+            header,
+            total_difficulty,
+            uncles,
+            transactions,
+            size: Some(U256::from(block_length)),
+            base_fee_per_gas,
+        })
+    }
+}
+
 /// Block header representation.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -76,6 +139,58 @@ pub struct Header {
     pub size: Option<U256>,
 }
 
+// === impl Header ===
+
+impl Header {
+    /// Converts the primitive header type to this RPC type
+    ///
+    /// The `block_hash` is `None` if the header is not sealed yet.
+    pub fn from_primitive_with_hash(
+        primitive_header: PrimitiveHeader,
+        block_hash: Option<H256>,
+    ) -> Self {
+        let PrimitiveHeader {
+            parent_hash,
+            ommers_hash,
+            beneficiary,
+            state_root,
+            transactions_root,
+            receipts_root,
+            logs_bloom,
+            difficulty,
+            number,
+            gas_limit,
+            gas_used,
+            timestamp,
+            mix_hash,
+            nonce,
+            base_fee_per_gas: _,
+            extra_data,
+        } = primitive_header;
+
+        Self {
+            hash: block_hash,
+            parent_hash,
+            uncles_hash: ommers_hash,
+            author: beneficiary,
+            miner: beneficiary,
+            state_root,
+            transactions_root,
+            receipts_root,
+            number: Some(U256::from(number)),
+            gas_used: U256::from(gas_used),
+            gas_limit: U256::from(gas_limit),
+            extra_data,
+            logs_bloom,
+            timestamp: U256::from(timestamp),
+            difficulty,
+            mix_hash,
+            nonce: Some(H64::from_low_u64_be(nonce)),
+            size: None,
+        }
+    }
+}
+
 /// A Block representation that allows to include additional fields
 pub type RichBlock = Rich<Block>;
 
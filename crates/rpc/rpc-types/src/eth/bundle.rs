@@ -0,0 +1,37 @@
+use reth_primitives::{Bytes, H256, U64};
+use serde::{Deserialize, Serialize};
+
+/// Bundle of transactions for `eth_sendBundle`.
+///
+/// The transactions are executed in the given order and are only included together, in the block
+/// with the given number.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthSendBundle {
+    /// Raw, signed transactions of the bundle.
+    pub txs: Vec<Bytes>,
+    /// The number of the block the bundle is valid for.
+    pub block_number: U64,
+}
+
+/// Response of `eth_sendBundle`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthBundleHash {
+    /// Hash of the bundle, this is the keccak256 of the concatenated transaction hashes.
+    pub bundle_hash: H256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_send_bundle() {
+        let s = r#"{"txs":["0x01","0x02"],"blockNumber":"0x10"}"#;
+        let bundle: EthSendBundle = serde_json::from_str(s).unwrap();
+        assert_eq!(bundle.txs.len(), 2);
+        assert_eq!(bundle.block_number, U64::from(16));
+        assert_eq!(serde_json::to_string(&bundle).unwrap(), s);
+    }
+}
@@ -2,6 +2,7 @@
 
 mod account;
 mod block;
+mod bundle;
 mod call;
 pub mod engine;
 mod fee;
@@ -16,6 +17,7 @@ mod work;
 
 pub use account::*;
 pub use block::*;
+pub use bundle::{EthBundleHash, EthSendBundle};
 pub use call::CallRequest;
 pub use fee::FeeHistory;
 pub use filter::*;
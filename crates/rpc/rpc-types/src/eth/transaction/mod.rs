@@ -9,7 +9,10 @@ pub use signature::Signature;
 pub use typed::*;
 
 use reth_primitives::{
-    rpc::transaction::eip2930::AccessListItem, Address, Bytes, H256, U128, U256, U64,
+    rpc::{self, transaction::eip2930::AccessListItem},
+    Address, BlockNumber, Bytes, Transaction as PrimitiveTransaction,
+    TransactionKind as PrimitiveTransactionKind, TransactionSignedEcRecovered, H256, U128, U256,
+    U64,
 };
 use serde::{Deserialize, Serialize};
 
@@ -65,3 +68,96 @@ pub struct Transaction {
     #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub transaction_type: Option<U64>,
 }
+
+impl Transaction {
+    /// Create a new rpc transaction result for a _pending_ signed transaction, setting block
+    /// environment related fields to `None`.
+    pub fn from_recovered(tx: TransactionSignedEcRecovered) -> Self {
+        Self::fill(tx, None, None, None)
+    }
+
+    /// Create a new rpc transaction result for a transaction that is included in a block, using
+    /// the given block hash, number, and tx index fields to populate the corresponding fields in
+    /// the rpc result.
+    ///
+    /// The block hash is `None` if the block is not sealed yet, for example the pending block.
+    pub fn from_recovered_with_block_context(
+        tx: TransactionSignedEcRecovered,
+        block_hash: Option<H256>,
+        block_number: BlockNumber,
+        tx_index: usize,
+    ) -> Self {
+        Self::fill(tx, block_hash, Some(block_number), Some(U256::from(tx_index)))
+    }
+
+    /// Create a new rpc transaction result from the given transaction and the block
+    /// environment related fields.
+    fn fill(
+        tx: TransactionSignedEcRecovered,
+        block_hash: Option<H256>,
+        block_number: Option<BlockNumber>,
+        transaction_index: Option<U256>,
+    ) -> Self {
+        let from = tx.signer();
+        let signed_tx = tx.into_signed();
+
+        let to = match signed_tx.kind() {
+            PrimitiveTransactionKind::Create => None,
+            PrimitiveTransactionKind::Call(to) => Some(*to),
+        };
+
+        let (gas_price, max_fee_per_gas, max_priority_fee_per_gas, access_list) = match &signed_tx
+            .transaction
+        {
+            PrimitiveTransaction::Legacy(tx) => (Some(U128::from(tx.gas_price)), None, None, None),
+            PrimitiveTransaction::Eip2930(tx) => {
+                (Some(U128::from(tx.gas_price)), None, None, Some(&tx.access_list))
+            }
+            PrimitiveTransaction::Eip1559(tx) => (
+                None,
+                Some(U128::from(tx.max_fee_per_gas)),
+                Some(U128::from(tx.max_priority_fee_per_gas)),
+                Some(&tx.access_list),
+            ),
+        };
+
+        let access_list = access_list.map(|list| {
+            list.0
+                .iter()
+                .map(|item| AccessListItem {
+                    address: rpc::H160(item.address.0),
+                    storage_keys: item.storage_keys.iter().map(|key| rpc::H256(key.0)).collect(),
+                })
+                .collect()
+        });
+
+        let is_legacy = matches!(signed_tx.transaction, PrimitiveTransaction::Legacy(_));
+        let chain_id = signed_tx.chain_id().copied();
+        let signature =
+            Signature::from_primitive_signature(signed_tx.signature().clone(), is_legacy, chain_id);
+        let transaction_type = match signed_tx.transaction {
+            PrimitiveTransaction::Legacy(_) => None,
+            _ => Some(U64::from(signed_tx.tx_type() as u8)),
+        };
+
+        Self {
+            hash: signed_tx.hash(),
+            nonce: U256::from(signed_tx.nonce()),
+            block_hash,
+            block_number: block_number.map(U256::from),
+            transaction_index,
+            from,
+            to,
+            value: U256::from(*signed_tx.value()),
+            gas_price,
+            gas: U256::from(signed_tx.gas_limit()),
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            input: signed_tx.input().clone(),
+            signature: Some(signature),
+            chain_id: chain_id.map(U64::from),
+            access_list,
+            transaction_type,
+        }
+    }
+}
@@ -1,5 +1,5 @@
 //! Signature related RPC values
-use reth_primitives::U256;
+use reth_primitives::{Signature as PrimitiveSignature, U256};
 use serde::{Deserialize, Serialize};
 
 /// Container type for all signature fields in RPC
@@ -12,3 +12,26 @@ pub struct Signature {
     /// The standardised V field of the signature (0 or 1).
     pub v: U256,
 }
+
+impl Signature {
+    /// Creates a new rpc signature from a primitive signature.
+    ///
+    /// The `v` value of legacy transactions is encoded with the [EIP-155](https://eips.ethereum.org/EIPS/eip-155)
+    /// scheme if a `legacy_chain_id` is given. For all other transaction types `v` is the y parity.
+    pub fn from_primitive_signature(
+        signature: PrimitiveSignature,
+        is_legacy: bool,
+        legacy_chain_id: Option<u64>,
+    ) -> Self {
+        let parity = signature.odd_y_parity as u64;
+        let v = if !is_legacy {
+            parity
+        } else if let Some(chain_id) = legacy_chain_id {
+            // EIP-155: v = {0, 1} + CHAIN_ID * 2 + 35
+            parity + chain_id * 2 + 35
+        } else {
+            parity + 27
+        };
+        Self { r: signature.r, s: signature.s, v: U256::from(v) }
+    }
+}
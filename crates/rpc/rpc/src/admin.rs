@@ -5,26 +5,30 @@ use reth_network_api::{NetworkInfo, PeerKind, Peers};
 use reth_primitives::NodeRecord;
 use reth_rpc_api::AdminApiServer;
 use reth_rpc_types::NodeInfo;
+use reth_transaction_pool::TransactionPool;
 
 /// `admin` API implementation.
 ///
 /// This type provides the functionality for handling `admin` related requests.
-pub struct AdminApi<N> {
+pub struct AdminApi<N, Pool> {
     /// An interface to interact with the network
     network: N,
+    /// The transaction pool
+    pool: Pool,
 }
 
-impl<N> AdminApi<N> {
+impl<N, Pool> AdminApi<N, Pool> {
     /// Creates a new instance of `AdminApi`.
-    pub fn new(network: N) -> Self {
-        AdminApi { network }
+    pub fn new(network: N, pool: Pool) -> Self {
+        AdminApi { network, pool }
     }
 }
 
 #[async_trait]
-impl<N> AdminApiServer for AdminApi<N>
+impl<N, Pool> AdminApiServer for AdminApi<N, Pool>
 where
     N: NetworkInfo + Peers + 'static,
+    Pool: TransactionPool + 'static,
 {
     fn add_peer(&self, record: NodeRecord) -> RpcResult<bool> {
         self.network.add_peer(record.id, record.tcp_addr());
@@ -59,9 +63,14 @@ where
 
         Ok(NodeInfo::new(enr, status))
     }
+
+    fn reload_transaction_ban_list(&self) -> RpcResult<bool> {
+        self.pool.reload_ban_list().to_rpc_result()?;
+        Ok(true)
+    }
 }
 
-impl<N> std::fmt::Debug for AdminApi<N> {
+impl<N, Pool> std::fmt::Debug for AdminApi<N, Pool> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AdminApi").finish_non_exhaustive()
     }
@@ -1,19 +1,55 @@
 //! Contains RPC handler implementations specific to blocks.
 
 use crate::{eth::error::EthResult, EthApi};
-use reth_primitives::H256;
-use reth_provider::{BlockProvider, StateProviderFactory};
-use reth_rpc_types::RichBlock;
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber},
+    H256,
+};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
+use reth_rpc_types::{Block, RichBlock};
 
 impl<Client, Pool, Network> EthApi<Client, Pool, Network>
 where
-    Client: BlockProvider + StateProviderFactory + 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
 {
     pub(crate) async fn block_by_hash(
         &self,
-        _hash: H256,
-        _full: bool,
+        hash: H256,
+        full: bool,
     ) -> EthResult<Option<RichBlock>> {
-        todo!()
+        self.block_by_id(BlockId::Hash(hash.0.into()), full)
+    }
+
+    /// Returns the block with the given number.
+    ///
+    /// If the number is `pending`, this returns the pending block or the latest block if no pending
+    /// block is available.
+    pub(crate) async fn block_by_number(
+        &self,
+        number: BlockNumber,
+        full: bool,
+    ) -> EthResult<Option<RichBlock>> {
+        if number == BlockNumber::Pending {
+            if let Some(pending) = self.pending_block()? {
+                // the state root of the pending block is not computed, hence it has no hash
+                let block =
+                    Block::from_block(pending.block.clone(), pending.total_difficulty, full, None)?;
+                return Ok(Some(RichBlock { inner: block, extra_info: Default::default() }))
+            }
+            return self.block_by_id(BlockId::Number(BlockNumber::Latest), full)
+        }
+        self.block_by_id(BlockId::Number(number), full)
+    }
+
+    /// Returns the block with the given [BlockId] from the database.
+    fn block_by_id(&self, block_id: BlockId, full: bool) -> EthResult<Option<RichBlock>> {
+        let block = match self.client().block(block_id)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let block_hash = block.header.hash_slow();
+        let total_difficulty = self.client().header_td(&block_hash)?.unwrap_or_default();
+        let block = Block::from_block(block, total_difficulty, full, Some(block_hash))?;
+        Ok(Some(RichBlock { inner: block, extra_info: Default::default() }))
     }
 }
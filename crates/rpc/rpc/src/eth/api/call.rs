@@ -0,0 +1,145 @@
+//! Contains RPC handler implementations specific to `eth_call`.
+
+use crate::{
+    eth::error::{EthApiError, EthResult},
+    EthApi,
+};
+use reth_executor::{
+    config::revm_spec,
+    revm_wrap::{fill_block_env, State, SubState},
+};
+use reth_primitives::{
+    rpc::{BlockId, BlockNumber},
+    Address, Bytes, Head, Header, U256,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, OverlayStateProvider, StateProvider, StateProviderFactory,
+};
+use reth_rpc_types::CallRequest;
+use revm::{TransactOut, TransactTo, TxEnv, EVM};
+
+impl<Client, Pool, Network> EthApi<Client, Pool, Network>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+{
+    /// Executes the call request on top of the state at the given [BlockId] without committing
+    /// any changes.
+    ///
+    /// If the block id is `pending`, the call is executed on top of the pending block.
+    pub(crate) fn call(&self, request: CallRequest, block_id: Option<BlockId>) -> EthResult<Bytes> {
+        let mut block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Latest));
+        if block_id == BlockId::Number(BlockNumber::Pending) {
+            if let Some(pending) = self.pending_block()? {
+                let state = OverlayStateProvider::new(self.client().latest()?, &pending.post_state);
+                return self.transact_call(
+                    request,
+                    &pending.block.header,
+                    pending.total_difficulty,
+                    state,
+                )
+            }
+            block_id = BlockId::Number(BlockNumber::Latest);
+        }
+
+        let header = self.header_at_block_id(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        let total_difficulty = self.client().header_td(&header.hash_slow())?.unwrap_or_default();
+        let state = self.state_at_block_id(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        self.transact_call(request, &header, total_difficulty, state)
+    }
+
+    /// Executes the call request in the block environment of the given header, with the spec
+    /// that is active at that block.
+    fn transact_call<S: StateProvider>(
+        &self,
+        request: CallRequest,
+        header: &Header,
+        total_difficulty: U256,
+        state: S,
+    ) -> EthResult<Bytes> {
+        let mut db = SubState::new(State::new(state));
+        let mut evm = EVM::new();
+        evm.env.cfg.chain_id = U256::from(self.chain_spec().chain().id());
+        evm.env.cfg.spec_id = revm_spec(
+            self.chain_spec(),
+            Head {
+                number: header.number,
+                timestamp: header.timestamp,
+                total_difficulty,
+                ..Default::default()
+            },
+        );
+        fill_block_env(&mut evm.env.block, header, header.difficulty == U256::ZERO);
+
+        // calls without a gas price are not charged, so they must not be rejected by the base fee
+        if request.gas_price.is_none() && request.max_fee_per_gas.is_none() {
+            evm.env.block.basefee = U256::ZERO;
+        }
+        evm.env.tx = call_request_to_tx_env(request, header.gas_limit);
+        evm.database(&mut db);
+
+        let (result, _) = evm.transact();
+        let output = match result.out {
+            TransactOut::Call(out) => Bytes::from(out),
+            TransactOut::Create(out, _) => Bytes::from(out),
+            TransactOut::None => Bytes::default(),
+        };
+
+        match result.exit_reason {
+            revm::return_ok!() => Ok(output),
+            revm::return_revert!() => Err(EthApiError::Revert(output)),
+            revm::Return::FatalExternalError => {
+                Err(reth_interfaces::executor::Error::ExecutionFatalError.into())
+            }
+            reason => Err(EthApiError::EvmHalt(reason)),
+        }
+    }
+}
+
+/// Converts the [CallRequest] into a [TxEnv].
+///
+/// Missing fields are filled with defaults, the gas limit defaults to the gas limit of the block.
+fn call_request_to_tx_env(request: CallRequest, block_gas_limit: u64) -> TxEnv {
+    let CallRequest {
+        from,
+        to,
+        gas_price,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        gas,
+        value,
+        data,
+        nonce,
+        access_list,
+        ..
+    } = request;
+
+    let gas_price = gas_price.or(max_fee_per_gas).map(U256::from).unwrap_or_default();
+
+    TxEnv {
+        caller: from.unwrap_or_default(),
+        gas_limit: gas
+            .map(|gas| u64::try_from(gas).unwrap_or(u64::MAX))
+            .unwrap_or(block_gas_limit)
+            .min(block_gas_limit),
+        gas_price,
+        gas_priority_fee: max_priority_fee_per_gas.map(U256::from),
+        transact_to: match to {
+            Some(to) => TransactTo::Call(to),
+            None => TransactTo::create(),
+        },
+        value: value.unwrap_or_default(),
+        data: data.map(|data| data.0).unwrap_or_default(),
+        chain_id: None,
+        nonce: nonce.and_then(|nonce| u64::try_from(nonce).ok()),
+        access_list: access_list
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| {
+                (
+                    Address::from(item.address.0),
+                    item.storage_keys.into_iter().map(|key| U256::from_be_bytes(key.0)).collect(),
+                )
+            })
+            .collect(),
+    }
+}
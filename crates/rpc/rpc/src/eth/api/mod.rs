@@ -3,24 +3,33 @@
 //! The entire implementation of the namespace is quite large, hence it is divided across several
 //! files.
 
-use crate::eth::signer::EthSigner;
+use crate::eth::{
+    error::{EthApiError, EthResult},
+    pending_block::{PendingBlock, PendingBlockCache},
+    signer::EthSigner,
+};
 use async_trait::async_trait;
 use reth_interfaces::Result;
 use reth_network_api::NetworkInfo;
 use reth_primitives::{
     rpc::{BlockId, BlockNumber},
-    Address, ChainInfo, TransactionSigned, H256, U64,
+    Address, ChainInfo, ChainSpec, Header, TransactionSigned, H256, U64,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, OverlayStateProvider, StateProvider, StateProviderFactory,
 };
-use reth_provider::{BlockProvider, StateProviderFactory};
 
 use reth_transaction_pool::TransactionPool;
 use std::sync::Arc;
 
 mod block;
+mod call;
 mod server;
 mod state;
 mod transactions;
 
+pub(crate) use transactions::recover_raw_transaction;
+
 /// `Eth` API trait.
 ///
 /// Defines core functionality of the `eth` API implementation.
@@ -58,9 +67,28 @@ pub struct EthApi<Client, Pool, Network> {
 }
 
 impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
-    /// Creates a new, shareable instance.
-    pub fn new(client: Client, pool: Pool, network: Network) -> Self {
-        let inner = EthApiInner { client, pool, network, signers: Default::default() };
+    /// Creates a new, shareable instance for the given chain.
+    pub fn new(client: Client, pool: Pool, network: Network, chain_spec: Arc<ChainSpec>) -> Self {
+        Self::with_pending_block(client, pool, network, Default::default(), chain_spec)
+    }
+
+    /// Creates a new, shareable instance for the given chain that serves `pending` queries from
+    /// the given [PendingBlockCache].
+    pub fn with_pending_block(
+        client: Client,
+        pool: Pool,
+        network: Network,
+        pending_block: PendingBlockCache,
+        chain_spec: Arc<ChainSpec>,
+    ) -> Self {
+        let inner = EthApiInner {
+            client,
+            pool,
+            network,
+            signers: Default::default(),
+            pending_block,
+            chain_spec,
+        };
         Self { inner: Arc::new(inner) }
     }
 
@@ -78,6 +106,16 @@ impl<Client, Pool, Network> EthApi<Client, Pool, Network> {
     pub(crate) fn pool(&self) -> &Pool {
         &self.inner.pool
     }
+
+    /// Returns the spec of the chain.
+    pub(crate) fn chain_spec(&self) -> &ChainSpec {
+        &self.inner.chain_spec
+    }
+
+    /// Returns the cache of the pending block.
+    pub fn pending_block_cache(&self) -> &PendingBlockCache {
+        &self.inner.pending_block
+    }
 }
 
 // === State access helpers ===
@@ -149,6 +187,63 @@ where
     ) -> Result<Option<<Client as StateProviderFactory>::HistorySP<'_>>> {
         self.state_at_block_number(BlockNumber::Latest)
     }
+
+    /// Returns the pending block if it was built on top of the current head.
+    pub(crate) fn pending_block(&self) -> Result<Option<Arc<PendingBlock>>> {
+        let pending = match self.inner.pending_block.get() {
+            Some(pending) => pending,
+            None => return Ok(None),
+        };
+        // the pending block is outdated if the chain advanced since it was built
+        if pending.parent_hash() != self.client().chain_info()?.best_hash {
+            return Ok(None)
+        }
+        Ok(Some(pending))
+    }
+
+    /// Executes the closure with the state at the given [BlockId] or the latest state.
+    ///
+    /// If the block id is `pending`, the closure is called with the post state of the pending
+    /// block. If there is no pending block, the latest state is used instead.
+    pub(crate) fn with_state_at_block_id_or_latest<F, R>(
+        &self,
+        block_id: Option<BlockId>,
+        f: F,
+    ) -> EthResult<R>
+    where
+        F: FnOnce(&dyn StateProvider) -> EthResult<R>,
+    {
+        let block_id = match block_id {
+            Some(BlockId::Number(BlockNumber::Pending)) => {
+                if let Some(pending) = self.pending_block()? {
+                    let state =
+                        OverlayStateProvider::new(self.client().latest()?, &pending.post_state);
+                    return f(&state)
+                }
+                None
+            }
+            block_id => block_id,
+        };
+        let state =
+            self.state_at_block_id_or_latest(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
+        f(&state)
+    }
+}
+
+impl<Client, Pool, Network> EthApi<Client, Pool, Network>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+{
+    /// Returns the header of the block with the given [BlockId].
+    pub(crate) fn header_at_block_id(&self, block_id: BlockId) -> Result<Option<Header>> {
+        match block_id {
+            BlockId::Hash(hash) => self.client().header(&H256(hash.0)),
+            BlockId::Number(num) => match self.convert_block_number(num)? {
+                Some(number) => self.client().header_by_number(number),
+                None => Ok(None),
+            },
+        }
+    }
 }
 
 #[async_trait]
@@ -195,4 +290,8 @@ struct EthApiInner<Client, Pool, Network> {
     network: Network,
     /// All configured Signers
     signers: Vec<Box<dyn EthSigner>>,
+    /// The most recently built pending block.
+    pending_block: PendingBlockCache,
+    /// The spec of the chain, used to configure the EVM.
+    chain_spec: Arc<ChainSpec>,
 }
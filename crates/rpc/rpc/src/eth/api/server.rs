@@ -6,11 +6,12 @@ use crate::{
     result::{internal_rpc_err, ToRpcResult},
 };
 use jsonrpsee::core::RpcResult as Result;
+use reth_network_api::NetworkInfo;
 use reth_primitives::{
-    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId},
+    rpc::{transaction::eip2930::AccessListWithGasUsed, BlockId, BlockNumber as RpcBlockNumber},
     Address, BlockNumber, Bytes, H256, H64, U256, U64,
 };
-use reth_provider::{BlockProvider, StateProviderFactory};
+use reth_provider::{BlockProvider, HeaderProvider, StateProviderFactory};
 use reth_rpc_api::EthApiServer;
 use reth_rpc_types::{
     CallRequest, EIP1186AccountProofResponse, FeeHistory, Index, RichBlock, SyncStatus,
@@ -26,8 +27,8 @@ impl<Client, Pool, Network> EthApiServer for EthApi<Client, Pool, Network>
 where
     Self: EthApiSpec,
     Pool: TransactionPool + 'static,
-    Client: BlockProvider + StateProviderFactory + 'static,
-    Network: 'static,
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+    Network: NetworkInfo + 'static,
 {
     async fn protocol_version(&self) -> Result<U64> {
         EthApiSpec::protocol_version(self).await.to_rpc_result()
@@ -55,16 +56,16 @@ where
         Ok(Some(EthApiSpec::chain_id(self)))
     }
 
-    async fn block_by_hash(&self, _hash: H256, _full: bool) -> Result<Option<RichBlock>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<RichBlock>> {
+        EthApi::block_by_hash(self, hash, full).await.to_rpc_result()
     }
 
     async fn block_by_number(
         &self,
-        _number: BlockNumber,
-        _full: bool,
+        number: RpcBlockNumber,
+        full: bool,
     ) -> Result<Option<RichBlock>> {
-        Err(internal_rpc_err("unimplemented"))
+        EthApi::block_by_number(self, number, full).await.to_rpc_result()
     }
 
     async fn block_transaction_count_by_hash(&self, _hash: H256) -> Result<Option<U256>> {
@@ -129,33 +130,33 @@ where
         Err(internal_rpc_err("unimplemented"))
     }
 
-    async fn balance(&self, _address: Address, _block_number: Option<BlockId>) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn balance(&self, address: Address, block_number: Option<BlockId>) -> Result<U256> {
+        EthApi::balance(self, address, block_number).to_rpc_result()
     }
 
     async fn storage_at(
         &self,
-        _address: Address,
-        _index: U256,
-        _block_number: Option<BlockId>,
+        address: Address,
+        index: U256,
+        block_number: Option<BlockId>,
     ) -> Result<H256> {
-        Err(internal_rpc_err("unimplemented"))
+        EthApi::storage_at(self, address, index, block_number).to_rpc_result()
     }
 
     async fn transaction_count(
         &self,
-        _address: Address,
-        _block_number: Option<BlockId>,
+        address: Address,
+        block_number: Option<BlockId>,
     ) -> Result<U256> {
-        Err(internal_rpc_err("unimplemented"))
+        EthApi::get_transaction_count(self, address, block_number).to_rpc_result()
     }
 
     async fn get_code(&self, address: Address, block_number: Option<BlockId>) -> Result<Bytes> {
         EthApi::get_code(self, address, block_number).to_rpc_result()
     }
 
-    async fn call(&self, _request: CallRequest, _block_number: Option<BlockId>) -> Result<Bytes> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn call(&self, request: CallRequest, block_number: Option<BlockId>) -> Result<Bytes> {
+        EthApi::call(self, request, block_number).to_rpc_result()
     }
 
     async fn create_access_list(
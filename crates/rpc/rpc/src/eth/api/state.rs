@@ -1,30 +1,46 @@
 //! Contains RPC handler implementations specific to state.
 
-use crate::{
-    eth::error::{EthApiError, EthResult},
-    EthApi,
-};
-use reth_interfaces::Result;
+use crate::{eth::error::EthResult, EthApi};
 use reth_primitives::{rpc::BlockId, Address, Bytes, H256, U256};
-use reth_provider::{BlockProvider, StateProvider, StateProviderFactory};
+use reth_provider::{BlockProvider, StateProviderFactory};
 
 impl<Client, Pool, Network> EthApi<Client, Pool, Network>
 where
     Client: BlockProvider + StateProviderFactory + 'static,
 {
     pub(crate) fn get_code(&self, address: Address, block_id: Option<BlockId>) -> EthResult<Bytes> {
-        let state =
-            self.state_at_block_id_or_latest(block_id)?.ok_or(EthApiError::UnknownBlockNumber)?;
-        let code = state.account_code(address)?.unwrap_or_default();
-        Ok(code)
+        self.with_state_at_block_id_or_latest(block_id, |state| {
+            Ok(state.account_code(address)?.unwrap_or_default())
+        })
     }
 
-    async fn storage_at(
+    pub(crate) fn balance(&self, address: Address, block_id: Option<BlockId>) -> EthResult<U256> {
+        self.with_state_at_block_id_or_latest(block_id, |state| {
+            Ok(state.basic_account(address)?.map(|account| account.balance).unwrap_or_default())
+        })
+    }
+
+    pub(crate) fn get_transaction_count(
+        &self,
+        address: Address,
+        block_id: Option<BlockId>,
+    ) -> EthResult<U256> {
+        self.with_state_at_block_id_or_latest(block_id, |state| {
+            let nonce =
+                state.basic_account(address)?.map(|account| account.nonce).unwrap_or_default();
+            Ok(U256::from(nonce))
+        })
+    }
+
+    pub(crate) fn storage_at(
         &self,
-        _address: Address,
-        _index: U256,
-        _block_number: Option<BlockId>,
-    ) -> Result<H256> {
-        todo!()
+        address: Address,
+        index: U256,
+        block_id: Option<BlockId>,
+    ) -> EthResult<H256> {
+        self.with_state_at_block_id_or_latest(block_id, |state| {
+            let value = state.storage(address, H256(index.to_be_bytes()))?.unwrap_or_default();
+            Ok(H256(value.to_be_bytes()))
+        })
     }
 }
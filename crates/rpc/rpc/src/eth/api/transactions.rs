@@ -4,7 +4,9 @@ use crate::{
     eth::error::{EthApiError, EthResult},
     EthApi,
 };
-use reth_primitives::{Bytes, FromRecoveredTransaction, TransactionSigned, H256};
+use reth_primitives::{
+    Bytes, FromRecoveredTransaction, TransactionSigned, TransactionSignedEcRecovered, H256,
+};
 use reth_provider::{BlockProvider, StateProviderFactory};
 use reth_rlp::Decodable;
 use reth_rpc_types::TransactionRequest;
@@ -24,16 +26,7 @@ where
     ///
     /// Returns the hash of the transaction.
     pub(crate) async fn send_raw_transaction(&self, tx: Bytes) -> EthResult<H256> {
-        let mut data = tx.as_ref();
-        if data.is_empty() {
-            return Err(EthApiError::EmptyRawTransactionData)
-        }
-
-        let transaction = TransactionSigned::decode(&mut data)
-            .map_err(|_| EthApiError::FailedToDecodeSignedTransaction)?;
-
-        let recovered =
-            transaction.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)?;
+        let recovered = recover_raw_transaction(tx)?;
 
         let pool_transaction = <Pool::Transaction>::from_recovered_transaction(recovered);
 
@@ -44,11 +37,25 @@ where
     }
 }
 
+/// Decodes the raw transaction and recovers its signer.
+pub(crate) fn recover_raw_transaction(tx: Bytes) -> EthResult<TransactionSignedEcRecovered> {
+    let mut data = tx.as_ref();
+    if data.is_empty() {
+        return Err(EthApiError::EmptyRawTransactionData)
+    }
+
+    let transaction = TransactionSigned::decode(&mut data)
+        .map_err(|_| EthApiError::FailedToDecodeSignedTransaction)?;
+
+    transaction.into_ecrecovered().ok_or(EthApiError::InvalidTransactionSignature)
+}
+
 #[cfg(test)]
 mod tests {
-    use reth_primitives::{hex_literal::hex, Bytes};
+    use reth_primitives::{hex_literal::hex, Bytes, MAINNET};
     use reth_provider::test_utils::NoopProvider;
     use reth_transaction_pool::{test_utils::testing_pool, TransactionPool};
+    use std::sync::Arc;
 
     use crate::EthApi;
 
@@ -58,7 +65,7 @@ mod tests {
 
         let pool = testing_pool();
 
-        let eth_api = EthApi::new(noop_provider, pool.clone(), ());
+        let eth_api = EthApi::new(noop_provider, pool.clone(), (), Arc::new(MAINNET.clone()));
 
         // https://etherscan.io/tx/0xa694b71e6c128a2ed8e2e0f6770bddbe52e3bb8f10e8472f9a79ab81497a8b5d
         let tx_1 = Bytes::from(hex!("02f871018303579880850555633d1b82520894eee27662c2b8eba3cd936a23f039f3189633e4c887ad591c62bdaeb180c080a07ea72c68abfb8fca1bd964f0f99132ed9280261bdca3e549546c0205e800f7d0a05b4ef3039e9c9b9babc179a1878fb825b5aaf5aed2fa8744854150157b08d6f3"));
@@ -0,0 +1,345 @@
+//! Support for private transactions and transaction bundles.
+//!
+//! Bundles are submitted via `eth_sendBundle`, stored in the shared [BundleStore] and included
+//! atomically by the [PendingBlockBuilder](crate::PendingBlockBuilder) in their target block.
+
+use crate::{
+    eth::{
+        api::recover_raw_transaction,
+        error::{EthApiError, EthResult},
+        pending_block::next_block_header,
+    },
+    result::ToRpcResult,
+};
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult;
+use parking_lot::RwLock;
+use reth_executor::{
+    execution_result::TransactionChangeSet,
+    executor::Executor,
+    revm_wrap::{State, SubState},
+};
+use reth_interfaces::executor::Error as ExecutorError;
+use reth_primitives::{
+    keccak256, Address, Bytes, ChainSpec, FromRecoveredTransaction, Header,
+    TransactionSignedEcRecovered, H256, U256,
+};
+use reth_provider::{BlockProvider, HeaderProvider, StateProvider, StateProviderFactory};
+use reth_rpc_api::EthBundleApiServer;
+use reth_rpc_types::{EthBundleHash, EthSendBundle};
+use reth_transaction_pool::{TransactionOrigin, TransactionPool};
+use std::sync::Arc;
+
+/// An ordered list of transactions that must be included together in a specific block.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    /// Hash of the bundle, the keccak256 of the concatenated transaction hashes.
+    pub hash: H256,
+    /// The transactions of the bundle, in execution order.
+    pub transactions: Vec<TransactionSignedEcRecovered>,
+    /// The number of the block the bundle targets.
+    pub block_number: u64,
+}
+
+// === impl Bundle ===
+
+impl Bundle {
+    /// Creates a new bundle for the given block.
+    pub fn new(transactions: Vec<TransactionSignedEcRecovered>, block_number: u64) -> Self {
+        let hashes =
+            transactions.iter().flat_map(|tx| tx.hash().to_fixed_bytes()).collect::<Vec<_>>();
+        Self { hash: keccak256(hashes), transactions, block_number }
+    }
+
+    /// Total gas limit of all transactions of the bundle, or `None` if it overflows.
+    pub fn gas_limit(&self) -> Option<u64> {
+        self.transactions.iter().try_fold(0u64, |total, tx| total.checked_add(tx.gas_limit()))
+    }
+}
+
+/// A shareable store of bundles that were not yet included.
+#[derive(Debug, Clone, Default)]
+pub struct BundleStore {
+    inner: Arc<RwLock<Vec<Arc<Bundle>>>>,
+}
+
+// === impl BundleStore ===
+
+impl BundleStore {
+    /// Adds a new bundle, replacing an identical bundle for the same block.
+    pub fn insert(&self, bundle: Bundle) {
+        let mut bundles = self.inner.write();
+        bundles.retain(|b| b.hash != bundle.hash || b.block_number != bundle.block_number);
+        bundles.push(Arc::new(bundle));
+    }
+
+    /// Returns all bundles that target the given block, in the order they were submitted.
+    pub fn bundles_for_block(&self, block_number: u64) -> Vec<Arc<Bundle>> {
+        self.inner.read().iter().filter(|b| b.block_number == block_number).cloned().collect()
+    }
+
+    /// Removes all bundles that target a block before the given block number.
+    pub fn remove_expired(&self, block_number: u64) {
+        self.inner.write().retain(|b| b.block_number >= block_number);
+    }
+
+    /// Number of stored bundles.
+    pub fn len(&self) -> usize {
+        self.inner.read().len()
+    }
+
+    /// Returns true if no bundles are stored.
+    pub fn is_empty(&self) -> bool {
+        self.inner.read().is_empty()
+    }
+}
+
+/// The result of executing all transactions of a [Bundle].
+#[derive(Debug)]
+pub(crate) struct ExecutedBundle {
+    /// The changesets of the transactions, in execution order.
+    pub(crate) changesets: Vec<TransactionChangeSet>,
+    /// Total gas used by the bundle.
+    pub(crate) gas_used: u64,
+}
+
+/// Errors that make a [Bundle] invalid.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum BundleError {
+    /// A transaction of the bundle was rejected or reverted.
+    #[error("bundle transaction {0:?} failed")]
+    TransactionFailed(H256),
+    /// The bundle does not fit into the remaining gas of the block.
+    #[error("bundle exceeds the block gas limit")]
+    ExceedsGasLimit,
+    /// Execution failed for reasons unrelated to the bundle.
+    #[error(transparent)]
+    Executor(#[from] ExecutorError),
+}
+
+/// Executes all transactions of the bundle on top of the given state.
+///
+/// The bundle is executed atomically: if any of its transactions is rejected or reverts, the
+/// entire bundle is invalid.
+pub(crate) fn execute_bundle<SP: StateProvider>(
+    chain_spec: &ChainSpec,
+    header: &Header,
+    total_difficulty: U256,
+    state: SP,
+    bundle: &Bundle,
+    cumulative_gas_used: u64,
+) -> Result<ExecutedBundle, BundleError> {
+    // the gas limits are chosen by the sender of the bundle
+    let gas_limit =
+        bundle.gas_limit().and_then(|gas_limit| gas_limit.checked_add(cumulative_gas_used));
+    if gas_limit.map_or(true, |gas_limit| gas_limit > header.gas_limit) {
+        return Err(BundleError::ExceedsGasLimit)
+    }
+
+    let mut db = SubState::new(State::new(state));
+    let mut executor = Executor::new(chain_spec, &mut db);
+    executor.init_block_env(header, total_difficulty);
+
+    let mut gas_used = 0;
+    let mut changesets = Vec::with_capacity(bundle.transactions.len());
+    for tx in bundle.transactions.iter() {
+        let (changeset, tx_gas_used) =
+            match executor.simulate_transaction(tx, tx.signer(), cumulative_gas_used + gas_used) {
+                Ok(res) => res,
+                Err(ExecutorError::TransactionRejected { hash, .. }) => {
+                    return Err(BundleError::TransactionFailed(hash))
+                }
+                Err(err) => return Err(err.into()),
+            };
+        if !changeset.receipt.success {
+            return Err(BundleError::TransactionFailed(tx.hash()))
+        }
+        gas_used += tx_gas_used;
+        changesets.push(changeset);
+    }
+
+    Ok(ExecutedBundle { changesets, gas_used })
+}
+
+/// `eth_` bundle API implementation for private transaction submission.
+///
+/// Private transactions are inserted into the pool with [TransactionOrigin::Private] and bundles
+/// are added to the [BundleStore] after they were simulated on top of the current head.
+pub struct EthBundle<Client, Pool> {
+    /// All nested fields bundled together.
+    inner: Arc<EthBundleInner<Client, Pool>>,
+}
+
+// === impl EthBundle ===
+
+impl<Client, Pool> EthBundle<Client, Pool> {
+    /// Creates a new instance that stores bundles in the given [BundleStore].
+    pub fn new(
+        client: Client,
+        pool: Pool,
+        chain_spec: Arc<ChainSpec>,
+        bundles: BundleStore,
+    ) -> Self {
+        Self { inner: Arc::new(EthBundleInner { client, pool, chain_spec, bundles }) }
+    }
+}
+
+impl<Client, Pool> EthBundle<Client, Pool>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Inserts the transaction into the pool without propagating it.
+    async fn send_private_raw_transaction(&self, tx: Bytes) -> EthResult<H256> {
+        let recovered = recover_raw_transaction(tx)?;
+        let pool_transaction = <Pool::Transaction>::from_recovered_transaction(recovered);
+        let hash =
+            self.inner.pool.add_transaction(TransactionOrigin::Private, pool_transaction).await?;
+        Ok(hash)
+    }
+
+    /// Simulates the bundle on top of the current head and stores it if it's valid.
+    ///
+    /// The simulation is executed on a blocking task, since it reads the state from the database
+    /// and runs the EVM.
+    async fn send_bundle(&self, bundle: EthSendBundle) -> EthResult<EthBundleHash> {
+        let EthSendBundle { txs, block_number } = bundle;
+        if txs.is_empty() {
+            return Err(EthApiError::EmptyBundle)
+        }
+        let transactions =
+            txs.into_iter().map(recover_raw_transaction).collect::<EthResult<Vec<_>>>()?;
+        let block_number = block_number.as_u64();
+
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || inner.simulate_bundle(transactions, block_number))
+            .await
+            .map_err(|_| EthApiError::InternalBlockingTaskError)?
+    }
+}
+
+/// Container type for [EthBundle].
+struct EthBundleInner<Client, Pool> {
+    /// The client used to access the head state.
+    client: Client,
+    /// The pool private transactions are submitted to.
+    pool: Pool,
+    /// The chain bundles are simulated for.
+    chain_spec: Arc<ChainSpec>,
+    /// Where valid bundles are stored.
+    bundles: BundleStore,
+}
+
+impl<Client, Pool> EthBundleInner<Client, Pool>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+{
+    /// Simulates the bundle on top of the current head and stores it if it's valid.
+    fn simulate_bundle(
+        &self,
+        transactions: Vec<TransactionSignedEcRecovered>,
+        block_number: u64,
+    ) -> EthResult<EthBundleHash> {
+        let (header, total_difficulty) =
+            next_block_header(&self.client, &self.chain_spec, Address::zero())?;
+        if block_number < header.number {
+            return Err(EthApiError::ExpiredBundle(block_number))
+        }
+
+        let bundle = Bundle::new(transactions, block_number);
+        execute_bundle(
+            &self.chain_spec,
+            &header,
+            total_difficulty,
+            self.client.latest()?,
+            &bundle,
+            0,
+        )?;
+
+        let bundle_hash = bundle.hash;
+        self.bundles.insert(bundle);
+        Ok(EthBundleHash { bundle_hash })
+    }
+}
+
+#[async_trait]
+impl<Client, Pool> EthBundleApiServer for EthBundle<Client, Pool>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+    Pool: TransactionPool + 'static,
+{
+    async fn send_private_raw_transaction(&self, bytes: Bytes) -> RpcResult<H256> {
+        EthBundle::send_private_raw_transaction(self, bytes).await.to_rpc_result()
+    }
+
+    async fn send_bundle(&self, bundle: EthSendBundle) -> RpcResult<EthBundleHash> {
+        EthBundle::send_bundle(self, bundle).await.to_rpc_result()
+    }
+}
+
+impl<Client, Pool> std::fmt::Debug for EthBundle<Client, Pool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EthBundle").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Transaction, TransactionSigned, TxLegacy, MAINNET};
+    use reth_provider::test_utils::NoopProvider;
+
+    fn transaction(nonce: u64, gas_limit: u64) -> TransactionSignedEcRecovered {
+        let tx = TransactionSigned::from_transaction_and_signature(
+            Transaction::Legacy(TxLegacy { nonce, gas_limit, ..Default::default() }),
+            Default::default(),
+        );
+        TransactionSignedEcRecovered::from_signed_transaction(tx, Address::zero())
+    }
+
+    fn bundle(block_number: u64, nonce: u64) -> Bundle {
+        Bundle::new(vec![transaction(nonce, 0)], block_number)
+    }
+
+    #[test]
+    fn reject_overflowing_gas_limit() {
+        let header = Header { gas_limit: 30_000_000, ..Default::default() };
+        let execute = |bundle: &Bundle, cumulative_gas_used| {
+            execute_bundle(
+                &MAINNET,
+                &header,
+                U256::ZERO,
+                NoopProvider::default(),
+                bundle,
+                cumulative_gas_used,
+            )
+        };
+
+        let bundle = Bundle::new(vec![transaction(0, u64::MAX), transaction(1, 21_000)], 1);
+        assert_eq!(bundle.gas_limit(), None);
+        assert!(matches!(execute(&bundle, 0), Err(BundleError::ExceedsGasLimit)));
+
+        let bundle = Bundle::new(vec![transaction(0, u64::MAX)], 1);
+        assert_eq!(bundle.gas_limit(), Some(u64::MAX));
+        assert!(matches!(execute(&bundle, 21_000), Err(BundleError::ExceedsGasLimit)));
+    }
+
+    #[test]
+    fn bundle_store_expiry() {
+        let store = BundleStore::default();
+        store.insert(bundle(1, 0));
+        store.insert(bundle(2, 0));
+        store.insert(bundle(2, 1));
+        // identical bundles are replaced
+        store.insert(bundle(2, 1));
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.bundles_for_block(2).len(), 2);
+
+        store.remove_expired(2);
+        assert_eq!(store.len(), 2);
+        assert!(store.bundles_for_block(1).is_empty());
+
+        store.remove_expired(3);
+        assert!(store.is_empty());
+    }
+}
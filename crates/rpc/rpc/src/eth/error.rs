@@ -1,6 +1,8 @@
 //! Error variants for the `eth_` namespace.
 
-use crate::{impl_to_rpc_result, result::ToRpcResult};
+use crate::{eth::bundle::BundleError, impl_to_rpc_result, result::ToRpcResult};
+use reth_primitives::Bytes;
+use reth_rpc_types::BlockError;
 use reth_transaction_pool::error::PoolError;
 
 /// Result alias
@@ -22,6 +24,27 @@ pub(crate) enum EthApiError {
     #[error("Unknown block number")]
     // TODO return -32602 here
     UnknownBlockNumber,
+    /// Thrown when the execution of a call reverted, contains the revert data
+    #[error("execution reverted")]
+    Revert(Bytes),
+    /// Thrown when the EVM halted the execution of a call
+    #[error("EVM error {0:?}")]
+    EvmHalt(revm::Return),
+    /// Thrown when a bundle contains no transactions
+    #[error("empty bundle")]
+    EmptyBundle,
+    /// Thrown when a bundle targets a block that was already mined
+    #[error("bundle target block {0} already mined")]
+    ExpiredBundle(u64),
+    /// Thrown when a bundle failed the simulation
+    #[error(transparent)]
+    InvalidBundle(#[from] BundleError),
+    /// Thrown when a block could not be converted into its RPC representation
+    #[error(transparent)]
+    InvalidBlockData(#[from] BlockError),
+    /// Thrown when a task spawned on the blocking thread pool panicked or was cancelled
+    #[error("internal blocking task error")]
+    InternalBlockingTaskError,
     /// Other internal error
     #[error(transparent)]
     Internal(#[from] reth_interfaces::Error),
@@ -48,6 +71,8 @@ pub(crate) enum GethTxPoolError {
     NegativeValue,
     #[error("oversized data")]
     OversizedData,
+    #[error("address banned")]
+    BannedAddress,
 }
 
 impl From<PoolError> for GethTxPoolError {
@@ -59,6 +84,7 @@ impl From<PoolError> for GethTxPoolError {
             PoolError::DiscardedOnInsert(_) => GethTxPoolError::TxPoolOverflow,
             PoolError::TxExceedsGasLimit(_, _, _) => GethTxPoolError::GasLimit,
             PoolError::TxExceedsMaxInitCodeSize(_, _, _) => GethTxPoolError::OversizedData,
+            PoolError::BannedAddress(_, _) => GethTxPoolError::BannedAddress,
         }
     }
 }
@@ -1,9 +1,15 @@
 //! `eth` namespace handler implementation.
 
 mod api;
+mod bundle;
 pub(crate) mod error;
+mod pending_block;
 mod pubsub;
 mod signer;
 
 pub use api::{EthApi, EthApiSpec};
+pub use bundle::{Bundle, BundleStore, EthBundle};
+pub use pending_block::{
+    PendingBlock, PendingBlockBuilder, PendingBlockCache, DEFAULT_PENDING_BLOCK_INTERVAL,
+};
 pub use pubsub::EthPubSub;
@@ -0,0 +1,331 @@
+//! Support for the _pending_ block.
+//!
+//! The pending block is built by executing the best transactions of the pool on top of the current
+//! head state. It is rebuilt periodically by the [PendingBlockBuilder] and shared with the
+//! [EthApi](crate::EthApi) via the [PendingBlockCache].
+//!
+//! Bundles from the [BundleStore] that target the pending block are included before the
+//! transactions of the pool.
+
+use crate::eth::bundle::{execute_bundle, BundleStore};
+use parking_lot::RwLock;
+use reth_consensus::validation::calculate_next_block_base_fee;
+use reth_executor::{
+    executor::Executor,
+    revm_wrap::{State, SubState},
+};
+use reth_interfaces::{executor::Error as ExecutorError, provider::Error as ProviderError, Result};
+use reth_primitives::{
+    proofs, Address, Block, Bloom, ChainSpec, Hardfork, Header, IntoRecoveredTransaction, Receipt,
+    EMPTY_OMMER_ROOT, H256, U256,
+};
+use reth_provider::{
+    BlockProvider, HeaderProvider, OverlayStateProvider, StateOverlay, StateProviderFactory,
+};
+use reth_transaction_pool::TransactionPool;
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, trace};
+
+/// The default interval at which the pending block is rebuilt.
+pub const DEFAULT_PENDING_BLOCK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The pending block: the best transactions of the pool executed on top of the current head.
+#[derive(Debug, Clone)]
+pub struct PendingBlock {
+    /// The pending block.
+    ///
+    /// Note: the state root of the pending block is not computed.
+    pub block: Block,
+    /// The senders of the transactions of the block.
+    pub senders: Vec<Address>,
+    /// The receipts of the transactions of the block.
+    pub receipts: Vec<Receipt>,
+    /// The total difficulty of the pending block.
+    pub total_difficulty: U256,
+    /// All state changes caused by executing the pending block.
+    pub post_state: StateOverlay,
+}
+
+// === impl PendingBlock ===
+
+impl PendingBlock {
+    /// Hash of the parent of the pending block.
+    pub fn parent_hash(&self) -> H256 {
+        self.block.parent_hash
+    }
+
+    /// The number of the pending block.
+    pub fn number(&self) -> u64 {
+        self.block.number
+    }
+}
+
+/// A shareable cache for the latest [PendingBlock].
+#[derive(Debug, Clone, Default)]
+pub struct PendingBlockCache {
+    inner: Arc<RwLock<Option<Arc<PendingBlock>>>>,
+}
+
+// === impl PendingBlockCache ===
+
+impl PendingBlockCache {
+    /// Returns the current pending block, if any.
+    pub fn get(&self) -> Option<Arc<PendingBlock>> {
+        self.inner.read().clone()
+    }
+
+    /// Replaces the current pending block.
+    pub fn set(&self, block: PendingBlock) {
+        *self.inner.write() = Some(Arc::new(block));
+    }
+
+    /// Removes the current pending block.
+    pub fn clear(&self) {
+        self.inner.write().take();
+    }
+}
+
+/// Builds the pending block from the best transactions of the pool.
+///
+/// The [PendingBlockBuilder::run] future periodically rebuilds the pending block and updates the
+/// [PendingBlockCache].
+#[must_use = "PendingBlockBuilder does nothing unless run"]
+pub struct PendingBlockBuilder<Client, Pool> {
+    /// The client used to access the head state.
+    client: Client,
+    /// The pool to take the transactions from.
+    pool: Pool,
+    /// The chain the pending block is built for.
+    chain_spec: ChainSpec,
+    /// Where the pending block is stored.
+    cache: PendingBlockCache,
+    /// The beneficiary of the pending block.
+    beneficiary: Address,
+    /// How often the pending block is rebuilt.
+    interval: Duration,
+    /// Bundles that are included in their target block.
+    bundles: BundleStore,
+}
+
+// === impl PendingBlockBuilder ===
+
+impl<Client, Pool> PendingBlockBuilder<Client, Pool> {
+    /// Creates a new builder that stores the pending block in the given cache.
+    pub fn new(
+        client: Client,
+        pool: Pool,
+        chain_spec: ChainSpec,
+        cache: PendingBlockCache,
+    ) -> Self {
+        Self {
+            client,
+            pool,
+            chain_spec,
+            cache,
+            beneficiary: Address::zero(),
+            interval: DEFAULT_PENDING_BLOCK_INTERVAL,
+            bundles: Default::default(),
+        }
+    }
+
+    /// Sets the beneficiary of the pending block.
+    pub fn with_beneficiary(mut self, beneficiary: Address) -> Self {
+        self.beneficiary = beneficiary;
+        self
+    }
+
+    /// Sets the interval at which the pending block is rebuilt.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the store of bundles that are included in the pending block.
+    pub fn with_bundles(mut self, bundles: BundleStore) -> Self {
+        self.bundles = bundles;
+        self
+    }
+}
+
+impl<Client, Pool> PendingBlockBuilder<Client, Pool>
+where
+    Client: BlockProvider + HeaderProvider + StateProviderFactory + 'static,
+    Pool: TransactionPool + 'static,
+    Pool::Transaction: IntoRecoveredTransaction,
+{
+    /// Rebuilds the pending block at the configured interval until the future is dropped.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match self.build_pending_block() {
+                Ok(block) => {
+                    trace!(target: "rpc::eth::pending", number = block.number(), txs = block.block.body.len(), "Built pending block");
+                    self.cache.set(block);
+                }
+                Err(err) => {
+                    debug!(target: "rpc::eth::pending", ?err, "Failed to build pending block");
+                    self.cache.clear();
+                }
+            }
+        }
+    }
+
+    /// Builds a new pending block on top of the current head.
+    ///
+    /// Bundles that target the pending block are included first, each bundle is skipped entirely
+    /// if any of its transactions fails. Then transactions are taken from
+    /// [TransactionPool::best_transactions] until the block is full. Transactions that are
+    /// rejected by the EVM are skipped, together with all transactions that depend on them.
+    /// Transactions that exceed the remaining gas of the block are only skipped.
+    pub fn build_pending_block(&self) -> Result<PendingBlock> {
+        let (mut header, total_difficulty) =
+            next_block_header(&self.client, &self.chain_spec, self.beneficiary)?;
+
+        let mut cumulative_gas_used = 0;
+        let mut body = Vec::new();
+        let mut senders = Vec::new();
+        let mut changesets = Vec::new();
+
+        // bundles are executed on top of the state changes of all previously included bundles
+        self.bundles.remove_expired(header.number);
+        let mut bundle_state = StateOverlay::default();
+        for bundle in self.bundles.bundles_for_block(header.number) {
+            let state = OverlayStateProvider::new(self.client.latest()?, &bundle_state);
+            match execute_bundle(
+                &self.chain_spec,
+                &header,
+                total_difficulty,
+                state,
+                &bundle,
+                cumulative_gas_used,
+            ) {
+                Ok(executed) => {
+                    cumulative_gas_used += executed.gas_used;
+                    for (tx, changeset) in bundle.transactions.iter().zip(executed.changesets) {
+                        bundle_state.apply_transaction_changeset(&changeset);
+                        senders.push(tx.signer());
+                        body.push(tx.clone().into_signed());
+                        changesets.push(changeset);
+                    }
+                }
+                Err(err) => {
+                    trace!(target: "rpc::eth::pending", hash = ?bundle.hash, ?err, "Skipping invalid bundle");
+                }
+            }
+        }
+
+        let mut db = SubState::new(State::new(OverlayStateProvider::new(
+            self.client.latest()?,
+            &bundle_state,
+        )));
+        let mut executor = Executor::new(&self.chain_spec, &mut db);
+        executor.init_block_env(&header, total_difficulty);
+        let num_bundle_transactions = changesets.len();
+
+        let mut best_transactions = self.pool.best_transactions();
+        while let Some(pool_tx) = best_transactions.next() {
+            // the transaction doesn't fit into the remaining gas of the block, but a smaller one
+            // might, so only this transaction is skipped
+            if cumulative_gas_used + pool_tx.gas_limit() > header.gas_limit {
+                continue
+            }
+
+            let tx = pool_tx.transaction.to_recovered_transaction();
+            let sender = tx.signer();
+            match executor.simulate_transaction(&tx, sender, cumulative_gas_used) {
+                Ok((changeset, gas_used)) => {
+                    cumulative_gas_used += gas_used;
+                    senders.push(sender);
+                    body.push(tx.into_signed());
+                    changesets.push(changeset);
+                }
+                Err(ExecutorError::TransactionRejected { hash, error_code }) => {
+                    trace!(target: "rpc::eth::pending", ?hash, error_code, "Skipping rejected transaction");
+                    best_transactions.mark_invalid(&pool_tx);
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let block_reward = executor.block_reward_changeset(&header, total_difficulty, &[])?;
+
+        let mut post_state = bundle_state.clone();
+        for changeset in changesets.iter().skip(num_bundle_transactions) {
+            post_state.apply_transaction_changeset(changeset);
+        }
+        for (address, changeset) in block_reward.unwrap_or_else(BTreeMap::new).iter() {
+            post_state.apply_account_changeset(*address, changeset);
+        }
+
+        let receipts =
+            changesets.into_iter().map(|changeset| changeset.receipt).collect::<Vec<_>>();
+
+        header.gas_used = cumulative_gas_used;
+        header.transactions_root = proofs::calculate_transaction_root(body.iter());
+        header.receipts_root = proofs::calculate_receipt_root(receipts.iter());
+        header.logs_bloom = receipts.iter().fold(Bloom::zero(), |bloom, r| bloom | r.bloom);
+
+        Ok(PendingBlock {
+            block: Block { header, body, ommers: Vec::new() },
+            senders,
+            receipts,
+            total_difficulty,
+            post_state,
+        })
+    }
+}
+
+/// Returns the header of the next block on top of the current head, without any transactions, and
+/// its total difficulty.
+pub(crate) fn next_block_header<Client>(
+    client: &Client,
+    chain_spec: &ChainSpec,
+    beneficiary: Address,
+) -> Result<(Header, U256)>
+where
+    Client: BlockProvider + HeaderProvider,
+{
+    let parent_hash = client.chain_info()?.best_hash;
+    let parent =
+        client.header(&parent_hash)?.ok_or(ProviderError::BlockHash { block_hash: parent_hash })?;
+    let parent_td = client.header_td(&parent_hash)?.unwrap_or_default();
+
+    let is_merged = chain_spec.fork(Hardfork::Paris).active_at_ttd(parent_td);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default()
+        .max(parent.timestamp + 1);
+
+    let header = Header {
+        parent_hash,
+        ommers_hash: EMPTY_OMMER_ROOT,
+        beneficiary,
+        difficulty: if is_merged { U256::ZERO } else { parent.difficulty },
+        number: parent.number + 1,
+        gas_limit: parent.gas_limit,
+        timestamp,
+        mix_hash: parent.mix_hash,
+        base_fee_per_gas: parent.base_fee_per_gas.map(|base_fee| {
+            calculate_next_block_base_fee(parent.gas_used, parent.gas_limit, base_fee)
+        }),
+        ..Default::default()
+    };
+    let total_difficulty = parent_td + header.difficulty;
+    Ok((header, total_difficulty))
+}
+
+impl<Client, Pool> std::fmt::Debug for PendingBlockBuilder<Client, Pool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingBlockBuilder")
+            .field("beneficiary", &self.beneficiary)
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
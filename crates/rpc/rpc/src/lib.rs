@@ -23,7 +23,10 @@ mod web3;
 pub use admin::AdminApi;
 pub use debug::DebugApi;
 pub use engine::EngineApi;
-pub use eth::{EthApi, EthApiSpec, EthPubSub};
+pub use eth::{
+    Bundle, BundleStore, EthApi, EthApiSpec, EthBundle, EthPubSub, PendingBlock,
+    PendingBlockBuilder, PendingBlockCache, DEFAULT_PENDING_BLOCK_INTERVAL,
+};
 pub use layers::{AuthLayer, AuthValidator, JwtAuthValidator, JwtError, JwtSecret};
 pub use net::NetApi;
 pub use trace::TraceApi;
@@ -103,6 +103,7 @@ macro_rules! impl_to_rpc_result {
 
 impl_to_rpc_result!(reth_interfaces::Error);
 impl_to_rpc_result!(reth_network_api::NetworkError);
+impl_to_rpc_result!(reth_transaction_pool::error::BanListError);
 
 /// Constructs an internal JSON-RPC error.
 pub(crate) fn internal_rpc_err(msg: impl Into<String>) -> jsonrpsee::core::Error {
@@ -7,7 +7,7 @@ use reth_network::{
     config::{mainnet_nodes, rng_secret_key},
     NetworkConfig, NetworkConfigBuilder, PeersConfig,
 };
-use reth_primitives::{ChainSpec, NodeRecord};
+use reth_primitives::{ChainSpec, NodeRecord, PruneModes};
 use reth_provider::ShareableDatabase;
 use serde::{Deserialize, Serialize};
 
@@ -20,6 +20,8 @@ pub struct Config {
     pub stages: StageConfig,
     /// Configuration for the discovery service.
     pub peers: PeersConfig,
+    /// Configuration for pruning.
+    pub prune: PruneConfig,
 }
 
 impl Config {
@@ -152,9 +154,28 @@ impl Default for ExecutionConfig {
     }
 }
 
+/// Pruning configuration.
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct PruneConfig {
+    /// The minimum number of blocks between two runs of the pruner.
+    pub block_interval: u64,
+    /// The maximum number of blocks pruned per part in a single transaction.
+    pub batch_size: u64,
+    /// The pruning mode of each part, parts without a mode are never pruned.
+    pub parts: PruneModes,
+}
+
+impl Default for PruneConfig {
+    fn default() -> Self {
+        Self { block_interval: 5, batch_size: 10_000, parts: PruneModes::default() }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Config;
+    use reth_primitives::{PruneMode, PruneModes};
 
     const EXTENSION: &str = "toml";
 
@@ -185,4 +206,40 @@ mod tests {
             assert_eq!(config, loaded_config);
         })
     }
+
+    #[test]
+    fn test_load_prune_config() {
+        with_tempdir("config-prune-test", |config_path| {
+            std::fs::write(
+                config_path,
+                r#"
+[prune]
+block_interval = 10
+
+[prune.parts]
+receipts = "full"
+account_history = { distance = 1000 }
+transaction_lookup = { before = 15000000 }
+"#,
+            )
+            .unwrap();
+
+            let config: Config = confy::load_path(config_path).unwrap();
+            assert_eq!(config.prune.block_interval, 10);
+            assert_eq!(
+                config.prune.parts,
+                PruneModes {
+                    receipts: Some(PruneMode::Full),
+                    account_history: Some(PruneMode::Distance(1000)),
+                    storage_history: None,
+                    transaction_lookup: Some(PruneMode::Before(15_000_000)),
+                }
+            );
+
+            // a config with pruning can be stored again
+            confy::store_path(config_path, &config).unwrap();
+            let loaded_config: Config = confy::load_path(config_path).unwrap();
+            assert_eq!(config, loaded_config);
+        })
+    }
 }
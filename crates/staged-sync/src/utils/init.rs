@@ -4,6 +4,7 @@ use reth_db::{
     mdbx::{Env, WriteMap},
     tables,
     transaction::{DbTx, DbTxMut},
+    version::{check_database_version, init_database_version},
 };
 use reth_primitives::{Account, ChainSpec, H256};
 use std::{path::Path, sync::Arc};
@@ -16,7 +17,10 @@ pub fn init_db<P: AsRef<Path>>(path: P) -> eyre::Result<Env<WriteMap>> {
         path.as_ref(),
         reth_db::mdbx::EnvKind::RW,
     )?;
+    // the tables of a database with another schema version are left untouched
+    check_database_version(&db)?;
     db.create_tables()?;
+    init_database_version(&db)?;
 
     Ok(db)
 }
@@ -65,8 +69,14 @@ pub fn init_genesis<DB: Database>(db: Arc<DB>, chain: ChainSpec) -> Result<H256,
 #[cfg(test)]
 mod tests {
 
-    use super::init_genesis;
-    use reth_db::mdbx::test_utils::create_test_rw_db;
+    use super::{init_db, init_genesis};
+    use reth_db::{
+        database::Database,
+        mdbx::test_utils::create_test_rw_db,
+        tables,
+        transaction::DbTxMut,
+        version::{database_version, DatabaseVersionError, DB_VERSION},
+    };
     use reth_primitives::{
         GOERLI, GOERLI_GENESIS, MAINNET, MAINNET_GENESIS, SEPOLIA, SEPOLIA_GENESIS,
     };
@@ -97,4 +107,23 @@ mod tests {
         // actual, expected
         assert_eq!(genesis_hash, SEPOLIA_GENESIS);
     }
+
+    #[test]
+    fn init_db_checks_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = init_db(dir.path()).unwrap();
+        assert_eq!(database_version(&db.tx().unwrap()).unwrap(), Some(DB_VERSION));
+
+        db.update(|tx| {
+            tx.put::<tables::Config>(b"DatabaseVersion".to_vec(), u64::MAX.to_be_bytes().to_vec())
+        })
+        .unwrap()
+        .unwrap();
+        drop(db);
+        let err = init_db(dir.path()).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<DatabaseVersionError>(),
+            Some(&DatabaseVersionError::TooNew { found: u64::MAX, supported: DB_VERSION })
+        );
+    }
 }
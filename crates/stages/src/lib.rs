@@ -53,8 +53,8 @@ mod db;
 mod error;
 mod id;
 mod pipeline;
+mod pruner;
 mod stage;
-mod trie;
 mod util;
 
 #[allow(missing_docs)]
@@ -73,6 +73,7 @@ pub use db::Transaction;
 pub use error::*;
 pub use id::*;
 pub use pipeline::*;
+pub use pruner::{prune_checkpoint, Pruner};
 pub use stage::*;
 
 // NOTE: Needed so the link in the module-level rustdoc works.
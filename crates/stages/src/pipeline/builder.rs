@@ -1,4 +1,4 @@
-use crate::{pipeline::QueuedStage, Pipeline, Stage, StageSet};
+use crate::{pipeline::QueuedStage, Pipeline, Pruner, Stage, StageSet};
 use reth_db::database::Database;
 use reth_interfaces::sync::{NoopSyncStateUpdate, SyncStateUpdater};
 use reth_primitives::BlockNumber;
@@ -62,6 +62,12 @@ where
         self
     }
 
+    /// Set a [Pruner] that runs after each pass of the pipeline.
+    pub fn with_pruner(mut self, pruner: Pruner) -> Self {
+        self.pipeline.pruner = Some(pruner);
+        self
+    }
+
     /// Builds the final [`Pipeline`].
     pub fn build(self) -> Pipeline<DB, U> {
         self.pipeline
@@ -1,5 +1,6 @@
 use crate::{
-    db::Transaction, error::*, ExecInput, ExecOutput, Stage, StageError, StageId, UnwindInput,
+    db::Transaction, error::*, ExecInput, ExecOutput, Pruner, Stage, StageError, StageId,
+    UnwindInput,
 };
 use reth_db::database::Database;
 use reth_interfaces::sync::{SyncState, SyncStateUpdater};
@@ -74,11 +75,17 @@ use state::*;
 /// In case of a validation error (as determined by the consensus engine) in one of the stages, the
 /// pipeline will unwind the stages in reverse order of execution. It is also possible to
 /// request an unwind manually (see [Pipeline::unwind]).
+///
+/// # Pruning
+///
+/// If a [Pruner] is configured, it runs after each pass of the pipeline, based on the lowest
+/// progress of all stages.
 pub struct Pipeline<DB: Database, U: SyncStateUpdater> {
     stages: Vec<QueuedStage<DB>>,
     max_block: Option<BlockNumber>,
     listeners: PipelineEventListeners,
     sync_state_updater: Option<U>,
+    pruner: Option<Pruner>,
 }
 
 impl<DB: Database, U: SyncStateUpdater> Default for Pipeline<DB, U> {
@@ -88,6 +95,7 @@ impl<DB: Database, U: SyncStateUpdater> Default for Pipeline<DB, U> {
             max_block: None,
             listeners: PipelineEventListeners::default(),
             sync_state_updater: None,
+            pruner: None,
         }
     }
 }
@@ -95,10 +103,7 @@ impl<DB: Database, U: SyncStateUpdater> Default for Pipeline<DB, U> {
 impl<DB: Database, U: SyncStateUpdater> Debug for Pipeline<DB, U> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Pipeline")
-            .field(
-                "stages",
-                &self.stages.iter().map(|stage| stage.stage.id()).collect::<Vec<StageId>>(),
-            )
+            .field("stages", &self.stage_ids())
             .field("max_block", &self.max_block)
             .finish()
     }
@@ -115,6 +120,11 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
         self.listeners.new_listener()
     }
 
+    /// Returns the ids of the stages in the order of execution.
+    pub fn stage_ids(&self) -> Vec<StageId> {
+        self.stages.iter().map(|stage| stage.stage.id()).collect()
+    }
+
     /// Run the pipeline in an infinite loop. Will terminate early if the user has specified
     /// a `max_block` in the pipeline.
     pub async fn run(&mut self, db: Arc<DB>) -> Result<(), PipelineError> {
@@ -126,6 +136,10 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
             };
             let next_action = self.run_loop(&mut state, db.as_ref()).await?;
 
+            if next_action.should_continue() {
+                self.prune(db.as_ref())?;
+            }
+
             // Terminate the loop early if it's reached the maximum user
             // configured block.
             if next_action.should_continue() &&
@@ -191,6 +205,31 @@ impl<DB: Database, U: SyncStateUpdater> Pipeline<DB, U> {
         Ok(pipeline_progress.next_ctrl())
     }
 
+    /// Runs the pruner, if it is configured and the chain advanced far enough since its last run.
+    fn prune(&mut self, db: &DB) -> Result<(), PipelineError> {
+        let pruner = match self.pruner.as_mut() {
+            Some(pruner) => pruner,
+            None => return Ok(()),
+        };
+
+        // data is only pruned below the progress of the slowest stage
+        let tip = db
+            .view(|tx| {
+                self.stages
+                    .iter()
+                    .map(|stage| stage.stage.id().get_progress(tx).map(Option::unwrap_or_default))
+                    .collect::<Result<Vec<_>, _>>()
+            })??
+            .into_iter()
+            .min();
+
+        if let Some(tip) = tip.filter(|tip| pruner.is_pruning_needed(*tip)) {
+            let done = pruner.run(db, tip)?;
+            debug!(target: "sync::pipeline", tip, done, "Pruner finished");
+        }
+        Ok(())
+    }
+
     /// Unwind the stages to the target block.
     ///
     /// If the unwind is due to a bad block the number of that block should be specified.
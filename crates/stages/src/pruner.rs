@@ -0,0 +1,452 @@
+//! Pruning of the history the node is not configured to keep.
+
+use crate::{db::Transaction, DatabaseIntegrityError, PipelineError, StageError};
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    models::{storage_sharded_key::StorageShardedKey, ShardedKey, TransitionIdAddress},
+    table::Table,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    Error as DbError, TransitionList,
+};
+use reth_primitives::{Address, BlockNumber, PruneModes, PrunePart, TransitionId, TxNumber, H256};
+use reth_provider::static_files::StaticFileProvider;
+use std::{
+    collections::BTreeSet,
+    ops::{Range, RangeInclusive},
+    sync::Arc,
+};
+use tracing::*;
+
+/// Prunes the parts of the database configured in [PruneModes].
+///
+/// The pruner is run by the [Pipeline][crate::Pipeline] after it committed its progress. Every run
+/// prunes at most `batch_size` blocks of each part in a single transaction, so a large backlog,
+/// e.g. after enabling pruning on an existing node, is worked through over multiple runs.
+///
+/// The highest pruned block of each part is stored in [tables::PruneCheckpoints], the providers
+/// return [Pruned][reth_interfaces::provider::Error::Pruned] errors for data below it.
+///
+/// The transactions of blocks that were moved to static files are read from the
+/// [StaticFileProvider] set with [Pruner::with_static_files].
+#[derive(Debug)]
+pub struct Pruner {
+    modes: PruneModes,
+    /// The minimum number of blocks between two runs.
+    block_interval: u64,
+    /// The maximum number of blocks pruned per part in a single run.
+    batch_size: u64,
+    /// The tip of the last run that pruned everything up to its targets.
+    last_finished_tip: Option<BlockNumber>,
+    /// The static files that blocks are moved to.
+    static_files: Option<Arc<StaticFileProvider>>,
+}
+
+impl Pruner {
+    /// Creates a new pruner.
+    pub fn new(modes: PruneModes, block_interval: u64, batch_size: u64) -> Self {
+        Self {
+            modes,
+            block_interval,
+            batch_size: batch_size.max(1),
+            last_finished_tip: None,
+            static_files: None,
+        }
+    }
+
+    /// Sets the static files that blocks are moved to.
+    pub fn with_static_files(mut self, static_files: Arc<StaticFileProvider>) -> Self {
+        self.static_files = Some(static_files);
+        self
+    }
+
+    /// Returns true if the pruner should run for the chain tip.
+    ///
+    /// This is the case if the last run did not reach its targets or the tip advanced by at least
+    /// `block_interval` blocks since.
+    pub fn is_pruning_needed(&self, tip: BlockNumber) -> bool {
+        !self.modes.is_empty() &&
+            self.last_finished_tip
+                .map_or(true, |last| tip >= last.saturating_add(self.block_interval))
+    }
+
+    /// Prunes the next batch of each part, based on the chain tip.
+    ///
+    /// Returns true if all parts are pruned up to their targets.
+    pub fn run<DB: Database>(&mut self, db: &DB, tip: BlockNumber) -> Result<bool, PipelineError> {
+        let mut done = true;
+        for part in PrunePart::ALL {
+            let target = match self.modes.prune_target_block(part, tip) {
+                Some(target) => target,
+                None => continue,
+            };
+
+            let mut tx = Transaction::new(db)?;
+            let from = match prune_checkpoint(&tx, part)? {
+                Some(pruned) if pruned >= target => continue,
+                Some(pruned) => pruned + 1,
+                None => 0,
+            };
+            let to = target.min(from.saturating_add(self.batch_size - 1));
+
+            debug!(target: "sync::pruner", %part, from, to, target, "Pruning");
+            let blocks = from..=to;
+            match part {
+                PrunePart::Receipts => prune_receipts(&tx, blocks)?,
+                PrunePart::AccountHistory => prune_account_history(&tx, blocks)?,
+                PrunePart::StorageHistory => prune_storage_history(&tx, blocks)?,
+                PrunePart::TransactionLookup => {
+                    prune_transaction_lookup(&tx, self.static_files.as_deref(), blocks)?
+                }
+            }
+            tx.put::<tables::PruneCheckpoints>(part.as_str().as_bytes().to_vec(), to)?;
+            tx.commit()?;
+
+            done &= to == target;
+        }
+
+        if done {
+            self.last_finished_tip = Some(tip);
+        }
+        Ok(done)
+    }
+}
+
+/// Returns the highest pruned block of the part.
+pub fn prune_checkpoint<'db>(
+    tx: &impl DbTx<'db>,
+    part: PrunePart,
+) -> Result<Option<BlockNumber>, DbError> {
+    tx.get::<tables::PruneCheckpoints>(part.as_str().as_bytes().to_vec())
+}
+
+/// Returns the transaction ids of the blocks.
+fn tx_id_range<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    blocks: &RangeInclusive<BlockNumber>,
+) -> Result<Range<TxNumber>, StageError> {
+    let start = tx.get_block_body_by_num(*blocks.start())?.start_tx_id;
+    let end = tx.get_block_body_by_num(*blocks.end())?;
+    Ok(start..end.start_tx_id + end.tx_count)
+}
+
+/// Returns the state transitions of the blocks.
+fn transition_range<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    blocks: &RangeInclusive<BlockNumber>,
+) -> Result<Range<TransitionId>, StageError> {
+    let start = match blocks.start().checked_sub(1) {
+        Some(parent) => tx.get_block_transition(parent)?,
+        None => 0,
+    };
+    Ok(start..tx.get_block_transition(*blocks.end())?)
+}
+
+/// Deletes all entries of the table in the key range.
+fn prune_table<DB: Database, T: Table>(
+    tx: &Transaction<'_, DB>,
+    range: Range<T::Key>,
+) -> Result<(), DbError> {
+    let mut cursor = tx.cursor_write::<T>()?;
+    let mut walker = cursor.walk_range(range)?;
+    while let Some((key, _)) = walker.next().transpose()? {
+        tx.delete::<T>(key, None)?;
+    }
+    Ok(())
+}
+
+/// Prunes [tables::Receipts] and [tables::Logs] of the blocks.
+fn prune_receipts<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> Result<(), StageError> {
+    let tx_ids = tx_id_range(tx, &blocks)?;
+    prune_table::<_, tables::Receipts>(tx, tx_ids.clone())?;
+    prune_table::<_, tables::Logs>(tx, tx_ids)?;
+    Ok(())
+}
+
+/// Prunes [tables::TxHashNumber] of the blocks.
+///
+/// Transactions that are not in [tables::Transactions] anymore are read from the static files.
+fn prune_transaction_lookup<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    static_files: Option<&StaticFileProvider>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> Result<(), StageError> {
+    for id in tx_id_range(tx, &blocks)? {
+        let transaction = match tx.get::<tables::Transactions>(id)? {
+            Some(transaction) => Some(transaction),
+            None => match static_files {
+                Some(static_files) => static_files
+                    .transaction(id)
+                    .map_err(|error| StageError::Fatal(Box::new(error)))?,
+                None => None,
+            },
+        };
+        let transaction = transaction.ok_or(DatabaseIntegrityError::Transaction { id })?;
+        tx.delete::<tables::TxHashNumber>(transaction.hash, None)?;
+    }
+    Ok(())
+}
+
+/// Prunes [tables::AccountChangeSet] of the blocks and removes their transitions from
+/// [tables::AccountHistory].
+fn prune_account_history<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> Result<(), StageError> {
+    let transitions = transition_range(tx, &blocks)?;
+    let addresses = tx
+        .cursor_read::<tables::AccountChangeSet>()?
+        .walk_range(transitions.clone())?
+        .map(|entry| entry.map(|(_, account_before)| account_before.address))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+    prune_table::<_, tables::AccountChangeSet>(tx, transitions.clone())?;
+
+    let mut cursor = tx.cursor_write::<tables::AccountHistory>()?;
+    for address in addresses {
+        let first_shard = ShardedKey::new(address, 0);
+        let mut item = cursor.seek(first_shard.clone())?;
+        while let Some((key, list)) = item {
+            if key.key != address {
+                break
+            }
+            if key.highest_transition_id < transitions.end {
+                // all transitions of the shard are pruned
+                cursor.delete_current()?;
+                item = cursor.seek(first_shard.clone())?;
+                continue
+            }
+            if let Some(list) = retain_transitions(&list, transitions.end) {
+                cursor.upsert(key, list)?;
+            } else {
+                cursor.delete_current()?;
+            }
+            break
+        }
+    }
+    Ok(())
+}
+
+/// Prunes [tables::StorageChangeSet] of the blocks and removes their transitions from
+/// [tables::StorageHistory].
+fn prune_storage_history<DB: Database>(
+    tx: &Transaction<'_, DB>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> Result<(), StageError> {
+    let transitions = transition_range(tx, &blocks)?;
+    let keys: Range<TransitionIdAddress> =
+        (transitions.start, Address::zero()).into()..(transitions.end, Address::zero()).into();
+    let slots = tx
+        .cursor_read::<tables::StorageChangeSet>()?
+        .walk_range(keys.clone())?
+        .map(|entry| entry.map(|(key, storage_entry)| (key.address(), storage_entry.key)))
+        .collect::<Result<BTreeSet<(Address, H256)>, _>>()?;
+    prune_table::<_, tables::StorageChangeSet>(tx, keys)?;
+
+    let mut cursor = tx.cursor_write::<tables::StorageHistory>()?;
+    for (address, storage_key) in slots {
+        let first_shard = StorageShardedKey::new(address, storage_key, 0);
+        let mut item = cursor.seek(first_shard.clone())?;
+        while let Some((key, list)) = item {
+            if key.address != address || key.sharded_key.key != storage_key {
+                break
+            }
+            if key.sharded_key.highest_transition_id < transitions.end {
+                // all transitions of the shard are pruned
+                cursor.delete_current()?;
+                item = cursor.seek(first_shard.clone())?;
+                continue
+            }
+            if let Some(list) = retain_transitions(&list, transitions.end) {
+                cursor.upsert(key, list)?;
+            } else {
+                cursor.delete_current()?;
+            }
+            break
+        }
+    }
+    Ok(())
+}
+
+/// Returns the transitions of the shard that are not below `first_kept`, or `None` if no
+/// transition is left.
+fn retain_transitions(list: &TransitionList, first_kept: TransitionId) -> Option<TransitionList> {
+    let kept = list.iter(0).filter(|id| *id as u64 >= first_kept).collect::<Vec<usize>>();
+    (!kept.is_empty()).then(|| TransitionList::new(kept).expect("transitions are sorted"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestTransaction;
+    use assert_matches::assert_matches;
+    use reth_db::models::AccountBeforeTx;
+    use reth_interfaces::test_utils::generators::random_block_range;
+    use reth_primitives::{PruneMode, Receipt, U256};
+    use reth_provider::static_files::StaticFileProducer;
+
+    /// Inserts ten blocks with one transaction each, so the transaction id and the transition of
+    /// every transaction is its block number.
+    fn insert_blocks(test_tx: &TestTransaction) -> Vec<reth_primitives::SealedBlock> {
+        let blocks = random_block_range(0..10, H256::zero(), 1..2);
+        test_tx.insert_blocks(blocks.iter(), None).unwrap();
+        test_tx
+            .commit(|tx| {
+                for block in blocks.iter() {
+                    tx.put::<tables::BlockTransitionIndex>(block.number, block.number + 1)?;
+                    tx.put::<tables::Receipts>(block.number, Receipt::default())?;
+                    tx.put::<tables::TxHashNumber>(block.body[0].hash, block.number)?;
+                }
+                Ok(())
+            })
+            .unwrap();
+        blocks
+    }
+
+    #[test]
+    fn prune_in_batches() {
+        let test_tx = TestTransaction::default();
+        let blocks = insert_blocks(&test_tx);
+
+        let modes = PruneModes {
+            receipts: Some(PruneMode::Distance(2)),
+            transaction_lookup: Some(PruneMode::Before(3)),
+            // the state history of the most recent blocks is always kept
+            account_history: Some(PruneMode::Full),
+            ..Default::default()
+        };
+        let mut pruner = Pruner::new(modes, 5, 4);
+        assert!(pruner.is_pruning_needed(9));
+
+        // only the receipts of blocks 0..=3 are pruned in the first run
+        assert!(!pruner.run(test_tx.tx.as_ref(), 9).unwrap());
+        assert!(pruner.is_pruning_needed(9));
+        assert_eq!(test_tx.query(|tx| prune_checkpoint(tx, PrunePart::Receipts)).unwrap(), Some(3));
+        assert_eq!(
+            test_tx.query(|tx| prune_checkpoint(tx, PrunePart::TransactionLookup)).unwrap(),
+            Some(2)
+        );
+
+        assert!(pruner.run(test_tx.tx.as_ref(), 9).unwrap());
+        assert!(!pruner.is_pruning_needed(10));
+        assert!(pruner.is_pruning_needed(14));
+        assert_eq!(test_tx.query(|tx| prune_checkpoint(tx, PrunePart::Receipts)).unwrap(), Some(7));
+        assert_eq!(
+            test_tx.query(|tx| prune_checkpoint(tx, PrunePart::AccountHistory)).unwrap(),
+            None
+        );
+
+        let receipts = test_tx.table::<tables::Receipts>().unwrap();
+        assert_eq!(receipts.into_iter().map(|(tx_id, _)| tx_id).collect::<Vec<_>>(), vec![8, 9]);
+        test_tx
+            .query(|tx| {
+                assert_eq!(tx.get::<tables::TxHashNumber>(blocks[2].body[0].hash)?, None);
+                assert_eq!(tx.get::<tables::TxHashNumber>(blocks[3].body[0].hash)?, Some(3));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn prune_transaction_lookup_of_moved_blocks() {
+        let test_tx = TestTransaction::default();
+        let blocks = insert_blocks(&test_tx);
+        test_tx
+            .commit(|tx| {
+                for block in blocks.iter() {
+                    tx.put::<tables::HeaderTD>(block.num_hash().into(), U256::ZERO.into())?;
+                }
+                tx.put::<tables::SyncStage>(b"Execution".to_vec(), 5)
+            })
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = Arc::new(StaticFileProvider::new(dir.path()).unwrap());
+        let producer =
+            StaticFileProducer::new(test_tx.inner_raw(), static_files.clone(), ["Execution"]);
+        assert_eq!(producer.run(9).unwrap(), Some(4));
+
+        let modes =
+            PruneModes { transaction_lookup: Some(PruneMode::Before(8)), ..Default::default() };
+        // the transactions of the moved blocks are not in the database anymore
+        assert_matches!(
+            Pruner::new(modes.clone(), 5, 10).run(test_tx.tx.as_ref(), 9),
+            Err(PipelineError::Stage(StageError::DatabaseIntegrity(
+                DatabaseIntegrityError::Transaction { id: 0 }
+            )))
+        );
+
+        let mut pruner = Pruner::new(modes, 5, 10).with_static_files(static_files);
+        assert!(pruner.run(test_tx.tx.as_ref(), 9).unwrap());
+        test_tx
+            .query(|tx| {
+                for block in &blocks[..8] {
+                    assert_eq!(tx.get::<tables::TxHashNumber>(block.body[0].hash)?, None);
+                }
+                assert_eq!(tx.get::<tables::TxHashNumber>(blocks[8].body[0].hash)?, Some(8));
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn prune_history() {
+        let test_tx = TestTransaction::default();
+        insert_blocks(&test_tx);
+        let address = Address::random();
+        let storage_key = H256::random();
+        test_tx
+            .commit(|tx| {
+                for shard in [ShardedKey::new(address, 2), ShardedKey::new(address, u64::MAX)] {
+                    let list =
+                        if shard.highest_transition_id == 2 { vec![1, 2] } else { vec![5, 8] };
+                    tx.put::<tables::AccountHistory>(shard, TransitionList::new(list).unwrap())?;
+                }
+                tx.put::<tables::StorageHistory>(
+                    StorageShardedKey::new(address, storage_key, u64::MAX),
+                    TransitionList::new(vec![2, 5]).unwrap(),
+                )?;
+                for transition in [1, 2, 5, 8] {
+                    tx.put::<tables::AccountChangeSet>(
+                        transition,
+                        AccountBeforeTx { address, info: None },
+                    )?;
+                }
+                for transition in [2, 5] {
+                    tx.put::<tables::StorageChangeSet>(
+                        (transition, address).into(),
+                        reth_primitives::StorageEntry { key: storage_key, ..Default::default() },
+                    )?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        let mut tx = test_tx.inner();
+        prune_account_history(&tx, 0..=5).unwrap();
+        prune_storage_history(&tx, 0..=5).unwrap();
+        tx.commit().unwrap();
+
+        let account_history = test_tx.table::<tables::AccountHistory>().unwrap();
+        assert_eq!(account_history.len(), 1);
+        assert_eq!(account_history[0].0, ShardedKey::new(address, u64::MAX));
+        assert_eq!(account_history[0].1.iter(0).collect::<Vec<_>>(), vec![8]);
+        let account_changesets = test_tx.table::<tables::AccountChangeSet>().unwrap();
+        assert_eq!(account_changesets.into_iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![8]);
+
+        assert!(test_tx.table_is_empty::<tables::StorageHistory>().unwrap());
+        assert!(test_tx.table_is_empty::<tables::StorageChangeSet>().unwrap());
+    }
+
+    #[test]
+    fn retain_shard_transitions() {
+        let list = TransitionList::new(vec![1, 4, 7]).unwrap();
+        assert_eq!(
+            retain_transitions(&list, 4).map(|list| list.iter(0).collect::<Vec<_>>()),
+            Some(vec![4, 7])
+        );
+        assert!(retain_transitions(&list, 8).is_none());
+    }
+}
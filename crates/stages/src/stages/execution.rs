@@ -5,18 +5,15 @@ use crate::{
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
     database::Database,
-    models::{BlockNumHash, StoredBlockBody, TransitionIdAddress},
+    models::{BlockNumHash, StoredBlockBody},
     tables,
     transaction::{DbTx, DbTxMut},
 };
-use reth_executor::{
-    execution_result::AccountChangeSet,
-    revm_wrap::{State, SubState},
+use reth_executor::revm_wrap::{State, SubState};
+use reth_primitives::{Address, Block, ChainSpec, Hardfork, Header, MAINNET, U256};
+use reth_provider::{
+    write_execution_result, CachedStateProvider, LatestStateProviderRef, StateCache,
 };
-use reth_primitives::{
-    Address, Block, ChainSpec, Hardfork, Header, StorageEntry, H256, MAINNET, U256,
-};
-use reth_provider::LatestStateProviderRef;
 use std::fmt::Debug;
 use tracing::*;
 
@@ -57,18 +54,30 @@ pub struct ExecutionStage {
     pub chain_spec: ChainSpec,
     /// Commit threshold
     pub commit_threshold: u64,
+    /// Cache of the bytecodes read during execution.
+    pub state_cache: StateCache,
 }
 
 impl Default for ExecutionStage {
     fn default() -> Self {
-        Self { chain_spec: MAINNET.clone(), commit_threshold: 1000 }
+        Self {
+            chain_spec: MAINNET.clone(),
+            commit_threshold: 1000,
+            state_cache: StateCache::disabled(),
+        }
     }
 }
 
 impl ExecutionStage {
     /// Create new execution stage with specified config.
     pub fn new(chain_spec: ChainSpec, commit_threshold: u64) -> Self {
-        Self { chain_spec, commit_threshold }
+        Self { chain_spec, commit_threshold, state_cache: StateCache::disabled() }
+    }
+
+    /// Shares the bytecodes read during execution through the given cache.
+    pub fn with_state_cache(mut self, state_cache: StateCache) -> Self {
+        self.state_cache = state_cache;
+        self
     }
 }
 
@@ -135,7 +144,11 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
             .collect::<Result<Vec<_>, _>>()?;
 
         // Create state provider with cached state
-        let mut state_provider = SubState::new(State::new(LatestStateProviderRef::new(&**tx)));
+        // only bytecodes are shared, the state of the transaction is not committed yet
+        let mut state_provider = SubState::new(State::new(CachedStateProvider::bytecodes_only(
+            LatestStateProviderRef::new(&**tx),
+            self.state_cache.clone(),
+        )));
 
         // Fetch transactions, execute them and generate results
         let mut block_change_patches = Vec::with_capacity(canonical_batch.len());
@@ -208,105 +221,15 @@ impl<DB: Database> Stage<DB> for ExecutionStage {
         for (results, block_number) in block_change_patches.into_iter() {
             let spurious_dragon_active =
                 self.chain_spec.fork(Hardfork::SpuriousDragon).active_at_block(block_number);
-            // insert state change set
-            for result in results.changesets.into_iter() {
-                for (address, account_change_set) in result.changeset.into_iter() {
-                    let AccountChangeSet { account, wipe_storage, storage } = account_change_set;
-                    // apply account change to db. Updates AccountChangeSet and PlainAccountState
-                    // tables.
-                    trace!(target: "sync::stages::execution", ?address, current_transition_id, ?account, wipe_storage, "Applying account changeset");
-                    account.apply_to_db(
-                        &**tx,
-                        address,
-                        current_transition_id,
-                        spurious_dragon_active,
-                    )?;
-
-                    let storage_id = TransitionIdAddress((current_transition_id, address));
-
-                    // cast key to H256 and trace the change
-                    let storage = storage
-                        .into_iter()
-                        .map(|(key, (old_value,new_value))| {
-                            let hkey = H256(key.to_be_bytes());
-                            trace!(target: "sync::stages::execution", ?address, current_transition_id, ?hkey, ?old_value, ?new_value, "Applying storage changeset");
-                            (hkey, old_value,new_value)
-                        })
-                        .collect::<Vec<_>>();
-
-                    let mut cursor_storage_changeset =
-                        tx.cursor_write::<tables::StorageChangeSet>()?;
-                    cursor_storage_changeset.seek_exact(storage_id)?;
-
-                    if wipe_storage {
-                        // iterate over storage and save them before entry is deleted.
-                        tx.cursor_read::<tables::PlainStorageState>()?
-                            .walk(address)?
-                            .take_while(|res| {
-                                res.as_ref().map(|(k, _)| *k == address).unwrap_or_default()
-                            })
-                            .try_for_each(|entry| {
-                                let (_, old_value) = entry?;
-                                cursor_storage_changeset.append(storage_id, old_value)
-                            })?;
-
-                        // delete all entries
-                        tx.delete::<tables::PlainStorageState>(address, None)?;
-
-                        // insert storage changeset
-                        for (key, _, new_value) in storage {
-                            // old values are already cleared.
-                            if new_value != U256::ZERO {
-                                tx.put::<tables::PlainStorageState>(
-                                    address,
-                                    StorageEntry { key, value: new_value },
-                                )?;
-                            }
-                        }
-                    } else {
-                        // insert storage changeset
-                        for (key, old_value, new_value) in storage {
-                            let old_entry = StorageEntry { key, value: old_value };
-                            let new_entry = StorageEntry { key, value: new_value };
-                            // insert into StorageChangeSet
-                            cursor_storage_changeset.append(storage_id, old_entry)?;
-
-                            // Always delete old value as duplicate table, put will not override it
-                            tx.delete::<tables::PlainStorageState>(address, Some(old_entry))?;
-                            if new_value != U256::ZERO {
-                                tx.put::<tables::PlainStorageState>(address, new_entry)?;
-                            }
-                        }
-                    }
-                }
-                // insert bytecode
-                for (hash, bytecode) in result.new_bytecodes.into_iter() {
-                    // make different types of bytecode. Checked and maybe even analyzed (needs to
-                    // be packed). Currently save only raw bytes.
-                    let bytecode = bytecode.bytes();
-                    trace!(target: "sync::stages::execution", ?hash, ?bytecode, len = bytecode.len(), "Inserting bytecode");
-                    tx.put::<tables::Bytecodes>(hash, bytecode[..bytecode.len()].to_vec())?;
-
-                    // NOTE: bytecode bytes are not inserted in change set and can be found in
-                    // separate table
-                }
-                current_transition_id += 1;
-            }
-
-            // If there is block reward we will add account changeset to db
-            if let Some(block_reward_changeset) = results.block_reward {
-                // we are sure that block reward index is present.
-                for (address, changeset) in block_reward_changeset.into_iter() {
-                    trace!(target: "sync::stages::execution", ?address, current_transition_id, "Applying block reward");
-                    changeset.apply_to_db(
-                        &**tx,
-                        address,
-                        current_transition_id,
-                        spurious_dragon_active,
-                    )?;
-                }
-                current_transition_id += 1;
-            }
+            // receipts are not stored by the stage
+            current_transition_id = write_execution_result(
+                &**tx,
+                None,
+                current_transition_id,
+                results,
+                spurious_dragon_active,
+            )
+            .map_err(|e| StageError::Fatal(Box::new(e)))?;
         }
 
         let done = !capped;
@@ -411,7 +334,8 @@ mod tests {
         models::AccountBeforeTx,
     };
     use reth_primitives::{
-        hex_literal::hex, keccak256, Account, ChainSpecBuilder, SealedBlock, H160, U256,
+        hex_literal::hex, keccak256, Account, ChainSpecBuilder, SealedBlock, StorageEntry, H160,
+        H256, U256,
     };
     use reth_provider::insert_canonical_block;
     use reth_rlp::Decodable;
@@ -118,9 +118,14 @@ impl<DB: Database> Stage<DB> for TotalDifficultyStage {
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
     use reth_db::transaction::DbTx;
-    use reth_interfaces::test_utils::generators::{random_header, random_header_range};
-    use reth_primitives::{BlockNumber, SealedHeader, MAINNET};
+    use reth_interfaces::test_utils::generators::{
+        random_block_range, random_header, random_header_range,
+    };
+    use reth_primitives::{BlockNumber, SealedHeader, H256, MAINNET};
+    use reth_provider::static_files::{StaticFileProducer, StaticFileProvider};
+    use std::sync::Arc;
 
     use super::*;
     use crate::test_utils::{
@@ -170,6 +175,44 @@ mod tests {
         assert!(runner.validate_execution(first_input, result.ok()).is_ok(), "validation failed");
     }
 
+    #[tokio::test]
+    async fn execute_after_moving_blocks_to_static_files() {
+        let (stage_progress, previous_stage) = (5, 10);
+        let runner = TotalDifficultyTestRunner::default();
+
+        let blocks = random_block_range(0..previous_stage + 1, H256::zero(), 0..2);
+        runner.tx.insert_blocks(blocks.iter(), None).expect("failed to insert blocks");
+        runner
+            .tx
+            .commit(|tx| {
+                let mut td = U256::ZERO;
+                for block in blocks.iter().take(stage_progress as usize + 1) {
+                    td += block.difficulty;
+                    tx.put::<tables::HeaderTD>(block.num_hash().into(), td.into())?;
+                }
+                tx.put::<tables::SyncStage>(TOTAL_DIFFICULTY.0.as_bytes().to_vec(), stage_progress)
+            })
+            .expect("failed to insert total difficulty");
+
+        // move the blocks the stage has processed
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = Arc::new(StaticFileProvider::new(dir.path()).unwrap());
+        let producer =
+            StaticFileProducer::new(runner.tx.inner_raw(), static_files, [TOTAL_DIFFICULTY.0]);
+        assert_eq!(producer.run(previous_stage).unwrap(), Some(stage_progress - 1));
+
+        let input = ExecInput {
+            previous_stage: Some((PREV_STAGE_ID, previous_stage)),
+            stage_progress: Some(stage_progress),
+        };
+        let result = runner.execute(input).await.unwrap();
+        assert_matches!(
+            result,
+            Ok(ExecOutput { done: true, stage_progress }) if stage_progress == previous_stage
+        );
+        assert!(runner.validate_execution(input, result.ok()).is_ok(), "validation failed");
+    }
+
     struct TotalDifficultyTestRunner {
         tx: TestTransaction,
         commit_threshold: u64,
@@ -3,10 +3,15 @@
 use criterion::{
     black_box, criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, Criterion,
 };
-use reth_db::cursor::{DbDupCursorRO, DbDupCursorRW};
+use reth_db::{
+    cursor::{DbDupCursorRO, DbDupCursorRW},
+    tables::codecs::compression::{
+        train_dictionary, DEFAULT_DICTIONARY_SAMPLES, DEFAULT_DICTIONARY_SIZE,
+    },
+};
 use std::time::Instant;
 
-criterion_group!(benches, db, serialization);
+criterion_group!(benches, db, serialization, compression);
 criterion_main!(benches);
 
 pub fn db(c: &mut Criterion) {
@@ -47,6 +52,69 @@ pub fn serialization(c: &mut Criterion) {
     measure_table_serialization::<PlainAccountState>(&mut group);
 }
 
+pub fn compression(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tables_compression");
+    group.measurement_time(std::time::Duration::from_millis(200));
+    group.warm_up_time(std::time::Duration::from_millis(200));
+
+    measure_dictionary_compression::<BlockBodies>(&mut group, Tables::BlockBodies);
+    measure_dictionary_compression::<Transactions>(&mut group, Tables::Transactions);
+}
+
+/// Measures `Compress` and `Decompress` with a zstd dictionary trained on the test vectors, to
+/// compare with the uncompressed values of `tables_serialization`.
+fn measure_dictionary_compression<T>(group: &mut BenchmarkGroup<WallTime>, table: Tables)
+where
+    T: Table + Default,
+    T::Key: Default + Clone + for<'de> serde::Deserialize<'de>,
+    T::Value: Default + Clone + for<'de> serde::Deserialize<'de>,
+{
+    let input = &load_vectors::<T>();
+    let db = set_up_db::<T>(Path::new(BENCH_DB_PATH), input);
+    let dictionaries = db.dictionaries();
+    let dictionary = train_dictionary::<T, _>(
+        &db.tx().expect("tx"),
+        dictionaries,
+        DEFAULT_DICTIONARY_SAMPLES,
+        DEFAULT_DICTIONARY_SIZE,
+    )
+    .expect("dictionary");
+    dictionaries.register(table, &dictionary).expect("dictionary");
+
+    let compressed = &input
+        .iter()
+        .map(|(_, _, v, _)| {
+            bytes::Bytes::copy_from_slice(v.clone().compress_with(dictionaries).as_ref())
+        })
+        .collect::<Vec<_>>();
+
+    group.bench_function(format!("{}.ValueCompressZstd", T::NAME), move |b| {
+        b.iter_with_setup(
+            || input.clone(),
+            |input| {
+                black_box({
+                    for (_, _, v, _) in input {
+                        v.compress_with(dictionaries);
+                    }
+                });
+            },
+        )
+    });
+
+    group.bench_function(format!("{}.ValueDecompressZstd", T::NAME), |b| {
+        b.iter_with_setup(
+            || compressed.clone(),
+            |compressed| {
+                black_box({
+                    for v in compressed {
+                        let _ = <T as Table>::Value::decompress_with(v, dictionaries);
+                    }
+                });
+            },
+        )
+    });
+}
+
 /// Measures `Encode`, `Decode`, `Compress` and `Decompress`.
 fn measure_table_serialization<T>(group: &mut BenchmarkGroup<WallTime>)
 where
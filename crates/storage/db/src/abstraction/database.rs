@@ -1,5 +1,7 @@
 use crate::{
     common::{Bounds, Sealed},
+    table::Table,
+    tables::codecs::compression::Dictionaries,
     transaction::{DbTx, DbTxMut},
     Error,
 };
@@ -23,6 +25,12 @@ pub trait Database: for<'a> DatabaseGAT<'a> {
     /// Create read write transaction only possible if database is open with write access.
     fn tx_mut(&self) -> Result<<Self as DatabaseGAT<'_>>::TXMut, Error>;
 
+    /// Returns the compression dictionaries of the database.
+    fn dictionaries(&self) -> &Dictionaries;
+
+    /// Returns true if the table was created in the database.
+    fn table_exists<T: Table>(&self) -> Result<bool, Error>;
+
     /// Takes a function and passes a read-only transaction into it, making sure it's closed in the
     /// end of the execution.
     fn view<T, F>(&self, mut f: F) -> Result<T, Error>
@@ -4,8 +4,6 @@ pub mod common;
 pub mod cursor;
 /// Database traits.
 pub mod database;
-/// mock
-pub mod mock;
 /// Table traits
 pub mod table;
 /// Transaction database traits.
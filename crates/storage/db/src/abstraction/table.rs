@@ -1,4 +1,4 @@
-use crate::Error;
+use crate::{tables::codecs::compression::Dictionaries, Error};
 use bytes::Bytes;
 use serde::Serialize;
 use std::{
@@ -13,12 +13,29 @@ pub trait Compress: Send + Sync + Sized + Debug {
 
     /// Compresses data going into the database.
     fn compress(self) -> Self::Compressed;
+
+    /// Compresses data going into a database with its compression dictionaries.
+    ///
+    /// Only the values of [compressed tables](crate::tables::codecs::compression) use them.
+    fn compress_with(self, _dictionaries: &Dictionaries) -> Self::Compressed {
+        self.compress()
+    }
 }
 
 /// Trait that will transform the data to be read from the DB.
 pub trait Decompress: Send + Sync + Sized + Debug {
     /// Decompresses data coming from the database.
     fn decompress<B: Into<Bytes>>(value: B) -> Result<Self, Error>;
+
+    /// Decompresses data coming from a database with its compression dictionaries.
+    ///
+    /// Only the values of [compressed tables](crate::tables::codecs::compression) use them.
+    fn decompress_with<B: Into<Bytes>>(
+        value: B,
+        _dictionaries: &Dictionaries,
+    ) -> Result<Self, Error> {
+        Self::decompress(value)
+    }
 }
 
 /// Trait that will transform the data to be saved in the DB.
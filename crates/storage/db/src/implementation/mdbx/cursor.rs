@@ -8,7 +8,7 @@ use crate::{
         ReverseWalker, Walker,
     },
     table::{Compress, DupSort, Encode, Table},
-    tables::utils::*,
+    tables::{codecs::compression::Dictionaries, utils::*},
     Error,
 };
 use reth_libmdbx::{self, TransactionKind, WriteFlags, RO, RW};
@@ -32,6 +32,8 @@ pub struct Cursor<'tx, K: TransactionKind, T: Table> {
     pub inner: reth_libmdbx::Cursor<'tx, K>,
     /// Table name as is inside the database.
     pub table: &'static str,
+    /// The compression dictionaries of the database.
+    pub dictionaries: &'tx Dictionaries,
     /// Phantom data to enforce encoding/decoding.
     pub _dbi: std::marker::PhantomData<T>,
 }
@@ -39,38 +41,38 @@ pub struct Cursor<'tx, K: TransactionKind, T: Table> {
 /// Takes `(key, value)` from the database and decodes it appropriately.
 #[macro_export]
 macro_rules! decode {
-    ($v:expr) => {
-        $v.map_err(|e| Error::Read(e.into()))?.map(decoder::<T>).transpose()
+    ($v:expr, $d:expr) => {
+        $v.map_err(|e| Error::Read(e.into()))?.map(|kv| decoder::<T>($d, kv)).transpose()
     };
 }
 
 impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T> {
     fn first(&mut self) -> PairResult<T> {
-        decode!(self.inner.first())
+        decode!(self.inner.first(), self.dictionaries)
     }
 
     fn seek_exact(&mut self, key: <T as Table>::Key) -> PairResult<T> {
-        decode!(self.inner.set_key(key.encode().as_ref()))
+        decode!(self.inner.set_key(key.encode().as_ref()), self.dictionaries)
     }
 
     fn seek(&mut self, key: <T as Table>::Key) -> PairResult<T> {
-        decode!(self.inner.set_range(key.encode().as_ref()))
+        decode!(self.inner.set_range(key.encode().as_ref()), self.dictionaries)
     }
 
     fn next(&mut self) -> PairResult<T> {
-        decode!(self.inner.next())
+        decode!(self.inner.next(), self.dictionaries)
     }
 
     fn prev(&mut self) -> PairResult<T> {
-        decode!(self.inner.prev())
+        decode!(self.inner.prev(), self.dictionaries)
     }
 
     fn last(&mut self) -> PairResult<T> {
-        decode!(self.inner.last())
+        decode!(self.inner.last(), self.dictionaries)
     }
 
     fn current(&mut self) -> PairResult<T> {
-        decode!(self.inner.get_current())
+        decode!(self.inner.get_current(), self.dictionaries)
     }
 
     fn walk<'cursor>(
@@ -84,7 +86,7 @@ impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T>
             .inner
             .set_range(start_key.encode().as_ref())
             .map_err(|e| Error::Read(e.into()))?
-            .map(decoder::<T>);
+            .map(|kv| decoder::<T>(self.dictionaries, kv));
 
         Ok(Walker::new(self, start))
     }
@@ -100,7 +102,7 @@ impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T>
             .inner
             .set_range(range.start.encode().as_ref())
             .map_err(|e| Error::Read(e.into()))?
-            .map(decoder::<T>);
+            .map(|kv| decoder::<T>(self.dictionaries, kv));
 
         Ok(RangeWalker::new(self, start, range.end))
     }
@@ -117,7 +119,7 @@ impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T>
                 .inner
                 .set_range(start_key.encode().as_ref())
                 .map_err(|e| Error::Read(e.into()))?
-                .map(decoder::<T>);
+                .map(|kv| decoder::<T>(self.dictionaries, kv));
 
             return Ok(ReverseWalker::new(self, start))
         }
@@ -130,17 +132,21 @@ impl<'tx, K: TransactionKind, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, K, T>
 impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx, K, T> {
     /// Returns the next `(key, value)` pair of a DUPSORT table.
     fn next_dup(&mut self) -> PairResult<T> {
-        decode!(self.inner.next_dup())
+        decode!(self.inner.next_dup(), self.dictionaries)
     }
 
     /// Returns the next `(key, value)` pair skipping the duplicates.
     fn next_no_dup(&mut self) -> PairResult<T> {
-        decode!(self.inner.next_nodup())
+        decode!(self.inner.next_nodup(), self.dictionaries)
     }
 
     /// Returns the next `value` of a duplicate `key`.
     fn next_dup_val(&mut self) -> ValueOnlyResult<T> {
-        self.inner.next_dup().map_err(|e| Error::Read(e.into()))?.map(decode_value::<T>).transpose()
+        self.inner
+            .next_dup()
+            .map_err(|e| Error::Read(e.into()))?
+            .map(|kv| decode_value::<T>(self.dictionaries, kv))
+            .transpose()
     }
 
     fn seek_by_key_subkey(
@@ -151,7 +157,7 @@ impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx,
         self.inner
             .get_both_range(key.encode().as_ref(), subkey.encode().as_ref())
             .map_err(|e| Error::Read(e.into()))?
-            .map(decode_one::<T>)
+            .map(|value| decode_one::<T>(self.dictionaries, value))
             .transpose()
     }
 
@@ -168,7 +174,7 @@ impl<'tx, K: TransactionKind, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx,
             .inner
             .get_both_range(key.as_ref(), subkey.encode().as_ref())
             .map_err(|e| Error::Read(e.into()))?
-            .map(|val| decoder::<T>((Cow::Owned(key), val)));
+            .map(|val| decoder::<T>(self.dictionaries, (Cow::Owned(key), val)));
 
         Ok(DupWalker::<'cursor, 'tx, T, Self> { cursor: self, start, _tx_phantom: PhantomData {} })
     }
@@ -180,13 +186,21 @@ impl<'tx, T: Table> DbCursorRW<'tx, T> for Cursor<'tx, RW, T> {
     fn upsert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
         // Default `WriteFlags` is UPSERT
         self.inner
-            .put(key.encode().as_ref(), value.compress().as_ref(), WriteFlags::UPSERT)
+            .put(
+                key.encode().as_ref(),
+                value.compress_with(self.dictionaries).as_ref(),
+                WriteFlags::UPSERT,
+            )
             .map_err(|e| Error::Write(e.into()))
     }
 
     fn insert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
         self.inner
-            .put(key.encode().as_ref(), value.compress().as_ref(), WriteFlags::NO_OVERWRITE)
+            .put(
+                key.encode().as_ref(),
+                value.compress_with(self.dictionaries).as_ref(),
+                WriteFlags::NO_OVERWRITE,
+            )
             .map_err(|e| Error::Write(e.into()))
     }
 
@@ -194,7 +208,11 @@ impl<'tx, T: Table> DbCursorRW<'tx, T> for Cursor<'tx, RW, T> {
     /// will fail if the inserted key is less than the last table key
     fn append(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
         self.inner
-            .put(key.encode().as_ref(), value.compress().as_ref(), WriteFlags::APPEND)
+            .put(
+                key.encode().as_ref(),
+                value.compress_with(self.dictionaries).as_ref(),
+                WriteFlags::APPEND,
+            )
             .map_err(|e| Error::Write(e.into()))
     }
 
@@ -210,7 +228,11 @@ impl<'tx, T: DupSort> DbDupCursorRW<'tx, T> for Cursor<'tx, RW, T> {
 
     fn append_dup(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
         self.inner
-            .put(key.encode().as_ref(), value.compress().as_ref(), WriteFlags::APPEND_DUP)
+            .put(
+                key.encode().as_ref(),
+                value.compress_with(self.dictionaries).as_ref(),
+                WriteFlags::APPEND_DUP,
+            )
             .map_err(|e| Error::Write(e.into()))
     }
 }
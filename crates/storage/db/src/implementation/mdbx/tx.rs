@@ -3,7 +3,7 @@
 use super::cursor::Cursor;
 use crate::{
     table::{Compress, DupSort, Encode, Table},
-    tables::utils::decode_one,
+    tables::{codecs::compression::Dictionaries, utils::decode_one},
     transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
     Error,
 };
@@ -16,15 +16,18 @@ use std::{marker::PhantomData, time::Instant};
 pub struct Tx<'a, K: TransactionKind, E: EnvironmentKind> {
     /// Libmdbx-sys transaction.
     pub inner: Transaction<'a, K, E>,
+    /// The compression dictionaries of the database.
+    pub dictionaries: &'a Dictionaries,
 }
 
 impl<'env, K: TransactionKind, E: EnvironmentKind> Tx<'env, K, E> {
-    /// Creates new `Tx` object with a `RO` or `RW` transaction.
-    pub fn new<'a>(inner: Transaction<'a, K, E>) -> Self
+    /// Creates new `Tx` object with a `RO` or `RW` transaction on a database with the given
+    /// compression dictionaries.
+    pub fn new<'a>(inner: Transaction<'a, K, E>, dictionaries: &'a Dictionaries) -> Self
     where
         'a: 'env,
     {
-        Self { inner }
+        Self { inner, dictionaries }
     }
 
     /// Gets this transaction ID.
@@ -42,6 +45,7 @@ impl<'env, K: TransactionKind, E: EnvironmentKind> Tx<'env, K, E> {
                 )
                 .map_err(|e| Error::InitCursor(e.into()))?,
             table: T::NAME,
+            dictionaries: self.dictionaries,
             _dbi: PhantomData,
         })
     }
@@ -82,7 +86,7 @@ impl<'tx, K: TransactionKind, E: EnvironmentKind> DbTx<'tx> for Tx<'tx, K, E> {
                 key.encode().as_ref(),
             )
             .map_err(|e| Error::Read(e.into()))?
-            .map(decode_one::<T>)
+            .map(|value| decode_one::<T>(self.dictionaries, value))
             .transpose()
     }
 }
@@ -93,7 +97,7 @@ impl<E: EnvironmentKind> DbTxMut<'_> for Tx<'_, RW, E> {
             .put(
                 &self.inner.open_db(Some(T::NAME)).map_err(|e| Error::Write(e.into()))?,
                 &key.encode(),
-                &value.compress(),
+                &value.compress_with(self.dictionaries),
                 WriteFlags::UPSERT,
             )
             .map_err(|e| Error::Write(e.into()))
@@ -102,7 +106,7 @@ impl<E: EnvironmentKind> DbTxMut<'_> for Tx<'_, RW, E> {
     fn delete<T: Table>(&self, key: T::Key, value: Option<T::Value>) -> Result<bool, Error> {
         let mut data = None;
 
-        let value = value.map(Compress::compress);
+        let value = value.map(|value| value.compress_with(self.dictionaries));
         if let Some(value) = &value {
             data = Some(value.as_ref());
         };
@@ -0,0 +1,352 @@
+//! Cursor over a table of the in-memory database.
+
+use super::{next_key, tx::TxTables, Entry, MemTable, EINVAL, EKEYMISMATCH, KEYEXIST, NOTFOUND};
+use crate::{
+    common::{PairResult, ValueOnlyResult},
+    cursor::{
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, RangeWalker,
+        ReverseWalker, Walker,
+    },
+    table::{Compress, DupSort, Encode, Table},
+    tables::{codecs::compression::Dictionaries, utils::decoder},
+    Error,
+};
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    ops::{Bound, Range},
+};
+
+/// Position of a [Cursor] in its table.
+#[derive(Debug, Clone)]
+enum Position {
+    /// The cursor was not positioned yet.
+    Unset,
+    /// The cursor points at the entry.
+    ///
+    /// If the entry was deleted, the cursor points at the gap it left, like MDBX cursors do.
+    At(Entry),
+    /// A seek went past the last entry.
+    End,
+}
+
+/// Cursor over a table of the in-memory database.
+#[derive(Debug)]
+pub struct Cursor<'tx, T: Table> {
+    /// The tables of the transaction.
+    tables: &'tx TxTables,
+    /// The compression dictionaries of the database.
+    dictionaries: &'tx Dictionaries,
+    /// The position of the cursor.
+    position: Position,
+    /// Phantom data to enforce encoding/decoding.
+    _table: PhantomData<T>,
+}
+
+impl<'tx, T: Table> Cursor<'tx, T> {
+    /// Creates a new unpositioned cursor.
+    pub(crate) fn new(tables: &'tx TxTables, dictionaries: &'tx Dictionaries) -> Self {
+        Self { tables, dictionaries, position: Position::Unset, _table: PhantomData }
+    }
+
+    /// Reads the table of the cursor.
+    fn read<R>(&self, f: impl FnOnce(&MemTable) -> R) -> Result<R, Error> {
+        self.tables.read(T::NAME, Error::Read, f)
+    }
+
+    /// Writes the table of the cursor.
+    fn write<R>(
+        &self,
+        err: fn(u32) -> Error,
+        f: impl FnOnce(&mut MemTable) -> R,
+    ) -> Result<R, Error> {
+        self.tables.write(T::NAME, err, f)
+    }
+
+    /// Moves the cursor to the entry and returns it, the cursor stays in place if there is none.
+    fn move_to(&mut self, entry: Option<Entry>) -> PairResult<T> {
+        match entry {
+            Some(entry) => {
+                self.position = Position::At(entry.clone());
+                self.decode(entry).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the entry at the position of the cursor, which is the next entry if the entry of
+    /// the position was deleted.
+    fn current_entry(&self) -> Result<Option<Entry>, Error> {
+        match &self.position {
+            Position::At(entry) => {
+                self.read(|table| table.entries.range(entry.clone()..).next().cloned())
+            }
+            Position::Unset | Position::End => Ok(None),
+        }
+    }
+
+    /// Encodes the `(key, value)` pair.
+    fn encode(&self, key: T::Key, value: T::Value) -> Entry {
+        (key.encode().as_ref().to_vec(), value.compress_with(self.dictionaries).as_ref().to_vec())
+    }
+
+    /// Decodes an encoded `(key, value)` pair.
+    fn decode(&self, entry: Entry) -> Result<(T::Key, T::Value), Error> {
+        decoder::<T>(self.dictionaries, (Cow::Owned(entry.0), Cow::Owned(entry.1)))
+    }
+}
+
+impl<'tx, T: DupSort> Cursor<'tx, T> {
+    /// Moves the cursor to the first duplicate of the key that is greater or equal than the
+    /// subkey.
+    fn seek_dup(&mut self, key: T::Key, subkey: T::SubKey) -> PairResult<T> {
+        let key = key.encode().as_ref().to_vec();
+        let subkey = subkey.encode().as_ref().to_vec();
+        let entry = self.read(|table| {
+            table.entries.range((key.clone(), subkey)..).next().filter(|(k, _)| *k == key).cloned()
+        })?;
+        self.move_to(entry)
+    }
+}
+
+impl<'tx, T: Table> DbCursorRO<'tx, T> for Cursor<'tx, T> {
+    fn first(&mut self) -> PairResult<T> {
+        let entry = self.read(|table| table.entries.iter().next().cloned())?;
+        self.move_to(entry)
+    }
+
+    fn seek_exact(&mut self, key: T::Key) -> PairResult<T> {
+        let key = key.encode().as_ref().to_vec();
+        match self.read(|table| table.seek(&key))? {
+            Some(entry) if entry.0 == key => self.move_to(Some(entry)),
+            // the cursor is left at the next key
+            entry => {
+                self.position = entry.map_or(Position::End, Position::At);
+                Ok(None)
+            }
+        }
+    }
+
+    fn seek(&mut self, key: T::Key) -> PairResult<T> {
+        let key = key.encode().as_ref().to_vec();
+        match self.read(|table| table.seek(&key))? {
+            Some(entry) => self.move_to(Some(entry)),
+            None => {
+                self.position = Position::End;
+                Ok(None)
+            }
+        }
+    }
+
+    fn next(&mut self) -> PairResult<T> {
+        let entry = match &self.position {
+            Position::Unset => return self.first(),
+            Position::At(entry) => self.read(|table| {
+                table
+                    .entries
+                    .range((Bound::Excluded(entry.clone()), Bound::Unbounded))
+                    .next()
+                    .cloned()
+            })?,
+            Position::End => None,
+        };
+        self.move_to(entry)
+    }
+
+    fn prev(&mut self) -> PairResult<T> {
+        let entry = match &self.position {
+            Position::Unset | Position::End => return self.last(),
+            Position::At(entry) => {
+                self.read(|table| table.entries.range(..entry.clone()).next_back().cloned())?
+            }
+        };
+        self.move_to(entry)
+    }
+
+    fn last(&mut self) -> PairResult<T> {
+        let entry = self.read(|table| table.entries.iter().next_back().cloned())?;
+        self.move_to(entry)
+    }
+
+    fn current(&mut self) -> PairResult<T> {
+        self.current_entry()?.map(|entry| self.decode(entry)).transpose()
+    }
+
+    fn walk<'cursor>(
+        &'cursor mut self,
+        start_key: T::Key,
+    ) -> Result<Walker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = self.seek(start_key).transpose();
+        Ok(Walker::new(self, start))
+    }
+
+    fn walk_range<'cursor>(
+        &'cursor mut self,
+        range: Range<T::Key>,
+    ) -> Result<RangeWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = self.seek(range.start).transpose();
+        Ok(RangeWalker::new(self, start, range.end))
+    }
+
+    fn walk_back<'cursor>(
+        &'cursor mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<ReverseWalker<'cursor, 'tx, T, Self>, Error>
+    where
+        Self: Sized,
+    {
+        let start = match start_key {
+            Some(start_key) => self.seek(start_key),
+            None => self.last(),
+        }
+        .transpose();
+        Ok(ReverseWalker::new(self, start))
+    }
+}
+
+impl<'tx, T: DupSort> DbDupCursorRO<'tx, T> for Cursor<'tx, T> {
+    fn next_dup(&mut self) -> PairResult<T> {
+        let entry = match &self.position {
+            Position::Unset => return self.first(),
+            Position::At(entry) => self.read(|table| {
+                table
+                    .entries
+                    .range((Bound::Excluded(entry.clone()), Bound::Unbounded))
+                    .next()
+                    .filter(|(key, _)| *key == entry.0)
+                    .cloned()
+            })?,
+            Position::End => None,
+        };
+        self.move_to(entry)
+    }
+
+    fn next_no_dup(&mut self) -> PairResult<T> {
+        let entry = match &self.position {
+            Position::Unset => return self.first(),
+            Position::At((key, _)) => self.read(|table| table.seek(&next_key(key)))?,
+            Position::End => None,
+        };
+        self.move_to(entry)
+    }
+
+    fn next_dup_val(&mut self) -> ValueOnlyResult<T> {
+        Ok(self.next_dup()?.map(|(_, value)| value))
+    }
+
+    fn seek_by_key_subkey(&mut self, key: T::Key, subkey: T::SubKey) -> ValueOnlyResult<T> {
+        Ok(self.seek_dup(key, subkey)?.map(|(_, value)| value))
+    }
+
+    fn walk_dup<'cursor>(
+        &'cursor mut self,
+        key: T::Key,
+        subkey: T::SubKey,
+    ) -> Result<DupWalker<'cursor, 'tx, T, Self>, Error> {
+        let start = self.seek_dup(key, subkey).transpose();
+        Ok(DupWalker::<'cursor, 'tx, T, Self> { cursor: self, start, _tx_phantom: PhantomData {} })
+    }
+}
+
+impl<'tx, T: Table> DbCursorRW<'tx, T> for Cursor<'tx, T> {
+    fn upsert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = self.encode(key, value);
+        self.write(Error::Write, |table| table.upsert(entry.clone()))?;
+        self.position = Position::At(entry);
+        Ok(())
+    }
+
+    fn insert(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = self.encode(key, value);
+        let existing = self.write(Error::Write, |table| match table.dups(&entry.0).next() {
+            Some(existing) => Some(existing.clone()),
+            None => {
+                table.entries.insert(entry.clone());
+                None
+            }
+        })?;
+        match existing {
+            // the cursor is left at the existing key
+            Some(existing) => {
+                self.position = Position::At(existing);
+                Err(Error::Write(KEYEXIST))
+            }
+            None => {
+                self.position = Position::At(entry);
+                Ok(())
+            }
+        }
+    }
+
+    fn append(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = self.encode(key, value);
+        let last = self.write(Error::Write, |table| match table.entries.iter().next_back() {
+            Some(last) if last.0 > entry.0 => Some(last.clone()),
+            _ => {
+                table.upsert(entry.clone());
+                None
+            }
+        })?;
+        match last {
+            // the cursor is left at the end of the table
+            Some(last) => {
+                self.position = Position::At(last);
+                Err(Error::Write(EKEYMISMATCH))
+            }
+            None => {
+                self.position = Position::At(entry);
+                Ok(())
+            }
+        }
+    }
+
+    fn delete_current(&mut self) -> Result<(), Error> {
+        if let Position::Unset = self.position {
+            return Err(Error::Delete(EINVAL))
+        }
+        let entry = self.current_entry()?.ok_or(Error::Delete(NOTFOUND))?;
+        self.write(Error::Delete, |table| table.entries.remove(&entry))?;
+        self.position = Position::At(entry);
+        Ok(())
+    }
+}
+
+impl<'tx, T: DupSort> DbDupCursorRW<'tx, T> for Cursor<'tx, T> {
+    fn delete_current_duplicates(&mut self) -> Result<(), Error> {
+        if let Position::Unset = self.position {
+            return Err(Error::Delete(EINVAL))
+        }
+        let (key, _) = self.current_entry()?.ok_or(Error::Delete(NOTFOUND))?;
+        let last = self.write(Error::Delete, |table| table.remove_dups(&key))?;
+        self.position = last.map_or(Position::End, Position::At);
+        Ok(())
+    }
+
+    fn append_dup(&mut self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = self.encode(key, value);
+        let last = self.write(Error::Write, |table| match table.dups(&entry.0).next_back() {
+            Some(last) if last.1 >= entry.1 => Some(last.clone()),
+            _ => {
+                table.entries.insert(entry.clone());
+                None
+            }
+        })?;
+        match last {
+            // the cursor is left at the last duplicate of the key
+            Some(last) => {
+                self.position = Position::At(last);
+                Err(Error::Write(EKEYMISMATCH))
+            }
+            None => {
+                self.position = Position::At(entry);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! In-memory database, mainly used for tests.
+//!
+//! All tables keep their encoded `(key, value)` pairs in byte order, so cursors behave the same as
+//! on MDBX, including the duplicates of [DupSort][crate::table::DupSort] tables. Transactions work
+//! on a copy-on-write snapshot of the tables that replaces the committed tables on commit, and is
+//! discarded if the transaction is dropped without committing.
+
+use crate::{
+    database::{Database, DatabaseGAT},
+    table::Table,
+    tables::{codecs::compression::Dictionaries, TableType, TABLES},
+    Error,
+};
+use parking_lot::{Condvar, Mutex, RwLock};
+use std::{
+    collections::{btree_set, BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+pub mod cursor;
+
+pub mod tx;
+use tx::Tx;
+
+/// Error code of an insert of an existing key, same as `MDBX_KEYEXIST`.
+const KEYEXIST: u32 = -30799i32 as u32;
+/// Error code of a missing entry, same as `MDBX_NOTFOUND`.
+const NOTFOUND: u32 = -30798i32 as u32;
+/// Error code of an out of order append, same as `MDBX_EKEYMISMATCH`.
+const EKEYMISMATCH: u32 = -30418i32 as u32;
+/// Error code of an operation on an unpositioned cursor, same as `MDBX_EINVAL`.
+const EINVAL: u32 = 22;
+/// Error code of a write in a read-only transaction, same as `MDBX_EACCESS`.
+const EACCESS: u32 = 13;
+
+/// Encoded `(key, value)` pair of a table.
+type Entry = (Vec<u8>, Vec<u8>);
+
+/// The tables of the database, keyed by their name.
+type Tables = BTreeMap<&'static str, Arc<MemTable>>;
+
+/// The entries of a table.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemTable {
+    /// Whether the table can store multiple values per key.
+    dupsort: bool,
+    /// The `(key, value)` pairs of the table.
+    entries: BTreeSet<Entry>,
+}
+
+impl MemTable {
+    /// Returns the first entry with a key greater or equal than `key`.
+    fn seek(&self, key: &[u8]) -> Option<Entry> {
+        self.entries.range((key.to_vec(), Vec::new())..).next().cloned()
+    }
+
+    /// Returns all entries of the key.
+    fn dups(&self, key: &[u8]) -> btree_set::Range<'_, Entry> {
+        self.entries.range((key.to_vec(), Vec::new())..(next_key(key), Vec::new()))
+    }
+
+    /// Inserts the entry, replacing the value of the key if the table is not dupsort.
+    fn upsert(&mut self, entry: Entry) {
+        if !self.dupsort {
+            self.remove_dups(&entry.0);
+        }
+        self.entries.insert(entry);
+    }
+
+    /// Removes all entries of the key and returns the last one.
+    fn remove_dups(&mut self, key: &[u8]) -> Option<Entry> {
+        let dups = self.dups(key).cloned().collect::<Vec<_>>();
+        for entry in dups.iter() {
+            self.entries.remove(entry);
+        }
+        dups.into_iter().last()
+    }
+}
+
+/// Returns the smallest key that is greater than `key`.
+fn next_key(key: &[u8]) -> Vec<u8> {
+    let mut next = key.to_vec();
+    next.push(0);
+    next
+}
+
+/// In-memory database.
+///
+/// There can be any number of read-only transactions, but only one read-write transaction at a
+/// time. Opening another one blocks until the open one is committed or dropped.
+#[derive(Debug)]
+pub struct MemDatabase {
+    /// The committed tables.
+    tables: RwLock<Tables>,
+    /// Whether a read-write transaction is open.
+    writer: Mutex<bool>,
+    /// Notified when the read-write transaction is closed.
+    writer_closed: Condvar,
+    /// The compression dictionaries of the database.
+    dictionaries: Dictionaries,
+}
+
+impl MemDatabase {
+    /// Creates an empty database with all [TABLES].
+    pub fn new() -> Self {
+        let tables = TABLES
+            .iter()
+            .map(|(table_type, name)| {
+                let dupsort = matches!(table_type, TableType::DupSort);
+                (*name, Arc::new(MemTable { dupsort, entries: BTreeSet::new() }))
+            })
+            .collect();
+        Self {
+            tables: RwLock::new(tables),
+            writer: Mutex::new(false),
+            writer_closed: Condvar::new(),
+            dictionaries: Dictionaries::default(),
+        }
+    }
+
+    /// Begins a transaction on a snapshot of the committed tables.
+    fn begin(&self, writable: bool) -> Tx<'_> {
+        if writable {
+            let mut open = self.writer.lock();
+            while *open {
+                self.writer_closed.wait(&mut open);
+            }
+            *open = true;
+        }
+        let tables = self.tables.read().clone();
+        Tx::new(self, tables, writable)
+    }
+
+    /// Replaces the committed tables with the tables of a read-write transaction.
+    fn commit(&self, tables: Tables) {
+        *self.tables.write() = tables;
+    }
+
+    /// Allows the next read-write transaction to begin.
+    fn close_writer(&self) {
+        *self.writer.lock() = false;
+        self.writer_closed.notify_one();
+    }
+}
+
+impl Default for MemDatabase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> DatabaseGAT<'a> for MemDatabase {
+    type TX = Tx<'a>;
+    type TXMut = Tx<'a>;
+}
+
+impl Database for MemDatabase {
+    fn tx(&self) -> Result<<Self as DatabaseGAT<'_>>::TX, Error> {
+        Ok(self.begin(false))
+    }
+
+    fn tx_mut(&self) -> Result<<Self as DatabaseGAT<'_>>::TXMut, Error> {
+        Ok(self.begin(true))
+    }
+
+    fn dictionaries(&self) -> &Dictionaries {
+        &self.dictionaries
+    }
+
+    fn table_exists<T: Table>(&self) -> Result<bool, Error> {
+        Ok(self.tables.read().contains_key(T::NAME))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemDatabase;
+    use crate::implementation::conformance::database_conformance_tests;
+
+    database_conformance_tests!(MemDatabase::new());
+}
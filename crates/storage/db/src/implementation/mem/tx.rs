@@ -0,0 +1,151 @@
+//! Transaction of the in-memory database.
+
+use super::{cursor::Cursor, MemDatabase, MemTable, Tables, EACCESS, NOTFOUND};
+use crate::{
+    table::{Compress, DupSort, Encode, Table},
+    tables::utils::decode_one,
+    transaction::{DbTx, DbTxGAT, DbTxMut, DbTxMutGAT},
+    Error,
+};
+use parking_lot::Mutex;
+use std::{borrow::Cow, sync::Arc};
+
+/// The tables of a transaction.
+#[derive(Debug)]
+pub(crate) struct TxTables {
+    /// Snapshot of the tables, tables are copied on their first write.
+    inner: Mutex<Tables>,
+    /// Whether the tables can be written.
+    writable: bool,
+}
+
+impl TxTables {
+    /// Reads the table, failing with `err` if it does not exist.
+    pub(crate) fn read<R>(
+        &self,
+        name: &'static str,
+        err: fn(u32) -> Error,
+        f: impl FnOnce(&MemTable) -> R,
+    ) -> Result<R, Error> {
+        let tables = self.inner.lock();
+        Ok(f(tables.get(name).ok_or(err(NOTFOUND))?))
+    }
+
+    /// Writes the table, failing with `err` if it does not exist or the tables are read-only.
+    pub(crate) fn write<R>(
+        &self,
+        name: &'static str,
+        err: fn(u32) -> Error,
+        f: impl FnOnce(&mut MemTable) -> R,
+    ) -> Result<R, Error> {
+        if !self.writable {
+            return Err(err(EACCESS))
+        }
+        let mut tables = self.inner.lock();
+        Ok(f(Arc::make_mut(tables.get_mut(name).ok_or(err(NOTFOUND))?)))
+    }
+}
+
+/// Transaction of the in-memory database.
+///
+/// Dropping a read-write transaction without committing discards its changes.
+#[derive(Debug)]
+pub struct Tx<'db> {
+    /// The database of the transaction.
+    db: &'db MemDatabase,
+    /// The tables as seen by the transaction.
+    tables: TxTables,
+}
+
+impl<'db> Tx<'db> {
+    /// Creates a new transaction on a snapshot of the tables.
+    pub(crate) fn new(db: &'db MemDatabase, tables: Tables, writable: bool) -> Self {
+        Self { db, tables: TxTables { inner: Mutex::new(tables), writable } }
+    }
+
+    /// Create db Cursor
+    pub fn new_cursor<T: Table>(&self) -> Result<Cursor<'_, T>, Error> {
+        self.tables.read(T::NAME, Error::InitCursor, |_| ())?;
+        Ok(Cursor::new(&self.tables, &self.db.dictionaries))
+    }
+}
+
+impl Drop for Tx<'_> {
+    fn drop(&mut self) {
+        if self.tables.writable {
+            self.db.close_writer();
+        }
+    }
+}
+
+impl<'a> DbTxGAT<'a> for Tx<'_> {
+    type Cursor<T: Table> = Cursor<'a, T>;
+    type DupCursor<T: DupSort> = Cursor<'a, T>;
+}
+
+impl<'a> DbTxMutGAT<'a> for Tx<'_> {
+    type CursorMut<T: Table> = Cursor<'a, T>;
+    type DupCursorMut<T: DupSort> = Cursor<'a, T>;
+}
+
+impl<'tx> DbTx<'tx> for Tx<'tx> {
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>, Error> {
+        let key = key.encode().as_ref().to_vec();
+        self.tables
+            .read(T::NAME, Error::Read, |table| {
+                table.dups(&key).next().map(|(_, value)| value.clone())
+            })?
+            .map(|value| decode_one::<T>(&self.db.dictionaries, Cow::Owned(value)))
+            .transpose()
+    }
+
+    fn commit(self) -> Result<bool, Error> {
+        if self.tables.writable {
+            let tables = std::mem::take(&mut *self.tables.inner.lock());
+            self.db.commit(tables);
+        }
+        Ok(false)
+    }
+
+    fn cursor_read<T: Table>(&self) -> Result<<Self as DbTxGAT<'_>>::Cursor<T>, Error> {
+        self.new_cursor()
+    }
+
+    fn cursor_dup_read<T: DupSort>(&self) -> Result<<Self as DbTxGAT<'_>>::DupCursor<T>, Error> {
+        self.new_cursor()
+    }
+}
+
+impl DbTxMut<'_> for Tx<'_> {
+    fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<(), Error> {
+        let entry = (
+            key.encode().as_ref().to_vec(),
+            value.compress_with(&self.db.dictionaries).as_ref().to_vec(),
+        );
+        self.tables.write(T::NAME, Error::Write, |table| table.upsert(entry))
+    }
+
+    fn delete<T: Table>(&self, key: T::Key, value: Option<T::Value>) -> Result<bool, Error> {
+        let key = key.encode().as_ref().to_vec();
+        let value = value.map(|value| value.compress_with(&self.db.dictionaries).as_ref().to_vec());
+        self.tables.write(T::NAME, Error::Delete, |table| match value {
+            // only the matching value is deleted
+            Some(value) => table.entries.remove(&(key, value)),
+            None => table.remove_dups(&key).is_some(),
+        })
+    }
+
+    fn clear<T: Table>(&self) -> Result<(), Error> {
+        self.tables.write(T::NAME, Error::Delete, |table| table.entries.clear())
+    }
+
+    fn cursor_write<T: Table>(&self) -> Result<<Self as DbTxMutGAT<'_>>::CursorMut<T>, Error> {
+        self.new_cursor()
+    }
+
+    fn cursor_dup_write<T: DupSort>(
+        &self,
+    ) -> Result<<Self as DbTxMutGAT<'_>>::DupCursorMut<T>, Error> {
+        self.new_cursor()
+    }
+}
@@ -1,6 +1,7 @@
+use super::compression::{self, Dictionaries};
 use crate::{
-    table::{Compress, Decompress},
-    tables::models::*,
+    table::{Compress, Decompress, Table},
+    tables::{self, models::*},
     Error,
 };
 use reth_codecs::{main_codec, Compact};
@@ -33,18 +34,62 @@ macro_rules! impl_compression_for_compact {
     };
 }
 
+/// Implements compression for Compact types whose values are prefixed with a marker and compressed
+/// with the dictionary of a table, see [compression].
+///
+/// Without the dictionaries of a database, values are only prefixed with the marker.
+macro_rules! impl_dictionary_compression_for_compact {
+    ($($name:tt => $table:ident),+) => {
+        $(
+            impl Compress for $name
+            {
+                type Compressed = Vec<u8>;
+
+                fn compress(self) -> Self::Compressed {
+                    let mut buf = vec![compression::UNCOMPRESSED];
+                    let _  = Compact::to_compact(self, &mut buf);
+                    buf
+                }
+
+                fn compress_with(self, dictionaries: &Dictionaries) -> Self::Compressed {
+                    dictionaries.compress(<tables::$table as Table>::NAME, self.compress())
+                }
+            }
+
+            impl Decompress for $name
+            {
+                fn decompress<B: Into<bytes::Bytes>>(value: B) -> Result<$name, Error> {
+                    Self::decompress_with(value, &Dictionaries::default())
+                }
+
+                fn decompress_with<B: Into<bytes::Bytes>>(
+                    value: B,
+                    dictionaries: &Dictionaries,
+                ) -> Result<$name, Error> {
+                    let value = dictionaries.decompress(value.into())?;
+                    let (obj, _) = Compact::from_compact(&value, value.len());
+                    Ok(obj)
+                }
+            }
+        )+
+    };
+}
+
 impl_compression_for_compact!(
     Header,
     Account,
     Log,
-    Receipt,
     TxType,
     StorageEntry,
     StorageTrieEntry,
-    StoredBlockBody,
     StoredBlockOmmers
 );
-impl_compression_for_compact!(AccountBeforeTx, TransactionSigned);
+impl_compression_for_compact!(AccountBeforeTx);
+impl_dictionary_compression_for_compact!(
+    StoredBlockBody => BlockBodies,
+    TransactionSigned => Transactions,
+    Receipt => Receipts
+);
 impl_compression_for_compact!(CompactU256);
 impl_compression_for_compact!(H256, H160);
 
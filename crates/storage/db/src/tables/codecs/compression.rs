@@ -0,0 +1,541 @@
+//! Optional zstd compression of the values of large tables.
+//!
+//! The values of the [COMPRESSED_TABLES] start with a marker byte that tells whether the rest of
+//! the value is stored as is ([UNCOMPRESSED]) or compressed with a dictionary ([ZSTD]). Values are
+//! only compressed once a dictionary was trained for their table with [train_dictionary] and
+//! stored with [store_dictionary], so a table can hold both kinds of values while it is being
+//! recompressed.
+//!
+//! Every database keeps its own [Dictionaries], see [Database::dictionaries], which are passed to
+//! [Compress::compress_with] and [Decompress::decompress_with] by its transactions and cursors.
+//! Compressed values are decompressed with the dictionary whose id is in their zstd frame. If the
+//! dictionary is unknown, e.g. because another process stored it, the dictionaries are reloaded
+//! from the database first.
+
+use crate::{
+    cursor::{DbCursorRO, Walker},
+    database::{Database, DatabaseGAT},
+    table::{Compress, Decode, Decompress, Encode, Table},
+    tables::{self, Tables},
+    transaction::{DbTx, DbTxMut},
+    version::Migration,
+    Error,
+};
+use bytes::Bytes;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::{cell::RefCell, fmt, marker::PhantomData, sync::Arc};
+use zstd::{
+    dict::{DecoderDictionary, EncoderDictionary},
+    zstd_safe::{CCtx, DCtx},
+};
+
+/// Marker of a value that is stored as is.
+pub const UNCOMPRESSED: u8 = 0;
+/// Marker of a value that is compressed with zstd and a dictionary of its table.
+pub const ZSTD: u8 = 1;
+
+/// Default maximum size of a trained dictionary in bytes, the same as the zstd cli.
+pub const DEFAULT_DICTIONARY_SIZE: usize = 112_640;
+/// Default number of values a dictionary is trained on.
+pub const DEFAULT_DICTIONARY_SAMPLES: usize = 100_000;
+
+/// The zstd level values are compressed with.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Prefix of the keys of the dictionaries in [tables::Config], followed by the table name.
+const DICTIONARY_KEY_PREFIX: &[u8] = b"ZstdDictionary/";
+
+/// The tables with compressed values, together with the table whose dictionary they use.
+///
+/// Tables with the same value type share a dictionary.
+pub const COMPRESSED_TABLES: [(Tables, Tables); 5] = [
+    (Tables::BlockBodies, Tables::BlockBodies),
+    (Tables::NonCanonicalTransactions, Tables::Transactions),
+    (Tables::Transactions, Tables::Transactions),
+    (Tables::Receipts, Tables::Receipts),
+    (Tables::Logs, Tables::Receipts),
+];
+
+/// The tables that dictionaries are trained for.
+pub const DICTIONARY_TABLES: [Tables; 3] =
+    [Tables::BlockBodies, Tables::Transactions, Tables::Receipts];
+
+/// Returns the table whose dictionary compresses the values of `table`, or `None` if its values are
+/// not compressed.
+pub fn dictionary_table(table: Tables) -> Option<Tables> {
+    COMPRESSED_TABLES
+        .into_iter()
+        .find(|(compressed, _)| *compressed == table)
+        .map(|(_, dictionary)| dictionary)
+}
+
+thread_local! {
+    /// The zstd contexts of the current thread, they are reused for all values.
+    static CONTEXTS: RefCell<Contexts> = RefCell::new(Contexts {
+        compressor: CCtx::create(),
+        decompressor: DCtx::create(),
+    });
+}
+
+/// Reusable zstd contexts.
+struct Contexts {
+    compressor: CCtx<'static>,
+    decompressor: DCtx<'static>,
+}
+
+/// A dictionary, prepared for compression and decompression.
+struct Dictionary {
+    /// The name of the table the dictionary was trained for.
+    table: &'static str,
+    /// The id of the dictionary, it is part of every frame compressed with it.
+    id: u32,
+    encoder: EncoderDictionary<'static>,
+    decoder: DecoderDictionary<'static>,
+}
+
+impl fmt::Debug for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dictionary")
+            .field("table", &self.table)
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Registers the dictionaries that are stored in a database, see [Dictionaries::with_loader].
+type Loader = Box<dyn Fn(&Dictionaries) -> Result<(), Error> + Send + Sync>;
+
+/// The compression dictionaries of a database.
+///
+/// Every database has its own dictionaries, values must never be compressed with the dictionary of
+/// another database since they could not be read from it.
+#[derive(Default)]
+pub struct Dictionaries {
+    /// The registered dictionaries, the latest last.
+    registered: RwLock<Vec<Arc<Dictionary>>>,
+    /// Reloads the dictionaries from the database when a value has an unknown dictionary.
+    loader: Option<Loader>,
+}
+
+impl fmt::Debug for Dictionaries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dictionaries")
+            .field("registered", &self.registered)
+            .field("loader", &self.loader.is_some())
+            .finish()
+    }
+}
+
+impl Dictionaries {
+    /// Creates dictionaries that are reloaded with `loader` when a value was compressed with an
+    /// unknown dictionary, since another process may have stored it after they were loaded.
+    pub fn with_loader(
+        loader: impl Fn(&Dictionaries) -> Result<(), Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self { registered: Default::default(), loader: Some(Box::new(loader)) }
+    }
+
+    /// Registers a dictionary of `table`. Values of the table are compressed with its latest
+    /// registered dictionary.
+    pub fn register(&self, table: Tables, dictionary: &[u8]) -> Result<(), Error> {
+        let id = zstd::zstd_safe::get_dict_id_from_dict(dictionary)
+            .ok_or_else(|| Error::Compression(format!("invalid dictionary for table {table}")))?
+            .get();
+        let dictionary = Dictionary {
+            table: table.name(),
+            id,
+            encoder: EncoderDictionary::copy(dictionary, COMPRESSION_LEVEL),
+            decoder: DecoderDictionary::copy(dictionary),
+        };
+
+        let mut registered = self.registered.write();
+        registered.retain(|registered| registered.id != id);
+        registered.push(Arc::new(dictionary));
+        Ok(())
+    }
+
+    /// Registers all dictionaries that are stored in the database.
+    pub fn load<'a, TX: DbTx<'a>>(&self, tx: &TX) -> Result<(), Error> {
+        for table in DICTIONARY_TABLES {
+            if let Some(dictionary) = stored_dictionary(tx, table)? {
+                self.register(table, &dictionary)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the latest registered dictionary of the table.
+    fn latest(&self, table: &str) -> Option<Arc<Dictionary>> {
+        self.registered.read().iter().rev().find(|dictionary| dictionary.table == table).cloned()
+    }
+
+    /// Returns the registered dictionary with the id.
+    fn by_id(&self, id: u32) -> Option<Arc<Dictionary>> {
+        self.registered.read().iter().find(|dictionary| dictionary.id == id).cloned()
+    }
+
+    /// Returns the dictionary with the id, reloading the dictionaries if it is not registered.
+    fn by_id_or_reload(&self, id: u32) -> Result<Option<Arc<Dictionary>>, Error> {
+        if let Some(dictionary) = self.by_id(id) {
+            return Ok(Some(dictionary))
+        }
+        match &self.loader {
+            Some(loader) => {
+                loader(self)?;
+                Ok(self.by_id(id))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Compresses a value that starts with the [UNCOMPRESSED] marker with the latest dictionary of
+    /// `table`.
+    ///
+    /// The value is kept as is if the table has no dictionary, or if compression does not make it
+    /// smaller.
+    pub(crate) fn compress(&self, table: &str, value: Vec<u8>) -> Vec<u8> {
+        debug_assert_eq!(value.first(), Some(&UNCOMPRESSED));
+        let Some(dictionary) = self.latest(table) else { return value };
+
+        let compressed = CONTEXTS.with(|contexts| {
+            let mut compressed =
+                Vec::with_capacity(zstd::zstd_safe::compress_bound(value.len() - 1));
+            contexts
+                .borrow_mut()
+                .compressor
+                .compress_using_cdict(&mut compressed, &value[1..], dictionary.encoder.as_cdict())
+                .map(|_| compressed)
+        });
+        match compressed {
+            Ok(compressed) if compressed.len() + 1 < value.len() => {
+                [[ZSTD].as_slice(), compressed.as_slice()].concat()
+            }
+            _ => value,
+        }
+    }
+
+    /// Strips the marker of a value, decompressing it if needed.
+    pub fn decompress(&self, value: Bytes) -> Result<Bytes, Error> {
+        match value.first() {
+            Some(&UNCOMPRESSED) => Ok(value.slice(1..)),
+            Some(&ZSTD) => {
+                let frame = &value[1..];
+                let id = zstd::zstd_safe::get_dict_id_from_frame(frame)
+                    .ok_or_else(|| Error::Compression("value without dictionary".to_string()))?;
+                let dictionary = self
+                    .by_id_or_reload(id.get())?
+                    .ok_or_else(|| Error::Compression(format!("unknown dictionary {id}")))?;
+
+                // values are compressed with their size, see `Dictionaries::compress`
+                let size = match zstd::zstd_safe::get_frame_content_size(frame) {
+                    Ok(Some(size)) => size as usize,
+                    _ => return Err(Error::Compression("value without content size".to_string())),
+                };
+                let mut decompressed = Vec::with_capacity(size);
+                CONTEXTS
+                    .with(|contexts| {
+                        contexts.borrow_mut().decompressor.decompress_using_ddict(
+                            &mut decompressed,
+                            frame,
+                            dictionary.decoder.as_ddict(),
+                        )
+                    })
+                    .map_err(|code| {
+                        Error::Compression(zstd::zstd_safe::get_error_name(code).to_string())
+                    })?;
+                Ok(decompressed.into())
+            }
+            _ => Err(Error::DecodeError),
+        }
+    }
+}
+
+/// Returns the key of the dictionary of the table in [tables::Config].
+fn dictionary_key(table: Tables) -> Vec<u8> {
+    [DICTIONARY_KEY_PREFIX, table.name().as_bytes()].concat()
+}
+
+/// Returns the dictionary of the table that is stored in the database, if any.
+pub fn stored_dictionary<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    table: Tables,
+) -> Result<Option<Vec<u8>>, Error> {
+    tx.get::<tables::Config>(dictionary_key(table))
+}
+
+/// Stores the dictionary of `table` in the database and registers it with the
+/// [Database::dictionaries] once it is committed.
+///
+/// A table only has a single dictionary, since the values that were compressed with a replaced
+/// dictionary could not be read anymore.
+pub fn store_dictionary<DB: Database>(
+    db: &DB,
+    table: Tables,
+    dictionary: &[u8],
+) -> Result<(), Error> {
+    if !DICTIONARY_TABLES.contains(&table) {
+        return Err(Error::Compression(format!("values of table {table} are not compressed")))
+    }
+    db.update(|tx| {
+        if stored_dictionary(tx, table)?.is_some() {
+            return Err(Error::Compression(format!("table {table} already has a dictionary")))
+        }
+        tx.put::<tables::Config>(dictionary_key(table), dictionary.to_vec())
+    })??;
+    db.dictionaries().register(table, dictionary)
+}
+
+/// Trains a dictionary of at most `max_size` bytes on up to `samples` of the latest values of `T`.
+///
+/// Values that are already compressed are decompressed with the `dictionaries` of the database.
+pub fn train_dictionary<'a, T: Table, TX: DbTx<'a>>(
+    tx: &TX,
+    dictionaries: &Dictionaries,
+    samples: usize,
+    max_size: usize,
+) -> Result<Vec<u8>, Error> {
+    let samples = tx
+        .cursor_read::<StoredValues<T>>()?
+        .walk_back(None)?
+        .take(samples)
+        .map(|entry| dictionaries.decompress(entry?.1 .0.into()))
+        .collect::<Result<Vec<_>, _>>()?;
+    zstd::dict::from_samples(&samples, max_size).map_err(|e| Error::Compression(e.to_string()))
+}
+
+/// A value as it is stored in the database, including its marker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoredValue(pub Vec<u8>);
+
+impl Compress for StoredValue {
+    type Compressed = Vec<u8>;
+
+    fn compress(self) -> Self::Compressed {
+        self.0
+    }
+}
+
+impl Decompress for StoredValue {
+    fn decompress<B: Into<Bytes>>(value: B) -> Result<Self, Error> {
+        Ok(Self(value.into().to_vec()))
+    }
+}
+
+/// The table `T` with its values as they are stored, to read or write them without decoding.
+#[derive(Debug)]
+pub struct StoredValues<T>(PhantomData<T>);
+
+impl<T: Table> Table for StoredValues<T> {
+    const NAME: &'static str = T::NAME;
+    type Key = T::Key;
+    type Value = StoredValue;
+}
+
+/// Migration of databases from before the compression markers, prefixes all values of the
+/// [COMPRESSED_TABLES] with the [UNCOMPRESSED] marker.
+#[derive(Debug)]
+pub struct AddCompressionMarkers;
+
+impl<DB: Database> Migration<DB> for AddCompressionMarkers {
+    fn from_version(&self) -> u64 {
+        1
+    }
+
+    fn description(&self) -> &'static str {
+        "add compression markers to block bodies, transactions and receipts"
+    }
+
+    fn migrate_batch<'a>(
+        &self,
+        tx: &<DB as DatabaseGAT<'a>>::TXMut,
+        checkpoint: Option<Vec<u8>>,
+        batch_size: usize,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        // the checkpoint is the index of the table, followed by the encoded key to continue at
+        let (index, start) = match checkpoint {
+            Some(checkpoint) if !checkpoint.is_empty() => {
+                (checkpoint[0] as usize, (checkpoint.len() > 1).then(|| checkpoint[1..].to_vec()))
+            }
+            _ => (0, None),
+        };
+        let (table, _) = *COMPRESSED_TABLES.get(index).ok_or(Error::DecodeError)?;
+
+        let next = crate::table_dispatch!(table, T => add_markers::<T, _>(tx, start, batch_size))?;
+        Ok(match next {
+            Some(key) => Some([[index as u8].as_slice(), key.as_slice()].concat()),
+            None if index + 1 < COMPRESSED_TABLES.len() => Some(vec![index as u8 + 1]),
+            None => None,
+        })
+    }
+}
+
+/// Prefixes up to `batch_size` values of `T`, starting at the encoded key `start`, with the
+/// [UNCOMPRESSED] marker. Returns the encoded key of the next value, if any.
+fn add_markers<'a, T: Table, TX: DbTx<'a> + DbTxMut<'a>>(
+    tx: &TX,
+    start: Option<Vec<u8>>,
+    batch_size: usize,
+) -> Result<Option<Vec<u8>>, Error> {
+    let mut entries = {
+        let mut cursor = tx.cursor_read::<StoredValues<T>>()?;
+        let first = match start {
+            Some(key) => cursor.seek(T::Key::decode(key)?),
+            None => cursor.first(),
+        }
+        .transpose();
+        Walker::new(&mut cursor, first).take(batch_size + 1).collect::<Result<Vec<_>, _>>()?
+    };
+    let next = if entries.len() > batch_size { entries.pop() } else { None };
+
+    for (key, StoredValue(value)) in entries {
+        let value = [[UNCOMPRESSED].as_slice(), value.as_slice()].concat();
+        tx.put::<StoredValues<T>>(key, StoredValue(value))?;
+    }
+    Ok(next.map(|(key, _)| key.encode().as_ref().to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mem::MemDatabase, version::Migrator};
+    use reth_codecs::Compact;
+    use reth_primitives::{Address, Log, Receipt, TxType, H256};
+
+    fn receipt(number: u64) -> Receipt {
+        Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            cumulative_gas_used: number * 21_000,
+            bloom: Default::default(),
+            logs: vec![Log {
+                address: Address::from_low_u64_be(number % 10),
+                topics: vec![H256::from_low_u64_be(number)],
+                data: number.to_be_bytes().to_vec().into(),
+            }],
+        }
+    }
+
+    #[test]
+    fn compress_with_dictionary() {
+        let db = MemDatabase::new();
+        db.update(|tx| {
+            for number in 0..1_000 {
+                tx.put::<tables::Receipts>(number, receipt(number)).unwrap();
+            }
+        })
+        .unwrap();
+        let stored = |number| {
+            db.tx().unwrap().get::<StoredValues<tables::Receipts>>(number).unwrap().unwrap().0
+        };
+        assert_eq!(stored(0)[0], UNCOMPRESSED);
+
+        let dictionary = train_dictionary::<tables::Receipts, _>(
+            &db.tx().unwrap(),
+            db.dictionaries(),
+            1_000,
+            1_024,
+        )
+        .unwrap();
+        store_dictionary(&db, Tables::Receipts, &dictionary).unwrap();
+        assert!(store_dictionary(&db, Tables::Receipts, &dictionary).is_err());
+        assert!(store_dictionary(&db, Tables::Logs, &dictionary).is_err());
+
+        db.update(|tx| tx.put::<tables::Receipts>(1_000, receipt(1_000)).unwrap()).unwrap();
+        assert_eq!(stored(1_000)[0], ZSTD);
+        assert!(stored(1_000).len() < stored(0).len());
+
+        // values of both kinds can be read
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.get::<tables::Receipts>(0).unwrap(), Some(receipt(0)));
+        assert_eq!(tx.get::<tables::Receipts>(1_000).unwrap(), Some(receipt(1_000)));
+    }
+
+    #[test]
+    fn dictionaries_are_per_database() {
+        let trained = MemDatabase::new();
+        let other = MemDatabase::new();
+        for db in [&trained, &other] {
+            db.update(|tx| {
+                for number in 0..1_000 {
+                    tx.put::<tables::Receipts>(number, receipt(number)).unwrap();
+                }
+            })
+            .unwrap();
+        }
+        let dictionary = train_dictionary::<tables::Receipts, _>(
+            &trained.tx().unwrap(),
+            trained.dictionaries(),
+            1_000,
+            1_024,
+        )
+        .unwrap();
+        store_dictionary(&trained, Tables::Receipts, &dictionary).unwrap();
+
+        // the other database does not compress with the dictionary of the trained one
+        other.update(|tx| tx.put::<tables::Receipts>(1_000, receipt(1_000)).unwrap()).unwrap();
+        let stored =
+            other.tx().unwrap().get::<StoredValues<tables::Receipts>>(1_000).unwrap().unwrap();
+        assert_eq!(stored.0[0], UNCOMPRESSED);
+
+        // and can not read values compressed with it
+        trained.update(|tx| tx.put::<tables::Receipts>(1_000, receipt(1_000)).unwrap()).unwrap();
+        let compressed =
+            trained.tx().unwrap().get::<StoredValues<tables::Receipts>>(1_000).unwrap().unwrap();
+        assert_eq!(compressed.0[0], ZSTD);
+        assert!(other.dictionaries().decompress(compressed.0.into()).is_err());
+    }
+
+    #[test]
+    fn reload_unknown_dictionaries() {
+        let db = MemDatabase::new();
+        db.update(|tx| {
+            for number in 0..1_000 {
+                tx.put::<tables::Receipts>(number, receipt(number)).unwrap();
+            }
+        })
+        .unwrap();
+        let dictionary = train_dictionary::<tables::Receipts, _>(
+            &db.tx().unwrap(),
+            db.dictionaries(),
+            1_000,
+            1_024,
+        )
+        .unwrap();
+        store_dictionary(&db, Tables::Receipts, &dictionary).unwrap();
+        db.update(|tx| tx.put::<tables::Receipts>(1_000, receipt(1_000)).unwrap()).unwrap();
+        let compressed =
+            db.tx().unwrap().get::<StoredValues<tables::Receipts>>(1_000).unwrap().unwrap();
+
+        // dictionaries that were loaded before the dictionary was stored
+        assert!(Dictionaries::default().decompress(compressed.0.clone().into()).is_err());
+        let reloading = Dictionaries::with_loader(move |dictionaries| {
+            dictionaries.register(Tables::Receipts, &dictionary)
+        });
+        let mut value = Vec::new();
+        receipt(1_000).to_compact(&mut value);
+        assert_eq!(reloading.decompress(compressed.0.into()).unwrap(), Bytes::from(value));
+    }
+
+    #[test]
+    fn migrate_compression_markers() {
+        let db = MemDatabase::new();
+        db.update(|tx| {
+            for number in 0..10 {
+                // values from before the markers
+                let mut value = Vec::new();
+                receipt(number).to_compact(&mut value);
+                tx.put::<StoredValues<tables::Receipts>>(number, StoredValue(value)).unwrap();
+            }
+            tx.put::<tables::CanonicalHeaders>(0, Default::default()).unwrap();
+        })
+        .unwrap();
+
+        Migrator::new(&db).with_batch_size(3).run(|_, _| {}).unwrap();
+
+        let tx = db.tx().unwrap();
+        for number in 0..10 {
+            assert_eq!(tx.get::<tables::Receipts>(number).unwrap(), Some(receipt(number)));
+        }
+    }
+}
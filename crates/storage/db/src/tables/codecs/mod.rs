@@ -3,6 +3,8 @@
 mod compact;
 pub use compact::CompactU256;
 
+pub mod compression;
+
 pub mod fuzz;
 
 mod postcard;
@@ -32,36 +32,224 @@ pub enum TableType {
     DupSort,
 }
 
-/// Default tables that should be present inside database.
-pub const TABLES: [(TableType, &str); 27] = [
-    (TableType::Table, CanonicalHeaders::const_name()),
-    (TableType::Table, HeaderTD::const_name()),
-    (TableType::Table, HeaderNumbers::const_name()),
-    (TableType::Table, Headers::const_name()),
-    (TableType::Table, BlockBodies::const_name()),
-    (TableType::Table, BlockOmmers::const_name()),
-    (TableType::Table, NonCanonicalTransactions::const_name()),
-    (TableType::Table, Transactions::const_name()),
-    (TableType::Table, TxHashNumber::const_name()),
-    (TableType::Table, Receipts::const_name()),
-    (TableType::Table, Logs::const_name()),
-    (TableType::Table, PlainAccountState::const_name()),
-    (TableType::DupSort, PlainStorageState::const_name()),
-    (TableType::Table, Bytecodes::const_name()),
-    (TableType::Table, BlockTransitionIndex::const_name()),
-    (TableType::Table, TxTransitionIndex::const_name()),
-    (TableType::Table, AccountHistory::const_name()),
-    (TableType::Table, StorageHistory::const_name()),
-    (TableType::DupSort, AccountChangeSet::const_name()),
-    (TableType::DupSort, StorageChangeSet::const_name()),
-    (TableType::Table, HashedAccount::const_name()),
-    (TableType::DupSort, HashedStorage::const_name()),
-    (TableType::Table, AccountsTrie::const_name()),
-    (TableType::DupSort, StoragesTrie::const_name()),
-    (TableType::Table, TxSenders::const_name()),
-    (TableType::Table, Config::const_name()),
-    (TableType::Table, SyncStage::const_name()),
-];
+/// Declares [TABLES] and the [Tables] selector from a single list of `(TableType, Table)` pairs.
+macro_rules! tables {
+    ($(($table_type:ident, $table:ident)),* $(,)?) => {
+        /// Default tables that should be present inside database.
+        pub const TABLES: [(TableType, &str); [$(stringify!($table)),*].len()] =
+            [$((TableType::$table_type, $table::const_name())),*];
+
+        /// Selects one of the [TABLES] at runtime, e.g. from a command line argument.
+        ///
+        /// Use [`table_dispatch`](crate::table_dispatch) to call code that is generic over the
+        /// selected table.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Tables {
+            $(
+                #[doc = concat!("See [`", stringify!($table), "`].")]
+                $table,
+            )*
+        }
+
+        impl Tables {
+            /// All tables, in the order of [TABLES].
+            pub const ALL: [Tables; TABLES.len()] = [$(Tables::$table),*];
+
+            /// Returns the name of the table as it is present inside the database.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Tables::$table => $table::const_name(),)*
+                }
+            }
+
+            /// Returns the type of the table.
+            pub fn table_type(&self) -> TableType {
+                match self {
+                    $(Tables::$table => TableType::$table_type,)*
+                }
+            }
+        }
+    };
+}
+
+tables!(
+    (Table, CanonicalHeaders),
+    (Table, HeaderTD),
+    (Table, HeaderNumbers),
+    (Table, Headers),
+    (Table, BlockBodies),
+    (Table, BlockOmmers),
+    (Table, NonCanonicalTransactions),
+    (Table, Transactions),
+    (Table, TxHashNumber),
+    (Table, Receipts),
+    (Table, Logs),
+    (Table, PlainAccountState),
+    (DupSort, PlainStorageState),
+    (Table, Bytecodes),
+    (Table, BlockTransitionIndex),
+    (Table, TxTransitionIndex),
+    (Table, AccountHistory),
+    (Table, StorageHistory),
+    (DupSort, AccountChangeSet),
+    (DupSort, StorageChangeSet),
+    (Table, HashedAccount),
+    (DupSort, HashedStorage),
+    (Table, AccountsTrie),
+    (DupSort, StoragesTrie),
+    (Table, TxSenders),
+    (Table, Config),
+    (Table, SyncStage),
+    (Table, PruneCheckpoints),
+);
+
+impl std::fmt::Display for Tables {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for Tables {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Tables::ALL
+            .into_iter()
+            .find(|table| table.name() == s)
+            .ok_or_else(|| format!("unknown table: {s}"))
+    }
+}
+
+#[macro_export]
+/// Evaluates `$body` with `$table` being the type of the table selected by a [Tables] value.
+///
+/// The match over [Tables] is exhaustive, so a table added to [TABLES] needs an arm here.
+///
+/// ```
+/// use reth_db::{table::Table, table_dispatch, Tables};
+///
+/// let selected: Tables = "Headers".parse().unwrap();
+/// let name = table_dispatch!(selected, T => T::NAME);
+/// assert_eq!(name, "Headers");
+/// ```
+macro_rules! table_dispatch {
+    ($selector:expr, $table:ident => $body:expr) => {
+        match $selector {
+            $crate::tables::Tables::CanonicalHeaders => {
+                type $table = $crate::tables::CanonicalHeaders;
+                $body
+            }
+            $crate::tables::Tables::HeaderTD => {
+                type $table = $crate::tables::HeaderTD;
+                $body
+            }
+            $crate::tables::Tables::HeaderNumbers => {
+                type $table = $crate::tables::HeaderNumbers;
+                $body
+            }
+            $crate::tables::Tables::Headers => {
+                type $table = $crate::tables::Headers;
+                $body
+            }
+            $crate::tables::Tables::BlockBodies => {
+                type $table = $crate::tables::BlockBodies;
+                $body
+            }
+            $crate::tables::Tables::BlockOmmers => {
+                type $table = $crate::tables::BlockOmmers;
+                $body
+            }
+            $crate::tables::Tables::NonCanonicalTransactions => {
+                type $table = $crate::tables::NonCanonicalTransactions;
+                $body
+            }
+            $crate::tables::Tables::Transactions => {
+                type $table = $crate::tables::Transactions;
+                $body
+            }
+            $crate::tables::Tables::TxHashNumber => {
+                type $table = $crate::tables::TxHashNumber;
+                $body
+            }
+            $crate::tables::Tables::Receipts => {
+                type $table = $crate::tables::Receipts;
+                $body
+            }
+            $crate::tables::Tables::Logs => {
+                type $table = $crate::tables::Logs;
+                $body
+            }
+            $crate::tables::Tables::PlainAccountState => {
+                type $table = $crate::tables::PlainAccountState;
+                $body
+            }
+            $crate::tables::Tables::PlainStorageState => {
+                type $table = $crate::tables::PlainStorageState;
+                $body
+            }
+            $crate::tables::Tables::Bytecodes => {
+                type $table = $crate::tables::Bytecodes;
+                $body
+            }
+            $crate::tables::Tables::BlockTransitionIndex => {
+                type $table = $crate::tables::BlockTransitionIndex;
+                $body
+            }
+            $crate::tables::Tables::TxTransitionIndex => {
+                type $table = $crate::tables::TxTransitionIndex;
+                $body
+            }
+            $crate::tables::Tables::AccountHistory => {
+                type $table = $crate::tables::AccountHistory;
+                $body
+            }
+            $crate::tables::Tables::StorageHistory => {
+                type $table = $crate::tables::StorageHistory;
+                $body
+            }
+            $crate::tables::Tables::AccountChangeSet => {
+                type $table = $crate::tables::AccountChangeSet;
+                $body
+            }
+            $crate::tables::Tables::StorageChangeSet => {
+                type $table = $crate::tables::StorageChangeSet;
+                $body
+            }
+            $crate::tables::Tables::HashedAccount => {
+                type $table = $crate::tables::HashedAccount;
+                $body
+            }
+            $crate::tables::Tables::HashedStorage => {
+                type $table = $crate::tables::HashedStorage;
+                $body
+            }
+            $crate::tables::Tables::AccountsTrie => {
+                type $table = $crate::tables::AccountsTrie;
+                $body
+            }
+            $crate::tables::Tables::StoragesTrie => {
+                type $table = $crate::tables::StoragesTrie;
+                $body
+            }
+            $crate::tables::Tables::TxSenders => {
+                type $table = $crate::tables::TxSenders;
+                $body
+            }
+            $crate::tables::Tables::Config => {
+                type $table = $crate::tables::Config;
+                $body
+            }
+            $crate::tables::Tables::SyncStage => {
+                type $table = $crate::tables::SyncStage;
+                $body
+            }
+            $crate::tables::Tables::PruneCheckpoints => {
+                type $table = $crate::tables::PruneCheckpoints;
+                $body
+            }
+        }
+    };
+}
 
 #[macro_export]
 /// Macro to declare all necessary tables.
@@ -300,6 +488,11 @@ table!(
     ( SyncStage ) StageId | BlockNumber
 );
 
+table!(
+    /// Stores the highest pruned block number of each prune part.
+    ( PruneCheckpoints ) PrunePartId | BlockNumber
+);
+
 ///
 /// Alias Types
 
@@ -307,6 +500,8 @@ table!(
 pub type TransitionList = IntegerList;
 /// Encoded stage id.
 pub type StageId = Vec<u8>;
+/// Encoded prune part, see [`reth_primitives::PrunePart::as_str`].
+pub type PrunePartId = Vec<u8>;
 
 //
 // TODO: Temporary types, until they're properly defined alongside with the Encode and Decode Trait
@@ -320,3 +515,19 @@ pub type ConfigValue = Vec<u8>;
 pub type BlockNumHashTxNumber = Vec<u8>;
 /// Temporary placeholder type for DB.
 pub type Bytecode = Vec<u8>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::Table;
+
+    #[test]
+    fn tables_selector_matches_table_list() {
+        for (table, (_, name)) in Tables::ALL.iter().zip(TABLES.iter()) {
+            assert_eq!(table.name(), *name);
+            assert_eq!(name.parse::<Tables>(), Ok(*table));
+            assert_eq!(crate::table_dispatch!(table, T => T::NAME), *name);
+        }
+        assert!("Unknown".parse::<Tables>().is_err());
+    }
+}
@@ -1,6 +1,7 @@
 //! Small database table utilities and helper functions
 use crate::{
     table::{Decode, Decompress, Table},
+    tables::codecs::compression::Dictionaries,
     Error,
 };
 use bytes::Bytes;
@@ -42,6 +43,7 @@ macro_rules! impl_fixed_arbitrary {
 
 /// Helper function to decode a `(key, value)` pair.
 pub(crate) fn decoder<'a, T>(
+    dictionaries: &Dictionaries,
     kv: (Cow<'a, [u8]>, Cow<'a, [u8]>),
 ) -> Result<(T::Key, T::Value), Error>
 where
@@ -51,22 +53,28 @@ where
 {
     Ok((
         Decode::decode(Bytes::from(kv.0.into_owned()))?,
-        Decompress::decompress(Bytes::from(kv.1.into_owned()))?,
+        Decompress::decompress_with(Bytes::from(kv.1.into_owned()), dictionaries)?,
     ))
 }
 
 /// Helper function to decode only a value from a `(key, value)` pair.
-pub(crate) fn decode_value<'a, T>(kv: (Cow<'a, [u8]>, Cow<'a, [u8]>)) -> Result<T::Value, Error>
+pub(crate) fn decode_value<'a, T>(
+    dictionaries: &Dictionaries,
+    kv: (Cow<'a, [u8]>, Cow<'a, [u8]>),
+) -> Result<T::Value, Error>
 where
     T: Table,
 {
-    Decompress::decompress(Bytes::from(kv.1.into_owned()))
+    Decompress::decompress_with(Bytes::from(kv.1.into_owned()), dictionaries)
 }
 
 /// Helper function to decode a value. It can be a key or subkey.
-pub(crate) fn decode_one<T>(value: Cow<'_, [u8]>) -> Result<T::Value, Error>
+pub(crate) fn decode_one<T>(
+    dictionaries: &Dictionaries,
+    value: Cow<'_, [u8]>,
+) -> Result<T::Value, Error>
 where
     T: Table,
 {
-    Decompress::decompress(Bytes::from(value.into_owned()))
+    Decompress::decompress_with(Bytes::from(value.into_owned()), dictionaries)
 }
@@ -0,0 +1,403 @@
+//! Versioning of the database schema.
+//!
+//! The version of the schema is stored in [tables::Config]. Opening a database with an older
+//! version fails with [DatabaseVersionError::Outdated] until the registered [Migration]s upgraded
+//! it with a [Migrator].
+
+use crate::{
+    cursor::DbCursorRO,
+    database::{Database, DatabaseGAT},
+    tables::{self, codecs::compression::AddCompressionMarkers},
+    transaction::{DbTx, DbTxMut},
+    Error,
+};
+
+/// The current version of the database schema.
+///
+/// Bump it whenever the layout of a table or the encoding of its keys or values changes, and
+/// register a [Migration] from the previous version in [migrations].
+pub const DB_VERSION: u64 = 2;
+
+/// The version of databases that were created before the schema was versioned.
+const UNVERSIONED_DB_VERSION: u64 = 1;
+
+/// The default number of entries a [Migration] processes per batch.
+const DEFAULT_BATCH_SIZE: usize = 10_000;
+
+/// The key of the schema version in [tables::Config].
+const VERSION_KEY: &[u8] = b"DatabaseVersion";
+
+/// The key of the checkpoint of an interrupted migration in [tables::Config].
+const MIGRATION_CHECKPOINT_KEY: &[u8] = b"MigrationCheckpoint";
+
+/// Errors of the database version check and migrations.
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+pub enum DatabaseVersionError {
+    /// A database error.
+    #[error(transparent)]
+    Database(#[from] Error),
+    /// The database was created by a newer client.
+    #[error("Database version {found} is newer than the supported version {supported}")]
+    TooNew {
+        /// The version of the database.
+        found: u64,
+        /// The version of this client.
+        supported: u64,
+    },
+    /// The database has to be migrated.
+    #[error(
+        "Database version {found} is outdated, version {expected} is required. Run `reth db migrate` to upgrade it"
+    )]
+    Outdated {
+        /// The version of the database.
+        found: u64,
+        /// The version of this client.
+        expected: u64,
+    },
+    /// No migration upgrades the version.
+    #[error("No migration from database version {version}")]
+    MissingMigration {
+        /// The version without a migration.
+        version: u64,
+    },
+}
+
+/// A migration of the database from one version to the next.
+///
+/// Migrations run in batches, each batch is committed together with a checkpoint so an interrupted
+/// migration resumes where it stopped.
+pub trait Migration<DB: Database>: Send + Sync {
+    /// Returns the version the migration upgrades from, the database is at the next version once
+    /// the migration completed.
+    fn from_version(&self) -> u64;
+
+    /// Returns a short description of the migration.
+    fn description(&self) -> &'static str;
+
+    /// Migrates the next batch of at most `batch_size` entries, starting at the checkpoint that
+    /// was returned by the previous batch, or `None` for the first batch.
+    ///
+    /// Returns the checkpoint of the next batch, or `None` if the migration is complete.
+    fn migrate_batch<'a>(
+        &self,
+        tx: &<DB as DatabaseGAT<'a>>::TXMut,
+        checkpoint: Option<Vec<u8>>,
+        batch_size: usize,
+    ) -> Result<Option<Vec<u8>>, Error>;
+}
+
+/// Returns all registered migrations.
+pub fn migrations<DB: Database>() -> Vec<Box<dyn Migration<DB>>> {
+    vec![Box::new(AddCompressionMarkers)]
+}
+
+/// Returns the schema version of the database, or `None` if it was not recorded yet.
+pub fn database_version<'a, TX: DbTx<'a>>(tx: &TX) -> Result<Option<u64>, Error> {
+    tx.get::<tables::Config>(VERSION_KEY.to_vec())?.map(|value| decode_u64(&value)).transpose()
+}
+
+/// Returns the schema version of the database without writing to it.
+///
+/// Empty databases are at [DB_VERSION], databases with data but no version predate the
+/// versioning. Tables that were not created yet are empty.
+pub fn probe_database_version<DB: Database>(db: &DB) -> Result<u64, Error> {
+    if db.table_exists::<tables::Config>()? {
+        if let Some(version) = database_version(&db.tx()?)? {
+            return Ok(version)
+        }
+    }
+    if db.table_exists::<tables::CanonicalHeaders>()? &&
+        db.tx()?.cursor_read::<tables::CanonicalHeaders>()?.first()?.is_some()
+    {
+        Ok(UNVERSIONED_DB_VERSION)
+    } else {
+        Ok(DB_VERSION)
+    }
+}
+
+/// Returns the schema version of the database, recording it if the database has none.
+///
+/// The tables have to exist, see [probe_database_version] for the version of unversioned
+/// databases.
+pub fn init_database_version<DB: Database>(db: &DB) -> Result<u64, Error> {
+    if let Some(version) = database_version(&db.tx()?)? {
+        return Ok(version)
+    }
+    let version = probe_database_version(db)?;
+    db.update(|tx| {
+        tx.put::<tables::Config>(VERSION_KEY.to_vec(), version.to_be_bytes().to_vec())
+    })??;
+    Ok(version)
+}
+
+/// Checks that the database has the current schema version without writing to it.
+pub fn check_database_version<DB: Database>(db: &DB) -> Result<(), DatabaseVersionError> {
+    match probe_database_version(db)? {
+        DB_VERSION => Ok(()),
+        found if found > DB_VERSION => {
+            Err(DatabaseVersionError::TooNew { found, supported: DB_VERSION })
+        }
+        found => Err(DatabaseVersionError::Outdated { found, expected: DB_VERSION }),
+    }
+}
+
+/// Upgrades the database to a target version by running migrations.
+pub struct Migrator<'a, DB: Database> {
+    db: &'a DB,
+    migrations: Vec<Box<dyn Migration<DB>>>,
+    target_version: u64,
+    batch_size: usize,
+}
+
+impl<'a, DB: Database> std::fmt::Debug for Migrator<'a, DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Migrator")
+            .field("migrations", &self.migrations.len())
+            .field("target_version", &self.target_version)
+            .field("batch_size", &self.batch_size)
+            .finish()
+    }
+}
+
+impl<'a, DB: Database> Migrator<'a, DB> {
+    /// Creates a migrator that upgrades the database to [DB_VERSION] with the registered
+    /// [migrations].
+    pub fn new(db: &'a DB) -> Self {
+        Self {
+            db,
+            migrations: migrations(),
+            target_version: DB_VERSION,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets the migrations and the version they upgrade to.
+    pub fn with_migrations(
+        mut self,
+        migrations: Vec<Box<dyn Migration<DB>>>,
+        target_version: u64,
+    ) -> Self {
+        self.migrations = migrations;
+        self.target_version = target_version;
+        self
+    }
+
+    /// Sets the number of entries migrated per batch.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Returns the migrations that upgrade the database to the target version, in order.
+    ///
+    /// This does not write to the database, so it can be called before the tables are created.
+    pub fn pending(&self) -> Result<Vec<&dyn Migration<DB>>, DatabaseVersionError> {
+        let mut version = probe_database_version(self.db)?;
+        if version > self.target_version {
+            return Err(DatabaseVersionError::TooNew {
+                found: version,
+                supported: self.target_version,
+            })
+        }
+        let mut pending = Vec::new();
+        while version < self.target_version {
+            pending.push(self.migration(version)?);
+            version += 1;
+        }
+        Ok(pending)
+    }
+
+    /// Records the version of an unversioned database and runs all pending migrations.
+    ///
+    /// `on_batch` is called with each migration and the checkpoint of its next batch after every
+    /// committed batch.
+    pub fn run(
+        &self,
+        mut on_batch: impl FnMut(&dyn Migration<DB>, Option<&[u8]>),
+    ) -> Result<(), DatabaseVersionError> {
+        init_database_version(self.db)?;
+        for migration in self.pending()? {
+            loop {
+                let tx = self.db.tx_mut()?;
+                let checkpoint = migration_checkpoint(&tx, migration.from_version())?;
+                let next = migration.migrate_batch(&tx, checkpoint, self.batch_size)?;
+                match &next {
+                    Some(checkpoint) => {
+                        let mut value = migration.from_version().to_be_bytes().to_vec();
+                        value.extend_from_slice(checkpoint);
+                        tx.put::<tables::Config>(MIGRATION_CHECKPOINT_KEY.to_vec(), value)?;
+                    }
+                    None => {
+                        tx.delete::<tables::Config>(MIGRATION_CHECKPOINT_KEY.to_vec(), None)?;
+                        let version = migration.from_version() + 1;
+                        tx.put::<tables::Config>(
+                            VERSION_KEY.to_vec(),
+                            version.to_be_bytes().to_vec(),
+                        )?;
+                    }
+                }
+                tx.commit()?;
+                on_batch(migration, next.as_deref());
+                if next.is_none() {
+                    break
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn migration(&self, version: u64) -> Result<&dyn Migration<DB>, DatabaseVersionError> {
+        self.migrations
+            .iter()
+            .find(|migration| migration.from_version() == version)
+            .map(|migration| migration.as_ref())
+            .ok_or(DatabaseVersionError::MissingMigration { version })
+    }
+}
+
+/// Returns the checkpoint of the interrupted migration from the version, if any.
+fn migration_checkpoint<'a, TX: DbTx<'a>>(tx: &TX, version: u64) -> Result<Option<Vec<u8>>, Error> {
+    let value = match tx.get::<tables::Config>(MIGRATION_CHECKPOINT_KEY.to_vec())? {
+        Some(value) if value.len() >= 8 => value,
+        _ => return Ok(None),
+    };
+    Ok((decode_u64(&value[..8])? == version).then(|| value[8..].to_vec()))
+}
+
+fn decode_u64(value: &[u8]) -> Result<u64, Error> {
+    Ok(u64::from_be_bytes(value.try_into().map_err(|_| Error::DecodeError)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cursor::DbCursorRW,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+    };
+    use reth_primitives::H256;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    type TestDb = crate::mdbx::Env<WriteMap>;
+
+    /// Moves all canonical hashes one block up, failing after `fail_after` batches.
+    struct ShiftHashes {
+        batches: AtomicUsize,
+        fail_after: usize,
+    }
+
+    impl Migration<TestDb> for ShiftHashes {
+        fn from_version(&self) -> u64 {
+            1
+        }
+
+        fn description(&self) -> &'static str {
+            "shift canonical hashes"
+        }
+
+        fn migrate_batch<'a>(
+            &self,
+            tx: &<TestDb as DatabaseGAT<'a>>::TXMut,
+            checkpoint: Option<Vec<u8>>,
+            batch_size: usize,
+        ) -> Result<Option<Vec<u8>>, Error> {
+            if self.batches.fetch_add(1, Ordering::SeqCst) == self.fail_after {
+                return Err(Error::Write(0))
+            }
+            let start = checkpoint.map(|c| decode_u64(&c)).transpose()?.unwrap_or_default();
+            let entries = tx
+                .cursor_read::<tables::CanonicalHeaders>()?
+                .walk_range(start..start + batch_size as u64)?
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut cursor = tx.cursor_write::<tables::HeaderNumbers>()?;
+            for (number, hash) in &entries {
+                cursor.upsert(*hash, number + 1)?;
+            }
+            Ok((entries.len() == batch_size)
+                .then(|| (start + batch_size as u64).to_be_bytes().to_vec()))
+        }
+    }
+
+    #[test]
+    fn version_check() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        check_database_version(&*db).unwrap();
+        assert_eq!(database_version(&db.tx().unwrap()).unwrap(), None);
+        assert_eq!(init_database_version(&*db).unwrap(), DB_VERSION);
+        assert_eq!(database_version(&db.tx().unwrap()).unwrap(), Some(DB_VERSION));
+
+        db.update(|tx| {
+            tx.put::<tables::Config>(VERSION_KEY.to_vec(), vec![0, 0, 0, 0, 0, 0, 0, 9])
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            check_database_version(&*db),
+            Err(DatabaseVersionError::TooNew { found: 9, supported: DB_VERSION })
+        );
+    }
+
+    #[test]
+    fn probe_without_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = TestDb::open(dir.path(), EnvKind::RW).unwrap();
+        assert_eq!(probe_database_version(&db).unwrap(), DB_VERSION);
+        assert!(Migrator::new(&db).pending().unwrap().is_empty());
+        check_database_version(&db).unwrap();
+        assert!(!db.table_exists::<tables::Config>().unwrap());
+
+        db.create_tables().unwrap();
+        db.update(|tx| tx.put::<tables::CanonicalHeaders>(0, H256::zero())).unwrap().unwrap();
+        assert_eq!(probe_database_version(&db).unwrap(), UNVERSIONED_DB_VERSION);
+        assert_eq!(database_version(&db.tx().unwrap()).unwrap(), None);
+    }
+
+    #[test]
+    fn resume_interrupted_migration() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let hashes = (0..10).map(|_| H256::random()).collect::<Vec<_>>();
+        db.update(|tx| {
+            for (number, hash) in hashes.iter().enumerate() {
+                tx.put::<tables::CanonicalHeaders>(number as u64, *hash).unwrap();
+            }
+        })
+        .unwrap();
+
+        let migration = || -> Vec<Box<dyn Migration<TestDb>>> {
+            vec![Box::new(ShiftHashes { batches: AtomicUsize::new(0), fail_after: 2 })]
+        };
+        let migrator = Migrator::new(&*db).with_migrations(migration(), 2).with_batch_size(4);
+        assert_eq!(migrator.pending().unwrap().len(), 1);
+        assert_eq!(migrator.run(|_, _| {}), Err(DatabaseVersionError::Database(Error::Write(0))));
+
+        // the first two batches were committed
+        let tx = db.tx().unwrap();
+        assert_eq!(database_version(&tx).unwrap(), Some(1));
+        assert_eq!(tx.get::<tables::HeaderNumbers>(hashes[7]).unwrap(), Some(8));
+        assert_eq!(tx.get::<tables::HeaderNumbers>(hashes[8]).unwrap(), None);
+        drop(tx);
+
+        let mut checkpoints = Vec::new();
+        let migrator = Migrator::new(&*db)
+            .with_migrations(
+                vec![Box::new(ShiftHashes {
+                    batches: AtomicUsize::new(0),
+                    fail_after: usize::MAX,
+                })],
+                2,
+            )
+            .with_batch_size(4);
+        migrator.run(|_, checkpoint| checkpoints.push(checkpoint.map(<[u8]>::to_vec))).unwrap();
+        assert_eq!(checkpoints, vec![None]);
+
+        let tx = db.tx().unwrap();
+        assert_eq!(database_version(&tx).unwrap(), Some(2));
+        assert_eq!(tx.get::<tables::HeaderNumbers>(hashes[9]).unwrap(), Some(10));
+        assert_eq!(migration_checkpoint(&tx, 1).unwrap(), None);
+        assert!(Migrator::new(&*db).with_migrations(migration(), 2).pending().unwrap().is_empty());
+        assert_eq!(
+            Migrator::new(&*db).with_migrations(Vec::new(), 3).pending().err(),
+            Some(DatabaseVersionError::MissingMigration { version: 2 })
+        );
+    }
+}
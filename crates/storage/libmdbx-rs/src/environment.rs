@@ -1,7 +1,7 @@
 use crate::{
     database::Database,
     error::{mdbx_result, Error, Result},
-    flags::EnvironmentFlags,
+    flags::{CopyFlags, EnvironmentFlags},
     transaction::{RO, RW},
     Mode, Transaction, TransactionKind,
 };
@@ -15,7 +15,7 @@ use std::{
     marker::PhantomData,
     mem,
     ops::{Bound, RangeBounds},
-    os::unix::ffi::OsStrExt,
+    os::unix::{ffi::OsStrExt, io::RawFd},
     path::Path,
     ptr, result,
     sync::mpsc::{sync_channel, SyncSender},
@@ -138,6 +138,29 @@ where
         mdbx_result(unsafe { ffi::mdbx_env_sync_ex(self.env(), force, false) })
     }
 
+    /// Copies the environment to a new file at `path`, e.g. to make a backup of an environment
+    /// that is in use.
+    ///
+    /// The copy is made from a read-only transaction, so it contains the last committed state.
+    /// Unless [CopyFlags::COMPACT] is set, the copy waits for an open write transaction to finish
+    /// and blocks writers while it reads the meta pages. The file must not exist yet, but its
+    /// parent directory must. No lock file is created, it is recreated when the copy is opened.
+    ///
+    /// The path may not contain the null character.
+    pub fn copy(&self, path: &Path, flags: CopyFlags) -> Result<()> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::Invalid)?;
+        mdbx_result(unsafe { ffi::mdbx_env_copy(self.env(), path.as_ptr(), flags.bits()) })?;
+        Ok(())
+    }
+
+    /// Copies the environment to the file descriptor, which must be open for writing.
+    ///
+    /// See [Environment::copy()].
+    pub fn copy_to_fd(&self, fd: RawFd, flags: CopyFlags) -> Result<()> {
+        mdbx_result(unsafe { ffi::mdbx_env_copy2fd(self.env(), fd, flags.bits()) })?;
+        Ok(())
+    }
+
     /// Retrieves statistics about this environment.
     pub fn stat(&self) -> Result<Stat> {
         unsafe {
@@ -83,6 +83,36 @@ fn test_sync() {
     }
 }
 
+#[test]
+fn test_copy() {
+    let dir = tempdir().unwrap();
+    let env = Environment::new().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+    txn.commit().unwrap();
+
+    // the copy is a snapshot of the committed state, even with a write transaction open
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+
+    let copy_dir = tempdir().unwrap();
+    let copy_path = copy_dir.path().join("mdbx.dat");
+    env.copy(&copy_path, CopyFlags::COMPACT).unwrap();
+    txn.commit().unwrap();
+
+    // the destination file must not exist yet
+    assert!(env.copy(&copy_path, CopyFlags::empty()).is_err());
+
+    let copy = Environment::new().open(copy_dir.path()).unwrap();
+    let txn = copy.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
+    assert_eq!(txn.get::<()>(&db, b"key2").unwrap(), None);
+}
+
 #[test]
 fn test_stat() {
     let dir = tempdir().unwrap();
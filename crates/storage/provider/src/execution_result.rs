@@ -1,11 +1,13 @@
+//! Output of executing blocks, as written to the database by the
+//! [BlockExecutionWriter][crate::BlockExecutionWriter].
+
 use reth_db::{models::AccountBeforeTx, tables, transaction::DbTxMut, Error as DbError};
-use reth_primitives::{Account, Address, Receipt, H256, U256};
-use revm::Bytecode;
+use reth_primitives::{Account, Address, Bytes, Receipt, H256, U256};
 use std::collections::BTreeMap;
 
 /// Execution Result containing vector of transaction changesets
 /// and block reward if present
-#[derive(Debug)]
+#[derive(Debug, Default, Clone)]
 pub struct ExecutionResult {
     /// Transaction changeset containing [Receipt], changed [Accounts][Account] and Storages.
     pub changesets: Vec<TransactionChangeSet>,
@@ -15,7 +17,7 @@ pub struct ExecutionResult {
 }
 
 /// After transaction is executed this structure contain
-/// transaction [Receipt] every change to state ([Account], Storage, [Bytecode])
+/// transaction [Receipt] every change to state ([Account], Storage, Bytecode)
 /// that this transaction made and its old values
 /// so that history account table can be updated.
 #[derive(Debug, Clone)]
@@ -25,7 +27,7 @@ pub struct TransactionChangeSet {
     /// State change that this transaction made on state.
     pub changeset: BTreeMap<Address, AccountChangeSet>,
     /// new bytecode created as result of transaction execution.
-    pub new_bytecodes: BTreeMap<H256, Bytecode>,
+    pub new_bytecodes: BTreeMap<H256, Bytes>,
 }
 
 /// Contains old/new account changes
@@ -11,17 +11,25 @@
 /// Various provider traits.
 mod traits;
 pub use traits::{
-    AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider, StateProvider,
-    StateProviderFactory,
+    AccountProvider, BlockExecutionWriter, BlockHashProvider, BlockProvider, BlockWriter,
+    HeaderProvider, NodeDataProvider, ReceiptProvider, SnapProvider, StateProvider,
+    StateProviderFactory, StateRange, TransactionsProvider,
 };
 
 /// Provider trait implementations.
 pub mod providers;
 pub use providers::{
-    HistoricalStateProvider, HistoricalStateProviderRef, LatestStateProvider,
-    LatestStateProviderRef, ShareableDatabase,
+    write_execution_result, CachedStateProvider, HistoricalStateProvider,
+    HistoricalStateProviderRef, LatestStateProvider, LatestStateProviderRef, OverlayStateProvider,
+    ShareableDatabase, StateCache, StateOverlay, StateVersion,
 };
 
+pub mod execution_result;
+
+pub mod trie;
+
+pub mod static_files;
+
 /// Common database utilities.
 mod utils;
 pub use utils::{insert_block, insert_canonical_block};
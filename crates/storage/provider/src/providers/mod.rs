@@ -1,43 +1,100 @@
-use crate::{BlockHashProvider, BlockProvider, Error, HeaderProvider, StateProviderFactory};
+use crate::{
+    static_files::StaticFileProvider, BlockHashProvider, BlockProvider, Error, HeaderProvider,
+    NodeDataProvider, ReceiptProvider, StateProviderFactory, TransactionsProvider,
+};
 use reth_db::{
+    cursor::DbCursorRO,
     database::{Database, DatabaseGAT},
+    models::{BlockNumHash, StoredBlockBody},
     tables,
     transaction::DbTx,
 };
 use reth_interfaces::Result;
-use reth_primitives::{rpc::BlockId, Block, BlockHash, BlockNumber, ChainInfo, Header, H256, U256};
+use reth_primitives::{
+    rpc::BlockId, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, Bytes,
+    ChainInfo, Header, PrunePart, Receipt, TransactionMeta, TransactionSigned, TxHash, TxNumber,
+    H256, U256,
+};
 use std::sync::Arc;
 
+mod snap;
+
 mod state;
 pub use state::{
+    cached::{
+        CachedStateProvider, StateCache, StateVersion, DEFAULT_MAX_CACHED_ACCOUNTS,
+        DEFAULT_MAX_CACHED_BYTECODES, DEFAULT_MAX_CACHED_STORAGE_SLOTS,
+    },
     historical::{HistoricalStateProvider, HistoricalStateProviderRef},
     latest::{LatestStateProvider, LatestStateProviderRef},
+    overlay::{OverlayStateProvider, StateOverlay},
 };
 
+mod writer;
+pub use writer::write_execution_result;
+
 /// A common provider that fetches data from a database.
 ///
 /// This provider implements most provider or provider factory traits.
 pub struct ShareableDatabase<DB> {
     /// Database
     db: Arc<DB>,
+    /// Static files with the finalized block data that was moved out of the database.
+    static_files: Option<Arc<StaticFileProvider>>,
+    /// Cache of state reads, shared by all state providers.
+    state_cache: StateCache,
 }
 
 impl<DB> ShareableDatabase<DB> {
     /// create new database provider
     pub fn new(db: Arc<DB>) -> Self {
-        Self { db }
+        Self { db, static_files: None, state_cache: StateCache::disabled() }
+    }
+
+    /// Reads block data that is missing from the database from the static files.
+    pub fn with_static_files(mut self, static_files: Arc<StaticFileProvider>) -> Self {
+        self.static_files = Some(static_files);
+        self
+    }
+
+    /// Caches the reads of the state providers in the given cache.
+    pub fn with_state_cache(mut self, state_cache: StateCache) -> Self {
+        self.state_cache = state_cache;
+        self
+    }
+
+    fn static_files(&self) -> Option<&StaticFileProvider> {
+        self.static_files.as_deref()
+    }
+}
+
+impl<DB: Database> ShareableDatabase<DB> {
+    /// Converts the [BlockId] into a [BlockHashOrNumber]. Returns `None` for the pending block.
+    fn convert_block_id(&self, id: BlockId) -> Result<Option<BlockHashOrNumber>> {
+        match id {
+            BlockId::Hash(hash) => Ok(Some(H256(hash.0).into())),
+            BlockId::Number(num) => Ok(self.convert_block_number(num)?.map(Into::into)),
+        }
     }
 }
 
 impl<DB> Clone for ShareableDatabase<DB> {
     fn clone(&self) -> Self {
-        Self { db: Arc::clone(&self.db) }
+        Self {
+            db: Arc::clone(&self.db),
+            static_files: self.static_files.clone(),
+            state_cache: self.state_cache.clone(),
+        }
     }
 }
 
 impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
     fn header(&self, block_hash: &BlockHash) -> Result<Option<Header>> {
-        self.db.view(|tx| tx.get::<tables::Headers>((0, *block_hash).into()))?.map_err(Into::into)
+        let tx = self.db.tx()?;
+        match tx.get::<tables::HeaderNumbers>(*block_hash)? {
+            Some(number) => read_header(&tx, self.static_files(), (number, *block_hash).into()),
+            None => Ok(None),
+        }
     }
 
     fn header_by_number(&self, num: BlockNumber) -> Result<Option<Header>> {
@@ -51,7 +108,11 @@ impl<DB: Database> HeaderProvider for ShareableDatabase<DB> {
     fn header_td(&self, hash: &BlockHash) -> Result<Option<U256>> {
         if let Some(num) = self.db.view(|tx| tx.get::<tables::HeaderNumbers>(*hash))?? {
             let td = self.db.view(|tx| tx.get::<tables::HeaderTD>((num, *hash).into()))??;
-            Ok(td.map(|v| v.0))
+            match (td, self.static_files()) {
+                (Some(td), _) => Ok(Some(td.0)),
+                (None, Some(static_files)) => Ok(static_files.header_td(num)?),
+                (None, None) => Ok(None),
+            }
         } else {
             Ok(None)
         }
@@ -69,17 +130,52 @@ impl<DB: Database> BlockHashProvider for ShareableDatabase<DB> {
 
 impl<DB: Database> BlockProvider for ShareableDatabase<DB> {
     fn chain_info(&self) -> Result<ChainInfo> {
-        Ok(ChainInfo {
-            best_hash: Default::default(),
-            best_number: 0,
-            last_finalized: None,
-            safe_finalized: None,
-        })
+        // the best block is the highest canonical block
+        let (best_number, best_hash) = self
+            .db
+            .view(|tx| tx.cursor_read::<tables::CanonicalHeaders>()?.last())??
+            .unwrap_or_default();
+        Ok(ChainInfo { best_hash, best_number, last_finalized: None, safe_finalized: None })
+    }
+
+    fn block(&self, id: BlockId) -> Result<Option<Block>> {
+        let block = match self.convert_block_id(id)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let tx = self.db.tx()?;
+        match read_block_num_hash(&tx, block)? {
+            Some(num_hash) => {
+                Ok(read_block(&tx, self.static_files(), num_hash)?.map(|(block, _)| block))
+            }
+            None => Ok(None),
+        }
     }
 
-    fn block(&self, _id: BlockId) -> Result<Option<Block>> {
-        // TODO
-        Ok(None)
+    fn block_with_senders(&self, id: BlockId) -> Result<Option<BlockWithSenders>> {
+        let block = match self.convert_block_id(id)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let tx = self.db.tx()?;
+        let (block, body) = match read_block_num_hash(&tx, block)? {
+            Some(num_hash) => match read_block(&tx, self.static_files(), num_hash)? {
+                Some(block) => block,
+                None => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+
+        let mut senders = Vec::with_capacity(block.body.len());
+        for (tx_id, transaction) in body.tx_id_range().zip(block.body.iter()) {
+            let sender = match tx.get::<tables::TxSenders>(tx_id)? {
+                Some(sender) => sender,
+                // the senders of the block were not recovered yet
+                None => transaction.recover_signer().ok_or(Error::TransactionSender { tx_id })?,
+            };
+            senders.push(sender);
+        }
+        Ok(BlockWithSenders::new(block, senders))
     }
 
     fn block_number(&self, hash: H256) -> Result<Option<BlockNumber>> {
@@ -87,23 +183,140 @@ impl<DB: Database> BlockProvider for ShareableDatabase<DB> {
     }
 }
 
+impl<DB: Database> TransactionsProvider for ShareableDatabase<DB> {
+    fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
+        let tx = self.db.tx()?;
+        match read_transaction_id(&tx, hash)? {
+            Some(tx_id) => read_transaction(&tx, self.static_files(), tx_id),
+            None => Ok(None),
+        }
+    }
+
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        let tx = self.db.tx()?;
+        let tx_id = match read_transaction_id(&tx, hash)? {
+            Some(tx_id) => tx_id,
+            None => return Ok(None),
+        };
+        let transaction = match read_transaction(&tx, self.static_files(), tx_id)? {
+            Some(transaction) => transaction,
+            None => return Ok(None),
+        };
+        let (num_hash, body) = match read_transaction_block(&tx, tx_id)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let meta = TransactionMeta {
+            tx_hash: hash,
+            index: tx_id - body.start_tx_id,
+            block_hash: num_hash.hash(),
+            block_number: num_hash.number(),
+        };
+        Ok(Some((transaction, meta)))
+    }
+
+    fn transaction_by_block_and_index(
+        &self,
+        block: BlockHashOrNumber,
+        index: u64,
+    ) -> Result<Option<TransactionSigned>> {
+        let tx = self.db.tx()?;
+        let body = match read_block_body(&tx, block)? {
+            Some((_, body)) => body,
+            None => return Ok(None),
+        };
+        if index >= body.tx_count {
+            return Ok(None)
+        }
+        read_transaction(&tx, self.static_files(), body.start_tx_id + index)
+    }
+
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        let tx = self.db.tx()?;
+        match read_block_body(&tx, block)? {
+            Some((_, body)) => Ok(Some(read_transactions(&tx, self.static_files(), &body)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<DB: Database> ReceiptProvider for ShareableDatabase<DB> {
+    fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>> {
+        let tx = self.db.tx()?;
+        let tx_id = match read_transaction_id(&tx, hash)? {
+            Some(tx_id) => tx_id,
+            None => return Ok(None),
+        };
+        let receipt = read_receipt(&tx, self.static_files(), tx_id)?;
+        if receipt.is_none() {
+            if let Some((num_hash, _)) = read_transaction_block(&tx, tx_id)? {
+                ensure_not_pruned(&tx, PrunePart::Receipts, num_hash.number())?;
+            }
+        }
+        Ok(receipt)
+    }
+
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        let tx = self.db.tx()?;
+        let (num_hash, body) = match read_block_body(&tx, block)? {
+            Some(body) => body,
+            None => return Ok(None),
+        };
+        ensure_not_pruned(&tx, PrunePart::Receipts, num_hash.number())?;
+
+        let mut receipts = Vec::with_capacity(body.tx_count as usize);
+        for tx_id in body.tx_id_range() {
+            match read_receipt(&tx, self.static_files(), tx_id)? {
+                Some(receipt) => receipts.push(receipt),
+                // the block was not executed yet
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(receipts))
+    }
+}
+
+impl<DB: Database> NodeDataProvider for ShareableDatabase<DB> {
+    fn node_data(&self, hash: H256) -> Result<Option<Bytes>> {
+        let tx = self.db.tx()?;
+        if let Some(code) = tx.get::<tables::Bytecodes>(hash)? {
+            return Ok(Some(code.into()))
+        }
+        Ok(tx.get::<tables::AccountsTrie>(hash)?.map(Into::into))
+    }
+}
+
 impl<DB: Database> StateProviderFactory for ShareableDatabase<DB> {
-    type HistorySP<'a> = HistoricalStateProvider<'a,<DB as DatabaseGAT<'a>>::TX> where Self: 'a;
-    type LatestSP<'a> = LatestStateProvider<'a,<DB as DatabaseGAT<'a>>::TX> where Self: 'a;
+    type HistorySP<'a> = CachedStateProvider<HistoricalStateProvider<'a,<DB as DatabaseGAT<'a>>::TX>> where Self: 'a;
+    type LatestSP<'a> = CachedStateProvider<LatestStateProvider<'a,<DB as DatabaseGAT<'a>>::TX>> where Self: 'a;
     /// Storage provider for latest block
     fn latest(&self) -> Result<Self::LatestSP<'_>> {
-        Ok(LatestStateProvider::new(self.db.tx()?))
+        let tx = self.db.tx()?;
+        let version = StateVersion::latest(&tx)?;
+        Ok(CachedStateProvider::new(
+            LatestStateProvider::new(tx),
+            self.state_cache.clone(),
+            version,
+        ))
     }
 
     fn history_by_block_number(&self, block_number: BlockNumber) -> Result<Self::HistorySP<'_>> {
         let tx = self.db.tx()?;
+        ensure_history_not_pruned(&tx, block_number)?;
 
         // get transition id
         let transition = tx
             .get::<tables::BlockTransitionIndex>(block_number)?
             .ok_or(Error::BlockTransition { block_number })?;
 
-        Ok(HistoricalStateProvider::new(tx, transition))
+        let state = HistoricalStateProvider::new(tx, transition);
+        Ok(CachedStateProvider::bytecodes_only(state, self.state_cache.clone()))
     }
 
     fn history_by_block_hash(&self, block_hash: BlockHash) -> Result<Self::HistorySP<'_>> {
@@ -124,22 +337,228 @@ impl<DB: Database> StateProviderFactory for ShareableDatabase<DB> {
             }
             .into())
         }
+        ensure_history_not_pruned(&tx, block_number)?;
 
         // get transition id
         let transition = tx
             .get::<tables::BlockTransitionIndex>(block_number)?
             .ok_or(Error::BlockTransition { block_number })?;
 
-        Ok(HistoricalStateProvider::new(tx, transition))
+        let state = HistoricalStateProvider::new(tx, transition);
+        Ok(CachedStateProvider::bytecodes_only(state, self.state_cache.clone()))
+    }
+}
+
+/// Returns an error if the `part` of the block was pruned.
+fn ensure_not_pruned<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    part: PrunePart,
+    block_number: BlockNumber,
+) -> Result<()> {
+    match tx.get::<tables::PruneCheckpoints>(part.as_str().as_bytes().to_vec())? {
+        Some(pruned) if block_number <= pruned => Err(Error::Pruned { part, block_number }.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the number of the transaction with the given hash, if it is known.
+///
+/// Returns an error for unknown hashes once the transaction lookup was pruned, since the
+/// transaction may be in one of the pruned blocks.
+fn read_transaction_id<'a, TX: DbTx<'a>>(tx: &TX, hash: TxHash) -> Result<Option<TxNumber>> {
+    if let Some(tx_id) = tx.get::<tables::TxHashNumber>(hash)? {
+        return Ok(Some(tx_id))
+    }
+    let part = PrunePart::TransactionLookup;
+    match tx.get::<tables::PruneCheckpoints>(part.as_str().as_bytes().to_vec())? {
+        Some(block_number) => Err(Error::Pruned { part, block_number }.into()),
+        None => Ok(None),
     }
 }
 
+/// Returns an error if the state history after the block was pruned.
+///
+/// The state at a block is reverted with the changesets of all blocks after it, so the state at
+/// the highest pruned block is still available.
+fn ensure_history_not_pruned<'a, TX: DbTx<'a>>(tx: &TX, block_number: BlockNumber) -> Result<()> {
+    for part in [PrunePart::AccountHistory, PrunePart::StorageHistory] {
+        ensure_not_pruned(tx, part, block_number.saturating_add(1))?;
+    }
+    Ok(())
+}
+
+/// Returns the number and hash of the block, if it is known.
+///
+/// Block numbers are resolved via the canonical chain.
+fn read_block_num_hash<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    block: BlockHashOrNumber,
+) -> Result<Option<BlockNumHash>> {
+    Ok(match block {
+        BlockHashOrNumber::Hash(hash) => {
+            tx.get::<tables::HeaderNumbers>(hash)?.map(|number| (number, hash).into())
+        }
+        BlockHashOrNumber::Number(number) => {
+            tx.get::<tables::CanonicalHeaders>(number)?.map(|hash| (number, hash).into())
+        }
+    })
+}
+
+/// Returns the stored body of the block, if the block and its body are known.
+fn read_block_body<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    block: BlockHashOrNumber,
+) -> Result<Option<(BlockNumHash, StoredBlockBody)>> {
+    let num_hash = match read_block_num_hash(tx, block)? {
+        Some(num_hash) => num_hash,
+        None => return Ok(None),
+    };
+    Ok(tx.get::<tables::BlockBodies>(num_hash)?.map(|body| (num_hash, body)))
+}
+
+/// Returns the header of the block.
+///
+/// Headers are only removed from the database after they were moved to static files, and only
+/// canonical headers are moved, so a known header that is missing from the database is the
+/// canonical header in the static files.
+fn read_header<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    static_files: Option<&StaticFileProvider>,
+    num_hash: BlockNumHash,
+) -> Result<Option<Header>> {
+    match (tx.get::<tables::Headers>(num_hash)?, static_files) {
+        (Some(header), _) => Ok(Some(header)),
+        (None, Some(static_files)) => Ok(static_files.header(num_hash.number())?),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Returns the full block together with its stored body, if the header and body are known.
+fn read_block<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    static_files: Option<&StaticFileProvider>,
+    num_hash: BlockNumHash,
+) -> Result<Option<(Block, StoredBlockBody)>> {
+    let header = match read_header(tx, static_files, num_hash)? {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    let body = match tx.get::<tables::BlockBodies>(num_hash)? {
+        Some(body) => body,
+        None => return Ok(None),
+    };
+    let ommers = tx.get::<tables::BlockOmmers>(num_hash)?.map(|o| o.ommers).unwrap_or_default();
+    let transactions = read_transactions(tx, static_files, &body)?;
+    Ok(Some((Block { header, body: transactions, ommers }, body)))
+}
+
+/// Returns the transaction from the database or the static files.
+fn read_transaction<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    static_files: Option<&StaticFileProvider>,
+    tx_id: TxNumber,
+) -> Result<Option<TransactionSigned>> {
+    match (tx.get::<tables::Transactions>(tx_id)?, static_files) {
+        (Some(transaction), _) => Ok(Some(transaction)),
+        (None, Some(static_files)) => Ok(static_files.transaction(tx_id)?),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Returns all transactions of the body, in block order.
+fn read_transactions<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    static_files: Option<&StaticFileProvider>,
+    body: &StoredBlockBody,
+) -> Result<Vec<TransactionSigned>> {
+    let mut transactions = Vec::with_capacity(body.tx_count as usize);
+    for tx_id in body.tx_id_range() {
+        let transaction =
+            read_transaction(tx, static_files, tx_id)?.ok_or(Error::Transaction { tx_id })?;
+        transactions.push(transaction);
+    }
+    Ok(transactions)
+}
+
+/// Returns the receipt of the transaction, if it was executed.
+///
+/// If the receipt was stored without logs, the logs are read from [tables::Logs]. Receipts that
+/// were moved to static files are stored with their logs.
+pub(crate) fn read_receipt<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    static_files: Option<&StaticFileProvider>,
+    tx_id: TxNumber,
+) -> Result<Option<Receipt>> {
+    let mut receipt = match (tx.get::<tables::Receipts>(tx_id)?, static_files) {
+        (Some(receipt), _) => receipt,
+        (None, Some(static_files)) => return Ok(static_files.receipt(tx_id)?),
+        (None, None) => return Ok(None),
+    };
+    if receipt.logs.is_empty() {
+        if let Some(logs) = tx.get::<tables::Logs>(tx_id)? {
+            receipt.logs = logs.logs;
+        }
+    }
+    Ok(Some(receipt))
+}
+
+/// Returns the canonical block that contains the transaction, together with its stored body.
+///
+/// The transaction ids of canonical blocks are increasing, so this is a binary search over the
+/// canonical chain.
+fn read_transaction_block<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    tx_id: TxNumber,
+) -> Result<Option<(BlockNumHash, StoredBlockBody)>> {
+    let best_number = match tx.cursor_read::<tables::CanonicalHeaders>()?.last()? {
+        Some((number, _)) => number,
+        None => return Ok(None),
+    };
+
+    // find the highest block whose first transaction id is not above `tx_id`
+    let (mut low, mut high) = (0, best_number);
+    let mut found = None;
+    while low <= high {
+        let mid = low + (high - low) / 2;
+        match read_block_body(tx, mid.into())? {
+            Some((num_hash, body)) if body.start_tx_id <= tx_id => {
+                found = Some((num_hash, body));
+                low = mid + 1;
+            }
+            // either the block starts above `tx_id` or its body is not downloaded yet, in which
+            // case the bodies above it are missing too
+            _ => {
+                if mid == 0 {
+                    break
+                }
+                high = mid - 1;
+            }
+        }
+    }
+
+    Ok(found.filter(|(_, body)| body.tx_id_range().contains(&tx_id)))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::StateProviderFactory;
+    use crate::{
+        insert_canonical_block, BlockProvider, ReceiptProvider, StateProviderFactory,
+        TransactionsProvider,
+    };
 
     use super::ShareableDatabase;
-    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
+    use crate::Error;
+    use reth_db::{
+        database::Database,
+        mdbx::{test_utils::create_test_db, EnvKind, WriteMap},
+        tables,
+        transaction::DbTxMut,
+    };
+    use reth_interfaces::test_utils::generators::random_block;
+    use reth_primitives::{
+        rpc::{self, BlockId},
+        PrunePart, Receipt, H256,
+    };
 
     #[test]
     fn common_history_provider() {
@@ -147,4 +566,136 @@ mod tests {
         let provider = ShareableDatabase::new(db);
         let _ = provider.latest();
     }
+
+    #[test]
+    fn block_transactions_and_receipts() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        // the second block is empty
+        let blocks = [
+            random_block(0, None, Some(2), Some(0)),
+            random_block(1, None, Some(0), Some(1)),
+            random_block(2, None, Some(3), Some(0)),
+        ];
+        db.update(|tx| {
+            for block in blocks.iter() {
+                insert_canonical_block(tx, block, false).unwrap();
+            }
+            for tx_id in 0..5 {
+                tx.put::<tables::Receipts>(
+                    tx_id,
+                    Receipt { cumulative_gas_used: tx_id, ..Default::default() },
+                )
+                .unwrap();
+                tx.put::<tables::TxHashNumber>(
+                    blocks.iter().flat_map(|b| b.body.iter()).nth(tx_id as usize).unwrap().hash,
+                    tx_id,
+                )
+                .unwrap();
+            }
+        })
+        .unwrap();
+        let provider = ShareableDatabase::new(db);
+
+        for block in blocks.iter() {
+            let id = BlockId::Hash(rpc::H256(block.hash().0));
+            let expected = block.clone().unseal();
+            assert_eq!(provider.block(id).unwrap(), Some(expected.clone()));
+            let number = BlockId::Number(rpc::BlockNumber::Number(block.number.into()));
+            assert_eq!(provider.block(number).unwrap(), Some(expected.clone()));
+
+            let with_senders = provider.block_with_senders(id).unwrap().unwrap();
+            assert_eq!(with_senders.block, expected);
+            let senders: Vec<_> =
+                block.body.iter().map(|tx| tx.recover_signer().unwrap()).collect();
+            assert_eq!(with_senders.senders, senders);
+
+            assert_eq!(
+                provider.transactions_by_block(block.number.into()).unwrap(),
+                Some(block.body.clone())
+            );
+        }
+
+        let last = &blocks[2];
+        let transaction = &last.body[1];
+        assert_eq!(
+            provider.transaction_by_hash(transaction.hash).unwrap().as_ref(),
+            Some(transaction)
+        );
+        let (found, meta) =
+            provider.transaction_by_hash_with_meta(transaction.hash).unwrap().unwrap();
+        assert_eq!(&found, transaction);
+        assert_eq!(meta.index, 1);
+        assert_eq!(meta.block_number, 2);
+        assert_eq!(meta.block_hash, last.hash());
+        assert_eq!(
+            provider.transaction_by_block_and_index(last.hash().into(), 1).unwrap().as_ref(),
+            Some(transaction)
+        );
+        assert_eq!(provider.transaction_by_block_and_index(last.hash().into(), 3).unwrap(), None);
+        assert_eq!(provider.transaction_by_hash(H256::random()).unwrap(), None);
+
+        assert_eq!(
+            provider.receipt_by_hash(transaction.hash).unwrap().unwrap().cumulative_gas_used,
+            3
+        );
+        let receipts = provider.receipts_by_block(last.number.into()).unwrap().unwrap();
+        assert_eq!(receipts.iter().map(|r| r.cumulative_gas_used).collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(provider.receipts_by_block_hash(blocks[1].hash()).unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn pruned_data() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let blocks = [
+            random_block(0, None, Some(1), Some(0)),
+            random_block(1, None, Some(1), Some(0)),
+            random_block(2, None, Some(1), Some(0)),
+        ];
+        db.update(|tx| {
+            for block in blocks.iter() {
+                insert_canonical_block(tx, block, false).unwrap();
+            }
+            // receipts of block 0 and the state history of block 1 are pruned
+            tx.put::<tables::Receipts>(1, Receipt::default()).unwrap();
+            tx.put::<tables::Receipts>(2, Receipt::default()).unwrap();
+            tx.put::<tables::TxHashNumber>(blocks[0].body[0].hash, 0).unwrap();
+            tx.put::<tables::PruneCheckpoints>(PrunePart::Receipts.as_str().into(), 0).unwrap();
+            tx.put::<tables::PruneCheckpoints>(PrunePart::AccountHistory.as_str().into(), 1)
+                .unwrap();
+            tx.put::<tables::PruneCheckpoints>(PrunePart::TransactionLookup.as_str().into(), 1)
+                .unwrap();
+        })
+        .unwrap();
+        let provider = ShareableDatabase::new(db);
+
+        let pruned = |part, block_number| {
+            reth_interfaces::Error::Provider(Error::Pruned { part, block_number })
+        };
+        assert_eq!(
+            provider.receipts_by_block(0.into()).unwrap_err(),
+            pruned(PrunePart::Receipts, 0)
+        );
+        assert_eq!(
+            provider.receipt_by_hash(blocks[0].body[0].hash).unwrap_err(),
+            pruned(PrunePart::Receipts, 0)
+        );
+        assert_eq!(provider.receipts_by_block(1.into()).unwrap().map(|r| r.len()), Some(1));
+
+        assert!(provider.transaction_by_hash(blocks[0].body[0].hash).unwrap().is_some());
+        assert_eq!(
+            provider.transaction_by_hash(blocks[1].body[0].hash).unwrap_err(),
+            pruned(PrunePart::TransactionLookup, 1)
+        );
+        assert_eq!(
+            provider.transaction_by_hash_with_meta(H256::random()).unwrap_err(),
+            pruned(PrunePart::TransactionLookup, 1)
+        );
+
+        assert_eq!(
+            provider.history_by_block_number(0).err(),
+            Some(pruned(PrunePart::AccountHistory, 1))
+        );
+        assert!(provider.history_by_block_number(1).is_ok());
+        assert!(provider.history_by_block_hash(blocks[2].hash()).is_ok());
+    }
 }
@@ -0,0 +1,447 @@
+//! Serves the latest state from the hashed state and trie tables, as requested by the `snap`
+//! protocol.
+
+use super::ShareableDatabase;
+use crate::{SnapProvider, StateRange};
+use reth_db::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    database::Database,
+    tables,
+    transaction::DbTx,
+};
+use reth_interfaces::Result;
+use reth_primitives::{proofs::EMPTY_ROOT, Bytes, StorageEntry, H256, KECCAK_EMPTY, U256};
+use reth_rlp::{Encodable, Header, RlpDecodable, RlpEncodable, EMPTY_LIST_CODE};
+
+impl<DB: Database> SnapProvider for ShareableDatabase<DB> {
+    fn account_range(
+        &self,
+        root: H256,
+        origin: H256,
+        limit: H256,
+        max_bytes: usize,
+    ) -> Result<Option<StateRange>> {
+        let tx = self.db.tx()?;
+        if !is_latest_state_root(&tx, root)? {
+            return Ok(None)
+        }
+        let get_node =
+            |hash: H256| -> Result<Option<Vec<u8>>> { Ok(tx.get::<tables::AccountsTrie>(hash)?) };
+
+        let mut range = StateRange::default();
+        let mut size = 0;
+        let mut cursor = tx.cursor_read::<tables::HashedAccount>()?;
+        for entry in cursor.walk(origin)? {
+            let (hash, _) = entry?;
+            let Some(account) = trie_account(&get_node, root, hash)? else {
+                // the trie is not in sync with the hashed state
+                return Ok(None)
+            };
+            let body = account.slim();
+            size += 32 + body.len();
+            range.entries.push((hash, body));
+            if hash >= limit || size >= max_bytes {
+                break
+            }
+        }
+
+        let last = range.entries.last().map(|(hash, _)| *hash);
+        range.proof = range_proof(&get_node, root, origin, last)?;
+        Ok(Some(range))
+    }
+
+    fn storage_range(
+        &self,
+        root: H256,
+        account: H256,
+        origin: H256,
+        limit: H256,
+        max_bytes: usize,
+    ) -> Result<Option<StateRange>> {
+        let tx = self.db.tx()?;
+        if !is_latest_state_root(&tx, root)? {
+            return Ok(None)
+        }
+        let get_account_node =
+            |hash: H256| -> Result<Option<Vec<u8>>> { Ok(tx.get::<tables::AccountsTrie>(hash)?) };
+        let Some(storage_root) =
+            trie_account(&get_account_node, root, account)?.map(|account| account.storage_root)
+        else {
+            return Ok(None)
+        };
+        let mut range = StateRange::default();
+        if storage_root == EMPTY_ROOT {
+            return Ok(Some(range))
+        }
+        let get_node = |hash: H256| storage_trie_node(&tx, account, hash);
+
+        let mut size = 0;
+        let mut truncated = false;
+        let mut cursor = tx.cursor_dup_read::<tables::HashedStorage>()?;
+        let mut entry = cursor.seek_by_key_subkey(account, origin)?;
+        while let Some(StorageEntry { key, value }) = entry {
+            let mut data = Vec::new();
+            value.encode(&mut data);
+            size += 32 + data.len();
+            range.entries.push((key, data.into()));
+            if key >= limit || size >= max_bytes {
+                // the range has to be proven if there are slots left after it
+                truncated = cursor.next_dup_val()?.is_some();
+                break
+            }
+            entry = cursor.next_dup_val()?;
+        }
+
+        if origin != H256::zero() || truncated {
+            let last = range.entries.last().map(|(hash, _)| *hash);
+            range.proof = range_proof(&get_node, storage_root, origin, last)?;
+        }
+        Ok(Some(range))
+    }
+
+    fn bytecode(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        Ok(self.db.view(|tx| tx.get::<tables::Bytecodes>(code_hash))??.map(Into::into))
+    }
+
+    fn trie_nodes(&self, root: H256, paths: &[Bytes]) -> Result<Option<Vec<Bytes>>> {
+        let tx = self.db.tx()?;
+        if !is_latest_state_root(&tx, root)? {
+            return Ok(None)
+        }
+        let get_account_node =
+            |hash: H256| -> Result<Option<Vec<u8>>> { Ok(tx.get::<tables::AccountsTrie>(hash)?) };
+
+        let mut nodes = Vec::new();
+        match paths {
+            [] => {}
+            [path] => {
+                if let Some(node) = node_at_compact_path(&get_account_node, root, path)? {
+                    nodes.push(node)
+                }
+            }
+            [account, storage_paths @ ..] => {
+                if account.len() != 32 {
+                    return Ok(Some(nodes))
+                }
+                let account = H256::from_slice(account);
+                let Some(storage_root) = trie_account(&get_account_node, root, account)?
+                    .map(|account| account.storage_root)
+                else {
+                    return Ok(Some(nodes))
+                };
+                let get_node = |hash: H256| storage_trie_node(&tx, account, hash);
+                for path in storage_paths {
+                    match node_at_compact_path(&get_node, storage_root, path)? {
+                        Some(node) => nodes.push(node),
+                        None => break,
+                    }
+                }
+            }
+        }
+        Ok(Some(nodes))
+    }
+}
+
+/// Returns true if the given root is the state root of the latest canonical block, which is the
+/// only state the hashed state and trie tables hold.
+fn is_latest_state_root<'a, TX: DbTx<'a>>(tx: &TX, root: H256) -> Result<bool> {
+    let Some((number, hash)) = tx.cursor_read::<tables::CanonicalHeaders>()?.last()? else {
+        return Ok(false)
+    };
+    let Some(header) = tx.get::<tables::Headers>((number, hash).into())? else { return Ok(false) };
+    Ok(header.state_root == root && tx.get::<tables::AccountsTrie>(root)?.is_some())
+}
+
+/// Returns the node with the given hash from the storage trie of the given account.
+fn storage_trie_node<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    account: H256,
+    hash: H256,
+) -> Result<Option<Vec<u8>>> {
+    Ok(tx
+        .cursor_dup_read::<tables::StoragesTrie>()?
+        .seek_by_key_subkey(account, hash)?
+        .filter(|entry| entry.hash == hash)
+        .map(|entry| entry.node))
+}
+
+/// An account as it's stored in the leaves of the account trie.
+#[derive(Debug, Clone, RlpEncodable, RlpDecodable)]
+struct TrieAccount {
+    nonce: u64,
+    balance: U256,
+    storage_root: H256,
+    code_hash: H256,
+}
+
+/// The "slim" account encoding of the `snap` protocol.
+#[derive(Debug, Clone, RlpEncodable)]
+struct SlimAccount {
+    nonce: u64,
+    balance: U256,
+    storage_root: Bytes,
+    code_hash: Bytes,
+}
+
+impl TrieAccount {
+    /// Encodes the account in the "slim" format, with an empty storage root and code hash if the
+    /// account has no storage or code.
+    fn slim(&self) -> Bytes {
+        let slim = SlimAccount {
+            nonce: self.nonce,
+            balance: self.balance,
+            storage_root: if self.storage_root == EMPTY_ROOT {
+                Bytes::default()
+            } else {
+                self.storage_root.as_bytes().to_vec().into()
+            },
+            code_hash: if self.code_hash == KECCAK_EMPTY {
+                Bytes::default()
+            } else {
+                self.code_hash.as_bytes().to_vec().into()
+            },
+        };
+        let mut buf = Vec::new();
+        slim.encode(&mut buf);
+        buf.into()
+    }
+}
+
+/// Returns the account with the given hash from the account trie with the given root.
+fn trie_account<F>(get_node: F, root: H256, hash: H256) -> Result<Option<TrieAccount>>
+where
+    F: Fn(H256) -> Result<Option<Vec<u8>>>,
+{
+    let value = follow_path(get_node, root, &to_nibbles(hash.as_bytes()))?.value;
+    Ok(value.and_then(|value| reth_rlp::Decodable::decode(&mut value.as_slice()).ok()))
+}
+
+/// Returns the proof for the first and last key of a range, without duplicate nodes.
+fn range_proof<F>(get_node: F, root: H256, first: H256, last: Option<H256>) -> Result<Vec<Bytes>>
+where
+    F: Fn(H256) -> Result<Option<Vec<u8>>>,
+{
+    let mut proof = follow_path(&get_node, root, &to_nibbles(first.as_bytes()))?.proof;
+    if let Some(last) = last.filter(|last| *last != first) {
+        for node in follow_path(&get_node, root, &to_nibbles(last.as_bytes()))?.proof {
+            if !proof.contains(&node) {
+                proof.push(node);
+            }
+        }
+    }
+    Ok(proof)
+}
+
+/// Returns the node at the given compact encoded path of the trie with the given root.
+fn node_at_compact_path<F>(get_node: F, root: H256, path: &[u8]) -> Result<Option<Bytes>>
+where
+    F: Fn(H256) -> Result<Option<Vec<u8>>>,
+{
+    match decode_compact(path) {
+        Some((path, _)) => Ok(follow_path(get_node, root, &path)?.node),
+        None => Ok(None),
+    }
+}
+
+/// The trie nodes visited while following a path from the root of a trie.
+#[derive(Debug, Default)]
+struct TriePath {
+    /// The nodes referenced by hash along the path, starting with the root. These make up the
+    /// merkle proof of the path.
+    proof: Vec<Bytes>,
+    /// The node at the exact position of the path, if any.
+    node: Option<Bytes>,
+    /// The value of the leaf the path leads to, if any.
+    value: Option<Vec<u8>>,
+}
+
+/// Follows the path of nibbles from the root of the trie, as far as the trie goes.
+///
+/// Nodes that are missing or can't be decoded end the path.
+fn follow_path<F>(get_node: F, root: H256, mut path: &[u8]) -> Result<TriePath>
+where
+    F: Fn(H256) -> Result<Option<Vec<u8>>>,
+{
+    let mut res = TriePath::default();
+    let Some(mut node) = get_node(root)? else { return Ok(res) };
+    res.proof.push(node.clone().into());
+
+    loop {
+        if path.is_empty() {
+            res.node = Some(node.clone().into());
+        }
+        let child = match decode_node(&node) {
+            Some(TrieNode::Leaf(key, value)) => {
+                if key == path {
+                    res.value = Some(value.to_vec());
+                }
+                return Ok(res)
+            }
+            Some(TrieNode::Extension(key, child)) if path.starts_with(&key) => {
+                path = &path[key.len()..];
+                child.to_vec()
+            }
+            Some(TrieNode::Branch(children)) if !path.is_empty() => {
+                let child = children[path[0] as usize].to_vec();
+                path = &path[1..];
+                child
+            }
+            _ => return Ok(res),
+        };
+
+        node = if child.first().map_or(false, |b| *b >= EMPTY_LIST_CODE) {
+            // nodes shorter than 32 bytes are embedded in their parent
+            child
+        } else {
+            match rlp_string(&child) {
+                Some(hash) if hash.len() == 32 => match get_node(H256::from_slice(hash))? {
+                    Some(node) => {
+                        res.proof.push(node.clone().into());
+                        node
+                    }
+                    None => return Ok(res),
+                },
+                _ => return Ok(res),
+            }
+        };
+    }
+}
+
+/// A decoded trie node, referencing the encoded node.
+#[derive(Debug)]
+enum TrieNode<'a> {
+    /// The encoded references to the 16 children of a branch node.
+    Branch([&'a [u8]; 16]),
+    /// The nibbles of an extension node and the encoded reference to its child.
+    Extension(Vec<u8>, &'a [u8]),
+    /// The remaining nibbles of the key of a leaf and its value.
+    Leaf(Vec<u8>, &'a [u8]),
+}
+
+/// Decodes a branch, extension or leaf node.
+fn decode_node(node: &[u8]) -> Option<TrieNode<'_>> {
+    let mut buf = node;
+    let header = Header::decode(&mut buf).ok()?;
+    if !header.list {
+        return None
+    }
+    let mut payload = buf.get(..header.payload_length)?;
+
+    let mut items = Vec::with_capacity(17);
+    while !payload.is_empty() {
+        let mut item = payload;
+        let header = Header::decode(&mut item).ok()?;
+        let len = payload.len() - item.len() + header.payload_length;
+        items.push(payload.get(..len)?);
+        payload = &payload[len..];
+    }
+
+    match items.len() {
+        2 => {
+            let (key, is_leaf) = decode_compact(rlp_string(items[0])?)?;
+            if is_leaf {
+                Some(TrieNode::Leaf(key, rlp_string(items[1])?))
+            } else {
+                Some(TrieNode::Extension(key, items[1]))
+            }
+        }
+        17 => Some(TrieNode::Branch(items[..16].try_into().ok()?)),
+        _ => None,
+    }
+}
+
+/// Returns the payload of an RLP encoded string.
+fn rlp_string(item: &[u8]) -> Option<&[u8]> {
+    let mut buf = item;
+    let header = Header::decode(&mut buf).ok()?;
+    if header.list {
+        return None
+    }
+    buf.get(..header.payload_length)
+}
+
+/// Splits the bytes into nibbles.
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Decodes a compact (hex-prefix) encoded path into its nibbles and whether it's the path of a
+/// leaf.
+fn decode_compact(compact: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let first = *compact.first()?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return None
+    }
+    let mut nibbles = Vec::with_capacity(compact.len() * 2);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(to_nibbles(&compact[1..]));
+    Some((nibbles, flag & 2 == 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::keccak256;
+    use std::collections::HashMap;
+
+    fn encode_node(items: &[&[u8]]) -> Vec<u8> {
+        let payload_length = items.iter().map(|item| item.len()).sum();
+        let mut out = Vec::new();
+        Header { list: true, payload_length }.encode(&mut out);
+        items.iter().for_each(|item| out.extend_from_slice(item));
+        out
+    }
+
+    fn encode_string(bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        bytes.encode(&mut out);
+        out
+    }
+
+    #[test]
+    fn compact_paths() {
+        assert_eq!(decode_compact(&[0x00, 0x12]), Some((vec![1, 2], false)));
+        assert_eq!(decode_compact(&[0x11, 0x23]), Some((vec![1, 2, 3], false)));
+        assert_eq!(decode_compact(&[0x20]), Some((vec![], true)));
+        assert_eq!(decode_compact(&[0x3f]), Some((vec![0xf], true)));
+        assert_eq!(decode_compact(&[0x40]), None);
+        assert_eq!(decode_compact(&[]), None);
+    }
+
+    #[test]
+    fn follow_branch_to_leaf() {
+        let value = vec![0xab; 40];
+        // leaf below child 0x1 of the root branch, with the remaining 63 nibbles as key
+        let key = H256::repeat_byte(0x11);
+        let mut leaf_path = vec![0x31];
+        leaf_path.extend_from_slice(&key.as_bytes()[1..]);
+        let leaf = encode_node(&[&encode_string(&leaf_path), &encode_string(&value)]);
+        let leaf_hash = keccak256(&leaf);
+
+        let empty = encode_string(&[]);
+        let leaf_ref = encode_string(leaf_hash.as_bytes());
+        let mut children = vec![empty.as_slice(); 17];
+        children[1] = leaf_ref.as_slice();
+        let branch = encode_node(&children);
+        let root = keccak256(&branch);
+
+        let nodes = HashMap::from([(root, branch.clone()), (leaf_hash, leaf.clone())]);
+        let get_node = |hash: H256| -> Result<Option<Vec<u8>>> { Ok(nodes.get(&hash).cloned()) };
+
+        let res = follow_path(&get_node, root, &to_nibbles(key.as_bytes())).unwrap();
+        assert_eq!(res.value, Some(value));
+        assert_eq!(res.proof, vec![Bytes::from(branch.clone()), Bytes::from(leaf.clone())]);
+
+        // a key below an empty child of the branch only proves the branch
+        let res = follow_path(&get_node, root, &to_nibbles(&[0x22; 32])).unwrap();
+        assert_eq!(res.value, None);
+        assert_eq!(res.proof, vec![Bytes::from(branch.clone())]);
+
+        assert_eq!(node_at_compact_path(&get_node, root, &[0x00]).unwrap(), Some(branch.into()));
+        assert_eq!(node_at_compact_path(&get_node, root, &[0x11]).unwrap(), Some(leaf.into()));
+        assert_eq!(node_at_compact_path(&get_node, root, &[0x12]).unwrap(), None);
+    }
+}
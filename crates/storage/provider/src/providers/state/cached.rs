@@ -0,0 +1,293 @@
+use crate::{AccountProvider, BlockHashProvider, StateProvider};
+use lru::LruCache;
+use parking_lot::Mutex;
+use reth_db::{cursor::DbCursorRO, tables, transaction::DbTx};
+use reth_interfaces::Result;
+use reth_primitives::{Account, Address, Bytes, StorageKey, StorageValue, H256, U256};
+use std::{num::NonZeroUsize, sync::Arc};
+
+/// Default number of accounts kept in the [StateCache].
+pub const DEFAULT_MAX_CACHED_ACCOUNTS: usize = 100_000;
+/// Default number of storage slots kept in the [StateCache].
+pub const DEFAULT_MAX_CACHED_STORAGE_SLOTS: usize = 1_000_000;
+/// Default number of bytecodes kept in the [StateCache].
+pub const DEFAULT_MAX_CACHED_BYTECODES: usize = 10_000;
+
+/// Key of the execution stage in [tables::SyncStage], the stage that writes the plain state
+/// during sync.
+const EXECUTION_STAGE: &[u8] = b"Execution";
+
+/// Identifies the latest state of the database.
+///
+/// The plain state is written either by the execution stage, together with its checkpoint, or
+/// together with the canonical blocks it belongs to, see
+/// [BlockExecutionWriter](crate::BlockExecutionWriter). The state can only change if the hash of
+/// the executed block or the canonical tip changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateVersion {
+    /// Hash of the block the execution stage is at.
+    executed: Option<H256>,
+    /// Hash of the canonical tip.
+    tip: Option<H256>,
+}
+
+impl StateVersion {
+    /// Reads the version of the latest state from the transaction.
+    pub fn latest<'a, TX: DbTx<'a>>(tx: &TX) -> Result<Self> {
+        let executed = match tx.get::<tables::SyncStage>(EXECUTION_STAGE.to_vec())? {
+            Some(number) => tx.get::<tables::CanonicalHeaders>(number)?,
+            None => None,
+        };
+        let tip = tx.cursor_read::<tables::CanonicalHeaders>()?.last()?.map(|(_, hash)| hash);
+        Ok(Self { executed, tip })
+    }
+}
+
+/// The cached accounts and storage slots of a single version of the state.
+#[derive(Debug)]
+struct VersionedState {
+    /// The version of the state the entries belong to.
+    version: Option<StateVersion>,
+    /// Cached accounts, `None` if the account does not exist.
+    accounts: LruCache<Address, Option<Account>>,
+    /// Cached storage slots, `None` if the slot is not set.
+    storage: LruCache<(Address, StorageKey), Option<StorageValue>>,
+}
+
+impl VersionedState {
+    /// Drops all entries if the version changed.
+    fn advance(&mut self, version: StateVersion) {
+        if self.version != Some(version) {
+            self.accounts.clear();
+            self.storage.clear();
+            self.version = Some(version);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct StateCacheInner {
+    /// Accounts and storage slots of the latest state.
+    state: Mutex<VersionedState>,
+    /// Bytecodes by their hash, these never change and are shared by all states.
+    bytecodes: Mutex<LruCache<H256, Bytes>>,
+}
+
+/// A shareable LRU cache of state reads, see [CachedStateProvider].
+///
+/// Bytecodes are keyed by their hash and can be cached for any state, including the uncommitted
+/// state of the executor. Accounts and storage slots are only cached for the latest state, and
+/// dropped as soon as a provider for a newer [StateVersion] is created.
+#[derive(Debug, Clone)]
+pub struct StateCache {
+    /// The cached entries, `None` if caching is disabled.
+    inner: Option<Arc<StateCacheInner>>,
+}
+
+impl StateCache {
+    /// Creates a new cache that holds up to the given number of entries.
+    pub fn new(
+        max_accounts: NonZeroUsize,
+        max_storage_slots: NonZeroUsize,
+        max_bytecodes: NonZeroUsize,
+    ) -> Self {
+        let state = VersionedState {
+            version: None,
+            accounts: LruCache::new(max_accounts),
+            storage: LruCache::new(max_storage_slots),
+        };
+        let inner = StateCacheInner {
+            state: Mutex::new(state),
+            bytecodes: Mutex::new(LruCache::new(max_bytecodes)),
+        };
+        Self { inner: Some(Arc::new(inner)) }
+    }
+
+    /// Creates a cache that does not store anything.
+    pub fn disabled() -> Self {
+        Self { inner: None }
+    }
+
+    /// Drops all cached accounts and storage slots.
+    ///
+    /// This is only needed if the plain state was changed without changing its [StateVersion].
+    pub fn clear(&self) {
+        if let Some(inner) = &self.inner {
+            let mut state = inner.state.lock();
+            state.accounts.clear();
+            state.storage.clear();
+            state.version = None;
+        }
+    }
+
+    /// Marks `version` as the latest state.
+    fn advance(&self, version: StateVersion) {
+        if let Some(inner) = &self.inner {
+            inner.state.lock().advance(version)
+        }
+    }
+
+    /// Runs `f` on the cached state if it is at `version`.
+    fn with_state<R>(
+        &self,
+        version: StateVersion,
+        f: impl FnOnce(&mut VersionedState) -> R,
+    ) -> Option<R> {
+        let mut state = self.inner.as_ref()?.state.lock();
+        (state.version == Some(version)).then(|| f(&mut state))
+    }
+
+    fn account(&self, version: StateVersion, address: Address) -> Option<Option<Account>> {
+        self.with_state(version, |state| state.accounts.get(&address).copied()).flatten()
+    }
+
+    fn insert_account(&self, version: StateVersion, address: Address, account: Option<Account>) {
+        self.with_state(version, |state| state.accounts.put(address, account));
+    }
+
+    fn storage(
+        &self,
+        version: StateVersion,
+        key: (Address, StorageKey),
+    ) -> Option<Option<StorageValue>> {
+        self.with_state(version, |state| state.storage.get(&key).copied()).flatten()
+    }
+
+    fn insert_storage(
+        &self,
+        version: StateVersion,
+        key: (Address, StorageKey),
+        value: Option<StorageValue>,
+    ) {
+        self.with_state(version, |state| state.storage.put(key, value));
+    }
+
+    fn bytecode(&self, code_hash: H256) -> Option<Bytes> {
+        self.inner.as_ref()?.bytecodes.lock().get(&code_hash).cloned()
+    }
+
+    fn insert_bytecode(&self, code_hash: H256, bytecode: Bytes) {
+        if let Some(inner) = &self.inner {
+            inner.bytecodes.lock().put(code_hash, bytecode);
+        }
+    }
+}
+
+impl Default for StateCache {
+    fn default() -> Self {
+        Self::new(
+            NonZeroUsize::new(DEFAULT_MAX_CACHED_ACCOUNTS).unwrap(),
+            NonZeroUsize::new(DEFAULT_MAX_CACHED_STORAGE_SLOTS).unwrap(),
+            NonZeroUsize::new(DEFAULT_MAX_CACHED_BYTECODES).unwrap(),
+        )
+    }
+}
+
+/// A [StateProvider] that reads through a [StateCache].
+#[derive(Debug)]
+pub struct CachedStateProvider<SP> {
+    /// The underlying state.
+    state: SP,
+    /// The shared cache.
+    cache: StateCache,
+    /// The version of the state, `None` if only bytecodes are cached.
+    version: Option<StateVersion>,
+}
+
+impl<SP: StateProvider> CachedStateProvider<SP> {
+    /// Caches all reads of the latest state, which is at the given version.
+    pub fn new(state: SP, cache: StateCache, version: StateVersion) -> Self {
+        cache.advance(version);
+        Self { state, cache, version: Some(version) }
+    }
+
+    /// Only caches bytecodes, used for historical or uncommitted state.
+    pub fn bytecodes_only(state: SP, cache: StateCache) -> Self {
+        Self { state, cache, version: None }
+    }
+}
+
+impl<SP: StateProvider> AccountProvider for CachedStateProvider<SP> {
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        let Some(version) = self.version else { return self.state.basic_account(address) };
+        if let Some(account) = self.cache.account(version, address) {
+            return Ok(account)
+        }
+        let account = self.state.basic_account(address)?;
+        self.cache.insert_account(version, address, account);
+        Ok(account)
+    }
+}
+
+impl<SP: StateProvider> BlockHashProvider for CachedStateProvider<SP> {
+    fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+        self.state.block_hash(number)
+    }
+}
+
+impl<SP: StateProvider> StateProvider for CachedStateProvider<SP> {
+    fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        let Some(version) = self.version else { return self.state.storage(account, storage_key) };
+        if let Some(value) = self.cache.storage(version, (account, storage_key)) {
+            return Ok(value)
+        }
+        let value = self.state.storage(account, storage_key)?;
+        self.cache.insert_storage(version, (account, storage_key), value);
+        Ok(value)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        if let Some(bytecode) = self.cache.bytecode(code_hash) {
+            return Ok(Some(bytecode))
+        }
+        let bytecode = self.state.bytecode_by_hash(code_hash)?;
+        if let Some(bytecode) = &bytecode {
+            self.cache.insert_bytecode(code_hash, bytecode.clone());
+        }
+        Ok(bytecode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ExtendedAccount, MockEthProvider};
+
+    fn version(tip: u64) -> StateVersion {
+        StateVersion { executed: None, tip: Some(H256::from_low_u64_be(tip)) }
+    }
+
+    #[test]
+    fn caches_latest_state_per_version() {
+        let provider = MockEthProvider::default();
+        let address = Address::from_low_u64_be(1);
+        let bytecode = Bytes::from(vec![0x60, 0x00]);
+        provider.extend_accounts([(
+            address,
+            ExtendedAccount::new(1, U256::from(1)).with_bytecode(bytecode.clone()),
+        )]);
+        let code_hash = provider.basic_account(address).unwrap().unwrap().bytecode_hash.unwrap();
+
+        let cache = StateCache::default();
+        let state = CachedStateProvider::new(&provider, cache.clone(), version(1));
+        assert_eq!(state.basic_account(address).unwrap().map(|a| a.nonce), Some(1));
+        assert_eq!(state.bytecode_by_hash(code_hash).unwrap(), Some(bytecode.clone()));
+
+        // changes are not visible until the version changes
+        provider.extend_accounts([(address, ExtendedAccount::new(2, U256::from(1)))]);
+        let state = CachedStateProvider::new(&provider, cache.clone(), version(1));
+        assert_eq!(state.basic_account(address).unwrap().map(|a| a.nonce), Some(1));
+
+        let state = CachedStateProvider::new(&provider, cache.clone(), version(2));
+        assert_eq!(state.basic_account(address).unwrap().map(|a| a.nonce), Some(2));
+        // bytecodes outlive the version
+        assert_eq!(state.bytecode_by_hash(code_hash).unwrap(), Some(bytecode));
+
+        // providers of an older version bypass the cache
+        let stale = MockEthProvider::default();
+        let state = CachedStateProvider::new(&provider, cache.clone(), version(2));
+        let old = CachedStateProvider { state: &stale, cache, version: Some(version(1)) };
+        assert_eq!(old.basic_account(address).unwrap(), None);
+        assert_eq!(state.basic_account(address).unwrap().map(|a| a.nonce), Some(2));
+    }
+}
@@ -1,3 +1,5 @@
 //! [StateProvider](crate::StateProvider) implementations
+pub(crate) mod cached;
 pub(crate) mod historical;
 pub(crate) mod latest;
+pub(crate) mod overlay;
@@ -0,0 +1,206 @@
+use crate::{
+    execution_result::{AccountInfoChangeSet, ExecutionResult, TransactionChangeSet},
+    AccountProvider, BlockHashProvider, StateProvider,
+};
+use reth_interfaces::Result;
+use reth_primitives::{Account, Address, Bytes, StorageKey, StorageValue, H256, U256};
+use std::collections::HashMap;
+
+/// Uncommitted state changes, applied in order of execution.
+///
+/// Used together with [OverlayStateProvider] to chain the execution of transactions or blocks on
+/// top of a state without writing to the database.
+#[derive(Debug, Clone, Default)]
+pub struct StateOverlay {
+    /// Changed accounts, `None` if the account was destroyed.
+    accounts: HashMap<Address, Option<Account>>,
+    /// Changed storage of accounts.
+    storage: HashMap<Address, OverlayStorage>,
+    /// Bytecodes of newly created contracts.
+    bytecodes: HashMap<H256, Bytes>,
+}
+
+/// The changed storage of an account.
+#[derive(Debug, Clone, Default)]
+struct OverlayStorage {
+    /// Whether the storage of the account was wiped before the changed slots were written.
+    wiped: bool,
+    /// Changed storage slots.
+    slots: HashMap<StorageKey, StorageValue>,
+}
+
+impl StateOverlay {
+    /// Applies the changes of an executed block, including the block reward.
+    pub fn apply_execution_result(&mut self, result: &ExecutionResult) {
+        for changeset in result.changesets.iter() {
+            self.apply_transaction_changeset(changeset);
+        }
+        for (address, changeset) in result.block_reward.iter().flatten() {
+            self.apply_account_changeset(*address, changeset);
+        }
+    }
+
+    /// Applies the changes of an executed transaction.
+    pub fn apply_transaction_changeset(&mut self, changeset: &TransactionChangeSet) {
+        for (address, account_changeset) in changeset.changeset.iter() {
+            self.apply_account_changeset(*address, &account_changeset.account);
+
+            let storage = self.storage.entry(*address).or_default();
+            if account_changeset.wipe_storage {
+                storage.wiped = true;
+                storage.slots.clear();
+            }
+            for (key, (_, new_value)) in account_changeset.storage.iter() {
+                storage.slots.insert(H256(key.to_be_bytes()), *new_value);
+            }
+        }
+
+        for (hash, bytecode) in changeset.new_bytecodes.iter() {
+            self.bytecodes.insert(*hash, bytecode.clone());
+        }
+    }
+
+    /// Applies the change of a single account.
+    pub fn apply_account_changeset(&mut self, address: Address, changeset: &AccountInfoChangeSet) {
+        match changeset {
+            AccountInfoChangeSet::Created { new } | AccountInfoChangeSet::Changed { new, .. } => {
+                self.accounts.insert(address, Some(*new));
+            }
+            AccountInfoChangeSet::Destroyed { .. } => {
+                self.accounts.insert(address, None);
+            }
+            AccountInfoChangeSet::NoChange => {}
+        }
+    }
+
+    /// Returns the changed account, if any.
+    ///
+    /// The inner option is `None` if the account was destroyed.
+    pub fn account(&self, address: &Address) -> Option<Option<Account>> {
+        self.accounts.get(address).copied()
+    }
+
+    /// Returns the changed storage value, if any.
+    ///
+    /// The inner option is `None` if the storage of the account was wiped.
+    pub fn storage(&self, address: &Address, key: &StorageKey) -> Option<Option<StorageValue>> {
+        let storage = self.storage.get(address)?;
+        match storage.slots.get(key) {
+            Some(value) => Some(Some(*value)),
+            None if storage.wiped => Some(None),
+            None => None,
+        }
+    }
+
+    /// Returns the bytecode of a newly created contract.
+    pub fn bytecode(&self, code_hash: &H256) -> Option<&Bytes> {
+        self.bytecodes.get(code_hash)
+    }
+}
+
+/// A [StateProvider] that layers a [StateOverlay] on top of another state.
+#[derive(Debug)]
+pub struct OverlayStateProvider<'a, SP> {
+    /// The underlying state.
+    state: SP,
+    /// The changes on top of the state.
+    overlay: &'a StateOverlay,
+}
+
+impl<'a, SP: StateProvider> OverlayStateProvider<'a, SP> {
+    /// Create new overlay state provider
+    pub fn new(state: SP, overlay: &'a StateOverlay) -> Self {
+        Self { state, overlay }
+    }
+}
+
+impl<'a, SP: StateProvider> AccountProvider for OverlayStateProvider<'a, SP> {
+    fn basic_account(&self, address: Address) -> Result<Option<Account>> {
+        if let Some(account) = self.overlay.account(&address) {
+            return Ok(account)
+        }
+        self.state.basic_account(address)
+    }
+}
+
+impl<'a, SP: StateProvider> BlockHashProvider for OverlayStateProvider<'a, SP> {
+    fn block_hash(&self, number: U256) -> Result<Option<H256>> {
+        self.state.block_hash(number)
+    }
+}
+
+impl<'a, SP: StateProvider> StateProvider for OverlayStateProvider<'a, SP> {
+    fn storage(&self, account: Address, storage_key: StorageKey) -> Result<Option<StorageValue>> {
+        if let Some(value) = self.overlay.storage(&account, &storage_key) {
+            return Ok(value)
+        }
+        self.state.storage(account, storage_key)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        if let Some(bytecode) = self.overlay.bytecode(&code_hash) {
+            return Ok(Some(bytecode.clone()))
+        }
+        self.state.bytecode_by_hash(code_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        execution_result::AccountChangeSet,
+        test_utils::{ExtendedAccount, MockEthProvider},
+    };
+    use reth_primitives::Receipt;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn overlays_changes() {
+        let provider = MockEthProvider::default();
+        let unchanged = Address::from_low_u64_be(1);
+        let changed = Address::from_low_u64_be(2);
+        let destroyed = Address::from_low_u64_be(3);
+        provider.extend_accounts([
+            (unchanged, ExtendedAccount::new(1, U256::from(1))),
+            (changed, ExtendedAccount::new(1, U256::from(1))),
+            (destroyed, ExtendedAccount::new(1, U256::from(1))),
+        ]);
+
+        let old = Account { nonce: 1, balance: U256::from(1), bytecode_hash: None };
+        let new = Account { nonce: 2, balance: U256::from(2), bytecode_hash: None };
+        let slot = U256::from(7);
+
+        let mut changeset = BTreeMap::new();
+        changeset.insert(
+            changed,
+            AccountChangeSet {
+                account: AccountInfoChangeSet::Changed { new, old },
+                storage: BTreeMap::from([(slot, (U256::ZERO, U256::from(42)))]),
+                wipe_storage: false,
+            },
+        );
+        changeset.insert(
+            destroyed,
+            AccountChangeSet {
+                account: AccountInfoChangeSet::Destroyed { old },
+                storage: BTreeMap::new(),
+                wipe_storage: true,
+            },
+        );
+
+        let mut overlay = StateOverlay::default();
+        overlay.apply_transaction_changeset(&TransactionChangeSet {
+            receipt: Receipt::default(),
+            changeset,
+            new_bytecodes: BTreeMap::new(),
+        });
+
+        let state = OverlayStateProvider::new(&provider, &overlay);
+        assert_eq!(state.basic_account(unchanged).unwrap(), Some(old));
+        assert_eq!(state.basic_account(changed).unwrap(), Some(new));
+        assert_eq!(state.basic_account(destroyed).unwrap(), None);
+        assert_eq!(state.storage(changed, H256(slot.to_be_bytes())).unwrap(), Some(U256::from(42)));
+        assert_eq!(state.storage(destroyed, H256(slot.to_be_bytes())).unwrap(), None);
+    }
+}
@@ -0,0 +1,933 @@
+use crate::{
+    execution_result::{AccountChangeSet, ExecutionResult},
+    trie::DBTrieLoader,
+    BlockExecutionWriter, BlockWriter, Error,
+};
+use itertools::Itertools;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
+    models::{
+        sharded_key::NUM_OF_INDICES_IN_SHARD, storage_sharded_key::StorageShardedKey, BlockNumHash,
+        ShardedKey, StoredBlockBody, StoredBlockOmmers, TransitionIdAddress,
+    },
+    tables,
+    transaction::{DbTx, DbTxMut},
+    TransitionList,
+};
+use reth_interfaces::Result;
+use reth_primitives::{
+    keccak256, Account, Address, BlockNumber, Header, SealedBlock, StorageEntry, TransitionId,
+    TxNumber, H256, U256,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+use tracing::trace;
+
+impl<'tx, TX: DbTx<'tx> + DbTxMut<'tx>> BlockWriter for TX {
+    fn insert_block(
+        &self,
+        block: &SealedBlock,
+        senders: Option<Vec<Address>>,
+        has_block_reward: bool,
+    ) -> Result<()> {
+        let number = block.number;
+        let hash = block.hash();
+
+        let parent_td = if number == 0 {
+            U256::ZERO
+        } else {
+            let parent_number = number - 1;
+            let parent_hash = self
+                .get::<tables::CanonicalHeaders>(parent_number)?
+                .ok_or(Error::BlockNumber { block_number: parent_number })?;
+            if parent_hash != block.parent_hash {
+                return Err(Error::NonCanonicalBlock {
+                    block_number: parent_number,
+                    expected_hash: parent_hash,
+                    received_hash: block.parent_hash,
+                }
+                .into())
+            }
+            self.get::<tables::HeaderTD>((parent_number, parent_hash).into())?
+                .ok_or(Error::TotalDifficulty { block_number: parent_number })?
+                .0
+        };
+        let (start_tx_id, mut transition_id) = next_block_ids(self, number)?;
+
+        trace!(target: "provider::writer", number, ?hash, txs = block.body.len(), "Inserting block");
+        let key = BlockNumHash((number, hash));
+        self.put::<tables::CanonicalHeaders>(number, hash)?;
+        self.put::<tables::Headers>(key, block.header.as_ref().clone())?;
+        self.put::<tables::HeaderNumbers>(hash, number)?;
+        self.put::<tables::HeaderTD>(key, (parent_td + block.difficulty).into())?;
+        if !block.ommers.is_empty() {
+            self.put::<tables::BlockOmmers>(
+                key,
+                StoredBlockOmmers {
+                    ommers: block.ommers.iter().map(|h| h.as_ref().clone()).collect(),
+                },
+            )?;
+        }
+        self.put::<tables::BlockBodies>(
+            key,
+            StoredBlockBody { start_tx_id, tx_count: block.body.len() as u64 },
+        )?;
+
+        for (index, transaction) in block.body.iter().enumerate() {
+            let tx_id = start_tx_id + index as u64;
+            let sender = match senders.as_ref().and_then(|senders| senders.get(index)) {
+                Some(sender) => *sender,
+                None => transaction.recover_signer().ok_or(Error::TransactionSender { tx_id })?,
+            };
+            self.put::<tables::TxSenders>(tx_id, sender)?;
+            self.put::<tables::TxHashNumber>(transaction.hash, tx_id)?;
+            self.put::<tables::Transactions>(tx_id, transaction.clone())?;
+            self.put::<tables::TxTransitionIndex>(tx_id, transition_id)?;
+            transition_id += 1;
+        }
+
+        if has_block_reward {
+            transition_id += 1;
+        }
+        self.put::<tables::BlockTransitionIndex>(number, transition_id)?;
+        Ok(())
+    }
+
+    fn remove_blocks_above(&self, block: BlockNumber) -> Result<()> {
+        let removed = self
+            .cursor_read::<tables::CanonicalHeaders>()?
+            .walk(block.saturating_add(1))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        for (number, hash) in removed.into_iter().rev() {
+            trace!(target: "provider::writer", number, ?hash, "Removing block");
+            let key = BlockNumHash((number, hash));
+            if let Some(body) = self.get::<tables::BlockBodies>(key)? {
+                for tx_id in body.tx_id_range() {
+                    if let Some(transaction) = self.get::<tables::Transactions>(tx_id)? {
+                        self.delete::<tables::TxHashNumber>(transaction.hash, None)?;
+                    }
+                    self.delete::<tables::Transactions>(tx_id, None)?;
+                    self.delete::<tables::TxSenders>(tx_id, None)?;
+                    self.delete::<tables::TxTransitionIndex>(tx_id, None)?;
+                    self.delete::<tables::Receipts>(tx_id, None)?;
+                    self.delete::<tables::Logs>(tx_id, None)?;
+                }
+            }
+            self.delete::<tables::BlockBodies>(key, None)?;
+            self.delete::<tables::BlockOmmers>(key, None)?;
+            self.delete::<tables::BlockTransitionIndex>(number, None)?;
+            self.delete::<tables::HeaderTD>(key, None)?;
+            self.delete::<tables::Headers>(key, None)?;
+            self.delete::<tables::HeaderNumbers>(hash, None)?;
+            self.delete::<tables::CanonicalHeaders>(number, None)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'tx, TX: DbTx<'tx> + DbTxMut<'tx>> BlockExecutionWriter for TX {
+    fn append_block_with_execution(
+        &self,
+        block: &SealedBlock,
+        senders: Option<Vec<Address>>,
+        result: ExecutionResult,
+        spurious_dragon_active: bool,
+    ) -> Result<()> {
+        let number = block.number;
+        let (start_tx_id, from_transition) = next_block_ids(self, number)?;
+        self.insert_block(block, senders, result.block_reward.is_some())?;
+
+        let to_transition = write_execution_result(
+            self,
+            Some(start_tx_id),
+            from_transition,
+            result,
+            spurious_dragon_active,
+        )?;
+        let transitions = from_transition..to_transition;
+        hash_state(self, transitions.clone())?;
+        index_history(self, transitions.clone())?;
+
+        let loader = DBTrieLoader::default();
+        let state_root = if number == 0 {
+            loader.calculate_root(self)?
+        } else {
+            let parent_root = canonical_header(self, number - 1)?.state_root;
+            if transitions.is_empty() {
+                parent_root
+            } else {
+                loader.update_root(self, parent_root, transitions)?
+            }
+        };
+        if state_root != block.state_root {
+            return Err(Error::StateRootMismatch {
+                block_number: number,
+                got: state_root,
+                expected: block.state_root,
+            }
+            .into())
+        }
+        Ok(())
+    }
+
+    fn unwind_blocks_above(&self, block: BlockNumber) -> Result<()> {
+        let tip = match self.cursor_read::<tables::CanonicalHeaders>()?.last()? {
+            Some((tip, _)) if tip > block => tip,
+            _ => return Ok(()),
+        };
+
+        let from_transition = self
+            .get::<tables::BlockTransitionIndex>(block)?
+            .ok_or(Error::BlockTransition { block_number: block })?;
+        let to_transition = self
+            .get::<tables::BlockTransitionIndex>(tip)?
+            .ok_or(Error::BlockTransition { block_number: tip })?;
+        let transitions = from_transition..to_transition;
+
+        if !transitions.is_empty() {
+            trace!(target: "provider::writer", block, tip, ?transitions, "Reverting state");
+            revert_plain_state(self, transitions.clone())?;
+            revert_hashed_state(self, transitions.clone())?;
+
+            // the root node of the target state may still exist while nodes below it were
+            // removed by later updates, so the trie is always updated to the reverted state
+            let target_root = canonical_header(self, block)?.state_root;
+            let tip_root = canonical_header(self, tip)?.state_root;
+            let state_root =
+                DBTrieLoader::default().update_root(self, tip_root, transitions.clone())?;
+            if state_root != target_root {
+                return Err(Error::StateRootMismatch {
+                    block_number: block,
+                    got: state_root,
+                    expected: target_root,
+                }
+                .into())
+            }
+
+            unwind_history(self, transitions)?;
+            remove_changesets(self, from_transition)?;
+        }
+
+        self.remove_blocks_above(block)
+    }
+}
+
+/// Returns the first transaction id and the first transition of the block, based on its parent.
+fn next_block_ids<'tx, TX: DbTx<'tx>>(
+    tx: &TX,
+    number: BlockNumber,
+) -> Result<(TxNumber, TransitionId)> {
+    if number == 0 {
+        return Ok((0, 0))
+    }
+    let parent_number = number - 1;
+    let parent_hash = tx
+        .get::<tables::CanonicalHeaders>(parent_number)?
+        .ok_or(Error::BlockNumber { block_number: parent_number })?;
+    let parent_body = tx
+        .get::<tables::BlockBodies>((parent_number, parent_hash).into())?
+        .ok_or(Error::BlockBody { block_number: parent_number, block_hash: parent_hash })?;
+    let transition = tx
+        .get::<tables::BlockTransitionIndex>(parent_number)?
+        .ok_or(Error::BlockTransition { block_number: parent_number })?;
+    Ok((parent_body.start_tx_id + parent_body.tx_count, transition))
+}
+
+/// Returns the header of the canonical block.
+fn canonical_header<'tx, TX: DbTx<'tx>>(tx: &TX, number: BlockNumber) -> Result<Header> {
+    let hash = tx
+        .get::<tables::CanonicalHeaders>(number)?
+        .ok_or(Error::BlockNumber { block_number: number })?;
+    Ok(tx
+        .get::<tables::Headers>((number, hash).into())?
+        .ok_or(Error::BlockHash { block_hash: hash })?)
+}
+
+/// Writes the changesets, plain state and bytecodes of an executed block, starting at the given
+/// transition. The receipts are written as well if the first transaction id of the block is given.
+///
+/// Returns the transition after the block.
+pub fn write_execution_result<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
+    tx: &TX,
+    start_tx_id: Option<TxNumber>,
+    mut transition_id: TransitionId,
+    result: ExecutionResult,
+    spurious_dragon_active: bool,
+) -> Result<TransitionId> {
+    for (index, changeset) in result.changesets.into_iter().enumerate() {
+        if let Some(start_tx_id) = start_tx_id {
+            tx.put::<tables::Receipts>(start_tx_id + index as u64, changeset.receipt)?;
+        }
+        for (address, account_changeset) in changeset.changeset.into_iter() {
+            write_account_changeset(
+                tx,
+                address,
+                account_changeset,
+                transition_id,
+                spurious_dragon_active,
+            )?;
+        }
+        for (hash, bytecode) in changeset.new_bytecodes.into_iter() {
+            tx.put::<tables::Bytecodes>(hash, bytecode.to_vec())?;
+        }
+        transition_id += 1;
+    }
+
+    if let Some(block_reward) = result.block_reward {
+        for (address, changeset) in block_reward.into_iter() {
+            changeset.apply_to_db(tx, address, transition_id, spurious_dragon_active)?;
+        }
+        transition_id += 1;
+    }
+    Ok(transition_id)
+}
+
+/// Applies the change of the account to the plain state and records the old values in the
+/// changesets.
+fn write_account_changeset<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
+    tx: &TX,
+    address: Address,
+    account_changeset: AccountChangeSet,
+    transition_id: TransitionId,
+    spurious_dragon_active: bool,
+) -> Result<()> {
+    let AccountChangeSet { account, wipe_storage, storage } = account_changeset;
+    trace!(target: "provider::writer", ?address, transition_id, ?account, wipe_storage, "Applying account changeset");
+    account.apply_to_db(tx, address, transition_id, spurious_dragon_active)?;
+
+    let storage_id = TransitionIdAddress((transition_id, address));
+    let mut storage_changeset = tx.cursor_write::<tables::StorageChangeSet>()?;
+
+    if wipe_storage {
+        // record all slots of the account before they are deleted
+        let mut old_values = tx
+            .cursor_read::<tables::PlainStorageState>()?
+            .walk(address)?
+            .take_while(|res| res.as_ref().map(|(k, _)| *k == address).unwrap_or_default())
+            .map(|entry| entry.map(|(_, entry)| (entry.key, entry.value)))
+            .collect::<std::result::Result<BTreeMap<_, _>, _>>()?;
+        tx.delete::<tables::PlainStorageState>(address, None)?;
+
+        for (key, (_, new_value)) in storage.into_iter() {
+            // old values are already cleared
+            if new_value != U256::ZERO {
+                let key = H256(key.to_be_bytes());
+                // slots that were empty before the wipe are recorded as well, so that unwinding
+                // removes them
+                old_values.entry(key).or_insert(U256::ZERO);
+                tx.put::<tables::PlainStorageState>(
+                    address,
+                    StorageEntry { key, value: new_value },
+                )?;
+            }
+        }
+
+        for (key, value) in old_values {
+            storage_changeset.append(storage_id, StorageEntry { key, value })?;
+        }
+    } else {
+        for (key, (old_value, new_value)) in storage.into_iter() {
+            let key = H256(key.to_be_bytes());
+            let old_entry = StorageEntry { key, value: old_value };
+            storage_changeset.append(storage_id, old_entry)?;
+
+            // the table is dupsorted, so the old value has to be deleted explicitly
+            tx.delete::<tables::PlainStorageState>(address, Some(old_entry))?;
+            if new_value != U256::ZERO {
+                tx.put::<tables::PlainStorageState>(
+                    address,
+                    StorageEntry { key, value: new_value },
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Updates the hashed state of all accounts and storage slots changed in the transitions to the
+/// values of the plain state.
+fn hash_state<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
+    tx: &TX,
+    transitions: Range<TransitionId>,
+) -> Result<()> {
+    let (accounts, storages) = changed_state(tx, transitions)?;
+
+    let mut hashed_accounts = tx.cursor_write::<tables::HashedAccount>()?;
+    for address in accounts {
+        let hashed_address = keccak256(address);
+        if let Some(account) = tx.get::<tables::PlainAccountState>(address)? {
+            hashed_accounts.upsert(hashed_address, account)?;
+        } else if hashed_accounts.seek_exact(hashed_address)?.is_some() {
+            hashed_accounts.delete_current()?;
+        }
+    }
+
+    let mut plain_storage = tx.cursor_dup_read::<tables::PlainStorageState>()?;
+    let mut hashed_storage = tx.cursor_dup_write::<tables::HashedStorage>()?;
+    for (address, keys) in storages {
+        let hashed_address = keccak256(address);
+        for key in keys {
+            let value = plain_storage
+                .seek_by_key_subkey(address, key)?
+                .filter(|entry| entry.key == key)
+                .map(|entry| entry.value);
+            write_hashed_slot(&mut hashed_storage, hashed_address, keccak256(key), value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reverts the plain state to the values before the transitions.
+fn revert_plain_state<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
+    tx: &TX,
+    transitions: Range<TransitionId>,
+) -> Result<()> {
+    let (accounts, storages) = state_before(tx, transitions)?;
+
+    for (address, account) in accounts {
+        if let Some(account) = account {
+            tx.put::<tables::PlainAccountState>(address, account)?;
+        } else {
+            tx.delete::<tables::PlainAccountState>(address, None)?;
+        }
+    }
+
+    let mut plain_storage = tx.cursor_dup_write::<tables::PlainStorageState>()?;
+    for ((address, key), value) in storages {
+        if plain_storage
+            .seek_by_key_subkey(address, key)?
+            .filter(|entry| entry.key == key)
+            .is_some()
+        {
+            plain_storage.delete_current()?;
+        }
+        if value != U256::ZERO {
+            plain_storage.upsert(address, StorageEntry { key, value })?;
+        }
+    }
+    Ok(())
+}
+
+/// Reverts the hashed state to the values before the transitions.
+fn revert_hashed_state<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
+    tx: &TX,
+    transitions: Range<TransitionId>,
+) -> Result<()> {
+    let (accounts, storages) = state_before(tx, transitions)?;
+
+    let mut hashed_accounts = tx.cursor_write::<tables::HashedAccount>()?;
+    for (hashed_address, account) in
+        accounts.into_iter().map(|(address, account)| (keccak256(address), account))
+    {
+        if let Some(account) = account {
+            hashed_accounts.upsert(hashed_address, account)?;
+        } else if hashed_accounts.seek_exact(hashed_address)?.is_some() {
+            hashed_accounts.delete_current()?;
+        }
+    }
+
+    let mut hashed_storage = tx.cursor_dup_write::<tables::HashedStorage>()?;
+    for ((address, key), value) in storages {
+        let value = (value != U256::ZERO).then_some(value);
+        write_hashed_slot(&mut hashed_storage, keccak256(address), keccak256(key), value)?;
+    }
+    Ok(())
+}
+
+/// Replaces the value of the hashed storage slot, removing it if there is no value.
+fn write_hashed_slot<
+    'tx,
+    C: DbDupCursorRO<'tx, tables::HashedStorage> + DbCursorRW<'tx, tables::HashedStorage>,
+>(
+    cursor: &mut C,
+    hashed_address: H256,
+    hashed_key: H256,
+    value: Option<U256>,
+) -> Result<()> {
+    if cursor
+        .seek_by_key_subkey(hashed_address, hashed_key)?
+        .filter(|entry| entry.key == hashed_key)
+        .is_some()
+    {
+        cursor.delete_current()?;
+    }
+    if let Some(value) = value {
+        cursor.upsert(hashed_address, StorageEntry { key: hashed_key, value })?;
+    }
+    Ok(())
+}
+
+/// Returns the accounts and storage slots changed in the transitions.
+#[allow(clippy::type_complexity)]
+fn changed_state<'tx, TX: DbTx<'tx>>(
+    tx: &TX,
+    transitions: Range<TransitionId>,
+) -> Result<(BTreeSet<Address>, BTreeMap<Address, BTreeSet<H256>>)> {
+    let accounts = tx
+        .cursor_read::<tables::AccountChangeSet>()?
+        .walk_range(transitions.clone())?
+        .map(|res| res.map(|(_, account_before)| account_before.address))
+        .collect::<std::result::Result<BTreeSet<_>, _>>()?;
+
+    let mut storages: BTreeMap<Address, BTreeSet<H256>> = BTreeMap::new();
+    let mut storage_changesets = tx.cursor_read::<tables::StorageChangeSet>()?;
+    let mut walker = storage_changesets.walk_range(
+        (transitions.start, Address::zero()).into()..(transitions.end, Address::zero()).into(),
+    )?;
+    while let Some((key, entry)) = walker.next().transpose()? {
+        storages.entry(key.address()).or_default().insert(entry.key);
+    }
+    Ok((accounts, storages))
+}
+
+/// Returns the values of the accounts and storage slots changed in the transitions, as they were
+/// before the transitions.
+#[allow(clippy::type_complexity)]
+fn state_before<'tx, TX: DbTx<'tx>>(
+    tx: &TX,
+    transitions: Range<TransitionId>,
+) -> Result<(BTreeMap<Address, Option<Account>>, BTreeMap<(Address, H256), U256>)> {
+    // the changesets contain the value before the transition, so the first changeset of each
+    // account and slot has the value before all transitions
+    let mut accounts = BTreeMap::new();
+    for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(transitions.clone())? {
+        let (_, account_before) = entry?;
+        accounts.entry(account_before.address).or_insert(account_before.info);
+    }
+
+    let mut storages = BTreeMap::new();
+    let mut storage_changesets = tx.cursor_read::<tables::StorageChangeSet>()?;
+    let mut walker = storage_changesets.walk_range(
+        (transitions.start, Address::zero()).into()..(transitions.end, Address::zero()).into(),
+    )?;
+    while let Some((key, entry)) = walker.next().transpose()? {
+        storages.entry((key.address(), entry.key)).or_insert(entry.value);
+    }
+    Ok((accounts, storages))
+}
+
+/// Appends the transitions to the history indices of the changed accounts and storage slots.
+///
+/// See [tables::AccountHistory] for the sharding of the indices.
+fn index_history<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
+    tx: &TX,
+    transitions: Range<TransitionId>,
+) -> Result<()> {
+    let mut accounts: BTreeMap<Address, Vec<u64>> = BTreeMap::new();
+    for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(transitions.clone())? {
+        let (transition_id, account_before) = entry?;
+        accounts.entry(account_before.address).or_default().push(transition_id);
+    }
+    for (address, mut indices) in accounts {
+        let mut shard =
+            take_last_shard::<_, tables::AccountHistory>(tx, ShardedKey::new(address, u64::MAX))?;
+        shard.append(&mut indices);
+        for (highest, list) in chunk_shard(shard) {
+            tx.put::<tables::AccountHistory>(ShardedKey::new(address, highest), list)?;
+        }
+    }
+
+    let mut storages: BTreeMap<(Address, H256), Vec<u64>> = BTreeMap::new();
+    let mut storage_changesets = tx.cursor_read::<tables::StorageChangeSet>()?;
+    let mut walker = storage_changesets.walk_range(
+        (transitions.start, Address::zero()).into()..(transitions.end, Address::zero()).into(),
+    )?;
+    while let Some((key, entry)) = walker.next().transpose()? {
+        storages.entry((key.address(), entry.key)).or_default().push(key.transition_id());
+    }
+    for ((address, storage_key), mut indices) in storages {
+        let mut shard = take_last_shard::<_, tables::StorageHistory>(
+            tx,
+            StorageShardedKey::new(address, storage_key, u64::MAX),
+        )?;
+        shard.append(&mut indices);
+        for (highest, list) in chunk_shard(shard) {
+            tx.put::<tables::StorageHistory>(
+                StorageShardedKey::new(address, storage_key, highest),
+                list,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the last shard with the given key and returns its indices.
+fn take_last_shard<'tx, TX, T>(tx: &TX, key: T::Key) -> Result<Vec<u64>>
+where
+    TX: DbTx<'tx> + DbTxMut<'tx>,
+    T: reth_db::table::Table<Value = TransitionList>,
+{
+    match tx.get::<T>(key.clone())? {
+        Some(list) => {
+            tx.delete::<T>(key, None)?;
+            Ok(list.iter(0).map(|i| i as u64).collect())
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Splits the sorted indices into full shards, keyed by their highest index, and a last shard
+/// keyed by `u64::MAX`.
+fn chunk_shard(indices: Vec<u64>) -> Vec<(u64, TransitionList)> {
+    let mut chunks = indices
+        .iter()
+        .chunks(NUM_OF_INDICES_IN_SHARD)
+        .into_iter()
+        .map(|chunk| chunk.map(|i| *i as usize).collect::<Vec<usize>>())
+        .collect::<Vec<_>>();
+    let last = chunks.pop();
+    chunks
+        .into_iter()
+        .map(|list| (*list.last().expect("chunks are not empty") as u64, list))
+        .chain(last.map(|list| (u64::MAX, list)))
+        .map(|(highest, list)| {
+            (highest, TransitionList::new(list).expect("indices are sorted and not empty"))
+        })
+        .collect()
+}
+
+/// Removes all transitions of the range from the history indices.
+fn unwind_history<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
+    tx: &TX,
+    transitions: Range<TransitionId>,
+) -> Result<()> {
+    // the lowest transition to remove, per account and slot
+    let (accounts, storages) = {
+        let mut accounts = BTreeMap::new();
+        for entry in
+            tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(transitions.clone())?
+        {
+            let (transition_id, account_before) = entry?;
+            accounts.entry(account_before.address).or_insert(transition_id);
+        }
+        let mut storages = BTreeMap::new();
+        let mut storage_changesets = tx.cursor_read::<tables::StorageChangeSet>()?;
+        let mut walker = storage_changesets.walk_range(
+            (transitions.start, Address::zero()).into()..(transitions.end, Address::zero()).into(),
+        )?;
+        while let Some((key, entry)) = walker.next().transpose()? {
+            storages.entry((key.address(), entry.key)).or_insert(key.transition_id());
+        }
+        (accounts, storages)
+    };
+
+    let mut cursor = tx.cursor_write::<tables::AccountHistory>()?;
+    for (address, transition_id) in accounts {
+        let mut item = cursor.seek_exact(ShardedKey::new(address, u64::MAX))?;
+        let mut remaining = Vec::new();
+        while let Some((key, list)) = item {
+            if key.key != address {
+                break
+            }
+            cursor.delete_current()?;
+            let first = list.iter(0).next().expect("shards are not empty");
+            if first >= transition_id as usize {
+                // the whole shard is removed
+                item = cursor.prev()?;
+                continue
+            }
+            remaining = list.iter(0).take_while(|i| *i < transition_id as usize).collect();
+            break
+        }
+        if !remaining.is_empty() {
+            tx.put::<tables::AccountHistory>(
+                ShardedKey::new(address, u64::MAX),
+                TransitionList::new(remaining).expect("indices are sorted and not empty"),
+            )?;
+        }
+    }
+
+    let mut cursor = tx.cursor_write::<tables::StorageHistory>()?;
+    for ((address, storage_key), transition_id) in storages {
+        let mut item = cursor.seek_exact(StorageShardedKey::new(address, storage_key, u64::MAX))?;
+        let mut remaining = Vec::new();
+        while let Some((key, list)) = item {
+            if key.address != address || key.sharded_key.key != storage_key {
+                break
+            }
+            cursor.delete_current()?;
+            let first = list.iter(0).next().expect("shards are not empty");
+            if first >= transition_id as usize {
+                // the whole shard is removed
+                item = cursor.prev()?;
+                continue
+            }
+            remaining = list.iter(0).take_while(|i| *i < transition_id as usize).collect();
+            break
+        }
+        if !remaining.is_empty() {
+            tx.put::<tables::StorageHistory>(
+                StorageShardedKey::new(address, storage_key, u64::MAX),
+                TransitionList::new(remaining).expect("indices are sorted and not empty"),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes all account and storage changesets starting at the transition.
+fn remove_changesets<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
+    tx: &TX,
+    from_transition: TransitionId,
+) -> Result<()> {
+    let mut account_changesets = tx.cursor_dup_write::<tables::AccountChangeSet>()?;
+    let mut walker = account_changesets.walk_back(None)?;
+    while let Some((transition_id, _)) = walker.next().transpose()? {
+        if transition_id < from_transition {
+            break
+        }
+        tx.delete::<tables::AccountChangeSet>(transition_id, None)?;
+    }
+
+    let mut storage_changesets = tx.cursor_dup_write::<tables::StorageChangeSet>()?;
+    let mut walker = storage_changesets.walk_back(None)?;
+    while let Some((key, _)) = walker.next().transpose()? {
+        if key.transition_id() < from_transition {
+            break
+        }
+        tx.delete::<tables::StorageChangeSet>(key, None)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution_result::{AccountInfoChangeSet, TransactionChangeSet};
+    use assert_matches::assert_matches;
+    use reth_db::{
+        database::Database, mdbx::test_utils::create_test_rw_db, models::AccountBeforeTx,
+    };
+    use reth_interfaces::test_utils::generators::random_block;
+    use reth_primitives::{
+        proofs::{genesis_state_root, EMPTY_ROOT},
+        GenesisAccount, Receipt,
+    };
+    use std::collections::HashMap;
+
+    /// Returns the number of entries in the table.
+    fn entries<'tx, T: reth_db::table::Table, TX: DbTx<'tx>>(tx: &TX) -> usize {
+        tx.cursor_read::<T>().unwrap().walk_back(None).unwrap().count()
+    }
+
+    /// Returns the block with the given state root.
+    fn with_state_root(block: SealedBlock, state_root: H256) -> SealedBlock {
+        let (header, body, ommers) = block.split();
+        SealedBlock { header: Header { state_root, ..header.unseal() }.seal(), body, ommers }
+    }
+
+    /// Returns the result of a block with a single transaction that changes the account.
+    fn execution_result(address: Address, changeset: AccountChangeSet) -> ExecutionResult {
+        ExecutionResult {
+            changesets: vec![TransactionChangeSet {
+                receipt: Receipt::default(),
+                changeset: BTreeMap::from([(address, changeset)]),
+                new_bytecodes: BTreeMap::new(),
+            }],
+            block_reward: None,
+        }
+    }
+
+    /// Returns the state root after appending the block with its result on top of `blocks`.
+    fn state_root_after<DB: Database>(
+        db: &DB,
+        blocks: &[(SealedBlock, ExecutionResult)],
+        block: &SealedBlock,
+        result: ExecutionResult,
+    ) -> H256 {
+        let tx = db.tx_mut().unwrap();
+        for (block, result) in blocks {
+            tx.append_block_with_execution(block, None, result.clone(), true).unwrap();
+        }
+        let block = with_state_root(block.clone(), H256::zero());
+        match tx.append_block_with_execution(&block, None, result, true) {
+            Err(reth_interfaces::Error::Provider(Error::StateRootMismatch { got, .. })) => got,
+            res => panic!("unexpected result {res:?}"),
+        }
+    }
+
+    /// Returns all entries of the plain storage.
+    fn plain_storage<'tx, TX: DbTx<'tx>>(tx: &TX) -> Vec<(Address, StorageEntry)> {
+        tx.cursor_read::<tables::PlainStorageState>()
+            .unwrap()
+            .walk(Address::zero())
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn insert_and_remove_blocks() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+
+        let genesis = random_block(0, None, Some(0), Some(0));
+        let block1 = random_block(1, Some(genesis.hash()), Some(2), Some(1));
+        let block2 = random_block(2, Some(block1.hash()), Some(3), Some(0));
+        tx.insert_block(&genesis, None, false).unwrap();
+        tx.insert_block(&block1, None, true).unwrap();
+        tx.insert_block(&block2, None, false).unwrap();
+
+        // blocks have to extend the canonical chain
+        let orphan = random_block(3, Some(H256::random()), Some(0), Some(0));
+        assert_matches!(
+            tx.insert_block(&orphan, None, false),
+            Err(reth_interfaces::Error::Provider(Error::NonCanonicalBlock { block_number: 2, .. }))
+        );
+
+        assert_eq!(
+            tx.get::<tables::BlockBodies>((2, block2.hash()).into()).unwrap(),
+            Some(StoredBlockBody { start_tx_id: 2, tx_count: 3 })
+        );
+        assert_eq!(tx.get::<tables::BlockTransitionIndex>(1).unwrap(), Some(3));
+        assert_eq!(tx.get::<tables::BlockTransitionIndex>(2).unwrap(), Some(6));
+        assert_eq!(tx.get::<tables::TxHashNumber>(block2.body[0].hash).unwrap(), Some(2));
+        assert_eq!(tx.get::<tables::TxSenders>(4).unwrap(), block2.body[2].recover_signer());
+        assert_eq!(tx.get::<tables::TxTransitionIndex>(4).unwrap(), Some(4));
+        assert!(tx.get::<tables::BlockOmmers>((1, block1.hash()).into()).unwrap().is_some());
+
+        tx.remove_blocks_above(0).unwrap();
+        assert_eq!(entries::<tables::CanonicalHeaders, _>(&tx), 1);
+        assert_eq!(entries::<tables::Headers, _>(&tx), 1);
+        assert_eq!(entries::<tables::HeaderNumbers, _>(&tx), 1);
+        assert_eq!(entries::<tables::HeaderTD, _>(&tx), 1);
+        assert_eq!(entries::<tables::BlockBodies, _>(&tx), 1);
+        assert_eq!(entries::<tables::BlockTransitionIndex, _>(&tx), 1);
+        assert_eq!(entries::<tables::BlockOmmers, _>(&tx), 0);
+        assert_eq!(entries::<tables::Transactions, _>(&tx), 0);
+        assert_eq!(entries::<tables::TxSenders, _>(&tx), 0);
+        assert_eq!(entries::<tables::TxHashNumber, _>(&tx), 0);
+        assert_eq!(entries::<tables::TxTransitionIndex, _>(&tx), 0);
+
+        // the removed blocks can be inserted again
+        tx.insert_block(&block1, None, true).unwrap();
+    }
+
+    #[test]
+    fn append_and_unwind_execution() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+
+        let genesis = with_state_root(random_block(0, None, Some(0), Some(0)), EMPTY_ROOT);
+        tx.append_block_with_execution(&genesis, None, ExecutionResult::default(), true).unwrap();
+
+        let beneficiary = Address::random();
+        let balance = U256::from(2_000_000_000_000_000_000u128);
+        let account = Account { nonce: 0, balance, bytecode_hash: None };
+        let state_root = genesis_state_root(&HashMap::from([(
+            beneficiary,
+            GenesisAccount { balance, ..Default::default() },
+        )]));
+        let block1 =
+            with_state_root(random_block(1, Some(genesis.hash()), Some(0), Some(0)), state_root);
+        let result = ExecutionResult {
+            changesets: vec![],
+            block_reward: Some(BTreeMap::from([(
+                beneficiary,
+                AccountInfoChangeSet::Created { new: account },
+            )])),
+        };
+
+        // the state root of the block is verified
+        let invalid = with_state_root(block1.clone(), H256::random());
+        assert_matches!(
+            tx.append_block_with_execution(&invalid, None, result.clone(), true),
+            Err(reth_interfaces::Error::Provider(Error::StateRootMismatch { block_number: 1, got, .. })) if got == state_root
+        );
+        drop(tx);
+
+        let tx = db.tx_mut().unwrap();
+        tx.append_block_with_execution(&genesis, None, ExecutionResult::default(), true).unwrap();
+        tx.append_block_with_execution(&block1, None, result, true).unwrap();
+        assert_eq!(tx.get::<tables::PlainAccountState>(beneficiary).unwrap(), Some(account));
+        assert_eq!(tx.get::<tables::HashedAccount>(keccak256(beneficiary)).unwrap(), Some(account));
+        assert_eq!(
+            tx.get::<tables::AccountChangeSet>(0).unwrap(),
+            Some(AccountBeforeTx { address: beneficiary, info: None })
+        );
+        assert_eq!(
+            tx.get::<tables::AccountHistory>(ShardedKey::new(beneficiary, u64::MAX))
+                .unwrap()
+                .map(|list| list.iter(0).collect::<Vec<_>>()),
+            Some(vec![0])
+        );
+
+        tx.unwind_blocks_above(0).unwrap();
+        assert_eq!(tx.get::<tables::CanonicalHeaders>(1).unwrap(), None);
+        assert_eq!(entries::<tables::PlainAccountState, _>(&tx), 0);
+        assert_eq!(entries::<tables::HashedAccount, _>(&tx), 0);
+        assert_eq!(entries::<tables::AccountChangeSet, _>(&tx), 0);
+        assert_eq!(entries::<tables::AccountHistory, _>(&tx), 0);
+    }
+
+    #[test]
+    fn unwind_wiped_storage() {
+        let db = create_test_rw_db();
+        let address = Address::random();
+        let account = Account { nonce: 1, balance: U256::ZERO, bytecode_hash: None };
+        let slot = |key: u64| H256::from_low_u64_be(key);
+
+        let genesis = with_state_root(random_block(0, None, Some(0), Some(0)), EMPTY_ROOT);
+        let mut blocks = vec![(genesis.clone(), ExecutionResult::default())];
+
+        let result1 = execution_result(
+            address,
+            AccountChangeSet {
+                account: AccountInfoChangeSet::Created { new: account },
+                storage: BTreeMap::from([(U256::from(1), (U256::ZERO, U256::from(1)))]),
+                wipe_storage: false,
+            },
+        );
+        let block1 = random_block(1, Some(genesis.hash()), Some(1), Some(0));
+        let state_root = state_root_after(&db, &blocks, &block1, result1.clone());
+        let block1 = with_state_root(block1, state_root);
+        blocks.push((block1.clone(), result1));
+
+        // the storage is wiped and a slot that was empty before is written
+        let result2 = execution_result(
+            address,
+            AccountChangeSet {
+                account: AccountInfoChangeSet::Changed {
+                    new: Account { nonce: 2, ..account },
+                    old: account,
+                },
+                storage: BTreeMap::from([(U256::from(2), (U256::ZERO, U256::from(2)))]),
+                wipe_storage: true,
+            },
+        );
+        let block2 = random_block(2, Some(block1.hash()), Some(1), Some(0));
+        let state_root = state_root_after(&db, &blocks, &block2, result2.clone());
+        blocks.push((with_state_root(block2, state_root), result2));
+
+        let tx = db.tx_mut().unwrap();
+        for (block, result) in blocks {
+            tx.append_block_with_execution(&block, None, result, true).unwrap();
+        }
+        assert_eq!(
+            plain_storage(&tx),
+            vec![(address, StorageEntry { key: slot(2), value: U256::from(2) })]
+        );
+        assert_eq!(
+            tx.cursor_read::<tables::StorageChangeSet>()
+                .unwrap()
+                .walk_range((1, Address::zero()).into()..(2, Address::zero()).into())
+                .unwrap()
+                .map(|entry| entry.unwrap().1)
+                .collect::<Vec<_>>(),
+            vec![
+                StorageEntry { key: slot(1), value: U256::from(1) },
+                StorageEntry { key: slot(2), value: U256::ZERO },
+            ]
+        );
+
+        tx.unwind_blocks_above(1).unwrap();
+        assert_eq!(
+            plain_storage(&tx),
+            vec![(address, StorageEntry { key: slot(1), value: U256::from(1) })]
+        );
+        assert_eq!(entries::<tables::StorageChangeSet, _>(&tx), 1);
+    }
+}
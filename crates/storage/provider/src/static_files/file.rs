@@ -0,0 +1,572 @@
+//! The on-disk format of a single static file.
+
+use super::{StaticFileError, StaticFileSegment, BLOCKS_PER_STATIC_FILE};
+use reth_db::tables::codecs::compression::UNCOMPRESSED;
+use reth_primitives::BlockNumber;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// Magic bytes at the start of every index file, followed by the version of the file.
+const MAGIC: [u8; 3] = *b"RSF";
+
+/// The version new files are written with.
+///
+/// Version 2 added the [compression](reth_db::tables::codecs::compression) markers to the values
+/// of transactions and receipts.
+const VERSION: u8 = b'2';
+
+/// The version of files whose values are stored without their compression marker. They can still
+/// be read and appended to.
+const VERSION_WITHOUT_MARKERS: u8 = b'1';
+
+/// The size of the encoded [StaticFileHeader].
+const HEADER_SIZE: u64 = 40;
+
+/// The size of a single offset in the index file.
+const OFFSET_SIZE: u64 = 8;
+
+/// Extension of the data file.
+const DATA_EXTENSION: &str = "dat";
+
+/// Extension of the index file.
+const INDEX_EXTENSION: &str = "idx";
+
+/// The header of an index file, describing the committed content of a static file.
+///
+/// The header is followed by `row_count * columns + 1` little endian offsets into the data file,
+/// the cell of column `c` of row `r` spans from offset `(r - first_row) * columns + c` to the next
+/// one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticFileHeader {
+    /// The segment of the file.
+    pub segment: StaticFileSegment,
+    /// The version of the file.
+    pub version: u8,
+    /// The first block of the file.
+    pub block_start: BlockNumber,
+    /// The number of committed blocks.
+    pub block_count: u64,
+    /// The id of the first row, the block number or the transaction id.
+    pub first_row: u64,
+    /// The number of committed rows.
+    pub row_count: u64,
+}
+
+impl StaticFileHeader {
+    /// Returns the highest committed block of the file.
+    pub fn highest_block(&self) -> Option<BlockNumber> {
+        (self.block_count > 0).then(|| self.block_start + self.block_count - 1)
+    }
+
+    /// Returns the id of the next row.
+    pub fn next_row(&self) -> u64 {
+        self.first_row + self.row_count
+    }
+
+    /// Returns true if the values of the file are stored without their compression marker.
+    fn without_markers(&self) -> bool {
+        self.version == VERSION_WITHOUT_MARKERS &&
+            matches!(self.segment, StaticFileSegment::Transactions | StaticFileSegment::Receipts)
+    }
+
+    /// Returns the number of offsets in the index file.
+    fn offsets(&self) -> u64 {
+        self.row_count * self.segment.columns() as u64 + 1
+    }
+
+    fn encode(&self) -> [u8; HEADER_SIZE as usize] {
+        let mut buf = [0u8; HEADER_SIZE as usize];
+        buf[..3].copy_from_slice(&MAGIC);
+        buf[3] = self.version;
+        buf[4] = StaticFileSegment::ALL.iter().position(|s| *s == self.segment).unwrap() as u8;
+        buf[5] = self.segment.columns() as u8;
+        buf[8..16].copy_from_slice(&self.block_start.to_le_bytes());
+        buf[16..24].copy_from_slice(&self.block_count.to_le_bytes());
+        buf[24..32].copy_from_slice(&self.first_row.to_le_bytes());
+        buf[32..40].copy_from_slice(&self.row_count.to_le_bytes());
+        buf
+    }
+
+    fn decode(buf: &[u8; HEADER_SIZE as usize]) -> Result<Self, StaticFileError> {
+        if buf[..3] != MAGIC {
+            return Err(StaticFileError::InvalidFile("bad magic".to_string()))
+        }
+        let version = buf[3];
+        if version != VERSION && version != VERSION_WITHOUT_MARKERS {
+            return Err(StaticFileError::InvalidFile(format!("unknown version {}", version as char)))
+        }
+        let segment = *StaticFileSegment::ALL
+            .get(buf[4] as usize)
+            .ok_or_else(|| StaticFileError::InvalidFile(format!("unknown segment {}", buf[4])))?;
+        if buf[5] as usize != segment.columns() {
+            return Err(StaticFileError::InvalidFile(format!("bad column count {}", buf[5])))
+        }
+        let u64_at = |at: usize| u64::from_le_bytes(buf[at..at + 8].try_into().unwrap());
+        Ok(Self {
+            segment,
+            version,
+            block_start: u64_at(8),
+            block_count: u64_at(16),
+            first_row: u64_at(24),
+            row_count: u64_at(32),
+        })
+    }
+
+    fn read(file: &mut File) -> Result<Self, StaticFileError> {
+        let mut buf = [0u8; HEADER_SIZE as usize];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_exact(&mut buf)?;
+        Self::decode(&buf)
+    }
+}
+
+/// Returns the paths of the data and index file of the segment file that starts at the block.
+fn file_paths(
+    dir: &Path,
+    segment: StaticFileSegment,
+    block_start: BlockNumber,
+) -> (PathBuf, PathBuf) {
+    let name = segment.file_name(block_start);
+    (dir.join(format!("{name}.{DATA_EXTENSION}")), dir.join(format!("{name}.{INDEX_EXTENSION}")))
+}
+
+/// Returns the first blocks of all files of the segment in the directory, in ascending order.
+pub(crate) fn list_files(
+    dir: &Path,
+    segment: StaticFileSegment,
+) -> Result<Vec<BlockNumber>, StaticFileError> {
+    let mut starts = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(INDEX_EXTENSION) {
+            continue
+        }
+        let name = path.file_stem().and_then(|name| name.to_str()).unwrap_or_default();
+        if let Some((found, start)) = StaticFileSegment::parse_file_name(name) {
+            if found == segment {
+                starts.push(start);
+            }
+        }
+    }
+    starts.sort_unstable();
+    Ok(starts)
+}
+
+/// Reads rows of a committed static file.
+#[derive(Debug)]
+pub struct StaticFileReader {
+    /// The header at the time the file was opened.
+    header: StaticFileHeader,
+    /// The index file, only read with positioned reads so that rows can be read concurrently.
+    index: File,
+    /// The data file, only read with positioned reads.
+    data: File,
+}
+
+impl StaticFileReader {
+    /// Opens the segment file that starts at the block.
+    pub fn open(
+        dir: &Path,
+        segment: StaticFileSegment,
+        block_start: BlockNumber,
+    ) -> Result<Self, StaticFileError> {
+        let (data_path, index_path) = file_paths(dir, segment, block_start);
+        let mut index = File::open(index_path)?;
+        let header = StaticFileHeader::read(&mut index)?;
+        if header.segment != segment || header.block_start != block_start {
+            return Err(StaticFileError::InvalidFile(format!(
+                "header of {} does not match its name",
+                segment.file_name(block_start)
+            )))
+        }
+        Ok(Self { header, index, data: File::open(data_path)? })
+    }
+
+    /// Returns the header of the file.
+    pub fn header(&self) -> &StaticFileHeader {
+        &self.header
+    }
+
+    /// Returns true if the file contains the row.
+    pub fn contains_row(&self, row: u64) -> bool {
+        (self.header.first_row..self.header.next_row()).contains(&row)
+    }
+
+    /// Returns the decompressed column of the row.
+    ///
+    /// Returns `None` if the row is not in the file or the column is empty.
+    pub fn column(&self, row: u64, column: usize) -> Result<Option<Vec<u8>>, StaticFileError> {
+        if !self.contains_row(row) || column >= self.header.segment.columns() {
+            return Ok(None)
+        }
+        let position =
+            (row - self.header.first_row) * self.header.segment.columns() as u64 + column as u64;
+
+        let mut offsets = [0u8; 2 * OFFSET_SIZE as usize];
+        read_exact_at(&self.index, &mut offsets, HEADER_SIZE + position * OFFSET_SIZE)?;
+        let start = u64::from_le_bytes(offsets[..8].try_into().unwrap());
+        let end = u64::from_le_bytes(offsets[8..].try_into().unwrap());
+        if end < start {
+            return Err(StaticFileError::InvalidFile(format!("bad offsets of row {row}")))
+        }
+        if start == end {
+            return Ok(None)
+        }
+
+        let mut compressed = vec![0u8; (end - start) as usize];
+        read_exact_at(&self.data, &mut compressed, start)?;
+        let mut value = snap::raw::Decoder::new().decompress_vec(&compressed)?;
+        if self.header.without_markers() {
+            value.insert(0, UNCOMPRESSED);
+        }
+        Ok(Some(value))
+    }
+}
+
+/// Fills the buffer with the bytes of the file at the offset, without using the cursor of the file.
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+/// Fills the buffer with the bytes of the file at the offset, without using the cursor of the file.
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    while !buf.is_empty() {
+        match std::os::windows::fs::FileExt::seek_read(file, buf, offset) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => {
+                buf = &mut buf[read..];
+                offset += read as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Appends rows to the static files of a segment.
+///
+/// Appended rows are not visible to readers until they are [committed][Self::commit]. Once the
+/// current file holds [BLOCKS_PER_STATIC_FILE] blocks, the writer continues with the next file.
+#[derive(Debug)]
+pub struct StaticFileWriter {
+    /// The directory of the static files.
+    dir: PathBuf,
+    /// The committed header of the current file.
+    header: StaticFileHeader,
+    /// The header including the uncommitted rows.
+    pending: StaticFileHeader,
+    /// The index file of the current file.
+    index: File,
+    /// The data file of the current file.
+    data: BufWriter<File>,
+    /// The offset of the end of the data file.
+    data_len: u64,
+    /// The uncommitted offsets.
+    offsets: Vec<u64>,
+    /// Snappy encoder, reused across columns.
+    encoder: snap::raw::Encoder,
+}
+
+impl StaticFileWriter {
+    /// Opens the latest file of the segment in the directory, creating the first one if there
+    /// are none.
+    ///
+    /// Data that was appended but not committed before is discarded.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        segment: StaticFileSegment,
+    ) -> Result<Self, StaticFileError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        match list_files(&dir, segment)?.last() {
+            Some(start) => Self::open(dir, segment, *start),
+            None => Self::create(
+                dir,
+                StaticFileHeader {
+                    segment,
+                    version: VERSION,
+                    block_start: 0,
+                    block_count: 0,
+                    first_row: 0,
+                    row_count: 0,
+                },
+            ),
+        }
+    }
+
+    /// Opens an existing file and truncates it to its committed content.
+    fn open(
+        dir: PathBuf,
+        segment: StaticFileSegment,
+        block_start: BlockNumber,
+    ) -> Result<Self, StaticFileError> {
+        let (data_path, index_path) = file_paths(&dir, segment, block_start);
+        let mut index = OpenOptions::new().read(true).write(true).open(index_path)?;
+        let header = StaticFileHeader::read(&mut index)?;
+        let index_len = HEADER_SIZE + header.offsets() * OFFSET_SIZE;
+        index.set_len(index_len)?;
+
+        let mut last_offset = [0u8; OFFSET_SIZE as usize];
+        index.seek(SeekFrom::Start(index_len - OFFSET_SIZE))?;
+        index.read_exact(&mut last_offset)?;
+        let data_len = u64::from_le_bytes(last_offset);
+        let data = OpenOptions::new().write(true).open(data_path)?;
+        data.set_len(data_len)?;
+
+        let mut writer = Self::with_files(dir, header, index, data, data_len);
+        writer.data.seek(SeekFrom::End(0))?;
+        Ok(writer)
+    }
+
+    /// Creates a new file that starts with the header.
+    fn create(dir: PathBuf, header: StaticFileHeader) -> Result<Self, StaticFileError> {
+        let (data_path, index_path) = file_paths(&dir, header.segment, header.block_start);
+        let data = OpenOptions::new().write(true).create(true).truncate(true).open(data_path)?;
+        let mut index = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(index_path)?;
+        index.write_all(&header.encode())?;
+        index.write_all(&0u64.to_le_bytes())?;
+        index.sync_all()?;
+        Ok(Self::with_files(dir, header, index, data, 0))
+    }
+
+    fn with_files(
+        dir: PathBuf,
+        header: StaticFileHeader,
+        index: File,
+        data: File,
+        data_len: u64,
+    ) -> Self {
+        Self {
+            dir,
+            header,
+            pending: header,
+            index,
+            data: BufWriter::new(data),
+            data_len,
+            offsets: Vec::new(),
+            encoder: snap::raw::Encoder::new(),
+        }
+    }
+
+    /// Returns the segment of the writer.
+    pub fn segment(&self) -> StaticFileSegment {
+        self.header.segment
+    }
+
+    /// Returns the next block to append, including uncommitted blocks.
+    pub fn next_block(&self) -> BlockNumber {
+        self.pending.block_start + self.pending.block_count
+    }
+
+    /// Returns the id of the next row to append, including uncommitted rows.
+    pub fn next_row(&self) -> u64 {
+        self.pending.next_row()
+    }
+
+    /// Starts appending the rows of the next block, which has to be [Self::next_block].
+    ///
+    /// Commits the current file and creates a new one if the block belongs to the next file.
+    pub fn increment_block(&mut self, block: BlockNumber) -> Result<(), StaticFileError> {
+        let expected = self.next_block();
+        if block != expected {
+            return Err(StaticFileError::OutOfOrder {
+                segment: self.segment(),
+                expected,
+                got: block,
+            })
+        }
+        if block >= self.pending.block_start + BLOCKS_PER_STATIC_FILE {
+            self.commit()?;
+            let header = StaticFileHeader {
+                segment: self.segment(),
+                version: VERSION,
+                block_start: block,
+                block_count: 0,
+                first_row: self.next_row(),
+                row_count: 0,
+            };
+            *self = Self::create(self.dir.clone(), header)?;
+        }
+        self.pending.block_count += 1;
+        Ok(())
+    }
+
+    /// Appends a row to the current block, the row id has to be [Self::next_row].
+    ///
+    /// Empty columns are read back as absent.
+    pub fn append_row(&mut self, row: u64, columns: &[&[u8]]) -> Result<(), StaticFileError> {
+        let expected = self.next_row();
+        if row != expected {
+            return Err(StaticFileError::OutOfOrder { segment: self.segment(), expected, got: row })
+        }
+        if columns.len() != self.segment().columns() {
+            return Err(StaticFileError::InvalidFile(format!(
+                "expected {} columns, got {}",
+                self.segment().columns(),
+                columns.len()
+            )))
+        }
+        for column in columns {
+            // files without markers keep storing their values without them
+            let column = match column.split_first() {
+                Some((marker, value)) if self.pending.without_markers() => {
+                    if *marker != UNCOMPRESSED {
+                        return Err(StaticFileError::InvalidFile(
+                            "compressed value in a file without compression markers".to_string(),
+                        ))
+                    }
+                    value
+                }
+                _ => *column,
+            };
+            if !column.is_empty() {
+                let compressed = self.encoder.compress_vec(column)?;
+                self.data.write_all(&compressed)?;
+                self.data_len += compressed.len() as u64;
+            }
+            self.offsets.push(self.data_len);
+        }
+        self.pending.row_count += 1;
+        Ok(())
+    }
+
+    /// Makes all appended rows visible to new readers.
+    ///
+    /// The data and offsets are synced before the header is updated, so a crash never exposes
+    /// partially written rows.
+    pub fn commit(&mut self) -> Result<(), StaticFileError> {
+        if self.pending == self.header {
+            return Ok(())
+        }
+        self.data.flush()?;
+        self.data.get_ref().sync_all()?;
+
+        let mut offsets = Vec::with_capacity(self.offsets.len() * OFFSET_SIZE as usize);
+        for offset in self.offsets.drain(..) {
+            offsets.extend_from_slice(&offset.to_le_bytes());
+        }
+        self.index.seek(SeekFrom::End(0))?;
+        self.index.write_all(&offsets)?;
+        self.index.sync_all()?;
+
+        self.index.seek(SeekFrom::Start(0))?;
+        self.index.write_all(&self.pending.encode())?;
+        self.index.sync_all()?;
+        self.header = self.pending;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::tables::codecs::compression::ZSTD;
+
+    #[test]
+    fn write_and_read_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment = StaticFileSegment::Headers;
+        let mut writer = StaticFileWriter::new(dir.path(), segment).unwrap();
+        writer.increment_block(0).unwrap();
+        writer.append_row(0, &[b"header 0", b"td 0"]).unwrap();
+        writer.increment_block(1).unwrap();
+        writer.append_row(1, &[b"header 1", b""]).unwrap();
+        assert_eq!(
+            writer.append_row(3, &[b"header 3", b"td 3"]).unwrap_err().to_string(),
+            "static file headers expected 2, got 3"
+        );
+
+        // uncommitted rows are not visible
+        let reader = StaticFileReader::open(dir.path(), segment, 0).unwrap();
+        assert_eq!(reader.header().highest_block(), None);
+        assert_eq!(reader.column(0, 0).unwrap(), None);
+
+        writer.commit().unwrap();
+        let reader = StaticFileReader::open(dir.path(), segment, 0).unwrap();
+        assert_eq!(reader.header().highest_block(), Some(1));
+        assert_eq!(reader.column(0, 0).unwrap().as_deref(), Some(&b"header 0"[..]));
+        assert_eq!(reader.column(0, 1).unwrap().as_deref(), Some(&b"td 0"[..]));
+        assert_eq!(reader.column(1, 0).unwrap().as_deref(), Some(&b"header 1"[..]));
+        assert_eq!(reader.column(1, 1).unwrap(), None);
+        assert_eq!(reader.column(2, 0).unwrap(), None);
+
+        // reopening discards uncommitted rows
+        writer.increment_block(2).unwrap();
+        writer.append_row(2, &[b"header 2", b"td 2"]).unwrap();
+        drop(writer);
+        let mut writer = StaticFileWriter::new(dir.path(), segment).unwrap();
+        assert_eq!(writer.next_block(), 2);
+        writer.increment_block(2).unwrap();
+        writer.append_row(2, &[b"other header 2", b"td 2"]).unwrap();
+        writer.commit().unwrap();
+        let reader = StaticFileReader::open(dir.path(), segment, 0).unwrap();
+        assert_eq!(reader.column(2, 0).unwrap().as_deref(), Some(&b"other header 2"[..]));
+        assert_eq!(reader.column(1, 0).unwrap().as_deref(), Some(&b"header 1"[..]));
+    }
+
+    #[test]
+    fn read_and_append_files_without_markers() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment = StaticFileSegment::Receipts;
+        let header = StaticFileHeader {
+            segment,
+            version: VERSION_WITHOUT_MARKERS,
+            block_start: 0,
+            block_count: 0,
+            first_row: 0,
+            row_count: 0,
+        };
+        let mut writer = StaticFileWriter::create(dir.path().to_path_buf(), header).unwrap();
+        writer.increment_block(0).unwrap();
+        writer.append_row(0, &[&[UNCOMPRESSED, 1, 2][..]]).unwrap();
+        assert!(writer.append_row(1, &[&[ZSTD, 1, 2][..]]).is_err());
+        writer.commit().unwrap();
+
+        // reopened files keep their version
+        let mut writer = StaticFileWriter::new(dir.path(), segment).unwrap();
+        writer.increment_block(1).unwrap();
+        writer.append_row(1, &[&[UNCOMPRESSED, 3][..]]).unwrap();
+        writer.commit().unwrap();
+
+        let reader = StaticFileReader::open(dir.path(), segment, 0).unwrap();
+        assert_eq!(reader.header().version, VERSION_WITHOUT_MARKERS);
+        assert_eq!(reader.column(0, 0).unwrap(), Some(vec![UNCOMPRESSED, 1, 2]));
+        assert_eq!(reader.column(1, 0).unwrap(), Some(vec![UNCOMPRESSED, 3]));
+    }
+
+    #[test]
+    fn roll_to_next_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let segment = StaticFileSegment::Transactions;
+        let mut writer = StaticFileWriter::new(dir.path(), segment).unwrap();
+        writer.pending.block_count = BLOCKS_PER_STATIC_FILE - 1;
+        writer.increment_block(BLOCKS_PER_STATIC_FILE - 1).unwrap();
+        writer.append_row(0, &[b"tx 0"]).unwrap();
+        // empty block in the next file
+        writer.increment_block(BLOCKS_PER_STATIC_FILE).unwrap();
+        writer.increment_block(BLOCKS_PER_STATIC_FILE + 1).unwrap();
+        writer.append_row(1, &[b"tx 1"]).unwrap();
+        writer.commit().unwrap();
+
+        assert_eq!(list_files(dir.path(), segment).unwrap(), vec![0, BLOCKS_PER_STATIC_FILE]);
+        let first = StaticFileReader::open(dir.path(), segment, 0).unwrap();
+        assert_eq!(first.header().highest_block(), Some(BLOCKS_PER_STATIC_FILE - 1));
+        assert!(first.contains_row(0));
+        assert!(!first.contains_row(1));
+        let second = StaticFileReader::open(dir.path(), segment, BLOCKS_PER_STATIC_FILE).unwrap();
+        assert_eq!(second.header().highest_block(), Some(BLOCKS_PER_STATIC_FILE + 1));
+        assert_eq!(second.header().first_row, 1);
+        assert_eq!(second.column(1, 0).unwrap().as_deref(), Some(&b"tx 1"[..]));
+    }
+}
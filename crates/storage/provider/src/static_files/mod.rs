@@ -0,0 +1,157 @@
+//! Immutable flat-file storage of finalized block data.
+//!
+//! Finalized headers, transactions and receipts never change, so instead of keeping them in the
+//! database they are moved to append-only static files by the [StaticFileProducer]. Each
+//! [StaticFileSegment] is sharded into files of [BLOCKS_PER_STATIC_FILE] blocks, and every file
+//! consists of
+//!
+//! - a data file with the snappy compressed columns of all rows, and
+//! - an index file with a [header][StaticFileHeader] and the offsets of all columns in the data
+//!   file.
+//!
+//! The [StaticFileProvider] reads from the files, the [ShareableDatabase][crate::ShareableDatabase]
+//! uses it to transparently serve block data from either source.
+
+use reth_primitives::BlockNumber;
+use std::{fmt, io, str::FromStr};
+
+mod file;
+pub use file::{StaticFileHeader, StaticFileReader, StaticFileWriter};
+
+mod producer;
+pub use producer::StaticFileProducer;
+
+mod provider;
+pub use provider::StaticFileProvider;
+
+/// The number of blocks stored in a single static file.
+pub const BLOCKS_PER_STATIC_FILE: u64 = 500_000;
+
+/// Errors of static files.
+#[derive(Debug, thiserror::Error)]
+pub enum StaticFileError {
+    /// Failed to read or write a file.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Failed to compress or decompress a column.
+    #[error(transparent)]
+    Compression(#[from] snap::Error),
+    /// Failed to decode a column.
+    #[error(transparent)]
+    Decode(#[from] reth_db::Error),
+    /// The file is malformed.
+    #[error("invalid static file: {0}")]
+    InvalidFile(String),
+    /// A row or block was appended out of order.
+    #[error("static file {segment} expected {expected}, got {got}")]
+    OutOfOrder {
+        /// The segment of the file.
+        segment: StaticFileSegment,
+        /// The expected block or row.
+        expected: u64,
+        /// The appended block or row.
+        got: u64,
+    },
+}
+
+impl From<StaticFileError> for reth_interfaces::Error {
+    fn from(err: StaticFileError) -> Self {
+        match err {
+            StaticFileError::Decode(err) => err.into(),
+            err => crate::Error::StaticFile(err.to_string()).into(),
+        }
+    }
+}
+
+/// The kinds of data stored in static files.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum StaticFileSegment {
+    /// [tables::Headers][reth_db::tables::Headers] and
+    /// [tables::HeaderTD][reth_db::tables::HeaderTD], one row per block.
+    Headers,
+    /// [tables::Transactions][reth_db::tables::Transactions], one row per transaction.
+    Transactions,
+    /// [tables::Receipts][reth_db::tables::Receipts], one row per transaction.
+    Receipts,
+}
+
+impl StaticFileSegment {
+    /// All segments.
+    pub const ALL: [StaticFileSegment; 3] =
+        [StaticFileSegment::Headers, StaticFileSegment::Transactions, StaticFileSegment::Receipts];
+
+    /// Returns the name of the segment, used in file names.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            StaticFileSegment::Headers => "headers",
+            StaticFileSegment::Transactions => "transactions",
+            StaticFileSegment::Receipts => "receipts",
+        }
+    }
+
+    /// Returns the number of columns of each row.
+    pub const fn columns(&self) -> usize {
+        match self {
+            // header and total difficulty
+            StaticFileSegment::Headers => 2,
+            StaticFileSegment::Transactions | StaticFileSegment::Receipts => 1,
+        }
+    }
+
+    /// Returns the first block of the file that contains the block.
+    pub const fn file_start(block: BlockNumber) -> BlockNumber {
+        block - block % BLOCKS_PER_STATIC_FILE
+    }
+
+    /// Returns the file name of the segment file that starts at the block, without extension.
+    pub fn file_name(&self, file_start: BlockNumber) -> String {
+        format!("{}_{}_{}", self.as_str(), file_start, file_start + BLOCKS_PER_STATIC_FILE - 1)
+    }
+
+    /// Parses a file name created by [StaticFileSegment::file_name].
+    ///
+    /// Returns the segment and the first block of the file.
+    pub fn parse_file_name(name: &str) -> Option<(Self, BlockNumber)> {
+        let mut parts = name.split('_');
+        let segment = parts.next()?.parse().ok()?;
+        let start = parts.next()?.parse().ok()?;
+        let end: BlockNumber = parts.next()?.parse().ok()?;
+        (parts.next().is_none() && end == start + BLOCKS_PER_STATIC_FILE - 1)
+            .then_some((segment, start))
+    }
+}
+
+impl fmt::Display for StaticFileSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for StaticFileSegment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|segment| segment.as_str() == s)
+            .ok_or_else(|| format!("unknown static file segment: {s}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names() {
+        let name = StaticFileSegment::Receipts.file_name(BLOCKS_PER_STATIC_FILE);
+        assert_eq!(name, "receipts_500000_999999");
+        assert_eq!(
+            StaticFileSegment::parse_file_name(&name),
+            Some((StaticFileSegment::Receipts, BLOCKS_PER_STATIC_FILE))
+        );
+        assert_eq!(StaticFileSegment::parse_file_name("receipts_0_10"), None);
+        assert_eq!(StaticFileSegment::parse_file_name("blocks_0_499999"), None);
+        assert_eq!(StaticFileSegment::file_start(1_234_567), 1_000_000);
+    }
+}
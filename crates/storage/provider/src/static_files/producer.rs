@@ -0,0 +1,301 @@
+use super::{StaticFileProvider, StaticFileSegment, StaticFileWriter};
+use crate::{providers::read_receipt, Error};
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    table::Compress,
+    tables,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_interfaces::Result;
+use reth_primitives::BlockNumber;
+use std::{ops::RangeInclusive, sync::Arc};
+use tracing::debug;
+
+/// The default number of blocks moved to static files per batch.
+const DEFAULT_BATCH_SIZE: u64 = 10_000;
+
+/// Moves finalized headers, transactions and receipts from the database to static files.
+///
+/// Blocks are first committed to the static files and only then removed from the database, so
+/// the data is always available from at least one of them. Only canonical blocks below the
+/// progress of every stage are moved, since the stages read the moved tables of the blocks they
+/// process and of the block they continue from.
+#[derive(Debug)]
+pub struct StaticFileProducer<DB> {
+    /// The database to move the data from.
+    db: Arc<DB>,
+    /// The static files to move the data to.
+    static_files: Arc<StaticFileProvider>,
+    /// The ids of the stages whose progress limits the moved blocks.
+    stages: Vec<&'static str>,
+    /// The number of blocks moved per batch.
+    batch_size: u64,
+}
+
+impl<DB> Clone for StaticFileProducer<DB> {
+    fn clone(&self) -> Self {
+        Self {
+            db: Arc::clone(&self.db),
+            static_files: Arc::clone(&self.static_files),
+            stages: self.stages.clone(),
+            batch_size: self.batch_size,
+        }
+    }
+}
+
+impl<DB: Database> StaticFileProducer<DB> {
+    /// Creates a new producer for the pipeline with the stage ids.
+    ///
+    /// Stages without a checkpoint are treated as not having processed any block.
+    pub fn new(
+        db: Arc<DB>,
+        static_files: Arc<StaticFileProvider>,
+        stages: impl IntoIterator<Item = &'static str>,
+    ) -> Self {
+        let stages = stages.into_iter().collect();
+        Self { db, static_files, stages, batch_size: DEFAULT_BATCH_SIZE }
+    }
+
+    /// Sets the number of blocks moved per batch.
+    pub fn with_batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Moves all blocks up to the finalized block to static files.
+    ///
+    /// Returns the highest block that is stored in the static files of all segments.
+    pub fn run(&self, finalized_block: BlockNumber) -> Result<Option<BlockNumber>> {
+        // the block at the progress of a stage is still read when the stage continues
+        let target =
+            match self.lowest_stage_progress()?.and_then(|progress| progress.checked_sub(1)) {
+                Some(highest_processed) => highest_processed.min(finalized_block),
+                None => return Ok(None),
+            };
+
+        let mut highest = Some(target);
+        for segment in StaticFileSegment::ALL {
+            let mut writer = self.static_files.writer(segment)?;
+            // remove data left behind if the last run was interrupted
+            self.remove_moved(&writer)?;
+
+            while writer.next_block() <= target {
+                let start = writer.next_block();
+                let blocks = start..=target.min(start + self.batch_size - 1);
+                self.copy_blocks(&mut writer, blocks.clone())?;
+                writer.commit()?;
+                self.static_files.reload()?;
+                self.remove_moved(&writer)?;
+                debug!(target: "provider::static_files", %segment, ?blocks, "Moved blocks to static files");
+            }
+            highest = highest.min(writer.next_block().checked_sub(1));
+        }
+        Ok(highest)
+    }
+
+    /// Returns the lowest progress of all stages, or `None` if there are no stages.
+    fn lowest_stage_progress(&self) -> Result<Option<BlockNumber>> {
+        let progress = self.db.view(|tx| {
+            self.stages
+                .iter()
+                .map(|id| {
+                    Ok(tx.get::<tables::SyncStage>(id.as_bytes().to_vec())?.unwrap_or_default())
+                })
+                .collect::<std::result::Result<Vec<_>, reth_db::Error>>()
+        })??;
+        Ok(progress.into_iter().min())
+    }
+
+    /// Appends the blocks to the static files of the writer's segment.
+    fn copy_blocks(
+        &self,
+        writer: &mut StaticFileWriter,
+        blocks: RangeInclusive<BlockNumber>,
+    ) -> Result<()> {
+        let tx = self.db.tx()?;
+        for block_number in blocks {
+            let block_hash = tx
+                .get::<tables::CanonicalHeaders>(block_number)?
+                .ok_or(Error::BlockNumber { block_number })?;
+            let key = (block_number, block_hash).into();
+            writer.increment_block(block_number)?;
+
+            if writer.segment() == StaticFileSegment::Headers {
+                let header =
+                    tx.get::<tables::Headers>(key)?.ok_or(Error::BlockHash { block_hash })?;
+                let td = tx
+                    .get::<tables::HeaderTD>(key)?
+                    .ok_or(Error::TotalDifficulty { block_number })?;
+                writer.append_row(block_number, &[&header.compress(), &td.compress()])?;
+                continue
+            }
+
+            let body = tx
+                .get::<tables::BlockBodies>(key)?
+                .ok_or(Error::BlockBody { block_number, block_hash })?;
+            for tx_id in body.tx_id_range() {
+                let value = if writer.segment() == StaticFileSegment::Transactions {
+                    tx.get::<tables::Transactions>(tx_id)?
+                        .ok_or(Error::Transaction { tx_id })?
+                        .compress()
+                } else {
+                    // pruned receipts are stored as empty columns
+                    read_receipt(&tx, None, tx_id)?.map(Compress::compress).unwrap_or_default()
+                };
+                writer.append_row(tx_id, &[&value])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the committed data of the writer's segment from the database.
+    fn remove_moved(&self, writer: &StaticFileWriter) -> Result<()> {
+        self.db.update(|tx| {
+            match writer.segment() {
+                StaticFileSegment::Headers => {
+                    let keys = tx
+                        .cursor_read::<tables::Headers>()?
+                        .walk(Default::default())?
+                        .map(|entry| entry.map(|(key, _)| key))
+                        .take_while(|key| {
+                            key.as_ref().map_or(true, |key| key.number() < writer.next_block())
+                        })
+                        .collect::<std::result::Result<Vec<_>, _>>()?;
+                    for key in keys {
+                        // headers of forks are kept, they are not in static files
+                        if tx.get::<tables::CanonicalHeaders>(key.number())? == Some(key.hash()) {
+                            tx.delete::<tables::Headers>(key, None)?;
+                            tx.delete::<tables::HeaderTD>(key, None)?;
+                        }
+                    }
+                }
+                StaticFileSegment::Transactions => {
+                    remove_rows::<_, tables::Transactions>(tx, writer.next_row())?
+                }
+                StaticFileSegment::Receipts => {
+                    // the logs are part of the receipts in static files
+                    remove_rows::<_, tables::Receipts>(tx, writer.next_row())?;
+                    remove_rows::<_, tables::Logs>(tx, writer.next_row())?;
+                }
+            }
+            Ok::<_, reth_db::Error>(())
+        })??;
+        Ok(())
+    }
+}
+
+/// Removes all rows of the table below the transaction id.
+fn remove_rows<'a, TX, T>(tx: &TX, below: u64) -> std::result::Result<(), reth_db::Error>
+where
+    TX: DbTxMut<'a> + DbTx<'a>,
+    T: reth_db::table::Table<Key = u64>,
+{
+    let mut cursor = tx.cursor_write::<T>()?;
+    while let Some((tx_id, _)) = cursor.first()? {
+        if tx_id >= below {
+            break
+        }
+        cursor.delete_current()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        insert_canonical_block, BlockProvider, HeaderProvider, ReceiptProvider, ShareableDatabase,
+        TransactionsProvider,
+    };
+    use reth_db::mdbx::{test_utils::create_test_db, EnvKind, WriteMap};
+    use reth_interfaces::test_utils::generators::random_block;
+    use reth_primitives::{rpc::BlockId, Receipt};
+
+    #[test]
+    fn move_blocks_to_static_files() {
+        let db = create_test_db::<WriteMap>(EnvKind::RW);
+        let blocks = [
+            random_block(0, None, Some(2), Some(0)),
+            random_block(1, None, Some(0), Some(0)),
+            random_block(2, None, Some(3), Some(0)),
+            random_block(3, None, Some(1), Some(0)),
+        ];
+        db.update(|tx| {
+            for block in blocks.iter() {
+                insert_canonical_block(tx, block, false).unwrap();
+            }
+            // the receipt of the first transaction was pruned
+            for tx_id in 1..6 {
+                tx.put::<tables::Receipts>(
+                    tx_id,
+                    Receipt { cumulative_gas_used: tx_id, ..Default::default() },
+                )
+                .unwrap();
+                tx.put::<tables::Logs>(tx_id, Default::default()).unwrap();
+            }
+            tx.put::<tables::SyncStage>(b"Headers".to_vec(), 3).unwrap();
+        })
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let static_files = Arc::new(StaticFileProvider::new(dir.path()).unwrap());
+        let producer =
+            StaticFileProducer::new(db.clone(), static_files.clone(), ["Headers", "Execution"])
+                .with_batch_size(2);
+        // nothing is moved before every stage processed the blocks
+        assert_eq!(producer.run(3).unwrap(), None);
+        for segment in StaticFileSegment::ALL {
+            assert_eq!(static_files.highest_block(segment), None);
+        }
+
+        // the block at the lowest stage progress is kept in the database
+        db.update(|tx| tx.put::<tables::SyncStage>(b"Execution".to_vec(), 3)).unwrap().unwrap();
+        assert_eq!(producer.run(3).unwrap(), Some(2));
+        assert_eq!(producer.run(3).unwrap(), Some(2));
+        for segment in StaticFileSegment::ALL {
+            assert_eq!(static_files.highest_block(segment), Some(2));
+        }
+
+        // the data of the moved blocks is only in static files
+        let tx = db.tx().unwrap();
+        let headers =
+            tx.cursor_read::<tables::Headers>().unwrap().walk(Default::default()).unwrap().count();
+        assert_eq!(headers, 1);
+        assert_eq!(
+            tx.cursor_read::<tables::HeaderTD>().unwrap().first().unwrap().unwrap().0.number(),
+            3
+        );
+        assert_eq!(
+            tx.cursor_read::<tables::Transactions>().unwrap().first().unwrap().unwrap().0,
+            5
+        );
+        assert_eq!(tx.cursor_read::<tables::Receipts>().unwrap().first().unwrap().unwrap().0, 5);
+        assert_eq!(tx.cursor_read::<tables::Logs>().unwrap().first().unwrap().unwrap().0, 5);
+        drop(tx);
+
+        let provider = ShareableDatabase::new(db).with_static_files(static_files);
+        for block in blocks.iter() {
+            assert_eq!(
+                provider.header(&block.hash()).unwrap().as_ref(),
+                Some(block.header.as_ref())
+            );
+            assert!(provider.header_td(&block.hash()).unwrap().is_some());
+            let id =
+                BlockId::Number(reth_primitives::rpc::BlockNumber::Number(block.number.into()));
+            assert_eq!(provider.block(id).unwrap(), Some(block.clone().unseal()));
+            assert_eq!(
+                provider.transactions_by_block(block.number.into()).unwrap(),
+                Some(block.body.clone())
+            );
+        }
+        assert_eq!(
+            provider.transaction_by_block_and_index(blocks[2].hash().into(), 2).unwrap().as_ref(),
+            Some(&blocks[2].body[2])
+        );
+        let receipts = provider.receipts_by_block(blocks[2].number.into()).unwrap().unwrap();
+        assert_eq!(receipts.iter().map(|r| r.cumulative_gas_used).collect::<Vec<_>>(), [2, 3, 4]);
+        // the pruned receipt is missing from the static files too
+        assert_eq!(provider.receipts_by_block(blocks[0].number.into()).unwrap(), None);
+    }
+}
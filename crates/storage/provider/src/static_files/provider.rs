@@ -0,0 +1,115 @@
+use super::{
+    file::list_files, StaticFileError, StaticFileReader, StaticFileSegment, StaticFileWriter,
+};
+use parking_lot::RwLock;
+use reth_db::{table::Decompress, tables::codecs::CompactU256};
+use reth_primitives::{BlockNumber, Header, Receipt, TransactionSigned, TxNumber, U256};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Reads finalized block data from the static files in a directory.
+///
+/// The provider keeps the files open, [Self::reload] has to be called after a
+/// [StaticFileWriter] committed new data.
+#[derive(Debug)]
+pub struct StaticFileProvider {
+    /// The directory of the static files.
+    dir: PathBuf,
+    /// The open files of each segment, keyed by their first block.
+    files: RwLock<BTreeMap<StaticFileSegment, Vec<Arc<StaticFileReader>>>>,
+}
+
+impl StaticFileProvider {
+    /// Opens all static files in the directory, creating it if it does not exist.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, StaticFileError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        let provider = Self { dir, files: Default::default() };
+        provider.reload()?;
+        Ok(provider)
+    }
+
+    /// Returns the directory of the static files.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Reopens all static files, making newly committed data visible.
+    pub fn reload(&self) -> Result<(), StaticFileError> {
+        let mut files = BTreeMap::new();
+        for segment in StaticFileSegment::ALL {
+            let readers = list_files(&self.dir, segment)?
+                .into_iter()
+                .map(|start| StaticFileReader::open(&self.dir, segment, start).map(Arc::new))
+                .collect::<Result<Vec<_>, _>>()?;
+            files.insert(segment, readers);
+        }
+        *self.files.write() = files;
+        Ok(())
+    }
+
+    /// Returns a writer that appends to the static files of the segment.
+    ///
+    /// There must be only one writer per segment at a time.
+    pub fn writer(&self, segment: StaticFileSegment) -> Result<StaticFileWriter, StaticFileError> {
+        StaticFileWriter::new(&self.dir, segment)
+    }
+
+    /// Returns the highest block of the segment that is stored in static files.
+    pub fn highest_block(&self, segment: StaticFileSegment) -> Option<BlockNumber> {
+        self.files.read().get(&segment)?.iter().rev().find_map(|file| file.header().highest_block())
+    }
+
+    /// Returns the header of the block.
+    pub fn header(&self, number: BlockNumber) -> Result<Option<Header>, StaticFileError> {
+        self.decode(StaticFileSegment::Headers, number, 0)
+    }
+
+    /// Returns the total difficulty of the block.
+    pub fn header_td(&self, number: BlockNumber) -> Result<Option<U256>, StaticFileError> {
+        Ok(self.decode::<CompactU256>(StaticFileSegment::Headers, number, 1)?.map(|td| td.0))
+    }
+
+    /// Returns the transaction with the id.
+    pub fn transaction(
+        &self,
+        tx_id: TxNumber,
+    ) -> Result<Option<TransactionSigned>, StaticFileError> {
+        self.decode(StaticFileSegment::Transactions, tx_id, 0)
+    }
+
+    /// Returns the receipt of the transaction with the id.
+    pub fn receipt(&self, tx_id: TxNumber) -> Result<Option<Receipt>, StaticFileError> {
+        self.decode(StaticFileSegment::Receipts, tx_id, 0)
+    }
+
+    /// Returns the decoded column of the row, if it is stored in a static file.
+    fn decode<T: Decompress>(
+        &self,
+        segment: StaticFileSegment,
+        row: u64,
+        column: usize,
+    ) -> Result<Option<T>, StaticFileError> {
+        let file = match self.file(segment, row) {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+        match file.column(row, column)? {
+            Some(value) => Ok(Some(T::decompress(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the file of the segment that contains the row.
+    fn file(&self, segment: StaticFileSegment, row: u64) -> Option<Arc<StaticFileReader>> {
+        let files = self.files.read();
+        let files = files.get(&segment)?;
+        // the rows of the files are increasing
+        let index = files.partition_point(|file| file.header().first_row <= row).checked_sub(1)?;
+        let file = &files[index];
+        file.contains_row(row).then(|| Arc::clone(file))
+    }
+}
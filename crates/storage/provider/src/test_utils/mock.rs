@@ -1,10 +1,14 @@
-use crate::{AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider, StateProvider};
+use crate::{
+    AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider, NodeDataProvider,
+    ReceiptProvider, SnapProvider, StateProvider, StateRange, TransactionsProvider,
+};
 use parking_lot::Mutex;
 use reth_interfaces::Result;
 use reth_primitives::{
     keccak256,
     rpc::{BlockId, BlockNumber},
-    Account, Address, Block, BlockHash, Bytes, ChainInfo, Header, StorageKey, StorageValue, H256,
+    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockWithSenders, Bytes, ChainInfo,
+    Header, Receipt, StorageKey, StorageValue, TransactionMeta, TransactionSigned, TxHash, H256,
     U256,
 };
 use std::{collections::HashMap, sync::Arc};
@@ -18,6 +22,8 @@ pub struct MockEthProvider {
     pub headers: Arc<Mutex<HashMap<H256, Header>>>,
     /// Local account store
     pub accounts: Arc<Mutex<HashMap<Address, ExtendedAccount>>>,
+    /// Local receipt store, by transaction hash
+    pub receipts: Arc<Mutex<HashMap<TxHash, Receipt>>>,
 }
 
 /// An extended account for local store
@@ -79,6 +85,27 @@ impl MockEthProvider {
         self.accounts.lock().insert(address, account);
     }
 
+    /// Add the receipt of the transaction to local receipt store
+    pub fn add_receipt(&self, tx_hash: TxHash, receipt: Receipt) {
+        self.receipts.lock().insert(tx_hash, receipt);
+    }
+
+    /// Add multiple receipts to local receipt store
+    pub fn extend_receipts(&self, iter: impl IntoIterator<Item = (TxHash, Receipt)>) {
+        for (tx_hash, receipt) in iter.into_iter() {
+            self.add_receipt(tx_hash, receipt)
+        }
+    }
+
+    /// Returns the block with the given hash or number.
+    fn find_block(&self, block: BlockHashOrNumber) -> Option<Block> {
+        let lock = self.blocks.lock();
+        match block {
+            BlockHashOrNumber::Hash(hash) => lock.get(&hash).cloned(),
+            BlockHashOrNumber::Number(num) => lock.values().find(|b| b.number == num).cloned(),
+        }
+    }
+
     /// Add account to local account store
     pub fn extend_accounts(&self, iter: impl IntoIterator<Item = (Address, ExtendedAccount)>) {
         for (address, account) in iter.into_iter() {
@@ -154,6 +181,13 @@ impl BlockProvider for MockEthProvider {
         }
     }
 
+    fn block_with_senders(&self, id: BlockId) -> Result<Option<BlockWithSenders>> {
+        Ok(self.block(id)?.and_then(|block| {
+            let senders = block.body.iter().map(|tx| tx.recover_signer()).collect::<Option<_>>()?;
+            BlockWithSenders::new(block, senders)
+        }))
+    }
+
     fn block_number(&self, hash: H256) -> Result<Option<reth_primitives::BlockNumber>> {
         let lock = self.blocks.lock();
         let num = lock.iter().find_map(|(h, b)| if *h == hash { Some(b.number) } else { None });
@@ -167,6 +201,97 @@ impl AccountProvider for MockEthProvider {
     }
 }
 
+impl TransactionsProvider for MockEthProvider {
+    fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>> {
+        Ok(self.transaction_by_hash_with_meta(hash)?.map(|(tx, _)| tx))
+    }
+
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        let lock = self.blocks.lock();
+        Ok(lock.iter().find_map(|(block_hash, block)| {
+            block.body.iter().enumerate().find(|(_, tx)| tx.hash == hash).map(|(index, tx)| {
+                let meta = TransactionMeta {
+                    tx_hash: hash,
+                    index: index as u64,
+                    block_hash: *block_hash,
+                    block_number: block.number,
+                };
+                (tx.clone(), meta)
+            })
+        }))
+    }
+
+    fn transaction_by_block_and_index(
+        &self,
+        block: BlockHashOrNumber,
+        index: u64,
+    ) -> Result<Option<TransactionSigned>> {
+        Ok(self.find_block(block).and_then(|block| block.body.get(index as usize).cloned()))
+    }
+
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        Ok(self.find_block(block).map(|block| block.body))
+    }
+}
+
+impl ReceiptProvider for MockEthProvider {
+    fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>> {
+        Ok(self.receipts.lock().get(&hash).cloned())
+    }
+
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        let block = match self.find_block(block) {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let lock = self.receipts.lock();
+        Ok(block.body.iter().map(|tx| lock.get(&tx.hash).cloned()).collect())
+    }
+}
+
+impl NodeDataProvider for MockEthProvider {
+    fn node_data(&self, hash: H256) -> Result<Option<Bytes>> {
+        self.bytecode_by_hash(hash)
+    }
+}
+
+impl SnapProvider for MockEthProvider {
+    fn account_range(
+        &self,
+        _root: H256,
+        _origin: H256,
+        _limit: H256,
+        _max_bytes: usize,
+    ) -> Result<Option<StateRange>> {
+        Ok(None)
+    }
+
+    fn storage_range(
+        &self,
+        _root: H256,
+        _account: H256,
+        _origin: H256,
+        _limit: H256,
+        _max_bytes: usize,
+    ) -> Result<Option<StateRange>> {
+        Ok(None)
+    }
+
+    fn bytecode(&self, code_hash: H256) -> Result<Option<Bytes>> {
+        self.bytecode_by_hash(code_hash)
+    }
+
+    fn trie_nodes(&self, _root: H256, _paths: &[Bytes]) -> Result<Option<Vec<Bytes>>> {
+        Ok(None)
+    }
+}
+
 impl StateProvider for MockEthProvider {
     fn bytecode_by_hash(&self, code_hash: H256) -> Result<Option<Bytes>> {
         let lock = self.accounts.lock();
@@ -1,11 +1,13 @@
 use crate::{
-    AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider, StateProvider,
-    StateProviderFactory,
+    AccountProvider, BlockHashProvider, BlockProvider, HeaderProvider, NodeDataProvider,
+    ReceiptProvider, SnapProvider, StateProvider, StateProviderFactory, StateRange,
+    TransactionsProvider,
 };
 use reth_interfaces::Result;
 use reth_primitives::{
-    rpc::BlockId, Account, Address, Block, BlockHash, BlockNumber, Bytes, ChainInfo, Header,
-    StorageKey, StorageValue, H256, U256,
+    rpc::BlockId, Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber,
+    BlockWithSenders, Bytes, ChainInfo, Header, Receipt, StorageKey, StorageValue, TransactionMeta,
+    TransactionSigned, TxHash, H256, U256,
 };
 
 /// Supports various api interfaces for testing purposes.
@@ -34,6 +36,10 @@ impl BlockProvider for NoopProvider {
         Ok(None)
     }
 
+    fn block_with_senders(&self, _id: BlockId) -> Result<Option<BlockWithSenders>> {
+        Ok(None)
+    }
+
     fn block_number(&self, _hash: H256) -> Result<Option<BlockNumber>> {
         Ok(None)
     }
@@ -53,6 +59,81 @@ impl HeaderProvider for NoopProvider {
     }
 }
 
+impl TransactionsProvider for NoopProvider {
+    fn transaction_by_hash(&self, _hash: TxHash) -> Result<Option<TransactionSigned>> {
+        Ok(None)
+    }
+
+    fn transaction_by_hash_with_meta(
+        &self,
+        _hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>> {
+        Ok(None)
+    }
+
+    fn transaction_by_block_and_index(
+        &self,
+        _block: BlockHashOrNumber,
+        _index: u64,
+    ) -> Result<Option<TransactionSigned>> {
+        Ok(None)
+    }
+
+    fn transactions_by_block(
+        &self,
+        _block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>> {
+        Ok(None)
+    }
+}
+
+impl ReceiptProvider for NoopProvider {
+    fn receipt_by_hash(&self, _hash: TxHash) -> Result<Option<Receipt>> {
+        Ok(None)
+    }
+
+    fn receipts_by_block(&self, _block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>> {
+        Ok(None)
+    }
+}
+
+impl NodeDataProvider for NoopProvider {
+    fn node_data(&self, _hash: H256) -> Result<Option<Bytes>> {
+        Ok(None)
+    }
+}
+
+impl SnapProvider for NoopProvider {
+    fn account_range(
+        &self,
+        _root: H256,
+        _origin: H256,
+        _limit: H256,
+        _max_bytes: usize,
+    ) -> Result<Option<StateRange>> {
+        Ok(None)
+    }
+
+    fn storage_range(
+        &self,
+        _root: H256,
+        _account: H256,
+        _origin: H256,
+        _limit: H256,
+        _max_bytes: usize,
+    ) -> Result<Option<StateRange>> {
+        Ok(None)
+    }
+
+    fn bytecode(&self, _code_hash: H256) -> Result<Option<Bytes>> {
+        Ok(None)
+    }
+
+    fn trie_nodes(&self, _root: H256, _paths: &[Bytes]) -> Result<Option<Vec<Bytes>>> {
+        Ok(None)
+    }
+}
+
 impl AccountProvider for NoopProvider {
     fn basic_account(&self, _address: Address) -> Result<Option<Account>> {
         Ok(None)
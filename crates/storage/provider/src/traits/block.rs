@@ -2,7 +2,7 @@ use super::BlockHashProvider;
 use reth_interfaces::Result;
 use reth_primitives::{
     rpc::{BlockId, BlockNumber},
-    Block, ChainInfo, H256, U256,
+    Block, BlockWithSenders, ChainInfo, H256, U256,
 };
 
 /// Api trait for fetching `Block` related data.
@@ -13,6 +13,10 @@ pub trait BlockProvider: BlockHashProvider + Send + Sync {
     /// Returns the block. Returns `None` if block is not found.
     fn block(&self, id: BlockId) -> Result<Option<Block>>;
 
+    /// Returns the block with the senders of its transactions. Returns `None` if block is not
+    /// found.
+    fn block_with_senders(&self, id: BlockId) -> Result<Option<BlockWithSenders>>;
+
     /// Converts the `BlockNumber` variants.
     fn convert_block_number(
         &self,
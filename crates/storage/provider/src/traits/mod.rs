@@ -12,5 +12,20 @@ pub use block_hash::BlockHashProvider;
 mod header;
 pub use header::HeaderProvider;
 
+mod node_data;
+pub use node_data::NodeDataProvider;
+
+mod receipts;
+pub use receipts::ReceiptProvider;
+
+mod snap;
+pub use snap::{SnapProvider, StateRange};
+
+mod transactions;
+pub use transactions::TransactionsProvider;
+
+mod writer;
+pub use writer::{BlockExecutionWriter, BlockWriter};
+
 mod state;
 pub use state::{StateProvider, StateProviderFactory};
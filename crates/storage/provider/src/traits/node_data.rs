@@ -0,0 +1,11 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{Bytes, H256};
+
+/// Client trait for fetching state entries by their hash, as requested by `GetNodeData`.
+#[auto_impl(&)]
+pub trait NodeDataProvider: Send + Sync {
+    /// Get the contract bytecode or trie node with the given hash. Returns `None` if no such
+    /// entry is known.
+    fn node_data(&self, hash: H256) -> Result<Option<Bytes>>;
+}
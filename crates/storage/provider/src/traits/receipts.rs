@@ -0,0 +1,21 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{BlockHashOrNumber, Receipt, TxHash, H256};
+
+/// Client trait for fetching [Receipt] data.
+#[auto_impl(&)]
+pub trait ReceiptProvider: Send + Sync {
+    /// Get the receipt of the transaction with the given hash. Returns `None` if the transaction
+    /// is unknown or was not executed yet.
+    fn receipt_by_hash(&self, hash: TxHash) -> Result<Option<Receipt>>;
+
+    /// Get the receipts of all transactions of the block, in transaction order. Returns `None`
+    /// if the block is unknown or was not executed yet.
+    fn receipts_by_block(&self, block: BlockHashOrNumber) -> Result<Option<Vec<Receipt>>>;
+
+    /// Get the receipts of all transactions of the block with the given hash, in transaction
+    /// order. Returns `None` if the block is unknown.
+    fn receipts_by_block_hash(&self, block_hash: H256) -> Result<Option<Vec<Receipt>>> {
+        self.receipts_by_block(block_hash.into())
+    }
+}
@@ -0,0 +1,55 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{Bytes, H256};
+
+/// A range of consecutive trie leaves, together with the merkle proof of its boundaries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateRange {
+    /// The hashed keys and encoded values of the leaves, in ascending key order.
+    pub entries: Vec<(H256, Bytes)>,
+    /// The trie nodes proving the first and the last key of the range.
+    pub proof: Vec<Bytes>,
+}
+
+/// Client trait for serving the state of the latest block, as requested by the `snap` protocol.
+///
+/// All functions return `None` if the given state root is not the root of the latest state.
+#[auto_impl(&)]
+pub trait SnapProvider: Send + Sync {
+    /// Get the accounts with hashes in `origin..=limit`, with the first account after `limit`
+    /// terminating the range. Stops early once the range exceeds `max_bytes`.
+    ///
+    /// Accounts are encoded in the "slim" format, where the storage root and code hash are empty
+    /// if the account has no storage or code.
+    fn account_range(
+        &self,
+        root: H256,
+        origin: H256,
+        limit: H256,
+        max_bytes: usize,
+    ) -> Result<Option<StateRange>>;
+
+    /// Get the storage slots of the account with the given hash, like
+    /// [`SnapProvider::account_range`].
+    ///
+    /// The proof is only included if `origin` is not zero or if slots of the account follow the
+    /// range, because it was cut short by `limit` or `max_bytes`.
+    fn storage_range(
+        &self,
+        root: H256,
+        account: H256,
+        origin: H256,
+        limit: H256,
+        max_bytes: usize,
+    ) -> Result<Option<StateRange>>;
+
+    /// Get the contract bytecode with the given hash.
+    fn bytecode(&self, code_hash: H256) -> Result<Option<Bytes>>;
+
+    /// Get the trie nodes for a set of compact encoded paths.
+    ///
+    /// A single path refers to a node in the account trie. Otherwise the first entry is the
+    /// hash of an account, followed by paths into its storage trie. Stops at the first node
+    /// that is not found.
+    fn trie_nodes(&self, root: H256, paths: &[Bytes]) -> Result<Option<Vec<Bytes>>>;
+}
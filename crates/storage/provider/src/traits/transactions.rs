@@ -0,0 +1,32 @@
+use auto_impl::auto_impl;
+use reth_interfaces::Result;
+use reth_primitives::{BlockHashOrNumber, TransactionMeta, TransactionSigned, TxHash};
+
+/// Client trait for fetching [TransactionSigned] related data.
+#[auto_impl(&)]
+pub trait TransactionsProvider: Send + Sync {
+    /// Get transaction by transaction hash.
+    fn transaction_by_hash(&self, hash: TxHash) -> Result<Option<TransactionSigned>>;
+
+    /// Get transaction by transaction hash, together with the block it is included in and its
+    /// index in that block.
+    fn transaction_by_hash_with_meta(
+        &self,
+        hash: TxHash,
+    ) -> Result<Option<(TransactionSigned, TransactionMeta)>>;
+
+    /// Get the transaction at the given index of the block. Returns `None` if the block is
+    /// unknown or has fewer transactions.
+    fn transaction_by_block_and_index(
+        &self,
+        block: BlockHashOrNumber,
+        index: u64,
+    ) -> Result<Option<TransactionSigned>>;
+
+    /// Get all transactions of the block, in block order. Returns `None` if the block is
+    /// unknown.
+    fn transactions_by_block(
+        &self,
+        block: BlockHashOrNumber,
+    ) -> Result<Option<Vec<TransactionSigned>>>;
+}
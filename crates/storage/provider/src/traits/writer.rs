@@ -0,0 +1,55 @@
+use crate::execution_result::ExecutionResult;
+use reth_interfaces::Result;
+use reth_primitives::{Address, BlockNumber, SealedBlock};
+
+/// Write side of the block storage.
+///
+/// All writes happen in the transaction the trait is called on, nothing is committed. If a call
+/// fails, the transaction should be dropped, as it may contain partial writes.
+pub trait BlockWriter {
+    /// Inserts the block on top of the canonical chain.
+    ///
+    /// This writes the header, total difficulty, ommers, body, transactions, senders, the
+    /// transaction hash lookup and the transition indices of the block. The parent of the block
+    /// must be the canonical tip.
+    ///
+    /// The `senders` are recovered from the transactions if they are not provided.
+    /// `has_block_reward` reserves a state transition for the block reward after the
+    /// transactions.
+    fn insert_block(
+        &self,
+        block: &SealedBlock,
+        senders: Option<Vec<Address>>,
+        has_block_reward: bool,
+    ) -> Result<()>;
+
+    /// Removes all canonical blocks above `block`, the inverse of [BlockWriter::insert_block].
+    ///
+    /// The receipts of the removed transactions are removed as well, but the state is left
+    /// untouched, see [BlockExecutionWriter::unwind_blocks_above].
+    fn remove_blocks_above(&self, block: BlockNumber) -> Result<()>;
+}
+
+/// Write side of the block storage that also keeps the state in sync.
+pub trait BlockExecutionWriter: BlockWriter {
+    /// Inserts the block on top of the canonical chain together with the result of its
+    /// execution.
+    ///
+    /// In addition to [BlockWriter::insert_block], this writes the receipts, account and storage
+    /// changesets, plain state, bytecodes, hashed state and history indices, and updates the
+    /// state trie. Fails with [Error::StateRootMismatch][crate::Error::StateRootMismatch] if the
+    /// resulting state root does not match the header.
+    ///
+    /// The trie of the parent state must be present in the database.
+    fn append_block_with_execution(
+        &self,
+        block: &SealedBlock,
+        senders: Option<Vec<Address>>,
+        result: ExecutionResult,
+        spurious_dragon_active: bool,
+    ) -> Result<()>;
+
+    /// Reverts the state changes of all canonical blocks above `block` and removes the blocks,
+    /// the inverse of [BlockExecutionWriter::append_block_with_execution].
+    fn unwind_blocks_above(&self, block: BlockNumber) -> Result<()>;
+}
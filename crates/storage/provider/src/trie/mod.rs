@@ -1,9 +1,10 @@
-use crate::Transaction;
+//! Merkle Patricia trie of the state, backed by the [tables::AccountsTrie] and
+//! [tables::StoragesTrie] tables.
+
 use cita_trie::{PatriciaTrie, Trie};
 use hasher::HasherKeccak;
 use reth_db::{
     cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO},
-    database::Database,
     models::{AccountBeforeTx, TransitionIdAddress},
     tables,
     transaction::{DbTx, DbTxMut},
@@ -21,10 +22,11 @@ use std::{
     ops::Range,
     sync::Arc,
 };
-use tracing::*;
 
+/// Errors of trie operations.
+#[allow(missing_docs)]
 #[derive(Debug, thiserror::Error)]
-pub(crate) enum TrieError {
+pub enum TrieError {
     #[error("Some error occurred: {0}")]
     InternalError(#[from] cita_trie::TrieError),
     #[error("The root node wasn't found in the DB")]
@@ -35,14 +37,23 @@ pub(crate) enum TrieError {
     DecodeError(#[from] DecodeError),
 }
 
+impl From<TrieError> for reth_interfaces::Error {
+    fn from(err: TrieError) -> Self {
+        match err {
+            TrieError::DatabaseError(err) => err.into(),
+            err => crate::Error::Trie(err.to_string()).into(),
+        }
+    }
+}
+
 /// Database wrapper implementing HashDB trait.
-struct HashDatabase<'tx, 'itx, DB: Database> {
-    tx: &'tx Transaction<'itx, DB>,
+struct HashDatabase<'tx, TX> {
+    tx: &'tx TX,
 }
 
-impl<'tx, 'itx, DB> cita_trie::DB for HashDatabase<'tx, 'itx, DB>
+impl<'tx, 'itx, TX> cita_trie::DB for HashDatabase<'tx, TX>
 where
-    DB: Database,
+    TX: DbTx<'itx> + DbTxMut<'itx>,
 {
     type Error = TrieError;
 
@@ -70,9 +81,9 @@ where
     }
 }
 
-impl<'tx, 'itx, DB: Database> HashDatabase<'tx, 'itx, DB> {
+impl<'tx, 'itx, TX: DbTx<'itx> + DbTxMut<'itx>> HashDatabase<'tx, TX> {
     /// Instantiates a new Database for the accounts trie, with an empty root
-    fn new(tx: &'tx Transaction<'itx, DB>) -> Result<Self, TrieError> {
+    fn new(tx: &'tx TX) -> Result<Self, TrieError> {
         let root = EMPTY_ROOT;
         if tx.get::<tables::AccountsTrie>(root)?.is_none() {
             tx.put::<tables::AccountsTrie>(root, [EMPTY_STRING_CODE].to_vec())?;
@@ -81,7 +92,7 @@ impl<'tx, 'itx, DB: Database> HashDatabase<'tx, 'itx, DB> {
     }
 
     /// Instantiates a new Database for the accounts trie, with an existing root
-    fn from_root(tx: &'tx Transaction<'itx, DB>, root: H256) -> Result<Self, TrieError> {
+    fn from_root(tx: &'tx TX, root: H256) -> Result<Self, TrieError> {
         if root == EMPTY_ROOT {
             return Self::new(tx)
         }
@@ -91,14 +102,14 @@ impl<'tx, 'itx, DB: Database> HashDatabase<'tx, 'itx, DB> {
 }
 
 /// Database wrapper implementing HashDB trait.
-struct DupHashDatabase<'tx, 'itx, DB: Database> {
-    tx: &'tx Transaction<'itx, DB>,
+struct DupHashDatabase<'tx, TX> {
+    tx: &'tx TX,
     key: H256,
 }
 
-impl<'tx, 'itx, DB> cita_trie::DB for DupHashDatabase<'tx, 'itx, DB>
+impl<'tx, 'itx, TX> cita_trie::DB for DupHashDatabase<'tx, TX>
 where
-    DB: Database,
+    TX: DbTx<'itx> + DbTxMut<'itx>,
 {
     type Error = TrieError;
 
@@ -134,9 +145,9 @@ where
     }
 }
 
-impl<'tx, 'itx, DB: Database> DupHashDatabase<'tx, 'itx, DB> {
+impl<'tx, 'itx, TX: DbTx<'itx> + DbTxMut<'itx>> DupHashDatabase<'tx, TX> {
     /// Instantiates a new Database for the storage trie, with an empty root
-    fn new(tx: &'tx Transaction<'itx, DB>, key: H256) -> Result<Self, TrieError> {
+    fn new(tx: &'tx TX, key: H256) -> Result<Self, TrieError> {
         let root = EMPTY_ROOT;
         let mut cursor = tx.cursor_dup_write::<tables::StoragesTrie>()?;
         if cursor.seek_by_key_subkey(key, root)?.is_none() {
@@ -149,7 +160,7 @@ impl<'tx, 'itx, DB: Database> DupHashDatabase<'tx, 'itx, DB> {
     }
 
     /// Instantiates a new Database for the storage trie, with an existing root
-    fn from_root(tx: &'tx Transaction<'itx, DB>, key: H256, root: H256) -> Result<Self, TrieError> {
+    fn from_root(tx: &'tx TX, key: H256, root: H256) -> Result<Self, TrieError> {
         if root == EMPTY_ROOT {
             return Self::new(tx, key)
         }
@@ -190,14 +201,15 @@ impl EthAccount {
     }
 }
 
+/// Calculates and updates the state root, storing the trie nodes in the database.
 #[derive(Debug, Default)]
-pub(crate) struct DBTrieLoader;
+pub struct DBTrieLoader;
 
 impl DBTrieLoader {
     /// Calculates the root of the state trie, saving intermediate hashes in the database.
-    pub(crate) fn calculate_root<DB: Database>(
+    pub fn calculate_root<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
         &self,
-        tx: &Transaction<'_, DB>,
+        tx: &TX,
     ) -> Result<H256, TrieError> {
         tx.clear::<tables::AccountsTrie>()?;
         tx.clear::<tables::StoragesTrie>()?;
@@ -226,9 +238,9 @@ impl DBTrieLoader {
         Ok(root)
     }
 
-    fn calculate_storage_root<DB: Database>(
+    fn calculate_storage_root<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
         &self,
-        tx: &Transaction<'_, DB>,
+        tx: &TX,
         address: H256,
     ) -> Result<H256, TrieError> {
         let db = Arc::new(DupHashDatabase::new(tx, address)?);
@@ -255,9 +267,9 @@ impl DBTrieLoader {
     }
 
     /// Calculates the root of the state trie by updating an existing trie.
-    pub(crate) fn update_root<DB: Database>(
+    pub fn update_root<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
         &self,
-        tx: &Transaction<'_, DB>,
+        tx: &TX,
         root: H256,
         tid_range: Range<TransitionId>,
     ) -> Result<H256, TrieError> {
@@ -294,9 +306,9 @@ impl DBTrieLoader {
         Ok(root)
     }
 
-    fn update_storage_root<DB: Database>(
+    fn update_storage_root<'tx, TX: DbTx<'tx> + DbTxMut<'tx>>(
         &self,
-        tx: &Transaction<'_, DB>,
+        tx: &TX,
         root: H256,
         address: H256,
         changed_storages: BTreeSet<H256>,
@@ -324,9 +336,9 @@ impl DBTrieLoader {
         Ok(root)
     }
 
-    fn gather_changes<DB: Database>(
+    fn gather_changes<'tx, TX: DbTx<'tx>>(
         &self,
-        tx: &Transaction<'_, DB>,
+        tx: &TX,
         tid_range: Range<TransitionId>,
     ) -> Result<BTreeMap<H256, BTreeSet<H256>>, TrieError> {
         let mut account_cursor = tx.cursor_read::<tables::AccountChangeSet>()?;
@@ -366,14 +378,15 @@ impl DBTrieLoader {
 mod tests {
     use super::*;
     use assert_matches::assert_matches;
-    use reth_db::{mdbx::test_utils::create_test_rw_db, tables, transaction::DbTxMut};
+    use reth_db::{
+        database::Database, mdbx::test_utils::create_test_rw_db, tables, transaction::DbTxMut,
+    };
     use reth_primitives::{
         hex_literal::hex,
         keccak256,
         proofs::{genesis_state_root, KeccakHasher, EMPTY_ROOT},
-        Address, ChainSpec,
+        Address, ChainSpec, MAINNET,
     };
-    use reth_staged_sync::utils::chainspec::chain_spec_value_parser;
     use std::{collections::HashMap, str::FromStr};
     use triehash::sec_trie_root;
 
@@ -381,7 +394,7 @@ mod tests {
     fn empty_trie() {
         let trie = DBTrieLoader::default();
         let db = create_test_rw_db();
-        let tx = Transaction::new(db.as_ref()).unwrap();
+        let tx = db.tx_mut().unwrap();
         assert_matches!(trie.calculate_root(&tx), Ok(got) if got == EMPTY_ROOT);
     }
 
@@ -389,7 +402,7 @@ mod tests {
     fn single_account_trie() {
         let trie = DBTrieLoader::default();
         let db = create_test_rw_db();
-        let tx = Transaction::new(db.as_ref()).unwrap();
+        let tx = db.tx_mut().unwrap();
         let address = Address::from_str("9fe4abd71ad081f091bd06dd1c16f7e92927561e").unwrap();
         let account = Account { nonce: 0, balance: U256::ZERO, bytecode_hash: None };
         tx.put::<tables::HashedAccount>(keccak256(address), account).unwrap();
@@ -406,7 +419,7 @@ mod tests {
     fn two_accounts_trie() {
         let trie = DBTrieLoader::default();
         let db = create_test_rw_db();
-        let tx = Transaction::new(db.as_ref()).unwrap();
+        let tx = db.tx_mut().unwrap();
 
         let accounts = [
             (
@@ -437,7 +450,7 @@ mod tests {
     fn single_storage_trie() {
         let trie = DBTrieLoader::default();
         let db = create_test_rw_db();
-        let tx = Transaction::new(db.as_ref()).unwrap();
+        let tx = db.tx_mut().unwrap();
 
         let address = Address::from_str("9fe4abd71ad081f091bd06dd1c16f7e92927561e").unwrap();
         let hashed_address = keccak256(address);
@@ -465,7 +478,7 @@ mod tests {
     fn single_account_with_storage_trie() {
         let trie = DBTrieLoader::default();
         let db = create_test_rw_db();
-        let tx = Transaction::new(db.as_ref()).unwrap();
+        let tx = db.tx_mut().unwrap();
 
         let address = Address::from_str("9fe4abd71ad081f091bd06dd1c16f7e92927561e").unwrap();
         let hashed_address = keccak256(address);
@@ -513,8 +526,8 @@ mod tests {
     fn verify_genesis() {
         let trie = DBTrieLoader::default();
         let db = create_test_rw_db();
-        let mut tx = Transaction::new(db.as_ref()).unwrap();
-        let ChainSpec { genesis, .. } = chain_spec_value_parser("mainnet").unwrap();
+        let tx = db.tx_mut().unwrap();
+        let ChainSpec { genesis, .. } = MAINNET.clone();
 
         // Insert account state
         for (address, account) in &genesis.alloc {
@@ -528,7 +541,6 @@ mod tests {
             )
             .unwrap();
         }
-        tx.commit().unwrap();
 
         let state_root = genesis_state_root(&genesis.alloc);
 
@@ -541,7 +553,7 @@ mod tests {
     #[test]
     fn gather_changes() {
         let db = create_test_rw_db();
-        let tx = Transaction::new(db.as_ref()).unwrap();
+        let tx = db.tx_mut().unwrap();
 
         let address = Address::from_str("9fe4abd71ad081f091bd06dd1c16f7e92927561e").unwrap();
         let hashed_address = keccak256(address);
@@ -585,7 +597,7 @@ mod tests {
     fn test_with_accounts(accounts: BTreeMap<Address, (Account, BTreeSet<StorageEntry>)>) {
         let trie = DBTrieLoader::default();
         let db = create_test_rw_db();
-        let tx = Transaction::new(db.as_ref()).unwrap();
+        let tx = db.tx_mut().unwrap();
 
         let encoded_accounts = accounts
             .into_iter()
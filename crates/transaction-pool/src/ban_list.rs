@@ -0,0 +1,112 @@
+//! Addresses that are not allowed to interact with the pool.
+
+use crate::error::BanListError;
+use reth_primitives::Address;
+use std::{collections::HashSet, path::Path, str::FromStr};
+
+/// A list of banned transaction senders and recipients.
+///
+/// Transactions that are sent from a banned sender or to a banned recipient are rejected by the
+/// pool.
+///
+/// The file format expected by [AddressBanList::from_file] has one entry per line, which is either
+/// `sender <address>` or `recipient <address>`. Empty lines and lines starting with `#` are
+/// ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddressBanList {
+    /// Banned senders.
+    senders: HashSet<Address>,
+    /// Banned recipients.
+    recipients: HashSet<Address>,
+}
+
+// === impl AddressBanList ===
+
+impl AddressBanList {
+    /// Reads the ban list from the given file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, BanListError> {
+        let content = std::fs::read_to_string(path)?;
+        content.parse()
+    }
+
+    /// Bans the given sender.
+    pub fn ban_sender(&mut self, sender: Address) {
+        self.senders.insert(sender);
+    }
+
+    /// Bans the given recipient.
+    pub fn ban_recipient(&mut self, recipient: Address) {
+        self.recipients.insert(recipient);
+    }
+
+    /// Returns true if the sender is banned.
+    pub fn is_banned_sender(&self, sender: &Address) -> bool {
+        self.senders.contains(sender)
+    }
+
+    /// Returns true if the recipient is banned.
+    pub fn is_banned_recipient(&self, recipient: &Address) -> bool {
+        self.recipients.contains(recipient)
+    }
+
+    /// Returns all banned senders.
+    pub fn senders(&self) -> impl Iterator<Item = &Address> + '_ {
+        self.senders.iter()
+    }
+
+    /// Returns all banned recipients.
+    pub fn recipients(&self) -> impl Iterator<Item = &Address> + '_ {
+        self.recipients.iter()
+    }
+
+    /// Returns true if no address is banned.
+    pub fn is_empty(&self) -> bool {
+        self.senders.is_empty() && self.recipients.is_empty()
+    }
+}
+
+impl FromStr for AddressBanList {
+    type Err = BanListError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut list = AddressBanList::default();
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            let invalid = || BanListError::InvalidEntry(idx + 1, line.to_string());
+            let (kind, address) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+            let address = Address::from_str(address.trim()).map_err(|_| invalid())?;
+            match kind {
+                "sender" => list.ban_sender(address),
+                "recipient" => list.ban_recipient(address),
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ban_list() {
+        let s = r#"
+# known spammers
+sender 0x0000000000000000000000000000000000000001
+recipient 0x0000000000000000000000000000000000000002
+"#;
+        let list: AddressBanList = s.parse().unwrap();
+        assert!(list.is_banned_sender(&Address::from_low_u64_be(1)));
+        assert!(!list.is_banned_recipient(&Address::from_low_u64_be(1)));
+        assert!(list.is_banned_recipient(&Address::from_low_u64_be(2)));
+
+        let err = "receiver 0x0000000000000000000000000000000000000002"
+            .parse::<AddressBanList>()
+            .unwrap_err();
+        assert!(matches!(err, BanListError::InvalidEntry(1, _)));
+    }
+}
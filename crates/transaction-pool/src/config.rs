@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 /// Guarantees max transactions for one sender, compatible with geth/erigon
 pub(crate) const MAX_ACCOUNT_SLOTS_PER_SENDER: usize = 16;
 
@@ -12,6 +14,9 @@ pub struct PoolConfig {
     pub queued_limit: SubPoolLimit,
     /// Max number of executable transaction slots guaranteed per account
     pub max_account_slots: usize,
+    /// Optional file that contains banned senders and recipients, see
+    /// [AddressBanList](crate::ban_list::AddressBanList).
+    pub ban_list_path: Option<PathBuf>,
 }
 
 impl Default for PoolConfig {
@@ -21,6 +26,7 @@ impl Default for PoolConfig {
             basefee_limit: Default::default(),
             queued_limit: Default::default(),
             max_account_slots: MAX_ACCOUNT_SLOTS_PER_SENDER,
+            ban_list_path: None,
         }
     }
 }
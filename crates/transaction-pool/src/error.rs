@@ -29,6 +29,9 @@ pub enum PoolError {
     /// respect the max_init_code_size.
     #[error("[{0:?}] Transaction's size {1} exceeds max_init_code_size {2}.")]
     TxExceedsMaxInitCodeSize(TxHash, usize, usize),
+    /// Thrown when the sender or the recipient of the transaction is banned.
+    #[error("[{0:?}] Transaction rejected, address {1:?} is banned.")]
+    BannedAddress(TxHash, Address),
 }
 
 // === impl PoolError ===
@@ -43,6 +46,63 @@ impl PoolError {
             PoolError::DiscardedOnInsert(hash) => hash,
             PoolError::TxExceedsGasLimit(hash, _, _) => hash,
             PoolError::TxExceedsMaxInitCodeSize(hash, _, _) => hash,
+            PoolError::BannedAddress(hash, _) => hash,
         }
     }
+
+    /// Returns true if this error indicates that the transaction should not have been sent to us
+    /// in the first place.
+    ///
+    /// This is only the case if the transaction is invalid by consensus rules or violates the fee
+    /// rules of the chain. Errors caused by the local state or policy of the pool, like
+    /// [PoolError::DiscardedOnInsert] or [PoolError::BannedAddress], are not the fault of the
+    /// peer that relayed the transaction.
+    pub fn is_bad_transaction(&self) -> bool {
+        match self {
+            PoolError::ProtocolFeeCapTooLow(_, _) |
+            PoolError::TxExceedsGasLimit(_, _, _) |
+            PoolError::TxExceedsMaxInitCodeSize(_, _, _) => true,
+            PoolError::ReplacementUnderpriced(_) |
+            PoolError::SpammerExceededCapacity(_, _) |
+            PoolError::DiscardedOnInsert(_) |
+            PoolError::BannedAddress(_, _) => false,
+        }
+    }
+}
+
+/// Errors that can occur when loading an [AddressBanList](crate::ban_list::AddressBanList).
+#[derive(Debug, thiserror::Error)]
+pub enum BanListError {
+    /// Failed to read the ban list file.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Encountered an invalid entry at the given line.
+    #[error("invalid ban list entry at line {0}: {1:?}")]
+    InvalidEntry(usize, String),
+    /// The pool was not configured with a ban list file.
+    #[error("no ban list file configured")]
+    NotConfigured,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_transactions() {
+        let hash = TxHash::random();
+        assert!(PoolError::ProtocolFeeCapTooLow(hash, 1).is_bad_transaction());
+        assert!(PoolError::TxExceedsGasLimit(hash, 2, 1).is_bad_transaction());
+        assert!(PoolError::TxExceedsMaxInitCodeSize(hash, 2, 1).is_bad_transaction());
+    }
+
+    #[test]
+    fn pool_policy_errors_are_not_bad_transactions() {
+        let hash = TxHash::random();
+        let sender = Address::random();
+        assert!(!PoolError::ReplacementUnderpriced(hash).is_bad_transaction());
+        assert!(!PoolError::SpammerExceededCapacity(sender, hash).is_bad_transaction());
+        assert!(!PoolError::DiscardedOnInsert(hash).is_bad_transaction());
+        assert!(!PoolError::BannedAddress(hash, sender).is_bad_transaction());
+    }
 }
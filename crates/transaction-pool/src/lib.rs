@@ -80,8 +80,10 @@
 //! that provides the `TransactionPool` interface.
 
 pub use crate::{
+    ban_list::AddressBanList,
     config::PoolConfig,
     ordering::TransactionOrdering,
+    pool::{SubPool, TransactionEvent, TransactionEvents},
     traits::{
         BestTransactions, OnNewBlockEvent, PoolTransaction, PropagateKind, PropagatedTransactions,
         TransactionOrigin, TransactionPool,
@@ -89,7 +91,7 @@ pub use crate::{
     validate::{TransactionValidationOutcome, TransactionValidator},
 };
 use crate::{
-    error::PoolResult,
+    error::{BanListError, PoolError, PoolResult},
     pool::PoolInner,
     traits::{NewTransactionEvent, PoolSize},
     validate::ValidPoolTransaction,
@@ -98,6 +100,7 @@ use reth_primitives::{TxHash, U256};
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::mpsc::Receiver;
 
+pub mod ban_list;
 mod config;
 pub mod error;
 mod identifier;
@@ -163,8 +166,10 @@ where
         transaction: V::Transaction,
     ) -> (TxHash, TransactionValidationOutcome<V::Transaction>) {
         let hash = *transaction.hash();
-        // TODO(mattsse): this is where additional validate checks would go, like banned senders
-        // etc...
+        if let Some(address) = self.pool.banned_address(&transaction) {
+            let err = PoolError::BannedAddress(hash, address);
+            return (hash, TransactionValidationOutcome::Invalid(transaction, err))
+        }
         let outcome = self.pool.validator().validate_transaction(origin, transaction).await;
 
         (hash, outcome)
@@ -225,6 +230,10 @@ where
         self.pool.add_transaction_listener()
     }
 
+    fn transaction_event_listener(&self, tx_hash: TxHash) -> Option<TransactionEvents> {
+        self.pool.add_transaction_event_listener(tx_hash)
+    }
+
     fn pooled_transactions(&self) -> Vec<TxHash> {
         self.pool.pooled_transactions()
     }
@@ -250,6 +259,29 @@ where
         self.inner().get(tx_hash)
     }
 
+    fn transactions_by_subpool(
+        &self,
+        subpool: SubPool,
+    ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>> {
+        self.inner().transactions_in(subpool)
+    }
+
+    fn transaction_subpool(&self, tx_hash: &TxHash) -> Option<SubPool> {
+        self.inner().subpool_of(tx_hash)
+    }
+
+    fn ban_list(&self) -> AddressBanList {
+        self.inner().ban_list()
+    }
+
+    fn set_ban_list(&self, ban_list: AddressBanList) {
+        self.inner().set_ban_list(ban_list)
+    }
+
+    fn reload_ban_list(&self) -> Result<(), BanListError> {
+        self.inner().reload_ban_list()
+    }
+
     fn get_all(
         &self,
         txs: impl IntoIterator<Item = TxHash>,
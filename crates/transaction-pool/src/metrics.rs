@@ -1,7 +1,9 @@
 //! Transaction pool metrics.
 
-use metrics::Counter;
+use crate::{error::PoolError, traits::PoolSize};
+use metrics::{Counter, Gauge, Histogram};
 use reth_metrics_derive::Metrics;
+use std::time::Instant;
 
 /// Transaction pool metrics
 #[derive(Metrics)]
@@ -13,4 +15,87 @@ pub struct TxPoolMetrics {
     pub(crate) invalid_transactions: Counter,
     /// Number of removed transactions from the pool
     pub(crate) removed_transactions: Counter,
+    /// Number of transactions that were replaced by a transaction with the same sender and nonce
+    pub(crate) replaced_transactions: Counter,
+    /// Number of transactions that were discarded to enforce the size limits of the sub-pools
+    pub(crate) discarded_transactions: Counter,
+    /// Number of transactions that were discarded because an update of the sender's state, like a
+    /// new nonce or balance, made them invalid
+    pub(crate) discarded_on_update_transactions: Counter,
+    /// Number of transactions that were removed because they were mined
+    pub(crate) mined_transactions: Counter,
+
+    /// Number of transactions rejected because they could not replace an existing transaction
+    pub(crate) replacement_underpriced_transactions: Counter,
+    /// Number of transactions rejected because their fee cap is below the protocol minimum
+    pub(crate) fee_cap_too_low_transactions: Counter,
+    /// Number of transactions rejected because their sender exceeded its slot capacity
+    pub(crate) spammer_exceeded_capacity_transactions: Counter,
+    /// Number of transactions discarded right after insertion due to the pool size limits
+    pub(crate) discarded_on_insert_transactions: Counter,
+    /// Number of transactions rejected because their gas limit exceeds the block gas limit
+    pub(crate) exceeds_gas_limit_transactions: Counter,
+    /// Number of transactions rejected because their input exceeds the max init code size
+    pub(crate) exceeds_max_init_code_size_transactions: Counter,
+    /// Number of transactions rejected because their sender or recipient is banned
+    pub(crate) banned_address_transactions: Counter,
+
+    /// Number of transactions in the pending sub-pool
+    pub(crate) pending_pool_transactions: Gauge,
+    /// Total size of the pending sub-pool in bytes
+    pub(crate) pending_pool_size_bytes: Gauge,
+    /// Number of transactions in the basefee sub-pool
+    pub(crate) basefee_pool_transactions: Gauge,
+    /// Total size of the basefee sub-pool in bytes
+    pub(crate) basefee_pool_size_bytes: Gauge,
+    /// Number of transactions in the queued sub-pool
+    pub(crate) queued_pool_transactions: Gauge,
+    /// Total size of the queued sub-pool in bytes
+    pub(crate) queued_pool_size_bytes: Gauge,
+    /// Number of all transactions in the pool
+    pub(crate) total_transactions: Gauge,
+
+    /// How long transactions stayed in the pool until they were removed, in seconds
+    pub(crate) time_in_pool: Histogram,
+}
+
+// === impl TxPoolMetrics ===
+
+impl TxPoolMetrics {
+    /// Records the sizes of all sub-pools.
+    pub(crate) fn record_pool_size(&self, size: &PoolSize) {
+        self.pending_pool_transactions.set(size.pending as f64);
+        self.pending_pool_size_bytes.set(size.pending_size as f64);
+        self.basefee_pool_transactions.set(size.basefee as f64);
+        self.basefee_pool_size_bytes.set(size.basefee_size as f64);
+        self.queued_pool_transactions.set(size.queued as f64);
+        self.queued_pool_size_bytes.set(size.queued_size as f64);
+        self.total_transactions.set(size.total as f64);
+    }
+
+    /// Increments the counter that corresponds to the kind of the error.
+    pub(crate) fn record_error(&self, err: &PoolError) {
+        match err {
+            PoolError::ReplacementUnderpriced(_) => {
+                self.replacement_underpriced_transactions.increment(1)
+            }
+            PoolError::ProtocolFeeCapTooLow(_, _) => self.fee_cap_too_low_transactions.increment(1),
+            PoolError::SpammerExceededCapacity(_, _) => {
+                self.spammer_exceeded_capacity_transactions.increment(1)
+            }
+            PoolError::DiscardedOnInsert(_) => self.discarded_on_insert_transactions.increment(1),
+            PoolError::TxExceedsGasLimit(_, _, _) => {
+                self.exceeds_gas_limit_transactions.increment(1)
+            }
+            PoolError::TxExceedsMaxInitCodeSize(_, _, _) => {
+                self.exceeds_max_init_code_size_transactions.increment(1)
+            }
+            PoolError::BannedAddress(_, _) => self.banned_address_transactions.increment(1),
+        }
+    }
+
+    /// Records how long a transaction that was inserted at the given instant stayed in the pool.
+    pub(crate) fn record_time_in_pool(&self, inserted_at: Instant) {
+        self.time_in_pool.record(inserted_at.elapsed().as_secs_f64());
+    }
 }
@@ -1,6 +1,12 @@
 use crate::traits::PropagateKind;
+use futures_util::Stream;
 use reth_primitives::{TxHash, H256};
-use std::sync::Arc;
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc::UnboundedReceiver;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -26,3 +32,47 @@ pub enum TransactionEvent {
     /// Transaction was propagated to peers.
     Propagated(Arc<Vec<PropagateKind>>),
 }
+
+// === impl TransactionEvent ===
+
+impl TransactionEvent {
+    /// Returns `true` if no further events are emitted for the transaction after this event.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            TransactionEvent::Mined(_) |
+                TransactionEvent::Replaced(_) |
+                TransactionEvent::Discarded |
+                TransactionEvent::Invalid
+        )
+    }
+}
+
+/// A stream of [TransactionEvent]s of a single transaction.
+///
+/// The stream ends after a final event, see [TransactionEvent::is_final].
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct TransactionEvents {
+    /// Hash of the transaction the events belong to.
+    pub(crate) hash: TxHash,
+    /// The receiving half of the event channel.
+    pub(crate) events: UnboundedReceiver<TransactionEvent>,
+}
+
+// === impl TransactionEvents ===
+
+impl TransactionEvents {
+    /// The hash of the transaction the events belong to.
+    pub fn hash(&self) -> TxHash {
+        self.hash
+    }
+}
+
+impl Stream for TransactionEvents {
+    type Item = TransactionEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().events.poll_recv(cx)
+    }
+}
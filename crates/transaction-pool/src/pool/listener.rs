@@ -1,9 +1,12 @@
 //! Listeners for the transaction-pool
 
-use crate::{pool::events::TransactionEvent, traits::PropagateKind};
+use crate::{
+    pool::events::{TransactionEvent, TransactionEvents},
+    traits::PropagateKind,
+};
 use reth_primitives::{TxHash, H256};
 use std::{collections::HashMap, sync::Arc};
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{self, UnboundedSender};
 
 type EventBroadcast = UnboundedSender<TransactionEvent>;
 
@@ -18,6 +21,17 @@ pub(crate) struct PoolEventBroadcast {
 }
 
 impl PoolEventBroadcast {
+    /// Returns a new stream of all future events of the transaction with the given hash.
+    pub(crate) fn subscribe(&mut self, tx_hash: TxHash) -> TransactionEvents {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.broadcasters
+            .entry(tx_hash)
+            .or_insert_with(|| PoolEventBroadcaster { is_done: false, senders: Vec::new() })
+            .senders
+            .push(tx);
+        TransactionEvents { hash: tx_hash, events: rx }
+    }
+
     /// Calls the broadcast callback with the `PoolEventBroadcaster` that belongs to the hash.
     fn broadcast_with<F>(&mut self, hash: &TxHash, callback: F)
     where
@@ -46,8 +60,13 @@ impl PoolEventBroadcast {
     }
 
     /// Notify listeners about a transaction that was added to the queued pool.
-    pub(crate) fn queued(&mut self, tx: &TxHash) {
+    pub(crate) fn queued(&mut self, tx: &TxHash, replaced: Option<&TxHash>) {
         self.broadcast_with(tx, |notifier| notifier.queued());
+
+        if let Some(replaced) = replaced {
+            // notify listeners that this transaction was replaced
+            self.broadcast_with(replaced, |notifier| notifier.replaced(*tx));
+        }
     }
 
     /// Notify listeners about a transaction that was propagated.
@@ -60,6 +79,11 @@ impl PoolEventBroadcast {
         self.broadcast_with(tx, |notifier| notifier.discarded());
     }
 
+    /// Notify listeners about a transaction that became invalid.
+    pub(crate) fn invalid(&mut self, tx: &TxHash) {
+        self.broadcast_with(tx, |notifier| notifier.invalid());
+    }
+
     /// Notify listeners that the transaction was mined
     pub(crate) fn mined(&mut self, tx: &TxHash, block_hash: H256) {
         self.broadcast_with(tx, |notifier| notifier.mined(block_hash));
@@ -114,9 +138,38 @@ impl PoolEventBroadcaster {
         self.broadcast(TransactionEvent::Propagated(Arc::new(peers)));
     }
 
-    /// Transaction was replaced with the given transaction
+    /// Transaction was discarded.
     fn discarded(&mut self) {
         self.broadcast(TransactionEvent::Discarded);
         self.is_done = true;
     }
+
+    /// Transaction became invalid.
+    fn invalid(&mut self) {
+        self.broadcast(TransactionEvent::Invalid);
+        self.is_done = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notifies_subscribers_until_final_event() {
+        let tx = TxHash::random();
+        let replacement = TxHash::random();
+        let mut broadcast = PoolEventBroadcast::default();
+        let mut events = broadcast.subscribe(tx);
+
+        broadcast.queued(&tx, None);
+        broadcast.pending(&replacement, Some(&tx));
+        // no longer tracked after it was replaced
+        broadcast.pending(&tx, None);
+
+        assert_eq!(events.events.try_recv().unwrap(), TransactionEvent::Queued);
+        assert_eq!(events.events.try_recv().unwrap(), TransactionEvent::Replaced(replacement));
+        assert!(events.events.try_recv().is_err());
+        assert!(broadcast.broadcasters.is_empty());
+    }
 }
@@ -68,9 +68,10 @@
 #![allow(dead_code)] // TODO(mattsse): remove once remaining checks implemented
 
 use crate::{
-    error::{PoolError, PoolResult},
+    ban_list::AddressBanList,
+    error::{BanListError, PoolError, PoolResult},
     identifier::{SenderId, SenderIdentifiers, TransactionId},
-    pool::{listener::PoolEventBroadcast, state::SubPool, txpool::TxPool},
+    pool::{listener::PoolEventBroadcast, txpool::TxPool},
     traits::{
         NewTransactionEvent, PoolSize, PoolTransaction, PropagatedTransactions, TransactionOrigin,
     },
@@ -78,9 +79,9 @@ use crate::{
     OnNewBlockEvent, PoolConfig, TransactionOrdering, TransactionValidator,
 };
 use best::BestTransactions;
-pub use events::TransactionEvent;
+pub use events::{TransactionEvent, TransactionEvents};
 use parking_lot::{Mutex, RwLock};
-use reth_primitives::{Address, TxHash, H256};
+use reth_primitives::{Address, TransactionKind, TxHash, H256};
 use std::{collections::HashSet, fmt, sync::Arc, time::Instant};
 use tokio::sync::mpsc;
 use tracing::warn;
@@ -92,6 +93,7 @@ mod parked;
 pub(crate) mod pending;
 pub(crate) mod size;
 pub(crate) mod state;
+pub use state::SubPool;
 mod transaction;
 pub mod txpool;
 mod update;
@@ -106,6 +108,8 @@ pub struct PoolInner<V: TransactionValidator, T: TransactionOrdering> {
     pool: RwLock<TxPool<T>>,
     /// Pool settings.
     config: PoolConfig,
+    /// Senders and recipients that are not allowed to use the pool.
+    ban_list: RwLock<AddressBanList>,
     /// Manages listeners for transaction state change events.
     event_listener: RwLock<PoolEventBroadcast>,
     /// Listeners for new ready transactions.
@@ -123,6 +127,14 @@ where
 {
     /// Create a new transaction pool instance.
     pub(crate) fn new(validator: Arc<V>, ordering: Arc<T>, config: PoolConfig) -> Self {
+        let ban_list = match config.ban_list_path.as_ref().map(AddressBanList::from_file) {
+            Some(Ok(ban_list)) => ban_list,
+            Some(Err(err)) => {
+                warn!(target: "txpool", ?err, "Failed to load ban list, starting without");
+                Default::default()
+            }
+            None => Default::default(),
+        };
         Self {
             identifiers: Default::default(),
             validator,
@@ -130,6 +142,7 @@ where
             pool: RwLock::new(TxPool::new(ordering, config.clone())),
             pending_transaction_listener: Default::default(),
             transaction_listener: Default::default(),
+            ban_list: RwLock::new(ban_list),
             config,
         }
     }
@@ -154,6 +167,42 @@ where
         &self.validator
     }
 
+    /// Returns a copy of the current ban list.
+    pub(crate) fn ban_list(&self) -> AddressBanList {
+        self.ban_list.read().clone()
+    }
+
+    /// Replaces the current ban list.
+    pub(crate) fn set_ban_list(&self, ban_list: AddressBanList) {
+        *self.ban_list.write() = ban_list;
+    }
+
+    /// Reloads the ban list from the configured [PoolConfig::ban_list_path].
+    ///
+    /// The current ban list is kept if the file can't be loaded.
+    pub(crate) fn reload_ban_list(&self) -> Result<(), BanListError> {
+        let path = self.config.ban_list_path.as_ref().ok_or(BanListError::NotConfigured)?;
+        let ban_list = AddressBanList::from_file(path)?;
+        self.set_ban_list(ban_list);
+        Ok(())
+    }
+
+    /// Returns the banned address if the sender or the recipient of the transaction is banned.
+    pub(crate) fn banned_address(&self, transaction: &T::Transaction) -> Option<Address> {
+        let ban_list = self.ban_list.read();
+        if ban_list.is_empty() {
+            return None
+        }
+        let sender = transaction.sender();
+        if ban_list.is_banned_sender(&sender) {
+            return Some(sender)
+        }
+        match transaction.kind() {
+            TransactionKind::Call(to) if ban_list.is_banned_recipient(to) => Some(*to),
+            _ => None,
+        }
+    }
+
     /// Adds a new transaction listener to the pool that gets notified about every new _pending_
     /// transaction.
     pub fn add_pending_listener(&self) -> mpsc::Receiver<TxHash> {
@@ -171,10 +220,39 @@ where
         rx
     }
 
+    /// Returns a stream of all future events of the transaction with the given hash.
+    ///
+    /// Returns `None` if the transaction is not in the pool.
+    pub(crate) fn add_transaction_event_listener(
+        &self,
+        tx_hash: TxHash,
+    ) -> Option<TransactionEvents> {
+        // hold the pool lock so that the transaction can't be removed before the listener is
+        // installed
+        let pool = self.pool.read();
+        if !pool.contains(&tx_hash) {
+            return None
+        }
+        Some(self.event_listener.write().subscribe(tx_hash))
+    }
+
+    /// Returns all transactions that currently reside in the given sub-pool.
+    pub(crate) fn transactions_in(
+        &self,
+        subpool: SubPool,
+    ) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.pool.read().transactions_in(subpool)
+    }
+
+    /// Returns the sub-pool the transaction with the given hash currently resides in.
+    pub(crate) fn subpool_of(&self, tx_hash: &TxHash) -> Option<SubPool> {
+        self.pool.read().subpool_of(tx_hash)
+    }
+
     /// Returns hashes of _all_ transactions in the pool.
     pub(crate) fn pooled_transactions(&self) -> Vec<TxHash> {
         let pool = self.pool.read();
-        pool.all().hashes_iter().collect()
+        pool.all().public_hashes_iter().collect()
     }
 
     /// Updates the entire pool after a new block was executed.
@@ -223,6 +301,7 @@ where
                 Ok(hash)
             }
             TransactionValidationOutcome::Invalid(tx, err) => {
+                self.pool.read().metrics().record_error(&err);
                 let mut listener = self.event_listener.write();
                 listener.discarded(tx.hash());
                 Err(err)
@@ -249,11 +328,14 @@ where
 
         // It may happen that a newly added transaction is immediately discarded, so we need to
         // adjust the result here
+        let pool = self.pool.read();
         added
             .into_iter()
             .map(|res| match res {
                 Ok(ref hash) if discarded.contains(hash) => {
-                    Err(PoolError::DiscardedOnInsert(*hash))
+                    let err = PoolError::DiscardedOnInsert(*hash);
+                    pool.metrics().record_error(&err);
+                    Err(err)
                 }
                 other => other,
             })
@@ -317,14 +399,14 @@ where
 
         match tx {
             AddedTransaction::Pending(tx) => {
-                let AddedPendingTransaction { transaction, promoted, discarded, .. } = tx;
+                let AddedPendingTransaction { transaction, replaced, promoted, discarded, .. } = tx;
 
-                listener.pending(transaction.hash(), None);
+                listener.pending(transaction.hash(), replaced.as_ref().map(|tx| tx.hash()));
                 promoted.iter().for_each(|tx| listener.pending(tx, None));
                 discarded.iter().for_each(|tx| listener.discarded(tx));
             }
-            AddedTransaction::Parked { transaction, .. } => {
-                listener.queued(transaction.hash());
+            AddedTransaction::Parked { transaction, replaced, .. } => {
+                listener.queued(transaction.hash(), replaced.as_ref().map(|tx| tx.hash()));
             }
         }
     }
@@ -343,7 +425,7 @@ where
 
         let mut listener = self.event_listener.write();
 
-        removed.iter().for_each(|tx| listener.discarded(tx.hash()));
+        removed.iter().for_each(|tx| listener.invalid(tx.hash()));
 
         removed
     }
@@ -391,7 +473,15 @@ where
 
     /// Enforces the size limits of pool and returns the discarded transactions if violated.
     pub(crate) fn discard_worst(&self) -> HashSet<TxHash> {
-        self.pool.write().discard_worst().into_iter().map(|tx| *tx.hash()).collect()
+        let discarded = self.pool.write().discard_worst();
+        if discarded.is_empty() {
+            return Default::default()
+        }
+
+        let mut listener = self.event_listener.write();
+        discarded.iter().for_each(|tx| listener.discarded(tx.hash()));
+
+        discarded.into_iter().map(|tx| *tx.hash()).collect()
     }
 }
 
@@ -406,6 +496,8 @@ impl<V: TransactionValidator, T: TransactionOrdering> fmt::Debug for PoolInner<V
 pub struct AddedPendingTransaction<T: PoolTransaction> {
     /// Inserted transaction.
     transaction: Arc<ValidPoolTransaction<T>>,
+    /// The transaction that was replaced by the inserted transaction, if any.
+    replaced: Option<Arc<ValidPoolTransaction<T>>>,
     /// transactions promoted to the ready queue
     promoted: Vec<TxHash>,
     /// transaction that failed and became discarded
@@ -419,6 +511,7 @@ impl<T: PoolTransaction> AddedPendingTransaction<T> {
     fn new(transaction: Arc<ValidPoolTransaction<T>>) -> Self {
         Self {
             transaction,
+            replaced: None,
             promoted: Default::default(),
             discarded: Default::default(),
             removed: Default::default(),
@@ -436,6 +529,8 @@ pub enum AddedTransaction<T: PoolTransaction> {
     Parked {
         /// Inserted transaction.
         transaction: Arc<ValidPoolTransaction<T>>,
+        /// The transaction that was replaced by the inserted transaction, if any.
+        replaced: Option<Arc<ValidPoolTransaction<T>>>,
         /// The subpool it was moved to.
         subpool: SubPool,
     },
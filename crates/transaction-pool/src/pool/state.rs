@@ -58,8 +58,11 @@ impl TxState {
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 #[repr(u8)]
 pub enum SubPool {
+    /// The transaction is blocked by a nonce gap or its sender lacks the funds to pay for it.
     Queued = 0,
+    /// The transaction is ready to be included in the next block.
     Pending,
+    /// The fee cap of the transaction is below the base fee of the pending block.
     BaseFee,
 }
 
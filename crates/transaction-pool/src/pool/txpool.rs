@@ -120,9 +120,39 @@ impl<T: TransactionOrdering> TxPool<T> {
             basefee_size: self.basefee_pool.size(),
             queued: self.queued_pool.len(),
             queued_size: self.queued_pool.size(),
+            total: self.all_transactions.len(),
         }
     }
 
+    /// Returns the metrics of the pool.
+    pub(crate) fn metrics(&self) -> &TxPoolMetrics {
+        &self.metrics
+    }
+
+    /// Updates the size metrics of all sub-pools.
+    fn update_size_metrics(&self) {
+        self.metrics.record_pool_size(&self.size());
+    }
+
+    /// Returns all transactions that currently reside in the given sub-pool.
+    pub(crate) fn transactions_in(
+        &self,
+        subpool: SubPool,
+    ) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
+        self.all_transactions
+            .txs
+            .values()
+            .filter(|tx| tx.subpool == subpool)
+            .map(|tx| Arc::clone(&tx.transaction))
+            .collect()
+    }
+
+    /// Returns the sub-pool the transaction with the given hash currently resides in.
+    pub(crate) fn subpool_of(&self, tx_hash: &TxHash) -> Option<SubPool> {
+        let tx = self.all_transactions.by_hash.get(tx_hash)?;
+        self.all_transactions.txs.get(&tx.transaction_id).map(|tx| tx.subpool)
+    }
+
     /// Updates the pool based on the changed base fee.
     ///
     /// This enforces the dynamic fee requirement.
@@ -164,7 +194,10 @@ impl<T: TransactionOrdering> TxPool<T> {
     pub(crate) fn on_new_block(&mut self, event: OnNewBlockEvent) -> OnNewBlockOutcome {
         // Remove all transaction that were included in the block
         for tx_hash in &event.mined_transactions {
-            self.remove_transaction_by_hash(tx_hash);
+            if let Some(tx) = self.remove_transaction_by_hash(tx_hash) {
+                self.metrics.mined_transactions.increment(1);
+                self.metrics.record_time_in_pool(tx.timestamp);
+            }
             // Update removed transactions metric
             self.metrics.removed_transactions.increment(1);
         }
@@ -176,6 +209,8 @@ impl<T: TransactionOrdering> TxPool<T> {
         // Process the sub-pool updates
         let UpdateOutcome { promoted, discarded, .. } = self.process_updates(updates);
 
+        self.update_size_metrics();
+
         OnNewBlockOutcome {
             block_hash: event.hash,
             mined: event.mined_transactions,
@@ -220,21 +255,28 @@ impl<T: TransactionOrdering> TxPool<T> {
 
         match self.all_transactions.insert_tx(tx, on_chain_balance, on_chain_nonce) {
             Ok(InsertOk { transaction, move_to, replaced_tx, updates, .. }) => {
+                let replaced = replaced_tx.as_ref().map(|(replaced, _)| Arc::clone(replaced));
+                if let Some(replaced) = &replaced {
+                    self.metrics.replaced_transactions.increment(1);
+                    self.metrics.record_time_in_pool(replaced.timestamp);
+                }
                 self.add_new_transaction(transaction.clone(), replaced_tx, move_to);
                 // Update inserted transactions metric
                 self.metrics.inserted_transactions.increment(1);
                 let UpdateOutcome { promoted, discarded, removed } = self.process_updates(updates);
+                self.update_size_metrics();
 
                 // This transaction was moved to the pending pool.
                 let res = if move_to.is_pending() {
                     AddedTransaction::Pending(AddedPendingTransaction {
                         transaction,
+                        replaced,
                         promoted,
                         discarded,
                         removed,
                     })
                 } else {
-                    AddedTransaction::Parked { transaction, subpool: move_to }
+                    AddedTransaction::Parked { transaction, replaced, subpool: move_to }
                 };
 
                 Ok(res)
@@ -242,29 +284,31 @@ impl<T: TransactionOrdering> TxPool<T> {
             Err(e) => {
                 // Update invalid transactions metric
                 self.metrics.invalid_transactions.increment(1);
-                match e {
+                let err = match e {
                     InsertErr::Underpriced { existing, .. } => {
-                        Err(PoolError::ReplacementUnderpriced(existing))
+                        PoolError::ReplacementUnderpriced(existing)
                     }
                     InsertErr::ProtocolFeeCapTooLow { transaction, fee_cap } => {
-                        Err(PoolError::ProtocolFeeCapTooLow(*transaction.hash(), fee_cap))
+                        PoolError::ProtocolFeeCapTooLow(*transaction.hash(), fee_cap)
                     }
                     InsertErr::ExceededSenderTransactionsCapacity { transaction } => {
-                        Err(PoolError::SpammerExceededCapacity(
+                        PoolError::SpammerExceededCapacity(
                             transaction.sender(),
                             *transaction.hash(),
-                        ))
+                        )
                     }
                     InsertErr::TxGasLimitMoreThanAvailableBlockGas {
                         transaction,
                         block_gas_limit,
                         tx_gas_limit,
-                    } => Err(PoolError::TxExceedsGasLimit(
+                    } => PoolError::TxExceedsGasLimit(
                         *transaction.hash(),
                         block_gas_limit,
                         tx_gas_limit,
-                    )),
-                }
+                    ),
+                };
+                self.metrics.record_error(&err);
+                Err(err)
             }
         }
     }
@@ -281,6 +325,7 @@ impl<T: TransactionOrdering> TxPool<T> {
             let PoolUpdate { id, hash, current, destination } = update;
             match destination {
                 Destination::Discard => {
+                    self.metrics.discarded_on_update_transactions.increment(1);
                     outcome.discarded.push(hash);
                 }
                 Destination::Pool(move_to) => {
@@ -307,7 +352,16 @@ impl<T: TransactionOrdering> TxPool<T> {
         &mut self,
         hashes: impl IntoIterator<Item = TxHash>,
     ) -> Vec<Arc<ValidPoolTransaction<T::Transaction>>> {
-        hashes.into_iter().filter_map(|hash| self.remove_transaction_by_hash(&hash)).collect()
+        let removed = hashes
+            .into_iter()
+            .filter_map(|hash| self.remove_transaction_by_hash(&hash))
+            .collect::<Vec<_>>();
+        for tx in removed.iter() {
+            self.metrics.removed_transactions.increment(1);
+            self.metrics.record_time_in_pool(tx.timestamp);
+        }
+        self.update_size_metrics();
+        removed
     }
 
     /// Remove the transaction from the entire pool.
@@ -441,6 +495,12 @@ impl<T: TransactionOrdering> TxPool<T> {
             ]
         );
 
+        for tx in removed.iter() {
+            self.metrics.discarded_transactions.increment(1);
+            self.metrics.record_time_in_pool(tx.timestamp);
+        }
+        self.update_size_metrics();
+
         removed
     }
 
@@ -512,6 +572,11 @@ impl<T: PoolTransaction> AllTransactions<T> {
         self.by_hash.keys().copied()
     }
 
+    /// Returns an iterator over the hashes of all transactions that were not submitted privately.
+    pub(crate) fn public_hashes_iter(&self) -> impl Iterator<Item = TxHash> + '_ {
+        self.by_hash.values().filter(|tx| !tx.origin.is_private()).map(|tx| *tx.hash())
+    }
+
     /// Returns if the transaction for the given hash is already included in this pool
     pub(crate) fn contains(&self, tx_hash: &TxHash) -> bool {
         self.by_hash.contains_key(tx_hash)
@@ -1143,7 +1208,7 @@ impl SenderInfo {
 mod tests {
     use super::*;
     use crate::{
-        test_utils::{MockTransaction, MockTransactionFactory},
+        test_utils::{MockOrdering, MockTransaction, MockTransactionFactory},
         traits::TransactionOrigin,
     };
 
@@ -1190,6 +1255,22 @@ mod tests {
         assert!(inserted.state.intersects(expected_state));
     }
 
+    #[test]
+    fn private_transactions_are_not_public() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = AllTransactions::default();
+        let public = f.validated(MockTransaction::eip1559().inc_limit());
+        let private = f.validated_with_origin(
+            TransactionOrigin::Private,
+            MockTransaction::eip1559().inc_nonce().inc_limit(),
+        );
+        pool.insert_tx(public.clone(), U256::ZERO, 0).unwrap();
+        pool.insert_tx(private, U256::ZERO, 0).unwrap();
+
+        assert_eq!(pool.hashes_iter().count(), 2);
+        assert_eq!(pool.public_hashes_iter().collect::<Vec<_>>(), vec![*public.hash()]);
+    }
+
     #[test]
     fn insert_replace() {
         let on_chain_balance = U256::ZERO;
@@ -1376,4 +1457,23 @@ mod tests {
             Err(InsertErr::TxGasLimitMoreThanAvailableBlockGas { .. })
         ));
     }
+
+    #[test]
+    fn subpool_introspection() {
+        let mut f = MockTransactionFactory::default();
+        let mut pool = TxPool::new(Arc::new(MockOrdering::default()), Default::default());
+
+        // the sender can't afford the transaction
+        let tx = f.validated(MockTransaction::eip1559().inc_price().inc_limit());
+        pool.add_transaction(tx.clone(), U256::ZERO, 0).unwrap();
+
+        assert_eq!(pool.subpool_of(tx.hash()), Some(SubPool::Queued));
+        assert_eq!(pool.transactions_in(SubPool::Queued).len(), 1);
+        assert!(pool.transactions_in(SubPool::Pending).is_empty());
+        assert_eq!(pool.size().total, 1);
+
+        pool.remove_invalid(vec![*tx.hash()]);
+        assert_eq!(pool.subpool_of(tx.hash()), None);
+        assert_eq!(pool.size().total, 0);
+    }
 }
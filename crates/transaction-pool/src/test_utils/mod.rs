@@ -5,7 +5,8 @@ mod mock;
 mod pool;
 
 use crate::{
-    Pool, PoolTransaction, TransactionOrigin, TransactionValidationOutcome, TransactionValidator,
+    Pool, PoolConfig, PoolTransaction, TransactionOrigin, TransactionValidationOutcome,
+    TransactionValidator,
 };
 use async_trait::async_trait;
 pub use mock::*;
@@ -16,10 +17,15 @@ pub type TestPool = Pool<NoopTransactionValidator<MockTransaction>, MockOrdering
 
 /// Returns a new [Pool] used for testing purposes
 pub fn testing_pool() -> TestPool {
+    testing_pool_with_config(Default::default())
+}
+
+/// Returns a new [Pool] with the given [PoolConfig] used for testing purposes
+pub fn testing_pool_with_config(config: PoolConfig) -> TestPool {
     Pool::new(
         Arc::new(NoopTransactionValidator::default()),
         Arc::new(MockOrdering::default()),
-        Default::default(),
+        config,
     )
 }
 
@@ -1,4 +1,9 @@
-use crate::{error::PoolResult, pool::state::SubPool, validate::ValidPoolTransaction};
+use crate::{
+    ban_list::AddressBanList,
+    error::{BanListError, PoolResult},
+    pool::{state::SubPool, TransactionEvents},
+    validate::ValidPoolTransaction,
+};
 use reth_primitives::{
     Address, FromRecoveredTransaction, IntoRecoveredTransaction, PeerId, Transaction,
     TransactionKind, TransactionSignedEcRecovered, TxHash, H256, U256,
@@ -68,7 +73,17 @@ pub trait TransactionPool: Send + Sync + Clone {
     /// Returns a new stream that yields new valid transactions added to the pool.
     fn transactions_listener(&self) -> Receiver<NewTransactionEvent<Self::Transaction>>;
 
-    /// Returns hashes of all transactions in the pool.
+    /// Returns a new stream that yields all [TransactionEvent](crate::pool::TransactionEvent)s of
+    /// the transaction with the given hash.
+    ///
+    /// Returns `None` if the transaction is not in the pool.
+    ///
+    /// Consumer: RPC
+    fn transaction_event_listener(&self, tx_hash: TxHash) -> Option<TransactionEvents>;
+
+    /// Returns hashes of all transactions in the pool that can be propagated to peers.
+    ///
+    /// This excludes transactions that were submitted with [TransactionOrigin::Private].
     ///
     /// Note: This returns a `Vec` but should guarantee that all hashes are unique.
     ///
@@ -107,6 +122,40 @@ pub trait TransactionPool: Send + Sync + Clone {
     /// Returns the transaction for the given hash.
     fn get(&self, tx_hash: &TxHash) -> Option<Arc<ValidPoolTransaction<Self::Transaction>>>;
 
+    /// Returns all transactions that currently reside in the given [SubPool].
+    ///
+    /// Consumer: RPC
+    fn transactions_by_subpool(
+        &self,
+        subpool: SubPool,
+    ) -> Vec<Arc<ValidPoolTransaction<Self::Transaction>>>;
+
+    /// Returns the [SubPool] the transaction for the given hash currently resides in.
+    ///
+    /// This explains why a transaction is not yet included: only transactions in the
+    /// [SubPool::Pending] sub-pool are considered for the next block.
+    ///
+    /// Consumer: RPC
+    fn transaction_subpool(&self, tx_hash: &TxHash) -> Option<SubPool>;
+
+    /// Returns the senders and recipients that are currently banned from the pool.
+    ///
+    /// Consumer: RPC
+    fn ban_list(&self) -> AddressBanList;
+
+    /// Replaces the senders and recipients that are banned from the pool.
+    ///
+    /// This only affects new transactions, transactions that are already in the pool are kept.
+    ///
+    /// Consumer: RPC
+    fn set_ban_list(&self, ban_list: AddressBanList);
+
+    /// Reloads the ban list from the file the pool was configured with, see
+    /// [PoolConfig::ban_list_path](crate::PoolConfig::ban_list_path).
+    ///
+    /// Consumer: RPC
+    fn reload_ban_list(&self) -> Result<(), BanListError>;
+
     /// Returns all transactions objects for the given hashes.
     ///
     /// This adheres to the expected behavior of [`GetPooledTransactions`](https://github.com/ethereum/devp2p/blob/master/caps/eth.md#getpooledtransactions-0x09):
@@ -187,6 +236,10 @@ pub enum TransactionOrigin {
     /// This is usually considered an "untrusted" source, for example received from another in the
     /// network.
     External,
+    /// Transaction was submitted privately.
+    ///
+    /// Private transactions are never propagated to other peers.
+    Private,
 }
 
 // === impl TransactionOrigin ===
@@ -196,6 +249,11 @@ impl TransactionOrigin {
     pub fn is_local(&self) -> bool {
         matches!(self, TransactionOrigin::Local)
     }
+
+    /// Whether the transaction was submitted privately and must not be propagated.
+    pub fn is_private(&self) -> bool {
+        matches!(self, TransactionOrigin::Private)
+    }
 }
 
 /// Event fired when a new block was mined
@@ -410,4 +468,8 @@ pub struct PoolSize {
     pub queued: usize,
     /// Reported size of transactions in the _queued_ sub-pool.
     pub queued_size: usize,
+    /// Number of all transactions of all sub-pools
+    ///
+    /// Note: this is the sum of `pending + basefee + queued`
+    pub total: usize,
 }